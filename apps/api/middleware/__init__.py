@@ -9,8 +9,18 @@ from .logging import (
     StructuredLoggingMiddleware,
     RequestLoggingMiddleware
 )
+from .http_signatures import (
+    HTTPSignatureMiddleware,
+    HTTPSignatureError,
+    sign_request,
+    verify_request
+)
 
 __all__ = [
     "StructuredLoggingMiddleware",
-    "RequestLoggingMiddleware"
+    "RequestLoggingMiddleware",
+    "HTTPSignatureMiddleware",
+    "HTTPSignatureError",
+    "sign_request",
+    "verify_request"
 ]
\ No newline at end of file
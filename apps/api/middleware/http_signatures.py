@@ -0,0 +1,332 @@
+"""
+HTTP Message Signatures (RFC 9421) for ReliQuary agent requests.
+
+Agents sign each request over its method, path and body digest (RFC 9530 Content-Digest),
+with `created`/`expires` parameters bounding when the signature is valid::
+
+    Content-Digest: sha-256=:X48E9qOokqqrvdts8nOJRJN3OWDUoyWxBf7kbu9DBPE=:
+    Signature-Input: sig1=("@method" "@path" "content-digest");created=1760486400;expires=1760486700;keyid="agent-7";alg="ed25519"
+    Signature: sig1=:wqcAqbmYJ2ji2glfAMaRy4gruYYnx2nEFN2HN6jrnDnQCK1u02Gb04v9EDgw...:
+
+Ed25519 uses the registered "ed25519" algorithm name; Falcon signatures use "falcon512" or
+"falcon1024". The helpers work on plain header maps so any framework (or the gateway) can
+use them; `HTTPSignatureMiddleware` wires verification into the FastAPI app.
+"""
+
+import base64
+import hashlib
+import hmac
+import logging
+import re
+import time
+from dataclasses import dataclass
+from typing import Any, Callable, Dict, Iterable, List, Mapping, Optional, Sequence, Tuple
+
+from starlette.middleware.base import BaseHTTPMiddleware
+from starlette.requests import Request
+from starlette.responses import JSONResponse, Response
+from starlette.types import ASGIApp
+
+try:
+    import reliquary_encryptor
+except ImportError:
+    reliquary_encryptor = None
+
+SIGNATURE_ALGORITHMS = ("ed25519", "falcon512", "falcon1024")
+DEFAULT_COMPONENTS = ("@method", "@path", "content-digest")
+DEFAULT_LABEL = "sig1"
+DEFAULT_EXPIRES_SECONDS = 300
+
+_TOKEN = re.compile(r"^[a-z*][a-z0-9_\-.*]*$")
+
+
+class HTTPSignatureError(Exception):
+    """Raised when a request signature is missing, malformed, expired or doesn't verify"""
+
+
+@dataclass
+class VerifiedSignature:
+    """A request signature that passed verification"""
+    label: str
+    key_id: str
+    algorithm: str
+    created: int
+    expires: Optional[int]
+    components: List[str]
+
+
+def content_digest(body: bytes) -> str:
+    """RFC 9530 Content-Digest header value (SHA-256) for a body."""
+    return f"sha-256=:{base64.b64encode(hashlib.sha256(body).digest()).decode()}:"
+
+
+def _lower_headers(headers: Mapping[str, str]) -> Dict[str, str]:
+    return {name.lower(): str(value).strip() for name, value in headers.items()}
+
+
+def _component_value(component: str, method: str, path: str, headers: Dict[str, str]) -> str:
+    target, _, query = path.partition("?")
+    if component == "@method":
+        return method.upper()
+    if component == "@path":
+        return target or "/"
+    if component == "@query":
+        return f"?{query}"
+    if component == "@authority":
+        if "host" not in headers:
+            raise HTTPSignatureError("@authority needs a Host header")
+        return headers["host"].lower()
+    if component.startswith("@"):
+        raise HTTPSignatureError(f"Unsupported derived component: {component}")
+    if component not in headers:
+        raise HTTPSignatureError(f"Covered header is missing: {component}")
+    return headers[component]
+
+
+def signature_base(method: str, path: str, headers: Mapping[str, str],
+                   components: Sequence[str], signature_params: str) -> bytes:
+    """
+    Build the RFC 9421 signature base.
+
+    Args:
+        method: HTTP method
+        path: Request target (path and optional query)
+        headers: Request headers
+        components: Covered component identifiers, e.g. "@method" or "content-digest"
+        signature_params: Serialized inner list with parameters, as sent in Signature-Input
+    """
+    lowered = _lower_headers(headers)
+    lines = [f'"{component}": {_component_value(component, method, path, lowered)}'
+             for component in components]
+    lines.append(f'"@signature-params": {signature_params}')
+    return "\n".join(lines).encode("utf-8")
+
+
+def _serialize_params(components: Sequence[str], created: int, expires: Optional[int],
+                      key_id: str, algorithm: str) -> str:
+    covered = " ".join(f'"{component}"' for component in components)
+    params = f"({covered});created={created}"
+    if expires is not None:
+        params += f";expires={expires}"
+    return params + f';keyid="{key_id}";alg="{algorithm}"'
+
+
+def _split_members(value: str) -> Iterable[Tuple[str, str]]:
+    """Split a structured-field dictionary into (key, raw member value) pairs."""
+    depth, quoted, start = 0, False, 0
+    parts = []
+    for i, c in enumerate(value):
+        if quoted:
+            if c == '"' and value[i - 1] != "\\":
+                quoted = False
+        elif c == '"':
+            quoted = True
+        elif c == "(":
+            depth += 1
+        elif c == ")":
+            depth -= 1
+        elif c == "," and depth == 0:
+            parts.append(value[start:i])
+            start = i + 1
+    parts.append(value[start:])
+    for part in parts:
+        key, sep, member = part.strip().partition("=")
+        if not sep or not _TOKEN.match(key):
+            raise HTTPSignatureError("Malformed signature header")
+        yield key, member
+
+
+def _parse_signature_input(member: str) -> Tuple[List[str], Dict[str, Any]]:
+    """Covered components and parameters of one Signature-Input member."""
+    match = re.fullmatch(r'\(((?:"[^"]*"\s*)*)\)((?:;[a-z][a-z0-9_\-]*=(?:"[^"]*"|-?\d+))*)',
+                         member)
+    if not match:
+        raise HTTPSignatureError("Malformed Signature-Input")
+    components = re.findall(r'"([^"]*)"', match.group(1))
+    params: Dict[str, Any] = {}
+    for name, value in re.findall(r';([a-z][a-z0-9_\-]*)=("[^"]*"|-?\d+)', match.group(2)):
+        params[name] = value[1:-1] if value.startswith('"') else int(value)
+    return components, params
+
+
+def _sign(base: bytes, secret_key: Any, algorithm: str) -> bytes:
+    if reliquary_encryptor is None:
+        raise HTTPSignatureError("reliquary_encryptor is required for HTTP message signatures")
+    if algorithm == "ed25519":
+        return bytes(reliquary_encryptor.sign_ed25519(base, secret_key))
+    return bytes(reliquary_encryptor.sign_falcon(base, secret_key, _falcon_params(algorithm)))
+
+
+def _verify(base: bytes, signature: bytes, public_key: bytes, algorithm: str) -> bool:
+    if reliquary_encryptor is None:
+        raise HTTPSignatureError("reliquary_encryptor is required for HTTP message signatures")
+    try:
+        if algorithm == "ed25519":
+            return reliquary_encryptor.verify_ed25519(base, signature, public_key)
+        return reliquary_encryptor.verify_falcon(
+            base, signature, public_key, _falcon_params(algorithm))
+    except ValueError:
+        return False
+
+
+def _falcon_params(algorithm: str) -> Any:
+    if algorithm == "falcon512":
+        return reliquary_encryptor.FalconParams.Falcon512
+    return reliquary_encryptor.FalconParams.Falcon1024
+
+
+def sign_request(method: str, path: str, headers: Mapping[str, str], body: bytes,
+                 key_id: str, secret_key: Any, algorithm: str = "ed25519",
+                 components: Sequence[str] = DEFAULT_COMPONENTS,
+                 label: str = DEFAULT_LABEL, created: Optional[int] = None,
+                 expires_in: Optional[int] = DEFAULT_EXPIRES_SECONDS) -> Dict[str, str]:
+    """
+    Sign an outgoing request.
+
+    Args:
+        method: HTTP method
+        path: Request target (path and optional query)
+        headers: Headers that will be sent
+        body: Exact body bytes that will be sent
+        key_id: Id the verifier knows the key by
+        secret_key: Ed25519 or Falcon secret key (or SecretKeyHandle)
+        algorithm: "ed25519", "falcon512" or "falcon1024"
+        components: Components to cover; "content-digest" is added to the headers if covered
+        label: Signature label
+        created: Signing time in Unix seconds (defaults to now)
+        expires_in: Seconds until the signature expires, or None for no `expires`
+
+    Returns:
+        A copy of `headers` with Content-Digest, Signature-Input and Signature set
+    """
+    if algorithm not in SIGNATURE_ALGORITHMS:
+        raise ValueError(f"Unsupported signature algorithm: {algorithm}")
+    if not _TOKEN.match(label) or '"' in key_id or "\\" in key_id:
+        raise ValueError("Invalid signature label or key id")
+    signed = {name: value for name, value in headers.items()
+              if name.lower() not in ("content-digest", "signature-input", "signature")}
+    components = [component.lower() for component in components]
+    if "content-digest" in components:
+        signed["Content-Digest"] = content_digest(body)
+    created = int(time.time()) if created is None else int(created)
+    expires = created + expires_in if expires_in is not None else None
+
+    params = _serialize_params(components, created, expires, key_id, algorithm)
+    signature = _sign(signature_base(method, path, signed, components, params), secret_key,
+                      algorithm)
+    signed["Signature-Input"] = f"{label}={params}"
+    signed["Signature"] = f"{label}=:{base64.b64encode(signature).decode()}:"
+    return signed
+
+
+def verify_request(method: str, path: str, headers: Mapping[str, str], body: bytes,
+                   keys: Mapping[str, Tuple[bytes, str]],
+                   required_components: Sequence[str] = DEFAULT_COMPONENTS,
+                   label: Optional[str] = None, max_age: int = DEFAULT_EXPIRES_SECONDS,
+                   max_clock_skew: int = 30,
+                   clock: Callable[[], float] = time.time) -> VerifiedSignature:
+    """
+    Verify an incoming request signature.
+
+    Args:
+        method: HTTP method
+        path: Request target as received
+        headers: Received headers
+        body: Raw body bytes as received
+        keys: Key id -> (public key, algorithm) the verifier trusts
+        required_components: Components the signature must cover
+        label: Signature to check (defaults to the first one present)
+        max_age: Largest accepted age of `created` in seconds
+        max_clock_skew: How far `created` may be in the future
+        clock: Current time source
+
+    Returns:
+        The verified signature's label, key id and parameters
+
+    Raises:
+        HTTPSignatureError: If no valid, fresh signature covering the required components
+            from a trusted key is present
+    """
+    lowered = _lower_headers(headers)
+    if "signature-input" not in lowered or "signature" not in lowered:
+        raise HTTPSignatureError("Missing Signature-Input or Signature header")
+    inputs = dict(_split_members(lowered["signature-input"]))
+    signatures = dict(_split_members(lowered["signature"]))
+    if label is None:
+        label = next(iter(inputs))
+    if label not in inputs or label not in signatures:
+        raise HTTPSignatureError(f"No signature labeled {label}")
+
+    params_value = inputs[label]
+    components, params = _parse_signature_input(params_value)
+    missing = [c for c in required_components if c.lower() not in components]
+    if missing:
+        raise HTTPSignatureError(f"Signature does not cover: {', '.join(missing)}")
+
+    now = clock()
+    created = params.get("created")
+    if not isinstance(created, int):
+        raise HTTPSignatureError("Signature has no created parameter")
+    if created > now + max_clock_skew:
+        raise HTTPSignatureError("Signature created in the future")
+    if now - created > max_age:
+        raise HTTPSignatureError("Signature is too old")
+    expires = params.get("expires")
+    if expires is not None and (not isinstance(expires, int) or now > expires):
+        raise HTTPSignatureError("Signature has expired")
+
+    key_id = params.get("keyid")
+    if key_id not in keys:
+        raise HTTPSignatureError("Signature key is not trusted")
+    public_key, algorithm = keys[key_id]
+    if params.get("alg", algorithm) != algorithm:
+        raise HTTPSignatureError("Signature algorithm does not match the key")
+
+    if "content-digest" in components:
+        if not hmac.compare_digest(lowered.get("content-digest", ""), content_digest(body)):
+            raise HTTPSignatureError("Content-Digest does not match the body")
+
+    encoded = signatures[label]
+    if len(encoded) < 2 or encoded[0] != ":" or encoded[-1] != ":":
+        raise HTTPSignatureError("Malformed Signature")
+    try:
+        signature = base64.b64decode(encoded[1:-1], validate=True)
+    except ValueError:
+        raise HTTPSignatureError("Malformed Signature")
+    base = signature_base(method, path, lowered, components, params_value)
+    if not _verify(base, signature, public_key, algorithm):
+        raise HTTPSignatureError("Signature does not verify")
+    return VerifiedSignature(label, key_id, algorithm, created, expires, components)
+
+
+class HTTPSignatureMiddleware(BaseHTTPMiddleware):
+    """
+    Rejects requests without a valid RFC 9421 signature from a trusted key.
+
+    The verified key id is exposed to endpoints as `request.state.signature_key_id`.
+    """
+
+    def __init__(self, app: ASGIApp, keys: Mapping[str, Tuple[bytes, str]],
+                 required_components: Sequence[str] = DEFAULT_COMPONENTS,
+                 exempt_paths: Sequence[str] = ("/health",),
+                 max_age: int = DEFAULT_EXPIRES_SECONDS):
+        super().__init__(app)
+        self.keys = dict(keys)
+        self.required_components = tuple(required_components)
+        self.exempt_paths = tuple(exempt_paths)
+        self.max_age = max_age
+        self.logger = logging.getLogger(__name__)
+
+    async def dispatch(self, request: Request, call_next: Callable) -> Response:
+        if request.url.path in self.exempt_paths:
+            return await call_next(request)
+        target = request.url.path + (f"?{request.url.query}" if request.url.query else "")
+        try:
+            verified = verify_request(request.method, target, dict(request.headers),
+                                      await request.body(), self.keys,
+                                      self.required_components, max_age=self.max_age)
+        except HTTPSignatureError as e:
+            self.logger.warning(f"Rejected {request.method} {request.url.path}: {e}")
+            return JSONResponse({"detail": f"Invalid request signature: {e}"}, status_code=401)
+        request.state.signature_key_id = verified.key_id
+        return await call_next(request)
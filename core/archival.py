@@ -0,0 +1,344 @@
+"""
+Long-term archival re-signing of signature bundles.
+
+Signatures age: schemes get deprecated and validity windows run out, while archived records
+have to stay verifiable for decades. An archive stays verifiable by renewing its evidence
+before it lapses: the resigner countersigns each stored bundle with the current scheme and a
+fresh validity window. A countersignature covers the whole bundle, every earlier signature
+included, and the original layers are never touched, so the bundle keeps its full history::
+
+    layer 0  ed25519     2021-2026   original signature
+    layer 1  falcon1024  2025-2030   re-signed: ed25519 deprecated
+    layer 2  falcon1024  2029-2034   re-signed: window expiring
+
+A re-signed bundle is judged archivally (verify_archival_chain): each layer must have been in
+force when the next layer was added, and only the outermost layer must be in force now. A
+bundle is only re-signed while its chain still verifies that way; one that is broken or has
+already lapsed is reported instead, since countersigning it would vouch for evidence that can
+no longer be checked.
+"""
+
+import logging
+import os
+import threading
+import time
+from dataclasses import dataclass, field
+from datetime import datetime, timezone
+from typing import Any, Dict, FrozenSet, Iterator, List, Optional, Tuple
+
+from .heartbeat import SECONDS_PER_DAY
+
+try:
+    import reliquary_encryptor
+except ImportError:
+    reliquary_encryptor = None
+
+BUNDLE_V1 = 1
+
+# (signer, scheme, public_key_fingerprint, not_before, not_after, valid), as returned by
+# reliquary_encryptor.verify_signature_bundle
+LayerReport = Tuple[str, str, bytes, Optional[int], Optional[int], bool]
+
+
+class ArchivalError(Exception):
+    """Raised when bundles can't be checked or re-signed"""
+
+
+def _require_encryptor():
+    if reliquary_encryptor is None:
+        raise ArchivalError("Archival re-signing requires the reliquary_encryptor module")
+    return reliquary_encryptor
+
+
+@dataclass(frozen=True)
+class ArchivalPolicy:
+    """When a bundle is due for re-signing, and for how long the new signature holds"""
+    deprecated_schemes: FrozenSet[str] = frozenset({"ed25519"})
+    renew_before_days: float = 90.0
+    validity_days: float = 5 * 365.0
+
+
+@dataclass
+class BundleStatus:
+    """Archival state of one stored bundle"""
+    bundle_id: str
+    layers: int
+    outer_scheme: str
+    outer_not_after: Optional[int]
+    chain_valid: bool
+    reasons: List[str] = field(default_factory=list)
+
+    @property
+    def due(self) -> bool:
+        """Whether the bundle needs re-signing and can safely be re-signed"""
+        return self.chain_valid and bool(self.reasons)
+
+
+@dataclass
+class ResignResult:
+    """Outcome of one bundle in a re-signing run"""
+    bundle_id: str
+    action: str  # "resigned", "would_resign", "skipped" or "failed"
+    reasons: List[str]
+    layers: int
+    detail: str = ""
+
+
+class InMemoryBundleStore:
+    """Bundles with the messages they sign, keyed by bundle id"""
+
+    def __init__(self):
+        self._bundles: Dict[str, Tuple[bytes, bytes]] = {}
+        self._lock = threading.Lock()
+
+    def put(self, bundle_id: str, bundle: bytes, message: bytes):
+        with self._lock:
+            self._bundles[bundle_id] = (bundle, message)
+
+    def get(self, bundle_id: str) -> bytes:
+        with self._lock:
+            return self._bundles[bundle_id][0]
+
+    def items(self) -> Iterator[Tuple[str, bytes, bytes]]:
+        with self._lock:
+            snapshot = list(self._bundles.items())
+        for bundle_id, (bundle, message) in snapshot:
+            yield bundle_id, bundle, message
+
+    def update(self, bundle_id: str, bundle: bytes):
+        with self._lock:
+            self._bundles[bundle_id] = (bundle, self._bundles[bundle_id][1])
+
+
+class DirectoryBundleStore:
+    """
+    Detached bundles on disk: `report.pdf` is signed by `report.pdf.rqsb` next to it. Bundle
+    ids are paths relative to the root; updates replace the bundle file atomically.
+    """
+
+    def __init__(self, root: str, suffix: str = ".rqsb"):
+        self.root = root
+        self.suffix = suffix
+
+    def items(self) -> Iterator[Tuple[str, bytes, bytes]]:
+        for directory, _, files in os.walk(self.root):
+            for name in sorted(files):
+                if not name.endswith(self.suffix):
+                    continue
+                path = os.path.join(directory, name)
+                message_path = path[:-len(self.suffix)]
+                if not os.path.isfile(message_path):
+                    continue
+                with open(path, "rb") as f:
+                    bundle = f.read()
+                with open(message_path, "rb") as f:
+                    message = f.read()
+                yield os.path.relpath(message_path, self.root), bundle, message
+
+    def get(self, bundle_id: str) -> bytes:
+        with open(os.path.join(self.root, bundle_id + self.suffix), "rb") as f:
+            return f.read()
+
+    def update(self, bundle_id: str, bundle: bytes):
+        path = os.path.join(self.root, bundle_id + self.suffix)
+        temp_path = f"{path}.tmp"
+        with open(temp_path, "wb") as f:
+            f.write(bundle)
+            f.flush()
+            os.fsync(f.fileno())
+        os.replace(temp_path, path)
+
+
+def verify_archival_chain(bundle: bytes, message: bytes, now: float) -> List[LayerReport]:
+    """
+    Verify a bundle the archival way: each layer at the time the next layer was added (its
+    not_before), the outermost layer at `now`. Layers added without a not_before leave the
+    time unknown, so the layer below them is checked at `now`.
+
+    Returns:
+        One (signer, scheme, public_key_fingerprint, not_before, not_after, valid) tuple per
+        layer, innermost first
+    """
+    encryptor = _require_encryptor()
+    try:
+        current = encryptor.verify_signature_bundle(bundle, message, int(now))
+    except ValueError as e:
+        raise ArchivalError(f"Unreadable signature bundle: {e}") from e
+    reports_at = {int(now): current}
+    result = []
+    for i, layer in enumerate(current):
+        checked_at = int(now)
+        if i + 1 < len(current) and current[i + 1][3] is not None:
+            checked_at = current[i + 1][3]
+        if checked_at not in reports_at:
+            reports_at[checked_at] = encryptor.verify_signature_bundle(bundle, message,
+                                                                       checked_at)
+        result.append(layer[:5] + (reports_at[checked_at][i][5],))
+    return result
+
+
+class ArchivalResigner:
+    """
+    Walks a bundle store and countersigns bundles whose outermost signature uses a deprecated
+    scheme or whose validity window is about to run out.
+
+    The store needs items() yielding (bundle_id, bundle, message), get(bundle_id) and
+    update(bundle_id, bundle); InMemoryBundleStore and DirectoryBundleStore provide them.
+    """
+
+    def __init__(self, store: Any, signing_key: Any, public_key: bytes,
+                 scheme: str = "falcon1024", signer: str = "archival-resigner",
+                 policy: Optional[ArchivalPolicy] = None, clock: Any = None,
+                 audit_logger: Any = None):
+        """
+        Initialize the resigner.
+
+        Args:
+            store: Bundle store to maintain
+            signing_key: Secret key or key handle for the new signatures
+            public_key: Matching public key
+            scheme: Current signature scheme; must not be deprecated by the policy
+            signer: Label recorded in the new layers
+            policy: When to re-sign; defaults to ArchivalPolicy()
+            clock: Object with now() (e.g. a TrustedClock); defaults to the system clock
+            audit_logger: Sink with add_entry(dict) (e.g. MerkleLogWriter)
+        """
+        self.policy = policy or ArchivalPolicy()
+        if scheme in self.policy.deprecated_schemes:
+            raise ArchivalError(f"Can't re-sign with deprecated scheme '{scheme}'")
+        self.store = store
+        self.signing_key = signing_key
+        self.public_key = public_key
+        self.scheme = scheme
+        self.signer = signer
+        self.clock = clock
+        self.audit_logger = audit_logger
+        self.logger = logging.getLogger(__name__)
+        self._run_lock = threading.Lock()
+        self._thread: Optional[threading.Thread] = None
+        self._stop = threading.Event()
+
+    def _now(self) -> float:
+        return self.clock.now() if self.clock is not None else time.time()
+
+    def status(self, bundle_id: str, bundle: bytes, message: bytes,
+               now: Optional[float] = None) -> BundleStatus:
+        """Check one bundle against the policy"""
+        now = self._now() if now is None else now
+        layers = verify_archival_chain(bundle, message, now)
+        if not layers:
+            return BundleStatus(bundle_id, 0, "", None, False, ["empty"])
+        _, outer_scheme, _, _, outer_not_after, _ = layers[-1]
+        status = BundleStatus(bundle_id, len(layers), outer_scheme, outer_not_after,
+                              all(layer[5] for layer in layers))
+        if outer_scheme in self.policy.deprecated_schemes:
+            status.reasons.append("deprecated_scheme")
+        if outer_not_after is not None:
+            if outer_not_after < now:
+                status.reasons.append("lapsed")
+                status.chain_valid = False
+            elif outer_not_after - now <= self.policy.renew_before_days * SECONDS_PER_DAY:
+                status.reasons.append("expiring")
+        if not status.chain_valid and "lapsed" not in status.reasons:
+            status.reasons.append("broken")
+        return status
+
+    def scan(self, now: Optional[float] = None) -> List[BundleStatus]:
+        """Status of every stored bundle that needs attention"""
+        now = self._now() if now is None else now
+        statuses = []
+        for bundle_id, bundle, message in self.store.items():
+            try:
+                status = self.status(bundle_id, bundle, message, now)
+            except ArchivalError as e:
+                self.logger.warning(f"Bundle {bundle_id} can't be checked: {e}")
+                status = BundleStatus(bundle_id, 0, "", None, False, ["unreadable"])
+            if status.reasons:
+                statuses.append(status)
+        return statuses
+
+    def run(self, dry_run: bool = False, now: Optional[float] = None) -> List[ResignResult]:
+        """
+        Re-sign every bundle that is due.
+
+        Args:
+            dry_run: Only report what would be re-signed
+            now: Time to judge the bundles at; defaults to the clock
+
+        Returns:
+            One result per bundle that needed attention
+        """
+        with self._run_lock:
+            now = self._now() if now is None else now
+            results = []
+            for status in self.scan(now):
+                if not status.due:
+                    results.append(ResignResult(status.bundle_id, "skipped", status.reasons,
+                                                status.layers, "chain can't be verified"))
+                    self.logger.warning(f"Bundle {status.bundle_id} needs manual attention: "
+                                        f"{', '.join(status.reasons)}")
+                elif dry_run:
+                    results.append(ResignResult(status.bundle_id, "would_resign",
+                                                status.reasons, status.layers))
+                else:
+                    results.append(self._resign(status, now))
+            return results
+
+    def start(self, interval: float = SECONDS_PER_DAY):
+        """Run the re-signing pass in a background thread every `interval` seconds"""
+        self.stop()
+        self._stop.clear()
+
+        def loop():
+            while not self._stop.is_set():
+                try:
+                    self.run()
+                except Exception as e:
+                    self.logger.warning(f"Archival re-signing pass failed: {e}")
+                self._stop.wait(interval)
+
+        self._thread = threading.Thread(target=loop, name="archival-resign", daemon=True)
+        self._thread.start()
+
+    def stop(self):
+        """Stop the background thread, if any."""
+        if self._thread is not None:
+            self._stop.set()
+            self._thread.join()
+            self._thread = None
+
+    def _resign(self, status: BundleStatus, now: float) -> ResignResult:
+        encryptor = _require_encryptor()
+        try:
+            bundle = self.store.get(status.bundle_id)
+            window = {}
+            if bundle[4] != BUNDLE_V1:
+                window = {"not_before": int(now),
+                          "not_after": int(now + self.policy.validity_days * SECONDS_PER_DAY)}
+            resigned = encryptor.countersign_bundle(bundle, self.signing_key, self.public_key,
+                                                    self.scheme, self.signer, **window)
+            self.store.update(status.bundle_id, resigned)
+        except Exception as e:
+            self.logger.error(f"Re-signing bundle {status.bundle_id} failed: {e}")
+            return ResignResult(status.bundle_id, "failed", status.reasons, status.layers, str(e))
+
+        self._log({
+            "event": "archival_resign",
+            "timestamp": datetime.fromtimestamp(now, timezone.utc).isoformat(),
+            "bundle_id": status.bundle_id,
+            "reasons": status.reasons,
+            "previous_scheme": status.outer_scheme,
+            "previous_not_after": status.outer_not_after,
+            "scheme": self.scheme,
+            "signer": self.signer,
+            "not_after": window.get("not_after"),
+            "layers": status.layers + 1,
+        })
+        return ResignResult(status.bundle_id, "resigned", status.reasons, status.layers + 1)
+
+    def _log(self, entry: Dict[str, Any]):
+        if self.audit_logger:
+            try:
+                self.audit_logger.add_entry(entry)
+            except Exception as e:
+                self.logger.warning(f"Could not log archival re-signing: {e}")
@@ -0,0 +1,21 @@
+"""
+Cloud KMS Package for ReliQuary
+
+Wraps envelope data keys with AWS KMS, Google Cloud KMS or Azure Key Vault keys, storing the
+wrapped key in the standard RQEV envelope format.
+"""
+
+from .aws import AwsKmsBackend
+from .azure import AzureKeyVaultBackend
+from .backend import KmsBackend, KmsError
+from .envelope import CloudKmsEnvelope
+from .gcp import GcpKmsBackend
+
+__all__ = [
+    "AwsKmsBackend",
+    "AzureKeyVaultBackend",
+    "CloudKmsEnvelope",
+    "GcpKmsBackend",
+    "KmsBackend",
+    "KmsError"
+]
@@ -0,0 +1,64 @@
+"""
+AWS KMS backend.
+
+Uses KMS Encrypt/Decrypt (and GenerateDataKey for new DEKs) with a symmetric KMS key. The
+caller's context becomes the KMS encryption context, so it shows up in CloudTrail and KMS
+refuses to decrypt without it.
+"""
+
+import base64
+from typing import Any, Dict, Optional, Tuple
+
+from .backend import DEK_BYTES, KmsBackend, KmsError
+
+CONTEXT_KEY = "reliquary-aad"
+
+
+class AwsKmsBackend(KmsBackend):
+    """DEKs wrapped by AWS KMS; `key_id` is a key ARN, alias ARN or alias name"""
+
+    provider = "aws-kms"
+
+    def __init__(self, client: Any = None, region_name: Optional[str] = None):
+        """
+        Initialize the backend.
+
+        Args:
+            client: boto3 KMS client (created from the default session if omitted)
+            region_name: Region for the default client
+        """
+        if client is None:
+            try:
+                import boto3
+            except ImportError:
+                raise KmsError("boto3 is required for the AWS KMS backend")
+            client = boto3.client("kms", region_name=region_name)
+        self.client = client
+
+    @staticmethod
+    def _context(context: bytes) -> Dict[str, str]:
+        return {CONTEXT_KEY: base64.b64encode(context).decode("ascii")} if context else {}
+
+    def wrap_key(self, key_id: str, dek: bytes, context: bytes = b"") -> bytes:
+        try:
+            response = self.client.encrypt(KeyId=key_id, Plaintext=dek,
+                                           EncryptionContext=self._context(context))
+        except Exception as e:
+            raise KmsError(f"AWS KMS Encrypt with {key_id} failed: {e}")
+        return response["CiphertextBlob"]
+
+    def unwrap_key(self, key_id: str, wrapped_dek: bytes, context: bytes = b"") -> bytes:
+        try:
+            response = self.client.decrypt(KeyId=key_id, CiphertextBlob=wrapped_dek,
+                                           EncryptionContext=self._context(context))
+        except Exception as e:
+            raise KmsError(f"AWS KMS Decrypt with {key_id} failed: {e}")
+        return response["Plaintext"]
+
+    def generate_data_key(self, key_id: str, context: bytes = b"") -> Tuple[bytes, bytes]:
+        try:
+            response = self.client.generate_data_key(KeyId=key_id, NumberOfBytes=DEK_BYTES,
+                                                     EncryptionContext=self._context(context))
+        except Exception as e:
+            raise KmsError(f"AWS KMS GenerateDataKey with {key_id} failed: {e}")
+        return response["Plaintext"], response["CiphertextBlob"]
@@ -0,0 +1,61 @@
+"""
+Azure Key Vault backend.
+
+Wraps DEKs with WrapKey/UnwrapKey on a Key Vault (or Managed HSM) RSA key. Key wrapping in
+Key Vault has no associated data, so the caller's context is bound only by the envelope's own
+AES-GCM associated data.
+"""
+
+from typing import Any, Callable, Optional
+
+from .backend import KmsBackend, KmsError
+
+DEFAULT_ALGORITHM = "RSA-OAEP-256"
+
+
+class AzureKeyVaultBackend(KmsBackend):
+    """DEKs wrapped by Azure Key Vault; `key_id` is the key URL (optionally with a version)"""
+
+    provider = "azure-keyvault"
+
+    def __init__(self, credential: Any = None, algorithm: str = DEFAULT_ALGORITHM,
+                 client_factory: Optional[Callable[[str], Any]] = None):
+        """
+        Initialize the backend.
+
+        Args:
+            credential: Azure credential (DefaultAzureCredential if omitted)
+            algorithm: Key wrap algorithm, e.g. "RSA-OAEP-256"
+            client_factory: Returns a CryptographyClient for a key URL (replaces the SDK)
+        """
+        self.algorithm = algorithm
+        self._clients = {}
+        if client_factory is None:
+            try:
+                from azure.identity import DefaultAzureCredential
+                from azure.keyvault.keys.crypto import CryptographyClient
+            except ImportError:
+                raise KmsError("azure-keyvault-keys and azure-identity are required for the "
+                               "Azure Key Vault backend")
+            credential = credential or DefaultAzureCredential()
+
+            def client_factory(key_id: str) -> Any:
+                return CryptographyClient(key_id, credential)
+        self.client_factory = client_factory
+
+    def _client(self, key_id: str) -> Any:
+        if key_id not in self._clients:
+            self._clients[key_id] = self.client_factory(key_id)
+        return self._clients[key_id]
+
+    def wrap_key(self, key_id: str, dek: bytes, context: bytes = b"") -> bytes:
+        try:
+            return self._client(key_id).wrap_key(self.algorithm, dek).encrypted_key
+        except Exception as e:
+            raise KmsError(f"Key Vault wrapKey with {key_id} failed: {e}")
+
+    def unwrap_key(self, key_id: str, wrapped_dek: bytes, context: bytes = b"") -> bytes:
+        try:
+            return self._client(key_id).unwrap_key(self.algorithm, wrapped_dek).key
+        except Exception as e:
+            raise KmsError(f"Key Vault unwrapKey with {key_id} failed: {e}")
@@ -0,0 +1,47 @@
+"""
+Cloud KMS backend interface.
+
+A backend wraps and unwraps 32-byte data-encryption keys (DEKs) with a key that never leaves
+the provider's KMS. Envelope encryption itself happens locally in `reliquary_encryptor`; the
+KMS only ever sees the DEK.
+"""
+
+import os
+from abc import ABC, abstractmethod
+from typing import Tuple
+
+DEK_BYTES = 32
+
+
+class KmsError(Exception):
+    """Raised when a cloud KMS call fails or a backend is misconfigured"""
+
+
+class KmsBackend(ABC):
+    """
+    Abstract base class for cloud KMS backends.
+
+    `provider` names the backend in envelopes, so it must stay stable once data is sealed.
+    `context` is the caller's associated data; backends bind it to the wrapped key where the
+    KMS supports that (AWS encryption context, GCP additional authenticated data).
+    """
+
+    provider: str = ""
+
+    @abstractmethod
+    def wrap_key(self, key_id: str, dek: bytes, context: bytes = b"") -> bytes:
+        """Wrap `dek` under the KMS key `key_id`."""
+
+    @abstractmethod
+    def unwrap_key(self, key_id: str, wrapped_dek: bytes, context: bytes = b"") -> bytes:
+        """Unwrap a DEK previously wrapped under `key_id`."""
+
+    def generate_data_key(self, key_id: str, context: bytes = b"") -> Tuple[bytes, bytes]:
+        """
+        Create a fresh DEK and wrap it.
+
+        Returns:
+            (dek, wrapped_dek)
+        """
+        dek = os.urandom(DEK_BYTES)
+        return dek, self.wrap_key(key_id, dek, context)
@@ -0,0 +1,95 @@
+"""
+Envelope encryption with cloud-KMS-wrapped data keys.
+
+Produces the same RQEV envelopes as the post-quantum path (format version 3): the payload is
+sealed locally under a fresh DEK and the envelope carries the DEK wrapped by the chosen KMS,
+together with the provider and key id needed to unwrap it. Hybrid deployments can keep their
+cloud root of trust for some data and Kyber recipients for the rest, with one storage format.
+"""
+
+import logging
+from typing import Any, Dict, Iterable
+
+from .backend import KmsBackend, KmsError
+
+try:
+    import reliquary_encryptor
+except ImportError:
+    reliquary_encryptor = None
+
+
+class CloudKmsEnvelope:
+    """Seals and opens envelopes whose DEK is wrapped by one of several KMS backends"""
+
+    def __init__(self, backends: Iterable[KmsBackend], audit_logger: Any = None):
+        """
+        Initialize the envelope helper.
+
+        Args:
+            backends: KMS backends, at most one per provider
+            audit_logger: Sink with add_entry(dict) (e.g. MerkleLogWriter)
+        """
+        if reliquary_encryptor is None:
+            raise KmsError("reliquary_encryptor is required for KMS envelopes")
+        self.backends: Dict[str, KmsBackend] = {}
+        for backend in backends:
+            if backend.provider in self.backends:
+                raise ValueError(f"Duplicate backend for provider {backend.provider}")
+            self.backends[backend.provider] = backend
+        self.audit_logger = audit_logger
+        self.logger = logging.getLogger(__name__)
+
+    def _backend(self, provider: str) -> KmsBackend:
+        backend = self.backends.get(provider)
+        if backend is None:
+            raise KmsError(f"No backend configured for KMS provider {provider!r}")
+        return backend
+
+    def encrypt(self, data: bytes, provider: str, key_id: str, aad: bytes = b"") -> bytes:
+        """
+        Seal `data` under a new DEK wrapped by `key_id` in `provider`'s KMS.
+
+        Args:
+            data: Plaintext
+            provider: Backend provider name, e.g. "aws-kms"
+            key_id: KMS key identifier in that provider's format
+            aad: Associated data; must be repeated on decrypt
+
+        Returns:
+            RQEV envelope (version 3)
+        """
+        dek, wrapped_dek = self._backend(provider).generate_data_key(key_id, aad)
+        envelope = reliquary_encryptor.encrypt_with_kms_dek(data, dek, provider, key_id,
+                                                            wrapped_dek, aad)
+        self._log("kms_envelope_sealed", provider, key_id)
+        return envelope
+
+    def decrypt(self, envelope: bytes, aad: bytes = b"") -> bytes:
+        """
+        Open an envelope by asking the KMS recorded in it to unwrap the DEK.
+
+        Raises:
+            KmsError: If no backend matches the envelope or the KMS refuses to unwrap
+            ValueError: If the envelope is malformed or fails authentication
+        """
+        provider, key_id, wrapped_dek = reliquary_encryptor.kms_envelope_info(envelope)
+        dek = self._backend(provider).unwrap_key(key_id, wrapped_dek, aad)
+        plaintext = reliquary_encryptor.decrypt_with_kms_dek(envelope, dek, aad)
+        self._log("kms_envelope_opened", provider, key_id)
+        return plaintext
+
+    def rewrap(self, envelope: bytes, provider: str, key_id: str, aad: bytes = b"") -> bytes:
+        """
+        Move an envelope to another KMS key (or provider), e.g. after key rotation or when
+        migrating clouds. The payload is re-sealed under a fresh DEK.
+        """
+        return self.encrypt(self.decrypt(envelope, aad), provider, key_id, aad)
+
+    def _log(self, event: str, provider: str, key_id: str):
+        self.logger.debug(f"{event}: {provider} {key_id}")
+        if self.audit_logger:
+            try:
+                self.audit_logger.add_entry({"event": event, "kms_provider": provider,
+                                             "kms_key_id": key_id})
+            except Exception as e:
+                self.logger.warning(f"Could not log KMS envelope event: {e}")
@@ -0,0 +1,56 @@
+"""
+Google Cloud KMS backend.
+
+Uses Encrypt/Decrypt on a symmetric CryptoKey; the caller's context is passed as additional
+authenticated data.
+"""
+
+from typing import Any
+
+from .backend import KmsBackend, KmsError
+
+
+class GcpKmsBackend(KmsBackend):
+    """
+    DEKs wrapped by Cloud KMS; `key_id` is the CryptoKey resource name
+    (projects/.../locations/.../keyRings/.../cryptoKeys/...)
+    """
+
+    provider = "gcp-kms"
+
+    def __init__(self, client: Any = None):
+        """
+        Initialize the backend.
+
+        Args:
+            client: KeyManagementServiceClient (created with default credentials if omitted)
+        """
+        if client is None:
+            try:
+                from google.cloud import kms
+            except ImportError:
+                raise KmsError("google-cloud-kms is required for the GCP KMS backend")
+            client = kms.KeyManagementServiceClient()
+        self.client = client
+
+    def wrap_key(self, key_id: str, dek: bytes, context: bytes = b"") -> bytes:
+        try:
+            response = self.client.encrypt(request={
+                "name": key_id,
+                "plaintext": dek,
+                "additional_authenticated_data": context
+            })
+        except Exception as e:
+            raise KmsError(f"Cloud KMS encrypt with {key_id} failed: {e}")
+        return response.ciphertext
+
+    def unwrap_key(self, key_id: str, wrapped_dek: bytes, context: bytes = b"") -> bytes:
+        try:
+            response = self.client.decrypt(request={
+                "name": key_id,
+                "ciphertext": wrapped_dek,
+                "additional_authenticated_data": context
+            })
+        except Exception as e:
+            raise KmsError(f"Cloud KMS decrypt with {key_id} failed: {e}")
+        return response.plaintext
@@ -28,9 +28,108 @@ from .aes_gcm import (
 from .key_sharding import (
     split_key_into_shards,
     reconstruct_key_from_shards,
+    split_key_verifiable,
+    verify_shard,
+    find_invalid_shards,
+    reconstruct_key_verifiable,
+    generate_refresh,
+    apply_refresh_to_dealing,
+    apply_refresh_to_shard,
     KeyShard
 )
 
+from .dkg import (
+    new_dkg_session_id,
+    create_dkg_participant,
+    run_local_dkg,
+    dkg_result,
+    DkgResult
+)
+
+from .domain_hash import (
+    DomainHash,
+    DomainLabelCollision,
+    HashContext,
+    register_hash_context,
+    registered_hash_contexts
+)
+
+from .threshold_kek import (
+    wrap_kek_threshold,
+    partial_unwrap,
+    invalid_partials,
+    unwrap_kek_threshold
+)
+
+from .one_time_shares import (
+    create_one_time_shares,
+    share_envelope_id,
+    InMemoryTombstoneStore,
+    FileTombstoneStore,
+    OneTimeShareRecovery,
+    SharesBurned,
+    verify_tombstone_chain
+)
+
+from .social_recovery import (
+    create_social_recovery,
+    describe_recovery,
+    release_guardian_share,
+    invalid_released_shares,
+    recover_secret,
+    SocialRecoveryKit
+)
+
+from .verifier import (
+    verify_ed25519,
+    envelope_info,
+    envelope_recipients,
+    envelope_threshold,
+    kms_envelope_info,
+    create_merkle_root,
+    verify_merkle_proof,
+    verify_positional_proof,
+    verify_consistency_proof,
+    verify_multi_proof,
+    verify_sparse_proof,
+    verify_map_proof,
+    merkle_proof_size,
+    merkle_root_version,
+    merkle_root_hash_algorithm,
+    verifier_backends
+)
+
+from .pq_migration import (
+    envelope_needs_migration,
+    migrate_envelope,
+    migrate_multi_recipient_envelope,
+    migrate_signature_bundle,
+    ROUND3_KEMS,
+    ROUND3_SIGNATURE_SCHEMES
+)
+
+from .tree_head import (
+    sign_tree_head,
+    verify_tree_head,
+    request_timestamp,
+    verify_timestamped_tree_head,
+    SignedTreeHead,
+    TimestampedTreeHead,
+    SIGNATURE_ALGORITHMS as TREE_HEAD_SIGNATURE_ALGORITHMS
+)
+
+from .fault_injection import (
+    fault_injection_available,
+    crypto_faults,
+    FaultLog
+)
+
+from .pyca_adapters import (
+    to_pyca_private_key,
+    to_pyca_public_key,
+    from_pyca_key
+)
+
 __all__ = [
     # Rust FFI wrappers
     "encrypt_data_rust",
@@ -51,5 +150,94 @@ __all__ = [
     # Key sharding
     "split_key_into_shards",
     "reconstruct_key_from_shards",
-    "KeyShard"
+    "split_key_verifiable",
+    "verify_shard",
+    "find_invalid_shards",
+    "reconstruct_key_verifiable",
+    "generate_refresh",
+    "apply_refresh_to_dealing",
+    "apply_refresh_to_shard",
+    "KeyShard",
+
+    # Distributed key generation
+    "new_dkg_session_id",
+    "create_dkg_participant",
+    "run_local_dkg",
+    "dkg_result",
+    "DkgResult",
+
+    # Domain-separated hashing
+    "DomainHash",
+    "DomainLabelCollision",
+    "HashContext",
+    "register_hash_context",
+    "registered_hash_contexts",
+
+    # Threshold KEK
+    "wrap_kek_threshold",
+    "partial_unwrap",
+    "invalid_partials",
+    "unwrap_kek_threshold",
+
+    # One-time share envelopes
+    "create_one_time_shares",
+    "share_envelope_id",
+    "InMemoryTombstoneStore",
+    "FileTombstoneStore",
+    "OneTimeShareRecovery",
+    "SharesBurned",
+    "verify_tombstone_chain",
+
+    # Social recovery
+    "create_social_recovery",
+    "describe_recovery",
+    "release_guardian_share",
+    "invalid_released_shares",
+    "recover_secret",
+    "SocialRecoveryKit",
+
+    # Verify-only operations (native or pure-Python fallback)
+    "verify_ed25519",
+    "envelope_info",
+    "envelope_recipients",
+    "envelope_threshold",
+    "kms_envelope_info",
+    "create_merkle_root",
+    "verify_merkle_proof",
+    "verify_positional_proof",
+    "verify_consistency_proof",
+    "verify_multi_proof",
+    "verify_sparse_proof",
+    "verify_map_proof",
+    "merkle_proof_size",
+    "merkle_root_version",
+    "merkle_root_hash_algorithm",
+    "verifier_backends",
+
+    # FIPS 203 / 204 migration
+    "envelope_needs_migration",
+    "migrate_envelope",
+    "migrate_multi_recipient_envelope",
+    "migrate_signature_bundle",
+    "ROUND3_KEMS",
+    "ROUND3_SIGNATURE_SCHEMES",
+
+    # Signed tree heads
+    "sign_tree_head",
+    "verify_tree_head",
+    "request_timestamp",
+    "verify_timestamped_tree_head",
+    "SignedTreeHead",
+    "TimestampedTreeHead",
+    "TREE_HEAD_SIGNATURE_ALGORITHMS",
+
+    # Fault injection (resilience tests)
+    "fault_injection_available",
+    "crypto_faults",
+    "FaultLog",
+
+    # pyca/cryptography adapters
+    "to_pyca_private_key",
+    "to_pyca_public_key",
+    "from_pyca_key"
 ]
\ No newline at end of file
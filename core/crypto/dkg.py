@@ -0,0 +1,81 @@
+# core/crypto/dkg.py
+
+import os
+from dataclasses import dataclass
+from typing import Dict, List
+
+try:
+    import reliquary_encryptor
+except ImportError:
+    reliquary_encryptor = None
+
+# --- Distributed key generation for trustee quorums ---
+#
+# Each trustee runs one reliquary_encryptor.DkgParticipant. All protocol messages are bytes, so
+# they can be carried by whatever transport the agents already use; commitments, complaints and
+# justifications are broadcast to everyone, shares go privately to their recipient.
+
+
+@dataclass
+class DkgResult:
+    """One trustee's output of a DKG session."""
+    index: int
+    group_key: bytes
+    key_share: bytes
+    public_key: bytes
+    threshold: int
+    qualified: List[int]
+
+
+def new_dkg_session_id() -> bytes:
+    """Random session id to hand to every participant of a new DKG session."""
+    return os.urandom(16)
+
+
+def create_dkg_participant(session_id: bytes, index: int, threshold: int, count: int):
+    """
+    Create this trustee's DKG state.
+
+    Args:
+        session_id: Shared by all participants (see new_dkg_session_id)
+        index: This trustee's index, 1..count
+        threshold: Number of key shares needed to use the group key
+        count: Number of participants
+    """
+    if reliquary_encryptor is None:
+        raise RuntimeError("Distributed key generation requires reliquary_encryptor")
+    return reliquary_encryptor.DkgParticipant(session_id, index, threshold, count)
+
+
+def dkg_result(participant) -> DkgResult:
+    """Finish a participant whose complaints have been resolved."""
+    group_key, key_share = participant.finish()
+    threshold, _, public_key, qualified = reliquary_encryptor.dkg_group_key_info(group_key)
+    return DkgResult(index=participant.index, group_key=group_key, key_share=key_share,
+                     public_key=public_key, threshold=threshold, qualified=list(qualified))
+
+
+def run_local_dkg(participants: List) -> Dict[int, DkgResult]:
+    """
+    Drive every round for participants hosted in this process, routing messages directly.
+
+    Useful for single-host deployments and tests; distributed trustees run the same rounds
+    themselves, exchanging the messages over their own channels.
+
+    Returns:
+        Each qualified participant's result, keyed by index
+    """
+    by_index = {p.index: p for p in participants}
+    sent = {p.index: p.round1() for p in participants}
+    for sender, (commitment, shares) in sent.items():
+        for recipient, share in shares.items():
+            if recipient in by_index:
+                by_index[recipient].receive_commitment(commitment)
+                by_index[recipient].receive_share(share)
+
+    complaints = [c for p in participants for c in p.complaints()]
+    justifications = [j for p in participants for j in p.justify(complaints)]
+    disqualified = set()
+    for p in participants:
+        disqualified.update(p.resolve(complaints, justifications))
+    return {p.index: dkg_result(p) for p in participants if p.index not in disqualified}
@@ -0,0 +1,151 @@
+# core/crypto/domain_hash.py
+
+import hashlib
+import re
+import threading
+from dataclasses import dataclass
+from typing import Dict, Iterable, Union
+
+try:
+    import blake3
+except ImportError:
+    blake3 = None
+
+# --- Domain-separated hashing with registered context labels ---
+#
+# Every hash is computed over an unambiguous encoding of (scheme tag, algorithm, label, parts),
+# in the style of TupleHash: each field carries an 8-byte big-endian length, so no choice of
+# label or parts can be mistaken for another, and ("ab",) never hashes like ("a", "b"). With
+# BLAKE3 the label instead selects a derive_key context, which BLAKE3 keeps separate natively.
+# Labels must be registered once, by the subsystem that owns them, so two subsystems cannot
+# end up hashing under the same label by accident.
+
+SCHEME_TAG = b"reliquary/domain-hash/v1"
+_LABEL_PATTERN = re.compile(r"^[a-z0-9][a-z0-9._/-]{0,127}$")
+_ALGORITHMS = {
+    "sha256": hashlib.sha256,
+    "sha512": hashlib.sha512,
+    "sha3-256": hashlib.sha3_256,
+    "sha3-512": hashlib.sha3_512,
+    "blake2b": hashlib.blake2b,
+}
+
+Part = Union[bytes, bytearray, memoryview, str]
+
+
+class DomainLabelCollision(ValueError):
+    """A context label is already registered to a different owner."""
+
+
+@dataclass(frozen=True)
+class HashContext:
+    label: str
+    owner: str
+    description: str = ""
+
+
+_registry: Dict[str, HashContext] = {}
+_registry_lock = threading.Lock()
+
+
+def register_hash_context(label: str, owner: str, description: str = "") -> HashContext:
+    """
+    Claim a context label for a subsystem.
+
+    Labels are lowercase, start with a letter or digit and may contain ".", "_", "/" and "-",
+    e.g. "vaults/record-id". Registering the same label again with the same owner is a no-op,
+    so module reloads are harmless.
+
+    Args:
+        label: The context label
+        owner: The subsystem claiming it, usually the module's __name__
+        description: What the hashes under this label are used for
+
+    Raises:
+        ValueError: If the label is malformed
+        DomainLabelCollision: If another owner already registered the label
+    """
+    if not _LABEL_PATTERN.match(label):
+        raise ValueError(f"Invalid hash context label {label!r}")
+    with _registry_lock:
+        existing = _registry.get(label)
+        if existing is not None:
+            if existing.owner != owner:
+                raise DomainLabelCollision(
+                    f"Hash context '{label}' is already registered by {existing.owner}")
+            return existing
+        context = HashContext(label, owner, description)
+        _registry[label] = context
+        return context
+
+
+def registered_hash_contexts() -> Dict[str, HashContext]:
+    with _registry_lock:
+        return dict(_registry)
+
+
+def _field(data: bytes) -> bytes:
+    return len(data).to_bytes(8, "big") + data
+
+
+def _part_bytes(part: Part) -> bytes:
+    if isinstance(part, str):
+        return part.encode("utf-8")
+    if isinstance(part, (bytes, bytearray, memoryview)):
+        return bytes(part)
+    raise TypeError(f"Hash parts must be bytes or str, got {type(part).__name__}")
+
+
+class DomainHash:
+    """
+    Hash bound to a registered context label.
+
+    Usage:
+        register_hash_context("vaults/record-id", __name__)
+        digest = DomainHash("vaults/record-id").update(vault_id, record_bytes).digest()
+    """
+
+    def __init__(self, label: Union[str, HashContext], algorithm: str = "sha3-256"):
+        label = label.label if isinstance(label, HashContext) else label
+        with _registry_lock:
+            if label not in _registry:
+                raise KeyError(f"Hash context '{label}' is not registered")
+        self.label = label
+        self.algorithm = algorithm
+        if algorithm == "blake3":
+            if blake3 is None:
+                raise RuntimeError("The blake3 algorithm requires the blake3 package")
+            context = f"{SCHEME_TAG.decode()} {label}"
+            self._hash = blake3.blake3(derive_key_context=context)
+        elif algorithm in _ALGORITHMS:
+            self._hash = _ALGORITHMS[algorithm]()
+            self._hash.update(_field(SCHEME_TAG) + _field(algorithm.encode()) + _field(label.encode()))
+        else:
+            raise ValueError(f"Unsupported hash algorithm '{algorithm}'; "
+                             f"use one of {sorted(_ALGORITHMS) + ['blake3']}")
+
+    def update(self, *parts: Part) -> "DomainHash":
+        """Append parts, each one a separate field; returns self so calls can be chained."""
+        for part in parts:
+            self._hash.update(_field(_part_bytes(part)))
+        return self
+
+    def update_all(self, parts: Iterable[Part]) -> "DomainHash":
+        return self.update(*parts)
+
+    def digest(self) -> bytes:
+        return self._hash.digest()
+
+    def hexdigest(self) -> str:
+        return self.digest().hex()
+
+    def copy(self) -> "DomainHash":
+        clone = object.__new__(DomainHash)
+        clone.label, clone.algorithm, clone._hash = self.label, self.algorithm, self._hash.copy()
+        return clone
+
+    @classmethod
+    def hash(cls, label: Union[str, HashContext], *parts: Part,
+             algorithm: str = "sha3-256") -> bytes:
+        """One-shot digest of parts under label."""
+        return cls(label, algorithm).update(*parts).digest()
@@ -0,0 +1,73 @@
+# core/crypto/fault_injection.py
+
+from contextlib import contextmanager
+from typing import Dict, Iterator, List, Mapping, Tuple
+
+try:
+    import reliquary_encryptor
+except ImportError:
+    reliquary_encryptor = None
+
+# --- Fault injection in reliquary_encryptor for resilience tests ---
+#
+# Only available when the module is built with the `fault-injection` feature
+# (`maturin develop --features fault-injection`). Rules are process-wide, so suites that use
+# them should not run crypto in parallel with unrelated tests.
+
+
+def fault_injection_available() -> bool:
+    return reliquary_encryptor is not None and reliquary_encryptor.fault_injection_available()
+
+
+@contextmanager
+def crypto_faults(rules: Mapping[str, Mapping[str, float]],
+                  seed: int = 0) -> Iterator["FaultLog"]:
+    """
+    Inject faults into crypto operations for the duration of the block.
+
+    Args:
+        rules: Operation ("encrypt", "decrypt", "encapsulate", "decapsulate", "sign",
+            "verify" or "*") -> keyword arguments for set_fault, e.g.
+            {"decrypt": {"error": 0.5}, "sign": {"delay": 1.0, "delay_ms": 20}}
+        seed: Fault RNG seed; the same seed and call sequence give the same faults
+
+    Yields:
+        A FaultLog for inspecting what was injected
+
+    Raises:
+        RuntimeError: If the module was built without fault injection
+    """
+    if not fault_injection_available():
+        raise RuntimeError("reliquary_encryptor was built without the fault-injection feature")
+    reliquary_encryptor.reset_faults(seed)
+    try:
+        for operation, rule in rules.items():
+            reliquary_encryptor.set_fault(operation, **rule)
+        yield FaultLog()
+    finally:
+        reliquary_encryptor.reset_faults(seed)
+
+
+class FaultLog:
+    """View of the faults injected inside a crypto_faults block"""
+
+    @property
+    def faults(self) -> List[Tuple[str, str]]:
+        """(operation, kind) pairs, oldest first"""
+        return reliquary_encryptor.injected_faults()
+
+    def count(self, operation: str = None, kind: str = None) -> int:
+        return sum(1 for op, k in self.faults
+                   if operation in (None, op) and kind in (None, k))
+
+    def summary(self) -> Dict[str, Dict[str, int]]:
+        counts: Dict[str, Dict[str, int]] = {}
+        for op, kind in self.faults:
+            counts.setdefault(op, {}).setdefault(kind, 0)
+            counts[op][kind] += 1
+        return counts
+
+    @staticmethod
+    def is_corrupted(output: bytes) -> bool:
+        """True if `output` was deliberately corrupted inside this block"""
+        return reliquary_encryptor.is_injected_corruption(output)
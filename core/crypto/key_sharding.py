@@ -1,10 +1,16 @@
 # core/crypto/key_sharding.py
 
+import logging
 import os
 import requests
 from dataclasses import dataclass
 from datetime import datetime
-from typing import List
+from typing import List, Tuple
+
+try:
+    import reliquary_encryptor
+except ImportError:
+    reliquary_encryptor = None
 
 NODE_SSS_API_BASE_URL = "http://localhost:31415"
 
@@ -88,6 +94,123 @@ def reconstruct_key_from_shards(shards: List[KeyShard], threshold: int) -> bytes
     return bytes.fromhex(hex_secret)
 
 
+# --- Verifiable secret sharing (Feldman/Pedersen commitments) ---
+
+def _require_vss():
+    if reliquary_encryptor is None:
+        raise RuntimeError("Verifiable secret sharing requires reliquary_encryptor")
+
+
+def split_key_verifiable(secret: bytes, num_shares: int, threshold: int,
+                         scheme: str = "feldman") -> Tuple[bytes, List[KeyShard]]:
+    """
+    Split a secret key into shards that each trustee can verify on receipt.
+
+    Args:
+        secret: The secret key to split
+        num_shares: Number of shares to generate
+        threshold: Minimum number of shares required to reconstruct the secret
+        scheme: "feldman" or "pedersen" (commitments that also hide the secret unconditionally)
+
+    Returns:
+        (dealing, shards) where the dealing is public and must accompany every shard
+    """
+    if not (2 <= threshold <= num_shares):
+        raise ValueError("Threshold must be >= 2 and <= num_shares.")
+    _require_vss()
+    dealing, shares = reliquary_encryptor.vss_split(secret, threshold, num_shares, scheme)
+    return dealing, [
+        KeyShard(id=f"shard_{i+1}", data=share.hex(), index=i+1, threshold=threshold)
+        for i, share in enumerate(shares)
+    ]
+
+
+def verify_shard(dealing: bytes, shard: KeyShard) -> bool:
+    """
+    Check one shard against the dealing's commitments, without any other shard.
+
+    Returns:
+        False if the shard is corrupt, malformed or from another dealing
+    """
+    _require_vss()
+    try:
+        return reliquary_encryptor.vss_verify_share(dealing, bytes.fromhex(shard.data))
+    except ValueError:
+        return False
+
+
+def find_invalid_shards(dealing: bytes, shards: List[KeyShard]) -> List[KeyShard]:
+    """Shards that fail verification, so corruption is caught at distribution time."""
+    _require_vss()
+    blobs = []
+    for shard in shards:
+        try:
+            blobs.append(bytes.fromhex(shard.data))
+        except ValueError:
+            blobs.append(b"")
+    return [shards[i] for i in reliquary_encryptor.vss_check_shares(dealing, blobs)]
+
+
+def reconstruct_key_verifiable(dealing: bytes, shards: List[KeyShard]) -> bytes:
+    """
+    Reconstruct a secret key from verifiable shards; invalid shards are skipped.
+
+    Raises:
+        ValueError: If fewer than threshold shards verify (the message lists the bad ones)
+    """
+    _require_vss()
+    invalid = find_invalid_shards(dealing, shards)
+    if invalid:
+        logging.getLogger(__name__).warning(
+            f"Ignoring shards that fail verification: {[s.id for s in invalid]}")
+    valid = [bytes.fromhex(s.data) for s in shards if s not in invalid]
+    try:
+        return reliquary_encryptor.vss_combine(dealing, valid)
+    except ValueError as e:
+        raise ValueError(f"{e}; failing shards: {[s.id for s in invalid]}") from e
+
+
+
+# --- Proactive refresh: re-randomize shards without reconstructing the secret ---
+
+def generate_refresh(dealing: bytes) -> Tuple[bytes, List[bytes]]:
+    """
+    Contribute to a refresh round by dealing a random sharing of zero.
+
+    Returns:
+        (refresh, deltas) where the refresh is public and deltas[i] is sent privately to the
+        holder of shard i+1
+    """
+    _require_vss()
+    refresh, deltas = reliquary_encryptor.vss_refresh(dealing)
+    return refresh, list(deltas)
+
+
+def apply_refresh_to_dealing(dealing: bytes, refreshes: List[bytes]) -> bytes:
+    """Advance the public dealing to the next epoch once a round's refreshes are collected."""
+    _require_vss()
+    return reliquary_encryptor.vss_apply_refresh(dealing, refreshes)
+
+
+def apply_refresh_to_shard(dealing: bytes, shard: KeyShard, refreshes: List[bytes],
+                           deltas: List[bytes]) -> KeyShard:
+    """
+    Refresh one shard for the next epoch; the old shard should be destroyed afterwards.
+
+    Args:
+        dealing: The dealing before refreshes are applied
+        shard: The trustee's current shard
+        refreshes: Every refresh in the round
+        deltas: The delta addressed to this shard from each refresh
+
+    Raises:
+        ValueError: If the shard or any delta fails verification
+    """
+    _require_vss()
+    data = reliquary_encryptor.vss_refresh_share(
+        dealing, bytes.fromhex(shard.data), refreshes, deltas)
+    return KeyShard(id=shard.id, data=data.hex(), index=shard.index, threshold=shard.threshold)
+
 # Aliases for backward compatibility
 create_shares = split_key_into_shards
 reconstruct_secret = reconstruct_key_from_shards
@@ -0,0 +1,186 @@
+# core/crypto/one_time_shares.py
+
+import json
+import logging
+import os
+import threading
+from datetime import datetime, timezone
+from typing import Any, Dict, List, Optional, Sequence, Tuple
+
+try:
+    import reliquary_encryptor
+except ImportError:
+    reliquary_encryptor = None
+
+# --- One-time share envelopes: every combine burns a use and leaves signed evidence ---
+#
+# The encryptor module opens an envelope only together with a tombstone: a record of that
+# combine signed by a key-provider key and chained to the envelope's previous tombstone. The
+# module is stateless, so single use is enforced here: the latest tombstone is handed in (the
+# module refuses a burned one) and the new one is persisted before the secret is released.
+
+
+class SharesBurned(Exception):
+    """Raised when an envelope has used up all its combines"""
+
+
+def _require_encryptor():
+    if reliquary_encryptor is None:
+        raise RuntimeError("One-time share envelopes require reliquary_encryptor")
+
+
+def create_one_time_shares(secret: bytes, threshold: int, count: int,
+                           max_uses: int = 1) -> Tuple[str, List[bytes]]:
+    """
+    Split a secret into one-time share containers.
+
+    Args:
+        secret: The secret to protect
+        threshold: Shares needed to combine
+        count: Shares to create
+        max_uses: How many times the envelope may be combined before it is burned
+
+    Returns:
+        (envelope_id hex, share containers)
+    """
+    _require_encryptor()
+    envelope_id, shares = reliquary_encryptor.create_one_time_shares(secret, threshold, count,
+                                                                     max_uses)
+    return envelope_id.hex(), shares
+
+
+def share_envelope_id(share: bytes) -> str:
+    _require_encryptor()
+    return reliquary_encryptor.one_time_share_info(share)[0].hex()
+
+
+class InMemoryTombstoneStore:
+    """Tombstone chains per envelope id, oldest first"""
+
+    def __init__(self):
+        self._chains: Dict[str, List[bytes]] = {}
+
+    def chain(self, envelope_id: str) -> List[bytes]:
+        return list(self._chains.get(envelope_id, []))
+
+    def append(self, envelope_id: str, tombstone: bytes):
+        self._chains.setdefault(envelope_id, []).append(tombstone)
+
+
+class FileTombstoneStore:
+    """Tombstones as JSON lines in an append-only file, synced before append returns"""
+
+    def __init__(self, path: str):
+        self.path = path
+
+    def chain(self, envelope_id: str) -> List[bytes]:
+        if not os.path.exists(self.path):
+            return []
+        with open(self.path, "r", encoding="utf-8") as f:
+            records = [json.loads(line) for line in f if line.strip()]
+        return [bytes.fromhex(r["tombstone"]) for r in records if r["envelope_id"] == envelope_id]
+
+    def append(self, envelope_id: str, tombstone: bytes):
+        with open(self.path, "a", encoding="utf-8") as f:
+            f.write(json.dumps({"envelope_id": envelope_id, "tombstone": tombstone.hex()}) + "\n")
+            f.flush()
+            os.fsync(f.fileno())
+
+
+class OneTimeShareRecovery:
+    """
+    Combines one-time shares, at most max_uses times per envelope.
+
+    Usage:
+        recovery = OneTimeShareRecovery(FileTombstoneStore("tombstones.jsonl"), "recovery-signer")
+        secret = recovery.combine(shares)
+        recovery.combine(shares)  # raises SharesBurned for a single-use envelope
+    """
+
+    def __init__(self, store: Any, signer_label: str, signer_provider: str = "software",
+                 audit_logger: Any = None):
+        """
+        Initialize the recovery flow.
+
+        Args:
+            store: Tombstone store with chain(envelope_id) and append(envelope_id, tombstone)
+            signer_label: Provider key that signs the tombstones
+            signer_provider: Key provider holding that key
+            audit_logger: Sink with add_entry(dict) (e.g. MerkleLogWriter)
+        """
+        _require_encryptor()
+        self.store = store
+        self.signer_label = signer_label
+        self.signer_provider = signer_provider
+        self.audit_logger = audit_logger
+        self.logger = logging.getLogger(__name__)
+        self._lock = threading.Lock()
+
+    def combine(self, shares: Sequence[bytes]) -> bytes:
+        """
+        Combine shares of one envelope and burn a use.
+
+        Raises:
+            SharesBurned: If the envelope has no uses left
+            ValueError: If the shares are wrong, too few or from different envelopes
+        """
+        if not shares:
+            raise ValueError("No shares given")
+        envelope_id = share_envelope_id(shares[0])
+        with self._lock:
+            chain = self.store.chain(envelope_id)
+            previous = chain[-1] if chain else None
+            if previous is not None and reliquary_encryptor.ShareTombstone(previous).burned:
+                raise SharesBurned(f"Envelope {envelope_id} is burned")
+            secret, tombstone = reliquary_encryptor.combine_one_time_shares(
+                list(shares), self.signer_label, self.signer_provider, previous)
+            self.store.append(envelope_id, tombstone)
+
+        record = reliquary_encryptor.ShareTombstone(tombstone)
+        if self.audit_logger:
+            try:
+                self.audit_logger.add_entry({
+                    "event": "one_time_shares_combined",
+                    "timestamp": datetime.fromtimestamp(record.combined_at,
+                                                        timezone.utc).isoformat(),
+                    "envelope_id": envelope_id,
+                    "use_count": record.use_count,
+                    "max_uses": record.max_uses,
+                    "share_indices": record.share_indices,
+                    "tombstone": tombstone.hex(),
+                })
+            except Exception as e:
+                self.logger.warning(f"Could not log one-time share combine: {e}")
+        return secret
+
+    def status(self, envelope_id: str) -> Dict[str, Any]:
+        chain = self.store.chain(envelope_id)
+        if not chain:
+            return {"envelope_id": envelope_id, "use_count": 0, "burned": False}
+        latest = reliquary_encryptor.ShareTombstone(chain[-1])
+        return {"envelope_id": envelope_id, "use_count": latest.use_count,
+                "max_uses": latest.max_uses, "burned": latest.burned}
+
+    def evidence(self, envelope_id: str) -> List[Any]:
+        """The envelope's tombstones as ShareTombstone objects, oldest first"""
+        return [reliquary_encryptor.ShareTombstone(t) for t in self.store.chain(envelope_id)]
+
+
+def verify_tombstone_chain(tombstones: Sequence[bytes], signer_public_key: bytes,
+                           envelope_id: Optional[str] = None) -> bool:
+    """
+    Check that tombstones are an unbroken record of an envelope's combines: every one signed
+    under signer_public_key, the first one first, each following the one before it and all
+    opening the same ciphertext.
+    """
+    _require_encryptor()
+    records = [reliquary_encryptor.ShareTombstone(t) for t in tombstones]
+    if not records or records[0].use_count != 1 or any(records[0].previous_digest):
+        return False
+    if envelope_id is not None and records[0].envelope_id.hex() != envelope_id:
+        return False
+    for previous, record in zip(records, records[1:]):
+        if not record.follows(previous) or \
+                record.ciphertext_digest != records[0].ciphertext_digest:
+            return False
+    return all(record.verify(signer_public_key) for record in records)
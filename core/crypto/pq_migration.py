@@ -0,0 +1,102 @@
+# core/crypto/pq_migration.py
+
+from typing import List, Optional
+
+from .pure_verify import envelope_info
+
+try:
+    import reliquary_encryptor
+except ImportError:
+    reliquary_encryptor = None
+
+# --- Migration from round-3 Kyber / Dilithium to the final FIPS 203 / 204 standards ---
+#
+# ML-KEM-1024 and ML-DSA-87 sit alongside the round-3 parameter sets rather than replacing
+# them: envelopes carry KEM wire id 4 (KyberParams.MlKem1024) and signature bundles scheme id
+# 5 ("ml-dsa-87"), so old and new data coexist and stay readable. Existing data moves over by
+# re-wrapping: an envelope is opened with its Kyber key and re-sealed to an ML-KEM key, and a
+# signature bundle is countersigned with ML-DSA-87, keeping its earlier layers. To keep a whole
+# bundle store current, run ArchivalResigner with scheme="ml-dsa-87" and a policy whose
+# deprecated_schemes include ROUND3_SIGNATURE_SCHEMES.
+
+ML_KEM = "ml-kem-1024"
+ML_DSA = "ml-dsa-87"
+ROUND3_KEMS = frozenset({"kyber512", "kyber768", "kyber1024"})
+ROUND3_SIGNATURE_SCHEMES = frozenset({"dilithium5"})
+
+
+def _require_encryptor():
+    if reliquary_encryptor is None:
+        raise RuntimeError("Post-quantum migration requires the reliquary_encryptor module")
+    return reliquary_encryptor
+
+
+def envelope_needs_migration(envelope: bytes) -> bool:
+    """Whether any KEM in the envelope's header is a round-3 Kyber parameter set"""
+    info = envelope_info(envelope)
+    if info["version"] == 1:
+        return info["kem"] in ROUND3_KEMS
+    if info["version"] == 2:
+        return any(slot["kem"] in ROUND3_KEMS for slot in info["recipients"])
+    return False
+
+
+def migrate_envelope(envelope: bytes, kyber_secret_key, ml_kem_public_key: bytes) -> bytes:
+    """
+    Re-seal a single-recipient envelope to an ML-KEM-1024 public key.
+
+    Args:
+        envelope: Envelope sealed to any Kyber or ML-KEM key
+        kyber_secret_key: Secret key (or key handle) that opens it
+        ml_kem_public_key: ML-KEM-1024 public key to re-seal to
+
+    Returns:
+        The re-sealed envelope, or the envelope unchanged if it is already sealed to
+        ml_kem_public_key in the current format
+
+    Raises:
+        ValueError: If the envelope doesn't open or is multi-recipient
+    """
+    encryptor = _require_encryptor()
+    _, upgraded = encryptor.decrypt_and_rewrap(envelope, kyber_secret_key, ml_kem_public_key,
+                                               encryptor.KyberParams.MlKem1024)
+    return envelope if upgraded is None else upgraded
+
+
+def migrate_multi_recipient_envelope(envelope: bytes, kyber_secret_keys: List,
+                                     ml_kem_public_keys: List[bytes],
+                                     threshold: Optional[int] = None) -> bytes:
+    """
+    Rebuild a multi-recipient envelope's slots for ML-KEM-1024 recipients. The payload
+    ciphertext is kept; only the key shares are re-wrapped.
+
+    Args:
+        envelope: Multi-recipient envelope
+        kyber_secret_keys: At least the envelope's threshold of its recipients' secret keys
+        ml_kem_public_keys: ML-KEM-1024 public keys of the new recipients
+        threshold: New threshold; defaults to the current one
+
+    Raises:
+        ValueError: If too few keys open the envelope or the new threshold is out of range
+    """
+    encryptor = _require_encryptor()
+    if threshold is None:
+        threshold = envelope_info(envelope)["threshold"]
+    return encryptor.rewrap_recipients(envelope, ml_kem_public_keys, threshold,
+                                       kyber_secret_keys, encryptor.KyberParams.MlKem1024)
+
+
+def migrate_signature_bundle(bundle: bytes, ml_dsa_secret_key, ml_dsa_public_key: bytes,
+                             signer: str = "", not_before: Optional[int] = None,
+                             not_after: Optional[int] = None) -> bytes:
+    """
+    Countersign a signature bundle with ML-DSA-87. The existing signatures stay in the bundle
+    as inner layers; verify_signature_bundle reports the new one as the outermost.
+
+    Raises:
+        ValueError: If the bundle is malformed, or a validity window is given for a legacy v1
+            bundle
+    """
+    encryptor = _require_encryptor()
+    return encryptor.countersign_bundle(bundle, ml_dsa_secret_key, ml_dsa_public_key, ML_DSA,
+                                        signer, not_before=not_before, not_after=not_after)
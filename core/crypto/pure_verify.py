@@ -0,0 +1,600 @@
+# core/crypto/pure_verify.py
+
+import hashlib
+from typing import Callable, Dict, List, Optional, Tuple
+
+# --- Verify-only pure-Python fallback for the native modules ---
+#
+# Restricted environments that can't load native extensions still need to inspect envelopes,
+# check Merkle proofs and verify Ed25519 signatures. This module reimplements exactly those
+# read-only operations with the standard library alone, under the native function names and
+# with the same results and error messages (see tests/vectors/verify_conformance.json, which
+# both implementations are run against). Nothing here decrypts, signs or handles secret keys,
+# and it is not constant time, which verification of public data doesn't need. Use
+# core.crypto.verifier to get the native implementation when it is available and this one
+# otherwise.
+
+# --- Ed25519 (RFC 8032), strict verification as in ed25519-dalek's verify_strict ---
+
+_P = 2 ** 255 - 19
+_L = 2 ** 252 + 27742317777372353535851937790883648493
+_D = -121665 * pow(121666, _P - 2, _P) % _P
+_SQRT_M1 = pow(2, (_P - 1) // 4, _P)
+_IDENTITY = (0, 1, 1, 0)
+
+
+def _point_add(a, b):
+    # Extended twisted Edwards coordinates (X, Y, Z, T), add-2008-hwcd-3
+    x1, y1, z1, t1 = a
+    x2, y2, z2, t2 = b
+    aa = (y1 - x1) * (y2 - x2) % _P
+    bb = (y1 + x1) * (y2 + x2) % _P
+    cc = 2 * t1 * t2 * _D % _P
+    dd = 2 * z1 * z2 % _P
+    e, f, g, h = bb - aa, dd - cc, dd + cc, bb + aa
+    return e * f % _P, g * h % _P, f * g % _P, e * h % _P
+
+
+def _scalar_mul(k: int, point):
+    result = _IDENTITY
+    while k:
+        if k & 1:
+            result = _point_add(result, point)
+        point = _point_add(point, point)
+        k >>= 1
+    return result
+
+
+def _compress(point) -> bytes:
+    x, y, z, _ = point
+    z_inv = pow(z, _P - 2, _P)
+    x, y = x * z_inv % _P, y * z_inv % _P
+    return (y | (x & 1) << 255).to_bytes(32, "little")
+
+
+def _decompress(encoded: bytes):
+    """Decodes a point like curve25519-dalek: y is reduced mod p, None if x doesn't exist"""
+    y = int.from_bytes(encoded, "little")
+    sign = y >> 255
+    y = (y & (2 ** 255 - 1)) % _P
+    x2 = (y * y - 1) * pow(_D * y * y + 1, _P - 2, _P) % _P
+    x = pow(x2, (_P + 3) // 8, _P)
+    if (x * x - x2) % _P != 0:
+        x = x * _SQRT_M1 % _P
+    if (x * x - x2) % _P != 0:
+        return None
+    if x & 1 != sign:
+        x = (_P - x) % _P
+    return x, y, 1, x * y % _P
+
+
+def _is_small_order(point) -> bool:
+    return _compress(_scalar_mul(8, point)) == _compress(_IDENTITY)
+
+
+_BASE = _decompress((4 * pow(5, _P - 2, _P) % _P).to_bytes(32, "little"))
+
+
+def _fixed(value: bytes, length: int, what: str) -> bytes:
+    if len(value) != length:
+        raise ValueError(f"Invalid {what} length. Expected {length}, got {len(value)}")
+    return bytes(value)
+
+
+def verify_ed25519(msg: bytes, signature: bytes, pk_bytes: bytes) -> bool:
+    """
+    Verify an Ed25519 signature with strict (non-malleable) rules: S must be reduced, and
+    small-order public keys and R points are rejected.
+
+    Raises:
+        ValueError: If the key or signature has the wrong length, or the key is not a point
+    """
+    pk_bytes = _fixed(pk_bytes, 32, "Ed25519 public key")
+    public_key = _decompress(pk_bytes)
+    if public_key is None:
+        raise ValueError("Invalid public key: signature error: Cannot decompress Edwards point")
+    signature = _fixed(signature, 64, "Ed25519 signature")
+    r_bytes, s = signature[:32], int.from_bytes(signature[32:], "little")
+    r_point = _decompress(r_bytes)
+    if s >= _L or r_point is None or _is_small_order(public_key) or _is_small_order(r_point):
+        return False
+    k = int.from_bytes(hashlib.sha512(r_bytes + pk_bytes + bytes(msg)).digest(), "little") % _L
+    negated_key = ((_P - public_key[0]) % _P, public_key[1], 1, (_P - public_key[3]) % _P)
+    expected_r = _point_add(_scalar_mul(s, _BASE), _scalar_mul(k, negated_key))
+    return _compress(expected_r) == r_bytes
+
+
+# --- Envelope headers ("RQEV" v1 single recipient, v2 multi-recipient, v3 KMS-wrapped) ---
+
+_MAGIC = b"RQEV"
+_NONCE_BYTES = 12
+_TAG_BYTES = 16
+_FINGERPRINT_BYTES = 32
+_MAC_BYTES = 32
+_WRAPPED_SHARE_BYTES = 32 + _TAG_BYTES
+# Kyber / ML-KEM wire id -> (name, ciphertext bytes)
+_KEMS = {1: ("kyber512", 768), 2: ("kyber768", 1088), 3: ("kyber1024", 1568),
+         4: ("ml-kem-1024", 1568)}
+
+
+class _Reader:
+    def __init__(self, data: bytes):
+        self.data = bytes(data)
+        self.position = 0
+
+    def bytes(self, n: int) -> bytes:
+        if len(self.data) - self.position < n:
+            raise ValueError("Input is truncated")
+        out = self.data[self.position:self.position + n]
+        self.position += n
+        return out
+
+    def u8(self) -> int:
+        return self.bytes(1)[0]
+
+    def var(self, width: int) -> bytes:
+        return self.bytes(int.from_bytes(self.bytes(width), "big"))
+
+    def rest(self) -> bytes:
+        out = self.data[self.position:]
+        self.position = len(self.data)
+        return out
+
+
+def _kem(wire_id: int) -> Tuple[str, int]:
+    if wire_id not in _KEMS:
+        raise ValueError(f"Unknown envelope KEM id {wire_id}")
+    return _KEMS[wire_id]
+
+
+def _envelope_version(envelope: bytes) -> int:
+    if len(envelope) < 5 or envelope[:4] != _MAGIC:
+        raise ValueError("Not a Reliquary envelope")
+    return envelope[4]
+
+
+def _parse_single(envelope: bytes) -> Dict:
+    if len(envelope) < 6 or envelope[:4] != _MAGIC:
+        raise ValueError("Not a Reliquary envelope")
+    if envelope[4] != 1:
+        raise ValueError(f"Unsupported envelope version {envelope[4]}")
+    kem, ct_bytes = _kem(envelope[5])
+    if len(envelope) < 6 + ct_bytes + _NONCE_BYTES + _TAG_BYTES:
+        raise ValueError("Envelope is truncated")
+    return {"version": 1, "kem": kem,
+            "ciphertext_bytes": len(envelope) - 6 - ct_bytes - _NONCE_BYTES}
+
+
+def _parse_multi(envelope: bytes) -> Dict:
+    r = _Reader(envelope)
+    try:
+        magic = r.bytes(4)
+    except ValueError:
+        raise ValueError("Not a Reliquary envelope") from None
+    if magic != _MAGIC:
+        raise ValueError("Not a Reliquary envelope")
+    if r.u8() != 2:
+        raise ValueError("Not a multi-recipient envelope")
+    threshold, count = r.u8(), r.u8()
+    if threshold == 0 or threshold > count:
+        raise ValueError("Envelope threshold is out of range")
+    slots = []
+    for _ in range(count):
+        fingerprint = r.bytes(_FINGERPRINT_BYTES)
+        kem, ct_bytes = _kem(r.u8())
+        r.bytes(ct_bytes)
+        index = r.u8()
+        r.bytes(_NONCE_BYTES + _WRAPPED_SHARE_BYTES)
+        slots.append({"fingerprint": fingerprint, "kem": kem, "share_index": index})
+    r.bytes(_MAC_BYTES)
+    body = r.rest()
+    if len(body) < _NONCE_BYTES + _TAG_BYTES:
+        raise ValueError("Envelope is truncated")
+    return {"version": 2, "threshold": threshold, "recipients": slots,
+            "ciphertext_bytes": len(body) - _NONCE_BYTES}
+
+
+def _parse_kms(envelope: bytes) -> Dict:
+    r = _Reader(envelope)
+    try:
+        magic = r.bytes(4)
+    except ValueError:
+        raise ValueError("Not a Reliquary envelope") from None
+    if magic != _MAGIC:
+        raise ValueError("Not a Reliquary envelope")
+    if r.u8() != 3:
+        raise ValueError("Not a KMS-wrapped envelope")
+
+    def text(field: str, raw: bytes) -> str:
+        try:
+            return raw.decode("utf-8")
+        except UnicodeDecodeError:
+            raise ValueError(f"Envelope {field} is not UTF-8") from None
+
+    provider = text("KMS provider", r.var(1))
+    key_id = text("KMS key id", r.var(2))
+    wrapped_dek = r.var(2)
+    if not provider or not key_id or not wrapped_dek:
+        raise ValueError("Envelope KMS provider, key id and wrapped DEK must be non-empty")
+    r.bytes(_NONCE_BYTES)
+    ciphertext = r.rest()
+    if len(ciphertext) < _TAG_BYTES:
+        raise ValueError("Envelope is truncated")
+    return {"version": 3, "kms_provider": provider, "kms_key_id": key_id,
+            "wrapped_dek": wrapped_dek, "ciphertext_bytes": len(ciphertext)}
+
+
+def envelope_info(envelope: bytes) -> Dict:
+    """
+    Describe an envelope's header without opening it.
+
+    Returns:
+        {"version", "ciphertext_bytes"} plus "kem" (v1); "threshold" and "recipients", each
+        {"fingerprint", "kem", "share_index"} (v2); or "kms_provider", "kms_key_id" and
+        "wrapped_dek" (v3). ciphertext_bytes counts the payload ciphertext and its GCM tag
+
+    Raises:
+        ValueError: If the envelope is malformed or of an unknown version
+    """
+    version = _envelope_version(envelope)
+    if version == 2:
+        return _parse_multi(envelope)
+    if version == 3:
+        return _parse_kms(envelope)
+    return _parse_single(envelope)
+
+
+def envelope_recipients(envelope: bytes) -> List[bytes]:
+    """Recipient key fingerprints of a multi-recipient envelope, in slot order"""
+    return [slot["fingerprint"] for slot in _parse_multi(envelope)["recipients"]]
+
+
+def envelope_threshold(envelope: bytes) -> int:
+    """Number of recipient keys needed to open a multi-recipient envelope"""
+    return _parse_multi(envelope)["threshold"]
+
+
+def kms_envelope_info(envelope: bytes) -> Tuple[str, str, bytes]:
+    """(provider, key_id, wrapped_dek) of a KMS-wrapped envelope"""
+    parsed = _parse_kms(envelope)
+    return parsed["kms_provider"], parsed["kms_key_id"], parsed["wrapped_dek"]
+
+
+# --- BLAKE3 (hash mode, 32-byte output), for v2 Merkle trees built with it ---
+
+_B3_IV = (0x6A09E667, 0xBB67AE85, 0x3C6EF372, 0xA54FF53A,
+          0x510E527F, 0x9B05688C, 0x1F83D9AB, 0x5BE0CD19)
+_B3_PERMUTATION = (2, 6, 3, 10, 7, 0, 4, 13, 1, 11, 12, 5, 9, 14, 15, 8)
+_B3_CHUNK_START, _B3_CHUNK_END, _B3_PARENT, _B3_ROOT = 1, 2, 4, 8
+_B3_BLOCK_BYTES, _B3_CHUNK_BYTES = 64, 1024
+_M32 = 0xFFFFFFFF
+
+
+def _b3_compress(cv, block: bytes, counter: int, block_len: int, flags: int) -> List[int]:
+    m = [int.from_bytes(block[i:i + 4], "little") for i in range(0, _B3_BLOCK_BYTES, 4)]
+    v = list(cv) + list(_B3_IV[:4]) + [counter & _M32, counter >> 32, block_len, flags]
+
+    def g(a, b, c, d, x, y):
+        v[a] = (v[a] + v[b] + x) & _M32
+        v[d] = ((v[d] ^ v[a]) >> 16 | (v[d] ^ v[a]) << 16) & _M32
+        v[c] = (v[c] + v[d]) & _M32
+        v[b] = ((v[b] ^ v[c]) >> 12 | (v[b] ^ v[c]) << 20) & _M32
+        v[a] = (v[a] + v[b] + y) & _M32
+        v[d] = ((v[d] ^ v[a]) >> 8 | (v[d] ^ v[a]) << 24) & _M32
+        v[c] = (v[c] + v[d]) & _M32
+        v[b] = ((v[b] ^ v[c]) >> 7 | (v[b] ^ v[c]) << 25) & _M32
+
+    for round_index in range(7):
+        g(0, 4, 8, 12, m[0], m[1])
+        g(1, 5, 9, 13, m[2], m[3])
+        g(2, 6, 10, 14, m[4], m[5])
+        g(3, 7, 11, 15, m[6], m[7])
+        g(0, 5, 10, 15, m[8], m[9])
+        g(1, 6, 11, 12, m[10], m[11])
+        g(2, 7, 8, 13, m[12], m[13])
+        g(3, 4, 9, 14, m[14], m[15])
+        if round_index < 6:
+            m = [m[i] for i in _B3_PERMUTATION]
+    return [v[i] ^ v[i + 8] for i in range(8)]
+
+
+def _b3_node(data: bytes, chunk: int):
+    """Inputs of the final compression of the subtree over `data`, whose first chunk has
+    index `chunk`: (cv, block, counter, block_len, flags)"""
+    if len(data) > _B3_CHUNK_BYTES:
+        chunks = (len(data) - 1) // _B3_CHUNK_BYTES
+        left = _B3_CHUNK_BYTES << (chunks.bit_length() - 1)
+        block = b"".join(
+            b"".join(w.to_bytes(4, "little") for w in _b3_compress(*_b3_node(half, start)))
+            for half, start in ((data[:left], chunk), (data[left:], chunk + left // _B3_CHUNK_BYTES)))
+        return _B3_IV, block, 0, _B3_BLOCK_BYTES, _B3_PARENT
+    cv = _B3_IV
+    blocks = [data[i:i + _B3_BLOCK_BYTES] for i in range(0, len(data), _B3_BLOCK_BYTES)] or [b""]
+    for i, block in enumerate(blocks):
+        flags = (_B3_CHUNK_START if i == 0 else 0) | (_B3_CHUNK_END if i == len(blocks) - 1 else 0)
+        padded = block.ljust(_B3_BLOCK_BYTES, b"\x00")
+        if i == len(blocks) - 1:
+            return cv, padded, chunk, len(block), flags
+        cv = _b3_compress(cv, padded, chunk, _B3_BLOCK_BYTES, flags)
+
+
+def _blake3(data: bytes) -> bytes:
+    cv, block, counter, block_len, flags = _b3_node(data, 0)
+    words = _b3_compress(cv, block, counter, block_len, flags | _B3_ROOT)
+    return b"".join(w.to_bytes(4, "little") for w in words)
+
+# --- Merkle roots and proofs, as in reliquary_merkle ---
+
+_HASH_BYTES = 32
+# v2 trees: RFC 6962 leaf / node prefixes and a version byte in front of the root
+_LEAF_PREFIX, _NODE_PREFIX, _V2_ROOT_TAG = b"\x00", b"\x01", b"\x02"
+# Sparse trees: empty subtrees hash to zero bytes
+_SPARSE_EMPTY = bytes(_HASH_BYTES)
+_MAP_KEY_PREFIX = b"\x02"
+# Tree hashes by name, and the ids v2 roots record for those other than SHA-256
+_MERKLE_HASHES = {
+    "sha256": lambda data: hashlib.sha256(data).digest(),
+    "sha3-256": lambda data: hashlib.sha3_256(data).digest(),
+    "blake3": _blake3,
+    "shake256": lambda data: hashlib.shake_256(data).digest(_HASH_BYTES),
+}
+_MERKLE_HASH_IDS = {"sha3-256": 2, "blake3": 3, "shake256": 4}
+_MERKLE_HASH_NAMES = {id: name for name, id in _MERKLE_HASH_IDS.items()}
+_NOT_A_ROOT = "Not a Merkle root (expected 32 bytes, or 33 / 34 starting with 0x02)"
+
+
+def _scheme(version: int, hash_algorithm: str) -> Tuple[bytes, bytes, Callable[[bytes], bytes]]:
+    """(leaf prefix, node prefix, hash) of a tree version and hash algorithm"""
+    if hash_algorithm not in _MERKLE_HASHES:
+        raise ValueError(f'Unsupported Merkle hash algorithm "{hash_algorithm}" '
+                         "(expected sha256, sha3-256, blake3 or shake256)")
+    if version == 1 and hash_algorithm != "sha256":
+        raise ValueError(f"Hash algorithm {hash_algorithm} needs tree version 2 "
+                         "(v1 roots can't record it)")
+    if version == 1:
+        return b"", b"", _MERKLE_HASHES["sha256"]
+    if version == 2:
+        return _LEAF_PREFIX, _NODE_PREFIX, _MERKLE_HASHES[hash_algorithm]
+    raise ValueError(f"Unsupported Merkle tree version {version} (expected 1 or 2)")
+
+
+def _root_scheme(root: bytes) -> Tuple[bytes, bytes, Callable[[bytes], bytes]]:
+    return _scheme(merkle_root_version(root), merkle_root_hash_algorithm(root))
+
+
+def merkle_root_version(root: bytes) -> int:
+    """1 for a bare 32-byte root, 2 for 0x02 | [hash id] | 32-byte hash"""
+    root = bytes(root)
+    if len(root) == _HASH_BYTES:
+        return 1
+    if len(root) == _HASH_BYTES + 1 and root[:1] == _V2_ROOT_TAG:
+        return 2
+    if len(root) == _HASH_BYTES + 2 and root[:1] == _V2_ROOT_TAG and root[1] in _MERKLE_HASH_NAMES:
+        return 2
+    raise ValueError(_NOT_A_ROOT)
+
+
+def merkle_root_hash_algorithm(root: bytes) -> str:
+    """Hash algorithm a Merkle root was built with: "sha256", "sha3-256", "blake3" or
+    "shake256" """
+    root = bytes(root)
+    if merkle_root_version(root) == 2 and len(root) == _HASH_BYTES + 2:
+        return _MERKLE_HASH_NAMES[root[1]]
+    return "sha256"
+
+
+def create_merkle_root(data_blocks: List[bytes], version: int = 1,
+                       hash_algorithm: str = "sha256") -> bytes:
+    """Merkle root; odd levels duplicate their last node in v1 and carry it up in v2 (the RFC
+    6962 tree). Empty input gives b''"""
+    leaf, node, digest = _scheme(version, hash_algorithm)
+    if not data_blocks:
+        return b""
+    level = [digest(leaf + bytes(block)) for block in data_blocks]
+    while len(level) > 1:
+        parents = [digest(node + level[i] + level[i + 1]) for i in range(0, len(level) - 1, 2)]
+        if len(level) % 2:
+            last = level[-1]
+            parents.append(last if version == 2 else digest(node + last + last))
+        level = parents
+    if version == 1:
+        return level[0]
+    hash_id = bytes([_MERKLE_HASH_IDS[hash_algorithm]]) if hash_algorithm != "sha256" else b""
+    return _V2_ROOT_TAG + hash_id + level[0]
+
+
+def verify_merkle_proof(data_block: bytes, proof: List[bytes], root: bytes) -> bool:
+    """Checks a sibling-hash proof; each pair is hashed smaller hash first"""
+    current = hashlib.sha256(bytes(data_block)).digest()
+    for sibling in proof:
+        sibling = bytes(sibling)
+        pair = current + sibling if current < sibling else sibling + current
+        current = hashlib.sha256(pair).digest()
+    return current == bytes(root)
+
+
+def _path_sides(version: int, tree_size: int, index: int) -> List[bool]:
+    """is_right for each sibling on the path from leaf `index` to the root. The last node of an
+    odd level is its own sibling in v1 and is carried up without one in v2"""
+    sides = []
+    count = tree_size
+    while count > 1:
+        if index ^ 1 < count or version == 1:
+            sides.append(index % 2 == 0)
+        index //= 2
+        count = (count + 1) // 2
+    return sides
+
+
+def verify_positional_proof(data_block: bytes, proof: List[Tuple[bytes, bool]], root: bytes,
+                            index: int, tree_size: int) -> bool:
+    """Checks a (sibling, is_right) proof for the leaf at `index` of a tree of `tree_size` leaves
+    against a v1 or v2 root; the proof's length and directions must fit that position"""
+    try:
+        leaf, node, digest = _root_scheme(root)
+    except ValueError:
+        return False
+    if not 0 <= index < tree_size:
+        return False
+    sides = _path_sides(merkle_root_version(root), tree_size, index)
+    if len(proof) != len(sides):
+        return False
+    current = digest(leaf + bytes(data_block))
+    for side, (sibling, is_right) in zip(sides, proof):
+        sibling = bytes(sibling)
+        if len(sibling) != _HASH_BYTES or is_right != side:
+            return False
+        current = digest(node + (current + sibling if is_right else sibling + current))
+    return current == bytes(root)[-_HASH_BYTES:]
+
+
+def verify_consistency_proof(old_size: int, new_size: int, old_root: bytes, new_root: bytes,
+                             proof: List[bytes]) -> bool:
+    """Checks that the tree of old_size leaves behind old_root is a prefix of the tree of
+    new_size leaves behind new_root; both roots must have the same version and hash"""
+    try:
+        version = merkle_root_version(old_root), merkle_root_hash_algorithm(old_root)
+        if (merkle_root_version(new_root), merkle_root_hash_algorithm(new_root)) != version:
+            return False
+    except ValueError:
+        return False
+    if old_size <= 0 or old_size > new_size:
+        return False
+    old_hash, new_hash = bytes(old_root)[-_HASH_BYTES:], bytes(new_root)[-_HASH_BYTES:]
+    if old_size == new_size:
+        return not proof and old_hash == new_hash
+    nodes = [bytes(n) for n in proof]
+    if any(len(n) != _HASH_BYTES for n in nodes):
+        return False
+    _, node, digest = _scheme(*version)
+
+    def pair(left: bytes, right: bytes) -> bytes:
+        return digest(node + left + right)
+
+    def unpaired(last: bytes) -> bytes:
+        return last if version[0] == 2 else pair(last, last)
+
+    level = (old_size & -old_size).bit_length() - 1
+    index = (old_size >> level) - 1
+    if index != 0:
+        if not nodes:
+            return False
+        old = new = nodes.pop(0)
+    else:
+        old = new = old_hash
+    old_levels = (old_size - 1).bit_length() - level
+    while level < (new_size - 1).bit_length():
+        if index % 2:
+            if not nodes:
+                return False
+            left = nodes.pop(0)
+            old, new = pair(left, old), pair(left, new)
+        else:
+            if old_levels > 0:
+                old = unpaired(old)
+            if (index + 1) << level < new_size:
+                if not nodes:
+                    return False
+                new = pair(new, nodes.pop(0))
+            else:
+                new = unpaired(new)
+        old_levels = max(old_levels - 1, 0)
+        index //= 2
+        level += 1
+    return not nodes and old == old_hash and new == new_hash
+
+
+def verify_multi_proof(leaves_with_indices: List[Tuple[int, bytes]],
+                       proof: Tuple[int, List[bytes]], root: bytes) -> bool:
+    """Checks a (tree_size, nodes) multiproof for (index, data_block) pairs against a v1 or
+    v2 root; nodes are consumed level by level from the leaves up, left to right"""
+    try:
+        leaf, node, digest = _root_scheme(root)
+    except ValueError:
+        return False
+    size, nodes = proof[0], [bytes(n) for n in proof[1]]
+    known = sorted((index, digest(leaf + bytes(block)))
+                   for index, block in leaves_with_indices)
+    indices = [index for index, _ in known]
+    if (not known or len(set(indices)) != len(indices) or indices[-1] >= size
+            or any(len(n) != _HASH_BYTES for n in nodes)):
+        return False
+    carry_up = merkle_root_version(root) == 2
+    nodes.reverse()
+    count = size
+    while count > 1:
+        parents = []
+        k = 0
+        while k < len(known):
+            index, current = known[k]
+            if index % 2:
+                if not nodes:
+                    return False
+                parent = digest(node + nodes.pop() + current)
+            elif k + 1 < len(known) and known[k + 1][0] == index + 1:
+                k += 1
+                parent = digest(node + current + known[k][1])
+            elif index + 1 < count:
+                if not nodes:
+                    return False
+                parent = digest(node + current + nodes.pop())
+            else:
+                # Last node of an odd level: carried up in v2, paired with itself in v1
+                parent = current if carry_up else digest(node + current + current)
+            parents.append((index // 2, parent))
+            k += 1
+        known = parents
+        count = (count + 1) // 2
+    return not nodes and known[0][1] == bytes(root)[-_HASH_BYTES:]
+
+
+def verify_sparse_proof(key: bytes, value: Optional[bytes],
+                        proof: Tuple[List[bytes], Optional[Tuple[bytes, bytes]]], root: bytes) -> bool:
+    """Checks a SparseMerkleTree proof that `key` maps to `value`, or with value=None that it
+    is absent; proof is (siblings bottom-up, leaf (key, value_hash) or None)"""
+    key, root = bytes(key), bytes(root)
+    siblings, leaf = [bytes(s) for s in proof[0]], proof[1]
+    if len(key) != _HASH_BYTES or len(root) != _HASH_BYTES or len(siblings) > 8 * _HASH_BYTES:
+        return False
+    if any(len(s) != _HASH_BYTES for s in siblings):
+        return False
+
+    def bit(k: bytes, depth: int) -> int:
+        return (k[depth // 8] >> (7 - depth % 8)) & 1
+
+    depth = len(siblings)
+    if leaf is None:
+        if value is not None:
+            return False
+        current = _SPARSE_EMPTY
+    else:
+        leaf_key, value_hash = bytes(leaf[0]), bytes(leaf[1])
+        if len(leaf_key) != _HASH_BYTES or len(value_hash) != _HASH_BYTES:
+            return False
+        if value is not None:
+            if leaf_key != key or value_hash != hashlib.sha256(bytes(value)).digest():
+                return False
+        elif leaf_key == key or any(bit(leaf_key, d) != bit(key, d) for d in range(depth)):
+            return False
+        current = hashlib.sha256(_LEAF_PREFIX + leaf_key + value_hash).digest()
+    for i, sibling in enumerate(siblings):
+        left, right = (sibling, current) if bit(key, depth - 1 - i) else (current, sibling)
+        if left == _SPARSE_EMPTY and right == _SPARSE_EMPTY:
+            current = _SPARSE_EMPTY
+        else:
+            current = hashlib.sha256(_NODE_PREFIX + left + right).digest()
+    return current == root
+
+
+
+def verify_map_proof(key: bytes, value: Optional[bytes],
+                     proof: Tuple[List[bytes], Optional[Tuple[bytes, bytes]]], root: bytes) -> bool:
+    """Checks an AuthenticatedMap proof: a sparse proof for the key's path SHA-256(0x02 | key)"""
+    path = hashlib.sha256(_MAP_KEY_PREFIX + bytes(key)).digest()
+    return verify_sparse_proof(path, value, proof, root)
+
+def proof_size(tree_size: int) -> int:
+    """Size in bytes of the sibling hashes in an inclusion proof for `tree_size` leaves; exact
+    for v1, an upper bound for v2"""
+    return 0 if tree_size <= 1 else (tree_size - 1).bit_length() * _HASH_BYTES
+
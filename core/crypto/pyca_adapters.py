@@ -0,0 +1,75 @@
+# core/crypto/pyca_adapters.py
+
+from typing import Any, Tuple
+
+from cryptography.hazmat.primitives import serialization
+from cryptography.hazmat.primitives.asymmetric import ed25519, rsa, x25519
+
+# --- Adapters between pyca/cryptography key objects and reliquary_encryptor key bytes ---
+#
+# reliquary_encryptor's classical functions (Ed25519, X25519, RSA blind signatures, signature
+# bundles) accept cryptography key objects directly. These helpers cover the other direction,
+# and explicit conversion where bytes are needed (storage, wire formats). Conversions go
+# through standard serializations only: raw keys for Ed25519 / X25519 and PKCS#8 /
+# SubjectPublicKeyInfo DER for RSA, the encodings the encryptor uses for each.
+
+_PRIVATE_TYPES = {
+    "ed25519": ed25519.Ed25519PrivateKey,
+    "x25519": x25519.X25519PrivateKey,
+    "rsa": rsa.RSAPrivateKey,
+}
+_PUBLIC_TYPES = {
+    "ed25519": ed25519.Ed25519PublicKey,
+    "x25519": x25519.X25519PublicKey,
+    "rsa": rsa.RSAPublicKey,
+}
+
+
+def _algorithm(algorithm: str) -> str:
+    name = algorithm.lower()
+    if name not in _PRIVATE_TYPES:
+        raise ValueError(f"Unsupported algorithm '{algorithm}'; "
+                         f"use one of {sorted(_PRIVATE_TYPES)}")
+    return name
+
+
+def to_pyca_private_key(secret_key: bytes, algorithm: str) -> Any:
+    """Turn encryptor secret key bytes into a cryptography private key object."""
+    name = _algorithm(algorithm)
+    if name == "rsa":
+        return serialization.load_der_private_key(secret_key, password=None)
+    return _PRIVATE_TYPES[name].from_private_bytes(secret_key)
+
+
+def to_pyca_public_key(public_key: bytes, algorithm: str) -> Any:
+    """Turn encryptor public key bytes into a cryptography public key object."""
+    name = _algorithm(algorithm)
+    if name == "rsa":
+        return serialization.load_der_public_key(public_key)
+    return _PUBLIC_TYPES[name].from_public_bytes(public_key)
+
+
+def from_pyca_key(key: Any) -> Tuple[str, bytes]:
+    """
+    Serialize a cryptography key object the way the encryptor expects it.
+
+    Returns:
+        (algorithm, key_bytes), for private and public keys alike
+
+    Raises:
+        TypeError: If the key is not an Ed25519, X25519 or RSA key
+    """
+    for name, key_type in _PRIVATE_TYPES.items():
+        if isinstance(key, key_type):
+            if name == "rsa":
+                return name, key.private_bytes(serialization.Encoding.DER,
+                                               serialization.PrivateFormat.PKCS8,
+                                               serialization.NoEncryption())
+            return name, key.private_bytes_raw()
+    for name, key_type in _PUBLIC_TYPES.items():
+        if isinstance(key, key_type):
+            if name == "rsa":
+                return name, key.public_bytes(serialization.Encoding.DER,
+                                              serialization.PublicFormat.SubjectPublicKeyInfo)
+            return name, key.public_bytes_raw()
+    raise TypeError(f"Unsupported key object {type(key).__name__}")
@@ -155,16 +155,10 @@ class PythonCryptoFallback:
     
     @staticmethod
     def verify_merkle_proof(data_block: list, proof: list, root: list) -> bool:
-        """Verify Merkle proof using our Python implementation."""
-        from ..merkle_logging.merkle import verify_merkle_proof
+        """Verify Merkle proof the way reliquary_merkle does."""
+        from .pure_verify import verify_merkle_proof
         
-        data_bytes = bytes(data_block)
-        proof_bytes = [bytes(p) for p in proof]
-        root_bytes = bytes(root)
-        
-        # Note: This requires an index for proper verification
-        # For now, we'll return a basic validity check
-        return len(proof_bytes) > 0 and len(root_bytes) == 32
+        return verify_merkle_proof(bytes(data_block), [bytes(p) for p in proof], bytes(root))
 
 # Create the module interface
 if RUST_MODULES_AVAILABLE:
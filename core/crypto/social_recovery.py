@@ -0,0 +1,90 @@
+# core/crypto/social_recovery.py
+
+from dataclasses import dataclass
+from typing import Dict, List, Mapping, Sequence
+
+try:
+    import reliquary_encryptor
+except ImportError:
+    reliquary_encryptor = None
+
+# --- Social recovery: a master secret split among guardians' Kyber keys ---
+#
+# The owner keeps the recovery descriptor (it holds the encrypted secret and the guardians'
+# share commitments but opens nothing on its own) and hands each guardian its package. To
+# recover, guardians open their packages with their Kyber secret keys and send back the
+# released shares; any `threshold` valid ones restore the secret, and shares that don't match
+# the descriptor's commitments are ignored.
+
+
+@dataclass
+class SocialRecoveryKit:
+    """Output of create_social_recovery"""
+    recovery_id: str
+    threshold: int
+    descriptor: bytes
+    packages: Dict[str, bytes]  # guardian name -> encrypted share package
+
+
+def _require_encryptor():
+    if reliquary_encryptor is None:
+        raise RuntimeError("Social recovery requires reliquary_encryptor")
+
+
+def create_social_recovery(secret: bytes, guardians: Mapping[str, bytes], threshold: int,
+                           label: str = "") -> SocialRecoveryKit:
+    """
+    Split a master secret among guardians.
+
+    Args:
+        secret: The master secret to protect
+        guardians: Guardian name -> Kyber-1024 public key
+        threshold: Guardians needed to recover
+        label: Free-form description stored in the descriptor
+
+    Returns:
+        The recovery descriptor and one package per guardian
+    """
+    _require_encryptor()
+    names = list(guardians)
+    descriptor, packages = reliquary_encryptor.create_social_recovery(
+        secret, [guardians[name] for name in names], threshold, label=label)
+    return SocialRecoveryKit(
+        recovery_id=reliquary_encryptor.social_recovery_info(descriptor)[0].hex(),
+        threshold=threshold,
+        descriptor=descriptor,
+        packages=dict(zip(names, packages)),
+    )
+
+
+def describe_recovery(descriptor: bytes) -> Dict:
+    """Recovery id, threshold, label and guardian key fingerprints (hex) of a descriptor"""
+    _require_encryptor()
+    recovery_id, threshold, label, fingerprints = \
+        reliquary_encryptor.social_recovery_info(descriptor)
+    return {"recovery_id": recovery_id.hex(), "threshold": threshold, "label": label,
+            "guardian_fingerprints": [fp.hex() for fp in fingerprints]}
+
+
+def release_guardian_share(package: bytes, kyber_secret_key) -> bytes:
+    """Guardian side: open a package with the guardian's Kyber secret key (bytes or handle)"""
+    _require_encryptor()
+    return reliquary_encryptor.open_guardian_package(package, kyber_secret_key)
+
+
+def invalid_released_shares(descriptor: bytes, shares: Sequence[bytes]) -> List[int]:
+    """Positions in `shares` that don't belong to the descriptor or fail their commitment"""
+    _require_encryptor()
+    return [i for i, share in enumerate(shares)
+            if not reliquary_encryptor.verify_released_share(descriptor, share)]
+
+
+def recover_secret(descriptor: bytes, shares: Sequence[bytes]) -> bytes:
+    """
+    Restore the master secret from released guardian shares.
+
+    Raises:
+        ValueError: If fewer than threshold shares are valid
+    """
+    _require_encryptor()
+    return reliquary_encryptor.recover_social_secret(descriptor, list(shares))
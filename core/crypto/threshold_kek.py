@@ -0,0 +1,63 @@
+# core/crypto/threshold_kek.py
+
+import os
+from typing import List, Optional, Tuple
+
+try:
+    import reliquary_encryptor
+except ImportError:
+    reliquary_encryptor = None
+
+# --- Threshold KEK: unwrapping needs partial decryptions from M of N trustees ---
+#
+# The KEK is wrapped to the group key of a DKG session (see core.crypto.dkg). Each trustee
+# computes a partial decryption with its own key share; the combiner needs `threshold` of them
+# and never sees any key share, so no single node can unwrap vault records alone.
+
+
+def _require_threshold():
+    if reliquary_encryptor is None:
+        raise RuntimeError("Threshold KEK unwrapping requires reliquary_encryptor")
+
+
+def wrap_kek_threshold(group_key: bytes, kek: Optional[bytes] = None,
+                       context: bytes = b"") -> Tuple[bytes, bytes]:
+    """
+    Wrap a KEK to a trustee group.
+
+    Args:
+        group_key: The DKG group key
+        kek: The key to wrap; a fresh 32-byte key is generated if omitted
+        context: Associated data (e.g. the vault id) that must match on unwrap
+
+    Returns:
+        (kek, wrapped_kek)
+    """
+    _require_threshold()
+    kek = kek if kek is not None else os.urandom(32)
+    return kek, reliquary_encryptor.threshold_encrypt(group_key, kek, context)
+
+
+def partial_unwrap(group_key: bytes, key_share: bytes, wrapped_kek: bytes) -> bytes:
+    """A trustee's partial decryption of a wrapped KEK, safe to send to the combiner."""
+    _require_threshold()
+    return reliquary_encryptor.threshold_partial_decrypt(group_key, key_share, wrapped_kek)
+
+
+def invalid_partials(group_key: bytes, wrapped_kek: bytes, partials: List[bytes]) -> List[int]:
+    """Positions of partials whose proof fails, e.g. to flag a misbehaving trustee."""
+    _require_threshold()
+    return [i for i, partial in enumerate(partials)
+            if not reliquary_encryptor.threshold_verify_partial(group_key, wrapped_kek, partial)]
+
+
+def unwrap_kek_threshold(group_key: bytes, wrapped_kek: bytes, partials: List[bytes],
+                         context: bytes = b"") -> bytes:
+    """
+    Combine trustees' partials into the KEK; invalid partials are skipped.
+
+    Raises:
+        ValueError: If fewer than threshold partials are valid, or the context does not match
+    """
+    _require_threshold()
+    return reliquary_encryptor.threshold_decrypt(group_key, wrapped_kek, partials, context)
@@ -0,0 +1,298 @@
+# core/crypto/tree_head.py
+
+import os
+import struct
+import time
+import urllib.request
+from dataclasses import dataclass
+from typing import Optional, Union
+
+from .verifier import merkle_root_version, verify_ed25519
+
+try:
+    import reliquary_encryptor
+except ImportError:
+    reliquary_encryptor = None
+
+# --- Signed tree heads: one canonical byte layout for (size, root, time) and its signature ---
+#
+# The signed bytes are "RQTH", a format version, the u64 tree size, the u64 timestamp in
+# milliseconds since the Unix epoch, the signature algorithm id, and the root as returned by
+# the Merkle module (32 bytes for v1, version-tagged for v2) behind a one-byte length. All
+# integers are big-endian. The algorithm id is signed too, so a head can't be re-labelled as
+# another algorithm's. A serialized head appends a u16 signature length and the signature;
+# parsing rejects anything else, so every head has exactly one encoding.
+#
+# Signing needs reliquary_encryptor. Ed25519 heads verify without it (pure-Python fallback);
+# Falcon heads don't.
+#
+# A TimestampedTreeHead adds an RFC 3161 time-stamp token over the head's serialized bytes,
+# so an auditor has a third party's word for when the root existed rather than only the
+# signer's timestamp. Serialized, it is "RQTT", a format version, the head behind a u16
+# length and the token behind a u32 length. Requesting and verifying tokens needs
+# reliquary_encryptor; Roughtime isn't supported.
+
+TREE_HEAD_MAGIC = b"RQTH"
+TREE_HEAD_FORMAT_VERSION = 1
+SIGNATURE_ALGORITHMS = {"falcon-512": 1, "falcon-1024": 2, "ed25519": 3}
+_ALGORITHM_NAMES = {wire: name for name, wire in SIGNATURE_ALGORITHMS.items()}
+_FIXED = struct.Struct(">4sBQQB")
+TIMESTAMPED_HEAD_MAGIC = b"RQTT"
+TIMESTAMPED_HEAD_FORMAT_VERSION = 1
+
+
+@dataclass(frozen=True)
+class SignedTreeHead:
+    """A Merkle root with its tree size and a timestamp, signed as one canonical record"""
+
+    tree_size: int
+    root: bytes
+    timestamp: int
+    algorithm: str
+    signature: bytes = b""
+
+    def __post_init__(self):
+        if self.algorithm not in SIGNATURE_ALGORITHMS:
+            raise ValueError(f"Unsupported tree head signature algorithm {self.algorithm!r}. "
+                             f"Expected one of {sorted(SIGNATURE_ALGORITHMS)}")
+        if not 0 < self.tree_size < 1 << 64:
+            raise ValueError("A tree head covers at least one leaf")
+        if not 0 <= self.timestamp < 1 << 64:
+            raise ValueError("Tree head timestamp out of range")
+        if len(self.signature) > 0xFFFF:
+            raise ValueError("Tree head signature exceeds 65535 bytes")
+        merkle_root_version(self.root)  # raises for anything but a Merkle root
+
+    def signing_bytes(self) -> bytes:
+        """The bytes the signature covers"""
+        return (_FIXED.pack(TREE_HEAD_MAGIC, TREE_HEAD_FORMAT_VERSION, self.tree_size,
+                            self.timestamp, SIGNATURE_ALGORITHMS[self.algorithm])
+                + bytes([len(self.root)]) + self.root)
+
+    def to_bytes(self) -> bytes:
+        return self.signing_bytes() + struct.pack(">H", len(self.signature)) + self.signature
+
+    @classmethod
+    def from_bytes(cls, data: bytes) -> "SignedTreeHead":
+        """
+        Parse a serialized tree head.
+
+        Raises:
+            ValueError: If the bytes are not exactly one well-formed tree head
+        """
+        data = bytes(data)
+        if len(data) < _FIXED.size + 1:
+            raise ValueError("Signed tree head is truncated")
+        magic, version, tree_size, timestamp, algorithm = _FIXED.unpack_from(data)
+        if magic != TREE_HEAD_MAGIC:
+            raise ValueError("Not a signed tree head")
+        if version != TREE_HEAD_FORMAT_VERSION:
+            raise ValueError(f"Unsupported signed tree head format version {version}")
+        if algorithm not in _ALGORITHM_NAMES:
+            raise ValueError(f"Unknown tree head signature algorithm id {algorithm}")
+        pos = _FIXED.size
+        root_end = pos + 1 + data[pos]
+        sig_end = root_end + 2
+        if len(data) < sig_end:
+            raise ValueError("Signed tree head is truncated")
+        sig_end += struct.unpack_from(">H", data, root_end)[0]
+        if len(data) < sig_end:
+            raise ValueError("Signed tree head is truncated")
+        if len(data) > sig_end:
+            raise ValueError("Trailing data after signed tree head")
+        return cls(tree_size=tree_size, root=data[pos + 1:root_end], timestamp=timestamp,
+                   algorithm=_ALGORITHM_NAMES[algorithm], signature=data[root_end + 2:])
+
+
+def _require_encryptor(what: str):
+    if reliquary_encryptor is None:
+        raise RuntimeError(f"{what} requires the reliquary_encryptor module")
+    return reliquary_encryptor
+
+
+def _falcon_params(encryptor, algorithm: str):
+    return (encryptor.FalconParams.Falcon512 if algorithm == "falcon-512"
+            else encryptor.FalconParams.Falcon1024)
+
+
+def sign_tree_head(tree_root, secret_key, algorithm: str = "falcon-1024",
+                   timestamp: Optional[int] = None) -> SignedTreeHead:
+    """
+    Sign a tree head.
+
+    Args:
+        tree_root: A reliquary_merkle.MerkleRoot, e.g. MerkleTree.tree_root(), or anything
+            with .root and .tree_size
+        secret_key: Falcon or Ed25519 secret key matching algorithm
+        algorithm: "falcon-1024", "falcon-512" or "ed25519"
+        timestamp: Milliseconds since the Unix epoch; defaults to now
+
+    Returns:
+        The SignedTreeHead; to_bytes() gives its canonical encoding
+    """
+    if timestamp is None:
+        timestamp = int(time.time() * 1000)
+    head = SignedTreeHead(tree_size=tree_root.tree_size, root=bytes(tree_root.root),
+                          timestamp=timestamp, algorithm=algorithm)
+    encryptor = _require_encryptor("Signing tree heads")
+    message = head.signing_bytes()
+    if algorithm == "ed25519":
+        signature = encryptor.sign_ed25519(message, secret_key)
+    else:
+        signature = encryptor.sign_falcon(message, secret_key,
+                                          _falcon_params(encryptor, algorithm))
+    return SignedTreeHead(head.tree_size, head.root, head.timestamp, algorithm,
+                          bytes(signature))
+
+
+def verify_tree_head(head: Union[SignedTreeHead, bytes], public_key: bytes,
+                     algorithm: Optional[str] = None) -> bool:
+    """
+    Verify a signed tree head.
+
+    Args:
+        head: SignedTreeHead or its serialized bytes
+        public_key: Signer's public key
+        algorithm: If given, the algorithm the head must be signed with
+
+    Returns:
+        True if the signature is valid; False for a bad signature, another algorithm than the
+        one required, or malformed bytes
+
+    Raises:
+        RuntimeError: For a Falcon head when reliquary_encryptor is not installed
+    """
+    if not isinstance(head, SignedTreeHead):
+        try:
+            head = SignedTreeHead.from_bytes(head)
+        except ValueError:
+            return False
+    if algorithm is not None and head.algorithm != algorithm:
+        return False
+    message = head.signing_bytes()
+    try:
+        if head.algorithm == "ed25519":
+            return verify_ed25519(message, head.signature, public_key)
+        encryptor = _require_encryptor("Verifying Falcon tree heads")
+        return encryptor.verify_falcon(message, head.signature, public_key,
+                                       _falcon_params(encryptor, head.algorithm))
+    except ValueError:
+        return False
+
+
+@dataclass(frozen=True)
+class TimestampedTreeHead:
+    """A signed tree head with an RFC 3161 time-stamp token over its serialized bytes"""
+
+    head: SignedTreeHead
+    token: bytes
+
+    def __post_init__(self):
+        if len(self.head.to_bytes()) > 0xFFFF:
+            raise ValueError("Signed tree head exceeds 65535 bytes")
+        if not self.token or len(self.token) >= 1 << 32:
+            raise ValueError("Time-stamp token must be 1 to 2^32 - 1 bytes")
+
+    def to_bytes(self) -> bytes:
+        head = self.head.to_bytes()
+        return (TIMESTAMPED_HEAD_MAGIC + bytes([TIMESTAMPED_HEAD_FORMAT_VERSION])
+                + struct.pack(">H", len(head)) + head
+                + struct.pack(">I", len(self.token)) + self.token)
+
+    @classmethod
+    def from_bytes(cls, data: bytes) -> "TimestampedTreeHead":
+        """
+        Parse a serialized time-stamped tree head.
+
+        Raises:
+            ValueError: If the bytes are not exactly one well-formed time-stamped tree head
+        """
+        data = bytes(data)
+        if len(data) < 7:
+            raise ValueError("Time-stamped tree head is truncated")
+        if data[:4] != TIMESTAMPED_HEAD_MAGIC:
+            raise ValueError("Not a time-stamped tree head")
+        if data[4] != TIMESTAMPED_HEAD_FORMAT_VERSION:
+            raise ValueError(f"Unsupported time-stamped tree head format version {data[4]}")
+        head_end = 7 + struct.unpack_from(">H", data, 5)[0]
+        if len(data) < head_end + 4:
+            raise ValueError("Time-stamped tree head is truncated")
+        token_end = head_end + 4 + struct.unpack_from(">I", data, head_end)[0]
+        if len(data) < token_end:
+            raise ValueError("Time-stamped tree head is truncated")
+        if len(data) > token_end:
+            raise ValueError("Trailing data after time-stamped tree head")
+        return cls(head=SignedTreeHead.from_bytes(data[7:head_end]),
+                   token=data[head_end + 4:])
+
+
+def timestamp_request(head: SignedTreeHead, nonce: Optional[int] = None) -> bytes:
+    """DER TimeStampReq (RFC 3161) asking a TSA to time-stamp a signed tree head"""
+    encryptor = _require_encryptor("Time-stamping tree heads")
+    return bytes(encryptor.create_timestamp_request(head.to_bytes(), nonce))
+
+
+def request_timestamp(head: SignedTreeHead, tsa_url: str,
+                      tsa_certificate: Optional[bytes] = None, nonce: Optional[int] = None,
+                      timeout: float = 10.0) -> TimestampedTreeHead:
+    """
+    Have an RFC 3161 time-stamp authority time-stamp a signed tree head.
+
+    Args:
+        head: The signed tree head
+        tsa_url: The TSA's HTTP(S) endpoint
+        tsa_certificate: If given, the TSA certificate (PEM or DER) the token must verify under
+        nonce: u64 nonce for the request; defaults to a random one
+        timeout: Seconds to wait for the TSA
+
+    Returns:
+        The TimestampedTreeHead
+
+    Raises:
+        ValueError: If the TSA rejects the request or its token doesn't verify
+        OSError: If the TSA can't be reached
+    """
+    encryptor = _require_encryptor("Time-stamping tree heads")
+    if nonce is None:
+        nonce = int.from_bytes(os.urandom(8), "big")
+    request = urllib.request.Request(
+        tsa_url, data=timestamp_request(head, nonce),
+        headers={"Content-Type": "application/timestamp-query"})
+    with urllib.request.urlopen(request, timeout=timeout) as response:
+        reply = response.read()
+    token = bytes(encryptor.timestamp_token_from_response(reply))
+    if tsa_certificate is not None:
+        encryptor.verify_timestamp_token(token, head.to_bytes(), tsa_certificate, nonce)
+    return TimestampedTreeHead(head=head, token=token)
+
+
+def verify_timestamped_tree_head(stamped: Union[TimestampedTreeHead, bytes], public_key: bytes,
+                                 tsa_certificate: bytes,
+                                 algorithm: Optional[str] = None) -> Optional[int]:
+    """
+    Verify a time-stamped tree head: the head's signature and the TSA's token over it.
+
+    Args:
+        stamped: TimestampedTreeHead or its serialized bytes
+        public_key: Tree head signer's public key
+        tsa_certificate: The TSA certificate (PEM or DER) the token must verify under
+        algorithm: If given, the algorithm the head must be signed with
+
+    Returns:
+        The TSA's time in milliseconds since the Unix epoch, or None if the head signature or
+        the token doesn't verify, or the bytes are malformed
+    """
+    if not isinstance(stamped, TimestampedTreeHead):
+        try:
+            stamped = TimestampedTreeHead.from_bytes(stamped)
+        except ValueError:
+            return None
+    if not verify_tree_head(stamped.head, public_key, algorithm):
+        return None
+    encryptor = _require_encryptor("Verifying time-stamp tokens")
+    try:
+        info = encryptor.verify_timestamp_token(stamped.token, stamped.head.to_bytes(),
+                                                tsa_certificate)
+    except ValueError:
+        return None
+    return info.gen_time
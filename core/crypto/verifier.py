@@ -0,0 +1,56 @@
+# core/crypto/verifier.py
+
+import os
+from typing import Dict
+
+from . import pure_verify
+
+try:
+    import reliquary_encryptor
+except ImportError:
+    reliquary_encryptor = None
+
+try:
+    import reliquary_merkle
+except ImportError:
+    reliquary_merkle = None
+
+# --- Verify-only operations, native when the extension modules load, pure Python otherwise ---
+#
+# Set RELIQUARY_PURE_VERIFY=1 to use the pure-Python fallback even when the native modules are
+# installed (for reproducing restricted-environment behaviour). envelope_info always comes
+# from the fallback; parsing a header is cheap and the native module has no single call for it.
+
+_FORCE_PURE = os.environ.get("RELIQUARY_PURE_VERIFY", "").lower() in ("1", "true", "yes")
+
+_encryptor = None if _FORCE_PURE else reliquary_encryptor
+_merkle = None if _FORCE_PURE else reliquary_merkle
+
+
+def _select(module, name: str):
+    return getattr(module, name) if module is not None else getattr(pure_verify, name)
+
+
+verify_ed25519 = _select(_encryptor, "verify_ed25519")
+envelope_recipients = _select(_encryptor, "envelope_recipients")
+envelope_threshold = _select(_encryptor, "envelope_threshold")
+kms_envelope_info = _select(_encryptor, "kms_envelope_info")
+create_merkle_root = _select(_merkle, "create_merkle_root")
+verify_merkle_proof = _select(_merkle, "verify_merkle_proof")
+verify_positional_proof = _select(_merkle, "verify_positional_proof")
+verify_consistency_proof = _select(_merkle, "verify_consistency_proof")
+verify_multi_proof = _select(_merkle, "verify_multi_proof")
+verify_sparse_proof = _select(_merkle, "verify_sparse_proof")
+verify_map_proof = _select(_merkle, "verify_map_proof")
+merkle_proof_size = _select(_merkle, "proof_size")
+merkle_root_version = _select(_merkle, "merkle_root_version")
+merkle_root_hash_algorithm = _select(_merkle, "merkle_root_hash_algorithm")
+envelope_info = pure_verify.envelope_info
+
+
+def verifier_backends() -> Dict[str, str]:
+    """Which implementation serves each group of operations: "native" or "pure-python" """
+    return {
+        "encryptor": "native" if _encryptor is not None else "pure-python",
+        "merkle": "native" if _merkle is not None else "pure-python",
+    }
@@ -0,0 +1,363 @@
+"""
+Heartbeats and dead-man switch for ReliQuary inheritance.
+
+The vault owner periodically signs a heartbeat::
+
+    {"type": "reliquary-heartbeat", "owner_id": "...", "sequence": 42, "issued_at": 1760486400}
+
+with their Falcon or Ed25519 key. Each accepted heartbeat is written to the audit log with its
+signature, so liveness can be re-verified from the log alone. Silence is measured against a
+TrustedClock rather than the claimed `issued_at` or the local clock, so neither a backdated
+heartbeat nor a host clock pushed forward can satisfy a release condition early.
+"""
+
+import json
+import logging
+import statistics
+import threading
+import time
+from dataclasses import dataclass, asdict
+from typing import Any, Callable, Dict, Iterable, List, Optional, Tuple
+
+try:
+    import reliquary_encryptor
+except ImportError:
+    reliquary_encryptor = None
+
+HEARTBEAT_TYPE = "reliquary-heartbeat"
+SIGNATURE_ALGORITHMS = ("falcon512", "falcon1024", "ed25519")
+SECONDS_PER_DAY = 86400.0
+
+
+class HeartbeatError(Exception):
+    """Raised when a heartbeat is rejected or liveness can't be established"""
+
+
+class TrustedClock:
+    """
+    Time agreed on by several independent sources (e.g. authenticated NTP, Roughtime, a TSA).
+
+    `now()` takes the median of the sources that answer and fails closed when fewer than
+    `min_sources` answer or they disagree by more than `max_spread` seconds. The result never
+    goes backwards, so a source rolled back can't reopen a condition that was already met.
+    """
+
+    def __init__(self, sources: List[Callable[[], float]], min_sources: int = 2,
+                 max_spread: float = 5.0):
+        if min_sources < 1 or min_sources > len(sources):
+            raise ValueError("min_sources must be between 1 and the number of sources")
+        self.sources = sources
+        self.min_sources = min_sources
+        self.max_spread = max_spread
+        self.logger = logging.getLogger(__name__)
+        self._last = 0.0
+        self._lock = threading.Lock()
+
+    def now(self) -> float:
+        readings = []
+        for source in self.sources:
+            try:
+                readings.append(float(source()))
+            except Exception as e:
+                self.logger.warning(f"Time source failed: {e}")
+        if len(readings) < self.min_sources:
+            raise HeartbeatError(
+                f"Only {len(readings)} of {self.min_sources} required time sources answered")
+        if max(readings) - min(readings) > self.max_spread:
+            raise HeartbeatError(
+                f"Time sources disagree by {max(readings) - min(readings):.1f}s")
+        with self._lock:
+            self._last = max(self._last, statistics.median(readings))
+            return self._last
+
+
+@dataclass
+class Heartbeat:
+    """A signed statement from the owner that they were alive at `issued_at`"""
+    owner_id: str
+    sequence: int
+    issued_at: float
+    algorithm: str
+    signature: bytes
+
+    def payload(self) -> bytes:
+        return heartbeat_payload(self.owner_id, self.sequence, self.issued_at)
+
+    def to_dict(self) -> Dict[str, Any]:
+        data = asdict(self)
+        data["signature"] = self.signature.hex()
+        return data
+
+    @classmethod
+    def from_dict(cls, data: Dict[str, Any]) -> "Heartbeat":
+        return cls(
+            owner_id=data["owner_id"],
+            sequence=int(data["sequence"]),
+            issued_at=float(data["issued_at"]),
+            algorithm=data["algorithm"],
+            signature=bytes.fromhex(data["signature"])
+        )
+
+
+@dataclass
+class LivenessPolicy:
+    """Release condition: no accepted heartbeat for `silence_days`, plus `grace_days`"""
+    owner_id: str
+    silence_days: float
+    grace_days: float = 0.0
+
+    @property
+    def threshold_seconds(self) -> float:
+        return (self.silence_days + self.grace_days) * SECONDS_PER_DAY
+
+
+@dataclass
+class LivenessStatus:
+    """Outcome of a liveness check"""
+    owner_id: str
+    release_allowed: bool
+    checked_at: float
+    last_heartbeat_at: Optional[float]
+    silence_seconds: float
+    threshold_seconds: float
+    reason: str
+
+    def to_dict(self) -> Dict[str, Any]:
+        return asdict(self)
+
+
+@dataclass
+class _Owner:
+    public_key: bytes
+    algorithm: str
+    registered_at: float
+    last_sequence: int = -1
+    last_heartbeat_at: Optional[float] = None
+
+
+def heartbeat_payload(owner_id: str, sequence: int, issued_at: float) -> bytes:
+    """Canonical bytes an owner signs for a heartbeat."""
+    return json.dumps({
+        "type": HEARTBEAT_TYPE,
+        "owner_id": owner_id,
+        "sequence": sequence,
+        "issued_at": issued_at
+    }, sort_keys=True, separators=(",", ":")).encode("utf-8")
+
+
+def sign_heartbeat(owner_id: str, sequence: int, secret_key: Any,
+                   algorithm: str = "falcon1024",
+                   issued_at: Optional[float] = None) -> Heartbeat:
+    """
+    Sign a heartbeat on the owner's device.
+
+    Args:
+        owner_id: Owner the heartbeat is for
+        sequence: Strictly increasing per owner
+        secret_key: Falcon or Ed25519 secret key (bytes or a SecretKeyHandle for Falcon)
+        algorithm: "falcon512", "falcon1024" or "ed25519"
+        issued_at: Claimed signing time (defaults to now)
+
+    Returns:
+        The signed heartbeat
+    """
+    issued_at = float(time.time() if issued_at is None else issued_at)
+    payload = heartbeat_payload(owner_id, sequence, issued_at)
+    return Heartbeat(owner_id, sequence, issued_at, algorithm,
+                     sign_payload(payload, secret_key, algorithm))
+
+
+def sign_payload(payload: bytes, secret_key: Any, algorithm: str) -> bytes:
+    """Sign canonical payload bytes with a Falcon or Ed25519 secret key."""
+    if reliquary_encryptor is None:
+        raise HeartbeatError("reliquary_encryptor is required to sign")
+    if algorithm == "ed25519":
+        return bytes(reliquary_encryptor.sign_ed25519(payload, secret_key))
+    if algorithm in SIGNATURE_ALGORITHMS:
+        return bytes(reliquary_encryptor.sign_falcon(payload, secret_key,
+                                                     _falcon_params(algorithm)))
+    raise HeartbeatError(f"Unsupported signature algorithm: {algorithm}")
+
+
+def verify_payload(payload: bytes, signature: bytes, public_key: bytes, algorithm: str) -> bool:
+    """Check a Falcon or Ed25519 signature; malformed keys or signatures count as invalid."""
+    if reliquary_encryptor is None:
+        raise HeartbeatError("reliquary_encryptor is required to verify signatures")
+    try:
+        if algorithm == "ed25519":
+            return reliquary_encryptor.verify_ed25519(payload, signature, public_key)
+        return reliquary_encryptor.verify_falcon(
+            payload, signature, public_key, _falcon_params(algorithm))
+    except ValueError:
+        return False
+
+
+def _falcon_params(algorithm: str) -> Any:
+    if algorithm == "falcon512":
+        return reliquary_encryptor.FalconParams.Falcon512
+    return reliquary_encryptor.FalconParams.Falcon1024
+
+
+class HeartbeatMonitor:
+    """
+    Verifies and records owner heartbeats and evaluates dead-man release conditions.
+
+    A heartbeat is accepted only if its signature verifies under the owner's registered key,
+    its sequence is higher than any accepted before (no replay) and its `issued_at` is within
+    `max_clock_skew` of trusted time. Its time of acceptance, by the trusted clock, is what
+    counts for liveness.
+    """
+
+    def __init__(self, clock: TrustedClock, audit_logger: Any = None,
+                 max_clock_skew: float = 300.0):
+        """
+        Initialize the monitor.
+
+        Args:
+            clock: Trusted time used for acceptance and liveness checks
+            audit_logger: Sink with add_entry(dict) (e.g. MerkleLogWriter)
+            max_clock_skew: Largest accepted gap between `issued_at` and trusted time
+        """
+        self.clock = clock
+        self.audit_logger = audit_logger
+        self.max_clock_skew = max_clock_skew
+        self.logger = logging.getLogger(__name__)
+        self._owners: Dict[str, _Owner] = {}
+        self._lock = threading.Lock()
+
+    def register_owner(self, owner_id: str, public_key: bytes, algorithm: str = "falcon1024",
+                       registered_at: Optional[float] = None):
+        """
+        Register the key an owner signs heartbeats with. Silence is counted from registration
+        until the first heartbeat arrives.
+        """
+        if algorithm not in SIGNATURE_ALGORITHMS:
+            raise HeartbeatError(f"Unsupported heartbeat algorithm: {algorithm}")
+        registered_at = self.clock.now() if registered_at is None else registered_at
+        with self._lock:
+            if owner_id in self._owners:
+                raise HeartbeatError(f"Owner {owner_id} is already registered")
+            self._owners[owner_id] = _Owner(bytes(public_key), algorithm, registered_at)
+        self._log({
+            "event": "heartbeat_owner_registered",
+            "owner_id": owner_id,
+            "algorithm": algorithm,
+            "public_key": bytes(public_key).hex(),
+            "registered_at": registered_at
+        })
+
+    def record_heartbeat(self, heartbeat: Heartbeat) -> float:
+        """
+        Verify a heartbeat and record it.
+
+        Args:
+            heartbeat: Signed heartbeat from the owner
+
+        Returns:
+            Trusted time at which the heartbeat was accepted
+
+        Raises:
+            HeartbeatError: If the heartbeat is rejected or trusted time is unavailable
+        """
+        now = self.clock.now()
+        with self._lock:
+            owner = self._owner(heartbeat.owner_id)
+            self._check(owner, heartbeat, now)
+            owner.last_sequence = heartbeat.sequence
+            owner.last_heartbeat_at = now
+        self._log({"event": "heartbeat_recorded", "received_at": now, **heartbeat.to_dict()})
+        return now
+
+    def check_liveness(self, policy: LivenessPolicy) -> LivenessStatus:
+        """
+        Decide whether the owner has been silent long enough for the release condition.
+
+        Args:
+            policy: Owner and silence threshold
+
+        Returns:
+            Liveness status; `release_allowed` is True only if the threshold has passed
+
+        Raises:
+            HeartbeatError: If the owner is unknown or trusted time is unavailable
+        """
+        now = self.clock.now()
+        with self._lock:
+            owner = self._owner(policy.owner_id)
+            last = owner.last_heartbeat_at
+            since = owner.registered_at if last is None else last
+        silence = max(0.0, now - since)
+        release = silence >= policy.threshold_seconds
+        if release:
+            reason = f"No heartbeat for {silence / SECONDS_PER_DAY:.1f} days"
+        elif last is None:
+            reason = "No heartbeat yet; silence threshold not reached since registration"
+        else:
+            reason = "Owner heartbeat within silence threshold"
+        status = LivenessStatus(policy.owner_id, release, now, last, silence,
+                                policy.threshold_seconds, reason)
+        if release:
+            self.logger.warning(f"Dead-man condition met for owner {policy.owner_id}")
+            self._log({"event": "liveness_release_condition_met", **status.to_dict()})
+        return status
+
+    def replay(self, entries: Iterable[Dict[str, Any]]) -> int:
+        """
+        Rebuild state from audit log entries, re-verifying every heartbeat signature.
+
+        Entries of other events are skipped. Acceptance times are taken from the log, so the
+        log itself must be trusted (e.g. checked against its Merkle root) before replay.
+
+        Returns:
+            Number of heartbeats replayed
+        """
+        count = 0
+        for entry in entries:
+            event = entry.get("event")
+            if event == "heartbeat_owner_registered":
+                with self._lock:
+                    self._owners.setdefault(entry["owner_id"], _Owner(
+                        bytes.fromhex(entry["public_key"]), entry["algorithm"],
+                        float(entry["registered_at"])))
+            elif event == "heartbeat_recorded":
+                heartbeat = Heartbeat.from_dict(entry)
+                received_at = float(entry["received_at"])
+                with self._lock:
+                    owner = self._owner(heartbeat.owner_id)
+                    self._check(owner, heartbeat, received_at)
+                    owner.last_sequence = heartbeat.sequence
+                    owner.last_heartbeat_at = received_at
+                count += 1
+        return count
+
+    def owner_key(self, owner_id: str) -> Tuple[bytes, str]:
+        """(public_key, algorithm) an owner signs with."""
+        with self._lock:
+            owner = self._owner(owner_id)
+            return owner.public_key, owner.algorithm
+
+    def _owner(self, owner_id: str) -> _Owner:
+        owner = self._owners.get(owner_id)
+        if owner is None:
+            raise HeartbeatError(f"Unknown heartbeat owner: {owner_id}")
+        return owner
+
+    def _check(self, owner: _Owner, heartbeat: Heartbeat, now: float):
+        if heartbeat.algorithm != owner.algorithm:
+            raise HeartbeatError(
+                f"Heartbeat uses {heartbeat.algorithm}, owner is registered with {owner.algorithm}")
+        if heartbeat.sequence <= owner.last_sequence:
+            raise HeartbeatError(
+                f"Heartbeat sequence {heartbeat.sequence} is not after {owner.last_sequence}")
+        if abs(heartbeat.issued_at - now) > self.max_clock_skew:
+            raise HeartbeatError("Heartbeat issued_at is too far from trusted time")
+        if not verify_payload(heartbeat.payload(), heartbeat.signature, owner.public_key,
+                              owner.algorithm):
+            raise HeartbeatError("Heartbeat signature is invalid")
+
+    def _log(self, entry: Dict[str, Any]):
+        if self.audit_logger:
+            try:
+                self.audit_logger.add_entry(entry)
+            except Exception as e:
+                self.logger.warning(f"Could not log heartbeat event: {e}")
@@ -0,0 +1,260 @@
+# core/key_usage.py
+
+import csv
+import io
+import json
+import math
+from collections import Counter
+from dataclasses import dataclass, field
+from datetime import datetime, timedelta, timezone
+from typing import Any, Dict, Iterable, List, Optional, Sequence, Union
+
+try:
+    from zk.anomaly import AnomalyDetector
+except ImportError:
+    AnomalyDetector = None
+
+# --- Per-key usage reports over the audit log ---
+#
+# Audit entries come from several subsystems that name the same things differently, so each
+# report dimension is read from the first field present out of a list of candidates. Entries
+# without a key field are not key operations and are skipped; "anomaly_detected" entries
+# logged for a key are attached to its report instead of being counted.
+
+KEY_FIELDS = ("key_id", "kms_key_id", "key_fingerprint")
+OPERATION_FIELDS = ("operation", "op", "event")
+BYTES_FIELDS = ("bytes", "byte_count", "size")
+CALLER_FIELDS = ("caller", "agent_id", "user_id", "user", "actor")
+FAILED_STATUSES = {"failed", "failure", "error", "denied"}
+BUCKET_FORMATS = {"hour": "%Y-%m-%dT%H:00Z", "day": "%Y-%m-%d", "week": "%G-W%V",
+                  "month": "%Y-%m"}
+CSV_COLUMNS = ["key_id", "total_operations", "failures", "bytes_total", "distinct_callers",
+               "first_seen", "last_seen", "operations", "anomaly_flags"]
+
+Timestamp = Union[datetime, str, int, float]
+
+
+def _parse_time(value: Any) -> Optional[datetime]:
+    """Audit timestamps are ISO 8601 (naive ones are UTC) or Unix seconds."""
+    if isinstance(value, datetime):
+        return value.astimezone(timezone.utc) if value.tzinfo else value.replace(tzinfo=timezone.utc)
+    if isinstance(value, (int, float)) and not isinstance(value, bool):
+        return datetime.fromtimestamp(value, timezone.utc)
+    if isinstance(value, str):
+        try:
+            parsed = datetime.fromisoformat(value[:-1] if value.endswith("Z") else value)
+        except ValueError:
+            return None
+        return _parse_time(parsed)
+    return None
+
+
+def _first(entry: Dict[str, Any], fields: Sequence[str]) -> Any:
+    return next((entry[f] for f in fields if entry.get(f) not in (None, "")), None)
+
+
+def _succeeded(entry: Dict[str, Any]) -> bool:
+    if "success" in entry:
+        return bool(entry["success"])
+    return str(entry.get("status", "")).lower() not in FAILED_STATUSES
+
+
+@dataclass
+class KeyUsage:
+    """Aggregated usage of one key over the report period."""
+    key_id: str
+    operations: Counter = field(default_factory=Counter)
+    bytes_by_operation: Counter = field(default_factory=Counter)
+    callers: Counter = field(default_factory=Counter)
+    failures: int = 0
+    first_seen: Optional[datetime] = None
+    last_seen: Optional[datetime] = None
+    heatmap: Counter = field(default_factory=Counter)
+    anomalies: List[Dict[str, Any]] = field(default_factory=list)
+
+    @property
+    def total_operations(self) -> int:
+        return sum(self.operations.values())
+
+    @property
+    def bytes_total(self) -> int:
+        return sum(self.bytes_by_operation.values())
+
+    @property
+    def anomaly_flags(self) -> List[str]:
+        return sorted({a["kind"] for a in self.anomalies})
+
+    def to_dict(self) -> Dict[str, Any]:
+        return {
+            "key_id": self.key_id,
+            "total_operations": self.total_operations,
+            "operations": dict(sorted(self.operations.items())),
+            "bytes_total": self.bytes_total,
+            "bytes_by_operation": dict(sorted(self.bytes_by_operation.items())),
+            "distinct_callers": len(self.callers),
+            "callers": dict(sorted(self.callers.items())),
+            "failures": self.failures,
+            "first_seen": self.first_seen.isoformat() if self.first_seen else None,
+            "last_seen": self.last_seen.isoformat() if self.last_seen else None,
+            "heatmap": dict(sorted(self.heatmap.items())),
+            "anomaly_flags": self.anomaly_flags,
+            "anomalies": self.anomalies,
+        }
+
+
+class KeyUsageReporter:
+    """
+    Builds per-key usage reports (operation counts, byte volumes, distinct callers, a
+    time-bucketed activity heatmap and anomaly flags) from audit log entries.
+
+    Anomaly flags are:
+        volume_spike   a heatmap bucket far above the key's average bucket
+        failure_spike  / unusual_hour, from replaying the period through zk.anomaly's
+                       AnomalyDetector when it is available
+        anything else  anomaly_detected entries logged for the key at the time
+    """
+
+    def __init__(self, entries: Union[Iterable[Dict[str, Any]], Any],
+                 spike_sigma: float = 3.0, min_spike_count: int = 10):
+        """
+        Args:
+            entries: Audit entry dicts, or a MerkleLogWriter whose entries are read
+            spike_sigma: Standard deviations above the mean bucket that make a volume spike
+            min_spike_count: Operations a bucket needs before it can be a volume spike
+        """
+        if hasattr(entries, "get_entries"):
+            self._entries = entries.get_entries()
+        else:
+            self._entries = list(entries)
+        self.spike_sigma = spike_sigma
+        self.min_spike_count = min_spike_count
+
+    @classmethod
+    def from_log_file(cls, path: str, **kwargs) -> "KeyUsageReporter":
+        """Read a JSON-lines audit log (the MerkleLogWriter format) without opening a writer."""
+        with open(path, "rb") as f:
+            return cls([json.loads(line) for line in f if line.strip()], **kwargs)
+
+    def aggregate(self, start: Optional[Timestamp] = None, end: Optional[Timestamp] = None,
+                  bucket: str = "day") -> Dict[str, KeyUsage]:
+        """Per-key usage for entries with start <= timestamp < end, keyed by key id."""
+        if bucket not in BUCKET_FORMATS:
+            raise ValueError(f"Unknown heatmap bucket '{bucket}'; use one of {list(BUCKET_FORMATS)}")
+        start, end = _parse_time(start), _parse_time(end)
+        usage: Dict[str, KeyUsage] = {}
+        events = []
+        for entry in self._entries:
+            key_id = _first(entry, KEY_FIELDS)
+            if key_id is None:
+                if entry.get("event") == "anomaly_detected" and entry.get("subject_type") == "key":
+                    key_id = entry.get("subject")
+                if key_id is None:
+                    continue
+            when = _parse_time(entry.get("timestamp"))
+            if when is None or (start and when < start) or (end and when >= end):
+                continue
+            key = usage.setdefault(str(key_id), KeyUsage(str(key_id)))
+            if entry.get("event") == "anomaly_detected":
+                key.anomalies.append({"kind": entry.get("kind", "logged_anomaly"),
+                                      "timestamp": when.isoformat(), "source": "audit_log"})
+                continue
+
+            operation = str(_first(entry, OPERATION_FIELDS) or "unknown")
+            key.operations[operation] += 1
+            size = _first(entry, BYTES_FIELDS)
+            if isinstance(size, int) and not isinstance(size, bool) and size >= 0:
+                key.bytes_by_operation[operation] += size
+            caller = _first(entry, CALLER_FIELDS)
+            if caller is not None:
+                key.callers[str(caller)] += 1
+            success = _succeeded(entry)
+            if not success:
+                key.failures += 1
+            key.first_seen = min(filter(None, [key.first_seen, when]))
+            key.last_seen = max(filter(None, [key.last_seen, when]))
+            key.heatmap[when.strftime(BUCKET_FORMATS[bucket])] += 1
+            events.append((when, operation, key.key_id, success))
+
+        for key in usage.values():
+            self._flag_volume_spikes(key)
+        self._replay_anomalies(usage, events)
+        return dict(sorted(usage.items()))
+
+    def report(self, start: Optional[Timestamp] = None, end: Optional[Timestamp] = None,
+               days: Optional[int] = None, bucket: str = "day",
+               fmt: str = "dict") -> Union[Dict[str, Any], str]:
+        """
+        Per-key usage report for a period.
+
+        Args:
+            start: Period start (inclusive); ISO 8601 string, datetime or Unix seconds
+            end: Period end (exclusive); defaults to no limit
+            days: Instead of start, cover the last `days` days up to end (or now),
+                e.g. days=90 for the last quarter
+            bucket: Heatmap granularity: "hour", "day", "week" or "month"
+            fmt: "dict", "json" or "csv" (one row per key, without the heatmap)
+
+        Returns:
+            The report as a dict, or serialized as a JSON or CSV string
+        """
+        if days is not None:
+            if start is not None:
+                raise ValueError("Give either start or days, not both")
+            start = (_parse_time(end) or datetime.now(timezone.utc)) - timedelta(days=days)
+        keys = self.aggregate(start, end, bucket)
+        if fmt == "csv":
+            return self._to_csv(keys.values())
+        start, end = _parse_time(start), _parse_time(end)
+        report = {
+            "generated_at": datetime.now(timezone.utc).isoformat(),
+            "period": {"start": start.isoformat() if start else None,
+                       "end": end.isoformat() if end else None},
+            "bucket": bucket,
+            "entries_scanned": len(self._entries),
+            "keys": [key.to_dict() for key in keys.values()],
+        }
+        if fmt == "json":
+            return json.dumps(report, indent=2, sort_keys=True)
+        if fmt != "dict":
+            raise ValueError(f"Unknown report format '{fmt}'; use dict, json or csv")
+        return report
+
+    def _flag_volume_spikes(self, key: KeyUsage):
+        # Each active bucket is compared with the key's other active buckets
+        if len(key.heatmap) < 2:
+            return
+        for bucket, count in sorted(key.heatmap.items()):
+            others = [c for b, c in key.heatmap.items() if b != bucket]
+            mean = sum(others) / len(others)
+            std = math.sqrt(sum((c - mean) ** 2 for c in others) / len(others))
+            if count >= self.min_spike_count and count > mean + self.spike_sigma * max(std, 1.0):
+                key.anomalies.append({"kind": "volume_spike", "bucket": bucket,
+                                      "observed": count, "baseline": round(mean, 3),
+                                      "source": "report"})
+
+    @staticmethod
+    def _replay_anomalies(usage: Dict[str, KeyUsage], events: List):
+        if AnomalyDetector is None:
+            return
+        detector = AnomalyDetector()
+        for when, operation, key_id, success in sorted(events, key=lambda e: e[0]):
+            for flag in detector.record(operation, key_id=key_id, success=success,
+                                        timestamp=when.timestamp()):
+                usage[key_id].anomalies.append(
+                    {"kind": flag.kind.value, "timestamp": when.isoformat(),
+                     "operation": flag.operation, "observed": flag.observed,
+                     "baseline": flag.baseline, "source": "replay"})
+
+    @staticmethod
+    def _to_csv(keys: Iterable[KeyUsage]) -> str:
+        out = io.StringIO()
+        writer = csv.DictWriter(out, fieldnames=CSV_COLUMNS, lineterminator="\n")
+        writer.writeheader()
+        for key in keys:
+            row = key.to_dict()
+            writer.writerow({
+                **{column: row[column] for column in CSV_COLUMNS[:7]},
+                "operations": ";".join(f"{op}={n}" for op, n in row["operations"].items()),
+                "anomaly_flags": ";".join(row["anomaly_flags"]),
+            })
+        return out.getvalue()
@@ -0,0 +1,17 @@
+"""
+KMIP Package for ReliQuary
+
+Client for enterprise key management servers (KMIP 2.x over TLS) and a key ring whose keys
+stay in the KMS.
+"""
+
+from .client import KmipClient, KmipError, Operation, RevocationReason
+from .keyring import KmipKeyRing
+
+__all__ = [
+    "KmipClient",
+    "KmipError",
+    "KmipKeyRing",
+    "Operation",
+    "RevocationReason"
+]
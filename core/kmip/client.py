@@ -0,0 +1,278 @@
+"""
+KMIP 2.x client for enterprise key management servers.
+
+Speaks TTLV over mutually authenticated TLS (port 5696 by default) and covers the symmetric
+key lifecycle ReliQuary needs: Create, Register, Get, ReKey, Activate, Revoke, Destroy and
+Locate. Each call is one request message with a single batch item.
+"""
+
+import logging
+import socket
+import ssl
+import struct
+import threading
+from enum import IntEnum
+from typing import Any, Callable, List, Optional, Tuple
+
+from .ttlv import (Item, Tag, TTLVError, byte_string, decode, enumeration, integer, interval,
+                   structure, text)
+
+DEFAULT_PORT = 5696
+MAX_RESPONSE_BYTES = 16 * 1024 * 1024
+
+
+class Operation(IntEnum):
+    CREATE = 0x01
+    REGISTER = 0x03
+    REKEY = 0x04
+    LOCATE = 0x08
+    GET = 0x0A
+    ACTIVATE = 0x12
+    REVOKE = 0x13
+    DESTROY = 0x14
+
+
+class RevocationReason(IntEnum):
+    UNSPECIFIED = 0x01
+    KEY_COMPROMISE = 0x02
+    SUPERSEDED = 0x05
+    CESSATION_OF_OPERATION = 0x06
+
+
+OBJECT_TYPE_SYMMETRIC_KEY = 0x02
+ALGORITHM_AES = 0x03
+KEY_FORMAT_RAW = 0x01
+USAGE_ENCRYPT_DECRYPT = 0x04 | 0x08
+NAME_TYPE_TEXT = 0x01
+CREDENTIAL_USERNAME_PASSWORD = 0x01
+RESULT_SUCCESS = 0x00
+
+
+class KmipError(Exception):
+    """Raised when the KMS rejects a request or the connection fails"""
+
+    def __init__(self, message: str, result_reason: Optional[int] = None):
+        super().__init__(message)
+        self.result_reason = result_reason
+
+
+class KmipClient:
+    """
+    Client for one KMIP server.
+
+    The connection is opened on first use and reused; calls are serialized on it.
+    """
+
+    def __init__(self, host: str, port: int = DEFAULT_PORT, ca_file: Optional[str] = None,
+                 cert_file: Optional[str] = None, key_file: Optional[str] = None,
+                 username: Optional[str] = None, password: Optional[str] = None,
+                 timeout: float = 10.0, protocol_version: Tuple[int, int] = (2, 0),
+                 connect: Optional[Callable[[], Any]] = None):
+        """
+        Initialize the client.
+
+        Args:
+            host: KMS hostname
+            port: KMIP port
+            ca_file: CA bundle used to verify the server certificate
+            cert_file: Client certificate (most appliances require mutual TLS)
+            key_file: Client certificate's private key
+            username: Optional username credential, sent with every request
+            password: Password for `username`
+            timeout: Socket timeout in seconds
+            protocol_version: KMIP protocol version to announce, (2, 0) or (2, 1)
+            connect: Factory returning a connected socket-like object (replaces TLS setup)
+        """
+        if protocol_version[0] != 2:
+            raise ValueError("Only KMIP 2.x is supported")
+        self.host = host
+        self.port = port
+        self.ca_file = ca_file
+        self.cert_file = cert_file
+        self.key_file = key_file
+        self.username = username
+        self.password = password
+        self.timeout = timeout
+        self.protocol_version = protocol_version
+        self.connect = connect or self._connect_tls
+        self.logger = logging.getLogger(__name__)
+        self._socket = None
+        self._lock = threading.Lock()
+
+    def _connect_tls(self):
+        context = ssl.create_default_context(cafile=self.ca_file)
+        context.minimum_version = ssl.TLSVersion.TLSv1_2
+        if self.cert_file:
+            context.load_cert_chain(self.cert_file, self.key_file)
+        raw = socket.create_connection((self.host, self.port), timeout=self.timeout)
+        return context.wrap_socket(raw, server_hostname=self.host)
+
+    def close(self):
+        with self._lock:
+            if self._socket is not None:
+                self._socket.close()
+                self._socket = None
+
+    def __enter__(self):
+        return self
+
+    def __exit__(self, *exc):
+        self.close()
+
+    def _header(self) -> Item:
+        authentication = None
+        if self.username:
+            authentication = structure(
+                Tag.AUTHENTICATION,
+                structure(Tag.CREDENTIAL,
+                          enumeration(Tag.CREDENTIAL_TYPE, CREDENTIAL_USERNAME_PASSWORD),
+                          structure(Tag.CREDENTIAL_VALUE,
+                                    text(Tag.USERNAME, self.username),
+                                    text(Tag.PASSWORD, self.password or ""))))
+        return structure(
+            Tag.REQUEST_HEADER,
+            structure(Tag.PROTOCOL_VERSION,
+                      integer(Tag.PROTOCOL_VERSION_MAJOR, self.protocol_version[0]),
+                      integer(Tag.PROTOCOL_VERSION_MINOR, self.protocol_version[1])),
+            authentication,
+            integer(Tag.BATCH_COUNT, 1))
+
+    def _read_exact(self, n: int) -> bytes:
+        data = b""
+        while len(data) < n:
+            chunk = self._socket.recv(n - len(data))
+            if not chunk:
+                raise KmipError("KMIP server closed the connection")
+            data += chunk
+        return data
+
+    def _exchange(self, message: bytes) -> bytes:
+        with self._lock:
+            try:
+                if self._socket is None:
+                    self._socket = self.connect()
+                self._socket.sendall(message)
+                header = self._read_exact(8)
+                length = struct.unpack(">I", header[4:8])[0]
+                if length > MAX_RESPONSE_BYTES:
+                    raise KmipError(f"KMIP response of {length} bytes is too large")
+                return header + self._read_exact(length)
+            except (OSError, KmipError):
+                # A failed exchange leaves the stream in an unknown state
+                if self._socket is not None:
+                    self._socket.close()
+                    self._socket = None
+                raise
+
+    def request(self, operation: Operation, *payload: Optional[Item]) -> Item:
+        """
+        Send one operation and return its response payload.
+
+        Raises:
+            KmipError: If the connection fails or the server reports a failure
+        """
+        message = structure(
+            Tag.REQUEST_MESSAGE,
+            self._header(),
+            structure(Tag.BATCH_ITEM,
+                      enumeration(Tag.OPERATION, operation),
+                      structure(Tag.REQUEST_PAYLOAD, *payload)))
+        try:
+            raw = self._exchange(message.encode())
+        except OSError as e:
+            raise KmipError(f"KMIP {operation.name} failed: {e}")
+        try:
+            response = decode(raw)
+            if response.tag != Tag.RESPONSE_MESSAGE:
+                raise TTLVError("Not a KMIP response message")
+            item = response.require(Tag.BATCH_ITEM)
+            status = item.require(Tag.RESULT_STATUS).value
+        except TTLVError as e:
+            raise KmipError(f"Malformed KMIP response to {operation.name}: {e}")
+        if status != RESULT_SUCCESS:
+            reason = item.find(Tag.RESULT_REASON)
+            message_item = item.find(Tag.RESULT_MESSAGE)
+            detail = message_item.value if message_item else "no message"
+            self.logger.warning(f"KMIP {operation.name} failed: {detail}")
+            raise KmipError(f"KMIP {operation.name} failed: {detail}",
+                            reason.value if reason else None)
+        return item.find(Tag.RESPONSE_PAYLOAD) or structure(Tag.RESPONSE_PAYLOAD)
+
+    @staticmethod
+    def _unique_identifier(payload: Item) -> str:
+        try:
+            return payload.require(Tag.UNIQUE_IDENTIFIER).value
+        except TTLVError as e:
+            raise KmipError(f"KMIP response has no unique identifier: {e}")
+
+    @staticmethod
+    def _key_attributes(name: Optional[str], length: int) -> Item:
+        return structure(
+            Tag.ATTRIBUTES,
+            enumeration(Tag.CRYPTOGRAPHIC_ALGORITHM, ALGORITHM_AES),
+            integer(Tag.CRYPTOGRAPHIC_LENGTH, length),
+            integer(Tag.CRYPTOGRAPHIC_USAGE_MASK, USAGE_ENCRYPT_DECRYPT),
+            structure(Tag.NAME, text(Tag.NAME_VALUE, name),
+                      enumeration(Tag.NAME_TYPE, NAME_TYPE_TEXT)) if name else None)
+
+    def create_symmetric_key(self, name: Optional[str] = None, length: int = 256) -> str:
+        """Have the KMS generate an AES key; returns its unique identifier."""
+        payload = self.request(Operation.CREATE,
+                               enumeration(Tag.OBJECT_TYPE, OBJECT_TYPE_SYMMETRIC_KEY),
+                               self._key_attributes(name, length))
+        return self._unique_identifier(payload)
+
+    def register_symmetric_key(self, key: bytes, name: Optional[str] = None) -> str:
+        """Hand existing AES key material to the KMS; returns its unique identifier."""
+        key_block = structure(
+            Tag.KEY_BLOCK,
+            enumeration(Tag.KEY_FORMAT_TYPE, KEY_FORMAT_RAW),
+            structure(Tag.KEY_VALUE, byte_string(Tag.KEY_MATERIAL, key)),
+            enumeration(Tag.CRYPTOGRAPHIC_ALGORITHM, ALGORITHM_AES),
+            integer(Tag.CRYPTOGRAPHIC_LENGTH, len(key) * 8))
+        payload = self.request(Operation.REGISTER,
+                               enumeration(Tag.OBJECT_TYPE, OBJECT_TYPE_SYMMETRIC_KEY),
+                               self._key_attributes(name, len(key) * 8),
+                               structure(Tag.SYMMETRIC_KEY, key_block))
+        return self._unique_identifier(payload)
+
+    def get_key(self, unique_identifier: str) -> bytes:
+        """Fetch raw symmetric key material."""
+        payload = self.request(Operation.GET, text(Tag.UNIQUE_IDENTIFIER, unique_identifier),
+                               enumeration(Tag.KEY_FORMAT_TYPE, KEY_FORMAT_RAW))
+        try:
+            material = payload.require(Tag.SYMMETRIC_KEY).require(Tag.KEY_BLOCK) \
+                .require(Tag.KEY_VALUE).require(Tag.KEY_MATERIAL)
+        except TTLVError as e:
+            raise KmipError(f"KMIP Get returned no raw symmetric key: {e}")
+        return material.value
+
+    def rekey(self, unique_identifier: str) -> str:
+        """Create an active replacement for a key; returns the replacement's identifier."""
+        payload = self.request(Operation.REKEY, text(Tag.UNIQUE_IDENTIFIER, unique_identifier),
+                               interval(Tag.OFFSET, 0))
+        return self._unique_identifier(payload)
+
+    def activate(self, unique_identifier: str):
+        self.request(Operation.ACTIVATE, text(Tag.UNIQUE_IDENTIFIER, unique_identifier))
+
+    def revoke(self, unique_identifier: str,
+               reason: RevocationReason = RevocationReason.SUPERSEDED,
+               message: Optional[str] = None):
+        self.request(Operation.REVOKE, text(Tag.UNIQUE_IDENTIFIER, unique_identifier),
+                     structure(Tag.REVOCATION_REASON,
+                               enumeration(Tag.REVOCATION_REASON_CODE, reason),
+                               text(Tag.REVOCATION_MESSAGE, message) if message else None))
+
+    def destroy(self, unique_identifier: str):
+        self.request(Operation.DESTROY, text(Tag.UNIQUE_IDENTIFIER, unique_identifier))
+
+    def locate(self, name: str, max_items: Optional[int] = None) -> List[str]:
+        """Identifiers of the objects carrying `name`."""
+        payload = self.request(
+            Operation.LOCATE,
+            integer(Tag.MAXIMUM_ITEMS, max_items) if max_items else None,
+            structure(Tag.ATTRIBUTES,
+                      structure(Tag.NAME, text(Tag.NAME_VALUE, name),
+                                enumeration(Tag.NAME_TYPE, NAME_TYPE_TEXT))))
+        return [item.value for item in payload.find_all(Tag.UNIQUE_IDENTIFIER)]
@@ -0,0 +1,132 @@
+"""
+KMS-backed key ring.
+
+Binds a `reliquary_encryptor.KeyRing` to keys held in a KMIP server. Each ring version is a
+KMS object; the ring only records which object backs which version (the manifest) and fetches
+key material when a KeyRing is built. The KMIP Name attribute is the ring name and, because
+ReKey moves names to the replacement key, always points at the current version.
+"""
+
+import logging
+from typing import Any, Dict, List, Optional
+
+from .client import KmipClient, KmipError, RevocationReason
+
+try:
+    import reliquary_encryptor
+except ImportError:
+    reliquary_encryptor = None
+
+
+class KmipKeyRing:
+    """
+    Key ring whose versions live in a KMS.
+
+    Persist `manifest()` (it holds no key material) and pass it back to reopen the ring.
+    """
+
+    def __init__(self, client: KmipClient, name: str,
+                 manifest: Optional[Dict[str, Any]] = None, audit_logger: Any = None):
+        """
+        Initialize the ring.
+
+        Args:
+            client: Connected KMIP client
+            name: Ring name, used as the KMIP Name of the current key
+            manifest: Saved `manifest()` of an existing ring
+            audit_logger: Sink with add_entry(dict) (e.g. MerkleLogWriter)
+        """
+        manifest = manifest or {}
+        if manifest and manifest.get("name") != name:
+            raise ValueError(f"Manifest belongs to ring {manifest.get('name')!r}, not {name!r}")
+        self.client = client
+        self.name = name
+        self.versions: Dict[int, str] = {int(v): uid for v, uid in
+                                         manifest.get("versions", {}).items()}
+        self.current: Optional[int] = manifest.get("current")
+        self.retired: List[int] = sorted(manifest.get("retired", []))
+        self.audit_logger = audit_logger
+        self.logger = logging.getLogger(__name__)
+
+    def manifest(self) -> Dict[str, Any]:
+        """Version -> KMS identifier mapping, for persisting the ring."""
+        return {
+            "name": self.name,
+            "versions": {str(version): uid for version, uid in sorted(self.versions.items())},
+            "current": self.current,
+            "retired": list(self.retired)
+        }
+
+    def _next_version(self) -> int:
+        return max(list(self.versions) + self.retired + [0]) + 1
+
+    def _add(self, uid: str, event: str) -> int:
+        version = self._next_version()
+        self.versions[version] = uid
+        self.current = version
+        self._log(event, version, uid)
+        return version
+
+    def create(self) -> int:
+        """Start the ring with a KMS-generated key; returns its version."""
+        if self.versions:
+            raise KmipError(f"Key ring {self.name} already has keys; use rotate()")
+        uid = self.client.create_symmetric_key(self.name)
+        self.client.activate(uid)
+        return self._add(uid, "kmip_key_created")
+
+    def register(self, key: bytes) -> int:
+        """
+        Move an existing 32-byte key into KMS custody as the new current version, e.g. when
+        migrating a locally held ring.
+        """
+        if len(key) != 32:
+            raise ValueError("Key ring keys must be 32 bytes")
+        uid = self.client.register_symmetric_key(key, None if self.versions else self.name)
+        self.client.activate(uid)
+        return self._add(uid, "kmip_key_registered")
+
+    def rotate(self) -> int:
+        """ReKey the current version in the KMS and make the replacement current."""
+        if self.current is None:
+            raise KmipError(f"Key ring {self.name} has no current key to rotate")
+        uid = self.client.rekey(self.versions[self.current])
+        return self._add(uid, "kmip_key_rotated")
+
+    def retire(self, version: int, destroy: bool = True):
+        """
+        Revoke (and by default destroy) a superseded version in the KMS. Rewrap data under
+        it first; the current version can't be retired.
+        """
+        if version == self.current:
+            raise KmipError(f"Key version {version} is current; rotate first")
+        if version not in self.versions:
+            raise KmipError(f"Unknown key version {version}")
+        uid = self.versions[version]
+        self.client.revoke(uid, RevocationReason.SUPERSEDED, f"{self.name} v{version} retired")
+        if destroy:
+            self.client.destroy(uid)
+        del self.versions[version]
+        self.retired = sorted(self.retired + [version])
+        self._log("kmip_key_retired", version, uid)
+
+    def fetch(self) -> Any:
+        """
+        Fetch every live version from the KMS into a `reliquary_encryptor.KeyRing`.
+
+        Returns:
+            KeyRing with the same versions, current version and retired list
+        """
+        if reliquary_encryptor is None:
+            raise KmipError("reliquary_encryptor is required to build a KeyRing")
+        keys = {version: self.client.get_key(uid) for version, uid in self.versions.items()}
+        return reliquary_encryptor.KeyRing(keys, self.current, self.retired)
+
+    def _log(self, event: str, version: int, uid: str):
+        self.logger.info(f"Key ring {self.name}: {event} v{version} ({uid})")
+        if self.audit_logger:
+            try:
+                self.audit_logger.add_entry({"event": event, "key_ring": self.name,
+                                             "version": version, "kmip_id": uid})
+            except Exception as e:
+                self.logger.warning(f"Could not log key ring event: {e}")
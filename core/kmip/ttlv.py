@@ -0,0 +1,197 @@
+"""
+KMIP TTLV encoding.
+
+Every KMIP item is Tag (3 bytes) | Type (1 byte) | Length (4 bytes, big-endian) | Value, with
+the value padded to a multiple of 8 bytes. Structures contain their children's encodings
+back to back.
+"""
+
+import struct
+from dataclasses import dataclass, field
+from enum import IntEnum
+from typing import Any, Iterator, List, Optional
+
+
+class ItemType(IntEnum):
+    """TTLV item types"""
+    STRUCTURE = 0x01
+    INTEGER = 0x02
+    LONG_INTEGER = 0x03
+    BIG_INTEGER = 0x04
+    ENUMERATION = 0x05
+    BOOLEAN = 0x06
+    TEXT_STRING = 0x07
+    BYTE_STRING = 0x08
+    DATE_TIME = 0x09
+    INTERVAL = 0x0A
+
+
+class Tag(IntEnum):
+    """KMIP tags used by the client"""
+    ACTIVATION_DATE = 0x420001
+    AUTHENTICATION = 0x42000C
+    BATCH_COUNT = 0x42000D
+    BATCH_ITEM = 0x42000F
+    CREDENTIAL = 0x420023
+    CREDENTIAL_TYPE = 0x420024
+    CREDENTIAL_VALUE = 0x420025
+    CRYPTOGRAPHIC_ALGORITHM = 0x420028
+    CRYPTOGRAPHIC_LENGTH = 0x42002A
+    CRYPTOGRAPHIC_USAGE_MASK = 0x42002C
+    KEY_BLOCK = 0x420040
+    KEY_FORMAT_TYPE = 0x420042
+    KEY_MATERIAL = 0x420043
+    KEY_VALUE = 0x420045
+    MAXIMUM_ITEMS = 0x420050
+    NAME = 0x420053
+    NAME_TYPE = 0x420054
+    NAME_VALUE = 0x420055
+    OBJECT_TYPE = 0x420057
+    OFFSET = 0x420058
+    OPERATION = 0x42005C
+    PROTOCOL_VERSION = 0x420069
+    PROTOCOL_VERSION_MAJOR = 0x42006A
+    PROTOCOL_VERSION_MINOR = 0x42006B
+    REQUEST_HEADER = 0x420077
+    REQUEST_MESSAGE = 0x420078
+    REQUEST_PAYLOAD = 0x420079
+    RESPONSE_HEADER = 0x42007A
+    RESPONSE_MESSAGE = 0x42007B
+    RESPONSE_PAYLOAD = 0x42007C
+    RESULT_MESSAGE = 0x42007D
+    RESULT_REASON = 0x42007E
+    RESULT_STATUS = 0x42007F
+    REVOCATION_MESSAGE = 0x420080
+    REVOCATION_REASON = 0x420081
+    REVOCATION_REASON_CODE = 0x420082
+    SYMMETRIC_KEY = 0x42008F
+    TIME_STAMP = 0x420092
+    UNIQUE_IDENTIFIER = 0x420094
+    USERNAME = 0x420099
+    PASSWORD = 0x4200A1
+    ATTRIBUTES = 0x420125
+
+
+class TTLVError(ValueError):
+    """Raised for malformed TTLV data"""
+
+
+@dataclass
+class Item:
+    """One TTLV item; `value` is a list of child items for structures"""
+    tag: int
+    type: ItemType
+    value: Any = field(default=None)
+
+    def find(self, tag: int) -> Optional["Item"]:
+        """First child with `tag`, if any."""
+        return next(self.find_all(tag), None)
+
+    def find_all(self, tag: int) -> Iterator["Item"]:
+        return (child for child in self.value if child.tag == tag)
+
+    def require(self, tag: int) -> "Item":
+        child = self.find(tag)
+        if child is None:
+            raise TTLVError(f"Missing required item {tag:#08x}")
+        return child
+
+    def encode(self) -> bytes:
+        if self.type == ItemType.STRUCTURE:
+            body = b"".join(child.encode() for child in self.value)
+        elif self.type in (ItemType.INTEGER, ItemType.INTERVAL):
+            body = struct.pack(">i" if self.type == ItemType.INTEGER else ">I", self.value)
+        elif self.type == ItemType.ENUMERATION:
+            body = struct.pack(">I", self.value)
+        elif self.type in (ItemType.LONG_INTEGER, ItemType.DATE_TIME):
+            body = struct.pack(">q", self.value)
+        elif self.type == ItemType.BOOLEAN:
+            body = struct.pack(">Q", 1 if self.value else 0)
+        elif self.type == ItemType.TEXT_STRING:
+            body = self.value.encode("utf-8")
+        elif self.type == ItemType.BYTE_STRING:
+            body = bytes(self.value)
+        elif self.type == ItemType.BIG_INTEGER:
+            length = max(8, (self.value.bit_length() + 8 + 63) // 64 * 8)
+            body = self.value.to_bytes(length, "big", signed=True)
+        else:
+            raise TTLVError(f"Unknown item type {self.type}")
+        padding = b"\x00" * (-len(body) % 8)
+        return self.tag.to_bytes(3, "big") + bytes([self.type]) + struct.pack(">I", len(body)) \
+            + body + padding
+
+
+def _decode_value(item_type: ItemType, body: bytes) -> Any:
+    if item_type == ItemType.STRUCTURE:
+        return decode_all(body)
+    if item_type == ItemType.INTEGER:
+        return struct.unpack(">i", body)[0]
+    if item_type in (ItemType.ENUMERATION, ItemType.INTERVAL):
+        return struct.unpack(">I", body)[0]
+    if item_type in (ItemType.LONG_INTEGER, ItemType.DATE_TIME):
+        return struct.unpack(">q", body)[0]
+    if item_type == ItemType.BOOLEAN:
+        return struct.unpack(">Q", body)[0] != 0
+    if item_type == ItemType.TEXT_STRING:
+        return body.decode("utf-8")
+    if item_type == ItemType.BYTE_STRING:
+        return body
+    return int.from_bytes(body, "big", signed=True)
+
+
+def decode_all(data: bytes) -> List[Item]:
+    """Decode a sequence of TTLV items (a structure's body or a whole message)."""
+    items, offset = [], 0
+    while offset < len(data):
+        if len(data) - offset < 8:
+            raise TTLVError("Truncated TTLV header")
+        tag = int.from_bytes(data[offset:offset + 3], "big")
+        try:
+            item_type = ItemType(data[offset + 3])
+        except ValueError:
+            raise TTLVError(f"Unknown TTLV item type {data[offset + 3]:#04x}")
+        length = struct.unpack(">I", data[offset + 4:offset + 8])[0]
+        end = offset + 8 + length
+        padded_end = offset + 8 + length + (-length % 8)
+        if padded_end > len(data):
+            raise TTLVError("Truncated TTLV value")
+        try:
+            value = _decode_value(item_type, data[offset + 8:end])
+        except struct.error:
+            raise TTLVError(f"Invalid length {length} for TTLV {item_type.name}")
+        items.append(Item(tag, item_type, value))
+        offset = padded_end
+    return items
+
+
+def decode(data: bytes) -> Item:
+    """Decode exactly one TTLV item."""
+    items = decode_all(data)
+    if len(items) != 1:
+        raise TTLVError(f"Expected one TTLV item, got {len(items)}")
+    return items[0]
+
+
+def structure(tag: int, *children: Optional[Item]) -> Item:
+    """Structure item; None children are skipped so optional fields can be inlined."""
+    return Item(tag, ItemType.STRUCTURE, [child for child in children if child is not None])
+
+
+def integer(tag: int, value: int) -> Item:
+    return Item(tag, ItemType.INTEGER, value)
+
+
+def enumeration(tag: int, value: int) -> Item:
+    return Item(tag, ItemType.ENUMERATION, int(value))
+
+
+def text(tag: int, value: str) -> Item:
+    return Item(tag, ItemType.TEXT_STRING, value)
+
+
+def byte_string(tag: int, value: bytes) -> Item:
+    return Item(tag, ItemType.BYTE_STRING, bytes(value))
+
+
+def interval(tag: int, value: int) -> Item:
+    return Item(tag, ItemType.INTERVAL, value)
@@ -9,8 +9,18 @@ from .hasher import (
     hash_data
 )
 
+from .encoders import (
+    LeafEncodingError,
+    register_leaf_encoder,
+    get_leaf_encoder,
+    registered_leaf_encoders,
+    encode_leaf,
+    encode_leaves
+)
+
 from .merkle import (
-    MerkleTree
+    MerkleTree,
+    verify_exported_proof
 )
 
 from .writer import (
@@ -20,7 +30,14 @@ from .writer import (
 
 __all__ = [
     "hash_data",
+    "LeafEncodingError",
+    "register_leaf_encoder",
+    "get_leaf_encoder",
+    "registered_leaf_encoders",
+    "encode_leaf",
+    "encode_leaves",
     "MerkleTree",
+    "verify_exported_proof",
     "MerkleLogWriter",
     "MerkleLogEntry"
 ]
\ No newline at end of file
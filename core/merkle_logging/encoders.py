@@ -0,0 +1,120 @@
+# core/merkle_logging/encoders.py
+
+import json
+import math
+from typing import Any, Callable, Dict, List
+
+try:
+    import cbor2
+except ImportError:
+    cbor2 = None
+
+LeafEncoder = Callable[[Any], bytes]
+
+_ENCODERS: Dict[str, LeafEncoder] = {}
+
+
+class LeafEncodingError(ValueError):
+    """A leaf could not be serialized by the tree's encoder."""
+
+
+def register_leaf_encoder(name: str, encoder: LeafEncoder, replace: bool = False) -> None:
+    """
+    Register a leaf encoder under a name that trees and proofs record.
+
+    Every service building or verifying a tree must register the same encoder under the same
+    name, so a name must never be reused for a different serialization.
+
+    Raises:
+        ValueError: If the name is already registered and replace is False
+    """
+    if not name or not isinstance(name, str):
+        raise ValueError("Leaf encoder name must be a non-empty string")
+    if name in _ENCODERS and not replace:
+        raise ValueError(f"Leaf encoder '{name}' is already registered")
+    _ENCODERS[name] = encoder
+
+
+def get_leaf_encoder(name: str) -> LeafEncoder:
+    """Look up a registered encoder; raises KeyError for unknown names."""
+    try:
+        return _ENCODERS[name]
+    except KeyError:
+        raise KeyError(f"Unknown leaf encoder '{name}'; registered: {sorted(_ENCODERS)}") from None
+
+
+def registered_leaf_encoders() -> List[str]:
+    return sorted(_ENCODERS)
+
+
+def encode_leaf(obj: Any, encoder: str = "raw") -> bytes:
+    """Serialize one object with a registered encoder."""
+    try:
+        data = get_leaf_encoder(encoder)(obj)
+    except LeafEncodingError:
+        raise
+    except (TypeError, ValueError, OverflowError) as e:
+        raise LeafEncodingError(f"Cannot encode leaf with '{encoder}': {e}") from e
+    if not isinstance(data, bytes):
+        raise LeafEncodingError(f"Leaf encoder '{encoder}' returned {type(data).__name__}, not bytes")
+    return data
+
+
+def encode_leaves(objects: List[Any], encoder: str = "raw") -> List[bytes]:
+    """Serialize a list of objects, naming the position of any object that fails."""
+    leaves = []
+    for i, obj in enumerate(objects):
+        try:
+            leaves.append(encode_leaf(obj, encoder))
+        except LeafEncodingError as e:
+            raise LeafEncodingError(f"Leaf {i}: {e}") from e
+    return leaves
+
+
+# --- Built-in encoders ---
+
+def _encode_raw(obj: Any) -> bytes:
+    if not isinstance(obj, (bytes, bytearray, memoryview)):
+        raise LeafEncodingError(f"'raw' leaves must be bytes, got {type(obj).__name__}")
+    return bytes(obj)
+
+
+def _encode_utf8(obj: Any) -> bytes:
+    if not isinstance(obj, str):
+        raise LeafEncodingError(f"'utf8' leaves must be str, got {type(obj).__name__}")
+    return obj.encode("utf-8")
+
+
+def _check_json_floats(obj: Any) -> None:
+    if isinstance(obj, float) and not math.isfinite(obj):
+        raise LeafEncodingError("'json' leaves cannot contain NaN or infinity")
+    if isinstance(obj, dict):
+        for key in obj:
+            if not isinstance(key, str):
+                raise LeafEncodingError(f"'json' object keys must be str, got {type(key).__name__}")
+        for value in obj.values():
+            _check_json_floats(value)
+    elif isinstance(obj, (list, tuple)):
+        for value in obj:
+            _check_json_floats(value)
+
+
+def _encode_json(obj: Any) -> bytes:
+    # Canonical form: sorted keys, no whitespace, UTF-8 rather than \u escapes, and only string
+    # keys, since json.dumps would otherwise silently turn 1 and "1" into the same key.
+    _check_json_floats(obj)
+    return json.dumps(obj, sort_keys=True, separators=(",", ":"), ensure_ascii=False,
+                      allow_nan=False).encode("utf-8")
+
+
+def _encode_cbor(obj: Any) -> bytes:
+    if cbor2 is None:
+        raise LeafEncodingError("The 'cbor' leaf encoder requires cbor2")
+    # Canonical CBOR (RFC 8949 section 4.2): shortest forms and sorted map keys
+    return cbor2.dumps(obj, canonical=True)
+
+
+register_leaf_encoder("raw", _encode_raw)
+register_leaf_encoder("utf8", _encode_utf8)
+register_leaf_encoder("json", _encode_json)
+register_leaf_encoder("cbor", _encode_cbor)
@@ -1,15 +1,23 @@
 # core/merkle_logging/merkle.py
 
 import hashlib
-from typing import List, Optional, Tuple
+from typing import Any, Dict, List, Optional, Tuple
+from .encoders import encode_leaf, encode_leaves
 from .hasher import hash_data
 
 class MerkleTree:
     """A simple and robust Merkle tree implementation."""
     
-    def __init__(self, data_blocks: List[bytes]):
-        self.data_blocks = data_blocks.copy()
-        self.leaf_hashes = [hash_data(block) for block in data_blocks]
+    def __init__(self, data_blocks: List[Any], leaf_encoder: str = "raw"):
+        """
+        Args:
+            data_blocks: Leaves, serialized with leaf_encoder before hashing
+            leaf_encoder: Name of a registered leaf encoder ("raw", "utf8", "json", "cbor" or
+                one added with register_leaf_encoder); the default takes bytes as they are
+        """
+        self.leaf_encoder = leaf_encoder
+        self.data_blocks = encode_leaves(data_blocks, leaf_encoder)
+        self.leaf_hashes = [hash_data(block) for block in self.data_blocks]
         self.tree = self._build_tree()
         self.root = self.tree[-1][0] if self.tree and self.tree[-1] else b''
     
@@ -114,15 +122,67 @@ class MerkleTree:
         # Check that we used all proof elements and reached the root
         return proof_index == len(proof) and current_hash == self.root
 
+    def verify_object(self, obj: Any, index: int, proof: List[Tuple[bytes, bool]]) -> bool:
+        """Verify a proof for an unserialized leaf, encoding it with the tree's encoder."""
+        return self.verify_proof(encode_leaf(obj, self.leaf_encoder), index, proof)
+
+    def export_proof(self, index: int) -> Dict[str, Any]:
+        """
+        Self-describing proof for a leaf, verifiable with verify_exported_proof without the tree.
+
+        Returns:
+            Dictionary with the leaf index, tree size, root, leaf encoder and sibling hashes
+        """
+        return {
+            "leaf_index": index,
+            "tree_size": len(self.leaf_hashes),
+            "merkle_root": self.root.hex(),
+            "leaf_encoder": self.leaf_encoder,
+            "proof": [h.hex() for h, _ in self.get_proof(index)],
+        }
+
+def verify_exported_proof(obj: Any, exported: Dict[str, Any], root: Optional[bytes] = None) -> bool:
+    """
+    Verify an export_proof() result for an unserialized leaf.
+
+    The leaf is encoded with the encoder recorded in the proof, so the verifier needs no
+    knowledge of how the tree was built beyond having the same encoder registered.
+
+    Args:
+        obj: The leaf object
+        exported: Result of MerkleTree.export_proof
+        root: Trusted root to check against; defaults to the root recorded in the proof
+    """
+    index, size = exported["leaf_index"], exported["tree_size"]
+    if not 0 <= index < size:
+        return False
+    siblings = [bytes.fromhex(h) for h in exported["proof"]]
+    current_hash = hash_data(encode_leaf(obj, exported["leaf_encoder"]))
+    level_size = size
+    while level_size > 1:
+        sibling_index = index ^ 1
+        if sibling_index < level_size:
+            if not siblings:
+                return False
+            sibling = siblings.pop(0)
+            pair = current_hash + sibling if index % 2 == 0 else sibling + current_hash
+        else:
+            pair = current_hash + current_hash
+        current_hash = hash_data(pair)
+        index //= 2
+        level_size = (level_size + 1) // 2
+    expected = root if root is not None else bytes.fromhex(exported["merkle_root"])
+    return not siblings and current_hash == expected
+
 # Convenience functions for backward compatibility
-def create_merkle_root(data_blocks: List[bytes]) -> bytes:
+def create_merkle_root(data_blocks: List[Any], leaf_encoder: str = "raw") -> bytes:
     """
-    Creates a Merkle root from a list of data blocks.
+    Creates a Merkle root from a list of data blocks, serialized with leaf_encoder.
     """
     if not data_blocks:
         return b''
     
-    tree = MerkleTree(data_blocks)
+    tree = MerkleTree(data_blocks, leaf_encoder)
     return tree.root
 
 def generate_merkle_proof(data_blocks: List[bytes], target_index: int) -> List[bytes]:
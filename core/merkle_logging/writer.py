@@ -5,6 +5,17 @@ import os
 from datetime import datetime
 from typing import List, Dict, Any, Optional
 from .merkle import MerkleTree, hash_data
+from ..read_only import ensure_directory, ensure_writable, is_read_only
+
+try:
+    import reliquary_encryptor
+except ImportError:
+    reliquary_encryptor = None
+
+try:
+    import reliquary_merkle
+except ImportError:
+    reliquary_merkle = None
 
 class MerkleLogEntry:
     """Represents a single log entry with Merkle proof capability."""
@@ -33,12 +44,14 @@ class MerkleLogWriter:
         self.merkle_file_path = merkle_file_path or log_file_path + ".merkle"
         
         # Ensure directories exist
-        os.makedirs(os.path.dirname(log_file_path), exist_ok=True)
+        ensure_directory(os.path.dirname(log_file_path))
         if self.merkle_file_path != log_file_path:
-            os.makedirs(os.path.dirname(self.merkle_file_path), exist_ok=True)
+            ensure_directory(os.path.dirname(self.merkle_file_path))
         
         self._entries_cache: List[MerkleLogEntry] = []
         self._current_root: Optional[bytes] = None
+        # Incremental tree (O(log n) per append) when the native module is available
+        self._tree = reliquary_merkle.MerkleTree() if reliquary_merkle is not None else None
         self._load_existing_entries()
     
     def _load_existing_entries(self):
@@ -54,6 +67,8 @@ class MerkleLogWriter:
                         data = json.loads(line.decode('utf-8'))
                         entry = MerkleLogEntry(data, data.get("timestamp"))
                         self._entries_cache.append(entry)
+                        if self._tree is not None:
+                            self._tree.append(entry.to_bytes())
                     except (json.JSONDecodeError, UnicodeDecodeError) as e:
                         print(f"Warning: Failed to parse log entry: {e}")
         
@@ -67,11 +82,15 @@ class MerkleLogWriter:
             self._current_root = None
             return
         
-        entry_bytes = [entry.to_bytes() for entry in self._entries_cache]
-        tree = MerkleTree(entry_bytes)
-        self._current_root = tree.root
+        if self._tree is not None:
+            self._current_root = bytes(self._tree.root())
+        else:
+            entry_bytes = [entry.to_bytes() for entry in self._entries_cache]
+            self._current_root = MerkleTree(entry_bytes).root
         
-        # Save Merkle root to file
+        # Save Merkle root to file (read-only mode keeps it in memory only)
+        if is_read_only():
+            return
         merkle_data = {
             "root": self._current_root.hex(),
             "entry_count": len(self._entries_cache),
@@ -91,6 +110,7 @@ class MerkleLogWriter:
         Returns:
             The created MerkleLogEntry
         """
+        ensure_writable("Appending an audit log entry")
         entry = MerkleLogEntry(entry_data)
         
         # Write to log file
@@ -99,6 +119,8 @@ class MerkleLogWriter:
         
         # Add to cache and update Merkle root
         self._entries_cache.append(entry)
+        if self._tree is not None:
+            self._tree.append(entry.to_bytes())
         self._update_merkle_root()
         
         return entry
@@ -131,6 +153,32 @@ class MerkleLogWriter:
             "total_entries": len(self._entries_cache)
         }
     
+    def get_consistency_proof(self, old_size: int, new_size: Optional[int] = None) -> Dict[str, Any]:
+        """
+        Prove that the log at `old_size` entries is a prefix of the log at `new_size`.
+
+        Args:
+            old_size: Entry count of the earlier root, e.g. one a client saw before
+            new_size: Entry count of the later root; defaults to the current log
+
+        Returns:
+            Dictionary with both sizes, both roots and the proof nodes, all hex; check it
+            with core.crypto.verifier.verify_consistency_proof
+        """
+        if self._tree is None:
+            raise RuntimeError("Consistency proofs require the reliquary_merkle module")
+        if new_size is None:
+            new_size = len(self._entries_cache)
+        proof = self._tree.get_consistency_proof(old_size, new_size)
+
+        return {
+            "old_size": old_size,
+            "new_size": new_size,
+            "old_root": bytes(self._tree.root_at(old_size)).hex(),
+            "new_root": bytes(self._tree.root_at(new_size)).hex(),
+            "proof": [bytes(p).hex() for p in proof]
+        }
+
     def verify_entry_integrity(self, entry_index: int, provided_proof: Optional[List[str]] = None) -> bool:
         """
         Verify the integrity of a specific log entry.
@@ -190,6 +238,62 @@ class MerkleLogWriter:
         
         return calculated_root == self._current_root
     
+    def verify_entry_proofs(self, proofs: List[Dict[str, Any]], continue_on_error: bool = True,
+                            max_failures: Optional[int] = None) -> Dict[str, Any]:
+        """
+        Verify many exported proofs (from get_entry_proof) against this log in one pass.
+
+        Checks run in parallel in reliquary_encryptor when it is installed. Every failing proof
+        is reported rather than stopping at the first, unless continue_on_error is False or
+        max_failures is reached.
+
+        Args:
+            proofs: Proof dictionaries as returned by get_entry_proof
+            continue_on_error: Keep going after a failure
+            max_failures: Stop once this many failures have been found
+
+        Returns:
+            {"total", "checked", "passed", "aborted", "failures"} where failures lists
+            {"index", "entry_index", "reason"} in order; index is the position in `proofs`
+        """
+        size = len(self._entries_cache)
+        leaves, paths, indices = [], [], []
+        for proof in proofs:
+            index = proof.get("entry_index")
+            in_log = isinstance(index, int) and 0 <= index < size
+            leaves.append(self._entries_cache[index].to_bytes() if in_log else b"")
+            try:
+                paths.append([bytes.fromhex(h) for h in proof.get("proof", [])])
+            except (TypeError, ValueError):
+                paths.append([b""])
+            # An index outside the log fails the check with its own reason
+            indices.append(index if in_log else size)
+        limit = 1 if not continue_on_error else max_failures
+
+        if reliquary_encryptor is not None:
+            pipeline = reliquary_encryptor.VerificationPipeline()
+            pipeline.add_merkle_proofs(leaves, paths, indices, self._current_root or b"", size)
+            report = pipeline.run(max_failures=limit)
+            checked, failed = report.checked, report.failures
+        else:
+            tree = MerkleTree([entry.to_bytes() for entry in self._entries_cache])
+            checked, failed = 0, []
+            for i, (path, index) in enumerate(zip(paths, indices)):
+                if limit is not None and len(failed) >= limit:
+                    break
+                checked += 1
+                if index >= size or path != [h for h, _ in tree.get_proof(index)]:
+                    failed.append((i, "merkle_proof", "Computed root does not match"))
+        return {
+            "total": len(proofs),
+            "checked": checked,
+            "passed": checked - len(failed),
+            "aborted": checked < len(proofs),
+            "failures": [{"index": i, "entry_index": proofs[i].get("entry_index"),
+                          "reason": "Entry index is not in the log" if indices[i] >= size
+                          else reason} for i, _, reason in failed],
+        }
+
     def get_log_summary(self) -> Dict[str, Any]:
         """
         Get a summary of the current log state.
@@ -205,6 +309,29 @@ class MerkleLogWriter:
             "integrity_verified": self.verify_log_integrity()
         }
     
+    def find_entry(self, field: str, value: Any) -> Optional[MerkleLogEntry]:
+        """
+        Find the first log entry whose `field` equals `value`.
+        
+        Args:
+            field: Entry data key, e.g. "txid"
+            value: Value to match
+            
+        Returns:
+            The matching MerkleLogEntry, or None
+        """
+        return next((entry for entry in self._entries_cache
+                     if entry.data.get(field) == value), None)
+    
+    def get_entries(self) -> List[Dict[str, Any]]:
+        """
+        Get a copy of every entry's data, in log order.
+        
+        Returns:
+            List of entry dictionaries, each including its timestamp
+        """
+        return [dict(entry.data) for entry in self._entries_cache]
+    
     @property
     def entry_count(self) -> int:
         """Get the number of entries in the log."""
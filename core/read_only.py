@@ -0,0 +1,97 @@
+"""
+Process-wide read-only (forensic) mode.
+
+Incident responders turn it on before inspecting keystores, audit logs and containers; every
+write path (storage backends, audit logs, key counters, the TPM keystore, state import, and in
+Rust the LMS state files and provider/HSM keys) then raises ReadOnlyMode before touching
+anything. Setting RELIQUARY_READ_ONLY=1 turns it on and locks it for the life of the process.
+
+The flag is kept by reliquary_encryptor when it is installed, so Python and Rust always agree;
+without the extension a Python flag with the same behaviour is used.
+"""
+
+import os
+import threading
+
+try:
+    import reliquary_encryptor
+except ImportError:
+    reliquary_encryptor = None
+
+if reliquary_encryptor is not None:
+    ReadOnlyMode = reliquary_encryptor.ReadOnlyMode
+else:
+    class ReadOnlyMode(PermissionError):
+        """Raised when a write is attempted while read-only (forensic) mode is on"""
+
+_lock = threading.Lock()
+_state = {"enabled": False, "locked": False}
+
+if reliquary_encryptor is None and os.environ.get("RELIQUARY_READ_ONLY", "").lower() in (
+        "1", "true", "yes"):
+    _state.update(enabled=True, locked=True)
+
+
+def enable_read_only_mode(lock: bool = False):
+    """
+    Turn read-only mode on for the whole process.
+
+    Args:
+        lock: Keep it on until the process exits; disable_read_only_mode() then raises
+    """
+    if reliquary_encryptor is not None:
+        reliquary_encryptor.set_read_only_mode(True, lock)
+        return
+    with _lock:
+        _state["enabled"] = True
+        _state["locked"] = _state["locked"] or lock
+
+
+def disable_read_only_mode():
+    """
+    Turn read-only mode off.
+
+    Raises:
+        ReadOnlyMode: If the mode was locked on
+    """
+    if reliquary_encryptor is not None:
+        reliquary_encryptor.set_read_only_mode(False)
+        return
+    with _lock:
+        if _state["locked"]:
+            raise ReadOnlyMode("Read-only mode is locked for this process")
+        _state["enabled"] = False
+
+
+def is_read_only() -> bool:
+    """Whether read-only mode is on."""
+    if reliquary_encryptor is not None:
+        return reliquary_encryptor.read_only_mode()
+    return _state["enabled"]
+
+
+def is_read_only_locked() -> bool:
+    """Whether read-only mode is locked on for the rest of the process."""
+    if reliquary_encryptor is not None:
+        return reliquary_encryptor.read_only_mode_locked()
+    return _state["locked"]
+
+
+def ensure_directory(path: str, mode: int = 0o777):
+    """Create `path` (like os.makedirs with exist_ok) unless read-only mode is on."""
+    if not is_read_only():
+        os.makedirs(path, mode=mode, exist_ok=True)
+
+
+def ensure_writable(operation: str):
+    """
+    Refuse a write while read-only mode is on.
+
+    Args:
+        operation: What the caller was about to do, for the error message
+
+    Raises:
+        ReadOnlyMode: If read-only mode is on
+    """
+    if is_read_only():
+        raise ReadOnlyMode(f"{operation} refused: read-only mode is on")
@@ -0,0 +1,311 @@
+"""
+Staged release escrow for ReliQuary inheritance.
+
+A release moves through::
+
+    requested -> notified -> cooling_off -> released
+
+and can be cancelled with the owner's key at any point before it is released. Every step is
+approved by guardian signatures over::
+
+    {"type": "reliquary-release", "release_id": "...", "owner_id": "...", "state": "notified"}
+
+and written to the audit log with those signatures, so the state of every release can be
+rebuilt and re-verified from the log (`replay`). A release can only be requested while the
+owner's dead-man condition holds, and can only complete once the cooling-off period has passed
+by trusted time and the owner has still not sent a heartbeat; a heartbeat during cooling-off
+cancels the release.
+"""
+
+import json
+import logging
+import threading
+from dataclasses import dataclass, field
+from enum import Enum
+from typing import Any, Dict, Iterable, List, Optional
+
+from .heartbeat import (SECONDS_PER_DAY, HeartbeatMonitor, LivenessPolicy, sign_payload,
+                        verify_payload)
+
+RELEASE_TYPE = "reliquary-release"
+
+
+class EscrowError(Exception):
+    """Raised when a release transition is refused"""
+
+
+class ReleaseState(Enum):
+    """Stages of an inheritance release"""
+    REQUESTED = "requested"      # Dead-man condition met and a beneficiary asked for release
+    NOTIFIED = "notified"        # Guardians confirm the owner and contacts were notified
+    COOLING_OFF = "cooling_off"  # Waiting period in which the owner can still cancel
+    RELEASED = "released"        # Secrets may be handed to the beneficiary
+    CANCELLED = "cancelled"      # Stopped by the owner or by a heartbeat
+
+NEXT_STATE = {
+    ReleaseState.REQUESTED: ReleaseState.NOTIFIED,
+    ReleaseState.NOTIFIED: ReleaseState.COOLING_OFF,
+    ReleaseState.COOLING_OFF: ReleaseState.RELEASED,
+}
+TERMINAL_STATES = (ReleaseState.RELEASED, ReleaseState.CANCELLED)
+
+
+@dataclass
+class Guardian:
+    """A party whose signature approves release transitions"""
+    guardian_id: str
+    public_key: bytes
+    algorithm: str = "falcon1024"
+
+
+@dataclass
+class ReleaseRequest:
+    """Current state of one release and the transitions that led to it"""
+    release_id: str
+    owner_id: str
+    beneficiary: str
+    policy: LivenessPolicy
+    state: ReleaseState
+    updated_at: float
+    cooling_off_ends: Optional[float] = None
+    history: List[Dict[str, Any]] = field(default_factory=list)
+
+
+def release_payload(release_id: str, owner_id: str, state: ReleaseState) -> bytes:
+    """Canonical bytes guardians (or, for cancellation, the owner) sign to move to `state`."""
+    return json.dumps({
+        "type": RELEASE_TYPE,
+        "release_id": release_id,
+        "owner_id": owner_id,
+        "state": state.value
+    }, sort_keys=True, separators=(",", ":")).encode("utf-8")
+
+
+def sign_release_transition(release_id: str, owner_id: str, state: ReleaseState,
+                            secret_key: Any, algorithm: str = "falcon1024") -> bytes:
+    """Signature approving the move of a release to `state`."""
+    return sign_payload(release_payload(release_id, owner_id, state), secret_key, algorithm)
+
+
+class ReleaseEscrow:
+    """
+    Release state machine on top of a HeartbeatMonitor.
+
+    Owner keys and trusted time come from the monitor. Each forward transition needs valid
+    signatures from `required_approvals` distinct guardians; cancellation needs the owner's.
+    """
+
+    def __init__(self, monitor: HeartbeatMonitor, guardians: List[Guardian],
+                 required_approvals: int = 1, cooling_off_days: float = 7.0,
+                 audit_logger: Any = None):
+        """
+        Initialize the escrow.
+
+        Args:
+            monitor: Heartbeat monitor holding owner keys and the trusted clock
+            guardians: Parties allowed to approve transitions
+            required_approvals: Distinct guardian signatures needed per transition
+            cooling_off_days: Waiting period before a release can complete
+            audit_logger: Sink with add_entry(dict) (e.g. MerkleLogWriter)
+        """
+        if not 1 <= required_approvals <= len(guardians):
+            raise ValueError("required_approvals must be between 1 and the number of guardians")
+        self.monitor = monitor
+        self.guardians = {guardian.guardian_id: guardian for guardian in guardians}
+        self.required_approvals = required_approvals
+        self.cooling_off_seconds = cooling_off_days * SECONDS_PER_DAY
+        self.audit_logger = audit_logger
+        self.logger = logging.getLogger(__name__)
+        self._releases: Dict[str, ReleaseRequest] = {}
+        self._lock = threading.Lock()
+
+    def get(self, release_id: str) -> ReleaseRequest:
+        with self._lock:
+            return self._release(release_id)
+
+    def request_release(self, release_id: str, beneficiary: str, policy: LivenessPolicy,
+                        approvals: Dict[str, bytes]) -> ReleaseRequest:
+        """
+        Open a release for `policy.owner_id`. Refused unless the owner's dead-man condition
+        currently holds.
+
+        Args:
+            release_id: Unique id chosen by the requester (part of every signed payload)
+            beneficiary: Who receives the secrets on release
+            policy: Liveness condition that must hold now and again at release
+            approvals: Guardian id -> signature over the "requested" transition
+        """
+        status = self.monitor.check_liveness(policy)
+        if not status.release_allowed:
+            raise EscrowError(f"Release condition not met: {status.reason}")
+        with self._lock:
+            if release_id in self._releases:
+                raise EscrowError(f"Release {release_id} already exists")
+            request = ReleaseRequest(release_id, policy.owner_id, beneficiary, policy,
+                                     ReleaseState.REQUESTED, status.checked_at)
+            signers = self._check_approvals(request, ReleaseState.REQUESTED, approvals)
+            self._releases[release_id] = request
+            entry = self._record(request, None, signers, approvals, "requested")
+        self._log(entry)
+        return request
+
+    def advance(self, release_id: str, approvals: Dict[str, bytes]) -> ReleaseRequest:
+        """
+        Move a release to its next state with guardian approval.
+
+        Completing a release requires the cooling-off period to have passed by trusted time
+        and the owner's dead-man condition to still hold; if the owner has sent a heartbeat
+        in the meantime the release is cancelled instead.
+
+        Raises:
+            EscrowError: If the approvals are insufficient or a time check fails
+        """
+        now = self.monitor.clock.now()
+        request = self.get(release_id)
+        checked_state = request.state
+        if checked_state == ReleaseState.COOLING_OFF:
+            if now < request.cooling_off_ends:
+                raise EscrowError(
+                    f"Cooling-off period ends in {(request.cooling_off_ends - now) / 3600:.1f} hours")
+            if not self.monitor.check_liveness(request.policy).release_allowed:
+                self._cancel(request, {}, now, "owner heartbeat during cooling-off")
+                raise EscrowError("Owner sent a heartbeat; release cancelled")
+
+        with self._lock:
+            if request.state in TERMINAL_STATES:
+                raise EscrowError(f"Release {release_id} is already {request.state.value}")
+            if request.state != checked_state:
+                raise EscrowError(f"Release {release_id} changed state concurrently")
+            target = NEXT_STATE[request.state]
+            signers = self._check_approvals(request, target, approvals)
+            previous = request.state
+            request.state = target
+            request.updated_at = now
+            if target == ReleaseState.COOLING_OFF:
+                request.cooling_off_ends = now + self.cooling_off_seconds
+            entry = self._record(request, previous, signers, approvals, "guardian approval")
+        self._log(entry)
+        if target == ReleaseState.RELEASED:
+            self.logger.warning(f"Release {release_id} completed for {request.beneficiary}")
+        return request
+
+    def cancel(self, release_id: str, owner_signature: bytes) -> ReleaseRequest:
+        """Cancel a release that hasn't completed, with the owner's signature over "cancelled"."""
+        request = self.get(release_id)
+        public_key, algorithm = self.monitor.owner_key(request.owner_id)
+        payload = release_payload(release_id, request.owner_id, ReleaseState.CANCELLED)
+        if not verify_payload(payload, owner_signature, public_key, algorithm):
+            raise EscrowError("Owner signature is invalid")
+        self._cancel(request, {"owner": owner_signature}, self.monitor.clock.now(),
+                     "cancelled by owner")
+        return request
+
+    def replay(self, entries: Iterable[Dict[str, Any]]) -> int:
+        """
+        Rebuild releases from audit log entries, re-verifying every signature and the
+        cooling-off timing. Other events are skipped; the log must be trusted for its times.
+
+        Returns:
+            Number of transitions replayed
+        """
+        count = 0
+        for entry in entries:
+            if entry.get("event") != "release_transition":
+                continue
+            state = ReleaseState(entry["to_state"])
+            approvals = {signer: bytes.fromhex(sig) for signer, sig in entry["approvals"].items()}
+            at = float(entry["at"])
+            with self._lock:
+                if state == ReleaseState.REQUESTED:
+                    policy = LivenessPolicy(**entry["policy"])
+                    request = ReleaseRequest(entry["release_id"], entry["owner_id"],
+                                             entry["beneficiary"], policy, state, at)
+                    self._check_approvals(request, state, approvals)
+                    self._releases[request.release_id] = request
+                else:
+                    request = self._release(entry["release_id"])
+                    self._replay_step(request, state, approvals, at, entry)
+                request.history.append(entry)
+            count += 1
+        return count
+
+    def _replay_step(self, request: ReleaseRequest, state: ReleaseState,
+                     approvals: Dict[str, bytes], at: float, entry: Dict[str, Any]):
+        if request.state in TERMINAL_STATES:
+            raise EscrowError(f"Release {request.release_id} changed after {request.state.value}")
+        if state == ReleaseState.CANCELLED:
+            owner_signature = approvals.get("owner")
+            if owner_signature is not None:
+                public_key, algorithm = self.monitor.owner_key(request.owner_id)
+                payload = release_payload(request.release_id, request.owner_id, state)
+                if not verify_payload(payload, owner_signature, public_key, algorithm):
+                    raise EscrowError("Owner signature is invalid")
+        else:
+            if state != NEXT_STATE[request.state]:
+                raise EscrowError(f"Invalid transition {request.state.value} -> {state.value}")
+            if state == ReleaseState.RELEASED and at < request.cooling_off_ends:
+                raise EscrowError("Release completed before the cooling-off period ended")
+            self._check_approvals(request, state, approvals)
+        request.state = state
+        request.updated_at = at
+        request.cooling_off_ends = entry.get("cooling_off_ends")
+
+    def _cancel(self, request: ReleaseRequest, approvals: Dict[str, bytes], now: float,
+                reason: str):
+        with self._lock:
+            if request.state in TERMINAL_STATES:
+                raise EscrowError(
+                    f"Release {request.release_id} is already {request.state.value}")
+            previous = request.state
+            request.state = ReleaseState.CANCELLED
+            request.updated_at = now
+            entry = self._record(request, previous, list(approvals), approvals, reason)
+        self._log(entry)
+
+    def _release(self, release_id: str) -> ReleaseRequest:
+        request = self._releases.get(release_id)
+        if request is None:
+            raise EscrowError(f"Unknown release: {release_id}")
+        return request
+
+    def _check_approvals(self, request: ReleaseRequest, state: ReleaseState,
+                         approvals: Dict[str, bytes]) -> List[str]:
+        """Guardians whose signatures over the transition verify; raises if too few."""
+        payload = release_payload(request.release_id, request.owner_id, state)
+        signers = sorted(
+            guardian_id for guardian_id, signature in approvals.items()
+            if guardian_id in self.guardians and verify_payload(
+                payload, signature, self.guardians[guardian_id].public_key,
+                self.guardians[guardian_id].algorithm))
+        if len(signers) < self.required_approvals:
+            raise EscrowError(f"{state.value} needs {self.required_approvals} guardian "
+                              f"approvals, got {len(signers)} valid")
+        return signers
+
+    def _record(self, request: ReleaseRequest, previous: Optional[ReleaseState],
+                signers: List[str], approvals: Dict[str, bytes], reason: str) -> Dict[str, Any]:
+        """Append a transition to the request's history; returns the audit entry."""
+        entry = {
+            "event": "release_transition",
+            "release_id": request.release_id,
+            "owner_id": request.owner_id,
+            "beneficiary": request.beneficiary,
+            "policy": {"owner_id": request.policy.owner_id,
+                       "silence_days": request.policy.silence_days,
+                       "grace_days": request.policy.grace_days},
+            "from_state": previous.value if previous else None,
+            "to_state": request.state.value,
+            "at": request.updated_at,
+            "cooling_off_ends": request.cooling_off_ends,
+            "approvals": {signer: approvals[signer].hex() for signer in signers},
+            "reason": reason
+        }
+        request.history.append(entry)
+        return entry
+
+    def _log(self, entry: Dict[str, Any]):
+        if self.audit_logger:
+            try:
+                self.audit_logger.add_entry(entry)
+            except Exception as e:
+                self.logger.warning(f"Could not log release transition: {e}")
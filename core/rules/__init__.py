@@ -6,15 +6,28 @@ This package contains components for trust rule validation and enforcement.
 
 # Import rule components
 from .validator import (
-    RuleValidator,
-    TrustRule,
-    RuleValidationResult,
-    ValidationError
+    RulesValidator,
+    RuleType,
+    ValidationResult,
+    ValidationIssue,
+    ValidationSeverity
+)
+
+from .policy_manager import (
+    PolicyManager,
+    CompiledPolicy,
+    PolicyDecision,
+    PolicyError
 )
 
 __all__ = [
-    "RuleValidator",
-    "TrustRule",
-    "RuleValidationResult",
-    "ValidationError"
+    "RulesValidator",
+    "RuleType",
+    "ValidationResult",
+    "ValidationIssue",
+    "ValidationSeverity",
+    "PolicyManager",
+    "CompiledPolicy",
+    "PolicyDecision",
+    "PolicyError"
 ]
\ No newline at end of file
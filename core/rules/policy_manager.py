@@ -0,0 +1,437 @@
+"""
+Policy Manager for ReliQuary Trust Engine.
+This module hot-reloads signed policy bundles and swaps them in atomically.
+
+A bundle is a JSON document::
+
+    {"version": 7, "default_action": "deny", "rules": [...]}
+
+signed with a detached Falcon signature over the exact bundle bytes. Rules use the
+RulesValidator schema; they are compiled once on load so evaluation never re-parses them.
+"""
+
+import hashlib
+import json
+import logging
+import operator
+import os
+import threading
+from dataclasses import dataclass, field
+from typing import Any, Callable, Dict, List, Optional, Tuple
+
+from .validator import RulesValidator, ValidationSeverity
+
+try:
+    import reliquary_encryptor
+except ImportError:
+    reliquary_encryptor = None
+
+
+class PolicyError(Exception):
+    """Raised when a policy bundle is rejected"""
+
+
+def _contains(container: Any, item: Any) -> bool:
+    try:
+        return item in container
+    except TypeError:
+        return False
+
+
+CONDITION_OPERATORS: Dict[str, Callable[[Any, Any], bool]] = {
+    "equals": operator.eq,
+    "not_equals": operator.ne,
+    "greater_than": operator.gt,
+    "less_than": operator.lt,
+    "contains": _contains,
+    "not_contains": lambda container, item: not _contains(container, item),
+}
+
+_MISSING = object()
+
+
+def _lookup(context: Dict[str, Any], path: str) -> Any:
+    """Resolve a dotted field path against a nested context."""
+    value: Any = context
+    for part in path.split("."):
+        if not isinstance(value, dict) or part not in value:
+            return _MISSING
+        value = value[part]
+    return value
+
+
+@dataclass
+class CompiledRule:
+    """A rule with its conditions bound to operator functions"""
+    rule_id: str
+    priority: int
+    conditions: List[Tuple[str, Callable[[Any, Any], bool], Any]]
+    actions: List[Dict[str, Any]]
+
+    def matches(self, context: Dict[str, Any]) -> bool:
+        for path, compare, expected in self.conditions:
+            actual = _lookup(context, path)
+            if actual is _MISSING:
+                return False
+            try:
+                if not compare(actual, expected):
+                    return False
+            except TypeError:
+                return False
+        return True
+
+
+@dataclass
+class PolicyDecision:
+    """Outcome of evaluating a context against a compiled policy"""
+    action: str
+    rule_id: Optional[str]
+    policy_version: int
+    actions: List[Dict[str, Any]] = field(default_factory=list)
+
+
+@dataclass(frozen=True)
+class CompiledPolicy:
+    """Immutable, ready-to-evaluate policy bundle"""
+    version: int
+    default_action: str
+    rules: List[CompiledRule]
+    digest: str
+
+    def evaluate(self, context: Dict[str, Any]) -> PolicyDecision:
+        """First matching rule by descending priority wins; otherwise the default action."""
+        for rule in self.rules:
+            if rule.matches(context):
+                return PolicyDecision(
+                    action=rule.actions[0]["type"] if rule.actions else self.default_action,
+                    rule_id=rule.rule_id,
+                    policy_version=self.version,
+                    actions=rule.actions
+                )
+        return PolicyDecision(action=self.default_action, rule_id=None, policy_version=self.version)
+
+
+PolicyListener = Callable[[Optional[int], int, str], None]
+
+
+class PolicyManager:
+    """Verifies, compiles and atomically swaps policy bundles, keeping prior versions for rollback"""
+
+    def __init__(self, falcon_public_key: bytes, falcon_params: Any = None,
+                 validator: Optional[RulesValidator] = None, history_size: int = 10,
+                 audit_logger: Any = None):
+        """
+        Initialize the policy manager.
+
+        Args:
+            falcon_public_key: Key that must have signed every bundle
+            falcon_params: FalconParams of the signing key (Falcon-1024 by default)
+            validator: Rules validator to check bundles with
+            history_size: Number of superseded versions kept for rollback
+            audit_logger: Sink with add_entry(dict) (e.g. MerkleLogWriter) for shadow divergences
+        """
+        self.logger = logging.getLogger(__name__)
+        self.falcon_public_key = falcon_public_key
+        self.falcon_params = falcon_params
+        self.validator = validator or RulesValidator()
+        self.history_size = history_size
+        self.audit_logger = audit_logger
+
+        self._lock = threading.Lock()
+        self._active: Optional[CompiledPolicy] = None
+        self._history: List[CompiledPolicy] = []
+        self._listeners: List[PolicyListener] = []
+        self._shadow: Optional[CompiledPolicy] = None
+        self._shadow_stats = {"evaluations": 0, "divergences": 0, "errors": 0}
+
+        self._watch_thread: Optional[threading.Thread] = None
+        self._watch_stop = threading.Event()
+        self._watched_mtime: Optional[Tuple[int, int]] = None
+
+    @property
+    def active(self) -> Optional[CompiledPolicy]:
+        """Currently active policy. Hold on to the returned object for a consistent evaluation."""
+        return self._active
+
+    @property
+    def versions(self) -> List[int]:
+        """Versions available for rollback, oldest first."""
+        with self._lock:
+            return [policy.version for policy in self._history]
+
+    @property
+    def shadow(self) -> Optional[CompiledPolicy]:
+        """Candidate policy evaluated in shadow mode, if any."""
+        return self._shadow
+
+    @property
+    def shadow_stats(self) -> Dict[str, int]:
+        """Shadow evaluations, divergences and candidate errors since the candidate was set."""
+        with self._lock:
+            return dict(self._shadow_stats)
+
+    def evaluate(self, context: Dict[str, Any]) -> PolicyDecision:
+        """
+        Evaluate a context against the active policy.
+
+        If a shadow candidate is set it is evaluated too and divergences are audited, but the
+        returned decision is always the active policy's.
+        """
+        policy = self._active
+        if policy is None:
+            raise PolicyError("No policy loaded")
+        decision = policy.evaluate(context)
+        shadow = self._shadow
+        if shadow is not None:
+            self._evaluate_shadow(shadow, context, decision)
+        return decision
+
+    def subscribe(self, listener: PolicyListener):
+        """Register a callback invoked as listener(old_version, new_version, reason) after each swap."""
+        self._listeners.append(listener)
+
+    def compile(self, bundle: bytes, signature: bytes) -> CompiledPolicy:
+        """
+        Verify and compile a bundle without activating it.
+
+        Raises:
+            PolicyError: If the signature, JSON, or rules are invalid
+        """
+        self._verify_signature(bundle, signature)
+        try:
+            data = json.loads(bundle)
+        except (ValueError, UnicodeDecodeError) as e:
+            raise PolicyError(f"Policy bundle is not valid JSON: {e}")
+        if not isinstance(data, dict):
+            raise PolicyError("Policy bundle must be a JSON object")
+
+        version = data.get("version")
+        if not isinstance(version, int) or isinstance(version, bool) or version < 0:
+            raise PolicyError("Policy bundle needs a non-negative integer 'version'")
+        default_action = data.get("default_action", "deny")
+        if default_action not in self.validator.schema["action_types"]:
+            raise PolicyError(f"Invalid default action '{default_action}'")
+        rules = data.get("rules")
+        if not isinstance(rules, list):
+            raise PolicyError("Policy bundle needs a 'rules' list")
+
+        result = self.validator.validate_rules(rules)
+        errors = [issue for issue in result.issues if issue.severity == ValidationSeverity.ERROR]
+        if errors:
+            details = "; ".join(f"{issue.rule_id}: {issue.message}" for issue in errors)
+            raise PolicyError(f"Policy version {version} failed validation: {details}")
+
+        compiled = []
+        for i, rule in enumerate(rules):
+            if rule.get("enabled", True) is False:
+                continue
+            priority = rule.get("priority", 0)
+            if not isinstance(priority, int) or priority < 0:
+                priority = 0  # The validator only warns about these
+            compiled.append((priority, i, CompiledRule(
+                rule_id=rule["id"],
+                priority=priority,
+                conditions=[
+                    (c["field"], CONDITION_OPERATORS[c["operator"]], c["value"])
+                    for c in rule["conditions"]
+                ],
+                actions=rule["actions"]
+            )))
+        # Highest priority first; listing order breaks ties
+        compiled.sort(key=lambda entry: (-entry[0], entry[1]))
+
+        return CompiledPolicy(
+            version=version,
+            default_action=default_action,
+            rules=[rule for _, _, rule in compiled],
+            digest=hashlib.sha256(bundle).hexdigest()
+        )
+
+    def load(self, bundle: bytes, signature: bytes) -> CompiledPolicy:
+        """
+        Verify, compile and activate a bundle.
+
+        The version must be higher than the active one, so a replayed older bundle can't
+        silently downgrade policy; use rollback() for deliberate downgrades.
+
+        Raises:
+            PolicyError: If the bundle is rejected; the active policy is left unchanged
+        """
+        policy = self.compile(bundle, signature)
+        with self._lock:
+            old = self._active
+            if old is not None and policy.version <= old.version:
+                raise PolicyError(
+                    f"Policy version {policy.version} is not newer than active version {old.version}"
+                )
+            self._swap(policy)
+        self._notify(old.version if old else None, policy.version, "load")
+        return policy
+
+    def set_shadow(self, bundle: bytes, signature: bytes) -> CompiledPolicy:
+        """
+        Verify and compile a candidate bundle and run it in shadow mode alongside the active one.
+
+        Raises:
+            PolicyError: If the bundle is rejected
+        """
+        candidate = self.compile(bundle, signature)
+        with self._lock:
+            self._shadow = candidate
+            self._shadow_stats = {"evaluations": 0, "divergences": 0, "errors": 0}
+        self.logger.info(f"Shadow-evaluating candidate policy version {candidate.version}")
+        return candidate
+
+    def clear_shadow(self):
+        """Stop shadow evaluation."""
+        with self._lock:
+            self._shadow = None
+
+    def promote_shadow(self) -> CompiledPolicy:
+        """
+        Activate the shadow candidate, subject to the same version check as load().
+
+        Raises:
+            PolicyError: If there is no candidate or it is not newer than the active policy
+        """
+        with self._lock:
+            candidate = self._shadow
+            if candidate is None:
+                raise PolicyError("No shadow policy to promote")
+            old = self._active
+            if old is not None and candidate.version <= old.version:
+                raise PolicyError(
+                    f"Policy version {candidate.version} is not newer than active version {old.version}"
+                )
+            self._shadow = None
+            self._swap(candidate)
+        self._notify(old.version if old else None, candidate.version, "promote")
+        return candidate
+
+    def rollback(self, version: Optional[int] = None) -> CompiledPolicy:
+        """
+        Reactivate a superseded version (the most recent one by default).
+
+        Raises:
+            PolicyError: If no such version is kept
+        """
+        with self._lock:
+            candidates = [p for p in self._history if version is None or p.version == version]
+            if not candidates:
+                raise PolicyError(
+                    "No earlier policy to roll back to" if version is None
+                    else f"Policy version {version} is not available for rollback"
+                )
+            policy = candidates[-1]
+            self._history.remove(policy)
+            old = self._active
+            self._swap(policy)
+        self._notify(old.version if old else None, policy.version, "rollback")
+        return policy
+
+    def load_file(self, bundle_path: str, signature_path: Optional[str] = None) -> CompiledPolicy:
+        """Load a bundle from disk; the signature defaults to '<bundle_path>.sig'."""
+        with open(bundle_path, 'rb') as f:
+            bundle = f.read()
+        with open(signature_path or f"{bundle_path}.sig", 'rb') as f:
+            signature = f.read()
+        return self.load(bundle, signature)
+
+    def watch(self, bundle_path: str, interval: float = 5.0,
+              signature_path: Optional[str] = None):
+        """
+        Poll a bundle file in a background thread and hot-reload it when it changes.
+        Rejected bundles are logged and the active policy stays in place.
+        """
+        self.stop_watching()
+        self._watch_stop.clear()
+        self._watched_mtime = None
+        signature_path = signature_path or f"{bundle_path}.sig"
+
+        def poll():
+            while not self._watch_stop.is_set():
+                try:
+                    # Both files count: a bundle written before its signature is retried
+                    # once the signature lands
+                    mtime = (os.stat(bundle_path).st_mtime_ns, os.stat(signature_path).st_mtime_ns)
+                    if mtime != self._watched_mtime:
+                        self._watched_mtime = mtime
+                        self.load_file(bundle_path, signature_path)
+                except (OSError, PolicyError) as e:
+                    self.logger.warning(f"Policy reload from {bundle_path} skipped: {e}")
+                self._watch_stop.wait(interval)
+
+        self._watch_thread = threading.Thread(target=poll, name="policy-watch", daemon=True)
+        self._watch_thread.start()
+
+    def stop_watching(self):
+        """Stop the background watcher, if any."""
+        if self._watch_thread is not None:
+            self._watch_stop.set()
+            self._watch_thread.join()
+            self._watch_thread = None
+
+    def _swap(self, policy: CompiledPolicy):
+        """Activate a policy; caller holds the lock."""
+        if self._active is not None:
+            self._history.append(self._active)
+            self._history = self._history[-self.history_size:]
+        self._active = policy
+        self.logger.info(f"Activated policy version {policy.version} ({policy.digest[:16]})")
+
+    def _evaluate_shadow(self, shadow: CompiledPolicy, context: Dict[str, Any],
+                         decision: PolicyDecision):
+        """Run the candidate and audit any divergence; never raises."""
+        try:
+            candidate = shadow.evaluate(context)
+        except Exception as e:
+            with self._lock:
+                self._shadow_stats["evaluations"] += 1
+                self._shadow_stats["errors"] += 1
+            self.logger.warning(f"Shadow policy {shadow.version} failed: {e}")
+            return
+
+        diverged = (candidate.action, candidate.rule_id) != (decision.action, decision.rule_id)
+        with self._lock:
+            self._shadow_stats["evaluations"] += 1
+            if diverged:
+                self._shadow_stats["divergences"] += 1
+        if not diverged or not self.audit_logger:
+            return
+        try:
+            # The context itself may hold personal data; the digest is enough to correlate
+            context_digest = hashlib.sha256(
+                json.dumps(context, sort_keys=True, default=str).encode()).hexdigest()
+            self.audit_logger.add_entry({
+                "event": "policy_shadow_divergence",
+                "active_version": decision.policy_version,
+                "active_action": decision.action,
+                "active_rule": decision.rule_id,
+                "candidate_version": candidate.policy_version,
+                "candidate_action": candidate.action,
+                "candidate_rule": candidate.rule_id,
+                "context_digest": context_digest
+            })
+        except Exception as e:
+            self.logger.warning(f"Could not log shadow divergence: {e}")
+
+    def _verify_signature(self, bundle: bytes, signature: bytes):
+        if reliquary_encryptor is None:
+            raise PolicyError("reliquary_encryptor is required to verify policy signatures")
+        try:
+            if self.falcon_params is None:
+                valid = reliquary_encryptor.verify_falcon(bundle, signature, self.falcon_public_key)
+            else:
+                valid = reliquary_encryptor.verify_falcon(
+                    bundle, signature, self.falcon_public_key, self.falcon_params)
+        except ValueError as e:
+            raise PolicyError(f"Policy signature check failed: {e}")
+        if not valid:
+            raise PolicyError("Policy bundle signature is invalid")
+
+    def _notify(self, old_version: Optional[int], new_version: int, reason: str):
+        for listener in list(self._listeners):
+            try:
+                listener(old_version, new_version, reason)
+            except Exception as e:
+                self.logger.error(f"Policy change listener failed: {e}")
@@ -0,0 +1,418 @@
+"""
+Hardened parsing and out-of-process verification of untrusted inputs.
+
+Proofs, envelopes and certificates arriving from other parties are attacker-controlled. In
+hardened mode every input is held to strict ceilings before it is parsed: total size, JSON
+nesting depth (checked on the raw text, so the parser never recurses deeply), number of JSON
+values, string length, and in reliquary_encryptor the number of recipient slots, bundle layers
+and signers. Duplicate JSON keys and non-finite numbers are rejected outright.
+
+For inputs that should not be parsed in-process at all, SandboxedVerifier runs them through a
+spawned worker. The worker starts with a minimal environment, read-only mode locked on, no
+file writes (RLIMIT_FSIZE 0) and a memory ceiling, and accepts only the operations in
+SANDBOX_OPS. Each request and response is one frame: a 4-byte big-endian length followed by
+UTF-8 JSON, with bytes arguments base64-encoded. A worker that times out, crashes or breaks
+the protocol is killed and replaced on the next call.
+"""
+
+import base64
+import json
+import logging
+import os
+import re
+import select
+import struct
+import subprocess
+import sys
+import threading
+import time
+from dataclasses import asdict, dataclass
+from typing import Any, Callable, Dict, Optional, Tuple
+
+try:
+    import resource
+except ImportError:  # Not available on Windows
+    resource = None
+
+try:
+    import reliquary_encryptor
+except ImportError:
+    reliquary_encryptor = None
+
+PROJECT_ROOT = os.path.dirname(os.path.dirname(os.path.abspath(__file__)))
+MAX_FRAME_BYTES = 16 * 1024 * 1024
+
+_STRING = re.compile(r'"(?:[^"\\]|\\.)*"', re.DOTALL)
+_BRACKET = re.compile(r"[\[\]{}]")
+
+
+class SandboxError(Exception):
+    """Raised when the sandbox worker fails, times out or breaks the protocol"""
+
+
+class InputLimitError(ValueError):
+    """Raised when an untrusted input exceeds a hardened parsing limit"""
+
+
+@dataclass
+class ParseLimits:
+    """Ceilings applied to untrusted inputs in hardened mode"""
+    max_input_bytes: int = 1024 * 1024
+    max_depth: int = 32
+    max_items: int = 100_000  # JSON values in one document
+    max_string_bytes: int = 64 * 1024
+    max_binary_items: int = 64  # Recipient slots, bundle layers, signers (Rust parsers)
+
+
+_lock = threading.Lock()
+_hardened: Dict[str, Optional[ParseLimits]] = {"limits": None}
+
+
+def enable_hardened_parsing(limits: Optional[ParseLimits] = None):
+    """
+    Turn hardened parsing on for the whole process, here and in reliquary_encryptor.
+
+    Args:
+        limits: Ceilings to enforce; defaults to ParseLimits()
+    """
+    limits = limits or ParseLimits()
+    with _lock:
+        if reliquary_encryptor is not None:
+            reliquary_encryptor.set_hardened_parsing(
+                True, limits.max_input_bytes, limits.max_binary_items)
+        _hardened["limits"] = limits
+
+
+def disable_hardened_parsing():
+    """Turn hardened parsing off."""
+    with _lock:
+        if reliquary_encryptor is not None:
+            reliquary_encryptor.set_hardened_parsing(False)
+        _hardened["limits"] = None
+
+
+def hardened_limits() -> Optional[ParseLimits]:
+    """The limits in force, or None when hardened parsing is off."""
+    return _hardened["limits"]
+
+
+def check_input_size(what: str, data: Any, limits: Optional[ParseLimits] = None):
+    """
+    Refuse an input larger than the size ceiling.
+
+    Args:
+        what: Name of the input, for the error message
+        data: Bytes or str to check
+        limits: Limits to apply; defaults to the hardened limits, and nothing is checked when
+            hardened parsing is off
+
+    Raises:
+        InputLimitError: If the input is too large
+    """
+    limits = limits or hardened_limits()
+    if limits is not None and len(data) > limits.max_input_bytes:
+        raise InputLimitError(
+            f"{what} is {len(data)} bytes, over the limit of {limits.max_input_bytes}")
+
+
+def _reject_constant(name: str):
+    raise InputLimitError(f"Non-finite number {name} is not allowed")
+
+
+def _unique_keys(pairs):
+    obj = {}
+    for key, value in pairs:
+        if key in obj:
+            raise InputLimitError(f"Duplicate key {key!r}")
+        obj[key] = value
+    return obj
+
+
+def parse_json(data: Any, limits: Optional[ParseLimits] = None) -> Any:
+    """
+    Parse untrusted JSON within hard limits.
+
+    Size and nesting depth are checked on the raw text before json.loads runs; value count and
+    string lengths are checked on the result. Unlike check_input_size this always enforces
+    limits, falling back to ParseLimits() when hardened parsing is off.
+
+    Args:
+        data: JSON text as bytes or str
+        limits: Limits to apply; defaults to the hardened limits or ParseLimits()
+
+    Returns:
+        The parsed value
+
+    Raises:
+        InputLimitError: If the input exceeds a limit, repeats a key or contains NaN/Infinity
+        ValueError: If the input isn't valid JSON
+    """
+    limits = limits or hardened_limits() or ParseLimits()
+    check_input_size("JSON input", data, limits)
+    text = data.decode("utf-8") if isinstance(data, (bytes, bytearray)) else data
+
+    depth = 0
+    for bracket in _BRACKET.finditer(_STRING.sub("", text)):
+        depth += 1 if bracket.group() in "[{" else -1
+        if depth > limits.max_depth:
+            raise InputLimitError(f"JSON nesting is deeper than {limits.max_depth}")
+
+    value = json.loads(text, object_pairs_hook=_unique_keys, parse_constant=_reject_constant)
+
+    count, stack = 0, [value]
+    while stack:
+        item = stack.pop()
+        count += 1
+        if count > limits.max_items:
+            raise InputLimitError(f"JSON holds more than {limits.max_items} values")
+        if isinstance(item, dict):
+            stack.extend(item.keys())
+            stack.extend(item.values())
+        elif isinstance(item, list):
+            stack.extend(item)
+        elif isinstance(item, str) and len(item.encode("utf-8")) > limits.max_string_bytes:
+            raise InputLimitError(f"JSON string is longer than {limits.max_string_bytes} bytes")
+    return value
+
+
+def _verify_merkle_proof(leaf: bytes, proof: list, root: bytes, index: int) -> bool:
+    from core.merkle_logging.merkle import verify_merkle_proof
+    return verify_merkle_proof(leaf, proof, root, index)
+
+
+def _encryptor(name: str) -> Callable:
+    def call(*args):
+        if reliquary_encryptor is None:
+            raise RuntimeError("reliquary_encryptor is not available in the sandbox")
+        return getattr(reliquary_encryptor, name)(*args)
+    return call
+
+
+# Operation name -> (handler, argument schema). Schema types are "bytes", "bytes?" (or None),
+# "bytes[]" and "int"; nothing else crosses the process boundary.
+SANDBOX_OPS: Dict[str, Tuple[Callable, Tuple[Tuple[str, str], ...]]] = {
+    "parse_json": (lambda data: parse_json(data), (("data", "bytes"),)),
+    "validate_envelope": (_encryptor("validate_envelope"), (("envelope", "bytes"),)),
+    "validate_vrf_proof": (_encryptor("validate_vrf_proof"),
+                           (("proof", "bytes"), ("pk", "bytes?"))),
+    "validate_quorum_certificate": (_encryptor("validate_quorum_certificate"),
+                                    (("certificate", "bytes"),)),
+    "verify_merkle_proof": (_verify_merkle_proof, (
+        ("leaf", "bytes"), ("proof", "bytes[]"), ("root", "bytes"), ("index", "int"))),
+}
+
+
+def _encode_arg(kind: str, value: Any) -> Any:
+    if kind == "bytes?" and value is None:
+        return None
+    if kind in ("bytes", "bytes?"):
+        return base64.b64encode(bytes(value)).decode("ascii")
+    if kind == "bytes[]":
+        return [base64.b64encode(bytes(v)).decode("ascii") for v in value]
+    return int(value)
+
+
+def _decode_arg(kind: str, value: Any) -> Any:
+    if kind == "bytes?" and value is None:
+        return None
+    if kind in ("bytes", "bytes?") and isinstance(value, str):
+        return base64.b64decode(value, validate=True)
+    if kind == "bytes[]" and isinstance(value, list) and all(isinstance(v, str) for v in value):
+        return [base64.b64decode(v, validate=True) for v in value]
+    if kind == "int" and isinstance(value, int) and not isinstance(value, bool):
+        return value
+    raise ValueError(f"Argument is not {kind}")
+
+
+def _write_frame(stream, message: Dict[str, Any]):
+    payload = json.dumps(message).encode("utf-8")
+    stream.write(struct.pack(">I", len(payload)) + payload)
+    stream.flush()
+
+
+def _read_frame_blocking(stream) -> Optional[Dict[str, Any]]:
+    header = stream.read(4)
+    if len(header) < 4:
+        return None
+    (length,) = struct.unpack(">I", header)
+    if length > MAX_FRAME_BYTES:
+        raise ValueError("Frame is too large")
+    return json.loads(stream.read(length))
+
+
+def _handle(request: Dict[str, Any]) -> Dict[str, Any]:
+    op = SANDBOX_OPS.get(request.get("op"))
+    if op is None or not isinstance(request.get("args"), dict):
+        return {"ok": False, "error": "ValueError: unknown operation or malformed request"}
+    handler, schema = op
+    args = request["args"]
+    if set(args) - {name for name, _ in schema}:
+        return {"ok": False, "error": "ValueError: unexpected arguments"}
+    try:
+        result = handler(*[_decode_arg(kind, args.get(name)) for name, kind in schema])
+        return {"ok": True, "result": result}
+    except Exception as e:
+        return {"ok": False, "error": f"{type(e).__name__}: {e}"}
+
+
+def _worker_main():
+    """Sandbox worker loop: a limits frame, then one response per request frame."""
+    stdin, stdout = sys.stdin.buffer, sys.stdout.buffer
+    # Anything a handler prints must not corrupt the protocol stream
+    sys.stdout = sys.stderr
+    from core.read_only import enable_read_only_mode
+    enable_read_only_mode(lock=True)
+    config = _read_frame_blocking(stdin)
+    if config is None:
+        return
+    enable_hardened_parsing(ParseLimits(**config["limits"]))
+    while True:
+        try:
+            request = _read_frame_blocking(stdin)
+        except ValueError:
+            return
+        if request is None:
+            return
+        _write_frame(stdout, _handle(request))
+
+
+class SandboxedVerifier:
+    """Runs untrusted parsing and verification in a restricted worker process"""
+
+    def __init__(self, limits: Optional[ParseLimits] = None, timeout: float = 10.0,
+                 memory_bytes: int = 512 * 1024 * 1024):
+        """
+        Initialize the verifier; the worker is started on first use.
+
+        Args:
+            limits: Hardened parsing limits inside the worker; defaults to ParseLimits()
+            timeout: Seconds a single request may take before the worker is killed
+            memory_bytes: Address-space ceiling for the worker (POSIX only)
+        """
+        self.limits = limits or ParseLimits()
+        self.timeout = timeout
+        self.memory_bytes = memory_bytes
+        self.logger = logging.getLogger(__name__)
+        self._process: Optional[subprocess.Popen] = None
+        self._lock = threading.Lock()
+
+    def _restrict(self):
+        # Runs in the child between fork and exec
+        resource.setrlimit(resource.RLIMIT_AS, (self.memory_bytes, self.memory_bytes))
+        resource.setrlimit(resource.RLIMIT_FSIZE, (0, 0))
+        resource.setrlimit(resource.RLIMIT_CORE, (0, 0))
+
+    def _spawn(self):
+        paths = [PROJECT_ROOT] + [p for p in sys.path if p and p != PROJECT_ROOT]
+        env = {"PATH": os.defpath, "PYTHONPATH": os.pathsep.join(paths),
+               "RELIQUARY_READ_ONLY": "1", "PYTHONDONTWRITEBYTECODE": "1"}
+        self._process = subprocess.Popen(
+            [sys.executable, "-m", "core.sandbox"],
+            stdin=subprocess.PIPE, stdout=subprocess.PIPE, stderr=subprocess.DEVNULL,
+            env=env, cwd=PROJECT_ROOT, close_fds=True,
+            preexec_fn=self._restrict if resource is not None and os.name == "posix" else None)
+        _write_frame(self._process.stdin, {"limits": asdict(self.limits)})
+
+    def _read_exact(self, n: int, deadline: float) -> bytes:
+        out = b""
+        fd = self._process.stdout.fileno()
+        while len(out) < n:
+            remaining = deadline - time.monotonic()
+            if remaining <= 0 or not select.select([fd], [], [], remaining)[0]:
+                raise SandboxError(f"Sandbox worker timed out after {self.timeout}s")
+            chunk = os.read(fd, n - len(out))
+            if not chunk:
+                raise SandboxError("Sandbox worker exited unexpectedly")
+            out += chunk
+        return out
+
+    def _kill(self):
+        if self._process is not None:
+            self._process.kill()
+            self._process.wait()
+            self._process = None
+
+    def call(self, op: str, **args: Any) -> Any:
+        """
+        Run one operation from SANDBOX_OPS in the worker.
+
+        Args:
+            op: Operation name
+            **args: Arguments named as in the operation's schema
+
+        Returns:
+            The operation's result (JSON types only)
+
+        Raises:
+            ValueError: If the operation is unknown or the worker reports an error
+            SandboxError: If the worker times out, crashes or breaks the protocol
+        """
+        if op not in SANDBOX_OPS:
+            raise ValueError(f"Unknown sandbox operation {op!r}")
+        _, schema = SANDBOX_OPS[op]
+        request = {"op": op, "args": {name: _encode_arg(kind, args.get(name))
+                                      for name, kind in schema}}
+        with self._lock:
+            try:
+                if self._process is None or self._process.poll() is not None:
+                    self._spawn()
+                deadline = time.monotonic() + self.timeout
+                _write_frame(self._process.stdin, request)
+                (length,) = struct.unpack(">I", self._read_exact(4, deadline))
+                if length > MAX_FRAME_BYTES:
+                    raise SandboxError("Sandbox worker sent an oversized frame")
+                response = json.loads(self._read_exact(length, deadline))
+                if not isinstance(response, dict) or "ok" not in response:
+                    raise SandboxError("Sandbox worker sent a malformed response")
+            except (SandboxError, OSError, ValueError) as e:
+                self.logger.warning(f"Sandbox worker failed during {op}: {e}")
+                self._kill()
+                if isinstance(e, SandboxError):
+                    raise
+                raise SandboxError(f"Sandbox worker failed: {e}") from e
+        if not response["ok"]:
+            raise ValueError(response.get("error", "Sandbox operation failed"))
+        return response.get("result")
+
+    def parse_json(self, data: Any) -> Any:
+        """Parse untrusted JSON in the worker."""
+        return self.call("parse_json", data=data.encode("utf-8") if isinstance(data, str) else data)
+
+    def validate_envelope(self, envelope: bytes) -> list:
+        """reliquary_encryptor.validate_envelope, run in the worker."""
+        return [tuple(finding) for finding in self.call("validate_envelope", envelope=envelope)]
+
+    def validate_vrf_proof(self, proof: bytes, pk: Optional[bytes] = None) -> list:
+        """reliquary_encryptor.validate_vrf_proof, run in the worker."""
+        return [tuple(finding) for finding in self.call("validate_vrf_proof", proof=proof, pk=pk)]
+
+    def validate_quorum_certificate(self, certificate: bytes) -> list:
+        """reliquary_encryptor.validate_quorum_certificate, run in the worker."""
+        return [tuple(finding)
+                for finding in self.call("validate_quorum_certificate", certificate=certificate)]
+
+    def verify_merkle_proof(self, leaf: bytes, proof: list, root: bytes, index: int) -> bool:
+        """core.merkle_logging.merkle.verify_merkle_proof, run in the worker."""
+        return self.call("verify_merkle_proof", leaf=leaf, proof=proof, root=root, index=index)
+
+    def close(self):
+        """Stop the worker."""
+        with self._lock:
+            if self._process is not None:
+                self._process.stdin.close()
+                try:
+                    self._process.wait(timeout=self.timeout)
+                except subprocess.TimeoutExpired:
+                    self._process.kill()
+                    self._process.wait()
+                self._process = None
+
+    def __enter__(self):
+        return self
+
+    def __exit__(self, *exc):
+        self.close()
+
+
+if __name__ == "__main__":
+    _worker_main()
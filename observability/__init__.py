@@ -60,6 +60,13 @@ from .integration_manager import (
     setup_basic_observability
 )
 
+from .webhooks import (
+    WebhookSigner,
+    WebhookVerifier,
+    WebhookSignatureError,
+    SIGNATURE_HEADER
+)
+
 from .grafana_dashboards import (
     GrafanaDashboardConfig,
     GRAFANA_ALERT_RULES,
@@ -93,6 +100,12 @@ __all__ = [
     "Alert",
     "AlertRule",
     
+    # Webhook signing
+    "WebhookSigner",
+    "WebhookVerifier",
+    "WebhookSignatureError",
+    "SIGNATURE_HEADER",
+    
     # Dashboard integration
     "GrafanaDashboardConfig",
     "GRAFANA_ALERT_RULES",
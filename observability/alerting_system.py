@@ -16,6 +16,8 @@ from enum import Enum
 from collections import defaultdict, deque
 import uuid
 
+from .webhooks import WebhookSigner
+
 
 class AlertPriority(Enum):
     """Alert priority levels"""
@@ -72,8 +74,10 @@ class Alert:
 class IntelligentAlertManager:
     """Intelligent alerting system with correlation and automation"""
     
-    def __init__(self, manager_id: str = "alert_manager_v1"):
+    def __init__(self, manager_id: str = "alert_manager_v1",
+                 webhook_signer: Optional[WebhookSigner] = None):
         self.manager_id = manager_id
+        self.webhook_signer = webhook_signer
         self.logger = logging.getLogger(f"alert_manager.{manager_id}")
         
         self.alert_rules = {}
@@ -265,7 +269,9 @@ class IntelligentAlertManager:
             "current_value": alert.current_value,
             "threshold_value": alert.threshold_value
         }
-        self.logger.info(f"WEBHOOK: {json.dumps(payload)}")
+        body = json.dumps(payload).encode("utf-8")
+        headers = self.webhook_signer.headers(body) if self.webhook_signer else {}
+        self.logger.info(f"WEBHOOK: {body.decode()} headers={headers}")
     
     async def _execute_automated_responses(self, alert_id: str, responses: List[str]):
         """Execute automated responses for alert"""
@@ -0,0 +1,230 @@
+"""
+Webhook Signing for ReliQuary Notifications
+
+Outbound webhook bodies are signed so receivers can check where they came from and when.
+The signature header follows the Stripe layout::
+
+    ReliQuary-Signature: t=1760486400,v1=k2:5257a869...,v1=k1:9f86d081...,f1=pq1:Q2x1Yi...
+
+`t` is the signing time in Unix seconds. Each `v1` is a hex HMAC-SHA256 and each `f1` a
+base64 Falcon signature over ``f"{t}.".encode() + body``, prefixed with the id of the key that
+made it. During a key rotation the sender signs with both keys, so receivers can move to the
+new key at their own pace; a receiver accepts the body if any signature from a key it knows
+verifies and the timestamp is within tolerance.
+"""
+
+import base64
+import hashlib
+import hmac
+import logging
+import threading
+import time
+from typing import Any, Callable, Dict, List, Optional, Tuple
+
+try:
+    import reliquary_encryptor
+except ImportError:
+    reliquary_encryptor = None
+
+SIGNATURE_HEADER = "ReliQuary-Signature"
+HMAC_SCHEME = "v1"
+FALCON_SCHEME = "f1"
+DEFAULT_TOLERANCE_SECONDS = 300
+
+
+class WebhookSignatureError(Exception):
+    """Raised when a webhook signature header is malformed, stale or doesn't verify"""
+
+
+def _signed_content(timestamp: int, payload: bytes) -> bytes:
+    return f"{timestamp}.".encode("ascii") + payload
+
+
+def _check_key_id(key_id: str):
+    if not key_id or any(c in key_id for c in ",:= "):
+        raise ValueError(f"Invalid webhook key id: {key_id!r}")
+
+
+def _falcon_params(algorithm: str) -> Any:
+    if reliquary_encryptor is None:
+        raise WebhookSignatureError("reliquary_encryptor is required for Falcon webhook signatures")
+    if algorithm == "falcon512":
+        return reliquary_encryptor.FalconParams.Falcon512
+    if algorithm == "falcon1024":
+        return reliquary_encryptor.FalconParams.Falcon1024
+    raise ValueError(f"Unsupported Falcon algorithm: {algorithm}")
+
+
+def parse_signature_header(header: str) -> Tuple[int, List[Tuple[str, str, str]]]:
+    """
+    Split a signature header into its timestamp and (scheme, key_id, signature) entries.
+
+    Entries with unknown schemes are kept so callers can ignore them; a missing or repeated
+    timestamp is an error.
+    """
+    timestamp = None
+    signatures = []
+    for part in header.split(","):
+        name, sep, value = part.strip().partition("=")
+        if not sep or not value:
+            raise WebhookSignatureError("Malformed webhook signature header")
+        if name == "t":
+            if timestamp is not None or not value.isdigit():
+                raise WebhookSignatureError("Invalid webhook signature timestamp")
+            timestamp = int(value)
+        else:
+            key_id, sep, signature = value.partition(":")
+            if not sep or not key_id or not signature:
+                raise WebhookSignatureError("Webhook signature is missing its key id")
+            signatures.append((name, key_id, signature))
+    if timestamp is None:
+        raise WebhookSignatureError("Webhook signature header has no timestamp")
+    return timestamp, signatures
+
+
+class WebhookSigner:
+    """
+    Signs outbound webhook bodies with every active key.
+
+    HMAC keys are shared secrets per receiver; Falcon keys (raw secret keys or a
+    SecretKeyHandle) let any receiver verify with the public key alone.
+    """
+
+    def __init__(self, falcon_algorithm: str = "falcon1024"):
+        self.falcon_algorithm = falcon_algorithm
+        self.logger = logging.getLogger(__name__)
+        self._hmac_keys: Dict[str, bytes] = {}
+        self._falcon_keys: Dict[str, Any] = {}
+        self._lock = threading.Lock()
+
+    @property
+    def key_ids(self) -> List[str]:
+        with self._lock:
+            return sorted(set(self._hmac_keys) | set(self._falcon_keys))
+
+    def add_hmac_key(self, key_id: str, secret: bytes):
+        """Start signing with an HMAC secret (at least 32 bytes)."""
+        _check_key_id(key_id)
+        if len(secret) < 32:
+            raise ValueError("Webhook HMAC secrets must be at least 32 bytes")
+        with self._lock:
+            self._hmac_keys[key_id] = bytes(secret)
+
+    def add_falcon_key(self, key_id: str, secret_key: Any):
+        """Start signing with a Falcon secret key or SecretKeyHandle."""
+        _check_key_id(key_id)
+        _falcon_params(self.falcon_algorithm)
+        with self._lock:
+            self._falcon_keys[key_id] = secret_key
+
+    def remove_key(self, key_id: str) -> bool:
+        """Stop signing with a key, e.g. once receivers have moved to its successor."""
+        with self._lock:
+            removed = self._hmac_keys.pop(key_id, None) or self._falcon_keys.pop(key_id, None)
+        return removed is not None
+
+    def sign(self, payload: bytes, timestamp: Optional[int] = None) -> str:
+        """
+        Build the signature header value for a webhook body.
+
+        Args:
+            payload: Exact body bytes that will be sent
+            timestamp: Signing time (defaults to now)
+
+        Returns:
+            Header value with one signature per active key
+        """
+        timestamp = int(time.time()) if timestamp is None else int(timestamp)
+        content = _signed_content(timestamp, payload)
+        with self._lock:
+            hmac_keys = sorted(self._hmac_keys.items())
+            falcon_keys = sorted(self._falcon_keys.items())
+        if not hmac_keys and not falcon_keys:
+            raise WebhookSignatureError("No webhook signing keys configured")
+
+        parts = [f"t={timestamp}"]
+        for key_id, secret in hmac_keys:
+            digest = hmac.new(secret, content, hashlib.sha256).hexdigest()
+            parts.append(f"{HMAC_SCHEME}={key_id}:{digest}")
+        for key_id, secret_key in falcon_keys:
+            signature = reliquary_encryptor.sign_falcon(
+                content, secret_key, _falcon_params(self.falcon_algorithm))
+            parts.append(f"{FALCON_SCHEME}={key_id}:{base64.b64encode(bytes(signature)).decode()}")
+        return ",".join(parts)
+
+    def headers(self, payload: bytes, timestamp: Optional[int] = None) -> Dict[str, str]:
+        """HTTP headers to send with a webhook body."""
+        return {SIGNATURE_HEADER: self.sign(payload, timestamp)}
+
+
+class WebhookVerifier:
+    """
+    Verification helper for webhook receivers.
+
+    Configure the HMAC secrets and Falcon public keys the receiver trusts, by key id; keys
+    the receiver doesn't know are skipped, which is what makes rotation seamless.
+    """
+
+    def __init__(self, hmac_keys: Optional[Dict[str, bytes]] = None,
+                 falcon_public_keys: Optional[Dict[str, bytes]] = None,
+                 falcon_algorithm: str = "falcon1024",
+                 tolerance_seconds: int = DEFAULT_TOLERANCE_SECONDS,
+                 require_falcon: bool = False,
+                 clock: Callable[[], float] = time.time):
+        """
+        Initialize the verifier.
+
+        Args:
+            hmac_keys: Key id -> shared HMAC secret
+            falcon_public_keys: Key id -> Falcon public key
+            falcon_algorithm: "falcon512" or "falcon1024"
+            tolerance_seconds: Largest accepted age (or clock lead) of the timestamp
+            require_falcon: Only accept Falcon signatures, e.g. for non-repudiation
+            clock: Current time source
+        """
+        self.hmac_keys = dict(hmac_keys or {})
+        self.falcon_public_keys = dict(falcon_public_keys or {})
+        self.falcon_algorithm = falcon_algorithm
+        self.tolerance_seconds = tolerance_seconds
+        self.require_falcon = require_falcon
+        self.clock = clock
+
+    def verify(self, payload: bytes, header: Optional[str]) -> str:
+        """
+        Check a received webhook body against its signature header.
+
+        Args:
+            payload: Raw body bytes as received (before any JSON parsing)
+            header: Value of the ReliQuary-Signature header
+
+        Returns:
+            Id of the key whose signature verified
+
+        Raises:
+            WebhookSignatureError: If the header is missing, malformed, stale or no trusted
+                signature verifies
+        """
+        if not header:
+            raise WebhookSignatureError(f"Missing {SIGNATURE_HEADER} header")
+        timestamp, signatures = parse_signature_header(header)
+        if abs(self.clock() - timestamp) > self.tolerance_seconds:
+            raise WebhookSignatureError("Webhook timestamp is outside the tolerance window")
+
+        content = _signed_content(timestamp, payload)
+        for scheme, key_id, signature in signatures:
+            if scheme == HMAC_SCHEME and not self.require_falcon and key_id in self.hmac_keys:
+                expected = hmac.new(self.hmac_keys[key_id], content, hashlib.sha256).hexdigest()
+                if hmac.compare_digest(expected, signature):
+                    return key_id
+            elif scheme == FALCON_SCHEME and key_id in self.falcon_public_keys:
+                if self._verify_falcon(content, signature, self.falcon_public_keys[key_id]):
+                    return key_id
+        raise WebhookSignatureError("No webhook signature from a trusted key verified")
+
+    def _verify_falcon(self, content: bytes, signature: str, public_key: bytes) -> bool:
+        try:
+            raw = base64.b64decode(signature, validate=True)
+            return reliquary_encryptor.verify_falcon(
+                content, raw, public_key, _falcon_params(self.falcon_algorithm))
+        except ValueError:
+            return False
@@ -0,0 +1,167 @@
+use hkdf::Hkdf;
+use pyo3::prelude::*;
+use sha2::{Digest, Sha256};
+use zeroize::Zeroizing;
+
+use crate::fingerprint::{fingerprint, kyber_public_key_from_secret};
+use crate::handles::{DecryptionKeyArg, SigningKeyArg};
+use crate::params::{FalconParams, KyberParams};
+
+// Authenticated encapsulation layout (v1):
+//   magic "RQAK" | version u8 | kem id u8 | falcon logn u8 | kem ciphertext | falcon signature
+// The signature is detached and covers the transcript built by `transcript`.
+const MAGIC: &[u8; 4] = b"RQAK";
+const VERSION: u8 = 1;
+const HEADER_BYTES: usize = 7;
+const TRANSCRIPT_LABEL: &[u8] = b"reliquary/akem/v1/transcript";
+const SECRET_LABEL: &[u8] = b"reliquary/akem/v1/shared-secret";
+
+/// Upper bound on the authenticated ciphertext size (Falcon signatures vary in length).
+pub(crate) fn max_encapsulation_len(
+    kyber_params: KyberParams,
+    falcon_params: FalconParams,
+) -> usize {
+    HEADER_BYTES + kyber_params.ciphertext_bytes() + falcon_params.signature_bytes()
+}
+
+/// Binds the KEM ciphertext (via the header), both parties' key fingerprints and the caller's
+/// context string.
+fn transcript(
+    header_and_ct: &[u8],
+    recipient_pk: &[u8],
+    sender_pk: &[u8],
+    context: &[u8],
+) -> Vec<u8> {
+    let mut t =
+        Vec::with_capacity(TRANSCRIPT_LABEL.len() + header_and_ct.len() + 68 + context.len());
+    t.extend_from_slice(TRANSCRIPT_LABEL);
+    t.extend_from_slice(header_and_ct);
+    t.extend_from_slice(&fingerprint(recipient_pk));
+    t.extend_from_slice(&fingerprint(sender_pk));
+    t.extend_from_slice(&(context.len() as u32).to_be_bytes());
+    t.extend_from_slice(context);
+    t
+}
+
+/// Mixes the transcript into the KEM secret so both sides only agree on a key when they agree
+/// on identities and context.
+fn derive_secret(shared_secret: &[u8], transcript: &[u8]) -> Vec<u8> {
+    let mut key = vec![0u8; 32];
+    Hkdf::<Sha256>::new(Some(&Sha256::digest(transcript)), shared_secret)
+        .expand(SECRET_LABEL, &mut key)
+        .expect("32 bytes is a valid HKDF-SHA256 output length");
+    key
+}
+
+/// Authenticated Kyber encapsulation. The sender's Falcon key signs a transcript of the KEM
+/// ciphertext, the recipient and sender key fingerprints and `context`.
+/// Returns (shared_secret, auth_ciphertext); the 32-byte secret is already bound to the transcript
+#[pyfunction]
+#[pyo3(signature = (
+    recipient_pk,
+    sender_falcon_sk,
+    sender_falcon_pk,
+    context,
+    kyber_params = KyberParams::Kyber1024,
+    falcon_params = FalconParams::Falcon1024,
+))]
+pub fn auth_encapsulate_kyber(
+    py: Python<'_>,
+    recipient_pk: Vec<u8>,
+    sender_falcon_sk: SigningKeyArg,
+    sender_falcon_pk: Vec<u8>,
+    context: Vec<u8>,
+    kyber_params: KyberParams,
+    falcon_params: FalconParams,
+) -> PyResult<(Vec<u8>, Vec<u8>)> {
+    let falcon_params = sender_falcon_sk.falcon_params(falcon_params)?;
+    py.allow_threads(|| {
+        let (shared_secret, kem_ct) = crate::kyber_encapsulate(&recipient_pk, kyber_params)?;
+        let shared_secret = Zeroizing::new(shared_secret);
+
+        let mut out = Vec::with_capacity(max_encapsulation_len(kyber_params, falcon_params));
+        out.extend_from_slice(MAGIC);
+        out.push(VERSION);
+        out.push(kyber_params.wire_id());
+        out.push(falcon_params.logn());
+        out.extend_from_slice(&kem_ct);
+
+        let t = transcript(&out, &recipient_pk, &sender_falcon_pk, &context);
+        let signature = crate::falcon_sign_detached(&t, &sender_falcon_sk, falcon_params)?;
+        // Falcon secret keys don't embed the public key; a mismatched pair would only yield
+        // ciphertexts every recipient rejects, so catch it here.
+        if !crate::falcon_verify_detached(&t, &signature, &sender_falcon_pk, falcon_params)? {
+            return Err(pyo3::exceptions::PyValueError::new_err(
+                "Sender Falcon public key does not match the secret key",
+            ));
+        }
+        out.extend_from_slice(&signature);
+        Ok((derive_secret(&shared_secret, &t), out))
+    })
+}
+
+/// Authenticated Kyber decapsulation. Verifies the sender's signature over the transcript before
+/// returning the shared secret; raises ValueError if the sender, recipient or context don't match
+#[pyfunction]
+pub fn auth_decapsulate_kyber(
+    py: Python<'_>,
+    auth_ciphertext: Vec<u8>,
+    recipient_sk: DecryptionKeyArg,
+    sender_falcon_pk: Vec<u8>,
+    context: Vec<u8>,
+) -> PyResult<Vec<u8>> {
+    py.allow_threads(|| {
+        if auth_ciphertext.len() < HEADER_BYTES || &auth_ciphertext[..4] != MAGIC {
+            return Err(pyo3::exceptions::PyValueError::new_err(
+                "Not an authenticated KEM ciphertext",
+            ));
+        }
+        if auth_ciphertext[4] != VERSION {
+            return Err(pyo3::exceptions::PyValueError::new_err(format!(
+                "Unsupported authenticated KEM version {}",
+                auth_ciphertext[4]
+            )));
+        }
+        let kyber_params = KyberParams::from_wire_id(auth_ciphertext[5]).ok_or_else(|| {
+            pyo3::exceptions::PyValueError::new_err(format!(
+                "Unknown KEM id {}",
+                auth_ciphertext[5]
+            ))
+        })?;
+        let falcon_params = FalconParams::from_logn(auth_ciphertext[6]).ok_or_else(|| {
+            pyo3::exceptions::PyValueError::new_err(format!(
+                "Unknown Falcon parameter set (logn {})",
+                auth_ciphertext[6]
+            ))
+        })?;
+        let ct_end = HEADER_BYTES + kyber_params.ciphertext_bytes();
+        if auth_ciphertext.len() <= ct_end {
+            return Err(pyo3::exceptions::PyValueError::new_err(
+                "Authenticated KEM ciphertext is truncated",
+            ));
+        }
+        if recipient_sk.len() != kyber_params.secret_key_bytes() {
+            return Err(pyo3::exceptions::PyValueError::new_err(format!(
+                "Invalid secret key length. Expected {}, got {}",
+                kyber_params.secret_key_bytes(),
+                recipient_sk.len()
+            )));
+        }
+
+        let (header_and_ct, signature) = auth_ciphertext.split_at(ct_end);
+        let recipient_pk = kyber_public_key_from_secret(&recipient_sk, kyber_params);
+        let t = transcript(header_and_ct, recipient_pk, &sender_falcon_pk, &context);
+        if !crate::falcon_verify_detached(&t, signature, &sender_falcon_pk, falcon_params)? {
+            return Err(pyo3::exceptions::PyValueError::new_err(
+                "Sender authentication failed (wrong sender key, recipient or context)",
+            ));
+        }
+
+        let shared_secret = Zeroizing::new(crate::kyber_decapsulate(
+            &header_and_ct[HEADER_BYTES..],
+            &recipient_sk,
+            kyber_params,
+        )?);
+        Ok(derive_secret(&shared_secret, &t))
+    })
+}
@@ -0,0 +1,207 @@
+use std::collections::BTreeMap;
+
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use pyo3::prelude::*;
+
+use crate::limits;
+
+const LINE_WIDTH: usize = 64;
+const CRC24_INIT: u32 = 0x00B7_04CE;
+const CRC24_POLY: u32 = 0x0186_4CFB;
+
+/// Artifact types that can be armored. The label appears in the BEGIN/END lines, so a text
+/// blob always says what it contains.
+#[pyclass(eq, eq_int, module = "reliquary_encryptor")]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum ArmorType {
+    PublicKey,
+    SecretKey,
+    Signature,
+    Ciphertext,
+    Envelope,
+    MerkleProof,
+    Share,
+}
+
+const ARMOR_TYPES: [ArmorType; 7] = [
+    ArmorType::PublicKey,
+    ArmorType::SecretKey,
+    ArmorType::Signature,
+    ArmorType::Ciphertext,
+    ArmorType::Envelope,
+    ArmorType::MerkleProof,
+    ArmorType::Share,
+];
+
+#[pymethods]
+impl ArmorType {
+    /// Label used in the BEGIN/END lines, e.g. "MERKLE PROOF"
+    #[getter]
+    pub fn label(&self) -> &'static str {
+        match self {
+            ArmorType::PublicKey => "PUBLIC KEY",
+            ArmorType::SecretKey => "SECRET KEY",
+            ArmorType::Signature => "SIGNATURE",
+            ArmorType::Ciphertext => "CIPHERTEXT",
+            ArmorType::Envelope => "ENVELOPE",
+            ArmorType::MerkleProof => "MERKLE PROOF",
+            ArmorType::Share => "SHARE",
+        }
+    }
+}
+
+impl ArmorType {
+    fn from_label(label: &str) -> Option<Self> {
+        ARMOR_TYPES.into_iter().find(|kind| kind.label() == label)
+    }
+}
+
+/// CRC-24 as used by OpenPGP armor (RFC 4880 section 6.1)
+fn crc24(data: &[u8]) -> u32 {
+    let mut crc = CRC24_INIT;
+    for &byte in data {
+        crc ^= (byte as u32) << 16;
+        for _ in 0..8 {
+            crc <<= 1;
+            if crc & 0x0100_0000 != 0 {
+                crc ^= CRC24_POLY;
+            }
+        }
+    }
+    crc & 0x00FF_FFFF
+}
+
+fn checksum_line(data: &[u8]) -> String {
+    format!("={}", STANDARD.encode(&crc24(data).to_be_bytes()[1..]))
+}
+
+/// Exact armored length for `data_len` bytes, where `header_bytes` is the total length of the
+/// header lines including their "Key: " prefix and newline.
+pub(crate) fn encoded_len(kind: ArmorType, data_len: usize, header_bytes: usize) -> usize {
+    let frame = "-----BEGIN RELIQUARY -----\n".len() + "-----END RELIQUARY -----\n".len();
+    let body = data_len.div_ceil(3) * 4;
+    frame + 2 * kind.label().len() + header_bytes + 1 + body + body.div_ceil(LINE_WIDTH) + 6
+}
+
+pub(crate) fn encode(
+    kind: ArmorType,
+    data: &[u8],
+    headers: &BTreeMap<String, String>,
+) -> Result<String, String> {
+    let mut out = format!("-----BEGIN RELIQUARY {}-----\n", kind.label());
+    for (key, value) in headers {
+        if key.is_empty() || key.contains([':', '\n', '\r']) || key.trim() != key {
+            return Err(format!("Invalid armor header name: {:?}", key));
+        }
+        if value.contains(['\n', '\r']) {
+            return Err(format!("Armor header '{}' must be a single line", key));
+        }
+        out.push_str(&format!("{}: {}\n", key, value));
+    }
+    out.push('\n');
+    let body = STANDARD.encode(data);
+    for line in body.as_bytes().chunks(LINE_WIDTH) {
+        // base64 output is ASCII, so every chunk is valid UTF-8
+        out.push_str(std::str::from_utf8(line).unwrap_or_default());
+        out.push('\n');
+    }
+    out.push_str(&checksum_line(data));
+    out.push('\n');
+    out.push_str(&format!("-----END RELIQUARY {}-----\n", kind.label()));
+    Ok(out)
+}
+
+pub(crate) type Dearmored = (ArmorType, BTreeMap<String, String>, Vec<u8>);
+
+pub(crate) fn decode(text: &str) -> Result<Dearmored, String> {
+    limits::check_input("Armored text", text.len())?;
+    // Tolerate surrounding text (e.g. an email body) and CRLF line endings
+    let mut lines = text
+        .lines()
+        .map(str::trim_end)
+        .skip_while(|line| !line.starts_with("-----BEGIN RELIQUARY "));
+    let begin = lines.next().ok_or("No RELIQUARY armor block found")?;
+    let label = begin
+        .strip_prefix("-----BEGIN RELIQUARY ")
+        .and_then(|rest| rest.strip_suffix("-----"))
+        .ok_or("Malformed armor BEGIN line")?;
+    let kind =
+        ArmorType::from_label(label).ok_or_else(|| format!("Unknown armor type '{}'", label))?;
+
+    let mut headers = BTreeMap::new();
+    for line in lines.by_ref() {
+        if line.is_empty() {
+            break;
+        }
+        let (key, value) = line
+            .split_once(": ")
+            .ok_or_else(|| format!("Malformed armor header line: {:?}", line))?;
+        headers.insert(key.to_string(), value.to_string());
+    }
+
+    let mut body = String::new();
+    let mut checksum = None;
+    let end = format!("-----END RELIQUARY {}-----", kind.label());
+    let mut terminated = false;
+    for line in lines {
+        let line = line.trim_start();
+        if line == end {
+            terminated = true;
+            break;
+        }
+        if line.starts_with("-----END ") {
+            return Err(format!("Armor END line does not match BEGIN: {:?}", line));
+        }
+        if let Some(sum) = line.strip_prefix('=') {
+            checksum = Some(sum.to_string());
+        } else if checksum.is_some() {
+            return Err("Armor data after checksum line".to_string());
+        } else {
+            body.push_str(line);
+        }
+    }
+    if !terminated {
+        return Err("Armor block is missing its END line".to_string());
+    }
+
+    let data = STANDARD
+        .decode(body)
+        .map_err(|e| format!("Invalid armor base64: {}", e))?;
+    let checksum = checksum.ok_or("Armor block is missing its CRC-24 checksum")?;
+    if format!("={}", checksum) != checksum_line(&data) {
+        return Err("Armor CRC-24 checksum mismatch".to_string());
+    }
+    Ok((kind, headers, data))
+}
+
+/// Encodes a binary artifact as ASCII armor: BEGIN/END lines carrying the type label, optional
+/// "Key: value" headers, 64-column base64 and a CRC-24 checksum line
+#[pyfunction]
+#[pyo3(signature = (data, kind, headers = None))]
+pub fn armor(
+    data: Vec<u8>,
+    kind: ArmorType,
+    headers: Option<BTreeMap<String, String>>,
+) -> PyResult<String> {
+    encode(kind, &data, &headers.unwrap_or_default())
+        .map_err(pyo3::exceptions::PyValueError::new_err)
+}
+
+/// Decodes ASCII armor. Returns (kind, headers, data); raises ValueError on a malformed block,
+/// a checksum mismatch, or when `expected` is given and the block holds a different type
+#[pyfunction]
+#[pyo3(signature = (text, expected = None))]
+pub fn dearmor(text: &str, expected: Option<ArmorType>) -> PyResult<Dearmored> {
+    let (kind, headers, data) = decode(text).map_err(pyo3::exceptions::PyValueError::new_err)?;
+    if let Some(expected) = expected {
+        if kind != expected {
+            return Err(pyo3::exceptions::PyValueError::new_err(format!(
+                "Expected armored {}, found {}",
+                expected.label(),
+                kind.label()
+            )));
+        }
+    }
+    Ok((kind, headers, data))
+}
@@ -0,0 +1,271 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use pyo3::prelude::*;
+use sha3::{Digest, Sha3_256};
+
+use crate::fingerprint::{fingerprint, FINGERPRINT_BYTES};
+use crate::handles::{KeyAlgorithm, SecretKeyHandle, StoredKey};
+use crate::params::FalconParams;
+use crate::providers;
+use crate::schemes::SignatureScheme;
+use crate::usage::KeyUsagePolicy;
+use crate::wire::{put_var16, put_var8, Reader};
+
+// Key generation attestation record. Layout:
+//   magic "RQKA" | version u8 | algorithm var8 | public key fingerprint (32) | generated_at u64
+//   | entropy source var8 | host fingerprint (32) | self-test passed u8 | self-tests var8
+//   | signer provider var8 | signer label var8 | signature algorithm var8 | signature var16
+// The signature is made by a labeled key-provider key (the device or service key) over
+// SIGNED_LABEL | everything before the signature. The record names the key only by its public
+// key fingerprint, so it can be stored next to the key (e.g. in an encrypted backup) and checked
+// against it later. The signer's public key is not included: verifiers supply the one they
+// trust.
+const MAGIC: &[u8; 4] = b"RQKA";
+const VERSION: u8 = 1;
+const SIGNED_LABEL: &[u8] = b"reliquary/keygen-attestation/v1";
+const HOST_LABEL: &[u8] = b"reliquary/host-fingerprint/v1";
+const SELF_TESTS: &str = "pairwise-consistency";
+// Key pairs come from the PQClean randombytes, which reads the OS CSPRNG through getrandom.
+const ENTROPY_SOURCE: &str = "os-csprng/getrandom";
+
+fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(u64::MAX)
+}
+
+/// Stable identity of this machine: the systemd/D-Bus machine id and the hostname, whichever
+/// are available.
+fn host_identity() -> Vec<u8> {
+    let read = |paths: &[&str]| {
+        paths
+            .iter()
+            .find_map(|path| std::fs::read_to_string(path).ok())
+            .map(|s| s.trim().to_string())
+    };
+    let machine_id = read(&["/etc/machine-id", "/var/lib/dbus/machine-id"]).unwrap_or_default();
+    let hostname = read(&["/proc/sys/kernel/hostname", "/etc/hostname"])
+        .or_else(|| std::env::var("COMPUTERNAME").ok())
+        .or_else(|| std::env::var("HOSTNAME").ok())
+        .unwrap_or_default();
+    [machine_id.as_bytes(), &[0], hostname.as_bytes()].concat()
+}
+
+fn host_fingerprint(identity: &[u8]) -> [u8; 32] {
+    let mut hasher = Sha3_256::new();
+    hasher.update(HOST_LABEL);
+    hasher.update(identity);
+    hasher.finalize().into()
+}
+
+/// Pairwise consistency test of a freshly generated key pair: a Kyber key must decapsulate its
+/// own encapsulation and a Falcon key must verify its own signature.
+fn pairwise_consistency(key: &StoredKey) -> bool {
+    match key.algorithm {
+        KeyAlgorithm::Kyber(params) => crate::kyber_encapsulate(&key.public_key, params)
+            .and_then(|(ss, ct)| Ok(crate::kyber_decapsulate(&ct, &key.secret_key, params)? == ss))
+            .unwrap_or(false),
+        KeyAlgorithm::Falcon(params) => {
+            let msg = SIGNED_LABEL;
+            crate::falcon_sign(msg, &key.secret_key, params)
+                .and_then(|sig| crate::falcon_verify(msg, &sig, &key.public_key, params))
+                .unwrap_or(false)
+        }
+    }
+}
+
+pub(crate) fn verify_signature(
+    algorithm: &str,
+    msg: &[u8],
+    signature: &[u8],
+    public_key: &[u8],
+) -> PyResult<bool> {
+    match algorithm {
+        "ecdsa-p256-sha256" => providers::p256_verify(msg, signature, public_key),
+        // Falcon provider keys produce signed messages rather than detached signatures
+        "falcon1024" => crate::falcon_verify(msg, signature, public_key, FalconParams::Falcon1024),
+        "falcon512" => crate::falcon_verify(msg, signature, public_key, FalconParams::Falcon512),
+        other => SignatureScheme::parse(other)?.verify(msg, signature, public_key),
+    }
+}
+
+/// A parsed key generation attestation record. Create one from the record bytes and call
+/// `verify` with the attested public key and the trusted device/service public key
+#[pyclass(frozen, module = "reliquary_encryptor")]
+pub struct KeyAttestation {
+    record: Vec<u8>,
+    signed_len: usize,
+    #[pyo3(get)]
+    algorithm: String,
+    #[pyo3(get)]
+    public_key_fingerprint: Vec<u8>,
+    /// Unix seconds at which the key was generated, by the generating host's clock
+    #[pyo3(get)]
+    generated_at: u64,
+    #[pyo3(get)]
+    entropy_source: String,
+    /// SHA3-256 over the generating host's machine id and hostname
+    #[pyo3(get)]
+    host_fingerprint: Vec<u8>,
+    #[pyo3(get)]
+    self_test_passed: bool,
+    #[pyo3(get)]
+    self_tests: String,
+    #[pyo3(get)]
+    signer_provider: String,
+    #[pyo3(get)]
+    signer_label: String,
+    #[pyo3(get)]
+    signature_algorithm: String,
+    signature: Vec<u8>,
+}
+
+impl KeyAttestation {
+    pub(crate) fn parse(record: &[u8]) -> Result<Self, String> {
+        let mut r = Reader::new(record);
+        if r.bytes(MAGIC.len()).ok() != Some(&MAGIC[..]) {
+            return Err("Not a key generation attestation".to_string());
+        }
+        let version = r.u8()?;
+        if version != VERSION {
+            return Err(format!("Unsupported attestation version {}", version));
+        }
+        let text = |bytes: &[u8]| {
+            String::from_utf8(bytes.to_vec()).map_err(|_| "Attestation field is not UTF-8")
+        };
+        let algorithm = text(r.var8()?)?;
+        let public_key_fingerprint = r.bytes(FINGERPRINT_BYTES)?.to_vec();
+        let generated_at = r.u64()?;
+        let entropy_source = text(r.var8()?)?;
+        let host_fingerprint = r.bytes(32)?.to_vec();
+        let self_test_passed = match r.u8()? {
+            0 => false,
+            1 => true,
+            other => return Err(format!("Invalid attestation self-test status {}", other)),
+        };
+        let self_tests = text(r.var8()?)?;
+        let signer_provider = text(r.var8()?)?;
+        let signer_label = text(r.var8()?)?;
+        let signature_algorithm = text(r.var8()?)?;
+        let signed_len = r.position();
+        let signature = r.var16()?.to_vec();
+        if !r.is_empty() {
+            return Err("Trailing data after attestation".to_string());
+        }
+        Ok(KeyAttestation {
+            record: record.to_vec(),
+            signed_len,
+            algorithm,
+            public_key_fingerprint,
+            generated_at,
+            entropy_source,
+            host_fingerprint,
+            self_test_passed,
+            self_tests,
+            signer_provider,
+            signer_label,
+            signature_algorithm,
+            signature,
+        })
+    }
+
+    /// Whether the record attests `public_key` (by fingerprint).
+    pub(crate) fn attests(&self, public_key: &[u8]) -> bool {
+        fingerprint(public_key) == self.public_key_fingerprint.as_slice()
+    }
+}
+
+#[pymethods]
+impl KeyAttestation {
+    #[new]
+    fn new(record: Vec<u8>) -> PyResult<Self> {
+        Self::parse(&record).map_err(pyo3::exceptions::PyValueError::new_err)
+    }
+
+    /// True if the record attests `public_key`, its self-tests passed and its signature
+    /// verifies under `signer_public_key`
+    fn verify(&self, public_key: Vec<u8>, signer_public_key: Vec<u8>) -> PyResult<bool> {
+        if !self.attests(&public_key) || !self.self_test_passed {
+            return Ok(false);
+        }
+        let msg = [SIGNED_LABEL, &self.record[..self.signed_len]].concat();
+        verify_signature(
+            &self.signature_algorithm,
+            &msg,
+            &self.signature,
+            &signer_public_key,
+        )
+    }
+
+    fn __bytes__(&self) -> Vec<u8> {
+        self.record.clone()
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "KeyAttestation(algorithm='{}', generated_at={}, signer='{}:{}', self_test_passed={})",
+            self.algorithm,
+            self.generated_at,
+            self.signer_provider,
+            self.signer_label,
+            if self.self_test_passed {
+                "True"
+            } else {
+                "False"
+            }
+        )
+    }
+}
+
+/// Generates a key pair into a handle together with a signed attestation record: when
+/// (generated_at) and where (host fingerprint) the key was made, its entropy source and the
+/// result of a pairwise-consistency self-test. The record is signed by the provider key
+/// `signer_label` (e.g. a device key in the Secure Enclave or an HSM service key). A key that
+/// fails its self-test is discarded and raises ValueError. `backup` is as for
+/// `SecretKeyHandle.generate`. Returns (handle, record)
+#[pyfunction]
+#[pyo3(signature = (algorithm, signer_label, signer_provider = "software", policy = None, host_id = None, *, backup = false))]
+pub fn generate_attested_key(
+    py: Python<'_>,
+    algorithm: &str,
+    signer_label: &str,
+    signer_provider: &str,
+    policy: Option<KeyUsagePolicy>,
+    host_id: Option<Vec<u8>>,
+    backup: bool,
+) -> PyResult<(SecretKeyHandle, Vec<u8>)> {
+    let algorithm = KeyAlgorithm::parse(algorithm)?;
+    let policy = policy.unwrap_or_default();
+    policy.check_algorithm(algorithm)?;
+    let signer = providers::signer(signer_provider)?;
+    py.allow_threads(|| {
+        let key = StoredKey::generate(algorithm, false, backup, policy);
+        let generated_at = unix_now();
+        if !pairwise_consistency(&key) {
+            return Err(pyo3::exceptions::PyValueError::new_err(
+                "Generated key failed its pairwise-consistency self-test",
+            ));
+        }
+        let host = host_fingerprint(&host_id.unwrap_or_else(host_identity));
+
+        let mut record = MAGIC.to_vec();
+        record.push(VERSION);
+        (|| {
+            put_var8(&mut record, algorithm.name().as_bytes())?;
+            record.extend_from_slice(&fingerprint(&key.public_key));
+            record.extend_from_slice(&generated_at.to_be_bytes());
+            put_var8(&mut record, ENTROPY_SOURCE.as_bytes())?;
+            record.extend_from_slice(&host);
+            record.push(1);
+            put_var8(&mut record, SELF_TESTS.as_bytes())?;
+            put_var8(&mut record, signer.name().as_bytes())?;
+            put_var8(&mut record, signer_label.as_bytes())?;
+            put_var8(&mut record, signer.signature_algorithm().as_bytes())
+        })()
+        .map_err(pyo3::exceptions::PyValueError::new_err)?;
+        let signature = signer.sign(signer_label, &[SIGNED_LABEL, &record].concat())?;
+        put_var16(&mut record, &signature).map_err(pyo3::exceptions::PyValueError::new_err)?;
+        Ok((SecretKeyHandle::from_stored(key), record))
+    })
+}
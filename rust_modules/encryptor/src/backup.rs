@@ -0,0 +1,317 @@
+use aes_gcm::aead::{Aead, AeadCore, KeyInit, OsRng, Payload};
+use aes_gcm::{Aes256Gcm, Nonce};
+use argon2::{Algorithm, Argon2, Version};
+use pyo3::prelude::*;
+use rand::RngCore;
+use zeroize::Zeroizing;
+
+use crate::attest::KeyAttestation;
+use crate::handles::{with_backup_key, KeyAlgorithm, SecretKeyHandle, StoredKey};
+use crate::password::{check, MAX_ARGON2_ITERATIONS, MAX_ARGON2_MEMORY_KIB, MAX_PARALLELISM};
+use crate::usage::KeyUsagePolicy;
+use crate::wire::{put_var16, put_var8, Reader};
+
+// Encrypted key backups. Layout:
+//   magic "RQKB" | format u8 | kdf u8 | kdf params | algorithm var8 | public key var16
+//   | [format 2: keygen attestation var16] | nonce (12) | AES-256-GCM(secret key)
+// kdf 1 is Argon2id (memory_kib u32 | iterations u32 | parallelism u32 | salt var8) over a
+// passphrase; kdf 2 is a caller-held 32-byte KEK with no parameters. Everything before the
+// nonce is authenticated, so the algorithm, public key and costs can't be swapped, and the
+// public key can be read without the passphrase to identify a backup. Format 2 stores the
+// key's generation attestation (see attest.rs) next to it; backups without one stay format 1.
+const MAGIC: &[u8; 4] = b"RQKB";
+const FORMAT: u8 = 1;
+const FORMAT_ATTESTED: u8 = 2;
+const KDF_ARGON2ID: u8 = 1;
+const KDF_KEK: u8 = 2;
+const SALT_BYTES: usize = 16;
+const NONCE_BYTES: usize = 12;
+const KEY_BYTES: usize = 32;
+
+enum Protection {
+    Argon2id {
+        memory_kib: u32,
+        iterations: u32,
+        parallelism: u32,
+        salt: Vec<u8>,
+    },
+    Kek,
+}
+
+fn check_costs(memory_kib: u32, iterations: u32, parallelism: u32) -> Result<(), String> {
+    check("Argon2 parallelism", parallelism, 1, MAX_PARALLELISM)?;
+    check("Argon2 iterations", iterations, 1, MAX_ARGON2_ITERATIONS)?;
+    check(
+        "Argon2 memory_kib",
+        memory_kib,
+        8 * parallelism,
+        MAX_ARGON2_MEMORY_KIB,
+    )
+}
+
+/// The AES key protecting a backup, from exactly one of a passphrase or a KEK.
+fn wrapping_key(
+    protection: &Protection,
+    passphrase: Option<&str>,
+    kek: Option<&[u8]>,
+) -> Result<Zeroizing<[u8; KEY_BYTES]>, String> {
+    let mut key = Zeroizing::new([0u8; KEY_BYTES]);
+    match (protection, passphrase, kek) {
+        (
+            Protection::Argon2id {
+                memory_kib,
+                iterations,
+                parallelism,
+                salt,
+            },
+            Some(passphrase),
+            None,
+        ) => {
+            check_costs(*memory_kib, *iterations, *parallelism)?;
+            let params =
+                argon2::Params::new(*memory_kib, *iterations, *parallelism, Some(KEY_BYTES))
+                    .map_err(|e| e.to_string())?;
+            Argon2::new(Algorithm::Argon2id, Version::V0x13, params)
+                .hash_password_into(passphrase.as_bytes(), salt, key.as_mut())
+                .map_err(|e| e.to_string())?;
+        }
+        (Protection::Kek, None, Some(kek)) => {
+            if kek.len() != KEY_BYTES {
+                return Err(format!("KEK must be {} bytes", KEY_BYTES));
+            }
+            key.copy_from_slice(kek);
+        }
+        (Protection::Argon2id { .. }, _, _) => {
+            return Err("Key backup is passphrase-protected; pass passphrase only".to_string())
+        }
+        (Protection::Kek, _, _) => {
+            return Err("Key backup is KEK-protected; pass kek only".to_string())
+        }
+    }
+    Ok(key)
+}
+
+fn header(
+    protection: &Protection,
+    algorithm: KeyAlgorithm,
+    public_key: &[u8],
+    attestation: Option<&[u8]>,
+) -> Result<Vec<u8>, String> {
+    let mut out = MAGIC.to_vec();
+    out.push(if attestation.is_some() {
+        FORMAT_ATTESTED
+    } else {
+        FORMAT
+    });
+    match protection {
+        Protection::Argon2id {
+            memory_kib,
+            iterations,
+            parallelism,
+            salt,
+        } => {
+            out.push(KDF_ARGON2ID);
+            out.extend_from_slice(&memory_kib.to_be_bytes());
+            out.extend_from_slice(&iterations.to_be_bytes());
+            out.extend_from_slice(&parallelism.to_be_bytes());
+            put_var8(&mut out, salt)?;
+        }
+        Protection::Kek => out.push(KDF_KEK),
+    }
+    put_var8(&mut out, algorithm.name().as_bytes())?;
+    put_var16(&mut out, public_key)?;
+    if let Some(attestation) = attestation {
+        put_var16(&mut out, attestation)?;
+    }
+    Ok(out)
+}
+
+struct Backup<'a> {
+    protection: Protection,
+    algorithm: &'a str,
+    public_key: &'a [u8],
+    attestation: Option<&'a [u8]>,
+    header: &'a [u8],
+    nonce: &'a [u8],
+    ciphertext: &'a [u8],
+}
+
+fn parse(blob: &[u8]) -> Result<Backup<'_>, String> {
+    let mut r = Reader::new(blob);
+    if r.bytes(MAGIC.len()).ok() != Some(&MAGIC[..]) {
+        return Err("Not an encrypted key backup".to_string());
+    }
+    let format = r.u8()?;
+    if format != FORMAT && format != FORMAT_ATTESTED {
+        return Err(format!("Unsupported key backup format {}", format));
+    }
+    let protection = match r.u8()? {
+        KDF_ARGON2ID => Protection::Argon2id {
+            memory_kib: r.u32()?,
+            iterations: r.u32()?,
+            parallelism: r.u32()?,
+            salt: r.var8()?.to_vec(),
+        },
+        KDF_KEK => Protection::Kek,
+        other => return Err(format!("Unsupported key backup KDF {}", other)),
+    };
+    let algorithm =
+        std::str::from_utf8(r.var8()?).map_err(|_| "Invalid key backup algorithm".to_string())?;
+    let public_key = r.var16()?;
+    let attestation = if format == FORMAT_ATTESTED {
+        Some(r.var16()?)
+    } else {
+        None
+    };
+    let header = &blob[..r.position()];
+    let nonce = r.bytes(NONCE_BYTES)?;
+    Ok(Backup {
+        protection,
+        algorithm,
+        public_key,
+        attestation,
+        header,
+        nonce,
+        ciphertext: r.rest(),
+    })
+}
+
+/// Encrypts the secret key behind a `SecretKeyHandle` into a versioned, authenticated backup.
+/// Protect it with a `passphrase` (Argon2id, RFC 9106 second recommended costs by default) or a
+/// 32-byte `kek`. The handle must have been created with `backup=True` (or `exportable=True`)
+/// and its usage policy must list "export"; the raw key is never returned to Python. An
+/// `attestation` record from `generate_attested_key` for this key is stored alongside it,
+/// authenticated but readable without the passphrase
+#[pyfunction]
+#[allow(clippy::too_many_arguments)]
+#[pyo3(signature = (handle, passphrase = None, *, kek = None, memory_kib = 65536, iterations = 3, parallelism = 4, attestation = None))]
+pub fn export_key_encrypted(
+    py: Python<'_>,
+    handle: &Bound<'_, PyAny>,
+    passphrase: Option<&str>,
+    kek: Option<Vec<u8>>,
+    memory_kib: u32,
+    iterations: u32,
+    parallelism: u32,
+    attestation: Option<Vec<u8>>,
+) -> PyResult<Vec<u8>> {
+    if passphrase.is_some() == kek.is_some() {
+        return Err(pyo3::exceptions::PyValueError::new_err(
+            "Pass exactly one of passphrase or kek",
+        ));
+    }
+    let kek = kek.map(Zeroizing::new);
+    let protection = if kek.is_some() {
+        Protection::Kek
+    } else {
+        let mut salt = vec![0u8; SALT_BYTES];
+        OsRng.fill_bytes(&mut salt);
+        Protection::Argon2id {
+            memory_kib,
+            iterations,
+            parallelism,
+            salt,
+        }
+    };
+    let wrapping = py
+        .allow_threads(|| wrapping_key(&protection, passphrase, kek.as_deref().map(Vec::as_slice)))
+        .map_err(pyo3::exceptions::PyValueError::new_err)?;
+    with_backup_key(handle, |key| {
+        if let Some(attestation) = &attestation {
+            let parsed = KeyAttestation::parse(attestation)
+                .map_err(pyo3::exceptions::PyValueError::new_err)?;
+            if !parsed.attests(&key.public_key) {
+                return Err(pyo3::exceptions::PyValueError::new_err(
+                    "Attestation record is for a different key",
+                ));
+            }
+        }
+        let mut out = header(
+            &protection,
+            key.algorithm,
+            &key.public_key,
+            attestation.as_deref(),
+        )
+        .map_err(pyo3::exceptions::PyValueError::new_err)?;
+        let nonce = Aes256Gcm::generate_nonce(&mut OsRng);
+        let ct = Aes256Gcm::new(wrapping.as_ref().into())
+            .encrypt(
+                &nonce,
+                Payload {
+                    msg: &key.secret_key,
+                    aad: &out,
+                },
+            )
+            .expect("AES-GCM encryption of in-memory data cannot fail");
+        out.extend_from_slice(&nonce);
+        out.extend_from_slice(&ct);
+        Ok(out)
+    })
+}
+
+/// Restores a backup made by `export_key_encrypted` into a new `SecretKeyHandle` with `policy`
+/// and `backup` as for `SecretKeyHandle.generate`. Raises ValueError for a wrong passphrase or
+/// KEK, or a tampered backup
+#[pyfunction]
+#[pyo3(signature = (blob, passphrase = None, *, kek = None, policy = None, backup = false))]
+pub fn import_key_encrypted(
+    py: Python<'_>,
+    blob: Vec<u8>,
+    passphrase: Option<&str>,
+    kek: Option<Vec<u8>>,
+    policy: Option<KeyUsagePolicy>,
+    backup: bool,
+) -> PyResult<SecretKeyHandle> {
+    let kek = kek.map(Zeroizing::new);
+    let key = py
+        .allow_threads(|| {
+            let backup = parse(&blob)?;
+            let wrapping = wrapping_key(
+                &backup.protection,
+                passphrase,
+                kek.as_deref().map(Vec::as_slice),
+            )?;
+            let secret_key = Aes256Gcm::new(wrapping.as_ref().into())
+                .decrypt(
+                    Nonce::from_slice(backup.nonce),
+                    Payload {
+                        msg: backup.ciphertext,
+                        aad: backup.header,
+                    },
+                )
+                .map(Zeroizing::new)
+                .map_err(|_| "Key backup decryption failed".to_string())?;
+            Ok::<_, String>((
+                backup.algorithm.to_string(),
+                backup.public_key.to_vec(),
+                secret_key,
+            ))
+        })
+        .map_err(pyo3::exceptions::PyValueError::new_err)?;
+    let (algorithm, public_key, secret_key) = key;
+    Ok(SecretKeyHandle::from_stored(StoredKey::import(
+        KeyAlgorithm::parse(&algorithm)?,
+        public_key,
+        &secret_key,
+        false,
+        backup,
+        policy.unwrap_or_default(),
+    )?))
+}
+
+/// Returns (algorithm, public_key) of a key backup without decrypting it
+#[pyfunction]
+pub fn encrypted_key_info(blob: Vec<u8>) -> PyResult<(String, Vec<u8>)> {
+    let backup = parse(&blob).map_err(pyo3::exceptions::PyValueError::new_err)?;
+    Ok((
+        KeyAlgorithm::parse(backup.algorithm)?.name().to_string(),
+        backup.public_key.to_vec(),
+    ))
+}
+
+/// Returns the keygen attestation record stored in a key backup, or None if it has none
+#[pyfunction]
+pub fn encrypted_key_attestation(blob: Vec<u8>) -> PyResult<Option<Vec<u8>>> {
+    let backup = parse(&blob).map_err(pyo3::exceptions::PyValueError::new_err)?;
+    Ok(backup.attestation.map(<[u8]>::to_vec))
+}
@@ -0,0 +1,104 @@
+use pqcrypto_traits::kem::{PublicKey as KemPublicKey, SecretKey as KemSecretKey};
+use pyo3::prelude::*;
+
+use crate::handles::DecryptionKeyArg;
+use crate::params::{with_kyber, KyberParams};
+use crate::{kyber_decapsulate, kyber_encapsulate, validate};
+
+// Batched Kyber operations for bulk provisioning. Inputs are checked up front so errors can
+// name the offending item, then the work is split into one contiguous chunk per core and run
+// with the GIL released. Results keep the input order.
+const MAX_BATCH: usize = 1 << 20;
+
+fn check_batch_size(n: usize) -> PyResult<()> {
+    if n > MAX_BATCH {
+        return Err(pyo3::exceptions::PyValueError::new_err(format!(
+            "Batch of {} exceeds the maximum of {}",
+            n, MAX_BATCH
+        )));
+    }
+    Ok(())
+}
+
+/// Applies `f` to every index in `0..n` on all available cores.
+pub(crate) fn parallel_map<R: Send>(
+    n: usize,
+    f: impl Fn(usize) -> PyResult<R> + Sync,
+) -> PyResult<Vec<R>> {
+    let threads = std::thread::available_parallelism().map_or(1, |t| t.get());
+    let chunk = n.div_ceil(threads).max(1);
+    let f = &f;
+    let chunks = std::thread::scope(|scope| {
+        let workers: Vec<_> = (0..n)
+            .step_by(chunk)
+            .map(|start| scope.spawn(move || (start..n.min(start + chunk)).map(f).collect()))
+            .collect();
+        workers
+            .into_iter()
+            .map(|worker| worker.join().expect("batch worker panicked"))
+            .collect::<Vec<PyResult<Vec<R>>>>()
+    });
+    let mut results = Vec::with_capacity(n);
+    for chunk in chunks {
+        results.extend(chunk?);
+    }
+    Ok(results)
+}
+
+/// Generates `n` Kyber keypairs in parallel. Returns a list of (public_key, secret_key)
+#[pyfunction]
+#[pyo3(signature = (n, params = KyberParams::Kyber1024))]
+pub fn generate_kyber_keys_batch(
+    py: Python<'_>,
+    n: usize,
+    params: KyberParams,
+) -> PyResult<Vec<(Vec<u8>, Vec<u8>)>> {
+    check_batch_size(n)?;
+    py.allow_threads(|| {
+        parallel_map(n, |_| {
+            with_kyber!(params, kyber => {
+                let (pk, sk) = kyber::keypair();
+                Ok((pk.as_bytes().to_vec(), sk.as_bytes().to_vec()))
+            })
+        })
+    })
+}
+
+/// Encapsulates to every public key in parallel. Returns a list of (shared_secret, ciphertext)
+/// in input order
+#[pyfunction]
+#[pyo3(signature = (pks, params = KyberParams::Kyber1024))]
+pub fn encapsulate_kyber_batch(
+    py: Python<'_>,
+    pks: Vec<Vec<u8>>,
+    params: KyberParams,
+) -> PyResult<Vec<(Vec<u8>, Vec<u8>)>> {
+    check_batch_size(pks.len())?;
+    for (i, pk) in pks.iter().enumerate() {
+        validate::check_kyber_public_key(pk, params).map_err(|e| {
+            pyo3::exceptions::PyValueError::new_err(format!("Public key {}: {}", i, e))
+        })?;
+    }
+    py.allow_threads(|| parallel_map(pks.len(), |i| kyber_encapsulate(&pks[i], params)))
+}
+
+/// Decapsulates every ciphertext with one secret key in parallel. Returns the shared secrets
+/// in input order. With a SecretKeyHandle, each ciphertext counts as one use of the key
+#[pyfunction]
+#[pyo3(signature = (cts, sk, params = KyberParams::Kyber1024))]
+pub fn decapsulate_kyber_batch(
+    py: Python<'_>,
+    cts: Vec<Vec<u8>>,
+    sk: DecryptionKeyArg,
+    params: KyberParams,
+) -> PyResult<Vec<Vec<u8>>> {
+    check_batch_size(cts.len())?;
+    let params = sk.kyber_params(params)?;
+    for (i, ct) in cts.iter().enumerate() {
+        validate::check_kyber_ciphertext(ct, params).map_err(|e| {
+            pyo3::exceptions::PyValueError::new_err(format!("Ciphertext {}: {}", i, e))
+        })?;
+    }
+    sk.authorize_more(cts.len().saturating_sub(1))?;
+    py.allow_threads(|| parallel_map(cts.len(), |i| kyber_decapsulate(&cts[i], &sk, params)))
+}
@@ -0,0 +1,245 @@
+use num_bigint_dig::{BigUint, ModInverse};
+use pyo3::prelude::*;
+use rand::RngCore;
+use rsa::pkcs8::{DecodePrivateKey, DecodePublicKey, EncodePrivateKey, EncodePublicKey};
+use rsa::traits::PublicKeyParts;
+use rsa::{Pss, RsaPrivateKey, RsaPublicKey};
+use sha2::{Digest, Sha384};
+use zeroize::Zeroizing;
+
+use crate::pyca::{PrivateKeyArg, PublicKeyArg, Rsa};
+
+// RSA blind signatures, RFC 9474 RSABSSA-SHA384-PSS-{Randomized,Deterministic}. The final
+// signatures are ordinary RSASSA-PSS (SHA-384, MGF1-SHA-384, 48-byte salt) signatures over the
+// prepared message, so any PSS verifier can check them. Keys travel as DER (SPKI / PKCS#8).
+const HASH_BYTES: usize = 48;
+const SALT_BYTES: usize = 48;
+const PREFIX_BYTES: usize = 32;
+const KEY_SIZES: [usize; 3] = [2048, 3072, 4096];
+
+fn public_key(der: &[u8]) -> PyResult<RsaPublicKey> {
+    RsaPublicKey::from_public_key_der(der).map_err(|e| {
+        pyo3::exceptions::PyValueError::new_err(format!("Invalid RSA public key: {}", e))
+    })
+}
+
+fn modulus_len(pk: &RsaPublicKey) -> usize {
+    pk.n().bits().div_ceil(8)
+}
+
+/// I2OSP: big-endian, left-padded to `len` bytes.
+fn to_bytes(x: &BigUint, len: usize) -> Vec<u8> {
+    let bytes = x.to_bytes_be();
+    let mut out = vec![0u8; len.saturating_sub(bytes.len())];
+    out.extend_from_slice(&bytes);
+    out
+}
+
+fn mgf1(seed: &[u8], len: usize) -> Vec<u8> {
+    let mut out = Vec::with_capacity(len + HASH_BYTES);
+    let mut counter = 0u32;
+    while out.len() < len {
+        out.extend_from_slice(
+            &Sha384::new()
+                .chain_update(seed)
+                .chain_update(counter.to_be_bytes())
+                .finalize(),
+        );
+        counter += 1;
+    }
+    out.truncate(len);
+    out
+}
+
+/// EMSA-PSS-ENCODE (RFC 8017 section 9.1.1) with SHA-384 and a random 48-byte salt.
+fn pss_encode(message: &[u8], em_bits: usize) -> Result<Vec<u8>, String> {
+    let em_len = em_bits.div_ceil(8);
+    if em_len < HASH_BYTES + SALT_BYTES + 2 {
+        return Err("RSA modulus is too small for PSS with SHA-384".to_string());
+    }
+    let mut salt = [0u8; SALT_BYTES];
+    rand::thread_rng().fill_bytes(&mut salt);
+    let h = Sha384::new()
+        .chain_update([0u8; 8])
+        .chain_update(Sha384::digest(message))
+        .chain_update(salt)
+        .finalize();
+
+    let db_len = em_len - HASH_BYTES - 1;
+    let mut db = vec![0u8; db_len - SALT_BYTES - 1];
+    db.push(0x01);
+    db.extend_from_slice(&salt);
+    for (d, m) in db.iter_mut().zip(mgf1(&h, db_len)) {
+        *d ^= m;
+    }
+    db[0] &= 0xff >> (8 * em_len - em_bits);
+
+    let mut em = db;
+    em.extend_from_slice(&h);
+    em.push(0xbc);
+    Ok(em)
+}
+
+/// Random r in [1, n) together with r^-1 mod n.
+fn blinding_factor(n: &BigUint) -> (BigUint, BigUint) {
+    let mut wide = Zeroizing::new(vec![0u8; n.bits().div_ceil(8) + 16]);
+    loop {
+        rand::thread_rng().fill_bytes(&mut wide);
+        let r = BigUint::from_bytes_be(&wide) % n;
+        if let Some(inverse) = r.clone().mod_inverse(n).and_then(|i| i.to_biguint()) {
+            return (r, inverse);
+        }
+    }
+}
+
+fn verify(pk: &RsaPublicKey, message: &[u8], signature: &[u8]) -> bool {
+    pk.verify(
+        Pss::new_with_salt::<Sha384>(SALT_BYTES),
+        &Sha384::digest(message),
+        signature,
+    )
+    .is_ok()
+}
+
+pub(crate) fn check_key_size(bits: usize) -> PyResult<()> {
+    if !KEY_SIZES.contains(&bits) {
+        return Err(pyo3::exceptions::PyValueError::new_err(format!(
+            "Unsupported RSA key size {}. Expected one of {:?}",
+            bits, KEY_SIZES
+        )));
+    }
+    Ok(())
+}
+
+/// Generates an RSA key for blind token issuance. Returns (public_key, secret_key) as DER
+/// (SubjectPublicKeyInfo and PKCS#8). The other blind signature functions also take
+/// cryptography RSAPublicKey / RSAPrivateKey objects in place of the DER
+#[pyfunction]
+#[pyo3(signature = (bits = 2048))]
+pub fn generate_blind_signing_keys(py: Python<'_>, bits: usize) -> PyResult<(Vec<u8>, Vec<u8>)> {
+    check_key_size(bits)?;
+    py.allow_threads(|| {
+        let sk = RsaPrivateKey::new(&mut rand::thread_rng(), bits).map_err(|e| {
+            pyo3::exceptions::PyValueError::new_err(format!("RSA key generation failed: {}", e))
+        })?;
+        let pk_der = sk.to_public_key().to_public_key_der().map_err(|e| {
+            pyo3::exceptions::PyValueError::new_err(format!("Key encoding failed: {}", e))
+        })?;
+        let sk_der = sk.to_pkcs8_der().map_err(|e| {
+            pyo3::exceptions::PyValueError::new_err(format!("Key encoding failed: {}", e))
+        })?;
+        Ok((pk_der.into_vec(), sk_der.as_bytes().to_vec()))
+    })
+}
+
+/// Client side: blinds a token for signing. With `randomized` (the default) a random 32-byte
+/// prefix is prepended so identical tokens can't be linked. Returns
+/// (blinded_message, inverse, prepared_message): send only blinded_message to the signer; keep
+/// inverse secret for `finalize_blind_signature`; prepared_message is what the final
+/// signature covers
+#[pyfunction]
+#[pyo3(signature = (pk_bytes, message, randomized = true))]
+pub fn blind_message(
+    py: Python<'_>,
+    pk_bytes: PublicKeyArg<Rsa>,
+    message: Vec<u8>,
+    randomized: bool,
+) -> PyResult<(Vec<u8>, Vec<u8>, Vec<u8>)> {
+    let pk = public_key(&pk_bytes)?;
+    py.allow_threads(|| {
+        let mut prepared = Vec::with_capacity(PREFIX_BYTES + message.len());
+        if randomized {
+            let mut prefix = [0u8; PREFIX_BYTES];
+            rand::thread_rng().fill_bytes(&mut prefix);
+            prepared.extend_from_slice(&prefix);
+        }
+        prepared.extend_from_slice(&message);
+
+        let encoded = pss_encode(&prepared, pk.n().bits() - 1)
+            .map_err(pyo3::exceptions::PyValueError::new_err)?;
+        let m = BigUint::from_bytes_be(&encoded);
+        let (r, inverse) = blinding_factor(pk.n());
+        let blinded = (m * r.modpow(pk.e(), pk.n())) % pk.n();
+        let len = modulus_len(&pk);
+        Ok((to_bytes(&blinded, len), to_bytes(&inverse, len), prepared))
+    })
+}
+
+/// Signer side: signs a blinded message without learning the token. Returns the blind
+/// signature
+#[pyfunction]
+pub fn blind_sign(
+    py: Python<'_>,
+    sk_bytes: PrivateKeyArg<Rsa>,
+    blinded_message: Vec<u8>,
+) -> PyResult<Vec<u8>> {
+    let sk = RsaPrivateKey::from_pkcs8_der(&sk_bytes).map_err(|e| {
+        pyo3::exceptions::PyValueError::new_err(format!("Invalid RSA secret key: {}", e))
+    })?;
+    py.allow_threads(|| {
+        let len = modulus_len(sk.as_ref());
+        if blinded_message.len() != len {
+            return Err(pyo3::exceptions::PyValueError::new_err(format!(
+                "Invalid blinded message length. Expected {}, got {}",
+                len,
+                blinded_message.len()
+            )));
+        }
+        let m = BigUint::from_bytes_be(&blinded_message);
+        if &m >= sk.n() {
+            return Err(pyo3::exceptions::PyValueError::new_err(
+                "Blinded message is out of range",
+            ));
+        }
+        // Blinded, CRT-checked RSASP1: a faulty signature is never released.
+        let s = rsa::hazmat::rsa_decrypt_and_check(&sk, Some(&mut rand::thread_rng()), &m)
+            .map_err(|e| {
+                pyo3::exceptions::PyValueError::new_err(format!("Blind signing failed: {}", e))
+            })?;
+        Ok(to_bytes(&s, len))
+    })
+}
+
+/// Client side: removes the blinding from a blind signature and checks the result. Returns
+/// a standard RSASSA-PSS signature over `prepared_message`
+#[pyfunction]
+pub fn finalize_blind_signature(
+    py: Python<'_>,
+    pk_bytes: PublicKeyArg<Rsa>,
+    prepared_message: Vec<u8>,
+    blind_signature: Vec<u8>,
+    inverse: Vec<u8>,
+) -> PyResult<Vec<u8>> {
+    let pk = public_key(&pk_bytes)?;
+    let inverse = Zeroizing::new(inverse);
+    py.allow_threads(|| {
+        let len = modulus_len(&pk);
+        if blind_signature.len() != len {
+            return Err(pyo3::exceptions::PyValueError::new_err(format!(
+                "Invalid blind signature length. Expected {}, got {}",
+                len,
+                blind_signature.len()
+            )));
+        }
+        let z = BigUint::from_bytes_be(&blind_signature);
+        let signature = to_bytes(&((z * BigUint::from_bytes_be(&inverse)) % pk.n()), len);
+        if !verify(&pk, &prepared_message, &signature) {
+            return Err(pyo3::exceptions::PyValueError::new_err(
+                "Blind signature does not verify (wrong key, message or inverse)",
+            ));
+        }
+        Ok(signature)
+    })
+}
+
+/// Verifies a finalized blind signature over a prepared message
+#[pyfunction]
+pub fn verify_blind_signature(
+    py: Python<'_>,
+    pk_bytes: PublicKeyArg<Rsa>,
+    prepared_message: Vec<u8>,
+    signature: Vec<u8>,
+) -> PyResult<bool> {
+    let pk = public_key(&pk_bytes)?;
+    Ok(py.allow_threads(|| verify(&pk, &prepared_message, &signature)))
+}
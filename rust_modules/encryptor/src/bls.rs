@@ -0,0 +1,169 @@
+use blst::min_pk::{AggregateSignature, PublicKey, SecretKey, Signature};
+use blst::BLST_ERROR;
+use pyo3::prelude::*;
+use rand::RngCore;
+use zeroize::Zeroizing;
+
+use crate::handles::SigningKeyArg;
+
+// IETF BLS signature ciphersuites (draft-irtf-cfrg-bls-signature) for the minimal-pubkey
+// variant: 48-byte G1 public keys, 96-byte G2 signatures. Same-message aggregation is only
+// safe with the proof-of-possession scheme, so keys are expected to come with a PoP.
+const SIG_DST: &[u8] = b"BLS_SIG_BLS12381G2_XMD:SHA-256_SSWU_RO_POP_";
+const POP_DST: &[u8] = b"BLS_POP_BLS12381G2_XMD:SHA-256_SSWU_RO_POP_";
+const IKM_BYTES: usize = 32;
+pub(crate) const SIGNATURE_BYTES: usize = 96;
+
+fn secret_key(sk_bytes: &[u8]) -> PyResult<SecretKey> {
+    SecretKey::from_bytes(sk_bytes).map_err(|e| {
+        pyo3::exceptions::PyValueError::new_err(format!("Invalid BLS secret key: {:?}", e))
+    })
+}
+
+fn public_key(pk_bytes: &[u8]) -> PyResult<PublicKey> {
+    // key_validate rejects the identity point and points outside the G1 subgroup
+    PublicKey::key_validate(pk_bytes).map_err(|e| {
+        pyo3::exceptions::PyValueError::new_err(format!("Invalid BLS public key: {:?}", e))
+    })
+}
+
+fn signature(sig_bytes: &[u8]) -> PyResult<Signature> {
+    Signature::from_bytes(sig_bytes).map_err(|e| {
+        pyo3::exceptions::PyValueError::new_err(format!("Invalid BLS signature: {:?}", e))
+    })
+}
+
+/// Generates a BLS12-381 keypair. Returns (public_key, secret_key); pass 32+ bytes of `ikm`
+/// for deterministic derivation (EIP-2333 style KeyGen), otherwise fresh randomness is used
+#[pyfunction]
+#[pyo3(signature = (ikm = None))]
+pub fn generate_bls_keys(ikm: Option<Vec<u8>>) -> PyResult<(Vec<u8>, Vec<u8>)> {
+    let ikm = Zeroizing::new(ikm.unwrap_or_else(|| {
+        let mut ikm = vec![0u8; IKM_BYTES];
+        rand::thread_rng().fill_bytes(&mut ikm);
+        ikm
+    }));
+    let sk = SecretKey::key_gen(&ikm, &[]).map_err(|_| {
+        pyo3::exceptions::PyValueError::new_err(format!("ikm must be at least {} bytes", IKM_BYTES))
+    })?;
+    Ok((sk.sk_to_pk().to_bytes().to_vec(), sk.to_bytes().to_vec()))
+}
+
+/// Signs a message. Returns the 96-byte signature
+#[pyfunction]
+pub fn sign_bls(msg: Vec<u8>, sk_bytes: SigningKeyArg) -> PyResult<Vec<u8>> {
+    Ok(secret_key(&sk_bytes)?
+        .sign(&msg, SIG_DST, &[])
+        .to_bytes()
+        .to_vec())
+}
+
+/// Verifies a single BLS signature
+#[pyfunction]
+pub fn verify_bls(
+    py: Python<'_>,
+    msg: Vec<u8>,
+    sig_bytes: Vec<u8>,
+    pk_bytes: Vec<u8>,
+) -> PyResult<bool> {
+    let pk = public_key(&pk_bytes)?;
+    let Ok(sig) = Signature::from_bytes(&sig_bytes) else {
+        return Ok(false);
+    };
+    Ok(
+        py.allow_threads(|| sig.verify(true, &msg, SIG_DST, &[], &pk, false))
+            == BLST_ERROR::BLST_SUCCESS,
+    )
+}
+
+/// Proof of possession for a secret key: a signature over its own public key under a separate
+/// domain. Collect one from every agent before accepting its key into a consensus set
+#[pyfunction]
+pub fn prove_bls_possession(sk_bytes: SigningKeyArg) -> PyResult<Vec<u8>> {
+    let sk = secret_key(&sk_bytes)?;
+    let pk = sk.sk_to_pk().to_bytes();
+    Ok(sk.sign(&pk, POP_DST, &[]).to_bytes().to_vec())
+}
+
+/// Verifies a proof of possession for a public key
+#[pyfunction]
+pub fn verify_bls_possession(pk_bytes: Vec<u8>, proof: Vec<u8>) -> PyResult<bool> {
+    let pk = public_key(&pk_bytes)?;
+    let Ok(proof) = Signature::from_bytes(&proof) else {
+        return Ok(false);
+    };
+    Ok(proof.verify(true, &pk_bytes, POP_DST, &[], &pk, false) == BLST_ERROR::BLST_SUCCESS)
+}
+
+/// Aggregates signatures (over the same or different messages) into one 96-byte signature
+#[pyfunction]
+pub fn aggregate_bls_signatures(signatures: Vec<Vec<u8>>) -> PyResult<Vec<u8>> {
+    if signatures.is_empty() {
+        return Err(pyo3::exceptions::PyValueError::new_err(
+            "Need at least one signature to aggregate",
+        ));
+    }
+    let parsed = signatures
+        .iter()
+        .map(|s| signature(s))
+        .collect::<PyResult<Vec<_>>>()?;
+    let refs: Vec<&Signature> = parsed.iter().collect();
+    let aggregate = AggregateSignature::aggregate(&refs, true).map_err(|e| {
+        pyo3::exceptions::PyValueError::new_err(format!("Invalid BLS signature: {:?}", e))
+    })?;
+    Ok(aggregate.to_signature().to_bytes().to_vec())
+}
+
+/// Verifies an aggregate of signatures by `public_keys` over the same message, e.g. agent
+/// votes on one decision. Every key must have had its proof of possession checked
+#[pyfunction]
+pub fn fast_aggregate_verify_bls(
+    py: Python<'_>,
+    msg: Vec<u8>,
+    aggregate_signature: Vec<u8>,
+    public_keys: Vec<Vec<u8>>,
+) -> PyResult<bool> {
+    if public_keys.is_empty() {
+        return Ok(false);
+    }
+    let pks = public_keys
+        .iter()
+        .map(|pk| public_key(pk))
+        .collect::<PyResult<Vec<_>>>()?;
+    let Ok(sig) = Signature::from_bytes(&aggregate_signature) else {
+        return Ok(false);
+    };
+    let refs: Vec<&PublicKey> = pks.iter().collect();
+    Ok(
+        py.allow_threads(|| sig.fast_aggregate_verify(true, &msg, SIG_DST, &refs))
+            == BLST_ERROR::BLST_SUCCESS,
+    )
+}
+
+/// Verifies an aggregate signature where signer i signed `messages[i]`
+#[pyfunction]
+pub fn aggregate_verify_bls(
+    py: Python<'_>,
+    messages: Vec<Vec<u8>>,
+    aggregate_signature: Vec<u8>,
+    public_keys: Vec<Vec<u8>>,
+) -> PyResult<bool> {
+    if public_keys.is_empty() || messages.len() != public_keys.len() {
+        return Err(pyo3::exceptions::PyValueError::new_err(
+            "messages and public_keys must be non-empty and the same length",
+        ));
+    }
+    let pks = public_keys
+        .iter()
+        .map(|pk| public_key(pk))
+        .collect::<PyResult<Vec<_>>>()?;
+    let Ok(sig) = Signature::from_bytes(&aggregate_signature) else {
+        return Ok(false);
+    };
+    let msgs: Vec<&[u8]> = messages.iter().map(Vec::as_slice).collect();
+    let refs: Vec<&PublicKey> = pks.iter().collect();
+    Ok(
+        py.allow_threads(|| sig.aggregate_verify(true, &msgs, SIG_DST, &refs, false))
+            == BLST_ERROR::BLST_SUCCESS,
+    )
+}
@@ -0,0 +1,361 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use pyo3::prelude::*;
+use sha3::{Digest, Sha3_512};
+
+use crate::fingerprint::fingerprint;
+use crate::handles::SigningKeyArg;
+use crate::limits;
+use crate::schemes::SignatureScheme;
+use crate::wire::{put_var16, put_var8, Reader};
+
+// Signature bundle layout:
+//   magic "RQSB" | version u8 | SHA3-512(message) | layer count u8 | layers
+//   v1 layer: scheme id u8 | signer label (var8) | public key (var16) | signature (var16)
+//   v2 layer: scheme id u8 | signer label (var8) | public key (var16)
+//             | not_before u64 | not_after u64 | signature (var16)
+// Layer i signs SIGNED_LABEL | the bundle truncated to its first i layers | its own fields up
+// to the signature, so every countersignature covers all earlier signatures and each layer's
+// validity window is covered by its own signature. Times are Unix seconds; 0 / u64::MAX mean
+// unbounded. New bundles are v2; v1 bundles still verify and can be countersigned without a
+// window.
+const MAGIC: &[u8; 4] = b"RQSB";
+const VERSION_V1: u8 = 1;
+const VERSION: u8 = 2;
+const DIGEST_BYTES: usize = 64;
+const COUNT_OFFSET: usize = 4 + 1 + DIGEST_BYTES;
+const SIGNED_LABEL: &[u8] = b"reliquary/signature-bundle/v1";
+
+/// Verification result for one layer:
+/// (signer, scheme, public_key_fingerprint, not_before, not_after, valid)
+pub(crate) type LayerReport = (String, String, Vec<u8>, Option<u64>, Option<u64>, bool);
+
+#[derive(Clone, Copy)]
+struct Window {
+    not_before: u64,
+    not_after: u64,
+}
+
+impl Window {
+    fn new(not_before: Option<u64>, not_after: Option<u64>) -> PyResult<Self> {
+        let window = Window {
+            not_before: not_before.unwrap_or(0),
+            not_after: not_after.unwrap_or(u64::MAX),
+        };
+        if window.not_before > window.not_after {
+            return Err(pyo3::exceptions::PyValueError::new_err(
+                "not_before must not be later than not_after",
+            ));
+        }
+        Ok(window)
+    }
+
+    fn contains(&self, now: u64, max_clock_skew: u64) -> bool {
+        self.not_before.saturating_sub(max_clock_skew) <= now
+            && now <= self.not_after.saturating_add(max_clock_skew)
+    }
+}
+
+struct Layer<'a> {
+    scheme: SignatureScheme,
+    signer: &'a [u8],
+    public_key: &'a [u8],
+    window: Option<Window>,
+    signature: &'a [u8],
+}
+
+struct Bundle<'a> {
+    version: u8,
+    digest: &'a [u8],
+    layers: Vec<Layer<'a>>,
+}
+
+fn parse(bytes: &[u8]) -> Result<Bundle<'_>, String> {
+    limits::check_input("Signature bundle", bytes.len())?;
+    let mut r = Reader::new(bytes);
+    if r.bytes(4).map_err(|_| "Not a signature bundle")? != MAGIC {
+        return Err("Not a signature bundle".to_string());
+    }
+    let version = r.u8()?;
+    if version != VERSION && version != VERSION_V1 {
+        return Err(format!("Unsupported signature bundle version {}", version));
+    }
+    let digest = r.bytes(DIGEST_BYTES)?;
+    let count = r.u8()?;
+    limits::check_items("Signature bundle", count as usize)?;
+    let mut layers = Vec::with_capacity(count as usize);
+    for _ in 0..count {
+        let id = r.u8()?;
+        let scheme = SignatureScheme::from_wire_id(id)
+            .ok_or_else(|| format!("Unknown signature scheme id {}", id))?;
+        let signer = r.var8()?;
+        let public_key = r.var16()?;
+        let window = if version == VERSION_V1 {
+            None
+        } else {
+            Some(Window {
+                not_before: r.u64()?,
+                not_after: r.u64()?,
+            })
+        };
+        layers.push(Layer {
+            scheme,
+            signer,
+            public_key,
+            window,
+            signature: r.var16()?,
+        });
+    }
+    if !r.is_empty() {
+        return Err("Trailing bytes after signature bundle".to_string());
+    }
+    Ok(Bundle {
+        version,
+        digest,
+        layers,
+    })
+}
+
+/// Everything in a layer except the signature, i.e. the part the layer signs over.
+fn put_layer_fields(
+    out: &mut Vec<u8>,
+    scheme: SignatureScheme,
+    signer: &[u8],
+    public_key: &[u8],
+    window: Option<Window>,
+) -> Result<(), String> {
+    out.push(scheme.wire_id());
+    put_var8(out, signer)?;
+    put_var16(out, public_key)?;
+    if let Some(window) = window {
+        out.extend_from_slice(&window.not_before.to_be_bytes());
+        out.extend_from_slice(&window.not_after.to_be_bytes());
+    }
+    Ok(())
+}
+
+fn serialize(version: u8, digest: &[u8], layers: &[Layer]) -> Result<Vec<u8>, String> {
+    let mut out = Vec::new();
+    out.extend_from_slice(MAGIC);
+    out.push(version);
+    out.extend_from_slice(digest);
+    out.push(u8::try_from(layers.len()).map_err(|_| "A bundle holds at most 255 layers")?);
+    for layer in layers {
+        put_layer_fields(
+            &mut out,
+            layer.scheme,
+            layer.signer,
+            layer.public_key,
+            layer.window,
+        )?;
+        put_var16(&mut out, layer.signature)?;
+    }
+    Ok(out)
+}
+
+/// Bytes signed by a new layer added on top of `layers`.
+fn signed_message(
+    version: u8,
+    digest: &[u8],
+    layers: &[Layer],
+    scheme: SignatureScheme,
+    signer: &[u8],
+    public_key: &[u8],
+    window: Option<Window>,
+) -> Result<Vec<u8>, String> {
+    let mut msg = SIGNED_LABEL.to_vec();
+    msg.extend_from_slice(&serialize(version, digest, layers)?);
+    put_layer_fields(&mut msg, scheme, signer, public_key, window)?;
+    Ok(msg)
+}
+
+#[allow(clippy::too_many_arguments)]
+fn add_layer(
+    version: u8,
+    digest: &[u8],
+    layers: &[Layer],
+    sk_bytes: &[u8],
+    public_key: &[u8],
+    scheme: SignatureScheme,
+    signer: &str,
+    window: Option<Window>,
+) -> PyResult<Vec<u8>> {
+    if layers.len() == u8::MAX as usize {
+        return Err(pyo3::exceptions::PyValueError::new_err(
+            "A bundle holds at most 255 layers",
+        ));
+    }
+    let msg = signed_message(
+        version,
+        digest,
+        layers,
+        scheme,
+        signer.as_bytes(),
+        public_key,
+        window,
+    )
+    .map_err(pyo3::exceptions::PyValueError::new_err)?;
+    let signature = scheme.sign(&msg, sk_bytes)?;
+    if !scheme.verify(&msg, &signature, public_key)? {
+        return Err(pyo3::exceptions::PyValueError::new_err(
+            "Public key does not match the signing key",
+        ));
+    }
+
+    let mut out =
+        serialize(version, digest, layers).map_err(pyo3::exceptions::PyValueError::new_err)?;
+    out[COUNT_OFFSET] += 1;
+    put_layer_fields(&mut out, scheme, signer.as_bytes(), public_key, window)
+        .map_err(pyo3::exceptions::PyValueError::new_err)?;
+    put_var16(&mut out, &signature).map_err(pyo3::exceptions::PyValueError::new_err)?;
+    Ok(out)
+}
+
+fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+/// Starts a signature bundle: the first signer signs the SHA3-512 digest of `message`.
+/// `signer` is a free-form label (e.g. an agent id) reported back on verification.
+/// `not_before` / `not_after` (Unix seconds) bound when the signature is acceptable and are
+/// covered by it
+#[pyfunction]
+#[pyo3(signature = (
+    message,
+    sk_bytes,
+    pk_bytes,
+    scheme = "falcon1024",
+    signer = "",
+    not_before = None,
+    not_after = None,
+))]
+#[allow(clippy::too_many_arguments)]
+pub fn create_signature_bundle(
+    py: Python<'_>,
+    message: Vec<u8>,
+    sk_bytes: SigningKeyArg,
+    pk_bytes: Vec<u8>,
+    scheme: &str,
+    signer: &str,
+    not_before: Option<u64>,
+    not_after: Option<u64>,
+) -> PyResult<Vec<u8>> {
+    let scheme = SignatureScheme::parse(scheme)?;
+    let window = Window::new(not_before, not_after)?;
+    py.allow_threads(|| {
+        let digest = Sha3_512::digest(&message);
+        add_layer(
+            VERSION,
+            &digest,
+            &[],
+            &sk_bytes,
+            &pk_bytes,
+            scheme,
+            signer,
+            Some(window),
+        )
+    })
+}
+
+/// Adds a countersignature layer covering the whole existing bundle, including every earlier
+/// signature. Returns the extended bundle. Legacy v1 bundles can't carry validity windows
+#[pyfunction]
+#[pyo3(signature = (
+    bundle,
+    sk_bytes,
+    pk_bytes,
+    scheme = "falcon1024",
+    signer = "",
+    not_before = None,
+    not_after = None,
+))]
+#[allow(clippy::too_many_arguments)]
+pub fn countersign_bundle(
+    py: Python<'_>,
+    bundle: Vec<u8>,
+    sk_bytes: SigningKeyArg,
+    pk_bytes: Vec<u8>,
+    scheme: &str,
+    signer: &str,
+    not_before: Option<u64>,
+    not_after: Option<u64>,
+) -> PyResult<Vec<u8>> {
+    let scheme = SignatureScheme::parse(scheme)?;
+    let window = Window::new(not_before, not_after)?;
+    py.allow_threads(|| {
+        let parsed = parse(&bundle).map_err(pyo3::exceptions::PyValueError::new_err)?;
+        let window = if parsed.version == VERSION_V1 {
+            if not_before.is_some() || not_after.is_some() {
+                return Err(pyo3::exceptions::PyValueError::new_err(
+                    "v1 signature bundles can't carry validity windows",
+                ));
+            }
+            None
+        } else {
+            Some(window)
+        };
+        add_layer(
+            parsed.version,
+            parsed.digest,
+            &parsed.layers,
+            &sk_bytes,
+            &pk_bytes,
+            scheme,
+            signer,
+            window,
+        )
+    })
+}
+
+/// Verifies every layer of a signature bundle against `message`. Returns one
+/// (signer, scheme, public_key_fingerprint, not_before, not_after, valid) tuple per layer,
+/// innermost first; unbounded window ends are None. A layer is valid only if its signature
+/// checks out and `now` (Unix seconds; defaults to the system clock - pass a Roughtime or TSA
+/// time where the local clock isn't trusted) lies within its window widened by
+/// `max_clock_skew` seconds. The chain is intact only if every layer is valid
+#[pyfunction]
+#[pyo3(signature = (bundle, message, now = None, max_clock_skew = 0))]
+pub fn verify_signature_bundle(
+    py: Python<'_>,
+    bundle: Vec<u8>,
+    message: Vec<u8>,
+    now: Option<u64>,
+    max_clock_skew: u64,
+) -> PyResult<Vec<LayerReport>> {
+    let now = now.unwrap_or_else(unix_now);
+    py.allow_threads(|| {
+        let parsed = parse(&bundle).map_err(pyo3::exceptions::PyValueError::new_err)?;
+        let covers_message = Sha3_512::digest(&message).as_slice() == parsed.digest;
+        let mut report = Vec::with_capacity(parsed.layers.len());
+        for (i, layer) in parsed.layers.iter().enumerate() {
+            let msg = signed_message(
+                parsed.version,
+                parsed.digest,
+                &parsed.layers[..i],
+                layer.scheme,
+                layer.signer,
+                layer.public_key,
+                layer.window,
+            )
+            .map_err(pyo3::exceptions::PyValueError::new_err)?;
+            let in_window = layer.window.is_none_or(|w| w.contains(now, max_clock_skew));
+            let valid = covers_message
+                && in_window
+                && layer
+                    .scheme
+                    .verify(&msg, layer.signature, layer.public_key)?;
+            report.push((
+                String::from_utf8_lossy(layer.signer).into_owned(),
+                layer.scheme.name().to_string(),
+                fingerprint(layer.public_key).to_vec(),
+                layer.window.map(|w| w.not_before).filter(|&t| t != 0),
+                layer.window.map(|w| w.not_after).filter(|&t| t != u64::MAX),
+                valid,
+            ));
+        }
+        Ok(report)
+    })
+}
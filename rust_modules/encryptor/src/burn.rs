@@ -0,0 +1,417 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use aes_gcm::aead::{Aead, AeadCore, KeyInit, OsRng, Payload};
+use aes_gcm::{Aes256Gcm, Nonce};
+use hkdf::Hkdf;
+use pyo3::prelude::*;
+use rand::RngCore;
+use sha2::Sha256;
+use sha3::{Digest, Sha3_256};
+use zeroize::Zeroizing;
+
+use crate::attest::verify_signature;
+use crate::providers;
+use crate::shamir;
+use crate::wire::{put_var16, put_var8, Reader};
+
+// One-time share container layout:
+//   magic "RQBS" | version u8 | envelope id (16) | threshold u8 | count u8 | max uses u8
+//   | share index u8 | DEK share (32) | nonce (12) | AES-256-GCM(secret)
+// The secret is encrypted once under a random data-encryption key (DEK) and the DEK is
+// Shamir-split, so every container carries the same ciphertext and its own DEK share. The AAD
+// is the header up to the share index, which ties the ciphertext to its envelope parameters.
+//
+// Tombstone record layout:
+//   magic "RQBT" | version u8 | envelope id (16) | use count u8 | max uses u8 | combined_at u64
+//   | share indices var8 | ciphertext digest (32) | previous tombstone digest (32)
+//   | signer provider var8 | signer label var8 | signature algorithm var8 | signature var16
+// Each successful combine yields a tombstone signed by a labeled key-provider key over
+// SIGNED_LABEL | everything before the signature. Tombstones of one envelope form a hash chain
+// through the previous tombstone digest (zero for the first use), and the envelope is burned
+// once use count reaches max uses. This module is stateless: it refuses a combine when handed
+// the envelope's burned tombstone, but it is up to the caller to hand in the latest one.
+const SHARE_MAGIC: &[u8; 4] = b"RQBS";
+const TOMBSTONE_MAGIC: &[u8; 4] = b"RQBT";
+const VERSION: u8 = 1;
+const ID_BYTES: usize = 16;
+const DEK_BYTES: usize = 32;
+const NONCE_BYTES: usize = 12;
+const DIGEST_BYTES: usize = 32;
+const KEY_LABEL: &[u8] = b"reliquary/one-time-shares/v1/aes-256-gcm";
+const SIGNED_LABEL: &[u8] = b"reliquary/share-tombstone/v1";
+
+/// (envelope_id, share containers)
+type OneTimeShares = (Vec<u8>, Vec<Vec<u8>>);
+
+fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(u64::MAX)
+}
+
+fn secret_key(dek: &[u8]) -> Zeroizing<[u8; 32]> {
+    let mut key = Zeroizing::new([0u8; 32]);
+    Hkdf::<Sha256>::new(None, dek)
+        .expand(KEY_LABEL, key.as_mut())
+        .expect("32 bytes is a valid HKDF-SHA256 output length");
+    key
+}
+
+struct ShareContainer<'a> {
+    /// Header bytes up to the share index; the AAD.
+    header: &'a [u8],
+    envelope_id: &'a [u8],
+    threshold: u8,
+    count: u8,
+    max_uses: u8,
+    index: u8,
+    share: &'a [u8],
+    nonce: &'a [u8],
+    ciphertext: &'a [u8],
+}
+
+fn parse_share(bytes: &[u8]) -> Result<ShareContainer<'_>, String> {
+    let mut r = Reader::new(bytes);
+    if r.bytes(4).map_err(|_| "Not a one-time share")? != SHARE_MAGIC {
+        return Err("Not a one-time share".to_string());
+    }
+    let version = r.u8()?;
+    if version != VERSION {
+        return Err(format!("Unsupported one-time share version {}", version));
+    }
+    let envelope_id = r.bytes(ID_BYTES)?;
+    let threshold = r.u8()?;
+    let count = r.u8()?;
+    let max_uses = r.u8()?;
+    let header = &bytes[..r.position()];
+    let index = r.u8()?;
+    if threshold == 0 || threshold > count || max_uses == 0 || index == 0 || index > count {
+        return Err("Malformed one-time share header".to_string());
+    }
+    Ok(ShareContainer {
+        header,
+        envelope_id,
+        threshold,
+        count,
+        max_uses,
+        index,
+        share: r.bytes(DEK_BYTES)?,
+        nonce: r.bytes(NONCE_BYTES)?,
+        ciphertext: r.rest(),
+    })
+}
+
+/// A parsed share tombstone. Create one from the record bytes and call `verify` with the
+/// trusted device/service public key
+#[pyclass(frozen, module = "reliquary_encryptor")]
+pub struct ShareTombstone {
+    record: Vec<u8>,
+    signed_len: usize,
+    #[pyo3(get)]
+    envelope_id: Vec<u8>,
+    /// How many times the envelope has been combined, this combine included
+    #[pyo3(get)]
+    use_count: u8,
+    #[pyo3(get)]
+    max_uses: u8,
+    /// Unix seconds at which the combine happened, by the combining host's clock
+    #[pyo3(get)]
+    combined_at: u64,
+    #[pyo3(get)]
+    share_indices: Vec<usize>,
+    /// SHA3-256 of the envelope ciphertext that was opened
+    #[pyo3(get)]
+    ciphertext_digest: Vec<u8>,
+    /// SHA3-256 of the envelope's previous tombstone; all zero for the first use
+    #[pyo3(get)]
+    previous_digest: Vec<u8>,
+    #[pyo3(get)]
+    signer_provider: String,
+    #[pyo3(get)]
+    signer_label: String,
+    #[pyo3(get)]
+    signature_algorithm: String,
+    signature: Vec<u8>,
+}
+
+impl ShareTombstone {
+    fn parse(record: &[u8]) -> Result<Self, String> {
+        let mut r = Reader::new(record);
+        if r.bytes(TOMBSTONE_MAGIC.len()).ok() != Some(&TOMBSTONE_MAGIC[..]) {
+            return Err("Not a share tombstone".to_string());
+        }
+        let version = r.u8()?;
+        if version != VERSION {
+            return Err(format!("Unsupported share tombstone version {}", version));
+        }
+        let text = |bytes: &[u8]| {
+            String::from_utf8(bytes.to_vec()).map_err(|_| "Tombstone field is not UTF-8")
+        };
+        let envelope_id = r.bytes(ID_BYTES)?.to_vec();
+        let use_count = r.u8()?;
+        let max_uses = r.u8()?;
+        let combined_at = r.u64()?;
+        let share_indices = r.var8()?.iter().map(|&i| i as usize).collect();
+        let ciphertext_digest = r.bytes(DIGEST_BYTES)?.to_vec();
+        let previous_digest = r.bytes(DIGEST_BYTES)?.to_vec();
+        let signer_provider = text(r.var8()?)?;
+        let signer_label = text(r.var8()?)?;
+        let signature_algorithm = text(r.var8()?)?;
+        let signed_len = r.position();
+        let signature = r.var16()?.to_vec();
+        if !r.is_empty() {
+            return Err("Trailing data after share tombstone".to_string());
+        }
+        Ok(ShareTombstone {
+            record: record.to_vec(),
+            signed_len,
+            envelope_id,
+            use_count,
+            max_uses,
+            combined_at,
+            share_indices,
+            ciphertext_digest,
+            previous_digest,
+            signer_provider,
+            signer_label,
+            signature_algorithm,
+            signature,
+        })
+    }
+
+    fn signature_valid(&self, signer_public_key: &[u8]) -> PyResult<bool> {
+        let msg = [SIGNED_LABEL, &self.record[..self.signed_len]].concat();
+        verify_signature(
+            &self.signature_algorithm,
+            &msg,
+            &self.signature,
+            signer_public_key,
+        )
+    }
+}
+
+#[pymethods]
+impl ShareTombstone {
+    #[new]
+    fn new(record: Vec<u8>) -> PyResult<Self> {
+        Self::parse(&record).map_err(pyo3::exceptions::PyValueError::new_err)
+    }
+
+    /// Whether the envelope has used up all its combines
+    #[getter]
+    fn burned(&self) -> bool {
+        self.use_count >= self.max_uses
+    }
+
+    /// True if the tombstone's signature verifies under `signer_public_key`
+    fn verify(&self, signer_public_key: Vec<u8>) -> PyResult<bool> {
+        self.signature_valid(&signer_public_key)
+    }
+
+    /// True if this tombstone directly follows `previous` in its envelope's chain
+    fn follows(&self, previous: &ShareTombstone) -> bool {
+        self.envelope_id == previous.envelope_id
+            && self.use_count == previous.use_count.saturating_add(1)
+            && self.previous_digest == previous.digest()
+    }
+
+    /// SHA3-256 of the record, as referenced by the next tombstone
+    fn digest(&self) -> Vec<u8> {
+        Sha3_256::digest(&self.record).to_vec()
+    }
+
+    fn __bytes__(&self) -> Vec<u8> {
+        self.record.clone()
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "ShareTombstone(envelope_id='{}', use_count={}, max_uses={}, signer='{}:{}')",
+            hex::encode(&self.envelope_id),
+            self.use_count,
+            self.max_uses,
+            self.signer_provider,
+            self.signer_label
+        )
+    }
+}
+
+/// Encrypts `secret` into `count` one-time share containers, any `threshold` of which open it
+/// with `combine_one_time_shares`, at most `max_uses` times. Returns (envelope_id, shares)
+#[pyfunction]
+#[pyo3(signature = (secret, threshold, count, max_uses = 1))]
+pub fn create_one_time_shares(
+    secret: Vec<u8>,
+    threshold: u8,
+    count: u8,
+    max_uses: u8,
+) -> PyResult<OneTimeShares> {
+    let secret = Zeroizing::new(secret);
+    (|| -> Result<OneTimeShares, String> {
+        if max_uses == 0 {
+            return Err("max_uses must be at least 1".to_string());
+        }
+        let mut envelope_id = [0u8; ID_BYTES];
+        rand::thread_rng().fill_bytes(&mut envelope_id);
+        let mut dek = Zeroizing::new([0u8; DEK_BYTES]);
+        rand::thread_rng().fill_bytes(dek.as_mut());
+        let xs: Vec<u8> = (1..=count).collect();
+        let shares = shamir::split(dek.as_ref(), threshold, &xs)?;
+
+        let mut header = SHARE_MAGIC.to_vec();
+        header.push(VERSION);
+        header.extend_from_slice(&envelope_id);
+        header.extend_from_slice(&[threshold, count, max_uses]);
+        let nonce = Aes256Gcm::generate_nonce(&mut OsRng);
+        let ciphertext = Aes256Gcm::new(secret_key(dek.as_ref()).as_slice().into())
+            .encrypt(
+                &nonce,
+                Payload {
+                    msg: &secret,
+                    aad: &header,
+                },
+            )
+            .map_err(|_| "One-time share encryption failed".to_string())?;
+
+        let containers = xs
+            .iter()
+            .zip(&shares)
+            .map(|(&x, share)| {
+                let mut out = header.clone();
+                out.push(x);
+                out.extend_from_slice(share);
+                out.extend_from_slice(&nonce);
+                out.extend_from_slice(&ciphertext);
+                out
+            })
+            .collect();
+        Ok((envelope_id.to_vec(), containers))
+    })()
+    .map_err(pyo3::exceptions::PyValueError::new_err)
+}
+
+/// Describes a one-time share container as (envelope_id, index, threshold, count, max_uses)
+#[pyfunction]
+pub fn one_time_share_info(share: Vec<u8>) -> PyResult<(Vec<u8>, u8, u8, u8, u8)> {
+    let parsed = parse_share(&share).map_err(pyo3::exceptions::PyValueError::new_err)?;
+    Ok((
+        parsed.envelope_id.to_vec(),
+        parsed.index,
+        parsed.threshold,
+        parsed.count,
+        parsed.max_uses,
+    ))
+}
+
+/// Combines one-time shares of one envelope and returns (secret, tombstone): a record of this
+/// combine signed by the provider key `signer_label`. `previous_tombstone` must be the
+/// envelope's latest tombstone, if it has been combined before; it has to come from the same
+/// signer, and a combine is refused once the envelope is burned. Callers keep the tombstones
+/// and must persist the new one before releasing the secret
+#[pyfunction]
+#[pyo3(signature = (shares, signer_label, signer_provider = "software", previous_tombstone = None))]
+pub fn combine_one_time_shares(
+    py: Python<'_>,
+    shares: Vec<Vec<u8>>,
+    signer_label: &str,
+    signer_provider: &str,
+    previous_tombstone: Option<Vec<u8>>,
+) -> PyResult<(Vec<u8>, Vec<u8>)> {
+    let signer = providers::signer(signer_provider)?;
+    let parsed = shares
+        .iter()
+        .map(|share| parse_share(share))
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(pyo3::exceptions::PyValueError::new_err)?;
+    let Some(first) = parsed.first() else {
+        return Err(pyo3::exceptions::PyValueError::new_err("No shares given"));
+    };
+    if parsed.iter().any(|share| {
+        share.header != first.header
+            || share.nonce != first.nonce
+            || share.ciphertext != first.ciphertext
+    }) {
+        return Err(pyo3::exceptions::PyValueError::new_err(
+            "Shares belong to different envelopes",
+        ));
+    }
+    if parsed.len() < first.threshold as usize {
+        return Err(pyo3::exceptions::PyValueError::new_err(format!(
+            "Envelope needs {} shares, got {}",
+            first.threshold,
+            parsed.len()
+        )));
+    }
+
+    let (use_count, previous_digest) = match previous_tombstone {
+        None => (1u8, [0u8; DIGEST_BYTES].to_vec()),
+        Some(record) => {
+            let previous =
+                ShareTombstone::parse(&record).map_err(pyo3::exceptions::PyValueError::new_err)?;
+            if previous.envelope_id != first.envelope_id {
+                return Err(pyo3::exceptions::PyValueError::new_err(
+                    "Previous tombstone belongs to a different envelope",
+                ));
+            }
+            if previous.signer_provider != signer.name() || previous.signer_label != signer_label {
+                return Err(pyo3::exceptions::PyValueError::new_err(
+                    "Previous tombstone was signed by a different key",
+                ));
+            }
+            if !previous.signature_valid(&signer.public_key(signer_label)?)? {
+                return Err(pyo3::exceptions::PyValueError::new_err(
+                    "Previous tombstone signature is invalid",
+                ));
+            }
+            if previous.burned() {
+                return Err(pyo3::exceptions::PyValueError::new_err(format!(
+                    "Envelope {} is burned ({} of {} uses)",
+                    hex::encode(first.envelope_id),
+                    previous.use_count,
+                    previous.max_uses
+                )));
+            }
+            (previous.use_count + 1, previous.digest())
+        }
+    };
+
+    py.allow_threads(|| {
+        let used = &parsed[..first.threshold as usize];
+        let points: Vec<(u8, &[u8])> = used.iter().map(|s| (s.index, s.share)).collect();
+        let dek =
+            shamir::interpolate(&points, 0).map_err(pyo3::exceptions::PyValueError::new_err)?;
+        let secret = Aes256Gcm::new(secret_key(&dek).as_slice().into())
+            .decrypt(
+                Nonce::from_slice(first.nonce),
+                Payload {
+                    msg: first.ciphertext,
+                    aad: first.header,
+                },
+            )
+            .map_err(|_| {
+                pyo3::exceptions::PyValueError::new_err(
+                    "One-time shares do not open the envelope (wrong or tampered shares)",
+                )
+            })?;
+
+        let mut record = TOMBSTONE_MAGIC.to_vec();
+        record.push(VERSION);
+        record.extend_from_slice(first.envelope_id);
+        record.extend_from_slice(&[use_count, first.max_uses]);
+        record.extend_from_slice(&unix_now().to_be_bytes());
+        (|| {
+            let indices: Vec<u8> = used.iter().map(|s| s.index).collect();
+            put_var8(&mut record, &indices)?;
+            record.extend_from_slice(&Sha3_256::digest(first.ciphertext));
+            record.extend_from_slice(&previous_digest);
+            put_var8(&mut record, signer.name().as_bytes())?;
+            put_var8(&mut record, signer_label.as_bytes())?;
+            put_var8(&mut record, signer.signature_algorithm().as_bytes())
+        })()
+        .map_err(pyo3::exceptions::PyValueError::new_err)?;
+        let signature = signer.sign(signer_label, &[SIGNED_LABEL, &record].concat())?;
+        put_var16(&mut record, &signature).map_err(pyo3::exceptions::PyValueError::new_err)?;
+        Ok((secret, record))
+    })
+}
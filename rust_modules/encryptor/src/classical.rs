@@ -0,0 +1,135 @@
+use ed25519_dalek::{Signature as Ed25519Signature, Signer as _, SigningKey, VerifyingKey};
+use pyo3::prelude::*;
+use rand::RngCore;
+use x25519_dalek::{PublicKey as X25519PublicKey, StaticSecret};
+use zeroize::Zeroizing;
+
+use crate::faults;
+use crate::pyca::{Ed25519, PrivateKeyArg, PublicKeyArg, X25519};
+
+const KEY_BYTES: usize = 32;
+pub(crate) const ED25519_SIGNATURE_BYTES: usize = 64;
+
+pub(crate) fn fixed<const N: usize>(bytes: &[u8], what: &str) -> PyResult<[u8; N]> {
+    bytes.try_into().map_err(|_| {
+        pyo3::exceptions::PyValueError::new_err(format!(
+            "Invalid {} length. Expected {}, got {}",
+            what,
+            N,
+            bytes.len()
+        ))
+    })
+}
+
+pub(crate) fn random_secret() -> Zeroizing<[u8; KEY_BYTES]> {
+    let mut secret = Zeroizing::new([0u8; KEY_BYTES]);
+    rand::thread_rng().fill_bytes(secret.as_mut());
+    secret
+}
+
+fn signing_key(sk: &[u8]) -> PyResult<SigningKey> {
+    let seed = Zeroizing::new(fixed::<KEY_BYTES>(sk, "Ed25519 secret key")?);
+    Ok(SigningKey::from_bytes(&seed))
+}
+
+/// Generates an Ed25519 keypair. Returns (public_key, secret_key); the secret key is the
+/// 32-byte RFC 8032 seed
+#[pyfunction]
+pub fn generate_ed25519_keys() -> (Vec<u8>, Vec<u8>) {
+    let seed = random_secret();
+    let sk = SigningKey::from_bytes(&seed);
+    (sk.verifying_key().to_bytes().to_vec(), seed.to_vec())
+}
+
+/// Derives the Ed25519 public key from a 32-byte secret key (or a cryptography
+/// Ed25519PrivateKey)
+#[pyfunction]
+pub fn ed25519_public_key(sk_bytes: PrivateKeyArg<Ed25519>) -> PyResult<Vec<u8>> {
+    Ok(ed25519_public_from_secret(&sk_bytes)?.to_vec())
+}
+
+pub(crate) fn ed25519_public_from_secret(sk_bytes: &[u8]) -> PyResult<[u8; KEY_BYTES]> {
+    Ok(signing_key(sk_bytes)?.verifying_key().to_bytes())
+}
+
+pub(crate) fn ed25519_sign(msg: &[u8], sk_bytes: &[u8]) -> PyResult<Vec<u8>> {
+    faults::inject(faults::Operation::Sign, || {
+        Ok(signing_key(sk_bytes)?.sign(msg).to_bytes().to_vec())
+    })
+}
+
+pub(crate) fn ed25519_verify(msg: &[u8], signature: &[u8], pk_bytes: &[u8]) -> PyResult<bool> {
+    faults::inject(faults::Operation::Verify, || {
+        let pk = VerifyingKey::from_bytes(&fixed::<KEY_BYTES>(pk_bytes, "Ed25519 public key")?)
+            .map_err(|e| {
+                pyo3::exceptions::PyValueError::new_err(format!("Invalid public key: {}", e))
+            })?;
+        let signature = Ed25519Signature::from_bytes(&fixed::<ED25519_SIGNATURE_BYTES>(
+            signature,
+            "Ed25519 signature",
+        )?);
+        Ok(pk.verify_strict(msg, &signature).is_ok())
+    })
+}
+
+/// Signs a message with Ed25519. Returns the 64-byte signature. The key may also be a
+/// cryptography Ed25519PrivateKey
+#[pyfunction]
+pub fn sign_ed25519(msg: Vec<u8>, sk_bytes: PrivateKeyArg<Ed25519>) -> PyResult<Vec<u8>> {
+    ed25519_sign(&msg, &sk_bytes)
+}
+
+/// Verifies an Ed25519 signature using strict (non-malleable) verification. The key may also
+/// be a cryptography Ed25519PublicKey
+#[pyfunction]
+pub fn verify_ed25519(
+    msg: Vec<u8>,
+    signature: Vec<u8>,
+    pk_bytes: PublicKeyArg<Ed25519>,
+) -> PyResult<bool> {
+    ed25519_verify(&msg, &signature, &pk_bytes)
+}
+
+/// Generates an X25519 keypair. Returns (public_key, secret_key)
+#[pyfunction]
+pub fn generate_x25519_keys() -> (Vec<u8>, Vec<u8>) {
+    let secret = StaticSecret::from(*random_secret());
+    (
+        X25519PublicKey::from(&secret).to_bytes().to_vec(),
+        secret.to_bytes().to_vec(),
+    )
+}
+
+/// Derives the X25519 public key from a 32-byte secret key (or a cryptography
+/// X25519PrivateKey)
+#[pyfunction]
+pub fn x25519_public_key(sk_bytes: PrivateKeyArg<X25519>) -> PyResult<Vec<u8>> {
+    Ok(x25519_public_from_secret(&sk_bytes)?.to_vec())
+}
+
+/// X25519 Diffie-Hellman. Returns the 32-byte shared secret; rejects low-order peer keys
+/// that would yield an all-zero secret. Either key may also be a cryptography X25519 key
+#[pyfunction]
+pub fn x25519_shared_secret(
+    sk_bytes: PrivateKeyArg<X25519>,
+    peer_pk_bytes: PublicKeyArg<X25519>,
+) -> PyResult<Vec<u8>> {
+    Ok(x25519_dh(&sk_bytes, &peer_pk_bytes)?.to_vec())
+}
+
+pub(crate) fn x25519_dh(sk_bytes: &[u8], peer_pk_bytes: &[u8]) -> PyResult<Zeroizing<[u8; 32]>> {
+    let secret = StaticSecret::from(fixed::<KEY_BYTES>(sk_bytes, "X25519 secret key")?);
+    let peer = X25519PublicKey::from(fixed::<KEY_BYTES>(peer_pk_bytes, "X25519 public key")?);
+    let shared = secret.diffie_hellman(&peer);
+    if !shared.was_contributory() {
+        return Err(pyo3::exceptions::PyValueError::new_err(
+            "X25519 peer public key is a low-order point",
+        ));
+    }
+    Ok(Zeroizing::new(shared.to_bytes()))
+}
+
+pub(crate) fn x25519_public_from_secret(sk_bytes: &[u8]) -> PyResult<[u8; 32]> {
+    let secret = StaticSecret::from(fixed::<KEY_BYTES>(sk_bytes, "X25519 secret key")?);
+    Ok(X25519PublicKey::from(&secret).to_bytes())
+}
@@ -0,0 +1,80 @@
+use pyo3::prelude::*;
+use zeroize::Zeroizing;
+
+// The C backend is used when the `dilithium-c` feature is on and the target can build PQClean;
+// keep this predicate in sync with the target table in Cargo.toml. Only Dilithium has a pure-Rust
+// fallback; Falcon still goes through pqcrypto-falcon and needs the C build on every target.
+#[cfg(all(
+    feature = "dilithium-c",
+    not(any(target_env = "musl", target_arch = "arm"))
+))]
+#[path = "pqclean.rs"]
+mod backend;
+#[cfg(not(all(
+    feature = "dilithium-c",
+    not(any(target_env = "musl", target_arch = "arm"))
+)))]
+#[path = "pure.rs"]
+mod backend;
+
+// Dilithium5 (round 3.1, NIST level 5). Both backends use identical encodings and deterministic
+// signing, so keys and signatures are interchangeable between builds.
+pub(crate) const PUBLIC_KEY_BYTES: usize = 2592;
+pub(crate) const SECRET_KEY_BYTES: usize = 4864;
+pub(crate) const SIGNATURE_BYTES: usize = 4595;
+
+fn check_length(bytes: &[u8], expected: usize, what: &str) -> PyResult<()> {
+    if bytes.len() != expected {
+        return Err(pyo3::exceptions::PyValueError::new_err(format!(
+            "Invalid {} length. Expected {}, got {}",
+            what,
+            expected,
+            bytes.len()
+        )));
+    }
+    Ok(())
+}
+
+pub(crate) fn dilithium_sign(msg: &[u8], sk_bytes: &[u8]) -> PyResult<Vec<u8>> {
+    check_length(sk_bytes, SECRET_KEY_BYTES, "secret key")?;
+    backend::sign(msg, sk_bytes)
+}
+
+pub(crate) fn dilithium_verify(msg: &[u8], sig_bytes: &[u8], pk_bytes: &[u8]) -> PyResult<bool> {
+    check_length(pk_bytes, PUBLIC_KEY_BYTES, "public key")?;
+    if sig_bytes.len() != SIGNATURE_BYTES {
+        return Ok(false);
+    }
+    backend::verify(msg, sig_bytes, pk_bytes)
+}
+
+/// Name of the Dilithium implementation compiled into this build: "pqclean-c" or "pure-rust"
+#[pyfunction]
+pub fn dilithium_backend() -> &'static str {
+    backend::NAME
+}
+
+/// Generate a Dilithium5 keypair. Returns (public_key, secret_key)
+#[pyfunction]
+pub fn generate_dilithium_keys(py: Python<'_>) -> PyResult<(Vec<u8>, Vec<u8>)> {
+    py.allow_threads(backend::keypair)
+}
+
+/// Dilithium5 signing. Returns a detached signature; signing is deterministic, so the same
+/// key and message always produce the same signature on either backend
+#[pyfunction]
+pub fn sign_dilithium(py: Python<'_>, msg: Vec<u8>, sk_bytes: Vec<u8>) -> PyResult<Vec<u8>> {
+    let sk_bytes = Zeroizing::new(sk_bytes);
+    py.allow_threads(|| dilithium_sign(&msg, &sk_bytes))
+}
+
+/// Dilithium5 detached signature verification
+#[pyfunction]
+pub fn verify_dilithium(
+    py: Python<'_>,
+    msg: Vec<u8>,
+    sig_bytes: Vec<u8>,
+    pk_bytes: Vec<u8>,
+) -> PyResult<bool> {
+    py.allow_threads(|| dilithium_verify(&msg, &sig_bytes, &pk_bytes))
+}
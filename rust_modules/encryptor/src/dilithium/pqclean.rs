@@ -0,0 +1,27 @@
+use pqcrypto_dilithium::dilithium5;
+use pqcrypto_traits::sign::{DetachedSignature, PublicKey, SecretKey};
+use pyo3::prelude::*;
+
+pub(super) const NAME: &str = "pqclean-c";
+
+pub(super) fn keypair() -> PyResult<(Vec<u8>, Vec<u8>)> {
+    let (pk, sk) = dilithium5::keypair();
+    Ok((pk.as_bytes().to_vec(), sk.as_bytes().to_vec()))
+}
+
+pub(super) fn sign(msg: &[u8], sk_bytes: &[u8]) -> PyResult<Vec<u8>> {
+    let sk = dilithium5::SecretKey::from_bytes(sk_bytes).map_err(|e| {
+        pyo3::exceptions::PyValueError::new_err(format!("Invalid secret key: {:?}", e))
+    })?;
+    Ok(dilithium5::detached_sign(msg, &sk).as_bytes().to_vec())
+}
+
+pub(super) fn verify(msg: &[u8], sig_bytes: &[u8], pk_bytes: &[u8]) -> PyResult<bool> {
+    let pk = dilithium5::PublicKey::from_bytes(pk_bytes).map_err(|e| {
+        pyo3::exceptions::PyValueError::new_err(format!("Invalid public key: {:?}", e))
+    })?;
+    let sig = dilithium5::DetachedSignature::from_bytes(sig_bytes).map_err(|e| {
+        pyo3::exceptions::PyValueError::new_err(format!("Invalid signature: {:?}", e))
+    })?;
+    Ok(dilithium5::verify_detached_signature(&sig, msg, &pk).is_ok())
+}
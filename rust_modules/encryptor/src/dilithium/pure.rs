@@ -0,0 +1,28 @@
+use crystals_dilithium::dilithium5;
+use pyo3::prelude::*;
+
+pub(super) const NAME: &str = "pure-rust";
+
+pub(super) fn keypair() -> PyResult<(Vec<u8>, Vec<u8>)> {
+    let keypair = dilithium5::Keypair::generate(None).map_err(|e| {
+        pyo3::exceptions::PyRuntimeError::new_err(format!("Key generation failed: {:?}", e))
+    })?;
+    Ok((
+        keypair.public.to_bytes().to_vec(),
+        keypair.secret.to_bytes().to_vec(),
+    ))
+}
+
+pub(super) fn sign(msg: &[u8], sk_bytes: &[u8]) -> PyResult<Vec<u8>> {
+    let sk = dilithium5::SecretKey::from_bytes(sk_bytes).map_err(|e| {
+        pyo3::exceptions::PyValueError::new_err(format!("Invalid secret key: {:?}", e))
+    })?;
+    Ok(sk.sign(msg).to_vec())
+}
+
+pub(super) fn verify(msg: &[u8], sig_bytes: &[u8], pk_bytes: &[u8]) -> PyResult<bool> {
+    let pk = dilithium5::PublicKey::from_bytes(pk_bytes).map_err(|e| {
+        pyo3::exceptions::PyValueError::new_err(format!("Invalid public key: {:?}", e))
+    })?;
+    Ok(pk.verify(msg, sig_bytes))
+}
@@ -0,0 +1,576 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use curve25519_dalek::constants::RISTRETTO_BASEPOINT_POINT;
+use curve25519_dalek::ristretto::RistrettoPoint;
+use curve25519_dalek::scalar::Scalar;
+use curve25519_dalek::traits::Identity;
+use pyo3::prelude::*;
+use sha2::{Digest, Sha512};
+use zeroize::{Zeroize, Zeroizing};
+
+use crate::vss::{commitment_at, evaluate, random_scalar, read_point, scalar};
+use crate::wire::Reader;
+
+// Distributed key generation over Ristretto255, so a trustee quorum holds a group key that no
+// single party ever sees. This is the joint-Feldman DKG with proofs of knowledge used by FROST:
+// every participant i deals a Feldman sharing of their own random a_i0 and the group secret is
+// x = sum_i a_i0 over the qualified participants. Participant j's key share is
+// x_j = sum_i f_i(j), and its public share x_j*G follows from the summed commitments.
+//
+// Rounds, driven from Python, with every message a self-describing byte string:
+//   1. round1() gives a broadcast commitment and one private share per other participant
+//   2. after receive_commitment/receive_share for everything that arrived, complaints() lists
+//      a broadcast complaint for every missing or invalid share
+//   3. justify(complaints) answers complaints against this participant by revealing the
+//      disputed share in a broadcast justification
+//   4. resolve(complaints, justifications) disqualifies every accused participant without a
+//      valid justification, and adopts revealed shares addressed to this participant
+//   5. finish() returns (group key, key share)
+// Commitments, complaints and justifications must go over an authenticated broadcast channel
+// so all honest participants agree on the qualified set; shares need a confidential,
+// authenticated channel (e.g. encrypt_to_public_key under the recipient's key). Layouts, all
+// after magic | version u8 | session id (16):
+//   commitment    "RQKC" | threshold u8 | count u8 | sender u8 | C_0..C_{threshold-1} (32 each)
+//                 | proof of knowledge of a_i0: R (32) | z (32)
+//   share         "RQKS" | sender u8 | recipient u8 | f_sender(recipient) (32)
+//   complaint     "RQKX" | accuser u8 | accused u8
+//   justification "RQKJ" | sender u8 | accuser u8 | f_sender(accuser) (32)
+//   group key     "RQKG" | threshold u8 | count u8 | qualified count u8 | qualified indices
+//                 | A_0..A_{threshold-1} (32 each), where A_0 = x*G is the group public key
+//   key share     "RQKK" | index u8 | x_index (32)
+// The proof of knowledge is a Schnorr proof binding C_0 to the session and sender, which stops
+// rogue-key attacks where a participant picks C_0 as a function of the others'.
+const COMMITMENT_MAGIC: &[u8; 4] = b"RQKC";
+const SHARE_MAGIC: &[u8; 4] = b"RQKS";
+const COMPLAINT_MAGIC: &[u8; 4] = b"RQKX";
+const JUSTIFICATION_MAGIC: &[u8; 4] = b"RQKJ";
+const GROUP_KEY_MAGIC: &[u8; 4] = b"RQKG";
+const KEY_SHARE_MAGIC: &[u8; 4] = b"RQKK";
+const VERSION: u8 = 1;
+const SESSION_BYTES: usize = 16;
+const POK_LABEL: &[u8] = b"reliquary/dkg/v1/proof-of-knowledge";
+
+/// (broadcast commitment, private share for each other participant's index)
+type Round1 = (Vec<u8>, BTreeMap<u8, Vec<u8>>);
+
+fn header(magic: &[u8; 4], session: &[u8]) -> Vec<u8> {
+    let mut out = magic.to_vec();
+    out.push(VERSION);
+    out.extend_from_slice(session);
+    out
+}
+
+/// Checks magic and version and returns (session id, reader positioned after it).
+fn read_header<'a>(
+    bytes: &'a [u8],
+    magic: &[u8; 4],
+    what: &str,
+) -> Result<(&'a [u8], Reader<'a>), String> {
+    let mut r = Reader::new(bytes);
+    if r.bytes(4).map_err(|_| format!("Not a DKG {}", what))? != magic {
+        return Err(format!("Not a DKG {}", what));
+    }
+    let version = r.u8()?;
+    if version != VERSION {
+        return Err(format!("Unsupported DKG {} version {}", what, version));
+    }
+    let session = r.bytes(SESSION_BYTES)?;
+    Ok((session, r))
+}
+
+fn finish_reading(r: &Reader<'_>, what: &str) -> Result<(), String> {
+    if !r.is_empty() {
+        return Err(format!("Trailing data after DKG {}", what));
+    }
+    Ok(())
+}
+
+fn pok_challenge(session: &[u8], sender: u8, c0: &RistrettoPoint, r: &RistrettoPoint) -> Scalar {
+    let mut hasher = Sha512::new();
+    hasher.update(POK_LABEL);
+    hasher.update(session);
+    hasher.update([sender]);
+    hasher.update(c0.compress().as_bytes());
+    hasher.update(r.compress().as_bytes());
+    Scalar::from_hash(hasher)
+}
+
+fn check_threshold(threshold: u8, count: u8) -> Result<(), String> {
+    if threshold < 2 || threshold > count {
+        return Err("DKG threshold must be at least 2 and at most the participant count".into());
+    }
+    Ok(())
+}
+
+/// The public result of a DKG run.
+pub(crate) struct GroupKey {
+    pub(crate) session: Vec<u8>,
+    pub(crate) threshold: u8,
+    pub(crate) count: u8,
+    pub(crate) qualified: Vec<u8>,
+    pub(crate) commitments: Vec<RistrettoPoint>,
+}
+
+impl GroupKey {
+    pub(crate) fn public_key(&self) -> RistrettoPoint {
+        self.commitments[0]
+    }
+
+    /// x_index * G, the public counterpart of a participant's key share.
+    pub(crate) fn public_share(&self, index: u8) -> RistrettoPoint {
+        commitment_at(&self.commitments, index)
+    }
+
+    fn encode(&self) -> Vec<u8> {
+        let mut out = header(GROUP_KEY_MAGIC, &self.session);
+        out.extend_from_slice(&[self.threshold, self.count, self.qualified.len() as u8]);
+        out.extend_from_slice(&self.qualified);
+        for commitment in &self.commitments {
+            out.extend_from_slice(commitment.compress().as_bytes());
+        }
+        out
+    }
+}
+
+pub(crate) fn parse_group_key(bytes: &[u8]) -> Result<GroupKey, String> {
+    let (session, mut r) = read_header(bytes, GROUP_KEY_MAGIC, "group key")?;
+    let threshold = r.u8()?;
+    let count = r.u8()?;
+    check_threshold(threshold, count)?;
+    let qualified_count = r.u8()? as usize;
+    let qualified = r.bytes(qualified_count)?.to_vec();
+    if qualified.len() < threshold as usize
+        || qualified.windows(2).any(|w| w[0] >= w[1])
+        || qualified.iter().any(|&i| i == 0 || i > count)
+    {
+        return Err("DKG group key has an invalid qualified set".to_string());
+    }
+    let commitments = (0..threshold)
+        .map(|j| read_point(&mut r, &format!("Group commitment {}", j)))
+        .collect::<Result<Vec<_>, _>>()?;
+    finish_reading(&r, "group key")?;
+    Ok(GroupKey {
+        session: session.to_vec(),
+        threshold,
+        count,
+        qualified,
+        commitments,
+    })
+}
+
+/// A participant's secret key share: (session id, index, x_index).
+pub(crate) fn parse_key_share(bytes: &[u8]) -> Result<(Vec<u8>, u8, Scalar), String> {
+    let (session, mut r) = read_header(bytes, KEY_SHARE_MAGIC, "key share")?;
+    let index = r.u8()?;
+    let x = scalar(r.bytes(32)?, "value")?;
+    finish_reading(&r, "key share")?;
+    Ok((session.to_vec(), index, x))
+}
+
+/// Whether `share` is the key share of a qualified participant in `group`.
+pub(crate) fn key_share_is_valid(group: &GroupKey, session: &[u8], index: u8, x: &Scalar) -> bool {
+    session == group.session.as_slice()
+        && group.qualified.contains(&index)
+        && RISTRETTO_BASEPOINT_POINT * x == group.public_share(index)
+}
+
+/// One participant in a DKG session. Every participant is constructed with the same session id
+/// (16 random bytes chosen by whoever convenes the quorum), threshold and count, and its own
+/// index in 1..=count. Malformed messages, messages from another session and out-of-order calls
+/// raise ValueError; a commitment with a bad proof of knowledge is not an error but disqualifies
+/// its sender
+#[pyclass(module = "reliquary_encryptor")]
+pub struct DkgParticipant {
+    session: [u8; SESSION_BYTES],
+    index: u8,
+    threshold: u8,
+    count: u8,
+    coefficients: Option<Zeroizing<Vec<Scalar>>>,
+    commitments: BTreeMap<u8, Vec<RistrettoPoint>>,
+    shares: BTreeMap<u8, Scalar>,
+    disqualified: BTreeSet<u8>,
+}
+
+impl Drop for DkgParticipant {
+    fn drop(&mut self) {
+        for share in self.shares.values_mut() {
+            share.zeroize();
+        }
+    }
+}
+
+impl DkgParticipant {
+    fn check_session(&self, session: &[u8]) -> Result<(), String> {
+        if session != self.session {
+            return Err("Message is from another DKG session".to_string());
+        }
+        Ok(())
+    }
+
+    fn check_participant(&self, index: u8, role: &str) -> Result<(), String> {
+        if index == 0 || index > self.count {
+            return Err(format!("DKG {} index {} is out of range", role, index));
+        }
+        Ok(())
+    }
+
+    fn parse_complaint(&self, complaint: &[u8]) -> Result<(u8, u8), String> {
+        let (session, mut r) = read_header(complaint, COMPLAINT_MAGIC, "complaint")?;
+        self.check_session(session)?;
+        let accuser = r.u8()?;
+        let accused = r.u8()?;
+        finish_reading(&r, "complaint")?;
+        self.check_participant(accuser, "accuser")?;
+        self.check_participant(accused, "accused")?;
+        Ok((accuser, accused))
+    }
+
+    fn coefficients(&self) -> Result<&[Scalar], String> {
+        self.coefficients
+            .as_deref()
+            .map(Vec::as_slice)
+            .ok_or_else(|| "round1() has not been run".to_string())
+    }
+
+    fn share_is_valid(&self, sender: u8, recipient: u8, value: &Scalar) -> bool {
+        self.commitments.get(&sender).is_some_and(|commitments| {
+            RISTRETTO_BASEPOINT_POINT * value == commitment_at(commitments, recipient)
+        })
+    }
+
+    /// Participants whose commitment arrived and who have not been disqualified.
+    fn candidates(&self) -> impl Iterator<Item = u8> + '_ {
+        self.commitments
+            .keys()
+            .copied()
+            .filter(|i| !self.disqualified.contains(i))
+    }
+}
+
+#[pymethods]
+impl DkgParticipant {
+    #[new]
+    fn new(session_id: Vec<u8>, index: u8, threshold: u8, count: u8) -> PyResult<Self> {
+        (|| {
+            let session = session_id
+                .as_slice()
+                .try_into()
+                .map_err(|_| format!("DKG session id must be {} bytes", SESSION_BYTES))?;
+            check_threshold(threshold, count)?;
+            if index == 0 || index > count {
+                return Err(format!("Participant index must be in 1..={}", count));
+            }
+            Ok(Self {
+                session,
+                index,
+                threshold,
+                count,
+                coefficients: None,
+                commitments: BTreeMap::new(),
+                shares: BTreeMap::new(),
+                disqualified: BTreeSet::new(),
+            })
+        })()
+        .map_err(pyo3::exceptions::PyValueError::new_err)
+    }
+
+    #[getter]
+    fn session_id(&self) -> Vec<u8> {
+        self.session.to_vec()
+    }
+
+    #[getter]
+    fn index(&self) -> u8 {
+        self.index
+    }
+
+    #[getter]
+    fn threshold(&self) -> u8 {
+        self.threshold
+    }
+
+    #[getter]
+    fn count(&self) -> u8 {
+        self.count
+    }
+
+    /// Participants disqualified so far, in index order
+    #[getter]
+    fn disqualified(&self) -> Vec<usize> {
+        self.disqualified.iter().map(|&i| i as usize).collect()
+    }
+
+    /// Deals this participant's contribution. Returns (commitment, shares) where the commitment
+    /// is broadcast and shares maps each other participant's index to the share sent privately
+    /// to them
+    fn round1(&mut self) -> PyResult<Round1> {
+        if self.coefficients.is_some() {
+            return Err(pyo3::exceptions::PyValueError::new_err(
+                "round1() has already been run",
+            ));
+        }
+        let a = Zeroizing::new(
+            (0..self.threshold)
+                .map(|_| random_scalar())
+                .collect::<Vec<_>>(),
+        );
+        let commitments: Vec<RistrettoPoint> =
+            a.iter().map(|aj| RISTRETTO_BASEPOINT_POINT * aj).collect();
+        let k = Zeroizing::new(random_scalar());
+        let r = RISTRETTO_BASEPOINT_POINT * *k;
+        let z = *k + a[0] * pok_challenge(&self.session, self.index, &commitments[0], &r);
+
+        let mut commitment = header(COMMITMENT_MAGIC, &self.session);
+        commitment.extend_from_slice(&[self.threshold, self.count, self.index]);
+        for c in &commitments {
+            commitment.extend_from_slice(c.compress().as_bytes());
+        }
+        commitment.extend_from_slice(r.compress().as_bytes());
+        commitment.extend_from_slice(z.as_bytes());
+
+        let mut shares = BTreeMap::new();
+        for recipient in (1..=self.count).filter(|&j| j != self.index) {
+            let mut share = header(SHARE_MAGIC, &self.session);
+            share.extend_from_slice(&[self.index, recipient]);
+            share.extend_from_slice(evaluate(&a, recipient).as_bytes());
+            shares.insert(recipient, share);
+        }
+        self.shares.insert(self.index, evaluate(&a, self.index));
+        self.commitments.insert(self.index, commitments);
+        self.coefficients = Some(a);
+        Ok((commitment, shares))
+    }
+
+    /// Records another participant's broadcast commitment. Returns False, and disqualifies the
+    /// sender, when its proof of knowledge does not verify
+    fn receive_commitment(&mut self, commitment: Vec<u8>) -> PyResult<bool> {
+        (|| {
+            let (session, mut r) = read_header(&commitment, COMMITMENT_MAGIC, "commitment")?;
+            self.check_session(session)?;
+            if (r.u8()?, r.u8()?) != (self.threshold, self.count) {
+                return Err("Commitment is for different DKG parameters".to_string());
+            }
+            let sender = r.u8()?;
+            self.check_participant(sender, "sender")?;
+            if sender == self.index {
+                return Err("Commitment claims to be from this participant".to_string());
+            }
+            let commitments = (0..self.threshold)
+                .map(|j| read_point(&mut r, &format!("Commitment {}", j)))
+                .collect::<Result<Vec<_>, _>>()?;
+            let nonce_point = read_point(&mut r, "Proof commitment")?;
+            let z = scalar(r.bytes(32)?, "proof response")?;
+            finish_reading(&r, "commitment")?;
+            if let Some(existing) = self.commitments.get(&sender) {
+                if *existing != commitments {
+                    self.disqualified.insert(sender);
+                    return Err(format!("Participant {} sent two commitments", sender));
+                }
+                return Ok(!self.disqualified.contains(&sender));
+            }
+            let c = pok_challenge(&self.session, sender, &commitments[0], &nonce_point);
+            let valid = RISTRETTO_BASEPOINT_POINT * z == nonce_point + commitments[0] * c;
+            if !valid {
+                self.disqualified.insert(sender);
+            }
+            self.commitments.insert(sender, commitments);
+            Ok(valid)
+        })()
+        .map_err(pyo3::exceptions::PyValueError::new_err)
+    }
+
+    /// Records a share sent privately to this participant. Shares are checked by complaints(),
+    /// so one may arrive before its sender's commitment
+    fn receive_share(&mut self, share: Vec<u8>) -> PyResult<()> {
+        let share = Zeroizing::new(share);
+        (|| {
+            let (session, mut r) = read_header(&share, SHARE_MAGIC, "share")?;
+            self.check_session(session)?;
+            let sender = r.u8()?;
+            let recipient = r.u8()?;
+            self.check_participant(sender, "sender")?;
+            if recipient != self.index || sender == self.index {
+                return Err(format!(
+                    "Share is addressed to participant {}, not {}",
+                    recipient, self.index
+                ));
+            }
+            let value = scalar(r.bytes(32)?, "value")?;
+            finish_reading(&r, "share")?;
+            if let Some(existing) = self.shares.get(&sender) {
+                if *existing != value {
+                    return Err(format!("Participant {} sent two different shares", sender));
+                }
+            }
+            self.shares.insert(sender, value);
+            Ok(())
+        })()
+        .map_err(pyo3::exceptions::PyValueError::new_err)
+    }
+
+    /// Broadcast complaints against every participant whose commitment arrived but whose share
+    /// is missing or does not match it
+    fn complaints(&self) -> PyResult<Vec<Vec<u8>>> {
+        self.coefficients()
+            .map_err(pyo3::exceptions::PyValueError::new_err)?;
+        Ok(self
+            .candidates()
+            .filter(|&j| {
+                !self
+                    .shares
+                    .get(&j)
+                    .is_some_and(|s| self.share_is_valid(j, self.index, s))
+            })
+            .map(|accused| {
+                let mut out = header(COMPLAINT_MAGIC, &self.session);
+                out.extend_from_slice(&[self.index, accused]);
+                out
+            })
+            .collect())
+    }
+
+    /// Answers the complaints against this participant among `complaints` (which may hold every
+    /// complaint broadcast in the session) with broadcast justifications
+    fn justify(&self, complaints: Vec<Vec<u8>>) -> PyResult<Vec<Vec<u8>>> {
+        (|| -> Result<Vec<Vec<u8>>, String> {
+            let a = self.coefficients()?;
+            let mut out = Vec::new();
+            for complaint in &complaints {
+                let (accuser, accused) = self.parse_complaint(complaint)?;
+                if accused == self.index {
+                    let mut justification = header(JUSTIFICATION_MAGIC, &self.session);
+                    justification.extend_from_slice(&[self.index, accuser]);
+                    justification.extend_from_slice(evaluate(a, accuser).as_bytes());
+                    out.push(justification);
+                }
+            }
+            Ok(out)
+        })()
+        .map_err(pyo3::exceptions::PyValueError::new_err)
+    }
+
+    /// Settles every complaint broadcast in the session: an accused participant is disqualified
+    /// unless one of `justifications` reveals a share for the accuser that matches its
+    /// commitment. Returns the participants disqualified so far
+    fn resolve(
+        &mut self,
+        complaints: Vec<Vec<u8>>,
+        justifications: Vec<Vec<u8>>,
+    ) -> PyResult<Vec<usize>> {
+        (|| -> Result<Vec<usize>, String> {
+            self.coefficients()?;
+            let mut revealed = BTreeMap::new();
+            for justification in &justifications {
+                let (session, mut r) =
+                    read_header(justification, JUSTIFICATION_MAGIC, "justification")?;
+                self.check_session(session)?;
+                let sender = r.u8()?;
+                let accuser = r.u8()?;
+                self.check_participant(sender, "sender")?;
+                self.check_participant(accuser, "accuser")?;
+                let value = scalar(r.bytes(32)?, "value")?;
+                finish_reading(&r, "justification")?;
+                if self.share_is_valid(sender, accuser, &value) {
+                    revealed.insert((accuser, sender), value);
+                }
+            }
+            for complaint in &complaints {
+                let (accuser, accused) = self.parse_complaint(complaint)?;
+                match revealed.get(&(accuser, accused)) {
+                    Some(value) if accuser == self.index => {
+                        self.shares.insert(accused, *value);
+                    }
+                    Some(_) => {}
+                    None => {
+                        self.disqualified.insert(accused);
+                    }
+                }
+            }
+            for value in revealed.values_mut() {
+                value.zeroize();
+            }
+            Ok(self.disqualified.iter().map(|&i| i as usize).collect())
+        })()
+        .map_err(pyo3::exceptions::PyValueError::new_err)
+    }
+
+    /// Completes the session. Returns (group_key, key_share): the group key is public and the
+    /// same for every honest participant; the key share is this participant's secret. Fails
+    /// while a qualified participant's share is missing or invalid, or if fewer than threshold
+    /// participants qualify
+    fn finish(&self) -> PyResult<(Vec<u8>, Vec<u8>)> {
+        (|| {
+            self.coefficients()?;
+            let qualified: Vec<u8> = self.candidates().collect();
+            if qualified.len() < self.threshold as usize {
+                return Err(format!(
+                    "Only {} participants qualified, {} are needed",
+                    qualified.len(),
+                    self.threshold
+                ));
+            }
+            let mut commitments = vec![RistrettoPoint::identity(); self.threshold as usize];
+            let mut x = Zeroizing::new(Scalar::ZERO);
+            for &j in &qualified {
+                let share = self
+                    .shares
+                    .get(&j)
+                    .filter(|s| self.share_is_valid(j, self.index, s))
+                    .ok_or_else(|| {
+                        format!(
+                            "No valid share from participant {}; complain and resolve first",
+                            j
+                        )
+                    })?;
+                *x += share;
+                for (sum, c) in commitments.iter_mut().zip(&self.commitments[&j]) {
+                    *sum += c;
+                }
+            }
+            let group = GroupKey {
+                session: self.session.to_vec(),
+                threshold: self.threshold,
+                count: self.count,
+                qualified,
+                commitments,
+            };
+            let mut key_share = header(KEY_SHARE_MAGIC, &self.session);
+            key_share.push(self.index);
+            key_share.extend_from_slice(x.as_bytes());
+            Ok((group.encode(), key_share))
+        })()
+        .map_err(pyo3::exceptions::PyValueError::new_err)
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "DkgParticipant(index={}, threshold={}, count={}, disqualified={:?})",
+            self.index, self.threshold, self.count, self.disqualified
+        )
+    }
+}
+
+/// Returns (threshold, count, public_key, qualified) from a DKG group key, where public_key is
+/// the 32-byte compressed Ristretto group public key
+#[pyfunction]
+pub fn dkg_group_key_info(group_key: Vec<u8>) -> PyResult<(u8, u8, Vec<u8>, Vec<usize>)> {
+    let group = parse_group_key(&group_key).map_err(pyo3::exceptions::PyValueError::new_err)?;
+    Ok((
+        group.threshold,
+        group.count,
+        group.public_key().compress().to_bytes().to_vec(),
+        group.qualified.iter().map(|&i| i as usize).collect(),
+    ))
+}
+
+/// Checks a key share against the group key's public shares. False means the share is corrupt,
+/// from another session or held by a disqualified participant
+#[pyfunction]
+pub fn dkg_verify_key_share(group_key: Vec<u8>, key_share: Vec<u8>) -> PyResult<bool> {
+    let key_share = Zeroizing::new(key_share);
+    let group = parse_group_key(&group_key).map_err(pyo3::exceptions::PyValueError::new_err)?;
+    let (session, index, x) =
+        parse_key_share(&key_share).map_err(pyo3::exceptions::PyValueError::new_err)?;
+    let x = Zeroizing::new(x);
+    Ok(key_share_is_valid(&group, &session, index, &x))
+}
@@ -0,0 +1,211 @@
+use aes_gcm::aead::{Aead, AeadCore, KeyInit, OsRng, Payload};
+use aes_gcm::{Aes256Gcm, Nonce};
+use hkdf::Hkdf;
+use pyo3::prelude::*;
+use sha2::Sha256;
+use zeroize::Zeroizing;
+
+use crate::faults;
+use crate::fingerprint::{fingerprint, kyber_public_key_from_secret};
+use crate::handles::DecryptionKeyArg;
+use crate::kms;
+use crate::limits;
+use crate::params::KyberParams;
+use crate::recipients;
+
+// Envelope layout (v1, single recipient):
+//   magic "RQEV" | version u8 | kem id u8 | kem ciphertext | nonce (12) | AES-256-GCM ct || tag
+// Everything before the nonce is authenticated as associated data. Multi-recipient envelopes
+// share the magic with version 2 (see recipients.rs) and cloud-KMS-wrapped ones with version 3
+// (see kms.rs).
+pub(crate) const MAGIC: &[u8; 4] = b"RQEV";
+pub(crate) const VERSION: u8 = 1;
+const NONCE_BYTES: usize = 12;
+const TAG_BYTES: usize = 16;
+const KDF_LABEL: &[u8] = b"reliquary/envelope/v1/aes-256-gcm";
+
+/// Parsed view of an envelope; slices borrow from the serialized bytes.
+pub(crate) struct Envelope<'a> {
+    pub params: KyberParams,
+    pub header: &'a [u8],
+    pub kem_ciphertext: &'a [u8],
+    pub nonce: &'a [u8],
+    pub ciphertext: &'a [u8],
+}
+
+pub(crate) fn parse(envelope: &[u8]) -> Result<Envelope<'_>, String> {
+    limits::check_input("Envelope", envelope.len())?;
+    if envelope.len() < MAGIC.len() + 2 || &envelope[..MAGIC.len()] != MAGIC {
+        return Err("Not a Reliquary envelope".to_string());
+    }
+    let version = envelope[4];
+    if version != VERSION {
+        return Err(format!("Unsupported envelope version {}", version));
+    }
+    let params = KyberParams::from_wire_id(envelope[5])
+        .ok_or_else(|| format!("Unknown envelope KEM id {}", envelope[5]))?;
+    let ct_end = 6 + params.ciphertext_bytes();
+    if envelope.len() < ct_end + NONCE_BYTES + TAG_BYTES {
+        return Err("Envelope is truncated".to_string());
+    }
+    Ok(Envelope {
+        params,
+        header: &envelope[..ct_end],
+        kem_ciphertext: &envelope[6..ct_end],
+        nonce: &envelope[ct_end..ct_end + NONCE_BYTES],
+        ciphertext: &envelope[ct_end + NONCE_BYTES..],
+    })
+}
+
+/// Exact envelope size for a plaintext of `plaintext_len` bytes.
+pub(crate) fn sealed_len(params: KyberParams, plaintext_len: usize) -> usize {
+    MAGIC.len() + 2 + params.ciphertext_bytes() + NONCE_BYTES + plaintext_len + TAG_BYTES
+}
+
+/// Derives the AES-256 key from the KEM shared secret, bound to the envelope header.
+fn derive_key(shared_secret: &[u8], header: &[u8]) -> Zeroizing<[u8; 32]> {
+    let mut key = Zeroizing::new([0u8; 32]);
+    Hkdf::<Sha256>::new(Some(header), shared_secret)
+        .expand(KDF_LABEL, key.as_mut())
+        .expect("32 bytes is a valid HKDF-SHA256 output length");
+    key
+}
+
+pub(crate) fn is_multi_recipient(envelope: &[u8]) -> bool {
+    envelope.len() > MAGIC.len()
+        && &envelope[..MAGIC.len()] == MAGIC
+        && envelope[4] == recipients::VERSION
+}
+
+pub(crate) fn seal(data: &[u8], pk_bytes: &[u8], params: KyberParams) -> PyResult<Vec<u8>> {
+    faults::inject(faults::Operation::Encrypt, || {
+        let (shared_secret, kem_ciphertext) = crate::kyber_encapsulate(pk_bytes, params)?;
+        let shared_secret = Zeroizing::new(shared_secret);
+
+        let mut envelope = Vec::with_capacity(sealed_len(params, data.len()));
+        envelope.extend_from_slice(MAGIC);
+        envelope.push(VERSION);
+        envelope.push(params.wire_id());
+        envelope.extend_from_slice(&kem_ciphertext);
+
+        let key = derive_key(&shared_secret, &envelope);
+        let cipher = Aes256Gcm::new(key.as_ref().into());
+        let nonce = Aes256Gcm::generate_nonce(&mut OsRng);
+        let ciphertext = cipher
+            .encrypt(
+                &nonce,
+                Payload {
+                    msg: data,
+                    aad: &envelope,
+                },
+            )
+            .map_err(|e| {
+                pyo3::exceptions::PyValueError::new_err(format!("Encryption error: {:?}", e))
+            })?;
+        envelope.extend_from_slice(&nonce);
+        envelope.extend_from_slice(&ciphertext);
+        Ok(envelope)
+    })
+}
+
+pub(crate) fn open(envelope: &[u8], sk_bytes: &[u8]) -> PyResult<Vec<u8>> {
+    faults::inject(faults::Operation::Decrypt, || {
+        if is_multi_recipient(envelope) {
+            return recipients::open(envelope, sk_bytes)
+                .map_err(pyo3::exceptions::PyValueError::new_err);
+        }
+        if kms::is_kms_wrapped(envelope) {
+            return Err(pyo3::exceptions::PyValueError::new_err(
+                "Envelope's data key is wrapped by a cloud KMS; open it with decrypt_with_kms_dek",
+            ));
+        }
+        let parsed = parse(envelope).map_err(pyo3::exceptions::PyValueError::new_err)?;
+        let shared_secret = Zeroizing::new(crate::kyber_decapsulate(
+            parsed.kem_ciphertext,
+            sk_bytes,
+            parsed.params,
+        )?);
+        let key = derive_key(&shared_secret, parsed.header);
+        let cipher = Aes256Gcm::new(key.as_ref().into());
+        cipher
+            .decrypt(
+                Nonce::from_slice(parsed.nonce),
+                Payload {
+                    msg: parsed.ciphertext,
+                    aad: parsed.header,
+                },
+            )
+            .map_err(|_| {
+                pyo3::exceptions::PyValueError::new_err(
+                    "Envelope authentication failed (wrong key or tampered envelope)",
+                )
+            })
+    })
+}
+
+/// One-shot public key encryption: Kyber encapsulation, HKDF-SHA256 key derivation and
+/// AES-256-GCM in a single self-describing envelope
+#[pyfunction]
+#[pyo3(signature = (data, kyber_pk, params = KyberParams::Kyber1024))]
+pub fn encrypt_to_public_key(
+    py: Python<'_>,
+    data: Vec<u8>,
+    kyber_pk: Vec<u8>,
+    params: KyberParams,
+) -> PyResult<Vec<u8>> {
+    py.allow_threads(|| seal(&data, &kyber_pk, params))
+}
+
+/// Opens an envelope from `encrypt_to_public_key` or `encrypt_to_recipients`. The Kyber
+/// parameter set is read from the envelope header
+#[pyfunction]
+pub fn decrypt_with_secret_key(
+    py: Python<'_>,
+    envelope: Vec<u8>,
+    kyber_sk: DecryptionKeyArg,
+) -> PyResult<Vec<u8>> {
+    py.allow_threads(|| open(&envelope, &kyber_sk))
+}
+
+/// Opens an envelope and, if it is stale, re-seals the plaintext to the current key.
+/// An envelope is stale when it was sealed to a different key than `current_pk`, under a
+/// different parameter set than `current_params`, or in an older envelope format.
+/// Returns (plaintext, upgraded_envelope); upgraded_envelope is None when the envelope is
+/// already current. Nothing is returned unless both the open and the re-seal succeed, so the
+/// caller can swap the stored envelope in one write
+#[pyfunction]
+#[pyo3(signature = (envelope, kyber_sk, current_pk, current_params = KyberParams::Kyber1024))]
+pub fn decrypt_and_rewrap(
+    py: Python<'_>,
+    envelope: Vec<u8>,
+    kyber_sk: DecryptionKeyArg,
+    current_pk: Vec<u8>,
+    current_params: KyberParams,
+) -> PyResult<(Vec<u8>, Option<Vec<u8>>)> {
+    if current_pk.len() != current_params.public_key_bytes() {
+        return Err(pyo3::exceptions::PyValueError::new_err(format!(
+            "Invalid public key length. Expected {}, got {}",
+            current_params.public_key_bytes(),
+            current_pk.len()
+        )));
+    }
+    if is_multi_recipient(&envelope) {
+        return Err(pyo3::exceptions::PyValueError::new_err(
+            "Multi-recipient envelopes can't be re-wrapped to a single key",
+        ));
+    }
+    py.allow_threads(|| {
+        let plaintext = Zeroizing::new(open(&envelope, &kyber_sk)?);
+        let parsed = parse(&envelope).map_err(pyo3::exceptions::PyValueError::new_err)?;
+        let sealed_pk = kyber_public_key_from_secret(&kyber_sk, parsed.params);
+        let stale = envelope[4] != VERSION
+            || parsed.params != current_params
+            || fingerprint(sealed_pk) != fingerprint(&current_pk);
+        let upgraded = if stale {
+            Some(seal(&plaintext, &current_pk, current_params)?)
+        } else {
+            None
+        };
+        Ok((plaintext.to_vec(), upgraded))
+    })
+}
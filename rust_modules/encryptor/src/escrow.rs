@@ -0,0 +1,202 @@
+use aes_gcm::aead::{Aead, AeadCore, KeyInit, OsRng, Payload};
+use aes_gcm::{Aes256Gcm, Nonce};
+use pyo3::prelude::*;
+use rand::RngCore;
+use zeroize::Zeroizing;
+
+use crate::wire::{put_var16, put_var8, Reader};
+
+// Dual-control key escrow. The escrowed key is split into two XOR shares; share A is wrapped
+// under role A's KEK and share B under role B's, so neither KEK alone learns anything about
+// the key and recovery needs both unwraps to succeed. Layout:
+//   magic "RQDE" | version u8 | role A var8 | role B var8
+//   | for each role: nonce (12) | AES-256-GCM(share) var16
+// Each share's AAD is header | role index u8 | caller AAD, so shares can't be swapped
+// between roles or escrows, and the role names can't be edited.
+const MAGIC: &[u8; 4] = b"RQDE";
+const VERSION: u8 = 1;
+const NONCE_BYTES: usize = 12;
+const KEK_BYTES: usize = 32;
+const MAX_KEY_BYTES: usize = 4096;
+
+struct Escrow<'a> {
+    header: &'a [u8],
+    roles: [&'a str; 2],
+    shares: [(&'a [u8], &'a [u8]); 2],
+}
+
+fn parse<'a>(blob: &'a [u8]) -> Result<Escrow<'a>, String> {
+    let mut r = Reader::new(blob);
+    if r.bytes(MAGIC.len())? != MAGIC {
+        return Err("Not a dual-control escrow".to_string());
+    }
+    let version = r.u8()?;
+    if version != VERSION {
+        return Err(format!("Unsupported escrow version {}", version));
+    }
+    let role_a = r.var8()?;
+    let role_b = r.var8()?;
+    let header = &blob[..r.position()];
+    let mut shares = [(&[][..], &[][..]); 2];
+    for share in shares.iter_mut() {
+        *share = (r.bytes(NONCE_BYTES)?, r.var16()?);
+    }
+    if !r.is_empty() {
+        return Err("Trailing data after escrow".to_string());
+    }
+    let role = |name: &'a [u8]| {
+        std::str::from_utf8(name).map_err(|_| "Escrow role name is not UTF-8".to_string())
+    };
+    Ok(Escrow {
+        header,
+        roles: [role(role_a)?, role(role_b)?],
+        shares,
+    })
+}
+
+fn share_aad(header: &[u8], index: u8, aad: &[u8]) -> Vec<u8> {
+    let mut out = Vec::with_capacity(header.len() + 1 + aad.len());
+    out.extend_from_slice(header);
+    out.push(index);
+    out.extend_from_slice(aad);
+    out
+}
+
+fn check_kek(role: &str, kek: &[u8]) -> Result<(), String> {
+    if kek.len() != KEK_BYTES {
+        return Err(format!("KEK for {} must be {} bytes", role, KEK_BYTES));
+    }
+    Ok(())
+}
+
+fn unwrap_share(
+    escrow: &Escrow<'_>,
+    index: usize,
+    kek: &[u8],
+    aad: &[u8],
+) -> Result<Zeroizing<Vec<u8>>, String> {
+    let role = escrow.roles[index];
+    check_kek(role, kek)?;
+    let (nonce, ciphertext) = escrow.shares[index];
+    Aes256Gcm::new(kek.into())
+        .decrypt(
+            Nonce::from_slice(nonce),
+            Payload {
+                msg: ciphertext,
+                aad: &share_aad(escrow.header, index as u8, aad),
+            },
+        )
+        .map(Zeroizing::new)
+        .map_err(|_| format!("Unwrap with the {} KEK failed", role))
+}
+
+/// Escrows `key` under two KEKs held by different roles; recovering it needs both. Role names
+/// are stored in the escrow and authenticated; `aad` must be repeated on unwrap
+#[pyfunction]
+#[pyo3(signature = (key, kek_a, kek_b, role_a = "custodian-a", role_b = "custodian-b", aad = Vec::new()))]
+pub fn escrow_wrap_dual(
+    key: Vec<u8>,
+    kek_a: Vec<u8>,
+    kek_b: Vec<u8>,
+    role_a: &str,
+    role_b: &str,
+    aad: Vec<u8>,
+) -> PyResult<Vec<u8>> {
+    let key = Zeroizing::new(key);
+    let keks = [Zeroizing::new(kek_a), Zeroizing::new(kek_b)];
+    (|| {
+        if key.is_empty() || key.len() > MAX_KEY_BYTES {
+            return Err(format!("Escrowed key must be 1 to {} bytes", MAX_KEY_BYTES));
+        }
+        if role_a.is_empty() || role_b.is_empty() || role_a == role_b {
+            return Err("Dual control needs two distinct, non-empty role names".to_string());
+        }
+        check_kek(role_a, &keks[0])?;
+        check_kek(role_b, &keks[1])?;
+        if keks[0] == keks[1] {
+            return Err("Dual control needs two different KEKs".to_string());
+        }
+
+        let mut share_a = Zeroizing::new(vec![0u8; key.len()]);
+        OsRng.fill_bytes(&mut share_a);
+        let share_b = Zeroizing::new(
+            key.iter()
+                .zip(share_a.iter())
+                .map(|(k, a)| k ^ a)
+                .collect::<Vec<u8>>(),
+        );
+
+        let mut out = MAGIC.to_vec();
+        out.push(VERSION);
+        put_var8(&mut out, role_a.as_bytes())?;
+        put_var8(&mut out, role_b.as_bytes())?;
+        let header_len = out.len();
+        for (index, (kek, share)) in keks.iter().zip([&share_a, &share_b]).enumerate() {
+            let nonce = Aes256Gcm::generate_nonce(&mut OsRng);
+            let ct = Aes256Gcm::new(kek.as_slice().into())
+                .encrypt(
+                    &nonce,
+                    Payload {
+                        msg: share,
+                        aad: &share_aad(&out[..header_len], index as u8, &aad),
+                    },
+                )
+                .expect("AES-GCM encryption of in-memory data cannot fail");
+            out.extend_from_slice(&nonce);
+            put_var16(&mut out, &ct)?;
+        }
+        Ok(out)
+    })()
+    .map_err(pyo3::exceptions::PyValueError::new_err)
+}
+
+/// Recovers a key escrowed by `escrow_wrap_dual`. Both KEKs must unwrap their share; a wrong
+/// KEK raises ValueError naming its role
+#[pyfunction]
+#[pyo3(signature = (blob, kek_a, kek_b, aad = Vec::new()))]
+pub fn escrow_unwrap_dual(
+    blob: Vec<u8>,
+    kek_a: Vec<u8>,
+    kek_b: Vec<u8>,
+    aad: Vec<u8>,
+) -> PyResult<Vec<u8>> {
+    let keks = [Zeroizing::new(kek_a), Zeroizing::new(kek_b)];
+    (|| {
+        let escrow = parse(&blob)?;
+        let share_a = unwrap_share(&escrow, 0, &keks[0], &aad)?;
+        let share_b = unwrap_share(&escrow, 1, &keks[1], &aad)?;
+        if share_a.len() != share_b.len() {
+            return Err("Escrow shares have different lengths".to_string());
+        }
+        Ok(share_a
+            .iter()
+            .zip(share_b.iter())
+            .map(|(a, b)| a ^ b)
+            .collect())
+    })()
+    .map_err(pyo3::exceptions::PyValueError::new_err)
+}
+
+/// Lets one custodian check their KEK against an escrow without the other KEK; the share it
+/// unwraps reveals nothing about the key
+#[pyfunction]
+#[pyo3(signature = (blob, role, kek, aad = Vec::new()))]
+pub fn escrow_verify_kek(blob: Vec<u8>, role: &str, kek: Vec<u8>, aad: Vec<u8>) -> PyResult<bool> {
+    let kek = Zeroizing::new(kek);
+    let escrow = parse(&blob).map_err(pyo3::exceptions::PyValueError::new_err)?;
+    let index = escrow
+        .roles
+        .iter()
+        .position(|r| *r == role)
+        .ok_or_else(|| {
+            pyo3::exceptions::PyValueError::new_err(format!("Escrow has no role '{}'", role))
+        })?;
+    Ok(unwrap_share(&escrow, index, &kek, &aad).is_ok())
+}
+
+/// Returns the (role_a, role_b) names recorded in an escrow
+#[pyfunction]
+pub fn escrow_roles(blob: Vec<u8>) -> PyResult<(String, String)> {
+    let escrow = parse(&blob).map_err(pyo3::exceptions::PyValueError::new_err)?;
+    Ok((escrow.roles[0].to_string(), escrow.roles[1].to_string()))
+}
@@ -0,0 +1,296 @@
+use pyo3::prelude::*;
+
+// Fault injection for resilience testing. Built only with the `fault-injection` feature; in
+// other builds `inject` is a plain call and the control functions aren't registered, so a
+// release module can't be made to misbehave. When built in, each hooked operation draws once
+// per call from a seeded RNG against its rule: fail with InjectedFault, sleep before running,
+// or run and corrupt the output (flip one byte, or flip a verification result). Corruptions
+// are recorded by SHA-256 of the corrupted bytes so tests can tell injected damage from real
+// bugs, and every injected fault is appended to an in-process log. The same seed and call
+// sequence give the same faults.
+pyo3::create_exception!(
+    reliquary_encryptor,
+    InjectedFault,
+    pyo3::exceptions::PyRuntimeError,
+    "Raised in place of a crypto operation's result by fault injection"
+);
+
+/// Hooked operations; each covers every code path that performs it (envelopes, raw AES-GCM,
+/// Kyber, Falcon and Ed25519).
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub(crate) enum Operation {
+    Encrypt,
+    Decrypt,
+    Encapsulate,
+    Decapsulate,
+    Sign,
+    Verify,
+}
+
+impl Operation {
+    #[cfg_attr(not(feature = "fault-injection"), allow(dead_code))]
+    const ALL: [Operation; 6] = [
+        Operation::Encrypt,
+        Operation::Decrypt,
+        Operation::Encapsulate,
+        Operation::Decapsulate,
+        Operation::Sign,
+        Operation::Verify,
+    ];
+
+    #[cfg_attr(not(feature = "fault-injection"), allow(dead_code))]
+    fn name(self) -> &'static str {
+        match self {
+            Operation::Encrypt => "encrypt",
+            Operation::Decrypt => "decrypt",
+            Operation::Encapsulate => "encapsulate",
+            Operation::Decapsulate => "decapsulate",
+            Operation::Sign => "sign",
+            Operation::Verify => "verify",
+        }
+    }
+}
+
+/// Outputs fault injection knows how to corrupt.
+pub(crate) trait Corruptible {
+    /// Damages the output in place, using `position` to pick what to damage; returns the
+    /// corrupted bytes to record, if the output is bytes.
+    #[cfg_attr(not(feature = "fault-injection"), allow(dead_code))]
+    fn corrupt(&mut self, position: u64) -> Option<&[u8]>;
+}
+
+impl Corruptible for Vec<u8> {
+    fn corrupt(&mut self, position: u64) -> Option<&[u8]> {
+        if self.is_empty() {
+            self.push(0xff);
+        } else {
+            let i = (position % self.len() as u64) as usize;
+            self[i] ^= 0x01 << (position % 8);
+        }
+        Some(self)
+    }
+}
+
+impl Corruptible for bool {
+    fn corrupt(&mut self, _position: u64) -> Option<&[u8]> {
+        *self = !*self;
+        None
+    }
+}
+
+/// Pairs such as (ciphertext, nonce) or (shared_secret, kem_ciphertext): the first is damaged.
+impl Corruptible for (Vec<u8>, Vec<u8>) {
+    fn corrupt(&mut self, position: u64) -> Option<&[u8]> {
+        self.0.corrupt(position)
+    }
+}
+
+/// Runs `operation` through fault injection.
+#[cfg(not(feature = "fault-injection"))]
+#[inline(always)]
+pub(crate) fn inject<T: Corruptible>(
+    _operation: Operation,
+    run: impl FnOnce() -> PyResult<T>,
+) -> PyResult<T> {
+    run()
+}
+
+/// Runs `operation` through fault injection.
+#[cfg(feature = "fault-injection")]
+pub(crate) fn inject<T: Corruptible>(
+    operation: Operation,
+    run: impl FnOnce() -> PyResult<T>,
+) -> PyResult<T> {
+    enabled::inject(operation, run)
+}
+
+/// Whether this build was compiled with the `fault-injection` feature
+#[pyfunction]
+pub fn fault_injection_available() -> bool {
+    cfg!(feature = "fault-injection")
+}
+
+#[cfg(feature = "fault-injection")]
+pub(crate) mod enabled {
+    use std::collections::{HashMap, HashSet};
+    use std::sync::{Mutex, MutexGuard, OnceLock};
+    use std::time::Duration;
+
+    use pyo3::prelude::*;
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+    use sha2::{Digest, Sha256};
+
+    use super::{Corruptible, InjectedFault, Operation};
+
+    #[derive(Clone, Copy)]
+    struct Rule {
+        error: f64,
+        delay: f64,
+        corrupt: f64,
+        delay_ms: u64,
+    }
+
+    enum Fault {
+        Error,
+        Delay(u64),
+        Corrupt(u64),
+    }
+
+    struct State {
+        rng: StdRng,
+        rules: HashMap<Operation, Rule>,
+        log: Vec<(Operation, &'static str)>,
+        corrupted: HashSet<[u8; 32]>,
+    }
+
+    fn state() -> MutexGuard<'static, State> {
+        static STATE: OnceLock<Mutex<State>> = OnceLock::new();
+        STATE
+            .get_or_init(|| {
+                Mutex::new(State {
+                    rng: StdRng::seed_from_u64(0),
+                    rules: HashMap::new(),
+                    log: Vec::new(),
+                    corrupted: HashSet::new(),
+                })
+            })
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    fn draw(operation: Operation) -> Option<Fault> {
+        let mut state = state();
+        let rule = *state.rules.get(&operation)?;
+        let roll: f64 = state.rng.gen();
+        let fault = if roll < rule.error {
+            Fault::Error
+        } else if roll < rule.error + rule.delay {
+            Fault::Delay(rule.delay_ms)
+        } else if roll < rule.error + rule.delay + rule.corrupt {
+            Fault::Corrupt(state.rng.gen())
+        } else {
+            return None;
+        };
+        let kind = match fault {
+            Fault::Error => "error",
+            Fault::Delay(_) => "delay",
+            Fault::Corrupt(_) => "corrupt",
+        };
+        state.log.push((operation, kind));
+        Some(fault)
+    }
+
+    pub(crate) fn inject<T: Corruptible>(
+        operation: Operation,
+        run: impl FnOnce() -> PyResult<T>,
+    ) -> PyResult<T> {
+        match draw(operation) {
+            None => run(),
+            Some(Fault::Error) => Err(InjectedFault::new_err(format!(
+                "Injected fault in {}",
+                operation.name()
+            ))),
+            Some(Fault::Delay(ms)) => {
+                std::thread::sleep(Duration::from_millis(ms));
+                run()
+            }
+            Some(Fault::Corrupt(position)) => {
+                let mut output = run()?;
+                if let Some(bytes) = output.corrupt(position) {
+                    let digest: [u8; 32] = Sha256::digest(bytes).into();
+                    state().corrupted.insert(digest);
+                }
+                Ok(output)
+            }
+        }
+    }
+
+    fn operations(name: &str) -> PyResult<Vec<Operation>> {
+        if name == "*" {
+            return Ok(Operation::ALL.to_vec());
+        }
+        Operation::ALL
+            .iter()
+            .find(|op| op.name() == name)
+            .map(|op| vec![*op])
+            .ok_or_else(|| {
+                pyo3::exceptions::PyValueError::new_err(format!(
+                    "Unknown operation '{}'; use one of {} or '*'",
+                    name,
+                    Operation::ALL.map(Operation::name).join(", ")
+                ))
+            })
+    }
+
+    /// Sets the fault rule for `operation` ("encrypt", "decrypt", "encapsulate",
+    /// "decapsulate", "sign", "verify", or "*" for all): per-call probabilities of raising
+    /// InjectedFault, sleeping `delay_ms` first, or corrupting the output. All zero removes the
+    /// rule
+    #[pyfunction]
+    #[pyo3(signature = (operation, error = 0.0, delay = 0.0, corrupt = 0.0, delay_ms = 100))]
+    pub fn set_fault(
+        operation: &str,
+        error: f64,
+        delay: f64,
+        corrupt: f64,
+        delay_ms: u64,
+    ) -> PyResult<()> {
+        let probabilities = [error, delay, corrupt];
+        if probabilities.iter().any(|p| !(0.0..=1.0).contains(p))
+            || probabilities.iter().sum::<f64>() > 1.0
+        {
+            return Err(pyo3::exceptions::PyValueError::new_err(
+                "Fault probabilities must each be in [0, 1] and sum to at most 1",
+            ));
+        }
+        let targets = operations(operation)?;
+        let mut state = state();
+        for target in targets {
+            if probabilities.iter().all(|p| *p == 0.0) {
+                state.rules.remove(&target);
+            } else {
+                state.rules.insert(
+                    target,
+                    Rule {
+                        error,
+                        delay,
+                        corrupt,
+                        delay_ms,
+                    },
+                );
+            }
+        }
+        Ok(())
+    }
+
+    /// Removes every fault rule, clears the fault log and corruption record, and reseeds the
+    /// fault RNG so the next run repeats exactly
+    #[pyfunction]
+    #[pyo3(signature = (seed = 0))]
+    pub fn reset_faults(seed: u64) {
+        let mut state = state();
+        state.rng = StdRng::seed_from_u64(seed);
+        state.rules.clear();
+        state.log.clear();
+        state.corrupted.clear();
+    }
+
+    /// Faults injected since the last reset, oldest first, as (operation, kind) with kind
+    /// "error", "delay" or "corrupt"
+    #[pyfunction]
+    pub fn injected_faults() -> Vec<(&'static str, &'static str)> {
+        state()
+            .log
+            .iter()
+            .map(|(operation, kind)| (operation.name(), *kind))
+            .collect()
+    }
+
+    /// True if `output` is exactly an output corrupted by fault injection since the last reset
+    #[pyfunction]
+    pub fn is_injected_corruption(output: Vec<u8>) -> bool {
+        let digest: [u8; 32] = Sha256::digest(&output).into();
+        state().corrupted.contains(&digest)
+    }
+}
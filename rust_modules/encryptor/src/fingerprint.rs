@@ -0,0 +1,24 @@
+use pyo3::prelude::*;
+use sha2::{Digest, Sha256};
+
+use crate::params::KyberParams;
+
+pub(crate) const FINGERPRINT_BYTES: usize = 32;
+
+/// SHA-256 of the encoded public key; identifies recipients and signers across formats.
+pub(crate) fn fingerprint(public_key: &[u8]) -> [u8; FINGERPRINT_BYTES] {
+    Sha256::digest(public_key).into()
+}
+
+/// The Kyber public key embedded in a PQClean secret key
+/// (layout: indcpa_sk (384 * k) | pk | H(pk) | z).
+pub(crate) fn kyber_public_key_from_secret(sk_bytes: &[u8], params: KyberParams) -> &[u8] {
+    let start = 384 * params.rank();
+    &sk_bytes[start..start + params.public_key_bytes()]
+}
+
+/// Returns the 32-byte fingerprint (SHA-256) of an encoded public key
+#[pyfunction]
+pub fn key_fingerprint(public_key: Vec<u8>) -> Vec<u8> {
+    fingerprint(&public_key).to_vec()
+}
@@ -0,0 +1,448 @@
+use std::marker::PhantomData;
+use std::ops::Deref;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+
+use pqcrypto_traits::kem::{PublicKey as KemPublicKey, SecretKey as KemSecretKey};
+use pqcrypto_traits::sign::{PublicKey as SigPublicKey, SecretKey as SigSecretKey};
+use pyo3::prelude::*;
+use zeroize::Zeroizing;
+
+use crate::params::{with_falcon, with_kyber, FalconParams, KyberParams};
+use crate::pyca;
+use crate::secret::LockedBytes;
+use crate::usage::{KeyOperation, KeyUsagePolicy};
+
+/// Algorithm a key handle was created for. Operations check it before touching key bytes.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum KeyAlgorithm {
+    Kyber(KyberParams),
+    Falcon(FalconParams),
+}
+
+impl KeyAlgorithm {
+    pub(crate) fn parse(name: &str) -> PyResult<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "kyber512" => Ok(KeyAlgorithm::Kyber(KyberParams::Kyber512)),
+            "kyber768" => Ok(KeyAlgorithm::Kyber(KyberParams::Kyber768)),
+            "kyber" | "kyber1024" => Ok(KeyAlgorithm::Kyber(KyberParams::Kyber1024)),
+            "ml-kem-1024" => Ok(KeyAlgorithm::Kyber(KyberParams::MlKem1024)),
+            "falcon512" => Ok(KeyAlgorithm::Falcon(FalconParams::Falcon512)),
+            "falcon" | "falcon1024" => Ok(KeyAlgorithm::Falcon(FalconParams::Falcon1024)),
+            _ => Err(pyo3::exceptions::PyValueError::new_err(format!(
+                "Unsupported key algorithm: {}",
+                name
+            ))),
+        }
+    }
+
+    pub(crate) fn name(self) -> &'static str {
+        match self {
+            KeyAlgorithm::Kyber(params) => params.name(),
+            KeyAlgorithm::Falcon(params) => params.name(),
+        }
+    }
+}
+
+/// Key material held on the Rust side of the FFI boundary. The secret half is kept in locked
+/// memory and wiped on drop.
+pub(crate) struct StoredKey {
+    pub(crate) algorithm: KeyAlgorithm,
+    pub(crate) public_key: Vec<u8>,
+    pub(crate) secret_key: LockedBytes,
+    pub(crate) exportable: bool,
+    /// Whether `export_key_encrypted` may back the key up; `exportable` implies it.
+    pub(crate) backup: bool,
+    policy: KeyUsagePolicy,
+    uses: AtomicU64,
+}
+
+impl StoredKey {
+    /// Checks a raw keypair against `algorithm` before moving it into Rust memory.
+    pub(crate) fn import(
+        algorithm: KeyAlgorithm,
+        public_key: Vec<u8>,
+        secret_key: &[u8],
+        exportable: bool,
+        backup: bool,
+        policy: KeyUsagePolicy,
+    ) -> PyResult<Self> {
+        policy.check_algorithm(algorithm)?;
+        let expected_secret_len = match algorithm {
+            KeyAlgorithm::Kyber(params) => {
+                crate::validate::check_kyber_public_key(&public_key, params)
+                    .map_err(pyo3::exceptions::PyValueError::new_err)?;
+                params.secret_key_bytes()
+            }
+            KeyAlgorithm::Falcon(params) => {
+                crate::validate::check_falcon_public_key(&public_key, params)
+                    .map_err(pyo3::exceptions::PyValueError::new_err)?;
+                params.secret_key_bytes()
+            }
+        };
+        if secret_key.len() != expected_secret_len {
+            return Err(pyo3::exceptions::PyValueError::new_err(format!(
+                "Invalid secret key length. Expected {}, got {}",
+                expected_secret_len,
+                secret_key.len()
+            )));
+        }
+        Ok(StoredKey {
+            algorithm,
+            public_key,
+            secret_key: LockedBytes::new(secret_key),
+            exportable,
+            backup,
+            policy,
+            uses: AtomicU64::new(0),
+        })
+    }
+
+    /// Generates a keypair; the policy must already have passed `check_algorithm`.
+    pub(crate) fn generate(
+        algorithm: KeyAlgorithm,
+        exportable: bool,
+        backup: bool,
+        policy: KeyUsagePolicy,
+    ) -> Self {
+        let (public_key, secret_key) = match algorithm {
+            KeyAlgorithm::Kyber(params) => with_kyber!(params, kyber => {
+                let (pk, sk) = kyber::keypair();
+                (pk.as_bytes().to_vec(), LockedBytes::new(sk.as_bytes()))
+            }),
+            KeyAlgorithm::Falcon(params) => with_falcon!(params, falcon => {
+                let (pk, sk) = falcon::keypair();
+                (pk.as_bytes().to_vec(), LockedBytes::new(sk.as_bytes()))
+            }),
+        };
+        StoredKey {
+            algorithm,
+            public_key,
+            secret_key,
+            exportable,
+            backup,
+            policy,
+            uses: AtomicU64::new(0),
+        }
+    }
+
+    /// Enforces the key's usage policy for `op`; `consume` counts the use.
+    pub(crate) fn authorize(&self, op: KeyOperation, consume: bool) -> PyResult<()> {
+        self.policy.authorize(op, &self.uses, consume)
+    }
+
+    /// Enforces the key's usage policy for `count` uses of `op` at once.
+    pub(crate) fn authorize_uses(&self, op: KeyOperation, count: u64) -> PyResult<()> {
+        self.policy.authorize_uses(op, &self.uses, count)
+    }
+}
+
+/// Runs `f` against the key behind a `SecretKeyHandle` once the key was created with `backup`
+/// or `exportable` and its policy allows "export". `f` must not hand the secret bytes back to
+/// Python unencrypted.
+pub(crate) fn with_backup_key<T>(
+    key: &Bound<'_, PyAny>,
+    f: impl FnOnce(&StoredKey) -> PyResult<T>,
+) -> PyResult<T> {
+    let handle = key
+        .downcast::<SecretKeyHandle>()
+        .map_err(|_| pyo3::exceptions::PyTypeError::new_err("Expected a SecretKeyHandle"))?;
+    let key = handle.borrow().key()?.clone();
+    if !key.backup && !key.exportable {
+        return Err(pyo3::exceptions::PyPermissionError::new_err(
+            "Key was not created with backup=True, so it can't be backed up",
+        ));
+    }
+    key.authorize(KeyOperation::Export, true)?;
+    f(&key)
+}
+
+/// Opaque secret key owned by Rust: the bytes sit in locked memory, are wiped when the handle
+/// is destroyed or garbage-collected, and are never handed back to Python. Every function that
+/// takes a secret key accepts a handle in place of the raw bytes, subject to the handle's
+/// usage policy; only a handle created with `exportable=True` hands them back, through `export`
+#[pyclass(module = "reliquary_encryptor")]
+pub struct SecretKeyHandle {
+    // Shared with in-flight operations, so `destroy` never pulls the key out from under one;
+    // the bytes are wiped as soon as the last of them finishes.
+    key: Option<Arc<StoredKey>>,
+}
+
+impl SecretKeyHandle {
+    pub(crate) fn from_stored(key: StoredKey) -> Self {
+        SecretKeyHandle {
+            key: Some(Arc::new(key)),
+        }
+    }
+
+    fn key(&self) -> PyResult<&Arc<StoredKey>> {
+        self.key.as_ref().ok_or_else(|| {
+            pyo3::exceptions::PyValueError::new_err("Secret key handle has been destroyed")
+        })
+    }
+}
+
+#[pymethods]
+impl SecretKeyHandle {
+    /// Generates a keypair directly into a handle ("kyber512", "kyber768", "kyber1024",
+    /// "falcon512" or "falcon1024"). With `backup=True`, `export_key_encrypted` may back the
+    /// key up; with `exportable=True`, `export` and PKCS#8 export may also hand out the raw
+    /// key. Both still need `policy` to list "export"
+    #[staticmethod]
+    #[pyo3(signature = (algorithm = "kyber1024", policy = None, *, backup = false, exportable = false))]
+    fn generate(
+        py: Python<'_>,
+        algorithm: &str,
+        policy: Option<KeyUsagePolicy>,
+        backup: bool,
+        exportable: bool,
+    ) -> PyResult<Self> {
+        let algorithm = KeyAlgorithm::parse(algorithm)?;
+        let policy = policy.unwrap_or_default();
+        policy.check_algorithm(algorithm)?;
+        let key = py.allow_threads(|| StoredKey::generate(algorithm, exportable, backup, policy));
+        Ok(SecretKeyHandle {
+            key: Some(Arc::new(key)),
+        })
+    }
+
+    /// Moves an existing raw keypair into a handle. Drop the caller's copy of `secret_key`
+    /// afterwards; Python can't wipe it. `backup` and `exportable` are as for `generate`
+    #[staticmethod]
+    #[pyo3(signature = (algorithm, public_key, secret_key, policy = None, *, backup = false, exportable = false))]
+    fn from_bytes(
+        algorithm: &str,
+        public_key: Vec<u8>,
+        secret_key: Vec<u8>,
+        policy: Option<KeyUsagePolicy>,
+        backup: bool,
+        exportable: bool,
+    ) -> PyResult<Self> {
+        let algorithm = KeyAlgorithm::parse(algorithm)?;
+        let secret_key = Zeroizing::new(secret_key);
+        Ok(SecretKeyHandle {
+            key: Some(Arc::new(StoredKey::import(
+                algorithm,
+                public_key,
+                &secret_key,
+                exportable,
+                backup,
+                policy.unwrap_or_default(),
+            )?)),
+        })
+    }
+
+    #[getter]
+    fn algorithm(&self) -> PyResult<&'static str> {
+        Ok(self.key()?.algorithm.name())
+    }
+
+    #[getter]
+    fn public_key(&self) -> PyResult<Vec<u8>> {
+        Ok(self.key()?.public_key.clone())
+    }
+
+    /// Whether the key bytes are locked into RAM. False when the platform or RLIMIT_MEMLOCK
+    /// doesn't allow it; the bytes are still wiped on destroy
+    #[getter]
+    fn locked(&self) -> PyResult<bool> {
+        Ok(self.key()?.secret_key.locked())
+    }
+
+    /// Whether `export` may hand out the raw key (still subject to the policy)
+    #[getter]
+    fn exportable(&self) -> PyResult<bool> {
+        Ok(self.key()?.exportable)
+    }
+
+    #[getter]
+    fn policy(&self) -> PyResult<KeyUsagePolicy> {
+        Ok(self.key()?.policy.clone())
+    }
+
+    /// Operations performed with this handle so far
+    #[getter]
+    fn uses(&self) -> PyResult<u64> {
+        Ok(self.key()?.uses.load(Ordering::SeqCst))
+    }
+
+    #[getter]
+    fn destroyed(&self) -> bool {
+        self.key.is_none()
+    }
+
+    /// Returns the raw secret key. Raises PermissionError unless the handle was created with
+    /// `exportable=True` and its policy allows "export"; each call counts as a use
+    fn export(&self) -> PyResult<Vec<u8>> {
+        let key = self.key()?;
+        if !key.exportable {
+            return Err(pyo3::exceptions::PyPermissionError::new_err(
+                "Secret key handle is not exportable",
+            ));
+        }
+        key.authorize(KeyOperation::Export, true)?;
+        Ok(key.secret_key.to_vec())
+    }
+
+    /// Wipes the key now instead of waiting for garbage collection. Later use raises ValueError
+    fn destroy(&mut self) {
+        self.key = None;
+    }
+
+    fn __repr__(&self) -> String {
+        match &self.key {
+            Some(key) => format!("SecretKeyHandle({})", key.algorithm.name()),
+            None => "SecretKeyHandle(destroyed)".to_string(),
+        }
+    }
+}
+
+/// Secret key that is either raw bytes (wiped after the call) or a `SecretKeyHandle`.
+/// Derefs to the key bytes; only reachable through a `KeyArg`, which enforces handle policies.
+pub(crate) enum SecretKeyArg {
+    Handle(Arc<StoredKey>),
+    Bytes(Zeroizing<Vec<u8>>),
+}
+
+impl SecretKeyArg {
+    /// Parameter set to use with this key: a handle's own, otherwise `requested`.
+    pub(crate) fn kyber_params(&self, requested: KyberParams) -> PyResult<KyberParams> {
+        match self {
+            SecretKeyArg::Handle(key) => match key.algorithm {
+                KeyAlgorithm::Kyber(params) => Ok(params),
+                other => Err(pyo3::exceptions::PyValueError::new_err(format!(
+                    "Secret key handle holds a {} key, expected a Kyber key",
+                    other.name()
+                ))),
+            },
+            SecretKeyArg::Bytes(_) => Ok(requested),
+        }
+    }
+
+    /// Parameter set to use with this key: a handle's own, otherwise `requested`.
+    pub(crate) fn falcon_params(&self, requested: FalconParams) -> PyResult<FalconParams> {
+        match self {
+            SecretKeyArg::Handle(key) => match key.algorithm {
+                KeyAlgorithm::Falcon(params) => Ok(params),
+                other => Err(pyo3::exceptions::PyValueError::new_err(format!(
+                    "Secret key handle holds a {} key, expected a Falcon key",
+                    other.name()
+                ))),
+            },
+            SecretKeyArg::Bytes(_) => Ok(requested),
+        }
+    }
+}
+
+impl Deref for SecretKeyArg {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        match self {
+            SecretKeyArg::Handle(key) => &key.secret_key,
+            SecretKeyArg::Bytes(bytes) => bytes,
+        }
+    }
+}
+
+/// What a secret key argument will be used for, checked against handle policies on
+/// extraction so a function can't reach the key bytes without passing the policy.
+pub(crate) trait KeyPurpose {
+    const OPERATION: KeyOperation;
+    /// Whether extraction counts as a use of the key.
+    const CONSUMES: bool = true;
+    /// Whether the key bytes leave Rust, which a handle must have been made `exportable` for.
+    const EXPORTS: bool = false;
+}
+
+pub(crate) struct Decryption;
+pub(crate) struct Signing;
+/// Key availability checks that never touch the secret bytes, such as unseal dry runs.
+pub(crate) struct Inspection;
+/// Serializing the key bytes for use outside Rust, such as PKCS#8.
+pub(crate) struct Export;
+
+impl KeyPurpose for Decryption {
+    const OPERATION: KeyOperation = KeyOperation::Decapsulate;
+}
+
+impl KeyPurpose for Signing {
+    const OPERATION: KeyOperation = KeyOperation::Sign;
+}
+
+impl KeyPurpose for Inspection {
+    const OPERATION: KeyOperation = KeyOperation::Decapsulate;
+    const CONSUMES: bool = false;
+}
+
+impl KeyPurpose for Export {
+    const OPERATION: KeyOperation = KeyOperation::Export;
+    const EXPORTS: bool = true;
+}
+
+/// Python-facing secret key argument for purpose `P`.
+pub(crate) struct KeyArg<P: KeyPurpose> {
+    key: SecretKeyArg,
+    _purpose: PhantomData<P>,
+}
+
+pub(crate) type DecryptionKeyArg = KeyArg<Decryption>;
+pub(crate) type SigningKeyArg = KeyArg<Signing>;
+pub(crate) type InspectionKeyArg = KeyArg<Inspection>;
+pub(crate) type ExportKeyArg = KeyArg<Export>;
+
+impl<P: KeyPurpose> KeyArg<P> {
+    /// Checks the handle's policy for `extra` further uses beyond the one counted on
+    /// extraction, for functions that apply the key more than once.
+    pub(crate) fn authorize_more(&self, extra: usize) -> PyResult<()> {
+        match &self.key {
+            SecretKeyArg::Handle(key) if P::CONSUMES => {
+                key.authorize_uses(P::OPERATION, extra as u64)
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+impl<P: KeyPurpose> Deref for KeyArg<P> {
+    type Target = SecretKeyArg;
+
+    fn deref(&self) -> &SecretKeyArg {
+        &self.key
+    }
+}
+
+impl<P: KeyPurpose> AsRef<[u8]> for KeyArg<P> {
+    fn as_ref(&self) -> &[u8] {
+        &self.key
+    }
+}
+
+impl<'py, P: KeyPurpose> FromPyObject<'py> for KeyArg<P> {
+    fn extract_bound(ob: &Bound<'py, PyAny>) -> PyResult<Self> {
+        let key = if let Ok(handle) = ob.downcast::<SecretKeyHandle>() {
+            let key = handle.borrow().key()?.clone();
+            if P::EXPORTS && !key.exportable {
+                return Err(pyo3::exceptions::PyPermissionError::new_err(
+                    "Secret key handle is not exportable",
+                ));
+            }
+            key.authorize(P::OPERATION, P::CONSUMES)?;
+            SecretKeyArg::Handle(key)
+        } else if let Ok(bytes) = ob.extract::<Vec<u8>>() {
+            SecretKeyArg::Bytes(Zeroizing::new(bytes))
+        } else if let Some(bytes) = pyca::any_private_key(ob)? {
+            // Classical keys held by pyca/cryptography, serialized the way their raw-bytes
+            // form would be passed
+            SecretKeyArg::Bytes(bytes)
+        } else {
+            return Err(pyo3::exceptions::PyTypeError::new_err(
+                "Expected secret key bytes or a SecretKeyHandle",
+            ));
+        };
+        Ok(KeyArg {
+            key,
+            _purpose: PhantomData,
+        })
+    }
+}
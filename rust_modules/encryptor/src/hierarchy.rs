@@ -0,0 +1,181 @@
+use std::collections::BTreeMap;
+
+use ::hkdf::Hkdf;
+use pyo3::prelude::*;
+use sha3::Sha3_256;
+use zeroize::Zeroizing;
+
+// Hierarchical key derivation for vault namespaces. The root node key is
+// HKDF-Extract(salt = ROOT_LABEL, master key); every path segment is one HKDF-SHA3-256 expand
+// step from its parent with info:
+//   STEP_LABEL | epoch u32 | segment length u16 | segment
+// Each node has an epoch (default 0). Rotating a node bumps its epoch, which changes its key
+// and therefore every key beneath it, revoking the whole subtree at once.
+const ROOT_LABEL: &[u8] = b"reliquary/hierarchy/v1/root";
+const STEP_LABEL: &[u8] = b"reliquary/hierarchy/v1/child";
+const KEY_BYTES: usize = 32;
+const MIN_MASTER_KEY_BYTES: usize = 32;
+const MAX_DEPTH: usize = 32;
+const MAX_SEGMENT_BYTES: usize = 255;
+
+fn segments(path: &str) -> Result<Vec<&str>, String> {
+    if path.is_empty() {
+        return Err("Derivation path must not be empty".to_string());
+    }
+    let segments: Vec<&str> = path.split('/').collect();
+    if segments.len() > MAX_DEPTH {
+        return Err(format!(
+            "Derivation path is deeper than {} segments",
+            MAX_DEPTH
+        ));
+    }
+    for segment in &segments {
+        if segment.is_empty() {
+            return Err(format!("Empty segment in derivation path '{}'", path));
+        }
+        if segment.len() > MAX_SEGMENT_BYTES {
+            return Err(format!(
+                "Path segment longer than {} bytes",
+                MAX_SEGMENT_BYTES
+            ));
+        }
+    }
+    Ok(segments)
+}
+
+fn join(base: &str, path: &str) -> String {
+    if base.is_empty() {
+        path.to_string()
+    } else {
+        format!("{}/{}", base, path)
+    }
+}
+
+fn step(parent: &[u8; KEY_BYTES], segment: &str, epoch: u32) -> Zeroizing<[u8; KEY_BYTES]> {
+    let mut info = Vec::with_capacity(STEP_LABEL.len() + 6 + segment.len());
+    info.extend_from_slice(STEP_LABEL);
+    info.extend_from_slice(&epoch.to_be_bytes());
+    info.extend_from_slice(&(segment.len() as u16).to_be_bytes());
+    info.extend_from_slice(segment.as_bytes());
+
+    let mut child = Zeroizing::new([0u8; KEY_BYTES]);
+    Hkdf::<Sha3_256>::from_prk(parent)
+        .expect("node keys are full-length PRKs")
+        .expand(&info, child.as_mut())
+        .expect("32 bytes is a valid HKDF-SHA3-256 output length");
+    child
+}
+
+/// Derives per-tenant / per-record subkeys from one master key along labeled paths such as
+/// "tenant/42/records/7". `child` hands out a subtree without exposing anything above it;
+/// `rotate` revokes a subtree. Persist `epochs` next to the master key: keys can only be
+/// re-derived with the same epochs
+#[pyclass(module = "reliquary_encryptor")]
+pub struct KeyHierarchy {
+    key: Zeroizing<[u8; KEY_BYTES]>,
+    path: String,
+    epochs: BTreeMap<String, u32>,
+}
+
+impl KeyHierarchy {
+    fn node(&self, path: &str) -> Result<(Zeroizing<[u8; KEY_BYTES]>, String), String> {
+        let mut key = self.key.clone();
+        let mut absolute = self.path.clone();
+        for segment in segments(path)? {
+            absolute = join(&absolute, segment);
+            key = step(
+                &key,
+                segment,
+                self.epochs.get(&absolute).copied().unwrap_or(0),
+            );
+        }
+        Ok((key, absolute))
+    }
+}
+
+#[pymethods]
+impl KeyHierarchy {
+    /// `epochs` maps node paths to their rotation counters, as returned by the `epochs` getter
+    #[new]
+    #[pyo3(signature = (master_key, epochs = None))]
+    fn new(master_key: Vec<u8>, epochs: Option<BTreeMap<String, u32>>) -> PyResult<Self> {
+        let master_key = Zeroizing::new(master_key);
+        if master_key.len() < MIN_MASTER_KEY_BYTES {
+            return Err(pyo3::exceptions::PyValueError::new_err(format!(
+                "Master key must be at least {} bytes",
+                MIN_MASTER_KEY_BYTES
+            )));
+        }
+        let epochs = epochs.unwrap_or_default();
+        for path in epochs.keys() {
+            segments(path).map_err(pyo3::exceptions::PyValueError::new_err)?;
+        }
+        let (prk, _) = Hkdf::<Sha3_256>::extract(Some(ROOT_LABEL), &master_key);
+        Ok(KeyHierarchy {
+            key: Zeroizing::new(prk.into()),
+            path: String::new(),
+            epochs,
+        })
+    }
+
+    /// Path of this node from the root ("" for the root itself)
+    #[getter]
+    fn path(&self) -> &str {
+        &self.path
+    }
+
+    /// Rotation counters of this node's subtree, keyed by absolute path
+    #[getter]
+    fn epochs(&self) -> BTreeMap<String, u32> {
+        self.epochs.clone()
+    }
+
+    /// 32-byte key for the node at `path` (relative to this node)
+    fn derive(&self, path: &str) -> PyResult<Vec<u8>> {
+        let (key, _) = self
+            .node(path)
+            .map_err(pyo3::exceptions::PyValueError::new_err)?;
+        Ok(key.to_vec())
+    }
+
+    /// Hierarchy rooted at `path`, e.g. to hand one tenant its own subtree. It can derive
+    /// everything below `path` and nothing above or beside it
+    fn child(&self, path: &str) -> PyResult<KeyHierarchy> {
+        let (key, absolute) = self
+            .node(path)
+            .map_err(pyo3::exceptions::PyValueError::new_err)?;
+        let prefix = format!("{}/", absolute);
+        let epochs = self
+            .epochs
+            .iter()
+            .filter(|(p, _)| p.starts_with(&prefix))
+            .map(|(p, e)| (p.clone(), *e))
+            .collect();
+        Ok(KeyHierarchy {
+            key,
+            path: absolute,
+            epochs,
+        })
+    }
+
+    /// Current epoch of the node at `path`
+    fn epoch(&self, path: &str) -> PyResult<u32> {
+        segments(path).map_err(pyo3::exceptions::PyValueError::new_err)?;
+        Ok(self
+            .epochs
+            .get(&join(&self.path, path))
+            .copied()
+            .unwrap_or(0))
+    }
+
+    /// Rotates the node at `path`: its key and every key beneath it change. Returns the new
+    /// epoch
+    fn rotate(&mut self, path: &str) -> PyResult<u32> {
+        segments(path).map_err(pyo3::exceptions::PyValueError::new_err)?;
+        let epoch = self.epochs.entry(join(&self.path, path)).or_insert(0);
+        *epoch = epoch.checked_add(1).ok_or_else(|| {
+            pyo3::exceptions::PyValueError::new_err("Node has exhausted its rotations")
+        })?;
+        Ok(*epoch)
+    }
+}
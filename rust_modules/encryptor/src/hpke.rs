@@ -0,0 +1,760 @@
+use aes_gcm::aead::{Aead as _, KeyInit, Payload};
+use aes_gcm::{Aes128Gcm, Aes256Gcm, Nonce};
+use hkdf::Hkdf;
+use pyo3::prelude::*;
+use sha2::{Sha256, Sha384, Sha512};
+use zeroize::Zeroizing;
+
+use crate::classical::{random_secret, x25519_dh, x25519_public_from_secret};
+use crate::handles::{DecryptionKeyArg, SigningKeyArg};
+use crate::params::{with_kyber, KyberParams};
+
+// Hybrid public key encryption per RFC 9180, base and auth modes. A suite is one KEM, KDF and
+// AEAD named by their registry identifiers:
+//   KEM  0x0020 DHKEM(X25519, HKDF-SHA256)
+//        0x0030 X25519Kyber768Draft00 (draft-westerbaan-cfrg-hpke-xyber768d00): X25519 and
+//               Kyber768 public keys, secret keys and encapsulations concatenated in that order;
+//               the shared secret is the DHKEM secret followed by the Kyber secret
+//        0x5201-0x5203 Kyber512/768/1024: Reliquary code points, not IANA-registered; enc is the
+//               Kyber ciphertext and the shared secret the Kyber shared secret
+//   KDF  0x0001 HKDF-SHA256, 0x0002 HKDF-SHA384, 0x0003 HKDF-SHA512
+//   AEAD 0x0001 AES-128-GCM, 0x0002 AES-256-GCM, 0xFFFF export-only
+// Only DHKEM has an authenticated encapsulation, so auth mode needs the X25519 KEM.
+const MODE_BASE: u8 = 0x00;
+const MODE_AUTH: u8 = 0x02;
+const VERSION_LABEL: &[u8] = b"HPKE-v1";
+const X25519_BYTES: usize = 32;
+// suite_id of DHKEM(X25519, HKDF-SHA256) inside ExtractAndExpand
+const DHKEM_X25519_SUITE: &[u8] = b"KEM\x00\x20";
+
+#[derive(Clone, Copy, PartialEq)]
+enum Kem {
+    X25519,
+    X25519Kyber768,
+    Kyber(KyberParams),
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Kdf {
+    Sha256,
+    Sha384,
+    Sha512,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Aead {
+    Aes128Gcm,
+    Aes256Gcm,
+    ExportOnly,
+}
+
+fn value_error(message: impl Into<String>) -> PyErr {
+    pyo3::exceptions::PyValueError::new_err(message.into())
+}
+
+impl Kem {
+    fn parse(name: &str) -> PyResult<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "x25519" => Ok(Kem::X25519),
+            "x25519-kyber768" => Ok(Kem::X25519Kyber768),
+            "kyber512" => Ok(Kem::Kyber(KyberParams::Kyber512)),
+            "kyber768" => Ok(Kem::Kyber(KyberParams::Kyber768)),
+            "kyber1024" => Ok(Kem::Kyber(KyberParams::Kyber1024)),
+            _ => Err(value_error(format!("Unknown HPKE KEM '{}'", name))),
+        }
+    }
+
+    fn id(self) -> u16 {
+        match self {
+            Kem::X25519 => 0x0020,
+            Kem::X25519Kyber768 => 0x0030,
+            Kem::Kyber(params) => 0x5200 | params.wire_id() as u16,
+        }
+    }
+
+    fn from_id(id: u16) -> Option<Self> {
+        match id {
+            0x0020 => Some(Kem::X25519),
+            0x0030 => Some(Kem::X25519Kyber768),
+            0x5201..=0x52ff => KyberParams::from_wire_id(id as u8).map(Kem::Kyber),
+            _ => None,
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            Kem::X25519 => "x25519",
+            Kem::X25519Kyber768 => "x25519-kyber768",
+            Kem::Kyber(params) => params.name(),
+        }
+    }
+
+    fn generate_keypair(self) -> (Vec<u8>, Zeroizing<Vec<u8>>) {
+        let x25519 = || {
+            let sk = random_secret();
+            let pk = x25519_public_from_secret(sk.as_ref()).expect("32-byte secret");
+            (pk.to_vec(), Zeroizing::new(sk.to_vec()))
+        };
+        let kyber = |params: KyberParams| {
+            with_kyber!(params, kyber => {
+                use pqcrypto_traits::kem::{PublicKey as _, SecretKey as _};
+                let (pk, sk) = kyber::keypair();
+                (pk.as_bytes().to_vec(), Zeroizing::new(sk.as_bytes().to_vec()))
+            })
+        };
+        match self {
+            Kem::X25519 => x25519(),
+            Kem::Kyber(params) => kyber(params),
+            Kem::X25519Kyber768 => {
+                let (pk_x, sk_x) = x25519();
+                let (pk_k, sk_k) = kyber(KyberParams::Kyber768);
+                (
+                    [pk_x, pk_k].concat(),
+                    Zeroizing::new([sk_x.as_slice(), &sk_k].concat()),
+                )
+            }
+        }
+    }
+
+    /// Returns (shared_secret, enc). With `sender_sk` this is AuthEncap.
+    fn encap(
+        self,
+        pk_r: &[u8],
+        sender_sk: Option<&[u8]>,
+    ) -> PyResult<(Zeroizing<Vec<u8>>, Vec<u8>)> {
+        if sender_sk.is_some() && self != Kem::X25519 {
+            return Err(value_error(format!(
+                "HPKE auth mode needs the x25519 KEM, not {}",
+                self.name()
+            )));
+        }
+        match self {
+            Kem::X25519 => dhkem_encap(pk_r, sender_sk),
+            Kem::Kyber(params) => {
+                let (ss, ct) = crate::kyber_encapsulate(pk_r, params)?;
+                Ok((Zeroizing::new(ss), ct))
+            }
+            Kem::X25519Kyber768 => {
+                let params = KyberParams::Kyber768;
+                if pk_r.len() != X25519_BYTES + params.public_key_bytes() {
+                    return Err(value_error("Invalid x25519-kyber768 public key length"));
+                }
+                let (pk_x, pk_k) = pk_r.split_at(X25519_BYTES);
+                let (ss_x, enc_x) = dhkem_encap(pk_x, None)?;
+                let (ss_k, enc_k) = crate::kyber_encapsulate(pk_k, params)?;
+                let ss_k = Zeroizing::new(ss_k);
+                Ok((
+                    Zeroizing::new([ss_x.as_slice(), &ss_k].concat()),
+                    [enc_x, enc_k].concat(),
+                ))
+            }
+        }
+    }
+
+    /// Recovers the shared secret from `enc`. With `sender_pk` this is AuthDecap.
+    fn decap(
+        self,
+        enc: &[u8],
+        sk_r: &[u8],
+        sender_pk: Option<&[u8]>,
+    ) -> PyResult<Zeroizing<Vec<u8>>> {
+        if sender_pk.is_some() && self != Kem::X25519 {
+            return Err(value_error(format!(
+                "HPKE auth mode needs the x25519 KEM, not {}",
+                self.name()
+            )));
+        }
+        match self {
+            Kem::X25519 => dhkem_decap(enc, sk_r, sender_pk),
+            Kem::Kyber(params) => Ok(Zeroizing::new(crate::kyber_decapsulate(enc, sk_r, params)?)),
+            Kem::X25519Kyber768 => {
+                let params = KyberParams::Kyber768;
+                if enc.len() != X25519_BYTES + params.ciphertext_bytes() {
+                    return Err(value_error("Invalid x25519-kyber768 encapsulation length"));
+                }
+                if sk_r.len() != X25519_BYTES + params.secret_key_bytes() {
+                    return Err(value_error("Invalid x25519-kyber768 secret key length"));
+                }
+                let (enc_x, enc_k) = enc.split_at(X25519_BYTES);
+                let (sk_x, sk_k) = sk_r.split_at(X25519_BYTES);
+                let ss_x = dhkem_decap(enc_x, sk_x, None)?;
+                let ss_k = Zeroizing::new(crate::kyber_decapsulate(enc_k, sk_k, params)?);
+                Ok(Zeroizing::new([ss_x.as_slice(), &ss_k].concat()))
+            }
+        }
+    }
+}
+
+/// ExtractAndExpand of DHKEM(X25519, HKDF-SHA256).
+fn dhkem_shared_secret(dh: &[u8], kem_context: &[u8]) -> PyResult<Zeroizing<Vec<u8>>> {
+    let kdf = Kdf::Sha256;
+    let eae_prk = kdf.labeled_extract(DHKEM_X25519_SUITE, b"", b"eae_prk", dh);
+    kdf.labeled_expand(
+        DHKEM_X25519_SUITE,
+        &eae_prk,
+        b"shared_secret",
+        kem_context,
+        X25519_BYTES,
+    )
+}
+
+fn dhkem_encap(pk_r: &[u8], sender_sk: Option<&[u8]>) -> PyResult<(Zeroizing<Vec<u8>>, Vec<u8>)> {
+    let sk_e = random_secret();
+    let enc = x25519_public_from_secret(sk_e.as_ref())?.to_vec();
+    let mut dh = Zeroizing::new(x25519_dh(sk_e.as_ref(), pk_r)?.to_vec());
+    let mut kem_context = [&enc, pk_r].concat();
+    if let Some(sk_s) = sender_sk {
+        dh.extend_from_slice(x25519_dh(sk_s, pk_r)?.as_ref());
+        kem_context.extend_from_slice(&x25519_public_from_secret(sk_s)?);
+    }
+    Ok((dhkem_shared_secret(&dh, &kem_context)?, enc))
+}
+
+fn dhkem_decap(enc: &[u8], sk_r: &[u8], sender_pk: Option<&[u8]>) -> PyResult<Zeroizing<Vec<u8>>> {
+    let mut dh = Zeroizing::new(x25519_dh(sk_r, enc)?.to_vec());
+    let mut kem_context = [enc, &x25519_public_from_secret(sk_r)?].concat();
+    if let Some(pk_s) = sender_pk {
+        dh.extend_from_slice(x25519_dh(sk_r, pk_s)?.as_ref());
+        kem_context.extend_from_slice(pk_s);
+    }
+    dhkem_shared_secret(&dh, &kem_context)
+}
+
+impl Kdf {
+    fn parse(name: &str) -> PyResult<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "hkdf-sha256" => Ok(Kdf::Sha256),
+            "hkdf-sha384" => Ok(Kdf::Sha384),
+            "hkdf-sha512" => Ok(Kdf::Sha512),
+            _ => Err(value_error(format!("Unknown HPKE KDF '{}'", name))),
+        }
+    }
+
+    fn id(self) -> u16 {
+        match self {
+            Kdf::Sha256 => 0x0001,
+            Kdf::Sha384 => 0x0002,
+            Kdf::Sha512 => 0x0003,
+        }
+    }
+
+    fn from_id(id: u16) -> Option<Self> {
+        [Kdf::Sha256, Kdf::Sha384, Kdf::Sha512]
+            .into_iter()
+            .find(|kdf| kdf.id() == id)
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            Kdf::Sha256 => "hkdf-sha256",
+            Kdf::Sha384 => "hkdf-sha384",
+            Kdf::Sha512 => "hkdf-sha512",
+        }
+    }
+
+    fn hash_len(self) -> usize {
+        match self {
+            Kdf::Sha256 => 32,
+            Kdf::Sha384 => 48,
+            Kdf::Sha512 => 64,
+        }
+    }
+
+    fn extract(self, salt: &[u8], ikm: &[u8]) -> Zeroizing<Vec<u8>> {
+        Zeroizing::new(match self {
+            Kdf::Sha256 => Hkdf::<Sha256>::extract(Some(salt), ikm).0.to_vec(),
+            Kdf::Sha384 => Hkdf::<Sha384>::extract(Some(salt), ikm).0.to_vec(),
+            Kdf::Sha512 => Hkdf::<Sha512>::extract(Some(salt), ikm).0.to_vec(),
+        })
+    }
+
+    fn expand(self, prk: &[u8], info: &[u8], len: usize) -> PyResult<Zeroizing<Vec<u8>>> {
+        let mut okm = Zeroizing::new(vec![0u8; len]);
+        let expanded = match self {
+            Kdf::Sha256 => Hkdf::<Sha256>::from_prk(prk).map(|h| h.expand(info, &mut okm)),
+            Kdf::Sha384 => Hkdf::<Sha384>::from_prk(prk).map(|h| h.expand(info, &mut okm)),
+            Kdf::Sha512 => Hkdf::<Sha512>::from_prk(prk).map(|h| h.expand(info, &mut okm)),
+        };
+        match expanded {
+            Ok(Ok(())) => Ok(okm),
+            _ => Err(value_error(format!(
+                "HPKE output length {} exceeds the {} limit",
+                len,
+                self.name()
+            ))),
+        }
+    }
+
+    fn labeled_extract(
+        self,
+        suite_id: &[u8],
+        salt: &[u8],
+        label: &[u8],
+        ikm: &[u8],
+    ) -> Zeroizing<Vec<u8>> {
+        let labeled_ikm = Zeroizing::new([VERSION_LABEL, suite_id, label, ikm].concat());
+        self.extract(salt, &labeled_ikm)
+    }
+
+    fn labeled_expand(
+        self,
+        suite_id: &[u8],
+        prk: &[u8],
+        label: &[u8],
+        info: &[u8],
+        len: usize,
+    ) -> PyResult<Zeroizing<Vec<u8>>> {
+        let len_prefix = u16::try_from(len)
+            .map_err(|_| value_error("HPKE output length must fit in 16 bits"))?
+            .to_be_bytes();
+        let labeled_info = [&len_prefix, VERSION_LABEL, suite_id, label, info].concat();
+        self.expand(prk, &labeled_info, len)
+    }
+}
+
+impl Aead {
+    fn parse(name: &str) -> PyResult<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "aes-128-gcm" => Ok(Aead::Aes128Gcm),
+            "aes-256-gcm" => Ok(Aead::Aes256Gcm),
+            "export-only" => Ok(Aead::ExportOnly),
+            _ => Err(value_error(format!("Unknown HPKE AEAD '{}'", name))),
+        }
+    }
+
+    fn id(self) -> u16 {
+        match self {
+            Aead::Aes128Gcm => 0x0001,
+            Aead::Aes256Gcm => 0x0002,
+            Aead::ExportOnly => 0xffff,
+        }
+    }
+
+    fn from_id(id: u16) -> Option<Self> {
+        [Aead::Aes128Gcm, Aead::Aes256Gcm, Aead::ExportOnly]
+            .into_iter()
+            .find(|aead| aead.id() == id)
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            Aead::Aes128Gcm => "aes-128-gcm",
+            Aead::Aes256Gcm => "aes-256-gcm",
+            Aead::ExportOnly => "export-only",
+        }
+    }
+
+    fn key_len(self) -> usize {
+        match self {
+            Aead::Aes128Gcm => 16,
+            Aead::Aes256Gcm => 32,
+            Aead::ExportOnly => 0,
+        }
+    }
+
+    fn nonce_len(self) -> usize {
+        match self {
+            Aead::Aes128Gcm | Aead::Aes256Gcm => 12,
+            Aead::ExportOnly => 0,
+        }
+    }
+}
+
+#[derive(Clone, Copy)]
+struct Suite {
+    kem: Kem,
+    kdf: Kdf,
+    aead: Aead,
+}
+
+impl Suite {
+    /// "HPKE" | kem_id | kdf_id | aead_id
+    fn id(&self) -> Vec<u8> {
+        let mut id = b"HPKE".to_vec();
+        for part in [self.kem.id(), self.kdf.id(), self.aead.id()] {
+            id.extend_from_slice(&part.to_be_bytes());
+        }
+        id
+    }
+
+    fn key_schedule(&self, mode: u8, shared_secret: &[u8], info: &[u8]) -> PyResult<Context> {
+        let (suite_id, kdf) = (self.id(), self.kdf);
+        // Base and auth modes: no PSK, so psk and psk_id are empty
+        let psk_id_hash = kdf.labeled_extract(&suite_id, b"", b"psk_id_hash", b"");
+        let info_hash = kdf.labeled_extract(&suite_id, b"", b"info_hash", info);
+        let context = [&[mode][..], &psk_id_hash, &info_hash].concat();
+        let secret = kdf.labeled_extract(&suite_id, shared_secret, b"secret", b"");
+        let expand =
+            |label: &[u8], len| kdf.labeled_expand(&suite_id, &secret, label, &context, len);
+        Ok(Context {
+            suite: *self,
+            key: expand(b"key", self.aead.key_len())?,
+            base_nonce: expand(b"base_nonce", self.aead.nonce_len())?.to_vec(),
+            exporter_secret: expand(b"exp", kdf.hash_len())?,
+            seq: 0,
+        })
+    }
+
+    fn setup_sender(
+        &self,
+        pk_r: &[u8],
+        info: &[u8],
+        sender_sk: Option<&[u8]>,
+    ) -> PyResult<(Vec<u8>, Context)> {
+        let (shared_secret, enc) = self.kem.encap(pk_r, sender_sk)?;
+        let mode = if sender_sk.is_some() {
+            MODE_AUTH
+        } else {
+            MODE_BASE
+        };
+        Ok((enc, self.key_schedule(mode, &shared_secret, info)?))
+    }
+
+    fn setup_recipient(
+        &self,
+        enc: &[u8],
+        sk_r: &[u8],
+        info: &[u8],
+        sender_pk: Option<&[u8]>,
+    ) -> PyResult<Context> {
+        let shared_secret = self.kem.decap(enc, sk_r, sender_pk)?;
+        let mode = if sender_pk.is_some() {
+            MODE_AUTH
+        } else {
+            MODE_BASE
+        };
+        self.key_schedule(mode, &shared_secret, info)
+    }
+}
+
+struct Context {
+    suite: Suite,
+    key: Zeroizing<Vec<u8>>,
+    base_nonce: Vec<u8>,
+    exporter_secret: Zeroizing<Vec<u8>>,
+    seq: u64,
+}
+
+impl Context {
+    /// base_nonce XOR the big-endian sequence number.
+    fn nonce(&self) -> PyResult<Vec<u8>> {
+        if self.suite.aead == Aead::ExportOnly {
+            return Err(value_error(
+                "Export-only HPKE suites can't seal or open messages",
+            ));
+        }
+        if self.seq == u64::MAX {
+            return Err(value_error("HPKE context reached its message limit"));
+        }
+        let mut nonce = self.base_nonce.clone();
+        let offset = nonce.len() - 8;
+        for (byte, seq) in nonce[offset..].iter_mut().zip(self.seq.to_be_bytes()) {
+            *byte ^= seq;
+        }
+        Ok(nonce)
+    }
+
+    fn seal(&mut self, plaintext: &[u8], aad: &[u8]) -> PyResult<Vec<u8>> {
+        let nonce = self.nonce()?;
+        let payload = Payload {
+            msg: plaintext,
+            aad,
+        };
+        let nonce = Nonce::from_slice(&nonce);
+        let sealed = match self.suite.aead {
+            Aead::Aes128Gcm => Aes128Gcm::new(self.key.as_slice().into()).encrypt(nonce, payload),
+            Aead::Aes256Gcm => Aes256Gcm::new(self.key.as_slice().into()).encrypt(nonce, payload),
+            Aead::ExportOnly => unreachable!("nonce rejects export-only suites"),
+        }
+        .map_err(|e| value_error(format!("Encryption error: {:?}", e)))?;
+        self.seq += 1;
+        Ok(sealed)
+    }
+
+    fn open(&mut self, ciphertext: &[u8], aad: &[u8]) -> PyResult<Vec<u8>> {
+        let nonce = self.nonce()?;
+        let payload = Payload {
+            msg: ciphertext,
+            aad,
+        };
+        let nonce = Nonce::from_slice(&nonce);
+        let opened = match self.suite.aead {
+            Aead::Aes128Gcm => Aes128Gcm::new(self.key.as_slice().into()).decrypt(nonce, payload),
+            Aead::Aes256Gcm => Aes256Gcm::new(self.key.as_slice().into()).decrypt(nonce, payload),
+            Aead::ExportOnly => unreachable!("nonce rejects export-only suites"),
+        }
+        .map_err(|_| value_error("HPKE decryption failed (wrong key, info or tampered message)"))?;
+        self.seq += 1;
+        Ok(opened)
+    }
+
+    fn export(&self, exporter_context: &[u8], length: usize) -> PyResult<Vec<u8>> {
+        let kdf = self.suite.kdf;
+        if length > 255 * kdf.hash_len() {
+            return Err(value_error(format!(
+                "HPKE export length must be at most {}",
+                255 * kdf.hash_len()
+            )));
+        }
+        Ok(kdf
+            .labeled_expand(
+                &self.suite.id(),
+                &self.exporter_secret,
+                b"sec",
+                exporter_context,
+                length,
+            )?
+            .to_vec())
+    }
+}
+
+/// An HPKE (RFC 9180) cipher suite. KEMs: "x25519" (DHKEM(X25519, HKDF-SHA256)),
+/// "x25519-kyber768" (X25519Kyber768Draft00) and "kyber512"/"kyber768"/"kyber1024"; KDFs:
+/// "hkdf-sha256"/"hkdf-sha384"/"hkdf-sha512"; AEADs: "aes-128-gcm"/"aes-256-gcm"/"export-only".
+/// Methods take an optional sender key to use auth mode, which needs the x25519 KEM
+#[pyclass(frozen, module = "reliquary_encryptor")]
+pub struct HpkeSuite {
+    suite: Suite,
+}
+
+impl HpkeSuite {
+    /// A Kyber key handle has to hold the suite's own parameter set; raw bytes are checked by
+    /// length when decapsulating.
+    fn check_recipient_key(&self, sk_r: &DecryptionKeyArg) -> PyResult<()> {
+        if let Kem::Kyber(params) = self.suite.kem {
+            let held = sk_r.kyber_params(params)?;
+            if held != params {
+                return Err(pyo3::exceptions::PyValueError::new_err(format!(
+                    "Secret key handle holds a {} key, but the suite uses {}",
+                    held.name(),
+                    params.name()
+                )));
+            }
+        }
+        Ok(())
+    }
+}
+
+#[pymethods]
+impl HpkeSuite {
+    #[new]
+    #[pyo3(signature = (kem = "x25519-kyber768", kdf = "hkdf-sha256", aead = "aes-256-gcm"))]
+    fn new(kem: &str, kdf: &str, aead: &str) -> PyResult<Self> {
+        Ok(HpkeSuite {
+            suite: Suite {
+                kem: Kem::parse(kem)?,
+                kdf: Kdf::parse(kdf)?,
+                aead: Aead::parse(aead)?,
+            },
+        })
+    }
+
+    /// Suite from its registry identifiers (kem_id, kdf_id, aead_id)
+    #[staticmethod]
+    fn from_ids(kem_id: u16, kdf_id: u16, aead_id: u16) -> PyResult<Self> {
+        let kem = Kem::from_id(kem_id).ok_or_else(|| value_error("Unsupported HPKE KEM id"))?;
+        let kdf = Kdf::from_id(kdf_id).ok_or_else(|| value_error("Unsupported HPKE KDF id"))?;
+        let aead = Aead::from_id(aead_id).ok_or_else(|| value_error("Unsupported HPKE AEAD id"))?;
+        Ok(HpkeSuite {
+            suite: Suite { kem, kdf, aead },
+        })
+    }
+
+    #[getter]
+    fn kem_id(&self) -> u16 {
+        self.suite.kem.id()
+    }
+
+    #[getter]
+    fn kdf_id(&self) -> u16 {
+        self.suite.kdf.id()
+    }
+
+    #[getter]
+    fn aead_id(&self) -> u16 {
+        self.suite.aead.id()
+    }
+
+    /// Generates a recipient (or auth-mode sender) key pair for the suite's KEM. Returns
+    /// (public_key, secret_key)
+    fn generate_keypair(&self) -> (Vec<u8>, Vec<u8>) {
+        let (pk, sk) = self.suite.kem.generate_keypair();
+        (pk, sk.to_vec())
+    }
+
+    /// Sets up a sender context for `pk_r`. Returns (enc, context); send `enc` to the recipient
+    #[pyo3(signature = (pk_r, info = Vec::new(), sender_sk = None))]
+    fn setup_sender(
+        &self,
+        py: Python<'_>,
+        pk_r: Vec<u8>,
+        info: Vec<u8>,
+        sender_sk: Option<SigningKeyArg>,
+    ) -> PyResult<(Vec<u8>, HpkeSenderContext)> {
+        let (enc, context) = py.allow_threads(|| {
+            self.suite
+                .setup_sender(&pk_r, &info, sender_sk.as_ref().map(|sk| sk.as_ref()))
+        })?;
+        Ok((enc, HpkeSenderContext { context }))
+    }
+
+    /// Sets up the recipient context matching a sender's `enc`
+    #[pyo3(signature = (enc, sk_r, info = Vec::new(), sender_pk = None))]
+    fn setup_recipient(
+        &self,
+        py: Python<'_>,
+        enc: Vec<u8>,
+        sk_r: DecryptionKeyArg,
+        info: Vec<u8>,
+        sender_pk: Option<Vec<u8>>,
+    ) -> PyResult<HpkeRecipientContext> {
+        self.check_recipient_key(&sk_r)?;
+        let context = py.allow_threads(|| {
+            self.suite
+                .setup_recipient(&enc, &sk_r, &info, sender_pk.as_deref())
+        })?;
+        Ok(HpkeRecipientContext { context })
+    }
+
+    /// Single-shot encryption to `pk_r`. Returns (enc, ciphertext)
+    #[pyo3(signature = (pk_r, plaintext, info = Vec::new(), aad = Vec::new(), sender_sk = None))]
+    fn seal(
+        &self,
+        py: Python<'_>,
+        pk_r: Vec<u8>,
+        plaintext: Vec<u8>,
+        info: Vec<u8>,
+        aad: Vec<u8>,
+        sender_sk: Option<SigningKeyArg>,
+    ) -> PyResult<(Vec<u8>, Vec<u8>)> {
+        py.allow_threads(|| {
+            let (enc, mut context) =
+                self.suite
+                    .setup_sender(&pk_r, &info, sender_sk.as_ref().map(|sk| sk.as_ref()))?;
+            Ok((enc, context.seal(&plaintext, &aad)?))
+        })
+    }
+
+    /// Single-shot decryption of a `seal` result
+    #[pyo3(signature = (enc, sk_r, ciphertext, info = Vec::new(), aad = Vec::new(), sender_pk = None))]
+    #[allow(clippy::too_many_arguments)]
+    fn open(
+        &self,
+        py: Python<'_>,
+        enc: Vec<u8>,
+        sk_r: DecryptionKeyArg,
+        ciphertext: Vec<u8>,
+        info: Vec<u8>,
+        aad: Vec<u8>,
+        sender_pk: Option<Vec<u8>>,
+    ) -> PyResult<Vec<u8>> {
+        self.check_recipient_key(&sk_r)?;
+        py.allow_threads(|| {
+            self.suite
+                .setup_recipient(&enc, &sk_r, &info, sender_pk.as_deref())?
+                .open(&ciphertext, &aad)
+        })
+    }
+
+    /// Single-shot secret export to `pk_r`. Returns (enc, secret)
+    #[pyo3(signature = (pk_r, exporter_context, length, info = Vec::new(), sender_sk = None))]
+    fn send_export(
+        &self,
+        py: Python<'_>,
+        pk_r: Vec<u8>,
+        exporter_context: Vec<u8>,
+        length: usize,
+        info: Vec<u8>,
+        sender_sk: Option<SigningKeyArg>,
+    ) -> PyResult<(Vec<u8>, Vec<u8>)> {
+        py.allow_threads(|| {
+            let (enc, context) =
+                self.suite
+                    .setup_sender(&pk_r, &info, sender_sk.as_ref().map(|sk| sk.as_ref()))?;
+            Ok((enc, context.export(&exporter_context, length)?))
+        })
+    }
+
+    /// Recipient side of `send_export`
+    #[pyo3(signature = (enc, sk_r, exporter_context, length, info = Vec::new(), sender_pk = None))]
+    #[allow(clippy::too_many_arguments)]
+    fn receive_export(
+        &self,
+        py: Python<'_>,
+        enc: Vec<u8>,
+        sk_r: DecryptionKeyArg,
+        exporter_context: Vec<u8>,
+        length: usize,
+        info: Vec<u8>,
+        sender_pk: Option<Vec<u8>>,
+    ) -> PyResult<Vec<u8>> {
+        self.check_recipient_key(&sk_r)?;
+        py.allow_threads(|| {
+            self.suite
+                .setup_recipient(&enc, &sk_r, &info, sender_pk.as_deref())?
+                .export(&exporter_context, length)
+        })
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "HpkeSuite(kem='{}', kdf='{}', aead='{}')",
+            self.suite.kem.name(),
+            self.suite.kdf.name(),
+            self.suite.aead.name()
+        )
+    }
+}
+
+/// Sender side of an HPKE context: seals messages in order and exports secrets
+#[pyclass(module = "reliquary_encryptor")]
+pub struct HpkeSenderContext {
+    context: Context,
+}
+
+#[pymethods]
+impl HpkeSenderContext {
+    /// Encrypts the next message
+    #[pyo3(signature = (plaintext, aad = Vec::new()))]
+    fn seal(&mut self, plaintext: Vec<u8>, aad: Vec<u8>) -> PyResult<Vec<u8>> {
+        self.context.seal(&plaintext, &aad)
+    }
+
+    /// Derives `length` bytes bound to this context and `exporter_context`
+    fn export(&self, exporter_context: Vec<u8>, length: usize) -> PyResult<Vec<u8>> {
+        self.context.export(&exporter_context, length)
+    }
+
+    /// Number of messages sealed so far
+    #[getter]
+    fn sequence_number(&self) -> u64 {
+        self.context.seq
+    }
+}
+
+/// Recipient side of an HPKE context: opens messages in the order they were sealed and
+/// exports secrets
+#[pyclass(module = "reliquary_encryptor")]
+pub struct HpkeRecipientContext {
+    context: Context,
+}
+
+#[pymethods]
+impl HpkeRecipientContext {
+    /// Decrypts the next message; a message that fails to open doesn't advance the context
+    #[pyo3(signature = (ciphertext, aad = Vec::new()))]
+    fn open(&mut self, ciphertext: Vec<u8>, aad: Vec<u8>) -> PyResult<Vec<u8>> {
+        self.context.open(&ciphertext, &aad)
+    }
+
+    /// Derives `length` bytes bound to this context and `exporter_context`
+    fn export(&self, exporter_context: Vec<u8>, length: usize) -> PyResult<Vec<u8>> {
+        self.context.export(&exporter_context, length)
+    }
+
+    /// Number of messages opened so far
+    #[getter]
+    fn sequence_number(&self) -> u64 {
+        self.context.seq
+    }
+}
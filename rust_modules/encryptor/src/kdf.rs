@@ -0,0 +1,156 @@
+use ::hkdf::Hkdf;
+use pyo3::prelude::*;
+use sha2::Sha256;
+use sha3::Sha3_256;
+use zeroize::Zeroizing;
+
+use crate::params::KyberParams;
+
+const KEM_KEY_LABEL: &[u8] = b"reliquary/kem/v1/";
+pub(crate) const DERIVED_KEY_BYTES: usize = 32;
+
+/// Turns a Kyber shared secret into a ready-to-use 32-byte key with HKDF-SHA3-256.
+/// The KEM ciphertext is the salt and the info string is
+/// "reliquary/kem/v1/" | parameter set name | 0x00 | caller info, so keys derived for
+/// different parameter sets or contexts never collide.
+pub(crate) fn derive_kem_key(
+    shared_secret: &[u8],
+    kem_ciphertext: &[u8],
+    params: KyberParams,
+    info: &[u8],
+) -> PyResult<Vec<u8>> {
+    if info.is_empty() {
+        return Err(pyo3::exceptions::PyValueError::new_err(
+            "info must be a non-empty domain-separation label",
+        ));
+    }
+    let mut label = Vec::with_capacity(KEM_KEY_LABEL.len() + 10 + info.len());
+    label.extend_from_slice(KEM_KEY_LABEL);
+    label.extend_from_slice(params.name().as_bytes());
+    label.push(0);
+    label.extend_from_slice(info);
+
+    let mut key = Zeroizing::new([0u8; DERIVED_KEY_BYTES]);
+    Hkdf::<Sha3_256>::new(Some(kem_ciphertext), shared_secret)
+        .expand(&label, key.as_mut())
+        .expect("32 bytes is a valid HKDF-SHA3-256 output length");
+    Ok(key.to_vec())
+}
+
+// Generic HKDF (RFC 5869) for Python callers. Output is standard HKDF so it interoperates with
+// other implementations, but `info` is mandatory and may not use the "reliquary/" prefix that
+// the library's own derivations are reserved under.
+const RESERVED_INFO_PREFIX: &[u8] = b"reliquary/";
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum HkdfHash {
+    Sha256,
+    Sha3_256,
+}
+
+impl HkdfHash {
+    fn parse(name: &str) -> PyResult<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "sha256" | "sha-256" => Ok(HkdfHash::Sha256),
+            "sha3-256" => Ok(HkdfHash::Sha3_256),
+            _ => Err(pyo3::exceptions::PyValueError::new_err(format!(
+                "Unsupported HKDF hash '{}' (expected 'sha256' or 'sha3-256')",
+                name
+            ))),
+        }
+    }
+
+    fn output_bytes(&self) -> usize {
+        32
+    }
+}
+
+fn check_info(info: &[u8]) -> PyResult<()> {
+    if info.is_empty() {
+        return Err(pyo3::exceptions::PyValueError::new_err(
+            "info must be a non-empty domain-separation label",
+        ));
+    }
+    if info.starts_with(RESERVED_INFO_PREFIX) {
+        return Err(pyo3::exceptions::PyValueError::new_err(
+            "info labels starting with 'reliquary/' are reserved for internal derivations",
+        ));
+    }
+    Ok(())
+}
+
+fn check_length(hash: HkdfHash, length: usize) -> PyResult<()> {
+    let max = 255 * hash.output_bytes();
+    if length == 0 || length > max {
+        return Err(pyo3::exceptions::PyValueError::new_err(format!(
+            "HKDF output length must be between 1 and {}",
+            max
+        )));
+    }
+    Ok(())
+}
+
+fn extract(hash: HkdfHash, salt: Option<&[u8]>, ikm: &[u8]) -> Zeroizing<Vec<u8>> {
+    Zeroizing::new(match hash {
+        HkdfHash::Sha256 => Hkdf::<Sha256>::extract(salt, ikm).0.to_vec(),
+        HkdfHash::Sha3_256 => Hkdf::<Sha3_256>::extract(salt, ikm).0.to_vec(),
+    })
+}
+
+fn expand(hash: HkdfHash, prk: &[u8], info: &[u8], length: usize) -> PyResult<Vec<u8>> {
+    let mut okm = Zeroizing::new(vec![0u8; length]);
+    let result = match hash {
+        HkdfHash::Sha256 => Hkdf::<Sha256>::from_prk(prk).map(|h| h.expand(info, &mut okm)),
+        HkdfHash::Sha3_256 => Hkdf::<Sha3_256>::from_prk(prk).map(|h| h.expand(info, &mut okm)),
+    };
+    result
+        .map_err(|_| {
+            pyo3::exceptions::PyValueError::new_err(format!(
+                "Pseudorandom key must be at least {} bytes",
+                hash.output_bytes()
+            ))
+        })?
+        .expect("output length checked against 255 * hash length");
+    Ok(okm.to_vec())
+}
+
+/// HKDF-Extract: condenses input keying material into a pseudorandom key. `hash` is "sha256"
+/// (default) or "sha3-256"; a missing salt is treated as all zeros per RFC 5869
+#[pyfunction]
+#[pyo3(signature = (ikm, salt = None, hash = "sha256"))]
+pub fn hkdf_extract(ikm: Vec<u8>, salt: Option<Vec<u8>>, hash: &str) -> PyResult<Vec<u8>> {
+    let ikm = Zeroizing::new(ikm);
+    let hash = HkdfHash::parse(hash)?;
+    Ok(extract(hash, salt.as_deref(), &ikm).to_vec())
+}
+
+/// HKDF-Expand: derives `length` bytes from a pseudorandom key. `info` is a mandatory
+/// domain-separation label naming what the key is for (e.g. b"myapp/session-key/v1")
+#[pyfunction]
+#[pyo3(signature = (prk, info, length = 32, hash = "sha256"))]
+pub fn hkdf_expand(prk: Vec<u8>, info: Vec<u8>, length: usize, hash: &str) -> PyResult<Vec<u8>> {
+    let prk = Zeroizing::new(prk);
+    let hash = HkdfHash::parse(hash)?;
+    check_info(&info)?;
+    check_length(hash, length)?;
+    expand(hash, &prk, &info, length)
+}
+
+/// One-shot HKDF (extract then expand). Use it to turn a Kyber shared secret into an AES key,
+/// e.g. hkdf(shared_secret, b"myapp/file-key/v1", salt=ciphertext)
+#[pyfunction]
+#[pyo3(signature = (ikm, info, length = 32, salt = None, hash = "sha256"))]
+pub fn hkdf(
+    ikm: Vec<u8>,
+    info: Vec<u8>,
+    length: usize,
+    salt: Option<Vec<u8>>,
+    hash: &str,
+) -> PyResult<Vec<u8>> {
+    let ikm = Zeroizing::new(ikm);
+    let hash = HkdfHash::parse(hash)?;
+    check_info(&info)?;
+    check_length(hash, length)?;
+    let prk = extract(hash, salt.as_deref(), &ikm);
+    expand(hash, &prk, &info, length)
+}
@@ -0,0 +1,301 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use aes_gcm::aead::{Aead, AeadCore, KeyInit, OsRng, Payload};
+use aes_gcm::{Aes256Gcm, Nonce};
+use pyo3::prelude::*;
+use rand::RngCore;
+use zeroize::Zeroizing;
+
+// Versioned AES-256-GCM key ring. Ciphertext layout:
+//   magic "RQKR" | format u8 | key version u32 | nonce (12) | ct || tag
+// The header is authenticated together with the caller's associated data, so an envelope
+// can't be relabelled to another key version. Version numbers start at 1 and are never
+// reused, even after the version is retired.
+const MAGIC: &[u8; 4] = b"RQKR";
+const FORMAT: u8 = 1;
+const HEADER_BYTES: usize = MAGIC.len() + 1 + 4;
+const NONCE_BYTES: usize = 12;
+const TAG_BYTES: usize = 16;
+const KEY_BYTES: usize = 32;
+
+fn header(version: u32) -> [u8; HEADER_BYTES] {
+    let mut out = [0u8; HEADER_BYTES];
+    out[..4].copy_from_slice(MAGIC);
+    out[4] = FORMAT;
+    out[5..].copy_from_slice(&version.to_be_bytes());
+    out
+}
+
+fn key_version(ciphertext: &[u8]) -> Result<u32, String> {
+    if ciphertext.len() < HEADER_BYTES + NONCE_BYTES + TAG_BYTES
+        || &ciphertext[..MAGIC.len()] != MAGIC
+    {
+        return Err("Not a key ring ciphertext".to_string());
+    }
+    if ciphertext[4] != FORMAT {
+        return Err(format!(
+            "Unsupported key ring ciphertext format {}",
+            ciphertext[4]
+        ));
+    }
+    Ok(u32::from_be_bytes(
+        ciphertext[5..HEADER_BYTES]
+            .try_into()
+            .expect("4-byte slice"),
+    ))
+}
+
+fn associated_data(header: &[u8], aad: &[u8]) -> Vec<u8> {
+    let mut out = Vec::with_capacity(header.len() + aad.len());
+    out.extend_from_slice(header);
+    out.extend_from_slice(aad);
+    out
+}
+
+fn seal(key: &[u8; KEY_BYTES], version: u32, plaintext: &[u8], aad: &[u8]) -> Vec<u8> {
+    let header = header(version);
+    let nonce = Aes256Gcm::generate_nonce(&mut OsRng);
+    let ct = Aes256Gcm::new(key.into())
+        .encrypt(
+            &nonce,
+            Payload {
+                msg: plaintext,
+                aad: &associated_data(&header, aad),
+            },
+        )
+        .expect("AES-GCM encryption of in-memory data cannot fail");
+    let mut out = Vec::with_capacity(HEADER_BYTES + NONCE_BYTES + ct.len());
+    out.extend_from_slice(&header);
+    out.extend_from_slice(&nonce);
+    out.extend_from_slice(&ct);
+    out
+}
+
+/// Holds every live version of a data-encryption key. `encrypt` always uses the current
+/// version; `decrypt` uses whichever version the ciphertext names. Rotate by adding a version,
+/// re-encrypt old data with `rewrap`, then `retire` the old version to destroy its key.
+/// Persist `export_keys()`, `current` and `retired` together to reconstruct the ring
+#[pyclass(module = "reliquary_encryptor")]
+pub struct KeyRing {
+    keys: BTreeMap<u32, Zeroizing<[u8; KEY_BYTES]>>,
+    current: Option<u32>,
+    retired: BTreeSet<u32>,
+}
+
+impl KeyRing {
+    fn key(&self, version: u32) -> Result<&[u8; KEY_BYTES], String> {
+        if let Some(key) = self.keys.get(&version) {
+            return Ok(key);
+        }
+        if self.retired.contains(&version) {
+            Err(format!("Key version {} has been retired", version))
+        } else {
+            Err(format!("Unknown key version {}", version))
+        }
+    }
+
+    fn current_key(&self) -> Result<(u32, &[u8; KEY_BYTES]), String> {
+        let version = self
+            .current
+            .ok_or_else(|| "Key ring has no current version".to_string())?;
+        Ok((version, self.key(version)?))
+    }
+
+    fn open(&self, ciphertext: &[u8], aad: &[u8]) -> Result<Zeroizing<Vec<u8>>, String> {
+        let version = key_version(ciphertext)?;
+        let key = self.key(version)?;
+        let (header, rest) = ciphertext.split_at(HEADER_BYTES);
+        let (nonce, ct) = rest.split_at(NONCE_BYTES);
+        Aes256Gcm::new(key.into())
+            .decrypt(
+                Nonce::from_slice(nonce),
+                Payload {
+                    msg: ct,
+                    aad: &associated_data(header, aad),
+                },
+            )
+            .map(Zeroizing::new)
+            .map_err(|_| "Decryption failed".to_string())
+    }
+}
+
+#[pymethods]
+impl KeyRing {
+    /// `keys` maps versions to 32-byte keys, as returned by `export_keys`. `current` defaults to
+    /// the highest version; `retired` lists destroyed versions so their numbers aren't reused
+    #[new]
+    #[pyo3(signature = (keys = None, current = None, retired = None))]
+    fn new(
+        keys: Option<BTreeMap<u32, Vec<u8>>>,
+        current: Option<u32>,
+        retired: Option<Vec<u32>>,
+    ) -> PyResult<Self> {
+        let retired: BTreeSet<u32> = retired.unwrap_or_default().into_iter().collect();
+        let mut ring = BTreeMap::new();
+        for (version, key) in keys.unwrap_or_default() {
+            let key = Zeroizing::new(key);
+            if version == 0 {
+                return Err(pyo3::exceptions::PyValueError::new_err(
+                    "Key versions start at 1",
+                ));
+            }
+            if retired.contains(&version) {
+                return Err(pyo3::exceptions::PyValueError::new_err(format!(
+                    "Key version {} is both live and retired",
+                    version
+                )));
+            }
+            let key: [u8; KEY_BYTES] = key.as_slice().try_into().map_err(|_| {
+                pyo3::exceptions::PyValueError::new_err(format!(
+                    "Key version {} must be {} bytes",
+                    version, KEY_BYTES
+                ))
+            })?;
+            ring.insert(version, Zeroizing::new(key));
+        }
+        let current = match current {
+            Some(version) if !ring.contains_key(&version) => {
+                return Err(pyo3::exceptions::PyValueError::new_err(format!(
+                    "Current version {} is not in the key ring",
+                    version
+                )));
+            }
+            Some(version) => Some(version),
+            None => ring.keys().next_back().copied(),
+        };
+        Ok(KeyRing {
+            keys: ring,
+            current,
+            retired,
+        })
+    }
+
+    /// Version used by `encrypt`, or None for an empty ring
+    #[getter]
+    fn current(&self) -> Option<u32> {
+        self.current
+    }
+
+    /// Live key versions, ascending
+    #[getter]
+    fn versions(&self) -> Vec<u32> {
+        self.keys.keys().copied().collect()
+    }
+
+    /// Retired key versions, ascending
+    #[getter]
+    fn retired(&self) -> Vec<u32> {
+        self.retired.iter().copied().collect()
+    }
+
+    /// Adds a key version (a fresh random key unless `key` is given) and returns its number.
+    /// With `make_current=False` the version can decrypt but `encrypt` keeps using the current
+    /// one, e.g. while the new key is still propagating to other nodes
+    #[pyo3(signature = (key = None, make_current = true))]
+    fn add_version(&mut self, key: Option<Vec<u8>>, make_current: bool) -> PyResult<u32> {
+        let key = match key {
+            Some(key) => {
+                let key = Zeroizing::new(key);
+                let key: [u8; KEY_BYTES] = key.as_slice().try_into().map_err(|_| {
+                    pyo3::exceptions::PyValueError::new_err(format!(
+                        "Key must be {} bytes",
+                        KEY_BYTES
+                    ))
+                })?;
+                Zeroizing::new(key)
+            }
+            None => {
+                let mut key = Zeroizing::new([0u8; KEY_BYTES]);
+                OsRng.fill_bytes(key.as_mut());
+                key
+            }
+        };
+        let highest = self
+            .keys
+            .keys()
+            .next_back()
+            .copied()
+            .max(self.retired.iter().next_back().copied())
+            .unwrap_or(0);
+        let version = highest.checked_add(1).ok_or_else(|| {
+            pyo3::exceptions::PyValueError::new_err("Key ring has exhausted its versions")
+        })?;
+        self.keys.insert(version, key);
+        if make_current || self.current.is_none() {
+            self.current = Some(version);
+        }
+        Ok(version)
+    }
+
+    /// Makes a live version the one `encrypt` uses
+    fn set_current(&mut self, version: u32) -> PyResult<()> {
+        self.key(version)
+            .map_err(pyo3::exceptions::PyValueError::new_err)?;
+        self.current = Some(version);
+        Ok(())
+    }
+
+    /// Destroys a version's key. Ciphertexts still under it can no longer be decrypted, so
+    /// `rewrap` them first. The current version can't be retired
+    fn retire(&mut self, version: u32) -> PyResult<()> {
+        if self.current == Some(version) {
+            return Err(pyo3::exceptions::PyValueError::new_err(format!(
+                "Key version {} is current; make another version current first",
+                version
+            )));
+        }
+        self.key(version)
+            .map_err(pyo3::exceptions::PyValueError::new_err)?;
+        self.keys.remove(&version);
+        self.retired.insert(version);
+        Ok(())
+    }
+
+    /// Encrypts under the current version; `aad` must be passed again to decrypt
+    #[pyo3(signature = (plaintext, aad = b"".to_vec()))]
+    fn encrypt(&self, py: Python<'_>, plaintext: Vec<u8>, aad: Vec<u8>) -> PyResult<Vec<u8>> {
+        let (version, key) = self
+            .current_key()
+            .map_err(pyo3::exceptions::PyValueError::new_err)?;
+        py.allow_threads(|| Ok(seal(key, version, &plaintext, &aad)))
+    }
+
+    /// Decrypts with the version named in the ciphertext
+    #[pyo3(signature = (ciphertext, aad = b"".to_vec()))]
+    fn decrypt(&self, py: Python<'_>, ciphertext: Vec<u8>, aad: Vec<u8>) -> PyResult<Vec<u8>> {
+        py.allow_threads(|| {
+            self.open(&ciphertext, &aad)
+                .map(|plaintext| plaintext.to_vec())
+                .map_err(pyo3::exceptions::PyValueError::new_err)
+        })
+    }
+
+    /// Re-encrypts a ciphertext under the current version without exposing the plaintext.
+    /// Ciphertexts already under the current version are re-encrypted too (fresh nonce)
+    #[pyo3(signature = (ciphertext, aad = b"".to_vec()))]
+    fn rewrap(&self, py: Python<'_>, ciphertext: Vec<u8>, aad: Vec<u8>) -> PyResult<Vec<u8>> {
+        let (version, key) = self
+            .current_key()
+            .map_err(pyo3::exceptions::PyValueError::new_err)?;
+        py.allow_threads(|| {
+            let plaintext = self
+                .open(&ciphertext, &aad)
+                .map_err(pyo3::exceptions::PyValueError::new_err)?;
+            Ok(seal(key, version, &plaintext, &aad))
+        })
+    }
+
+    /// Key version named in a ciphertext, e.g. to find data that still needs rewrapping
+    #[staticmethod]
+    fn key_version(ciphertext: Vec<u8>) -> PyResult<u32> {
+        key_version(&ciphertext).map_err(pyo3::exceptions::PyValueError::new_err)
+    }
+
+    /// Live keys by version, for persisting the ring. Store them as you would the master key
+    fn export_keys(&self) -> BTreeMap<u32, Vec<u8>> {
+        self.keys
+            .iter()
+            .map(|(version, key)| (*version, key.to_vec()))
+            .collect()
+    }
+}
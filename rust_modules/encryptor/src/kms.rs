@@ -0,0 +1,182 @@
+use aes_gcm::aead::{Aead, AeadCore, KeyInit, OsRng, Payload};
+use aes_gcm::{Aes256Gcm, Nonce};
+use hkdf::Hkdf;
+use pyo3::prelude::*;
+use sha2::Sha256;
+use zeroize::Zeroizing;
+
+use crate::envelope::MAGIC;
+use crate::limits;
+use crate::wire::{put_var16, put_var8, Reader};
+
+// KMS-wrapped envelope layout (v3):
+//   magic "RQEV" | version 3 | KMS provider (var8) | KMS key id (var16) | wrapped DEK (var16)
+//   | nonce (12) | AES-256-GCM ct || tag
+// The data-encryption key (DEK) is wrapped by a cloud KMS outside this module; the envelope
+// only records which provider and key wrapped it. The payload key is derived from the DEK with
+// the header as HKDF salt, and the header followed by the caller's AAD is the GCM associated
+// data, so the provider, key id and wrapped DEK can't be swapped between envelopes.
+pub(crate) const VERSION: u8 = 3;
+const NONCE_BYTES: usize = 12;
+const TAG_BYTES: usize = 16;
+const DEK_BYTES: usize = 32;
+const KDF_LABEL: &[u8] = b"reliquary/envelope/v3/aes-256-gcm";
+
+pub(crate) struct KmsEnvelope<'a> {
+    pub header: &'a [u8],
+    pub provider: &'a str,
+    pub key_id: &'a str,
+    pub wrapped_dek: &'a [u8],
+    pub nonce: &'a [u8],
+    pub ciphertext: &'a [u8],
+}
+
+pub(crate) fn is_kms_wrapped(envelope: &[u8]) -> bool {
+    envelope.len() > MAGIC.len() && &envelope[..MAGIC.len()] == MAGIC && envelope[4] == VERSION
+}
+
+pub(crate) fn parse<'a>(envelope: &'a [u8]) -> Result<KmsEnvelope<'a>, String> {
+    limits::check_input("Envelope", envelope.len())?;
+    let mut r = Reader::new(envelope);
+    if r.bytes(MAGIC.len())
+        .map_err(|_| "Not a Reliquary envelope")?
+        != MAGIC
+    {
+        return Err("Not a Reliquary envelope".to_string());
+    }
+    if r.u8()? != VERSION {
+        return Err("Not a KMS-wrapped envelope".to_string());
+    }
+    let text = |field: &'static str, bytes: &'a [u8]| {
+        std::str::from_utf8(bytes).map_err(|_| format!("Envelope {} is not UTF-8", field))
+    };
+    let provider = text("KMS provider", r.var8()?)?;
+    let key_id = text("KMS key id", r.var16()?)?;
+    let wrapped_dek = r.var16()?;
+    if provider.is_empty() || key_id.is_empty() || wrapped_dek.is_empty() {
+        return Err("Envelope KMS provider, key id and wrapped DEK must be non-empty".to_string());
+    }
+    let header = &envelope[..r.position()];
+    let nonce = r.bytes(NONCE_BYTES)?;
+    let ciphertext = r.rest();
+    if ciphertext.len() < TAG_BYTES {
+        return Err("Envelope is truncated".to_string());
+    }
+    Ok(KmsEnvelope {
+        header,
+        provider,
+        key_id,
+        wrapped_dek,
+        nonce,
+        ciphertext,
+    })
+}
+
+fn derive_key(dek: &[u8], header: &[u8]) -> Zeroizing<[u8; 32]> {
+    let mut key = Zeroizing::new([0u8; 32]);
+    Hkdf::<Sha256>::new(Some(header), dek)
+        .expand(KDF_LABEL, key.as_mut())
+        .expect("32 bytes is a valid HKDF-SHA256 output length");
+    key
+}
+
+fn check_dek(dek: &[u8]) -> Result<(), String> {
+    if dek.len() != DEK_BYTES {
+        return Err(format!("Data key must be {} bytes", DEK_BYTES));
+    }
+    Ok(())
+}
+
+fn seal(
+    data: &[u8],
+    dek: &[u8],
+    provider: &str,
+    key_id: &str,
+    wrapped_dek: &[u8],
+    aad: &[u8],
+) -> Result<Vec<u8>, String> {
+    check_dek(dek)?;
+    if provider.is_empty() || key_id.is_empty() || wrapped_dek.is_empty() {
+        return Err("KMS provider, key id and wrapped data key must be non-empty".to_string());
+    }
+    let mut envelope = MAGIC.to_vec();
+    envelope.push(VERSION);
+    put_var8(&mut envelope, provider.as_bytes())?;
+    put_var16(&mut envelope, key_id.as_bytes())?;
+    put_var16(&mut envelope, wrapped_dek)?;
+
+    let key = derive_key(dek, &envelope);
+    let nonce = Aes256Gcm::generate_nonce(&mut OsRng);
+    let ciphertext = Aes256Gcm::new(key.as_ref().into())
+        .encrypt(
+            &nonce,
+            Payload {
+                msg: data,
+                aad: &[envelope.as_slice(), aad].concat(),
+            },
+        )
+        .map_err(|e| format!("Encryption error: {:?}", e))?;
+    envelope.extend_from_slice(&nonce);
+    envelope.extend_from_slice(&ciphertext);
+    Ok(envelope)
+}
+
+fn open(envelope: &[u8], dek: &[u8], aad: &[u8]) -> Result<Vec<u8>, String> {
+    check_dek(dek)?;
+    let parsed = parse(envelope)?;
+    let key = derive_key(dek, parsed.header);
+    Aes256Gcm::new(key.as_ref().into())
+        .decrypt(
+            Nonce::from_slice(parsed.nonce),
+            Payload {
+                msg: parsed.ciphertext,
+                aad: &[parsed.header, aad].concat(),
+            },
+        )
+        .map_err(|_| "Envelope authentication failed (wrong data key or tampered envelope)".into())
+}
+
+/// Seals `data` under a 32-byte data key that a cloud KMS has wrapped. The envelope records
+/// `provider`, `key_id` and `wrapped_dek` so the key can be unwrapped by the same KMS later;
+/// the plaintext data key is never stored
+#[pyfunction]
+#[pyo3(signature = (data, dek, provider, key_id, wrapped_dek, aad = Vec::new()))]
+pub fn encrypt_with_kms_dek(
+    py: Python<'_>,
+    data: Vec<u8>,
+    dek: Vec<u8>,
+    provider: &str,
+    key_id: &str,
+    wrapped_dek: Vec<u8>,
+    aad: Vec<u8>,
+) -> PyResult<Vec<u8>> {
+    let dek = Zeroizing::new(dek);
+    py.allow_threads(|| seal(&data, &dek, provider, key_id, &wrapped_dek, &aad))
+        .map_err(pyo3::exceptions::PyValueError::new_err)
+}
+
+/// Opens a KMS-wrapped envelope with the data key the KMS unwrapped
+#[pyfunction]
+#[pyo3(signature = (envelope, dek, aad = Vec::new()))]
+pub fn decrypt_with_kms_dek(
+    py: Python<'_>,
+    envelope: Vec<u8>,
+    dek: Vec<u8>,
+    aad: Vec<u8>,
+) -> PyResult<Vec<u8>> {
+    let dek = Zeroizing::new(dek);
+    py.allow_threads(|| open(&envelope, &dek, &aad))
+        .map_err(pyo3::exceptions::PyValueError::new_err)
+}
+
+/// Returns (provider, key_id, wrapped_dek) from a KMS-wrapped envelope, for handing the
+/// wrapped data key to the KMS that can unwrap it
+#[pyfunction]
+pub fn kms_envelope_info(envelope: Vec<u8>) -> PyResult<(String, String, Vec<u8>)> {
+    let parsed = parse(&envelope).map_err(pyo3::exceptions::PyValueError::new_err)?;
+    Ok((
+        parsed.provider.to_string(),
+        parsed.key_id.to_string(),
+        parsed.wrapped_dek.to_vec(),
+    ))
+}
@@ -3,9 +3,6 @@ use aes_gcm::{
     Aes256Gcm, Nonce,
 };
 use generic_array::{typenum::U32, GenericArray};
-use hex;
-use pqcrypto_falcon::falcon1024 as falcon;
-use pqcrypto_kyber::kyber1024 as kyber;
 use pqcrypto_traits::kem::{
     Ciphertext as KemCiphertext, PublicKey as KemPublicKey, SecretKey as KemSecretKey, SharedSecret,
 };
@@ -14,18 +11,76 @@ use pqcrypto_traits::sign::{
 };
 use pyo3::prelude::*;
 use pyo3::Bound;
-// Kyber-1024 constants
-const KYBER_PUBLICKEYBYTES: usize = 1568;
-const KYBER_SECRETKEYBYTES: usize = 3168;
-const KYBER_CIPHERTEXTBYTES: usize = 1568;
-
-// Falcon-1024 constants
-const FALCON_PUBLICKEYBYTES: usize = 1793;
-const FALCON_SECRETKEYBYTES: usize = 2305;
+use zeroize::Zeroizing;
+
+mod acvp;
+mod akem;
+mod armor;
+mod attest;
+mod backup;
+mod batch;
+mod blind;
+mod bls;
+mod bundle;
+mod burn;
+mod classical;
+mod dilithium;
+mod dkg;
+mod envelope;
+mod escrow;
+mod faults;
+mod fingerprint;
+mod handles;
+mod hierarchy;
+mod hpke;
+mod kdf;
+mod keyring;
+mod kms;
+mod limits;
+mod lms;
+mod mceliece;
+mod mldsa;
+mod mlkem;
+mod params;
+mod password;
+mod pedersen;
+mod pipeline;
+mod pkcs;
+mod prehash;
+mod providers;
+mod pyca;
+mod quorum;
+mod range;
+mod ratchet;
+mod readonly;
+mod recipients;
+mod ring;
+mod schemes;
+mod secret;
+mod shamir;
+mod sizes;
+mod social;
+mod threshold;
+mod timelock;
+mod tsa;
+mod unseal;
+mod usage;
+mod validate;
+mod vrf;
+mod vss;
+mod wire;
+mod zk;
+
+use handles::{DecryptionKeyArg, SigningKeyArg};
+use params::{with_falcon, with_kyber, FalconParams, KyberParams};
 
 /// Python module for Reliquary encryption primitives
-#[pymodule]
-fn reliquary_encryptor(_py: Python<'_>, m: &Bound<'_, PyModule>) -> PyResult<()> {
+///
+/// Safe to call from many threads, including on free-threaded builds. Provider labels, locked
+/// key pages and parsing limits are process-wide state, so the module can't be imported into a
+/// subinterpreter; PyO3 rejects that with an ImportError.
+#[pymodule(gil_used = false)]
+fn reliquary_encryptor(py: Python<'_>, m: &Bound<'_, PyModule>) -> PyResult<()> {
     m.add_function(wrap_pyfunction!(encrypt_data, m)?)?;
     m.add_function(wrap_pyfunction!(decrypt_data, m)?)?;
 
@@ -33,32 +88,276 @@ fn reliquary_encryptor(_py: Python<'_>, m: &Bound<'_, PyModule>) -> PyResult<()>
     m.add_function(wrap_pyfunction!(decrypt_data_with_nonce, m)?)?;
 
     m.add_function(wrap_pyfunction!(generate_kyber_keys, m)?)?;
+    m.add_function(wrap_pyfunction!(mlkem::ml_kem_keys_from_seed, m)?)?;
     m.add_function(wrap_pyfunction!(encapsulate_kyber, m)?)?;
     m.add_function(wrap_pyfunction!(decapsulate_kyber, m)?)?;
+    m.add_function(wrap_pyfunction!(batch::generate_kyber_keys_batch, m)?)?;
+    m.add_function(wrap_pyfunction!(batch::encapsulate_kyber_batch, m)?)?;
+    m.add_function(wrap_pyfunction!(batch::decapsulate_kyber_batch, m)?)?;
+
+    m.add_function(wrap_pyfunction!(kdf::hkdf_extract, m)?)?;
+    m.add_function(wrap_pyfunction!(kdf::hkdf_expand, m)?)?;
+    m.add_function(wrap_pyfunction!(kdf::hkdf, m)?)?;
+    m.add_function(wrap_pyfunction!(acvp::process_acvp, m)?)?;
+    m.add_function(wrap_pyfunction!(acvp::acvp_algorithms, m)?)?;
+
+    m.add_function(wrap_pyfunction!(password::hash_password_argon2id, m)?)?;
+    m.add_function(wrap_pyfunction!(password::hash_password_scrypt, m)?)?;
+    m.add_function(wrap_pyfunction!(password::hash_password_pbkdf2, m)?)?;
+    m.add_function(wrap_pyfunction!(password::verify_password, m)?)?;
 
     m.add_function(wrap_pyfunction!(generate_falcon_keys, m)?)?;
     m.add_function(wrap_pyfunction!(sign_falcon, m)?)?;
     m.add_function(wrap_pyfunction!(verify_falcon, m)?)?;
+
+    m.add_function(wrap_pyfunction!(prehash::sign_falcon_prehashed, m)?)?;
+    m.add_function(wrap_pyfunction!(prehash::verify_falcon_prehashed, m)?)?;
+
+    m.add_function(wrap_pyfunction!(bundle::create_signature_bundle, m)?)?;
+    m.add_function(wrap_pyfunction!(bundle::countersign_bundle, m)?)?;
+    m.add_function(wrap_pyfunction!(bundle::verify_signature_bundle, m)?)?;
+
+    m.add_function(wrap_pyfunction!(quorum::sign_quorum_vote, m)?)?;
+    m.add_function(wrap_pyfunction!(quorum::aggregate_quorum_certificate, m)?)?;
+    m.add_function(wrap_pyfunction!(quorum::verify_quorum_certificate, m)?)?;
+
+    m.add_function(wrap_pyfunction!(validate::validate_kyber_public_key, m)?)?;
+    m.add_function(wrap_pyfunction!(validate::validate_kyber_ciphertext, m)?)?;
+    m.add_function(wrap_pyfunction!(validate::validate_falcon_public_key, m)?)?;
+    m.add_function(wrap_pyfunction!(validate::validate_kyber_pk, m)?)?;
+    m.add_function(wrap_pyfunction!(validate::validate_falcon_pk, m)?)?;
+    m.add_function(wrap_pyfunction!(validate::validate_envelope, m)?)?;
+    m.add_function(wrap_pyfunction!(validate::validate_vrf_proof, m)?)?;
+    m.add_function(wrap_pyfunction!(validate::validate_quorum_certificate, m)?)?;
+    m.add_function(wrap_pyfunction!(limits::set_hardened_parsing, m)?)?;
+    m.add_function(wrap_pyfunction!(limits::hardened_parsing, m)?)?;
+
+    m.add_function(wrap_pyfunction!(providers::key_providers, m)?)?;
+    m.add_function(wrap_pyfunction!(providers::provider_generate_key, m)?)?;
+    m.add_function(wrap_pyfunction!(providers::provider_public_key, m)?)?;
+    m.add_function(wrap_pyfunction!(providers::provider_sign, m)?)?;
+    m.add_function(wrap_pyfunction!(providers::provider_delete_key, m)?)?;
+    m.add_function(wrap_pyfunction!(providers::verify_p256, m)?)?;
+    #[cfg(feature = "hsm")]
+    {
+        m.add_function(wrap_pyfunction!(providers::hsm_generate_wrapping_key, m)?)?;
+        m.add_function(wrap_pyfunction!(providers::hsm_wrap_key, m)?)?;
+        m.add_function(wrap_pyfunction!(providers::hsm_unwrap_key, m)?)?;
+    }
+
+    m.add_function(wrap_pyfunction!(mceliece::mceliece_key_sizes, m)?)?;
+    m.add_function(wrap_pyfunction!(mceliece::generate_mceliece_keys, m)?)?;
+    m.add_function(wrap_pyfunction!(mceliece::encapsulate_mceliece, m)?)?;
+    m.add_function(wrap_pyfunction!(mceliece::decapsulate_mceliece, m)?)?;
+
+    m.add_function(wrap_pyfunction!(classical::generate_ed25519_keys, m)?)?;
+    m.add_function(wrap_pyfunction!(classical::ed25519_public_key, m)?)?;
+    m.add_function(wrap_pyfunction!(classical::sign_ed25519, m)?)?;
+    m.add_function(wrap_pyfunction!(classical::verify_ed25519, m)?)?;
+    m.add_function(wrap_pyfunction!(classical::generate_x25519_keys, m)?)?;
+    m.add_function(wrap_pyfunction!(classical::x25519_public_key, m)?)?;
+    m.add_function(wrap_pyfunction!(classical::x25519_shared_secret, m)?)?;
+
+    m.add_function(wrap_pyfunction!(bls::generate_bls_keys, m)?)?;
+    m.add_function(wrap_pyfunction!(bls::sign_bls, m)?)?;
+    m.add_function(wrap_pyfunction!(bls::verify_bls, m)?)?;
+    m.add_function(wrap_pyfunction!(bls::prove_bls_possession, m)?)?;
+    m.add_function(wrap_pyfunction!(bls::verify_bls_possession, m)?)?;
+    m.add_function(wrap_pyfunction!(bls::aggregate_bls_signatures, m)?)?;
+    m.add_function(wrap_pyfunction!(bls::fast_aggregate_verify_bls, m)?)?;
+    m.add_function(wrap_pyfunction!(bls::aggregate_verify_bls, m)?)?;
+
+    m.add_function(wrap_pyfunction!(vrf::vrf_prove, m)?)?;
+    m.add_function(wrap_pyfunction!(vrf::vrf_verify, m)?)?;
+    m.add_function(wrap_pyfunction!(vrf::vrf_proof_to_hash, m)?)?;
+
+    m.add_function(wrap_pyfunction!(blind::generate_blind_signing_keys, m)?)?;
+    m.add_function(wrap_pyfunction!(blind::blind_message, m)?)?;
+    m.add_function(wrap_pyfunction!(blind::blind_sign, m)?)?;
+    m.add_function(wrap_pyfunction!(blind::finalize_blind_signature, m)?)?;
+    m.add_function(wrap_pyfunction!(blind::verify_blind_signature, m)?)?;
+
+    m.add_function(wrap_pyfunction!(ring::generate_ring_keys, m)?)?;
+    m.add_function(wrap_pyfunction!(ring::ring_sign, m)?)?;
+    m.add_function(wrap_pyfunction!(ring::ring_verify, m)?)?;
+    m.add_function(wrap_pyfunction!(ring::ring_signature_tag, m)?)?;
+
+    m.add_function(wrap_pyfunction!(lms::generate_lms_keys, m)?)?;
+    m.add_function(wrap_pyfunction!(lms::verify_lms, m)?)?;
+
+    m.add_function(wrap_pyfunction!(readonly::set_read_only_mode, m)?)?;
+    m.add_function(wrap_pyfunction!(readonly::read_only_mode, m)?)?;
+    m.add_function(wrap_pyfunction!(readonly::read_only_mode_locked, m)?)?;
+    m.add_function(wrap_pyfunction!(faults::fault_injection_available, m)?)?;
+    #[cfg(feature = "fault-injection")]
+    {
+        m.add_function(wrap_pyfunction!(faults::enabled::set_fault, m)?)?;
+        m.add_function(wrap_pyfunction!(faults::enabled::reset_faults, m)?)?;
+        m.add_function(wrap_pyfunction!(faults::enabled::injected_faults, m)?)?;
+        m.add_function(wrap_pyfunction!(
+            faults::enabled::is_injected_corruption,
+            m
+        )?)?;
+    }
+
+    m.add_function(wrap_pyfunction!(dilithium::dilithium_backend, m)?)?;
+    m.add_function(wrap_pyfunction!(dilithium::generate_dilithium_keys, m)?)?;
+    m.add_function(wrap_pyfunction!(dilithium::sign_dilithium, m)?)?;
+    m.add_function(wrap_pyfunction!(dilithium::verify_dilithium, m)?)?;
+    m.add_function(wrap_pyfunction!(mldsa::generate_ml_dsa_keys, m)?)?;
+    m.add_function(wrap_pyfunction!(mldsa::ml_dsa_keys_from_seed, m)?)?;
+    m.add_function(wrap_pyfunction!(mldsa::sign_ml_dsa, m)?)?;
+    m.add_function(wrap_pyfunction!(mldsa::verify_ml_dsa, m)?)?;
+
+    m.add_function(wrap_pyfunction!(envelope::encrypt_to_public_key, m)?)?;
+    m.add_function(wrap_pyfunction!(envelope::decrypt_with_secret_key, m)?)?;
+    m.add_function(wrap_pyfunction!(envelope::decrypt_and_rewrap, m)?)?;
+    m.add_function(wrap_pyfunction!(recipients::encrypt_to_recipients, m)?)?;
+    m.add_function(wrap_pyfunction!(recipients::decrypt_with_secret_keys, m)?)?;
+    m.add_function(wrap_pyfunction!(recipients::envelope_recipients, m)?)?;
+    m.add_function(wrap_pyfunction!(recipients::envelope_threshold, m)?)?;
+    m.add_function(wrap_pyfunction!(recipients::prune_recipients, m)?)?;
+    m.add_function(wrap_pyfunction!(recipients::prune_recipients_bulk, m)?)?;
+    m.add_function(wrap_pyfunction!(recipients::remove_recipients, m)?)?;
+    m.add_function(wrap_pyfunction!(recipients::add_recipients, m)?)?;
+    m.add_function(wrap_pyfunction!(recipients::rewrap_recipients, m)?)?;
+    m.add_function(wrap_pyfunction!(kms::encrypt_with_kms_dek, m)?)?;
+    m.add_function(wrap_pyfunction!(kms::decrypt_with_kms_dek, m)?)?;
+    m.add_function(wrap_pyfunction!(kms::kms_envelope_info, m)?)?;
+    m.add_function(wrap_pyfunction!(vss::vss_split, m)?)?;
+    m.add_function(wrap_pyfunction!(vss::vss_verify_share, m)?)?;
+    m.add_function(wrap_pyfunction!(vss::vss_check_shares, m)?)?;
+    m.add_function(wrap_pyfunction!(vss::vss_combine, m)?)?;
+    m.add_function(wrap_pyfunction!(vss::vss_dealing_info, m)?)?;
+    m.add_function(wrap_pyfunction!(vss::vss_refresh, m)?)?;
+    m.add_function(wrap_pyfunction!(vss::vss_verify_delta, m)?)?;
+    m.add_function(wrap_pyfunction!(vss::vss_apply_refresh, m)?)?;
+    m.add_function(wrap_pyfunction!(vss::vss_refresh_share, m)?)?;
+    m.add_function(wrap_pyfunction!(dkg::dkg_group_key_info, m)?)?;
+    m.add_function(wrap_pyfunction!(dkg::dkg_verify_key_share, m)?)?;
+    m.add_function(wrap_pyfunction!(threshold::threshold_encrypt, m)?)?;
+    m.add_function(wrap_pyfunction!(threshold::threshold_partial_decrypt, m)?)?;
+    m.add_function(wrap_pyfunction!(threshold::threshold_verify_partial, m)?)?;
+    m.add_function(wrap_pyfunction!(threshold::threshold_decrypt, m)?)?;
+    m.add_function(wrap_pyfunction!(timelock::timelock_encrypt, m)?)?;
+    m.add_function(wrap_pyfunction!(timelock::timelock_decrypt, m)?)?;
+    m.add_function(wrap_pyfunction!(timelock::timelock_round, m)?)?;
+    m.add_function(wrap_pyfunction!(timelock::verify_beacon_signature, m)?)?;
+    m.add_function(wrap_pyfunction!(timelock::generate_beacon_keys, m)?)?;
+    m.add_function(wrap_pyfunction!(timelock::sign_beacon_round, m)?)?;
+    m.add_function(wrap_pyfunction!(tsa::create_timestamp_request, m)?)?;
+    m.add_function(wrap_pyfunction!(tsa::timestamp_token_from_response, m)?)?;
+    m.add_function(wrap_pyfunction!(tsa::verify_timestamp_token, m)?)?;
+    m.add_function(wrap_pyfunction!(zk::zk_setup, m)?)?;
+    m.add_function(wrap_pyfunction!(zk::zk_device_commitment, m)?)?;
+    m.add_function(wrap_pyfunction!(zk::zk_prove_device_enrolled, m)?)?;
+    m.add_function(wrap_pyfunction!(zk::zk_verify_device_enrolled, m)?)?;
+    m.add_function(wrap_pyfunction!(zk::zk_timestamp_commitment, m)?)?;
+    m.add_function(wrap_pyfunction!(zk::zk_prove_timestamp_window, m)?)?;
+    m.add_function(wrap_pyfunction!(zk::zk_verify_timestamp_window, m)?)?;
+    m.add_function(wrap_pyfunction!(zk::zk_member_leaf, m)?)?;
+    m.add_function(wrap_pyfunction!(zk::zk_merkle_root, m)?)?;
+    m.add_function(wrap_pyfunction!(zk::zk_merkle_path, m)?)?;
+    m.add_function(wrap_pyfunction!(zk::zk_prove_merkle_membership, m)?)?;
+    m.add_function(wrap_pyfunction!(zk::zk_verify_merkle_membership, m)?)?;
+    m.add_function(wrap_pyfunction!(pedersen::pedersen_commit, m)?)?;
+    m.add_function(wrap_pyfunction!(pedersen::pedersen_verify_opening, m)?)?;
+    m.add_function(wrap_pyfunction!(pedersen::pedersen_prove_opening, m)?)?;
+    m.add_function(wrap_pyfunction!(pedersen::pedersen_verify_proof, m)?)?;
+    m.add_function(wrap_pyfunction!(pedersen::pedersen_add, m)?)?;
+    m.add_function(wrap_pyfunction!(range::range_commit, m)?)?;
+    m.add_function(wrap_pyfunction!(range::range_prove, m)?)?;
+    m.add_function(wrap_pyfunction!(range::range_verify, m)?)?;
+    m.add_function(wrap_pyfunction!(range::range_verify_batch, m)?)?;
+
+    m.add_function(wrap_pyfunction!(unseal::unseal_request_digest, m)?)?;
+    m.add_function(wrap_pyfunction!(unseal::unseal_dry_run, m)?)?;
+    m.add_function(wrap_pyfunction!(unseal::unseal, m)?)?;
+
+    m.add_function(wrap_pyfunction!(backup::export_key_encrypted, m)?)?;
+    m.add_function(wrap_pyfunction!(backup::import_key_encrypted, m)?)?;
+    m.add_function(wrap_pyfunction!(backup::encrypted_key_info, m)?)?;
+    m.add_function(wrap_pyfunction!(backup::encrypted_key_attestation, m)?)?;
+    m.add_function(wrap_pyfunction!(attest::generate_attested_key, m)?)?;
+    m.add_function(wrap_pyfunction!(burn::create_one_time_shares, m)?)?;
+    m.add_function(wrap_pyfunction!(burn::one_time_share_info, m)?)?;
+    m.add_function(wrap_pyfunction!(burn::combine_one_time_shares, m)?)?;
+    m.add_function(wrap_pyfunction!(social::create_social_recovery, m)?)?;
+    m.add_function(wrap_pyfunction!(social::social_recovery_info, m)?)?;
+    m.add_function(wrap_pyfunction!(social::guardian_package_info, m)?)?;
+    m.add_function(wrap_pyfunction!(social::open_guardian_package, m)?)?;
+    m.add_function(wrap_pyfunction!(social::verify_released_share, m)?)?;
+    m.add_function(wrap_pyfunction!(social::recover_social_secret, m)?)?;
+    m.add_function(wrap_pyfunction!(escrow::escrow_wrap_dual, m)?)?;
+    m.add_function(wrap_pyfunction!(escrow::escrow_unwrap_dual, m)?)?;
+    m.add_function(wrap_pyfunction!(escrow::escrow_verify_kek, m)?)?;
+    m.add_function(wrap_pyfunction!(escrow::escrow_roles, m)?)?;
+    m.add_function(wrap_pyfunction!(pkcs::export_secret_key_pkcs8, m)?)?;
+    m.add_function(wrap_pyfunction!(pkcs::import_secret_key_pkcs8, m)?)?;
+    m.add_function(wrap_pyfunction!(pkcs::export_public_key_spki, m)?)?;
+    m.add_function(wrap_pyfunction!(pkcs::import_public_key_spki, m)?)?;
+
+    m.add_function(wrap_pyfunction!(fingerprint::key_fingerprint, m)?)?;
+    m.add_function(wrap_pyfunction!(akem::auth_encapsulate_kyber, m)?)?;
+    m.add_function(wrap_pyfunction!(akem::auth_decapsulate_kyber, m)?)?;
+
+    m.add_function(wrap_pyfunction!(sizes::signature_size, m)?)?;
+    m.add_function(wrap_pyfunction!(sizes::estimate_sealed_size, m)?)?;
+    m.add_function(wrap_pyfunction!(
+        sizes::estimate_auth_encapsulation_size,
+        m
+    )?)?;
+    m.add_function(wrap_pyfunction!(sizes::estimate_armored_size, m)?)?;
+
+    m.add_function(wrap_pyfunction!(armor::armor, m)?)?;
+    m.add_function(wrap_pyfunction!(armor::dearmor, m)?)?;
+
+    m.add_class::<KyberParams>()?;
+    m.add_class::<FalconParams>()?;
+    m.add_class::<mceliece::KeyBuffer>()?;
+    m.add_class::<armor::ArmorType>()?;
+    m.add_class::<tsa::TimestampInfo>()?;
+    m.add_class::<prehash::SigningDigest>()?;
+    m.add_class::<quorum::QuorumPolicy>()?;
+    m.add_class::<lms::LmsSigner>()?;
+    m.add_class::<hierarchy::KeyHierarchy>()?;
+    m.add_class::<keyring::KeyRing>()?;
+    m.add_class::<handles::SecretKeyHandle>()?;
+    m.add_class::<pedersen::PedersenKey>()?;
+    m.add_class::<usage::KeyUsagePolicy>()?;
+    m.add_class::<attest::KeyAttestation>()?;
+    m.add_class::<burn::ShareTombstone>()?;
+    m.add_class::<ratchet::RatchetSession>()?;
+    m.add_class::<hpke::HpkeSuite>()?;
+    m.add_class::<hpke::HpkeSenderContext>()?;
+    m.add_class::<hpke::HpkeRecipientContext>()?;
+    m.add_class::<pipeline::VerificationPipeline>()?;
+    m.add_class::<pipeline::VerificationReport>()?;
+    m.add_class::<dkg::DkgParticipant>()?;
+    m.add("ReadOnlyMode", py.get_type::<readonly::ReadOnlyMode>())?;
+    m.add("InjectedFault", py.get_type::<faults::InjectedFault>())?;
     Ok(())
 }
 
 /// Encrypts data using AES-GCM-256. Returns (ciphertext_with_tag, nonce)
 #[pyfunction]
 fn encrypt_data(data: Vec<u8>, key_bytes: Vec<u8>) -> PyResult<(Vec<u8>, Vec<u8>)> {
-    if key_bytes.len() != 32 {
-        return Err(pyo3::exceptions::PyValueError::new_err(
-            "Key must be 32 bytes for AES-256",
-        ));
-    }
-    let key = GenericArray::<u8, U32>::from_slice(&key_bytes);
-    let cipher = Aes256Gcm::new(key);
-    let nonce = Aes256Gcm::generate_nonce(&mut OsRng);
-
-    let ciphertext_with_tag = cipher.encrypt(&nonce, data.as_ref()).map_err(|e| {
-        pyo3::exceptions::PyValueError::new_err(format!("Encryption error: {:?}", e))
-    })?;
-
-    Ok((ciphertext_with_tag, nonce.to_vec()))
+    faults::inject(faults::Operation::Encrypt, || {
+        if key_bytes.len() != 32 {
+            return Err(pyo3::exceptions::PyValueError::new_err(
+                "Key must be 32 bytes for AES-256",
+            ));
+        }
+        let key = GenericArray::<u8, U32>::from_slice(&key_bytes);
+        let cipher = Aes256Gcm::new(key);
+        let nonce = Aes256Gcm::generate_nonce(&mut OsRng);
+
+        let ciphertext_with_tag = cipher.encrypt(&nonce, data.as_ref()).map_err(|e| {
+            pyo3::exceptions::PyValueError::new_err(format!("Encryption error: {:?}", e))
+        })?;
+
+        Ok((ciphertext_with_tag, nonce.to_vec()))
+    })
 }
 
 /// Encrypts data with an explicitly provided nonce (for Python FFI)
@@ -68,53 +367,57 @@ fn encrypt_data_with_nonce(
     key_bytes: Vec<u8>,
     nonce_bytes: Vec<u8>,
 ) -> PyResult<Vec<u8>> {
-    if key_bytes.len() != 32 {
-        return Err(pyo3::exceptions::PyValueError::new_err(
-            "Key must be 32 bytes",
-        ));
-    }
-    if nonce_bytes.len() != 12 {
-        return Err(pyo3::exceptions::PyValueError::new_err(
-            "Nonce must be 12 bytes",
-        ));
-    }
-
-    let key = GenericArray::<u8, U32>::from_slice(&key_bytes);
-    let cipher = Aes256Gcm::new(key);
-    let nonce = Nonce::from_slice(&nonce_bytes);
-
-    let ciphertext_with_tag = cipher.encrypt(nonce, data.as_ref()).map_err(|e| {
-        pyo3::exceptions::PyValueError::new_err(format!("Encryption error: {:?}", e))
-    })?;
-
-    Ok(ciphertext_with_tag)
+    faults::inject(faults::Operation::Encrypt, || {
+        if key_bytes.len() != 32 {
+            return Err(pyo3::exceptions::PyValueError::new_err(
+                "Key must be 32 bytes",
+            ));
+        }
+        if nonce_bytes.len() != 12 {
+            return Err(pyo3::exceptions::PyValueError::new_err(
+                "Nonce must be 12 bytes",
+            ));
+        }
+
+        let key = GenericArray::<u8, U32>::from_slice(&key_bytes);
+        let cipher = Aes256Gcm::new(key);
+        let nonce = Nonce::from_slice(&nonce_bytes);
+
+        let ciphertext_with_tag = cipher.encrypt(nonce, data.as_ref()).map_err(|e| {
+            pyo3::exceptions::PyValueError::new_err(format!("Encryption error: {:?}", e))
+        })?;
+
+        Ok(ciphertext_with_tag)
+    })
 }
 
 /// Decrypts AES-GCM-256 encrypted data. Returns plaintext or raises ValueError on failure
 #[pyfunction]
 fn decrypt_data(ciphertext_with_tag: &[u8], nonce: &[u8], key_bytes: &[u8]) -> PyResult<Vec<u8>> {
-    if key_bytes.len() != 32 {
-        return Err(pyo3::exceptions::PyValueError::new_err(
-            "Key must be 32 bytes",
-        ));
-    }
-    if nonce.len() != 12 {
-        return Err(pyo3::exceptions::PyValueError::new_err(
-            "Nonce must be 12 bytes",
-        ));
-    }
-
-    let key = GenericArray::<u8, U32>::from_slice(key_bytes);
-    let cipher = Aes256Gcm::new(key);
-    let nonce = Nonce::from_slice(nonce);
-
-    match cipher.decrypt(nonce, ciphertext_with_tag) {
-        Ok(plaintext) => Ok(plaintext),
-        Err(e) => Err(pyo3::exceptions::PyValueError::new_err(format!(
-            "Decryption failed: {:?}",
-            e
-        ))),
-    }
+    faults::inject(faults::Operation::Decrypt, || {
+        if key_bytes.len() != 32 {
+            return Err(pyo3::exceptions::PyValueError::new_err(
+                "Key must be 32 bytes",
+            ));
+        }
+        if nonce.len() != 12 {
+            return Err(pyo3::exceptions::PyValueError::new_err(
+                "Nonce must be 12 bytes",
+            ));
+        }
+
+        let key = GenericArray::<u8, U32>::from_slice(key_bytes);
+        let cipher = Aes256Gcm::new(key);
+        let nonce = Nonce::from_slice(nonce);
+
+        match cipher.decrypt(nonce, ciphertext_with_tag) {
+            Ok(plaintext) => Ok(plaintext),
+            Err(e) => Err(pyo3::exceptions::PyValueError::new_err(format!(
+                "Decryption failed: {:?}",
+                e
+            ))),
+        }
+    })
 }
 
 /// Python-friendly wrapper: Vec inputs/outputs
@@ -127,110 +430,252 @@ fn decrypt_data_with_nonce(
     decrypt_data(&ciphertext_with_tag, &nonce_bytes, &key_bytes)
 }
 
-/// Generate Kyber-1024 public/private keypair for post-quantum key encapsulation
+/// Generate a Kyber public/private keypair for post-quantum key encapsulation (Kyber-1024 by default)
 #[pyfunction]
-fn generate_kyber_keys() -> PyResult<(Vec<u8>, Vec<u8>)> {
-    let (pk, sk) = kyber::keypair();
-    Ok((pk.as_bytes().to_vec(), sk.as_bytes().to_vec()))
+#[pyo3(signature = (params = KyberParams::Kyber1024))]
+fn generate_kyber_keys(py: Python<'_>, params: KyberParams) -> PyResult<(Vec<u8>, Vec<u8>)> {
+    py.allow_threads(|| {
+        with_kyber!(params, kyber => {
+            let (pk, sk) = kyber::keypair();
+            Ok((pk.as_bytes().to_vec(), sk.as_bytes().to_vec()))
+        })
+    })
 }
 
-/// Kyber-1024 encapsulation - generate shared secret and ciphertext
+/// Kyber encapsulation - generate shared secret and ciphertext.
+/// With `info`, the shared secret is replaced by a 32-byte HKDF-SHA3-256 key bound to the
+/// ciphertext, parameter set and `info`; pass the same `info` to `decapsulate_kyber`
 #[pyfunction]
-fn encapsulate_kyber(pk_bytes: Vec<u8>) -> PyResult<(Vec<u8>, Vec<u8>)> {
-    if pk_bytes.len() != KYBER_PUBLICKEYBYTES {
-        return Err(pyo3::exceptions::PyValueError::new_err(format!(
-            "Invalid public key length. Expected {}, got {}",
-            KYBER_PUBLICKEYBYTES,
-            pk_bytes.len()
-        )));
-    }
-
-    let pk = kyber::PublicKey::from_bytes(&pk_bytes).map_err(|e| {
-        pyo3::exceptions::PyValueError::new_err(format!("Invalid public key: {:?}", e))
-    })?;
+#[pyo3(signature = (pk_bytes, params = KyberParams::Kyber1024, info = None))]
+fn encapsulate_kyber(
+    py: Python<'_>,
+    pk_bytes: Vec<u8>,
+    params: KyberParams,
+    info: Option<Vec<u8>>,
+) -> PyResult<(Vec<u8>, Vec<u8>)> {
+    py.allow_threads(|| {
+        let (ss, ct) = kyber_encapsulate(&pk_bytes, params)?;
+        match info {
+            Some(info) => {
+                let ss = Zeroizing::new(ss);
+                Ok((kdf::derive_kem_key(&ss, &ct, params, &info)?, ct))
+            }
+            None => Ok((ss, ct)),
+        }
+    })
+}
 
-    let (ss, ct) = kyber::encapsulate(&pk);
-    Ok((ss.as_bytes().to_vec(), ct.as_bytes().to_vec()))
+pub(crate) fn kyber_encapsulate(
+    pk_bytes: &[u8],
+    params: KyberParams,
+) -> PyResult<(Vec<u8>, Vec<u8>)> {
+    faults::inject(faults::Operation::Encapsulate, || {
+        validate::check_kyber_public_key(pk_bytes, params)
+            .map_err(pyo3::exceptions::PyValueError::new_err)?;
+
+        with_kyber!(params, kyber => {
+            let pk = kyber::PublicKey::from_bytes(pk_bytes).map_err(|e| {
+                pyo3::exceptions::PyValueError::new_err(format!("Invalid public key: {:?}", e))
+            })?;
+
+            let (ss, ct) = kyber::encapsulate(&pk);
+            Ok((ss.as_bytes().to_vec(), ct.as_bytes().to_vec()))
+        })
+    })
 }
 
-/// Kyber-1024 decapsulation - recover shared secret from ciphertext
+/// Kyber decapsulation - recover shared secret from ciphertext.
+/// With `info`, returns the same derived 32-byte key as `encapsulate_kyber(..., info=info)`.
+/// `sk_bytes` may be a SecretKeyHandle, whose own parameter set is used
 #[pyfunction]
-fn decapsulate_kyber(ct_bytes: Vec<u8>, sk_bytes: Vec<u8>) -> PyResult<Vec<u8>> {
-    if ct_bytes.len() != KYBER_CIPHERTEXTBYTES {
-        return Err(pyo3::exceptions::PyValueError::new_err(format!(
-            "Invalid ciphertext length. Expected {}, got {}",
-            KYBER_CIPHERTEXTBYTES,
-            ct_bytes.len()
-        )));
-    }
-
-    if sk_bytes.len() != KYBER_SECRETKEYBYTES {
-        return Err(pyo3::exceptions::PyValueError::new_err(format!(
-            "Invalid secret key length. Expected {}, got {}",
-            KYBER_SECRETKEYBYTES,
-            sk_bytes.len()
-        )));
-    }
-
-    let ct = kyber::Ciphertext::from_bytes(&ct_bytes).map_err(|e| {
-        pyo3::exceptions::PyValueError::new_err(format!("Invalid ciphertext: {:?}", e))
-    })?;
-
-    let sk = kyber::SecretKey::from_bytes(&sk_bytes).map_err(|e| {
-        pyo3::exceptions::PyValueError::new_err(format!("Invalid secret key: {:?}", e))
-    })?;
+#[pyo3(signature = (ct_bytes, sk_bytes, params = KyberParams::Kyber1024, info = None))]
+fn decapsulate_kyber(
+    py: Python<'_>,
+    ct_bytes: Vec<u8>,
+    sk_bytes: DecryptionKeyArg,
+    params: KyberParams,
+    info: Option<Vec<u8>>,
+) -> PyResult<Vec<u8>> {
+    let params = sk_bytes.kyber_params(params)?;
+    py.allow_threads(|| {
+        let ss = kyber_decapsulate(&ct_bytes, &sk_bytes, params)?;
+        match info {
+            Some(info) => {
+                let ss = Zeroizing::new(ss);
+                kdf::derive_kem_key(&ss, &ct_bytes, params, &info)
+            }
+            None => Ok(ss),
+        }
+    })
+}
 
-    let ss = kyber::decapsulate(&ct, &sk);
-    Ok(ss.as_bytes().to_vec())
+pub(crate) fn kyber_decapsulate(
+    ct_bytes: &[u8],
+    sk_bytes: &[u8],
+    params: KyberParams,
+) -> PyResult<Vec<u8>> {
+    faults::inject(faults::Operation::Decapsulate, || {
+        validate::check_kyber_ciphertext(ct_bytes, params)
+            .map_err(pyo3::exceptions::PyValueError::new_err)?;
+
+        if sk_bytes.len() != params.secret_key_bytes() {
+            return Err(pyo3::exceptions::PyValueError::new_err(format!(
+                "Invalid secret key length. Expected {}, got {}",
+                params.secret_key_bytes(),
+                sk_bytes.len()
+            )));
+        }
+        if params == KyberParams::MlKem1024 {
+            mlkem::check_decapsulation_key(sk_bytes)
+                .map_err(pyo3::exceptions::PyValueError::new_err)?;
+        }
+
+        with_kyber!(params, kyber => {
+            let ct = kyber::Ciphertext::from_bytes(ct_bytes).map_err(|e| {
+                pyo3::exceptions::PyValueError::new_err(format!("Invalid ciphertext: {:?}", e))
+            })?;
+
+            let sk = kyber::SecretKey::from_bytes(sk_bytes).map_err(|e| {
+                pyo3::exceptions::PyValueError::new_err(format!("Invalid secret key: {:?}", e))
+            })?;
+
+            let ss = kyber::decapsulate(&ct, &sk);
+            Ok(ss.as_bytes().to_vec())
+        })
+    })
 }
 
-/// Generate Falcon-1024 public/private keypair for post-quantum digital signatures
+/// Generate a Falcon public/private keypair for post-quantum digital signatures (Falcon-1024 by default)
 #[pyfunction]
-fn generate_falcon_keys() -> PyResult<(Vec<u8>, Vec<u8>)> {
-    let (pk, sk) = falcon::keypair();
-    Ok((pk.as_bytes().to_vec(), sk.as_bytes().to_vec()))
+#[pyo3(signature = (params = FalconParams::Falcon1024))]
+fn generate_falcon_keys(py: Python<'_>, params: FalconParams) -> PyResult<(Vec<u8>, Vec<u8>)> {
+    py.allow_threads(|| {
+        with_falcon!(params, falcon => {
+            let (pk, sk) = falcon::keypair();
+            Ok((pk.as_bytes().to_vec(), sk.as_bytes().to_vec()))
+        })
+    })
 }
 
-/// Falcon-1024 signature generation
+/// Falcon signature generation. `sk_bytes` may be a SecretKeyHandle, whose own parameter set
+/// is used
 #[pyfunction]
-fn sign_falcon(msg: Vec<u8>, sk_bytes: Vec<u8>) -> PyResult<Vec<u8>> {
-    if sk_bytes.len() != FALCON_SECRETKEYBYTES {
-        return Err(pyo3::exceptions::PyValueError::new_err(format!(
-            "Invalid secret key length. Expected {}, got {}",
-            FALCON_SECRETKEYBYTES,
-            sk_bytes.len()
-        )));
-    }
-
-    let sk = falcon::SecretKey::from_bytes(&sk_bytes).map_err(|e| {
-        pyo3::exceptions::PyValueError::new_err(format!("Invalid secret key: {:?}", e))
-    })?;
+#[pyo3(signature = (msg, sk_bytes, params = FalconParams::Falcon1024))]
+fn sign_falcon(
+    py: Python<'_>,
+    msg: Vec<u8>,
+    sk_bytes: SigningKeyArg,
+    params: FalconParams,
+) -> PyResult<Vec<u8>> {
+    let params = sk_bytes.falcon_params(params)?;
+    py.allow_threads(|| falcon_sign(&msg, &sk_bytes, params))
+}
 
-    let signed_msg = falcon::sign(&msg, &sk);
-    Ok(signed_msg.as_bytes().to_vec())
+pub(crate) fn falcon_sign(msg: &[u8], sk_bytes: &[u8], params: FalconParams) -> PyResult<Vec<u8>> {
+    faults::inject(faults::Operation::Sign, || {
+        if sk_bytes.len() != params.secret_key_bytes() {
+            return Err(pyo3::exceptions::PyValueError::new_err(format!(
+                "Invalid secret key length. Expected {}, got {}",
+                params.secret_key_bytes(),
+                sk_bytes.len()
+            )));
+        }
+
+        with_falcon!(params, falcon => {
+            let sk = falcon::SecretKey::from_bytes(sk_bytes).map_err(|e| {
+                pyo3::exceptions::PyValueError::new_err(format!("Invalid secret key: {:?}", e))
+            })?;
+
+            let signed_msg = falcon::sign(msg, &sk);
+            Ok(signed_msg.as_bytes().to_vec())
+        })
+    })
 }
 
-/// Falcon-1024 signature verification
-#[pyfunction]
-fn verify_falcon(msg: Vec<u8>, sig_bytes: Vec<u8>, pk_bytes: Vec<u8>) -> PyResult<bool> {
-    if pk_bytes.len() != FALCON_PUBLICKEYBYTES {
-        return Err(pyo3::exceptions::PyValueError::new_err(format!(
-            "Invalid public key length. Expected {}, got {}",
-            FALCON_PUBLICKEYBYTES,
-            pk_bytes.len()
-        )));
-    }
+/// Detached Falcon signature over `msg`, for formats that carry the message separately.
+pub(crate) fn falcon_sign_detached(
+    msg: &[u8],
+    sk_bytes: &[u8],
+    params: FalconParams,
+) -> PyResult<Vec<u8>> {
+    faults::inject(faults::Operation::Sign, || {
+        if sk_bytes.len() != params.secret_key_bytes() {
+            return Err(pyo3::exceptions::PyValueError::new_err(format!(
+                "Invalid secret key length. Expected {}, got {}",
+                params.secret_key_bytes(),
+                sk_bytes.len()
+            )));
+        }
+
+        with_falcon!(params, falcon => {
+            let sk = falcon::SecretKey::from_bytes(sk_bytes).map_err(|e| {
+                pyo3::exceptions::PyValueError::new_err(format!("Invalid secret key: {:?}", e))
+            })?;
+
+            Ok(falcon::detached_sign(msg, &sk).as_bytes().to_vec())
+        })
+    })
+}
 
-    let pk = falcon::PublicKey::from_bytes(&pk_bytes).map_err(|e| {
-        pyo3::exceptions::PyValueError::new_err(format!("Invalid public key: {:?}", e))
-    })?;
+pub(crate) fn falcon_verify_detached(
+    msg: &[u8],
+    sig_bytes: &[u8],
+    pk_bytes: &[u8],
+    params: FalconParams,
+) -> PyResult<bool> {
+    faults::inject(faults::Operation::Verify, || {
+        validate::check_falcon_public_key(pk_bytes, params)
+            .map_err(pyo3::exceptions::PyValueError::new_err)?;
+
+        with_falcon!(params, falcon => {
+            let pk = falcon::PublicKey::from_bytes(pk_bytes).map_err(|e| {
+                pyo3::exceptions::PyValueError::new_err(format!("Invalid public key: {:?}", e))
+            })?;
+
+            match falcon::DetachedSignature::from_bytes(sig_bytes) {
+                Ok(sig) => Ok(falcon::verify_detached_signature(&sig, msg, &pk).is_ok()),
+                Err(_) => Ok(false),
+            }
+        })
+    })
+}
 
-    let signed_msg = falcon::SignedMessage::from_bytes(&sig_bytes).map_err(|e| {
-        pyo3::exceptions::PyValueError::new_err(format!("Invalid signature: {:?}", e))
-    })?;
+/// Falcon signature verification
+#[pyfunction]
+#[pyo3(signature = (msg, sig_bytes, pk_bytes, params = FalconParams::Falcon1024))]
+fn verify_falcon(
+    py: Python<'_>,
+    msg: Vec<u8>,
+    sig_bytes: Vec<u8>,
+    pk_bytes: Vec<u8>,
+    params: FalconParams,
+) -> PyResult<bool> {
+    py.allow_threads(|| falcon_verify(&msg, &sig_bytes, &pk_bytes, params))
+}
 
-    match falcon::open(&signed_msg, &pk) {
-        Ok(recovered_msg) => Ok(recovered_msg == msg),
-        Err(_) => Ok(false),
-    }
+pub(crate) fn falcon_verify(
+    msg: &[u8],
+    sig_bytes: &[u8],
+    pk_bytes: &[u8],
+    params: FalconParams,
+) -> PyResult<bool> {
+    faults::inject(faults::Operation::Verify, || {
+        validate::check_falcon_public_key(pk_bytes, params)
+            .map_err(pyo3::exceptions::PyValueError::new_err)?;
+
+        with_falcon!(params, falcon => {
+            let pk = falcon::PublicKey::from_bytes(pk_bytes).map_err(|e| {
+                pyo3::exceptions::PyValueError::new_err(format!("Invalid public key: {:?}", e))
+            })?;
+
+            let signed_msg = falcon::SignedMessage::from_bytes(sig_bytes).map_err(|e| {
+                pyo3::exceptions::PyValueError::new_err(format!("Invalid signature: {:?}", e))
+            })?;
+
+            match falcon::open(&signed_msg, &pk) {
+                Ok(recovered_msg) => Ok(recovered_msg == msg),
+                Err(_) => Ok(false),
+            }
+        })
+    })
 }
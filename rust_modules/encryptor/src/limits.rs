@@ -0,0 +1,75 @@
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+
+use pyo3::prelude::*;
+
+// Ceilings for attacker-supplied artifacts (envelopes, signature bundles, quorum certificates,
+// VRF proofs, armored text). The binary parsers here are iterative and every field is bounded
+// by its own length prefix, so there is no recursion to limit; what hardened mode adds is a cap
+// on total input size and on repeated items (recipient slots, bundle layers, signers), checked
+// before anything is allocated for them. Outside hardened mode nothing is capped.
+const HARDENED_MAX_INPUT_BYTES: usize = 1 << 20;
+const HARDENED_MAX_ITEMS: usize = 64;
+
+// Process-wide switches: every caller of the (single) importing interpreter sees them.
+static HARDENED: AtomicBool = AtomicBool::new(false);
+static MAX_INPUT_BYTES: AtomicUsize = AtomicUsize::new(HARDENED_MAX_INPUT_BYTES);
+static MAX_ITEMS: AtomicUsize = AtomicUsize::new(HARDENED_MAX_ITEMS);
+
+/// Fails if hardened parsing is on and `what` is longer than the input ceiling.
+pub(crate) fn check_input(what: &str, len: usize) -> Result<(), String> {
+    let max = MAX_INPUT_BYTES.load(Ordering::SeqCst);
+    if HARDENED.load(Ordering::SeqCst) && len > max {
+        return Err(format!(
+            "{} is {} bytes, over the hardened parsing limit of {}",
+            what, len, max
+        ));
+    }
+    Ok(())
+}
+
+/// Fails if hardened parsing is on and `what` declares more than the item ceiling.
+pub(crate) fn check_items(what: &str, count: usize) -> Result<(), String> {
+    let max = MAX_ITEMS.load(Ordering::SeqCst);
+    if HARDENED.load(Ordering::SeqCst) && count > max {
+        return Err(format!(
+            "{} declares {} items, over the hardened parsing limit of {}",
+            what, count, max
+        ));
+    }
+    Ok(())
+}
+
+/// Turns hardened parsing on or off for the whole process. While on, untrusted inputs longer
+/// than `max_input_bytes` or declaring more than `max_items` recipient slots, bundle layers or
+/// signers are rejected before they are parsed (defaults 1 MiB and 64)
+#[pyfunction]
+#[pyo3(signature = (enabled, max_input_bytes = None, max_items = None))]
+pub fn set_hardened_parsing(
+    enabled: bool,
+    max_input_bytes: Option<usize>,
+    max_items: Option<usize>,
+) -> PyResult<()> {
+    if max_input_bytes == Some(0) || max_items == Some(0) {
+        return Err(pyo3::exceptions::PyValueError::new_err(
+            "Parsing limits must be at least 1",
+        ));
+    }
+    MAX_INPUT_BYTES.store(
+        max_input_bytes.unwrap_or(HARDENED_MAX_INPUT_BYTES),
+        Ordering::SeqCst,
+    );
+    MAX_ITEMS.store(max_items.unwrap_or(HARDENED_MAX_ITEMS), Ordering::SeqCst);
+    HARDENED.store(enabled, Ordering::SeqCst);
+    Ok(())
+}
+
+/// Current parsing limits as (hardened, max_input_bytes, max_items); the limits only apply
+/// while hardened is true
+#[pyfunction]
+pub fn hardened_parsing() -> (bool, usize, usize) {
+    (
+        HARDENED.load(Ordering::SeqCst),
+        MAX_INPUT_BYTES.load(Ordering::SeqCst),
+        MAX_ITEMS.load(Ordering::SeqCst),
+    )
+}
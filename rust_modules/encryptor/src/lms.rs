@@ -0,0 +1,499 @@
+use std::fs::{self, File, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use pyo3::prelude::*;
+use rand::RngCore;
+use sha2::{Digest, Sha256};
+use zeroize::Zeroizing;
+
+use crate::readonly;
+use crate::wire::Reader;
+
+// Leighton-Micali signatures (RFC 8554) with LMOTS_SHA256_N32_W8, emitted in single-level HSS
+// encoding so other HSS verifiers accept them:
+//   public key: levels u32 (= 1) | lms type u32 | ots type u32 | I (16) | T[1] (32)
+//   signature:  Nspk u32 (= 0) | q u32 | ots type u32 | C (32) | y[0..34] (32 each)
+//               | lms type u32 | auth path (32 * h)
+// One-time keys are derived from a 32-byte seed (RFC 8554 appendix A), so the state file only
+// holds the seed, the next leaf index and the cached tree:
+//   magic "RQLS" | version u8 | lms type u32 | I (16) | seed (32) | next q u32 | T[1..2^(h+1)]
+// The next index is persisted (write, fsync, rename) before a signature is released, so a crash
+// can only burn a leaf, never reuse one.
+const MAGIC: &[u8; 4] = b"RQLS";
+const VERSION: u8 = 1;
+const N: usize = 32;
+const ID_BYTES: usize = 16;
+const SEED_BYTES: usize = 32;
+const P: usize = 34;
+const LMOTS_SHA256_N32_W8: u32 = 4;
+const D_PBLC: u16 = 0x8080;
+const D_MESG: u16 = 0x8181;
+const D_LEAF: u16 = 0x8282;
+const D_INTR: u16 = 0x8383;
+const OTS_SIGNATURE_BYTES: usize = 4 + N + P * N;
+const PUBLIC_KEY_BYTES: usize = 4 + 4 + 4 + ID_BYTES + N;
+const STATE_HEADER_BYTES: usize = 4 + 1 + 4 + ID_BYTES + SEED_BYTES + 4;
+/// Heights `generate_lms_keys` accepts; keygen cost doubles with every level.
+const KEYGEN_HEIGHTS: [u32; 3] = [5, 10, 15];
+
+type Node = [u8; N];
+
+fn lms_type(height: u32) -> Option<u32> {
+    match height {
+        5 => Some(5),
+        10 => Some(6),
+        15 => Some(7),
+        20 => Some(8),
+        25 => Some(9),
+        _ => None,
+    }
+}
+
+fn lms_height(lms_type: u32) -> Option<u32> {
+    (5..=9).contains(&lms_type).then(|| (lms_type - 4) * 5)
+}
+
+fn signature_len(height: u32) -> usize {
+    4 + 4 + OTS_SIGNATURE_BYTES + 4 + N * height as usize
+}
+
+/// Signature size for any height an LMS public key can declare, not only the keygen ones.
+pub(crate) fn signature_size(height: u32) -> Result<usize, String> {
+    lms_type(height)
+        .map(|_| signature_len(height))
+        .ok_or_else(|| {
+            format!(
+                "Unsupported LMS height {}. Expected one of 5, 10, 15, 20, 25",
+                height
+            )
+        })
+}
+
+fn ots_hasher(id: &[u8], q: u32) -> Sha256 {
+    Sha256::new().chain_update(id).chain_update(q.to_be_bytes())
+}
+
+/// Runs the Winternitz chain for digit `i` from step `from` up to (not including) `to`.
+fn chain(id: &[u8], q: u32, i: u16, from: usize, to: usize, start: &Node) -> Node {
+    let mut tmp = *start;
+    for j in from..to {
+        tmp = ots_hasher(id, q)
+            .chain_update(i.to_be_bytes())
+            .chain_update([j as u8])
+            .chain_update(tmp)
+            .finalize()
+            .into();
+    }
+    tmp
+}
+
+fn ots_secret(id: &[u8], q: u32, i: u16, seed: &[u8]) -> Zeroizing<Node> {
+    Zeroizing::new(
+        ots_hasher(id, q)
+            .chain_update(i.to_be_bytes())
+            .chain_update([0xff])
+            .chain_update(seed)
+            .finalize()
+            .into(),
+    )
+}
+
+/// Q || Cksm(Q): with w = 8 every byte is one base-2^w digit.
+fn digits(id: &[u8], q: u32, c: &[u8], message: &[u8]) -> [u8; P] {
+    let hash = ots_hasher(id, q)
+        .chain_update(D_MESG.to_be_bytes())
+        .chain_update(c)
+        .chain_update(message)
+        .finalize();
+    let checksum: u16 = hash.iter().map(|&b| 255 - b as u16).sum();
+    let mut out = [0u8; P];
+    out[..N].copy_from_slice(&hash);
+    out[N..].copy_from_slice(&checksum.to_be_bytes());
+    out
+}
+
+fn ots_public_key(id: &[u8], q: u32, seed: &[u8]) -> Node {
+    let mut hasher = ots_hasher(id, q).chain_update(D_PBLC.to_be_bytes());
+    for i in 0..P as u16 {
+        hasher.update(chain(id, q, i, 0, 255, &ots_secret(id, q, i, seed)));
+    }
+    hasher.finalize().into()
+}
+
+fn ots_sign(id: &[u8], q: u32, seed: &[u8], message: &[u8]) -> Vec<u8> {
+    let mut c = [0u8; N];
+    rand::thread_rng().fill_bytes(&mut c);
+    let mut out = Vec::with_capacity(OTS_SIGNATURE_BYTES);
+    out.extend_from_slice(&LMOTS_SHA256_N32_W8.to_be_bytes());
+    out.extend_from_slice(&c);
+    for (i, &a) in digits(id, q, &c, message).iter().enumerate() {
+        let secret = ots_secret(id, q, i as u16, seed);
+        out.extend_from_slice(&chain(id, q, i as u16, 0, a as usize, &secret));
+    }
+    out
+}
+
+/// Candidate OTS public key recovered from a signature (RFC 8554 algorithm 4b).
+fn ots_candidate(id: &[u8], q: u32, signature: &[u8], message: &[u8]) -> Option<Node> {
+    let mut r = Reader::new(signature);
+    if r.u32().ok()? != LMOTS_SHA256_N32_W8 {
+        return None;
+    }
+    let c = r.bytes(N).ok()?;
+    let mut hasher = ots_hasher(id, q).chain_update(D_PBLC.to_be_bytes());
+    for (i, &a) in digits(id, q, c, message).iter().enumerate() {
+        let y: &Node = r.bytes(N).ok()?.try_into().ok()?;
+        hasher.update(chain(id, q, i as u16, a as usize, 255, y));
+    }
+    Some(hasher.finalize().into())
+}
+
+fn leaf_hash(id: &[u8], r: u32, ots_pk: &Node) -> Node {
+    Sha256::new()
+        .chain_update(id)
+        .chain_update(r.to_be_bytes())
+        .chain_update(D_LEAF.to_be_bytes())
+        .chain_update(ots_pk)
+        .finalize()
+        .into()
+}
+
+fn interior_hash(id: &[u8], r: u32, left: &Node, right: &Node) -> Node {
+    Sha256::new()
+        .chain_update(id)
+        .chain_update(r.to_be_bytes())
+        .chain_update(D_INTR.to_be_bytes())
+        .chain_update(left)
+        .chain_update(right)
+        .finalize()
+        .into()
+}
+
+/// Full tree in heap order: `tree[r]` is T[r] for 1 <= r < 2^(h+1); `tree[0]` is unused.
+/// Leaves are computed on all available cores.
+fn build_tree(id: &[u8], seed: &[u8], height: u32) -> Vec<Node> {
+    let leaves = 1usize << height;
+    let mut tree = vec![[0u8; N]; 2 * leaves];
+    let threads = std::thread::available_parallelism().map_or(1, |n| n.get());
+    let chunk = leaves.div_ceil(threads);
+    std::thread::scope(|scope| {
+        for (c, slots) in tree[leaves..].chunks_mut(chunk).enumerate() {
+            scope.spawn(move || {
+                for (k, slot) in slots.iter_mut().enumerate() {
+                    let q = (c * chunk + k) as u32;
+                    *slot = leaf_hash(id, leaves as u32 + q, &ots_public_key(id, q, seed));
+                }
+            });
+        }
+    });
+    for r in (1..leaves).rev() {
+        tree[r] = interior_hash(id, r as u32, &tree[2 * r], &tree[2 * r + 1]);
+    }
+    tree
+}
+
+fn verify(message: &[u8], signature: &[u8], public_key: &[u8]) -> bool {
+    let mut pk = Reader::new(public_key);
+    let (Ok(1), Ok(pk_type), Ok(LMOTS_SHA256_N32_W8), Ok(id), Ok(root)) = (
+        pk.u32(),
+        pk.u32(),
+        pk.u32(),
+        pk.bytes(ID_BYTES),
+        pk.bytes(N),
+    ) else {
+        return false;
+    };
+    let Some(height) = lms_height(pk_type) else {
+        return false;
+    };
+    if !pk.is_empty() || signature.len() != signature_len(height) {
+        return false;
+    }
+
+    let mut sig = Reader::new(signature);
+    let (Ok(0), Ok(q)) = (sig.u32(), sig.u32()) else {
+        return false;
+    };
+    if q >= 1 << height {
+        return false;
+    }
+    let ots_signature = sig
+        .bytes(OTS_SIGNATURE_BYTES)
+        .expect("length checked above");
+    if sig.u32() != Ok(pk_type) {
+        return false;
+    }
+    let Some(ots_pk) = ots_candidate(id, q, ots_signature, message) else {
+        return false;
+    };
+    let mut node = (1u32 << height) + q;
+    let mut tmp = leaf_hash(id, node, &ots_pk);
+    while node > 1 {
+        let sibling: &Node = sig
+            .bytes(N)
+            .expect("length checked above")
+            .try_into()
+            .expect("32-byte slice");
+        tmp = if node & 1 == 1 {
+            interior_hash(id, node / 2, sibling, &tmp)
+        } else {
+            interior_hash(id, node / 2, &tmp, sibling)
+        };
+        node /= 2;
+    }
+    tmp.as_slice() == root
+}
+
+struct State {
+    lms_type: u32,
+    height: u32,
+    id: [u8; ID_BYTES],
+    seed: Zeroizing<[u8; SEED_BYTES]>,
+    next: u32,
+    tree: Vec<Node>,
+}
+
+impl State {
+    fn public_key(&self) -> Vec<u8> {
+        let mut out = Vec::with_capacity(PUBLIC_KEY_BYTES);
+        out.extend_from_slice(&1u32.to_be_bytes());
+        out.extend_from_slice(&self.lms_type.to_be_bytes());
+        out.extend_from_slice(&LMOTS_SHA256_N32_W8.to_be_bytes());
+        out.extend_from_slice(&self.id);
+        out.extend_from_slice(&self.tree[1]);
+        out
+    }
+
+    fn remaining(&self) -> u32 {
+        (1u32 << self.height) - self.next
+    }
+
+    fn encode(&self) -> Zeroizing<Vec<u8>> {
+        let mut out = Zeroizing::new(Vec::with_capacity(
+            STATE_HEADER_BYTES + (self.tree.len() - 1) * N,
+        ));
+        out.extend_from_slice(MAGIC);
+        out.push(VERSION);
+        out.extend_from_slice(&self.lms_type.to_be_bytes());
+        out.extend_from_slice(&self.id);
+        out.extend_from_slice(self.seed.as_ref());
+        out.extend_from_slice(&self.next.to_be_bytes());
+        for node in &self.tree[1..] {
+            out.extend_from_slice(node);
+        }
+        out
+    }
+
+    fn decode(bytes: &[u8]) -> Result<Self, String> {
+        let mut r = Reader::new(bytes);
+        if r.bytes(4).map_err(|_| "Not an LMS state file")? != MAGIC {
+            return Err("Not an LMS state file".to_string());
+        }
+        let version = r.u8()?;
+        if version != VERSION {
+            return Err(format!("Unsupported LMS state version {}", version));
+        }
+        let lms_type = r.u32()?;
+        let height =
+            lms_height(lms_type).ok_or_else(|| format!("Unknown LMS type {}", lms_type))?;
+        let id = r.bytes(ID_BYTES)?.try_into().expect("16-byte slice");
+        let seed = Zeroizing::new(r.bytes(SEED_BYTES)?.try_into().expect("32-byte slice"));
+        let next = r.u32()?;
+        if next > 1 << height {
+            return Err("LMS state file is corrupt (index out of range)".to_string());
+        }
+        let mut tree = vec![[0u8; N]];
+        for _ in 1..(2usize << height) {
+            tree.push(r.bytes(N)?.try_into().expect("32-byte slice"));
+        }
+        if !r.is_empty() {
+            return Err("Trailing bytes after LMS state".to_string());
+        }
+        Ok(State {
+            lms_type,
+            height,
+            id,
+            seed,
+            next,
+            tree,
+        })
+    }
+
+    /// Atomically replaces the state file: write a sibling temp file, fsync, rename over the
+    /// old one and fsync the directory.
+    fn persist(&self, path: &Path) -> std::io::Result<()> {
+        let tmp = sibling(path, "tmp");
+        let mut file = create_private(&tmp, true)?;
+        file.write_all(&self.encode())?;
+        file.sync_all()?;
+        fs::rename(&tmp, path)?;
+        #[cfg(unix)]
+        {
+            let dir = path.parent().filter(|d| !d.as_os_str().is_empty());
+            File::open(dir.unwrap_or(Path::new(".")))?.sync_all()?;
+        }
+        Ok(())
+    }
+}
+
+fn sibling(path: &Path, extension: &str) -> PathBuf {
+    let mut name = path.as_os_str().to_owned();
+    name.push(".");
+    name.push(extension);
+    PathBuf::from(name)
+}
+
+/// Opens a file only the owner can read (0600 on Unix).
+fn create_private(path: &Path, truncate: bool) -> std::io::Result<File> {
+    let mut options = OpenOptions::new();
+    options.write(true);
+    if truncate {
+        options.create(true).truncate(true);
+    } else {
+        options.create_new(true);
+    }
+    #[cfg(unix)]
+    std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+    options.open(path)
+}
+
+/// Exclusive lock on `<state>.lock`, held for the signer's lifetime so two signers (in this or
+/// another process) can never hand out the same index.
+fn lock_state(path: &Path) -> PyResult<File> {
+    let lock = OpenOptions::new()
+        .create(true)
+        .truncate(false)
+        .write(true)
+        .open(sibling(path, "lock"))?;
+    lock.try_lock().map_err(|_| {
+        pyo3::exceptions::PyRuntimeError::new_err("LMS state file is in use by another signer")
+    })?;
+    Ok(lock)
+}
+
+/// Generates an LMS key (tree height 5, 10 or 15: 32, 1024 or 32768 signatures) and writes its
+/// private state to a new file at `state_path`, which must not exist. Returns the public key.
+/// The private key never leaves the state file; sign with `LmsSigner`. Height 15 takes a while
+#[pyfunction]
+#[pyo3(signature = (state_path, height = 10))]
+pub fn generate_lms_keys(py: Python<'_>, state_path: PathBuf, height: u32) -> PyResult<Vec<u8>> {
+    if !KEYGEN_HEIGHTS.contains(&height) {
+        return Err(pyo3::exceptions::PyValueError::new_err(format!(
+            "Unsupported LMS height {}. Expected one of {:?}",
+            height, KEYGEN_HEIGHTS
+        )));
+    }
+    readonly::check_writable("LMS key generation")?;
+    py.allow_threads(|| {
+        let mut file = create_private(&state_path, false)?;
+        let mut id = [0u8; ID_BYTES];
+        let mut seed = Zeroizing::new([0u8; SEED_BYTES]);
+        rand::thread_rng().fill_bytes(&mut id);
+        rand::thread_rng().fill_bytes(seed.as_mut());
+        let state = State {
+            lms_type: lms_type(height).expect("supported height"),
+            height,
+            tree: build_tree(&id, seed.as_ref(), height),
+            id,
+            seed,
+            next: 0,
+        };
+        file.write_all(&state.encode())?;
+        file.sync_all()?;
+        Ok(state.public_key())
+    })
+}
+
+/// Signs with the LMS key held in a state file. The file is locked for the signer's lifetime,
+/// and every signature advances the persisted leaf index before it is returned. In read-only
+/// mode the state is opened without the lock (no lock file is created) and signing is refused
+#[pyclass(module = "reliquary_encryptor")]
+pub struct LmsSigner {
+    path: PathBuf,
+    state: State,
+    lock: Option<File>,
+}
+
+#[pymethods]
+impl LmsSigner {
+    #[new]
+    fn new(state_path: PathBuf) -> PyResult<Self> {
+        let lock = if readonly::is_enabled() {
+            None
+        } else {
+            Some(lock_state(&state_path)?)
+        };
+        let bytes = Zeroizing::new(fs::read(&state_path)?);
+        let state = State::decode(&bytes).map_err(pyo3::exceptions::PyValueError::new_err)?;
+        Ok(LmsSigner {
+            path: state_path,
+            state,
+            lock,
+        })
+    }
+
+    /// Public key for `verify_lms`
+    #[getter]
+    fn public_key(&self) -> Vec<u8> {
+        self.state.public_key()
+    }
+
+    /// Tree height; the key signs 2^height messages in total
+    #[getter]
+    fn height(&self) -> u32 {
+        self.state.height
+    }
+
+    /// Signatures left before the key is exhausted
+    #[getter]
+    fn remaining_signatures(&self) -> u32 {
+        self.state.remaining()
+    }
+
+    /// Signs `message` with the next unused one-time key. Raises once the key is exhausted or
+    /// if the advanced state can't be persisted (no signature is released in that case)
+    fn sign(&mut self, py: Python<'_>, message: Vec<u8>) -> PyResult<Vec<u8>> {
+        readonly::check_writable("LMS signing")?;
+        if self.lock.is_none() {
+            return Err(pyo3::exceptions::PyRuntimeError::new_err(
+                "LMS signer was opened in read-only mode; reopen it to sign",
+            ));
+        }
+        if self.state.remaining() == 0 {
+            return Err(pyo3::exceptions::PyValueError::new_err(
+                "LMS key is exhausted",
+            ));
+        }
+        let q = self.state.next;
+        // Advance in memory first: even if persisting fails, this index is never reused here.
+        self.state.next += 1;
+        let state = &self.state;
+        let path = &self.path;
+        py.allow_threads(|| {
+            state.persist(path)?;
+            let mut out = Vec::with_capacity(signature_len(state.height));
+            out.extend_from_slice(&0u32.to_be_bytes());
+            out.extend_from_slice(&q.to_be_bytes());
+            out.extend_from_slice(&ots_sign(&state.id, q, state.seed.as_ref(), &message));
+            out.extend_from_slice(&state.lms_type.to_be_bytes());
+            let mut node = (1usize << state.height) + q as usize;
+            while node > 1 {
+                out.extend_from_slice(&state.tree[node ^ 1]);
+                node /= 2;
+            }
+            Ok(out)
+        })
+    }
+}
+
+/// Verifies a single-level HSS/LMS signature (RFC 8554, LMOTS_SHA256_N32_W8)
+#[pyfunction]
+pub fn verify_lms(
+    py: Python<'_>,
+    message: Vec<u8>,
+    signature: Vec<u8>,
+    public_key: Vec<u8>,
+) -> bool {
+    py.allow_threads(|| verify(&message, &signature, &public_key))
+}
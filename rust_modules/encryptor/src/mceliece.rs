@@ -0,0 +1,198 @@
+use std::ffi::{c_int, c_void};
+use std::ptr;
+
+use pqcrypto_classicmceliece::mceliece8192128f as mceliece;
+use pqcrypto_traits::kem::{
+    Ciphertext as KemCiphertext, PublicKey as KemPublicKey, SecretKey as KemSecretKey, SharedSecret,
+};
+use pyo3::buffer::PyBuffer;
+use pyo3::exceptions::PyBufferError;
+use pyo3::ffi;
+use pyo3::prelude::*;
+use pyo3::types::PyBytes;
+use zeroize::Zeroizing;
+
+// The reference implementation keeps whole keys in stack arrays (the public key alone is
+// ~1.3 MB), so every operation runs on a dedicated thread with a generous stack.
+const LARGE_STACK_BYTES: usize = 64 * 1024 * 1024;
+
+fn on_large_stack<T: Send>(f: impl FnOnce() -> T + Send) -> PyResult<T> {
+    std::thread::scope(|scope| {
+        std::thread::Builder::new()
+            .stack_size(LARGE_STACK_BYTES)
+            .spawn_scoped(scope, f)
+            .map_err(|e| {
+                pyo3::exceptions::PyRuntimeError::new_err(format!(
+                    "Failed to spawn McEliece worker: {}",
+                    e
+                ))
+            })?
+            .join()
+            .map_err(|_| pyo3::exceptions::PyRuntimeError::new_err("McEliece worker panicked"))
+    })
+}
+
+/// Read-only Rust-owned bytes exposed through the buffer protocol, so megabyte-sized keys can
+/// be wrapped in a `memoryview` or written to disk without an extra copy into `bytes`.
+/// Contents are zeroized when the object is collected.
+#[pyclass(frozen, module = "reliquary_encryptor")]
+pub struct KeyBuffer {
+    data: Zeroizing<Vec<u8>>,
+}
+
+impl KeyBuffer {
+    pub(crate) fn new(data: Vec<u8>) -> Self {
+        KeyBuffer {
+            data: Zeroizing::new(data),
+        }
+    }
+}
+
+#[pymethods]
+impl KeyBuffer {
+    fn __len__(&self) -> usize {
+        self.data.len()
+    }
+
+    /// Copies the contents into a new `bytes` object
+    fn to_bytes<'py>(&self, py: Python<'py>) -> Bound<'py, PyBytes> {
+        PyBytes::new(py, &self.data)
+    }
+
+    unsafe fn __getbuffer__(
+        slf: Bound<'_, Self>,
+        view: *mut ffi::Py_buffer,
+        flags: c_int,
+    ) -> PyResult<()> {
+        if view.is_null() {
+            return Err(PyBufferError::new_err("View is null"));
+        }
+        if (flags & ffi::PyBUF_WRITABLE) == ffi::PyBUF_WRITABLE {
+            return Err(PyBufferError::new_err("KeyBuffer is read-only"));
+        }
+        let data = &slf.get().data;
+
+        // SAFETY: `view` is non-null and provided by the interpreter. The exported pointer
+        // stays valid because the class is frozen (the Vec is never mutated or reallocated)
+        // and `view.obj` holds a strong reference until the buffer is released.
+        unsafe {
+            (*view).buf = data.as_ptr() as *mut c_void;
+            (*view).len = data.len() as isize;
+            (*view).readonly = 1;
+            (*view).itemsize = 1;
+            (*view).format = if (flags & ffi::PyBUF_FORMAT) == ffi::PyBUF_FORMAT {
+                c"B".as_ptr() as *mut _
+            } else {
+                ptr::null_mut()
+            };
+            (*view).ndim = 1;
+            (*view).shape = if (flags & ffi::PyBUF_ND) == ffi::PyBUF_ND {
+                &mut (*view).len
+            } else {
+                ptr::null_mut()
+            };
+            (*view).strides = if (flags & ffi::PyBUF_STRIDES) == ffi::PyBUF_STRIDES {
+                &mut (*view).itemsize
+            } else {
+                ptr::null_mut()
+            };
+            (*view).suboffsets = ptr::null_mut();
+            (*view).internal = ptr::null_mut();
+            (*view).obj = slf.into_ptr();
+        }
+        Ok(())
+    }
+
+    unsafe fn __releasebuffer__(&self, _view: *mut ffi::Py_buffer) {}
+}
+
+/// Returns (public_key_bytes, secret_key_bytes, ciphertext_bytes) for mceliece8192128f.
+/// Public keys are ~1.3 MB - budget storage and transport accordingly
+#[pyfunction]
+pub fn mceliece_key_sizes() -> (usize, usize, usize) {
+    (
+        mceliece::public_key_bytes(),
+        mceliece::secret_key_bytes(),
+        mceliece::ciphertext_bytes(),
+    )
+}
+
+/// Generate a Classic McEliece (mceliece8192128f, NIST level 5) keypair for archival secrets.
+/// Returns (public_key, secret_key) as KeyBuffer objects; the public key is ~1.3 MB
+#[pyfunction]
+pub fn generate_mceliece_keys(py: Python<'_>) -> PyResult<(KeyBuffer, KeyBuffer)> {
+    py.allow_threads(|| {
+        on_large_stack(|| {
+            let (pk, sk) = mceliece::keypair();
+            (
+                KeyBuffer::new(pk.as_bytes().to_vec()),
+                KeyBuffer::new(sk.as_bytes().to_vec()),
+            )
+        })
+    })
+}
+
+/// Classic McEliece encapsulation. Accepts any buffer (bytes, memoryview, KeyBuffer) as the
+/// public key. Returns (shared_secret, ciphertext)
+#[pyfunction]
+pub fn encapsulate_mceliece(
+    py: Python<'_>,
+    pk_buffer: PyBuffer<u8>,
+) -> PyResult<(Vec<u8>, Vec<u8>)> {
+    let pk_bytes = pk_buffer.to_vec(py)?;
+    if pk_bytes.len() != mceliece::public_key_bytes() {
+        return Err(pyo3::exceptions::PyValueError::new_err(format!(
+            "Invalid public key length. Expected {}, got {}",
+            mceliece::public_key_bytes(),
+            pk_bytes.len()
+        )));
+    }
+
+    py.allow_threads(|| {
+        on_large_stack(|| {
+            let pk = mceliece::PublicKey::from_bytes(&pk_bytes).map_err(|e| {
+                pyo3::exceptions::PyValueError::new_err(format!("Invalid public key: {:?}", e))
+            })?;
+            let (ss, ct) = mceliece::encapsulate(&pk);
+            Ok((ss.as_bytes().to_vec(), ct.as_bytes().to_vec()))
+        })?
+    })
+}
+
+/// Classic McEliece decapsulation. Accepts any buffer (bytes, memoryview, KeyBuffer) as the
+/// secret key
+#[pyfunction]
+pub fn decapsulate_mceliece(
+    py: Python<'_>,
+    ct_bytes: Vec<u8>,
+    sk_buffer: PyBuffer<u8>,
+) -> PyResult<Vec<u8>> {
+    if ct_bytes.len() != mceliece::ciphertext_bytes() {
+        return Err(pyo3::exceptions::PyValueError::new_err(format!(
+            "Invalid ciphertext length. Expected {}, got {}",
+            mceliece::ciphertext_bytes(),
+            ct_bytes.len()
+        )));
+    }
+    let sk_bytes = Zeroizing::new(sk_buffer.to_vec(py)?);
+    if sk_bytes.len() != mceliece::secret_key_bytes() {
+        return Err(pyo3::exceptions::PyValueError::new_err(format!(
+            "Invalid secret key length. Expected {}, got {}",
+            mceliece::secret_key_bytes(),
+            sk_bytes.len()
+        )));
+    }
+
+    py.allow_threads(|| {
+        on_large_stack(|| {
+            let ct = mceliece::Ciphertext::from_bytes(&ct_bytes).map_err(|e| {
+                pyo3::exceptions::PyValueError::new_err(format!("Invalid ciphertext: {:?}", e))
+            })?;
+            let sk = mceliece::SecretKey::from_bytes(&sk_bytes).map_err(|e| {
+                pyo3::exceptions::PyValueError::new_err(format!("Invalid secret key: {:?}", e))
+            })?;
+            let ss = mceliece::decapsulate(&ct, &sk);
+            Ok(ss.as_bytes().to_vec())
+        })?
+    })
+}
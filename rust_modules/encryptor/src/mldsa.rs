@@ -0,0 +1,208 @@
+use ml_dsa::{
+    EncodedVerifyingKey, ExpandedSigningKey, ExpandedSigningKeyBytes, MlDsa87, Signature,
+    VerifyingKey, B32,
+};
+use pyo3::prelude::*;
+use rand::rngs::OsRng;
+use rand::RngCore;
+use zeroize::Zeroizing;
+
+use crate::faults;
+use crate::handles::SigningKeyArg;
+
+// ML-DSA-87 (FIPS 204, NIST level 5) on top of RustCrypto's constant-time `ml-dsa`, with the
+// standard encodings: 2592-byte public key, 4896-byte expanded secret key, 4627-byte signature,
+// and the 32-byte seed ξ keys can be regenerated from. Signatures use the pure (non-prehash)
+// variant with an optional context string of up to 255 bytes. Signing is hedged by default, as
+// FIPS 204 recommends; the deterministic variant is available for callers that need
+// reproducible signatures, like Dilithium5 here. Pinned NIST ACVP-Server vectors for the
+// internal functions are in tests/vectors/acvp_ml_kem_ml_dsa.json and run through process_acvp.
+pub(crate) const PUBLIC_KEY_BYTES: usize = 2592;
+pub(crate) const SECRET_KEY_BYTES: usize = 4896;
+pub(crate) const SIGNATURE_BYTES: usize = 4627;
+pub(crate) const SEED_BYTES: usize = 32;
+const MAX_CONTEXT_BYTES: usize = 255;
+
+/// ML-DSA.KeyGen_internal from the seed ξ: (pk, sk).
+fn key_gen(seed: &[u8; SEED_BYTES]) -> (Vec<u8>, Zeroizing<Vec<u8>>) {
+    let xi = Zeroizing::new(B32::from(*seed));
+    let sk = ExpandedSigningKey::<MlDsa87>::from_seed(&xi);
+    // The expanded encoding is the secret key format everything else here stores
+    #[allow(deprecated)]
+    let expanded = Zeroizing::new(sk.to_expanded());
+    (
+        sk.verifying_key().encode().to_vec(),
+        Zeroizing::new(expanded.to_vec()),
+    )
+}
+
+/// skDecode; `sk` must have its standard length.
+fn decode_secret(sk: &[u8]) -> ExpandedSigningKey<MlDsa87> {
+    let expanded = Zeroizing::new(
+        ExpandedSigningKeyBytes::<MlDsa87>::try_from(sk).expect("secret key length checked"),
+    );
+    #[allow(deprecated)]
+    let sk = ExpandedSigningKey::from_expanded(&expanded);
+    sk
+}
+
+/// M' = 0 | len(ctx) | ctx | M for the pure signing variant.
+pub(crate) fn formatted_message(msg: &[u8], context: &[u8]) -> Result<Vec<u8>, String> {
+    if context.len() > MAX_CONTEXT_BYTES {
+        return Err(format!(
+            "ML-DSA context must be at most {} bytes, got {}",
+            MAX_CONTEXT_BYTES,
+            context.len()
+        ));
+    }
+    let mut formatted = Vec::with_capacity(2 + context.len() + msg.len());
+    formatted.push(0);
+    formatted.push(context.len() as u8);
+    formatted.extend_from_slice(context);
+    formatted.extend_from_slice(msg);
+    Ok(formatted)
+}
+
+/// ML-DSA.Sign_internal; `sk` must have its standard length.
+fn sign_internal(sk: &[u8], formatted: &[u8], rnd: &[u8; 32]) -> Vec<u8> {
+    let rnd = Zeroizing::new(B32::from(*rnd));
+    let signature = decode_secret(sk).sign_internal(&[formatted], &rnd);
+    signature.encode().to_vec()
+}
+
+/// ML-DSA.Verify_internal; false for a malformed public key or signature.
+fn verify_internal(pk: &[u8], formatted: &[u8], sig: &[u8]) -> bool {
+    let (Ok(pk), Ok(sig)) = (
+        EncodedVerifyingKey::<MlDsa87>::try_from(pk),
+        Signature::<MlDsa87>::try_from(sig),
+    ) else {
+        return false;
+    };
+    VerifyingKey::decode(&pk).verify_internal(formatted, &sig)
+}
+
+fn check_length(bytes: &[u8], expected: usize, what: &str) -> PyResult<()> {
+    if bytes.len() != expected {
+        return Err(pyo3::exceptions::PyValueError::new_err(format!(
+            "Invalid {} length. Expected {}, got {}",
+            what,
+            expected,
+            bytes.len()
+        )));
+    }
+    Ok(())
+}
+
+/// ML-DSA.Sign_internal over an already formatted message M', as ACVP's internal interface
+/// tests it.
+pub(crate) fn sign_formatted(sk: &[u8], formatted: &[u8], rnd: &[u8; 32]) -> PyResult<Vec<u8>> {
+    check_length(sk, SECRET_KEY_BYTES, "secret key")?;
+    Ok(sign_internal(sk, formatted, rnd))
+}
+
+/// ML-DSA.Verify_internal over an already formatted message M'.
+pub(crate) fn verify_formatted(pk: &[u8], formatted: &[u8], sig: &[u8]) -> PyResult<bool> {
+    check_length(pk, PUBLIC_KEY_BYTES, "public key")?;
+    Ok(sig.len() == SIGNATURE_BYTES && verify_internal(pk, formatted, sig))
+}
+
+pub(crate) fn keypair_from_seed(seed: &[u8]) -> PyResult<(Vec<u8>, Zeroizing<Vec<u8>>)> {
+    let seed: &[u8; SEED_BYTES] = seed.try_into().map_err(|_| {
+        pyo3::exceptions::PyValueError::new_err(format!(
+            "Invalid ML-DSA seed length. Expected {}, got {}",
+            SEED_BYTES,
+            seed.len()
+        ))
+    })?;
+    Ok(key_gen(seed))
+}
+
+/// Public key recomputed from a secret key: ρ and t = A·s1 + s2 are both recoverable.
+pub(crate) fn public_key_from_secret(sk: &[u8]) -> PyResult<Vec<u8>> {
+    check_length(sk, SECRET_KEY_BYTES, "secret key")?;
+    Ok(decode_secret(sk).verifying_key().encode().to_vec())
+}
+
+pub(crate) fn ml_dsa_sign(
+    msg: &[u8],
+    sk_bytes: &[u8],
+    context: &[u8],
+    deterministic: bool,
+) -> PyResult<Vec<u8>> {
+    faults::inject(faults::Operation::Sign, || {
+        check_length(sk_bytes, SECRET_KEY_BYTES, "secret key")?;
+        let formatted =
+            formatted_message(msg, context).map_err(pyo3::exceptions::PyValueError::new_err)?;
+        let mut rnd = Zeroizing::new([0u8; 32]);
+        if !deterministic {
+            OsRng.fill_bytes(rnd.as_mut());
+        }
+        Ok(sign_internal(sk_bytes, &formatted, &rnd))
+    })
+}
+
+pub(crate) fn ml_dsa_verify(
+    msg: &[u8],
+    sig_bytes: &[u8],
+    pk_bytes: &[u8],
+    context: &[u8],
+) -> PyResult<bool> {
+    faults::inject(faults::Operation::Verify, || {
+        check_length(pk_bytes, PUBLIC_KEY_BYTES, "public key")?;
+        if sig_bytes.len() != SIGNATURE_BYTES || context.len() > MAX_CONTEXT_BYTES {
+            return Ok(false);
+        }
+        let formatted =
+            formatted_message(msg, context).map_err(pyo3::exceptions::PyValueError::new_err)?;
+        Ok(verify_internal(pk_bytes, &formatted, sig_bytes))
+    })
+}
+
+/// Generate an ML-DSA-87 keypair. Returns (public_key, secret_key)
+#[pyfunction]
+pub fn generate_ml_dsa_keys(py: Python<'_>) -> PyResult<(Vec<u8>, Vec<u8>)> {
+    py.allow_threads(|| {
+        let mut seed = Zeroizing::new([0u8; SEED_BYTES]);
+        OsRng.fill_bytes(seed.as_mut());
+        let (pk, sk) = key_gen(&seed);
+        Ok((pk, sk.to_vec()))
+    })
+}
+
+/// ML-DSA-87 keypair from a 32-byte seed ξ (the FIPS 204 seed format). Returns
+/// (public_key, secret_key)
+#[pyfunction]
+pub fn ml_dsa_keys_from_seed(py: Python<'_>, seed: Vec<u8>) -> PyResult<(Vec<u8>, Vec<u8>)> {
+    let seed = Zeroizing::new(seed);
+    py.allow_threads(|| {
+        let (pk, sk) = keypair_from_seed(&seed)?;
+        Ok((pk, sk.to_vec()))
+    })
+}
+
+/// ML-DSA-87 signing with an optional context string (at most 255 bytes). Returns a
+/// detached signature; hedged unless `deterministic` is set
+#[pyfunction]
+#[pyo3(signature = (msg, sk_bytes, context = Vec::new(), deterministic = false))]
+pub fn sign_ml_dsa(
+    py: Python<'_>,
+    msg: Vec<u8>,
+    sk_bytes: SigningKeyArg,
+    context: Vec<u8>,
+    deterministic: bool,
+) -> PyResult<Vec<u8>> {
+    py.allow_threads(|| ml_dsa_sign(&msg, &sk_bytes, &context, deterministic))
+}
+
+/// ML-DSA-87 detached signature verification; the context must match the one signed with
+#[pyfunction]
+#[pyo3(signature = (msg, sig_bytes, pk_bytes, context = Vec::new()))]
+pub fn verify_ml_dsa(
+    py: Python<'_>,
+    msg: Vec<u8>,
+    sig_bytes: Vec<u8>,
+    pk_bytes: Vec<u8>,
+    context: Vec<u8>,
+) -> PyResult<bool> {
+    py.allow_threads(|| ml_dsa_verify(&msg, &sig_bytes, &pk_bytes, &context))
+}
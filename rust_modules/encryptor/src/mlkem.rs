@@ -0,0 +1,204 @@
+use ml_kem::ml_kem_1024::{DecapsulationKey, EncapsulationKey};
+#[allow(deprecated)]
+use ml_kem::ExpandedKeyEncoding;
+use ml_kem::{Decapsulate, Key, KeyExport, B32};
+use pyo3::prelude::*;
+use rand::rngs::OsRng;
+use rand::RngCore;
+use zeroize::{Zeroize, Zeroizing};
+
+// ML-KEM-1024 (FIPS 203) on top of RustCrypto's constant-time `ml-kem`. Encodings are the
+// standard ones: 1568-byte encapsulation key, 3168-byte expanded decapsulation key
+// dk_PKE | ek | H(ek) | z, 1568-byte ciphertext, and the 64-byte d | z seed keys can be
+// regenerated from. The decapsulation key has the same layout as a round-3 Kyber secret key,
+// so fingerprinting and public-key extraction work on both. `mlkem1024` mirrors the pqcrypto
+// module API so with_kyber! can dispatch to it like any Kyber parameter set.
+// process_acvp runs the internal functions against the pinned NIST ACVP-Server vectors in
+// tests/vectors/acvp_ml_kem_ml_dsa.json.
+pub(crate) const PUBLIC_KEY_BYTES: usize = 1568;
+pub(crate) const SECRET_KEY_BYTES: usize = 3168;
+pub(crate) const CIPHERTEXT_BYTES: usize = 1568;
+pub(crate) const SHARED_SECRET_BYTES: usize = 32;
+pub(crate) const SEED_BYTES: usize = 64;
+
+/// ML-KEM.KeyGen_internal from the 64-byte seed d | z: (ek, dk).
+pub(crate) fn keygen_from_seed(seed: &[u8; SEED_BYTES]) -> (Vec<u8>, Zeroizing<Vec<u8>>) {
+    let dk = DecapsulationKey::from_seed((*seed).into());
+    let ek = dk.encapsulation_key().to_bytes().to_vec();
+    // The expanded encoding is the secret key format everything else here stores
+    #[allow(deprecated)]
+    let expanded = Zeroizing::new(dk.to_expanded_bytes());
+    (ek, Zeroizing::new(expanded.to_vec()))
+}
+
+/// ML-KEM.Encaps_internal with explicit randomness m: (shared secret, ciphertext). Fails
+/// the FIPS 203 modulus check for an encapsulation key with unreduced coefficients.
+pub(crate) fn encapsulate_internal(ek: &[u8], m: &[u8; 32]) -> Result<([u8; 32], Vec<u8>), String> {
+    let ek = Key::<EncapsulationKey>::try_from(ek).map_err(|_| {
+        format!(
+            "Invalid public key length. Expected {}, got {}",
+            PUBLIC_KEY_BYTES,
+            ek.len()
+        )
+    })?;
+    let ek = EncapsulationKey::new(&ek).map_err(|_| {
+        "Invalid ML-KEM encapsulation key: coefficient not reduced mod 3329".to_string()
+    })?;
+    let m = Zeroizing::new(B32::from(*m));
+    let (ct, shared) = ek.encapsulate_deterministic(&m);
+    Ok((shared.into(), ct.to_vec()))
+}
+
+/// Decodes an expanded decapsulation key after the FIPS 203 checks on it: the embedded ek
+/// must be reduced mod q and the embedded H(ek) must match it.
+fn decapsulation_key(dk: &[u8]) -> Result<DecapsulationKey, String> {
+    let expanded: Zeroizing<ml_kem::ExpandedDecapsulationKey<ml_kem::MlKem1024>> =
+        Zeroizing::new(dk.try_into().map_err(|_| {
+            format!(
+                "Invalid secret key length. Expected {}, got {}",
+                SECRET_KEY_BYTES,
+                dk.len()
+            )
+        })?);
+    #[allow(deprecated)]
+    let dk = DecapsulationKey::from_expanded(&expanded);
+    dk.map_err(|_| "Invalid ML-KEM decapsulation key: H(ek) does not match ek".to_string())
+}
+
+/// ML-KEM.Decaps_internal; `ct` must have its standard length.
+pub(crate) fn decapsulate_internal(dk: &[u8], ct: &[u8]) -> Result<[u8; 32], String> {
+    let ct = ct.try_into().map_err(|_| {
+        format!(
+            "Invalid ciphertext length. Expected {}, got {}",
+            CIPHERTEXT_BYTES,
+            ct.len()
+        )
+    })?;
+    Ok(decapsulation_key(dk)?.decapsulate(&ct).into())
+}
+
+/// FIPS 203 decapsulation key check, as run before every decapsulation.
+pub(crate) fn check_decapsulation_key(dk: &[u8]) -> Result<(), String> {
+    decapsulation_key(dk).map(|_| ())
+}
+
+/// The pqcrypto-style API with_kyber! expects of a parameter-set module.
+pub(crate) mod mlkem1024 {
+    use super::*;
+
+    macro_rules! key_bytes {
+        ($(#[$meta:meta])* $name:ident, $len:expr, $trait:path) => {
+            $(#[$meta])*
+            pub struct $name([u8; $len]);
+
+            impl $trait for $name {
+                fn as_bytes(&self) -> &[u8] {
+                    &self.0
+                }
+
+                fn from_bytes(bytes: &[u8]) -> pqcrypto_traits::Result<Self> {
+                    bytes
+                        .try_into()
+                        .map($name)
+                        .map_err(|_| pqcrypto_traits::Error::BadLength {
+                            name: stringify!($name),
+                            actual: bytes.len(),
+                            expected: $len,
+                        })
+                }
+            }
+        };
+    }
+
+    key_bytes!(
+        #[derive(Clone, Copy)]
+        PublicKey,
+        PUBLIC_KEY_BYTES,
+        pqcrypto_traits::kem::PublicKey
+    );
+    key_bytes!(
+        #[derive(Clone, Copy)]
+        Ciphertext,
+        CIPHERTEXT_BYTES,
+        pqcrypto_traits::kem::Ciphertext
+    );
+    key_bytes!(
+        #[derive(Clone)]
+        SecretKey,
+        SECRET_KEY_BYTES,
+        pqcrypto_traits::kem::SecretKey
+    );
+    key_bytes!(
+        SharedSecret,
+        SHARED_SECRET_BYTES,
+        pqcrypto_traits::kem::SharedSecret
+    );
+
+    // Key material is wiped on drop and never copied implicitly
+    impl Drop for SecretKey {
+        fn drop(&mut self) {
+            self.0.zeroize();
+        }
+    }
+
+    impl Drop for SharedSecret {
+        fn drop(&mut self) {
+            self.0.zeroize();
+        }
+    }
+
+    pub fn public_key_bytes() -> usize {
+        PUBLIC_KEY_BYTES
+    }
+
+    pub fn secret_key_bytes() -> usize {
+        SECRET_KEY_BYTES
+    }
+
+    pub fn ciphertext_bytes() -> usize {
+        CIPHERTEXT_BYTES
+    }
+
+    pub fn keypair() -> (PublicKey, SecretKey) {
+        let mut seed = Zeroizing::new([0u8; SEED_BYTES]);
+        OsRng.fill_bytes(seed.as_mut());
+        let (ek, dk) = keygen_from_seed(&seed);
+        let mut sk = SecretKey([0u8; SECRET_KEY_BYTES]);
+        sk.0.copy_from_slice(&dk);
+        (PublicKey(ek.try_into().expect("ek length")), sk)
+    }
+
+    pub fn encapsulate(pk: &PublicKey) -> (SharedSecret, Ciphertext) {
+        let mut m = Zeroizing::new([0u8; 32]);
+        OsRng.fill_bytes(m.as_mut());
+        let (shared, ct) = encapsulate_internal(&pk.0, &m)
+            .expect("kyber_encapsulate range-checks the encapsulation key");
+        (
+            SharedSecret(shared),
+            Ciphertext(ct.try_into().expect("ciphertext length")),
+        )
+    }
+
+    pub fn decapsulate(ct: &Ciphertext, sk: &SecretKey) -> SharedSecret {
+        SharedSecret(
+            decapsulate_internal(&sk.0, &ct.0)
+                .expect("kyber_decapsulate checks the decapsulation key"),
+        )
+    }
+}
+
+/// ML-KEM-1024 key pair from a 64-byte seed d | z (the FIPS 203 seed format). Returns
+/// (public_key, secret_key) in the standard encodings
+#[pyfunction]
+pub fn ml_kem_keys_from_seed(seed: Vec<u8>) -> PyResult<(Vec<u8>, Vec<u8>)> {
+    let seed = Zeroizing::new(seed);
+    let seed: &[u8; SEED_BYTES] = seed.as_slice().try_into().map_err(|_| {
+        pyo3::exceptions::PyValueError::new_err(format!(
+            "Invalid ML-KEM seed length. Expected {}, got {}",
+            SEED_BYTES,
+            seed.len()
+        ))
+    })?;
+    let (ek, dk) = keygen_from_seed(seed);
+    Ok((ek, dk.to_vec()))
+}
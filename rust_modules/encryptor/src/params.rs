@@ -0,0 +1,172 @@
+use pyo3::prelude::*;
+
+/// Kyber parameter sets. Kyber1024 (NIST level 5) stays the default for vault keys;
+/// the smaller sets are meant for latency-sensitive session establishment. MlKem1024 is the
+/// final FIPS 203 standard at the same level, for deployments that require it.
+#[pyclass(eq, eq_int, module = "reliquary_encryptor")]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum KyberParams {
+    Kyber512,
+    Kyber768,
+    #[default]
+    Kyber1024,
+    MlKem1024,
+}
+
+/// Falcon parameter sets. Falcon1024 (NIST level 5) stays the default.
+#[pyclass(eq, eq_int, module = "reliquary_encryptor")]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum FalconParams {
+    Falcon512,
+    #[default]
+    Falcon1024,
+}
+
+/// Runs `$body` with `$m` bound to the pqcrypto module for the given Kyber parameter set.
+macro_rules! with_kyber {
+    ($params:expr, $m:ident => $body:expr) => {
+        match $params {
+            $crate::params::KyberParams::Kyber512 => {
+                use pqcrypto_kyber::kyber512 as $m;
+                $body
+            }
+            $crate::params::KyberParams::Kyber768 => {
+                use pqcrypto_kyber::kyber768 as $m;
+                $body
+            }
+            $crate::params::KyberParams::Kyber1024 => {
+                use pqcrypto_kyber::kyber1024 as $m;
+                $body
+            }
+            $crate::params::KyberParams::MlKem1024 => {
+                use $crate::mlkem::mlkem1024 as $m;
+                $body
+            }
+        }
+    };
+}
+
+/// Runs `$body` with `$m` bound to the pqcrypto module for the given Falcon parameter set.
+macro_rules! with_falcon {
+    ($params:expr, $m:ident => $body:expr) => {
+        match $params {
+            $crate::params::FalconParams::Falcon512 => {
+                use pqcrypto_falcon::falcon512 as $m;
+                $body
+            }
+            $crate::params::FalconParams::Falcon1024 => {
+                use pqcrypto_falcon::falcon1024 as $m;
+                $body
+            }
+        }
+    };
+}
+
+pub(crate) use with_falcon;
+pub(crate) use with_kyber;
+
+impl KyberParams {
+    /// Identifier written into envelope headers
+    pub(crate) fn wire_id(&self) -> u8 {
+        match self {
+            KyberParams::Kyber512 => 1,
+            KyberParams::Kyber768 => 2,
+            KyberParams::Kyber1024 => 3,
+            KyberParams::MlKem1024 => 4,
+        }
+    }
+
+    pub(crate) fn from_wire_id(id: u8) -> Option<Self> {
+        match id {
+            1 => Some(KyberParams::Kyber512),
+            2 => Some(KyberParams::Kyber768),
+            3 => Some(KyberParams::Kyber1024),
+            4 => Some(KyberParams::MlKem1024),
+            _ => None,
+        }
+    }
+}
+
+#[pymethods]
+impl KyberParams {
+    /// Module rank k of the parameter set
+    #[getter]
+    pub fn rank(&self) -> usize {
+        match self {
+            KyberParams::Kyber512 => 2,
+            KyberParams::Kyber768 => 3,
+            KyberParams::Kyber1024 | KyberParams::MlKem1024 => 4,
+        }
+    }
+
+    #[getter]
+    pub fn public_key_bytes(&self) -> usize {
+        with_kyber!(self, kyber => kyber::public_key_bytes())
+    }
+
+    #[getter]
+    pub fn secret_key_bytes(&self) -> usize {
+        with_kyber!(self, kyber => kyber::secret_key_bytes())
+    }
+
+    #[getter]
+    pub fn ciphertext_bytes(&self) -> usize {
+        with_kyber!(self, kyber => kyber::ciphertext_bytes())
+    }
+
+    #[getter]
+    pub fn name(&self) -> &'static str {
+        match self {
+            KyberParams::Kyber512 => "kyber512",
+            KyberParams::Kyber768 => "kyber768",
+            KyberParams::Kyber1024 => "kyber1024",
+            KyberParams::MlKem1024 => "ml-kem-1024",
+        }
+    }
+}
+
+impl FalconParams {
+    pub(crate) fn from_logn(logn: u8) -> Option<Self> {
+        match logn {
+            9 => Some(FalconParams::Falcon512),
+            10 => Some(FalconParams::Falcon1024),
+            _ => None,
+        }
+    }
+}
+
+#[pymethods]
+impl FalconParams {
+    /// Base-2 logarithm of the ring degree, also the public key header byte
+    #[getter]
+    pub fn logn(&self) -> u8 {
+        match self {
+            FalconParams::Falcon512 => 9,
+            FalconParams::Falcon1024 => 10,
+        }
+    }
+
+    #[getter]
+    pub fn public_key_bytes(&self) -> usize {
+        with_falcon!(self, falcon => falcon::public_key_bytes())
+    }
+
+    #[getter]
+    pub fn secret_key_bytes(&self) -> usize {
+        with_falcon!(self, falcon => falcon::secret_key_bytes())
+    }
+
+    /// Upper bound on the detached signature size
+    #[getter]
+    pub fn signature_bytes(&self) -> usize {
+        with_falcon!(self, falcon => falcon::signature_bytes())
+    }
+
+    #[getter]
+    pub fn name(&self) -> &'static str {
+        match self {
+            FalconParams::Falcon512 => "falcon512",
+            FalconParams::Falcon1024 => "falcon1024",
+        }
+    }
+}
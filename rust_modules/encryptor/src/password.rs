@@ -0,0 +1,158 @@
+use argon2::{Algorithm, Argon2, Version};
+use password_hash::{PasswordHash, PasswordHasher, PasswordVerifier, SaltString};
+use pbkdf2::Pbkdf2;
+use pyo3::prelude::*;
+use rand::rngs::OsRng;
+use scrypt::Scrypt;
+use zeroize::Zeroizing;
+
+// Password hashing with PHC string output ("$argon2id$v=19$m=...,t=...,p=...$salt$hash"), so
+// the algorithm and its cost parameters travel with every stored hash and `verify_password`
+// needs nothing else. Costs are capped on both hashing and verification so a tampered stored
+// hash can't make a login allocate gigabytes or spin for minutes.
+pub(crate) const MAX_ARGON2_MEMORY_KIB: u32 = 4 * 1024 * 1024;
+pub(crate) const MAX_ARGON2_ITERATIONS: u32 = 64;
+pub(crate) const MAX_PARALLELISM: u32 = 16;
+const MAX_SCRYPT_LOG_N: u8 = 24;
+const MAX_PBKDF2_ROUNDS: u32 = 10_000_000;
+
+pub(crate) fn check(name: &str, value: u32, min: u32, max: u32) -> Result<(), String> {
+    if value < min || value > max {
+        return Err(format!(
+            "{} must be between {} and {}, got {}",
+            name, min, max, value
+        ));
+    }
+    Ok(())
+}
+
+/// Rejects stored hashes whose cost parameters exceed what we would ever issue.
+fn check_stored_costs(hash: &PasswordHash<'_>) -> Result<(), String> {
+    let param = |name: &str| hash.params.get_decimal(name).unwrap_or(0);
+    match hash.algorithm.as_str() {
+        "argon2id" => {
+            check("Argon2 memory_kib", param("m"), 8, MAX_ARGON2_MEMORY_KIB)?;
+            check("Argon2 iterations", param("t"), 1, MAX_ARGON2_ITERATIONS)?;
+            check("Argon2 parallelism", param("p"), 1, MAX_PARALLELISM)
+        }
+        "scrypt" => {
+            check("scrypt log_n", param("ln"), 1, MAX_SCRYPT_LOG_N as u32)?;
+            check("scrypt r", param("r"), 1, 32)?;
+            check("scrypt p", param("p"), 1, MAX_PARALLELISM)
+        }
+        "pbkdf2-sha256" | "pbkdf2-sha512" => {
+            check("PBKDF2 rounds", param("i"), 1, MAX_PBKDF2_ROUNDS)
+        }
+        other => Err(format!("Unsupported password hash algorithm '{}'", other)),
+    }
+}
+
+fn verify(password: &[u8], encoded: &str) -> Result<bool, String> {
+    let hash = PasswordHash::new(encoded).map_err(|e| format!("Invalid password hash: {}", e))?;
+    check_stored_costs(&hash)?;
+    let result = match hash.algorithm.as_str() {
+        "argon2id" => Argon2::default().verify_password(password, &hash),
+        "scrypt" => Scrypt.verify_password(password, &hash),
+        _ => Pbkdf2.verify_password(password, &hash),
+    };
+    match result {
+        Ok(()) => Ok(true),
+        Err(password_hash::Error::Password) => Ok(false),
+        Err(e) => Err(format!("Invalid password hash: {}", e)),
+    }
+}
+
+/// Hashes a password with Argon2id. Defaults follow the OWASP minimum (19 MiB, 2 passes,
+/// 1 lane). Returns a PHC string
+#[pyfunction]
+#[pyo3(signature = (password, memory_kib = 19456, iterations = 2, parallelism = 1))]
+pub fn hash_password_argon2id(
+    py: Python<'_>,
+    password: &str,
+    memory_kib: u32,
+    iterations: u32,
+    parallelism: u32,
+) -> PyResult<String> {
+    check("parallelism", parallelism, 1, MAX_PARALLELISM)
+        .and(check("iterations", iterations, 1, MAX_ARGON2_ITERATIONS))
+        .and(check(
+            "memory_kib",
+            memory_kib,
+            8 * parallelism.min(MAX_PARALLELISM),
+            MAX_ARGON2_MEMORY_KIB,
+        ))
+        .map_err(pyo3::exceptions::PyValueError::new_err)?;
+    let params = argon2::Params::new(memory_kib, iterations, parallelism, None)
+        .map_err(|e| pyo3::exceptions::PyValueError::new_err(e.to_string()))?;
+    let password = Zeroizing::new(password.as_bytes().to_vec());
+    py.allow_threads(|| {
+        let salt = SaltString::generate(&mut OsRng);
+        Argon2::new(Algorithm::Argon2id, Version::V0x13, params)
+            .hash_password(&password, &salt)
+            .map(|hash| hash.to_string())
+            .map_err(|e| pyo3::exceptions::PyValueError::new_err(e.to_string()))
+    })
+}
+
+/// Hashes a password with scrypt (N = 2^log_n). Defaults are N = 2^17, r = 8, p = 1 (128 MiB).
+/// Returns a PHC string
+#[pyfunction]
+#[pyo3(signature = (password, log_n = 17, r = 8, p = 1))]
+pub fn hash_password_scrypt(
+    py: Python<'_>,
+    password: &str,
+    log_n: u8,
+    r: u32,
+    p: u32,
+) -> PyResult<String> {
+    check("log_n", log_n as u32, 1, MAX_SCRYPT_LOG_N as u32)
+        .and(check("r", r, 1, 32))
+        .and(check("p", p, 1, MAX_PARALLELISM))
+        .map_err(pyo3::exceptions::PyValueError::new_err)?;
+    let params = scrypt::Params::new(log_n, r, p, scrypt::Params::RECOMMENDED_LEN)
+        .map_err(|e| pyo3::exceptions::PyValueError::new_err(e.to_string()))?;
+    let password = Zeroizing::new(password.as_bytes().to_vec());
+    py.allow_threads(|| {
+        let salt = SaltString::generate(&mut OsRng);
+        Scrypt
+            .hash_password_customized(&password, None, None, params, &salt)
+            .map(|hash| hash.to_string())
+            .map_err(|e| pyo3::exceptions::PyValueError::new_err(e.to_string()))
+    })
+}
+
+/// Hashes a password with PBKDF2-HMAC-SHA256, for deployments that require a FIPS-approved
+/// KDF. Default is 600,000 rounds. Returns a PHC string
+#[pyfunction]
+#[pyo3(signature = (password, rounds = 600_000))]
+pub fn hash_password_pbkdf2(py: Python<'_>, password: &str, rounds: u32) -> PyResult<String> {
+    check("rounds", rounds, 1000, MAX_PBKDF2_ROUNDS)
+        .map_err(pyo3::exceptions::PyValueError::new_err)?;
+    let params = pbkdf2::Params {
+        rounds,
+        output_length: 32,
+    };
+    let password = Zeroizing::new(password.as_bytes().to_vec());
+    py.allow_threads(|| {
+        let salt = SaltString::generate(&mut OsRng);
+        Pbkdf2
+            .hash_password_customized(
+                &password,
+                Some(pbkdf2::Algorithm::Pbkdf2Sha256.ident()),
+                None,
+                params,
+                &salt,
+            )
+            .map(|hash| hash.to_string())
+            .map_err(|e| pyo3::exceptions::PyValueError::new_err(e.to_string()))
+    })
+}
+
+/// Checks a password against a PHC string from any of the `hash_password_*` functions.
+/// Returns False on mismatch; raises ValueError if the hash is malformed, uses an unsupported
+/// algorithm or exceeds the cost limits
+#[pyfunction]
+pub fn verify_password(py: Python<'_>, password: &str, encoded: &str) -> PyResult<bool> {
+    let password = Zeroizing::new(password.as_bytes().to_vec());
+    py.allow_threads(|| verify(&password, encoded).map_err(pyo3::exceptions::PyValueError::new_err))
+}
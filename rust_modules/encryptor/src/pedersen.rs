@@ -0,0 +1,268 @@
+use std::sync::OnceLock;
+
+use bulletproofs::PedersenGens;
+use curve25519_dalek::ristretto::{CompressedRistretto, RistrettoPoint};
+use curve25519_dalek::scalar::Scalar;
+use pyo3::prelude::*;
+use rand::RngCore;
+use sha2::{Digest, Sha512};
+use zeroize::Zeroizing;
+
+use crate::classical::fixed;
+use crate::range;
+use crate::secret::LockedBytes;
+use crate::vss::random_scalar;
+
+// Pedersen commitments over Ristretto255, shared by the range proofs and anything else that
+// commits to trust scores or balances: C = v*B + r*B_blinding with the dalek generators (B
+// the Ristretto basepoint, B_blinding hashed from it), so a commitment made by one service
+// opens and proves the same way in every other. Commitments are additively homomorphic.
+//
+// Opening proof (proof of knowledge of v and r, revealing neither):
+//   magic "RQPO" | version u8 | A (32) | s_v (32) | s_r (32)
+// with A = k_v*B + k_r*B_blinding, c = H(context, C, A), s_v = k_v + c*v, s_r = k_r + c*r.
+//
+// A `PedersenKey` keeps blinding factors out of Python: each one is derived from the key and
+// a label ("trust/agent-7/2025-06"), so any service holding the key recomputes the same
+// commitment without storing or passing blindings around.
+const PROOF_MAGIC: &[u8; 4] = b"RQPO";
+const FORMAT_VERSION: u8 = 1;
+const PROOF_BYTES: usize = 5 + 3 * 32;
+const KEY_BYTES: usize = 32;
+const CHALLENGE_LABEL: &[u8] = b"reliquary/pedersen/v1/opening";
+const BLINDING_LABEL: &[u8] = b"reliquary/pedersen/v1/blinding";
+
+pub(crate) fn generators() -> &'static PedersenGens {
+    static GENS: OnceLock<PedersenGens> = OnceLock::new();
+    GENS.get_or_init(PedersenGens::default)
+}
+
+pub(crate) fn commit(value: u64, blinding: Scalar) -> RistrettoPoint {
+    generators().commit(Scalar::from(value), blinding)
+}
+
+pub(crate) fn decode_blinding(blinding: &[u8]) -> PyResult<Scalar> {
+    Option::from(Scalar::from_canonical_bytes(fixed(blinding, "blinding")?)).ok_or_else(|| {
+        pyo3::exceptions::PyValueError::new_err("Blinding is not a canonical scalar")
+    })
+}
+
+pub(crate) fn decode_commitment(commitment: &[u8]) -> PyResult<RistrettoPoint> {
+    CompressedRistretto(fixed(commitment, "commitment")?)
+        .decompress()
+        .ok_or_else(|| {
+            pyo3::exceptions::PyValueError::new_err("Commitment is not a Ristretto point")
+        })
+}
+
+fn challenge(context: &[u8], commitment: &RistrettoPoint, nonce: &RistrettoPoint) -> Scalar {
+    let mut hasher = Sha512::new().chain_update(CHALLENGE_LABEL);
+    hasher.update((context.len() as u64).to_be_bytes());
+    hasher.update(context);
+    hasher.update(commitment.compress().as_bytes());
+    hasher.update(nonce.compress().as_bytes());
+    Scalar::from_hash(hasher)
+}
+
+fn prove_opening(value: u64, blinding: Scalar, context: &[u8]) -> Vec<u8> {
+    let gens = generators();
+    let (k_v, k_r) = (random_scalar(), random_scalar());
+    let nonce = gens.commit(k_v, k_r);
+    let c = challenge(context, &commit(value, blinding), &nonce);
+    let mut out = PROOF_MAGIC.to_vec();
+    out.push(FORMAT_VERSION);
+    out.extend_from_slice(nonce.compress().as_bytes());
+    out.extend_from_slice((k_v + c * Scalar::from(value)).as_bytes());
+    out.extend_from_slice((k_r + c * blinding).as_bytes());
+    out
+}
+
+fn verify_opening_proof(proof: &[u8], commitment: &RistrettoPoint, context: &[u8]) -> bool {
+    if proof.len() != PROOF_BYTES || &proof[..4] != PROOF_MAGIC || proof[4] != FORMAT_VERSION {
+        return false;
+    }
+    let Some(nonce) = CompressedRistretto(fixed_32(&proof[5..37])).decompress() else {
+        return false;
+    };
+    let scalars = [&proof[37..69], &proof[69..]]
+        .map(|bytes| Option::<Scalar>::from(Scalar::from_canonical_bytes(fixed_32(bytes))));
+    let [Some(s_v), Some(s_r)] = scalars else {
+        return false;
+    };
+    let c = challenge(context, commitment, &nonce);
+    generators().commit(s_v, s_r) == nonce + c * commitment
+}
+
+fn fixed_32(bytes: &[u8]) -> [u8; 32] {
+    bytes.try_into().expect("slice of 32 bytes")
+}
+
+/// Pedersen commitment to `value`. Returns (commitment, blinding); the blinding (32-byte
+/// scalar) is random unless given, and opens the commitment together with the value
+#[pyfunction]
+#[pyo3(signature = (value, blinding = None))]
+pub fn pedersen_commit(value: u64, blinding: Option<Vec<u8>>) -> PyResult<(Vec<u8>, Vec<u8>)> {
+    let blinding = match blinding {
+        Some(bytes) => decode_blinding(&bytes)?,
+        None => random_scalar(),
+    };
+    Ok((
+        commit(value, blinding).compress().to_bytes().to_vec(),
+        blinding.to_bytes().to_vec(),
+    ))
+}
+
+/// Checks a disclosed opening: whether `commitment` commits to `value` with `blinding`
+#[pyfunction]
+pub fn pedersen_verify_opening(
+    commitment: Vec<u8>,
+    value: u64,
+    blinding: Vec<u8>,
+) -> PyResult<bool> {
+    let commitment = decode_commitment(&commitment)?;
+    Ok(commit(value, decode_blinding(&blinding)?) == commitment)
+}
+
+/// Proves knowledge of the value and blinding behind a commitment without revealing either,
+/// bound to `context`
+#[pyfunction]
+#[pyo3(signature = (value, blinding, context = Vec::new()))]
+pub fn pedersen_prove_opening(
+    value: u64,
+    blinding: Vec<u8>,
+    context: Vec<u8>,
+) -> PyResult<Vec<u8>> {
+    Ok(prove_opening(value, decode_blinding(&blinding)?, &context))
+}
+
+/// Verifies an opening proof for `commitment` and the context it was made with. Raises
+/// ValueError for a malformed commitment; a bad proof is False
+#[pyfunction]
+#[pyo3(signature = (proof, commitment, context = Vec::new()))]
+pub fn pedersen_verify_proof(
+    proof: Vec<u8>,
+    commitment: Vec<u8>,
+    context: Vec<u8>,
+) -> PyResult<bool> {
+    let commitment = decode_commitment(&commitment)?;
+    Ok(verify_opening_proof(&proof, &commitment, &context))
+}
+
+/// Sum of commitments, which commits to the sum of the values under the sum of the blindings
+#[pyfunction]
+pub fn pedersen_add(commitments: Vec<Vec<u8>>) -> PyResult<Vec<u8>> {
+    let mut sum = RistrettoPoint::default();
+    for commitment in &commitments {
+        sum += decode_commitment(commitment)?;
+    }
+    Ok(sum.compress().to_bytes().to_vec())
+}
+
+/// Blinding-factor key held in locked Rust memory. Blindings are derived per label, so
+/// services sharing the key agree on every commitment, and never reach Python unless a
+/// commitment is explicitly opened. Load the same 32-byte key (e.g. from the KMS) everywhere
+/// commitments must match
+#[pyclass(module = "reliquary_encryptor")]
+pub struct PedersenKey {
+    key: Option<LockedBytes>,
+}
+
+impl PedersenKey {
+    fn blinding(&self, label: &str) -> PyResult<Scalar> {
+        let key = self.key.as_ref().ok_or_else(|| {
+            pyo3::exceptions::PyValueError::new_err("Pedersen key has been destroyed")
+        })?;
+        let mut hasher = Sha512::new().chain_update(BLINDING_LABEL);
+        hasher.update(&key[..]);
+        hasher.update(label.as_bytes());
+        Ok(Scalar::from_hash(hasher))
+    }
+}
+
+#[pymethods]
+impl PedersenKey {
+    #[staticmethod]
+    fn generate() -> Self {
+        let mut key = Zeroizing::new([0u8; KEY_BYTES]);
+        rand::thread_rng().fill_bytes(key.as_mut());
+        PedersenKey {
+            key: Some(LockedBytes::new(key.as_ref())),
+        }
+    }
+
+    /// Moves an existing 32-byte key into Rust memory. Drop the caller's copy afterwards;
+    /// Python can't wipe it
+    #[staticmethod]
+    fn from_bytes(key: Vec<u8>) -> PyResult<Self> {
+        let key = Zeroizing::new(key);
+        if key.len() != KEY_BYTES {
+            return Err(pyo3::exceptions::PyValueError::new_err(format!(
+                "Pedersen key must be {} bytes",
+                KEY_BYTES
+            )));
+        }
+        Ok(PedersenKey {
+            key: Some(LockedBytes::new(&key)),
+        })
+    }
+
+    /// Commitment to `value` under the blinding for `label`
+    fn commit(&self, value: u64, label: &str) -> PyResult<Vec<u8>> {
+        Ok(commit(value, self.blinding(label)?)
+            .compress()
+            .to_bytes()
+            .to_vec())
+    }
+
+    /// Opens the commitment for `label`: returns its blinding, for whoever should learn the
+    /// value. Every other commitment stays hidden
+    fn open(&self, label: &str) -> PyResult<Vec<u8>> {
+        Ok(self.blinding(label)?.to_bytes().to_vec())
+    }
+
+    /// Opening proof for the commitment to `value` under `label`, as `pedersen_prove_opening`
+    #[pyo3(signature = (value, label, context = Vec::new()))]
+    fn prove_opening(&self, value: u64, label: &str, context: Vec<u8>) -> PyResult<Vec<u8>> {
+        Ok(prove_opening(value, self.blinding(label)?, &context))
+    }
+
+    /// Range proof for the commitment to `value` under `label`, as `range_prove`
+    #[pyo3(signature = (value, label, lower = None, upper = None, bits = 32, context = Vec::new()))]
+    #[allow(clippy::too_many_arguments)]
+    fn prove_range(
+        &self,
+        py: Python<'_>,
+        value: u64,
+        label: &str,
+        lower: Option<u64>,
+        upper: Option<u64>,
+        bits: usize,
+        context: Vec<u8>,
+    ) -> PyResult<Vec<u8>> {
+        let blinding = self.blinding(label)?;
+        range::prove(py, value, blinding, lower, upper, bits, &context)
+    }
+
+    /// Whether the key bytes are locked into RAM
+    #[getter]
+    fn locked(&self) -> bool {
+        self.key.as_ref().is_some_and(LockedBytes::locked)
+    }
+
+    #[getter]
+    fn destroyed(&self) -> bool {
+        self.key.is_none()
+    }
+
+    /// Wipes the key now instead of waiting for garbage collection. Later use raises ValueError
+    fn destroy(&mut self) {
+        self.key = None;
+    }
+
+    fn __repr__(&self) -> &'static str {
+        match self.key {
+            Some(_) => "PedersenKey()",
+            None => "PedersenKey(destroyed)",
+        }
+    }
+}
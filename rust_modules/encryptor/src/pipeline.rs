@@ -0,0 +1,463 @@
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::Arc;
+
+use pyo3::prelude::*;
+use sha2::{Digest, Sha256};
+use sha3::Sha3_256;
+
+use crate::batch::parallel_map;
+use crate::schemes::SignatureScheme;
+
+// Bulk verification of audit exports and similar. Checks are queued with the add_* methods,
+// each one getting the next item index, and `run` verifies them all on every core with the GIL
+// released. Every failing item is reported with its index, check label and reason rather than
+// stopping at the first; `continue_on_error=False` or `max_failures` stop the run early, in
+// which case items not yet reached are counted as unchecked. Supported checks:
+//   signature     detached signature over a message (any SignatureScheme)
+//   chain         hash chain link: record i carries H(record i-1), or the genesis digest
+//   merkle_proof  inclusion proof in the core.merkle_logging MerkleTree layout (SHA-256 leaves,
+//                 odd levels duplicate their last node, so a duplicated level has no sibling)
+const HASH_BYTES: usize = 32;
+
+enum Check {
+    Signature {
+        scheme: SignatureScheme,
+        public_key: Arc<Vec<u8>>,
+        message: Vec<u8>,
+        signature: Vec<u8>,
+    },
+    ChainLink {
+        chain: Arc<Chain>,
+        position: usize,
+    },
+    MerkleProof {
+        root: Arc<Vec<u8>>,
+        tree_size: usize,
+        index: usize,
+        leaf: Vec<u8>,
+        proof: Vec<Vec<u8>>,
+    },
+}
+
+struct Chain {
+    records: Vec<Vec<u8>>,
+    links: Vec<Vec<u8>>,
+    genesis: Vec<u8>,
+    sha3: bool,
+}
+
+impl Chain {
+    fn digest(&self, record: &[u8]) -> Vec<u8> {
+        if self.sha3 {
+            Sha3_256::digest(record).to_vec()
+        } else {
+            Sha256::digest(record).to_vec()
+        }
+    }
+}
+
+struct Item {
+    label: Arc<str>,
+    check: Check,
+}
+
+fn hash_pair(left: &[u8], right: &[u8]) -> Vec<u8> {
+    Sha256::new()
+        .chain_update(left)
+        .chain_update(right)
+        .finalize()
+        .to_vec()
+}
+
+fn check_inclusion(
+    leaf: &[u8],
+    index: usize,
+    tree_size: usize,
+    proof: &[Vec<u8>],
+    root: &[u8],
+) -> Result<(), String> {
+    if index >= tree_size {
+        return Err(format!(
+            "Leaf index {} is outside a tree of {} leaves",
+            index, tree_size
+        ));
+    }
+    let mut current = Sha256::digest(leaf).to_vec();
+    let (mut index, mut width, mut used) = (index, tree_size, 0);
+    while width > 1 {
+        if index ^ 1 < width {
+            let sibling = proof.get(used).ok_or("Proof is too short")?;
+            if sibling.len() != HASH_BYTES {
+                return Err(format!("Proof hash {} is not {} bytes", used, HASH_BYTES));
+            }
+            current = if index % 2 == 0 {
+                hash_pair(&current, sibling)
+            } else {
+                hash_pair(sibling, &current)
+            };
+            used += 1;
+        } else {
+            current = hash_pair(&current, &current);
+        }
+        index /= 2;
+        width = width.div_ceil(2);
+    }
+    if used != proof.len() {
+        return Err(format!("Proof has {} unused hashes", proof.len() - used));
+    }
+    if current != root {
+        return Err("Computed root does not match".to_string());
+    }
+    Ok(())
+}
+
+impl Check {
+    fn kind(&self) -> &'static str {
+        match self {
+            Check::Signature { .. } => "signature",
+            Check::ChainLink { .. } => "chain",
+            Check::MerkleProof { .. } => "merkle_proof",
+        }
+    }
+
+    /// None if the item verifies, otherwise the reason it doesn't.
+    fn failure(&self) -> Option<String> {
+        match self {
+            Check::Signature {
+                scheme,
+                public_key,
+                message,
+                signature,
+            } => match scheme.verify(message, signature, public_key) {
+                Ok(true) => None,
+                Ok(false) => Some(format!("{} signature does not verify", scheme.name())),
+                Err(_) => Some(format!("{} public key is malformed", scheme.name())),
+            },
+            Check::ChainLink { chain, position } => {
+                let expected = match position {
+                    0 => chain.genesis.clone(),
+                    i => chain.digest(&chain.records[i - 1]),
+                };
+                (chain.links[*position] != expected).then(|| match position {
+                    0 => "Record does not link to the genesis digest".to_string(),
+                    i => format!("Record does not link to record {}", i - 1),
+                })
+            }
+            Check::MerkleProof {
+                root,
+                tree_size,
+                index,
+                leaf,
+                proof,
+            } => check_inclusion(leaf, *index, *tree_size, proof, root).err(),
+        }
+    }
+}
+
+enum Outcome {
+    Passed,
+    Failed(String),
+    Skipped,
+}
+
+/// Result of VerificationPipeline.run. `failures` lists (index, check, reason) in index order,
+/// where check is the label given when the item was added
+#[pyclass(frozen, module = "reliquary_encryptor")]
+pub struct VerificationReport {
+    #[pyo3(get)]
+    total: usize,
+    /// Items actually verified; less than total when the run stopped early
+    #[pyo3(get)]
+    checked: usize,
+    #[pyo3(get)]
+    passed: usize,
+    #[pyo3(get)]
+    failures: Vec<(usize, String, String)>,
+    /// True when the run stopped early because of continue_on_error or max_failures
+    #[pyo3(get)]
+    aborted: bool,
+}
+
+#[pymethods]
+impl VerificationReport {
+    /// True when every item was checked and passed
+    #[getter]
+    fn ok(&self) -> bool {
+        self.failures.is_empty() && self.checked == self.total
+    }
+
+    /// Failing item indices, in order
+    fn failed_indices(&self) -> Vec<usize> {
+        self.failures.iter().map(|(index, _, _)| *index).collect()
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "VerificationReport(total={}, checked={}, passed={}, failed={}, aborted={})",
+            self.total,
+            self.checked,
+            self.passed,
+            self.failures.len(),
+            if self.aborted { "True" } else { "False" }
+        )
+    }
+}
+
+/// Queue of independent checks verified in bulk by `run`. Every add_* method returns the index
+/// of the first item it added; items are numbered in the order they were added
+#[pyclass(module = "reliquary_encryptor")]
+#[derive(Default)]
+pub struct VerificationPipeline {
+    items: Vec<Item>,
+}
+
+impl VerificationPipeline {
+    fn push(&mut self, label: &Arc<str>, check: Check) {
+        self.items.push(Item {
+            label: label.clone(),
+            check,
+        });
+    }
+}
+
+fn label_or(label: Option<&str>, check: &str) -> Arc<str> {
+    Arc::from(label.unwrap_or(check))
+}
+
+#[pymethods]
+impl VerificationPipeline {
+    #[new]
+    fn new() -> Self {
+        Self::default()
+    }
+
+    /// Queues one detached signature check
+    #[pyo3(signature = (message, signature, public_key, scheme = "falcon1024", label = None))]
+    fn add_signature(
+        &mut self,
+        message: Vec<u8>,
+        signature: Vec<u8>,
+        public_key: Vec<u8>,
+        scheme: &str,
+        label: Option<&str>,
+    ) -> PyResult<usize> {
+        self.add_signatures(vec![message], vec![signature], public_key, scheme, label)
+    }
+
+    /// Queues one signature check per (message, signature), all under the same public key
+    #[pyo3(signature = (messages, signatures, public_key, scheme = "falcon1024", label = None))]
+    fn add_signatures(
+        &mut self,
+        messages: Vec<Vec<u8>>,
+        signatures: Vec<Vec<u8>>,
+        public_key: Vec<u8>,
+        scheme: &str,
+        label: Option<&str>,
+    ) -> PyResult<usize> {
+        let scheme = SignatureScheme::parse(scheme)?;
+        if messages.len() != signatures.len() {
+            return Err(pyo3::exceptions::PyValueError::new_err(format!(
+                "Got {} messages but {} signatures",
+                messages.len(),
+                signatures.len()
+            )));
+        }
+        let start = self.items.len();
+        let label = label_or(label, "signature");
+        let public_key = Arc::new(public_key);
+        for (message, signature) in messages.into_iter().zip(signatures) {
+            self.push(
+                &label,
+                Check::Signature {
+                    scheme,
+                    public_key: public_key.clone(),
+                    message,
+                    signature,
+                },
+            );
+        }
+        Ok(start)
+    }
+
+    /// Queues one link check per record: links[i] must be the digest of records[i-1], and
+    /// links[0] must equal `genesis` (32 zero bytes by default). algorithm is "sha256" or
+    /// "sha3-256"
+    #[pyo3(signature = (records, links, genesis = None, algorithm = "sha256", label = None))]
+    fn add_hash_chain(
+        &mut self,
+        records: Vec<Vec<u8>>,
+        links: Vec<Vec<u8>>,
+        genesis: Option<Vec<u8>>,
+        algorithm: &str,
+        label: Option<&str>,
+    ) -> PyResult<usize> {
+        let sha3 = match algorithm {
+            "sha256" => false,
+            "sha3-256" => true,
+            _ => {
+                return Err(pyo3::exceptions::PyValueError::new_err(format!(
+                    "Unknown chain hash '{}' (use sha256 or sha3-256)",
+                    algorithm
+                )))
+            }
+        };
+        if records.len() != links.len() {
+            return Err(pyo3::exceptions::PyValueError::new_err(format!(
+                "Got {} records but {} links",
+                records.len(),
+                links.len()
+            )));
+        }
+        let start = self.items.len();
+        let label = label_or(label, "chain");
+        let chain = Arc::new(Chain {
+            records,
+            links,
+            genesis: genesis.unwrap_or_else(|| vec![0u8; HASH_BYTES]),
+            sha3,
+        });
+        for position in 0..chain.records.len() {
+            self.push(
+                &label,
+                Check::ChainLink {
+                    chain: chain.clone(),
+                    position,
+                },
+            );
+        }
+        Ok(start)
+    }
+
+    /// Queues one Merkle inclusion proof check. `proof` is the sibling hashes from the leaf up,
+    /// as MerkleTree.get_proof returns them (without the direction flags)
+    #[pyo3(signature = (leaf, proof, index, root, tree_size, label = None))]
+    fn add_merkle_proof(
+        &mut self,
+        leaf: Vec<u8>,
+        proof: Vec<Vec<u8>>,
+        index: usize,
+        root: Vec<u8>,
+        tree_size: usize,
+        label: Option<&str>,
+    ) -> usize {
+        self.add_merkle_proofs(vec![leaf], vec![proof], vec![index], root, tree_size, label)
+            .expect("one leaf, proof and index")
+    }
+
+    /// Queues one inclusion proof check per (leaf, proof, index), all against the same root
+    #[pyo3(signature = (leaves, proofs, indices, root, tree_size, label = None))]
+    fn add_merkle_proofs(
+        &mut self,
+        leaves: Vec<Vec<u8>>,
+        proofs: Vec<Vec<Vec<u8>>>,
+        indices: Vec<usize>,
+        root: Vec<u8>,
+        tree_size: usize,
+        label: Option<&str>,
+    ) -> PyResult<usize> {
+        if leaves.len() != proofs.len() || leaves.len() != indices.len() {
+            return Err(pyo3::exceptions::PyValueError::new_err(format!(
+                "Got {} leaves, {} proofs and {} indices",
+                leaves.len(),
+                proofs.len(),
+                indices.len()
+            )));
+        }
+        let start = self.items.len();
+        let label = label_or(label, "merkle_proof");
+        let root = Arc::new(root);
+        for ((leaf, proof), index) in leaves.into_iter().zip(proofs).zip(indices) {
+            self.push(
+                &label,
+                Check::MerkleProof {
+                    root: root.clone(),
+                    tree_size,
+                    index,
+                    leaf,
+                    proof,
+                },
+            );
+        }
+        Ok(start)
+    }
+
+    /// Verifies every queued item in parallel. With continue_on_error=False the run stops at
+    /// the first failure; with max_failures it stops once that many have been found. Items
+    /// stay queued, so the pipeline can be run again
+    #[pyo3(signature = (continue_on_error = true, max_failures = None))]
+    fn run(
+        &self,
+        py: Python<'_>,
+        continue_on_error: bool,
+        max_failures: Option<usize>,
+    ) -> PyResult<VerificationReport> {
+        let limit = match (continue_on_error, max_failures) {
+            (false, _) => 1,
+            (true, Some(0)) => {
+                return Err(pyo3::exceptions::PyValueError::new_err(
+                    "max_failures must be at least 1",
+                ))
+            }
+            (true, limit) => limit.unwrap_or(usize::MAX),
+        };
+        let stop = AtomicBool::new(false);
+        let failed = AtomicUsize::new(0);
+        let outcomes = py.allow_threads(|| {
+            parallel_map(self.items.len(), |i| {
+                if stop.load(Ordering::Relaxed) {
+                    return Ok(Outcome::Skipped);
+                }
+                Ok(match self.items[i].check.failure() {
+                    None => Outcome::Passed,
+                    Some(reason) => {
+                        if failed.fetch_add(1, Ordering::SeqCst) + 1 >= limit {
+                            stop.store(true, Ordering::Relaxed);
+                        }
+                        Outcome::Failed(reason)
+                    }
+                })
+            })
+        })?;
+
+        let mut report = VerificationReport {
+            total: self.items.len(),
+            checked: 0,
+            passed: 0,
+            failures: Vec::new(),
+            aborted: false,
+        };
+        for (i, outcome) in outcomes.into_iter().enumerate() {
+            match outcome {
+                Outcome::Passed => {
+                    report.checked += 1;
+                    report.passed += 1;
+                }
+                Outcome::Failed(reason) => {
+                    report.checked += 1;
+                    report
+                        .failures
+                        .push((i, self.items[i].label.to_string(), reason));
+                }
+                Outcome::Skipped => report.aborted = true,
+            }
+        }
+        Ok(report)
+    }
+
+    /// Kind of check queued at `index`: "signature", "chain" or "merkle_proof"
+    fn kind(&self, index: usize) -> PyResult<&'static str> {
+        self.items
+            .get(index)
+            .map(|item| item.check.kind())
+            .ok_or_else(|| pyo3::exceptions::PyIndexError::new_err("No item at that index"))
+    }
+
+    /// Removes every queued item
+    fn clear(&mut self) {
+        self.items.clear();
+    }
+
+    fn __len__(&self) -> usize {
+        self.items.len()
+    }
+}
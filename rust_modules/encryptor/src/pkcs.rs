@@ -0,0 +1,377 @@
+use der::asn1::{BitStringRef, ObjectIdentifier, OctetStringRef};
+use der::pem::LineEnding;
+use der::{Decode, Encode};
+use pkcs8::PrivateKeyInfo;
+use pyo3::prelude::*;
+use spki::{AlgorithmIdentifierRef, SubjectPublicKeyInfoRef};
+use zeroize::Zeroizing;
+
+use crate::dilithium;
+use crate::fingerprint::kyber_public_key_from_secret;
+use crate::handles::ExportKeyArg;
+use crate::mldsa;
+use crate::params::{FalconParams, KyberParams};
+
+// PKCS#8 (RFC 5958 OneAsymmetricKey) and SPKI (RFC 5280) encodings. The round-3 post-quantum
+// OIDs are the ones OpenSSL 3.x picks up through oqs-provider; ML-KEM and ML-DSA use the NIST
+// OIDs with the expandedKey private key form, which OpenSSL 3.5 reads natively; Ed25519 /
+// X25519 follow RFC 8410.
+// Private keys are written as v1 unless a public key is supplied, since not every reader
+// (e.g. pyca/cryptography) accepts the v2 publicKey field.
+// To support another algorithm, add a KeyType variant with its OID and sizes.
+const PRIVATE_KEY_LABEL: &str = "PRIVATE KEY";
+const PUBLIC_KEY_LABEL: &str = "PUBLIC KEY";
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum KeyType {
+    Kyber(KyberParams),
+    Falcon(FalconParams),
+    Dilithium5,
+    MlDsa87,
+    Ed25519,
+    X25519,
+}
+
+const KEY_TYPES: [KeyType; 10] = [
+    KeyType::Kyber(KyberParams::Kyber512),
+    KeyType::Kyber(KyberParams::Kyber768),
+    KeyType::Kyber(KyberParams::Kyber1024),
+    KeyType::Kyber(KyberParams::MlKem1024),
+    KeyType::Falcon(FalconParams::Falcon512),
+    KeyType::Falcon(FalconParams::Falcon1024),
+    KeyType::Dilithium5,
+    KeyType::MlDsa87,
+    KeyType::Ed25519,
+    KeyType::X25519,
+];
+
+impl KeyType {
+    fn parse(name: &str) -> PyResult<Self> {
+        let name = name.to_ascii_lowercase();
+        KEY_TYPES
+            .into_iter()
+            .find(|key_type| key_type.name() == name)
+            .ok_or_else(|| {
+                pyo3::exceptions::PyValueError::new_err(format!(
+                    "Unsupported key algorithm: {}",
+                    name
+                ))
+            })
+    }
+
+    fn from_oid(oid: ObjectIdentifier) -> Result<Self, String> {
+        KEY_TYPES
+            .into_iter()
+            .find(|key_type| key_type.oid() == oid)
+            .ok_or_else(|| format!("Unsupported key algorithm OID {}", oid))
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            KeyType::Kyber(params) => params.name(),
+            KeyType::Falcon(params) => params.name(),
+            KeyType::Dilithium5 => "dilithium5",
+            KeyType::MlDsa87 => "ml-dsa-87",
+            KeyType::Ed25519 => "ed25519",
+            KeyType::X25519 => "x25519",
+        }
+    }
+
+    fn oid(self) -> ObjectIdentifier {
+        match self {
+            KeyType::Kyber(KyberParams::Kyber512) => {
+                ObjectIdentifier::new_unwrap("1.3.6.1.4.1.22554.5.6.1")
+            }
+            KeyType::Kyber(KyberParams::Kyber768) => {
+                ObjectIdentifier::new_unwrap("1.3.6.1.4.1.22554.5.6.2")
+            }
+            KeyType::Kyber(KyberParams::Kyber1024) => {
+                ObjectIdentifier::new_unwrap("1.3.6.1.4.1.22554.5.6.3")
+            }
+            KeyType::Kyber(KyberParams::MlKem1024) => {
+                ObjectIdentifier::new_unwrap("2.16.840.1.101.3.4.4.3")
+            }
+            KeyType::Falcon(FalconParams::Falcon512) => {
+                ObjectIdentifier::new_unwrap("1.3.9999.3.11")
+            }
+            KeyType::Falcon(FalconParams::Falcon1024) => {
+                ObjectIdentifier::new_unwrap("1.3.9999.3.14")
+            }
+            KeyType::Dilithium5 => ObjectIdentifier::new_unwrap("1.3.6.1.4.1.2.267.7.8.7"),
+            KeyType::MlDsa87 => ObjectIdentifier::new_unwrap("2.16.840.1.101.3.4.3.19"),
+            KeyType::Ed25519 => ObjectIdentifier::new_unwrap("1.3.101.112"),
+            KeyType::X25519 => ObjectIdentifier::new_unwrap("1.3.101.110"),
+        }
+    }
+
+    fn public_key_bytes(self) -> usize {
+        match self {
+            KeyType::Kyber(params) => params.public_key_bytes(),
+            KeyType::Falcon(params) => params.public_key_bytes(),
+            KeyType::Dilithium5 => dilithium::PUBLIC_KEY_BYTES,
+            KeyType::MlDsa87 => mldsa::PUBLIC_KEY_BYTES,
+            KeyType::Ed25519 | KeyType::X25519 => 32,
+        }
+    }
+
+    fn secret_key_bytes(self) -> usize {
+        match self {
+            KeyType::Kyber(params) => params.secret_key_bytes(),
+            KeyType::Falcon(params) => params.secret_key_bytes(),
+            KeyType::Dilithium5 => dilithium::SECRET_KEY_BYTES,
+            KeyType::MlDsa87 => mldsa::SECRET_KEY_BYTES,
+            KeyType::Ed25519 | KeyType::X25519 => 32,
+        }
+    }
+
+    /// RFC 8410 keys wrap the secret in a second OCTET STRING, as do the ML-KEM and ML-DSA
+    /// expandedKey forms.
+    fn nests_private_key(self) -> bool {
+        matches!(
+            self,
+            KeyType::Ed25519
+                | KeyType::X25519
+                | KeyType::Kyber(KyberParams::MlKem1024)
+                | KeyType::MlDsa87
+        )
+    }
+
+    /// Public key recomputed from the secret key, where the algorithm allows it.
+    fn derive_public_key(self, secret_key: &[u8]) -> PyResult<Option<Vec<u8>>> {
+        match self {
+            KeyType::Kyber(params) => Ok(Some(
+                kyber_public_key_from_secret(secret_key, params).to_vec(),
+            )),
+            KeyType::Ed25519 => {
+                crate::classical::ed25519_public_from_secret(secret_key).map(|pk| Some(pk.to_vec()))
+            }
+            KeyType::X25519 => {
+                crate::classical::x25519_public_from_secret(secret_key).map(|pk| Some(pk.to_vec()))
+            }
+            KeyType::MlDsa87 => mldsa::public_key_from_secret(secret_key).map(Some),
+            KeyType::Falcon(_) | KeyType::Dilithium5 => Ok(None),
+        }
+    }
+
+    fn algorithm(self) -> AlgorithmIdentifierRef<'static> {
+        AlgorithmIdentifierRef {
+            oid: self.oid(),
+            parameters: None,
+        }
+    }
+}
+
+fn check_len(bytes: &[u8], expected: usize, what: &str, key_type: KeyType) -> Result<(), String> {
+    if bytes.len() != expected {
+        return Err(format!(
+            "Invalid {} {} length. Expected {}, got {}",
+            key_type.name(),
+            what,
+            expected,
+            bytes.len()
+        ));
+    }
+    Ok(())
+}
+
+/// DER input as-is; PEM input decoded after checking its label.
+pub(crate) fn from_pem_or_der(data: &[u8], label: &str) -> Result<Zeroizing<Vec<u8>>, String> {
+    if !data.starts_with(b"-----BEGIN") {
+        return Ok(Zeroizing::new(data.to_vec()));
+    }
+    let (found, der) = der::pem::decode_vec(data).map_err(|e| format!("Invalid PEM: {}", e))?;
+    let der = Zeroizing::new(der);
+    if found != label {
+        return Err(format!("Expected a PEM '{}' block, got '{}'", label, found));
+    }
+    Ok(der)
+}
+
+fn to_pem_or_der(der: Vec<u8>, label: &str, pem: bool) -> Result<Vec<u8>, String> {
+    let der = Zeroizing::new(der);
+    if !pem {
+        return Ok(der.to_vec());
+    }
+    der::pem::encode_string(label, LineEnding::LF, &der)
+        .map(String::into_bytes)
+        .map_err(|e| format!("PEM encoding failed: {}", e))
+}
+
+/// Secret key plus the public key when the field carried one.
+type UnwrappedKey = (Zeroizing<Vec<u8>>, Option<Vec<u8>>);
+
+/// The secret key inside a PKCS#8 privateKey field. Accepts the raw key, the key wrapped in an
+/// OCTET STRING, and the secret-then-public concatenation older oqs-provider releases write.
+fn unwrap_private_key(key_type: KeyType, field: &[u8]) -> Result<UnwrappedKey, String> {
+    let sk_len = key_type.secret_key_bytes();
+    if key_type.nests_private_key() || field.len() != sk_len {
+        if let Ok(inner) = OctetStringRef::from_der(field) {
+            if inner.as_bytes().len() == sk_len {
+                return Ok((Zeroizing::new(inner.as_bytes().to_vec()), None));
+            }
+        }
+    }
+    if field.len() == sk_len {
+        return Ok((Zeroizing::new(field.to_vec()), None));
+    }
+    if field.len() == sk_len + key_type.public_key_bytes() {
+        let (sk, pk) = field.split_at(sk_len);
+        return Ok((Zeroizing::new(sk.to_vec()), Some(pk.to_vec())));
+    }
+    Err(format!(
+        "Invalid {} private key length {}",
+        key_type.name(),
+        field.len()
+    ))
+}
+
+fn encode_pkcs8(
+    key_type: KeyType,
+    secret_key: &[u8],
+    public_key: Option<Vec<u8>>,
+    pem: bool,
+) -> Result<Vec<u8>, String> {
+    check_len(
+        secret_key,
+        key_type.secret_key_bytes(),
+        "secret key",
+        key_type,
+    )?;
+    if let Some(pk) = &public_key {
+        check_len(pk, key_type.public_key_bytes(), "public key", key_type)?;
+        if let Some(derived) = key_type
+            .derive_public_key(secret_key)
+            .map_err(|e| e.to_string())?
+        {
+            if derived != *pk {
+                return Err("Public key does not match the secret key".to_string());
+            }
+        }
+    }
+
+    let nested = if key_type.nests_private_key() {
+        let inner = OctetStringRef::new(secret_key).map_err(|e| e.to_string())?;
+        Zeroizing::new(inner.to_der().map_err(|e| e.to_string())?)
+    } else {
+        Zeroizing::new(secret_key.to_vec())
+    };
+    let info = PrivateKeyInfo {
+        algorithm: key_type.algorithm(),
+        private_key: &nested,
+        public_key: public_key.as_deref(),
+    };
+    let der = info
+        .to_der()
+        .map_err(|e| format!("PKCS#8 encoding failed: {}", e))?;
+    to_pem_or_der(der, PRIVATE_KEY_LABEL, pem)
+}
+
+type ImportedSecretKey = (String, Vec<u8>, Option<Vec<u8>>);
+
+fn decode_pkcs8(data: &[u8]) -> Result<ImportedSecretKey, String> {
+    let der = from_pem_or_der(data, PRIVATE_KEY_LABEL)?;
+    let info = PrivateKeyInfo::from_der(&der).map_err(|e| format!("Invalid PKCS#8 key: {}", e))?;
+    if info.algorithm.parameters.is_some() {
+        return Err("Unexpected algorithm parameters in PKCS#8 key".to_string());
+    }
+    let key_type = KeyType::from_oid(info.algorithm.oid)?;
+    let (secret_key, embedded_pk) = unwrap_private_key(key_type, info.private_key)?;
+    let public_key = match (info.public_key, embedded_pk) {
+        (Some(pk), Some(embedded)) if pk != embedded.as_slice() => {
+            return Err("PKCS#8 key carries two different public keys".to_string())
+        }
+        (Some(pk), _) => Some(pk.to_vec()),
+        (None, embedded) => embedded,
+    };
+    let public_key = match public_key {
+        Some(pk) => {
+            check_len(&pk, key_type.public_key_bytes(), "public key", key_type)?;
+            Some(pk)
+        }
+        None => key_type
+            .derive_public_key(&secret_key)
+            .map_err(|e| e.to_string())?,
+    };
+    Ok((key_type.name().to_string(), secret_key.to_vec(), public_key))
+}
+
+fn encode_spki(key_type: KeyType, public_key: &[u8], pem: bool) -> Result<Vec<u8>, String> {
+    check_len(
+        public_key,
+        key_type.public_key_bytes(),
+        "public key",
+        key_type,
+    )?;
+    let info = SubjectPublicKeyInfoRef {
+        algorithm: key_type.algorithm(),
+        subject_public_key: BitStringRef::from_bytes(public_key).map_err(|e| e.to_string())?,
+    };
+    let der = info
+        .to_der()
+        .map_err(|e| format!("SPKI encoding failed: {}", e))?;
+    to_pem_or_der(der, PUBLIC_KEY_LABEL, pem)
+}
+
+fn decode_spki(data: &[u8]) -> Result<(String, Vec<u8>), String> {
+    let der = from_pem_or_der(data, PUBLIC_KEY_LABEL)?;
+    let info = SubjectPublicKeyInfoRef::from_der(&der)
+        .map_err(|e| format!("Invalid SPKI public key: {}", e))?;
+    if info.algorithm.parameters.is_some() {
+        return Err("Unexpected algorithm parameters in SPKI public key".to_string());
+    }
+    let key_type = KeyType::from_oid(info.algorithm.oid)?;
+    let public_key = info
+        .subject_public_key
+        .as_bytes()
+        .ok_or_else(|| "SPKI public key has unused bits".to_string())?;
+    check_len(
+        public_key,
+        key_type.public_key_bytes(),
+        "public key",
+        key_type,
+    )?;
+    Ok((key_type.name().to_string(), public_key.to_vec()))
+}
+
+/// Encodes a secret key as PKCS#8 (DER, or PEM with `pem=True`). `algorithm` is one of
+/// kyber512/768/1024, falcon512/1024, dilithium5, ed25519 or x25519. A given `public_key` is
+/// embedded (PKCS#8 v2) after checking it against the secret key where possible. A
+/// `SecretKeyHandle` raises PermissionError unless it was created with `exportable=True` and
+/// its policy allows "export"
+#[pyfunction]
+#[pyo3(signature = (secret_key, algorithm, public_key = None, pem = false))]
+pub fn export_secret_key_pkcs8(
+    secret_key: ExportKeyArg,
+    algorithm: &str,
+    public_key: Option<Vec<u8>>,
+    pem: bool,
+) -> PyResult<Vec<u8>> {
+    encode_pkcs8(KeyType::parse(algorithm)?, &secret_key, public_key, pem)
+        .map_err(pyo3::exceptions::PyValueError::new_err)
+}
+
+/// Decodes a PKCS#8 secret key (DER or PEM). Returns (algorithm, secret_key, public_key);
+/// public_key is None when the encoding doesn't carry one and it can't be derived
+#[pyfunction]
+pub fn import_secret_key_pkcs8(data: Vec<u8>) -> PyResult<ImportedSecretKey> {
+    let data = Zeroizing::new(data);
+    decode_pkcs8(&data).map_err(pyo3::exceptions::PyValueError::new_err)
+}
+
+/// Encodes a public key as SubjectPublicKeyInfo (DER, or PEM with `pem=True`)
+#[pyfunction]
+#[pyo3(signature = (public_key, algorithm, pem = false))]
+pub fn export_public_key_spki(
+    public_key: Vec<u8>,
+    algorithm: &str,
+    pem: bool,
+) -> PyResult<Vec<u8>> {
+    encode_spki(KeyType::parse(algorithm)?, &public_key, pem)
+        .map_err(pyo3::exceptions::PyValueError::new_err)
+}
+
+/// Decodes a SubjectPublicKeyInfo public key (DER or PEM). Returns (algorithm, public_key)
+#[pyfunction]
+pub fn import_public_key_spki(data: Vec<u8>) -> PyResult<(String, Vec<u8>)> {
+    decode_spki(&data).map_err(pyo3::exceptions::PyValueError::new_err)
+}
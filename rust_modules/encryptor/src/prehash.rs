@@ -0,0 +1,235 @@
+use std::fs::File;
+use std::io::Read;
+
+use pyo3::prelude::*;
+use sha2::{Digest, Sha512};
+use sha3::Sha3_512;
+
+use crate::handles::SigningKeyArg;
+use crate::params::FalconParams;
+
+const PREHASH_LABEL: &[u8] = b"reliquary/falcon-prehash/v1";
+const MAX_CONTEXT_BYTES: usize = 255;
+const FILE_CHUNK_BYTES: usize = 1 << 20;
+
+/// Hash functions accepted for prehashed signatures. The identifier byte is signed along with
+/// the digest so a signature over one hash can't be replayed as a signature over another.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum PrehashAlgorithm {
+    Sha3_512,
+    Sha512,
+}
+
+impl PrehashAlgorithm {
+    pub(crate) fn parse(name: &str) -> PyResult<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "sha3-512" => Ok(PrehashAlgorithm::Sha3_512),
+            "sha512" | "sha-512" => Ok(PrehashAlgorithm::Sha512),
+            _ => Err(pyo3::exceptions::PyValueError::new_err(format!(
+                "Unsupported prehash algorithm '{}' (expected 'sha3-512' or 'sha512')",
+                name
+            ))),
+        }
+    }
+
+    fn id(&self) -> u8 {
+        match self {
+            PrehashAlgorithm::Sha3_512 => 1,
+            PrehashAlgorithm::Sha512 => 2,
+        }
+    }
+
+    fn name(&self) -> &'static str {
+        match self {
+            PrehashAlgorithm::Sha3_512 => "sha3-512",
+            PrehashAlgorithm::Sha512 => "sha512",
+        }
+    }
+
+    fn digest_bytes(&self) -> usize {
+        64
+    }
+}
+
+#[derive(Clone)]
+enum Hasher {
+    Sha3_512(Sha3_512),
+    Sha512(Sha512),
+}
+
+impl Hasher {
+    fn new(algorithm: PrehashAlgorithm) -> Self {
+        match algorithm {
+            PrehashAlgorithm::Sha3_512 => Hasher::Sha3_512(Sha3_512::new()),
+            PrehashAlgorithm::Sha512 => Hasher::Sha512(Sha512::new()),
+        }
+    }
+
+    fn update(&mut self, data: &[u8]) {
+        match self {
+            Hasher::Sha3_512(h) => h.update(data),
+            Hasher::Sha512(h) => h.update(data),
+        }
+    }
+
+    fn finalize(self) -> Vec<u8> {
+        match self {
+            Hasher::Sha3_512(h) => h.finalize().to_vec(),
+            Hasher::Sha512(h) => h.finalize().to_vec(),
+        }
+    }
+}
+
+/// Message actually signed in prehash mode:
+/// label | hash id | len(context) | context | digest
+pub(crate) fn prehash_message(
+    digest: &[u8],
+    context: &[u8],
+    algorithm: PrehashAlgorithm,
+) -> PyResult<Vec<u8>> {
+    if digest.len() != algorithm.digest_bytes() {
+        return Err(pyo3::exceptions::PyValueError::new_err(format!(
+            "Invalid {} digest length. Expected {}, got {}",
+            algorithm.name(),
+            algorithm.digest_bytes(),
+            digest.len()
+        )));
+    }
+    if context.len() > MAX_CONTEXT_BYTES {
+        return Err(pyo3::exceptions::PyValueError::new_err(format!(
+            "Context must be at most {} bytes",
+            MAX_CONTEXT_BYTES
+        )));
+    }
+    let mut msg = Vec::with_capacity(PREHASH_LABEL.len() + 2 + context.len() + digest.len());
+    msg.extend_from_slice(PREHASH_LABEL);
+    msg.push(algorithm.id());
+    msg.push(context.len() as u8);
+    msg.extend_from_slice(context);
+    msg.extend_from_slice(digest);
+    Ok(msg)
+}
+
+/// Incremental hash for signing data too large to hold in memory. Feed it with `update` or
+/// `update_file`, then pass `digest()` to `sign_falcon_prehashed` / `verify_falcon_prehashed`
+/// with the same `algorithm`.
+#[pyclass(module = "reliquary_encryptor")]
+pub struct SigningDigest {
+    algorithm: PrehashAlgorithm,
+    hasher: Hasher,
+    bytes_hashed: u64,
+}
+
+#[pymethods]
+impl SigningDigest {
+    #[new]
+    #[pyo3(signature = (algorithm = "sha3-512"))]
+    fn new(algorithm: &str) -> PyResult<Self> {
+        let algorithm = PrehashAlgorithm::parse(algorithm)?;
+        Ok(SigningDigest {
+            algorithm,
+            hasher: Hasher::new(algorithm),
+            bytes_hashed: 0,
+        })
+    }
+
+    #[getter]
+    fn algorithm(&self) -> &'static str {
+        self.algorithm.name()
+    }
+
+    /// Total number of bytes fed in so far
+    #[getter]
+    fn bytes_hashed(&self) -> u64 {
+        self.bytes_hashed
+    }
+
+    /// Absorbs a chunk of data
+    fn update(&mut self, py: Python<'_>, data: Vec<u8>) {
+        let hasher = &mut self.hasher;
+        py.allow_threads(|| hasher.update(&data));
+        self.bytes_hashed += data.len() as u64;
+    }
+
+    /// Absorbs the whole contents of a file in 1 MiB chunks without holding the GIL.
+    /// Returns the number of bytes read
+    fn update_file(&mut self, py: Python<'_>, path: std::path::PathBuf) -> PyResult<u64> {
+        let hasher = &mut self.hasher;
+        let read = py.allow_threads(|| -> std::io::Result<u64> {
+            let mut file = File::open(&path)?;
+            let mut buf = vec![0u8; FILE_CHUNK_BYTES];
+            let mut total = 0u64;
+            loop {
+                let n = file.read(&mut buf)?;
+                if n == 0 {
+                    return Ok(total);
+                }
+                hasher.update(&buf[..n]);
+                total += n as u64;
+            }
+        })?;
+        self.bytes_hashed += read;
+        Ok(read)
+    }
+
+    /// Digest of everything absorbed so far; the object can keep absorbing afterwards
+    fn digest(&self) -> Vec<u8> {
+        self.hasher.clone().finalize()
+    }
+
+    /// Independent copy of the current state
+    fn copy(&self) -> Self {
+        SigningDigest {
+            algorithm: self.algorithm,
+            hasher: self.hasher.clone(),
+            bytes_hashed: self.bytes_hashed,
+        }
+    }
+}
+
+/// Falcon signature over a precomputed digest (e.g. from `SigningDigest`), domain-separated from
+/// plain `sign_falcon` by a prehash label, the hash identifier and `context`.
+/// Returns a detached signature
+#[pyfunction]
+#[pyo3(signature = (
+    digest,
+    context,
+    sk_bytes,
+    hash_algorithm = "sha3-512",
+    params = FalconParams::Falcon1024,
+))]
+pub fn sign_falcon_prehashed(
+    py: Python<'_>,
+    digest: Vec<u8>,
+    context: Vec<u8>,
+    sk_bytes: SigningKeyArg,
+    hash_algorithm: &str,
+    params: FalconParams,
+) -> PyResult<Vec<u8>> {
+    let params = sk_bytes.falcon_params(params)?;
+    let msg = prehash_message(&digest, &context, PrehashAlgorithm::parse(hash_algorithm)?)?;
+    py.allow_threads(|| crate::falcon_sign_detached(&msg, &sk_bytes, params))
+}
+
+/// Verifies a signature from `sign_falcon_prehashed`
+#[pyfunction]
+#[pyo3(signature = (
+    digest,
+    context,
+    signature,
+    pk_bytes,
+    hash_algorithm = "sha3-512",
+    params = FalconParams::Falcon1024,
+))]
+pub fn verify_falcon_prehashed(
+    py: Python<'_>,
+    digest: Vec<u8>,
+    context: Vec<u8>,
+    signature: Vec<u8>,
+    pk_bytes: Vec<u8>,
+    hash_algorithm: &str,
+    params: FalconParams,
+) -> PyResult<bool> {
+    let msg = prehash_message(&digest, &context, PrehashAlgorithm::parse(hash_algorithm)?)?;
+    py.allow_threads(|| crate::falcon_verify_detached(&msg, &signature, &pk_bytes, params))
+}
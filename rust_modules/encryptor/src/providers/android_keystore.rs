@@ -0,0 +1,241 @@
+use jni::objects::{JByteArray, JObject, JValue};
+use jni::{JNIEnv, JavaVM};
+use p256::elliptic_curve::sec1::ToEncodedPoint;
+use p256::pkcs8::DecodePublicKey;
+use pyo3::prelude::*;
+
+use super::{KeyProvider, Signer};
+
+const KEYSTORE: &str = "AndroidKeyStore";
+// android.security.keystore.KeyProperties.PURPOSE_SIGN
+const PURPOSE_SIGN: i32 = 4;
+
+/// Android Keystore provider: hardware-backed (TEE/StrongBox where available) P-256 keys.
+pub(crate) struct AndroidKeystoreProvider;
+
+/// Attaches the current thread to the app's JVM and runs `f`, clearing any pending Java
+/// exception so it surfaces as a Python error instead.
+fn with_env<T>(f: impl FnOnce(&mut JNIEnv) -> jni::errors::Result<T>) -> PyResult<T> {
+    let context = ndk_context::android_context();
+    // SAFETY: ndk-context hands out the process JavaVM pointer registered by the app.
+    let vm = unsafe { JavaVM::from_raw(context.vm().cast()) }.map_err(|e| {
+        pyo3::exceptions::PyRuntimeError::new_err(format!("JavaVM unavailable: {}", e))
+    })?;
+    let mut env = vm.attach_current_thread().map_err(|e| {
+        pyo3::exceptions::PyRuntimeError::new_err(format!("JNI attach failed: {}", e))
+    })?;
+    let result = f(&mut env);
+    if env.exception_check().unwrap_or(false) {
+        let _ = env.exception_clear();
+    }
+    result.map_err(|e| {
+        pyo3::exceptions::PyRuntimeError::new_err(format!("Android Keystore error: {}", e))
+    })
+}
+
+fn load_keystore<'local>(env: &mut JNIEnv<'local>) -> jni::errors::Result<JObject<'local>> {
+    let name = env.new_string(KEYSTORE)?;
+    let keystore = env
+        .call_static_method(
+            "java/security/KeyStore",
+            "getInstance",
+            "(Ljava/lang/String;)Ljava/security/KeyStore;",
+            &[JValue::Object(&name)],
+        )?
+        .l()?;
+    env.call_method(
+        &keystore,
+        "load",
+        "(Ljava/security/KeyStore$LoadStoreParameter;)V",
+        &[JValue::Object(&JObject::null())],
+    )?;
+    Ok(keystore)
+}
+
+fn contains_alias(env: &mut JNIEnv, keystore: &JObject, label: &str) -> jni::errors::Result<bool> {
+    let alias = env.new_string(label)?;
+    env.call_method(
+        keystore,
+        "containsAlias",
+        "(Ljava/lang/String;)Z",
+        &[JValue::Object(&alias)],
+    )?
+    .z()
+}
+
+/// SPKI DER from `PublicKey.getEncoded()` re-encoded as an uncompressed SEC1 point, matching
+/// what the Secure Enclave backend returns.
+fn sec1_from_spki(der: &[u8]) -> PyResult<Vec<u8>> {
+    let public_key = p256::PublicKey::from_public_key_der(der).map_err(|e| {
+        pyo3::exceptions::PyRuntimeError::new_err(format!("Unexpected keystore public key: {}", e))
+    })?;
+    Ok(public_key.to_encoded_point(false).as_bytes().to_vec())
+}
+
+fn missing(label: &str) -> PyErr {
+    pyo3::exceptions::PyValueError::new_err(format!("No Android Keystore key labeled '{}'", label))
+}
+
+impl KeyProvider for AndroidKeystoreProvider {
+    fn name(&self) -> &'static str {
+        "android-keystore"
+    }
+
+    fn generate_key(&self, label: &str) -> PyResult<Vec<u8>> {
+        let der = with_env(|env| {
+            let keystore = load_keystore(env)?;
+            if contains_alias(env, &keystore, label)? {
+                return Ok(None);
+            }
+            let alias = env.new_string(label)?;
+            let builder = env.new_object(
+                "android/security/keystore/KeyGenParameterSpec$Builder",
+                "(Ljava/lang/String;I)V",
+                &[JValue::Object(&alias), JValue::Int(PURPOSE_SIGN)],
+            )?;
+            let digest = env.new_string("SHA-256")?;
+            let digests = env.new_object_array(1, "java/lang/String", &digest)?;
+            env.call_method(
+                &builder,
+                "setDigests",
+                "([Ljava/lang/String;)Landroid/security/keystore/KeyGenParameterSpec$Builder;",
+                &[JValue::Object(&digests)],
+            )?;
+            let spec = env
+                .call_method(
+                    &builder,
+                    "build",
+                    "()Landroid/security/keystore/KeyGenParameterSpec;",
+                    &[],
+                )?
+                .l()?;
+
+            let algorithm = env.new_string("EC")?;
+            let provider = env.new_string(KEYSTORE)?;
+            let generator = env
+                .call_static_method(
+                    "java/security/KeyPairGenerator",
+                    "getInstance",
+                    "(Ljava/lang/String;Ljava/lang/String;)Ljava/security/KeyPairGenerator;",
+                    &[JValue::Object(&algorithm), JValue::Object(&provider)],
+                )?
+                .l()?;
+            env.call_method(
+                &generator,
+                "initialize",
+                "(Ljava/security/spec/AlgorithmParameterSpec;)V",
+                &[JValue::Object(&spec)],
+            )?;
+            let pair = env
+                .call_method(
+                    &generator,
+                    "generateKeyPair",
+                    "()Ljava/security/KeyPair;",
+                    &[],
+                )?
+                .l()?;
+            let public = env
+                .call_method(&pair, "getPublic", "()Ljava/security/PublicKey;", &[])?
+                .l()?;
+            let encoded = env.call_method(&public, "getEncoded", "()[B", &[])?.l()?;
+            env.convert_byte_array(JByteArray::from(encoded)).map(Some)
+        })?;
+        match der {
+            Some(der) => sec1_from_spki(&der),
+            None => Err(pyo3::exceptions::PyValueError::new_err(format!(
+                "An Android Keystore key labeled '{}' already exists",
+                label
+            ))),
+        }
+    }
+
+    fn public_key(&self, label: &str) -> PyResult<Vec<u8>> {
+        let der = with_env(|env| {
+            let keystore = load_keystore(env)?;
+            let alias = env.new_string(label)?;
+            let certificate = env
+                .call_method(
+                    &keystore,
+                    "getCertificate",
+                    "(Ljava/lang/String;)Ljava/security/cert/Certificate;",
+                    &[JValue::Object(&alias)],
+                )?
+                .l()?;
+            if certificate.is_null() {
+                return Ok(None);
+            }
+            let public = env
+                .call_method(
+                    &certificate,
+                    "getPublicKey",
+                    "()Ljava/security/PublicKey;",
+                    &[],
+                )?
+                .l()?;
+            let encoded = env.call_method(&public, "getEncoded", "()[B", &[])?.l()?;
+            env.convert_byte_array(JByteArray::from(encoded)).map(Some)
+        })?;
+        sec1_from_spki(&der.ok_or_else(|| missing(label))?)
+    }
+
+    fn delete_key(&self, label: &str) -> PyResult<bool> {
+        with_env(|env| {
+            let keystore = load_keystore(env)?;
+            if !contains_alias(env, &keystore, label)? {
+                return Ok(false);
+            }
+            let alias = env.new_string(label)?;
+            env.call_method(
+                &keystore,
+                "deleteEntry",
+                "(Ljava/lang/String;)V",
+                &[JValue::Object(&alias)],
+            )?;
+            Ok(true)
+        })
+    }
+}
+
+impl Signer for AndroidKeystoreProvider {
+    fn signature_algorithm(&self) -> &'static str {
+        "ecdsa-p256-sha256"
+    }
+
+    fn sign(&self, label: &str, msg: &[u8]) -> PyResult<Vec<u8>> {
+        let signature = with_env(|env| {
+            let keystore = load_keystore(env)?;
+            let alias = env.new_string(label)?;
+            let private = env
+                .call_method(
+                    &keystore,
+                    "getKey",
+                    "(Ljava/lang/String;[C)Ljava/security/Key;",
+                    &[JValue::Object(&alias), JValue::Object(&JObject::null())],
+                )?
+                .l()?;
+            if private.is_null() {
+                return Ok(None);
+            }
+            let algorithm = env.new_string("SHA256withECDSA")?;
+            let signature = env
+                .call_static_method(
+                    "java/security/Signature",
+                    "getInstance",
+                    "(Ljava/lang/String;)Ljava/security/Signature;",
+                    &[JValue::Object(&algorithm)],
+                )?
+                .l()?;
+            env.call_method(
+                &signature,
+                "initSign",
+                "(Ljava/security/PrivateKey;)V",
+                &[JValue::Object(&private)],
+            )?;
+            let data = env.byte_array_from_slice(msg)?;
+            env.call_method(&signature, "update", "([B)V", &[JValue::Object(&data)])?;
+            let signed = env.call_method(&signature, "sign", "()[B", &[])?.l()?;
+            env.convert_byte_array(JByteArray::from(signed)).map(Some)
+        })?;
+        signature.ok_or_else(|| missing(label))
+    }
+}
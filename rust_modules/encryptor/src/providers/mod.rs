@@ -0,0 +1,133 @@
+use p256::ecdsa::signature::Verifier;
+use p256::ecdsa::{Signature as P256Signature, VerifyingKey as P256VerifyingKey};
+use pyo3::prelude::*;
+
+use crate::readonly;
+
+#[cfg(feature = "hsm")]
+pub use pkcs11::{hsm_generate_wrapping_key, hsm_unwrap_key, hsm_wrap_key};
+
+#[cfg(all(feature = "android-keystore", target_os = "android"))]
+mod android_keystore;
+#[cfg(feature = "hsm")]
+mod pkcs11;
+#[cfg(all(feature = "secure-enclave", target_vendor = "apple"))]
+mod secure_enclave;
+mod software;
+
+/// A place where labeled keys live. Providers own the secret half; callers only ever see
+/// public keys and the results of operations.
+pub(crate) trait KeyProvider: Send + Sync {
+    /// Name callers use to select the provider, e.g. `"secure-enclave"`
+    fn name(&self) -> &'static str;
+
+    /// Creates a new key under `label` and returns its public key.
+    fn generate_key(&self, label: &str) -> PyResult<Vec<u8>>;
+
+    /// Returns the public key stored under `label`.
+    fn public_key(&self, label: &str) -> PyResult<Vec<u8>>;
+
+    /// Deletes the key stored under `label`, returning false if there was none.
+    fn delete_key(&self, label: &str) -> PyResult<bool>;
+}
+
+/// A key provider whose keys can produce signatures.
+pub(crate) trait Signer: KeyProvider {
+    /// Signature algorithm produced by `sign`, e.g. `"falcon1024"` or `"ecdsa-p256-sha256"`
+    fn signature_algorithm(&self) -> &'static str;
+
+    /// Signs `msg` with the key stored under `label`.
+    fn sign(&self, label: &str, msg: &[u8]) -> PyResult<Vec<u8>>;
+}
+
+/// Signers compiled into this build. The software provider is always present; platform
+/// keystores need their feature flag and a matching target OS, and PKCS#11 tokens the `hsm`
+/// feature (their labels are PKCS#11 URIs).
+fn signers() -> Vec<&'static dyn Signer> {
+    #[allow(unused_mut)]
+    let mut signers: Vec<&'static dyn Signer> = vec![&software::SoftwareProvider];
+    #[cfg(all(feature = "secure-enclave", target_vendor = "apple"))]
+    signers.push(&secure_enclave::SecureEnclaveProvider);
+    #[cfg(all(feature = "android-keystore", target_os = "android"))]
+    signers.push(&android_keystore::AndroidKeystoreProvider);
+    #[cfg(feature = "hsm")]
+    signers.push(&pkcs11::Pkcs11Provider);
+    signers
+}
+
+pub(crate) fn signer(name: &str) -> PyResult<&'static dyn Signer> {
+    signers()
+        .into_iter()
+        .find(|signer| signer.name() == name)
+        .ok_or_else(|| {
+            pyo3::exceptions::PyValueError::new_err(format!(
+                "Key provider '{}' is not available in this build",
+                name
+            ))
+        })
+}
+
+/// Lists the key providers available in this build as (name, signature_algorithm) pairs
+#[pyfunction]
+pub fn key_providers() -> Vec<(String, String)> {
+    signers()
+        .into_iter()
+        .map(|signer| {
+            (
+                signer.name().to_string(),
+                signer.signature_algorithm().to_string(),
+            )
+        })
+        .collect()
+}
+
+/// Creates a labeled key inside a provider. Returns its public key
+#[pyfunction]
+pub fn provider_generate_key(py: Python<'_>, provider: &str, label: &str) -> PyResult<Vec<u8>> {
+    readonly::check_writable("Provider key generation")?;
+    let signer = signer(provider)?;
+    py.allow_threads(|| signer.generate_key(label))
+}
+
+/// Returns the public key of a labeled provider key
+#[pyfunction]
+pub fn provider_public_key(provider: &str, label: &str) -> PyResult<Vec<u8>> {
+    signer(provider)?.public_key(label)
+}
+
+/// Signs a message with a labeled provider key; the secret key never leaves the provider
+#[pyfunction]
+pub fn provider_sign(
+    py: Python<'_>,
+    provider: &str,
+    label: &str,
+    msg: Vec<u8>,
+) -> PyResult<Vec<u8>> {
+    let signer = signer(provider)?;
+    py.allow_threads(|| signer.sign(label, &msg))
+}
+
+/// Deletes a labeled provider key. Returns False if there was none
+#[pyfunction]
+pub fn provider_delete_key(provider: &str, label: &str) -> PyResult<bool> {
+    readonly::check_writable("Provider key deletion")?;
+    signer(provider)?.delete_key(label)
+}
+
+/// Checks a DER-encoded ECDSA P-256/SHA-256 signature against a SEC1-encoded public key.
+pub(crate) fn p256_verify(msg: &[u8], signature_der: &[u8], public_key: &[u8]) -> PyResult<bool> {
+    let verifying_key = P256VerifyingKey::from_sec1_bytes(public_key).map_err(|e| {
+        pyo3::exceptions::PyValueError::new_err(format!("Invalid public key: {:?}", e))
+    })?;
+    let signature = P256Signature::from_der(signature_der).map_err(|e| {
+        pyo3::exceptions::PyValueError::new_err(format!("Invalid signature: {:?}", e))
+    })?;
+    Ok(verifying_key.verify(msg, &signature).is_ok())
+}
+
+/// Verifies a DER-encoded ECDSA P-256/SHA-256 signature from a device keystore against a
+/// SEC1-encoded public key
+#[pyfunction]
+pub fn verify_p256(msg: Vec<u8>, signature_der: Vec<u8>, public_key: Vec<u8>) -> PyResult<bool> {
+    p256_verify(&msg, &signature_der, &public_key)
+}
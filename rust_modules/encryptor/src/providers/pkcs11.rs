@@ -0,0 +1,469 @@
+// PKCS#11 token provider. Keys are addressed by RFC 7512 URIs, e.g.
+//
+//   pkcs11:token=vault;object=signing-key?module-path=/usr/lib/softhsm/libsofthsm2.so&pin-source=file:/run/pin
+//
+// Path attributes select the token (token, serial, slot-id) and the object (object, id, type);
+// query attributes name the module to load and the user PIN (pin-value or pin-source).
+// Signing keys are EC P-256 key pairs created on the token as sensitive and non-extractable;
+// signatures come back as DER so `verify_p256` accepts them. Wrapping keys are AES-256 token
+// keys, and wrapped blobs are: nonce (12) | ciphertext | tag (16), AES-GCM on the token.
+
+use std::collections::HashMap;
+use std::sync::{Mutex, OnceLock};
+
+use cryptoki::context::{CInitializeArgs, CInitializeFlags, Pkcs11};
+use cryptoki::error::{Error as Pkcs11Error, RvError};
+use cryptoki::mechanism::aead::GcmParams;
+use cryptoki::mechanism::Mechanism;
+use cryptoki::object::{Attribute, AttributeType, KeyType, ObjectClass, ObjectHandle};
+use cryptoki::session::{Session, UserType};
+use cryptoki::types::AuthPin;
+use p256::ecdsa::Signature as P256Signature;
+use pyo3::prelude::*;
+use rand::RngCore;
+use sha2::{Digest, Sha256};
+use zeroize::Zeroizing;
+
+use super::{KeyProvider, Signer};
+
+// DER OID for the P-256 curve (prime256v1), the CKA_EC_PARAMS of generated keys.
+const P256_EC_PARAMS: [u8; 10] = [0x06, 0x08, 0x2a, 0x86, 0x48, 0xce, 0x3d, 0x03, 0x01, 0x07];
+const GCM_NONCE_LEN: usize = 12;
+const GCM_TAG_LEN: usize = 16;
+
+/// PKCS#11 provider: keys live on a hardware token and are used through the module named in
+/// each key URI.
+pub(crate) struct Pkcs11Provider;
+
+fn value_error(message: String) -> PyErr {
+    pyo3::exceptions::PyValueError::new_err(message)
+}
+
+fn token_error(operation: &str, e: Pkcs11Error) -> PyErr {
+    pyo3::exceptions::PyRuntimeError::new_err(format!("PKCS#11 {} failed: {}", operation, e))
+}
+
+/// The parts of a PKCS#11 URI this provider understands.
+struct KeyUri {
+    module_path: String,
+    token: Option<String>,
+    serial: Option<String>,
+    slot_id: Option<u64>,
+    object: Option<String>,
+    id: Option<Vec<u8>>,
+    pin: Option<Zeroizing<String>>,
+}
+
+fn percent_decode(value: &str) -> PyResult<Vec<u8>> {
+    let bytes = value.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'%' {
+            let hex = bytes
+                .get(i + 1..i + 3)
+                .and_then(|h| std::str::from_utf8(h).ok())
+                .and_then(|h| u8::from_str_radix(h, 16).ok())
+                .ok_or_else(|| value_error(format!("Invalid percent-encoding in '{}'", value)))?;
+            out.push(hex);
+            i += 3;
+        } else {
+            out.push(bytes[i]);
+            i += 1;
+        }
+    }
+    Ok(out)
+}
+
+fn decode_text(value: &str) -> PyResult<String> {
+    String::from_utf8(percent_decode(value)?)
+        .map_err(|_| value_error(format!("PKCS#11 URI value '{}' is not UTF-8", value)))
+}
+
+impl KeyUri {
+    fn parse(uri: &str) -> PyResult<Self> {
+        let rest = uri
+            .strip_prefix("pkcs11:")
+            .ok_or_else(|| value_error("Key label must be a pkcs11: URI".to_string()))?;
+        let (path, query) = rest.split_once('?').unwrap_or((rest, ""));
+        let mut key = KeyUri {
+            module_path: String::new(),
+            token: None,
+            serial: None,
+            slot_id: None,
+            object: None,
+            id: None,
+            pin: None,
+        };
+
+        for attr in path.split(';').filter(|attr| !attr.is_empty()) {
+            let (name, value) = attr.split_once('=').ok_or_else(|| {
+                value_error(format!("Malformed PKCS#11 URI attribute '{}'", attr))
+            })?;
+            match name {
+                "token" => key.token = Some(decode_text(value)?),
+                "serial" => key.serial = Some(decode_text(value)?),
+                "slot-id" => {
+                    key.slot_id =
+                        Some(value.parse().map_err(|_| {
+                            value_error(format!("Invalid PKCS#11 slot-id '{}'", value))
+                        })?)
+                }
+                "object" => key.object = Some(decode_text(value)?),
+                "id" => key.id = Some(percent_decode(value)?),
+                // The provider picks the object class per operation
+                "type" => {}
+                _ => {
+                    return Err(value_error(format!(
+                        "Unsupported PKCS#11 URI attribute '{}'",
+                        name
+                    )))
+                }
+            }
+        }
+
+        for attr in query.split('&').filter(|attr| !attr.is_empty()) {
+            let (name, value) = attr.split_once('=').ok_or_else(|| {
+                value_error(format!("Malformed PKCS#11 URI attribute '{}'", attr))
+            })?;
+            match name {
+                "module-path" => key.module_path = decode_text(value)?,
+                "pin-value" => key.pin = Some(Zeroizing::new(decode_text(value)?)),
+                "pin-source" => {
+                    let source = decode_text(value)?;
+                    let path = source.strip_prefix("file:").unwrap_or(&source);
+                    let pin = Zeroizing::new(std::fs::read_to_string(path).map_err(|e| {
+                        value_error(format!("Could not read PIN from '{}': {}", path, e))
+                    })?);
+                    key.pin = Some(Zeroizing::new(pin.trim_end().to_string()));
+                }
+                _ => {
+                    return Err(value_error(format!(
+                        "Unsupported PKCS#11 URI attribute '{}'",
+                        name
+                    )))
+                }
+            }
+        }
+
+        if key.module_path.is_empty() {
+            return Err(value_error(
+                "PKCS#11 URI needs a module-path query attribute".to_string(),
+            ));
+        }
+        if key.object.is_none() && key.id.is_none() {
+            return Err(value_error(
+                "PKCS#11 URI needs an object or id attribute".to_string(),
+            ));
+        }
+        Ok(key)
+    }
+
+    /// Attributes that identify the key's objects, plus the object class.
+    fn template(&self, class: ObjectClass) -> Vec<Attribute> {
+        let mut template = vec![Attribute::Class(class)];
+        if let Some(object) = &self.object {
+            template.push(Attribute::Label(object.as_bytes().to_vec()));
+        }
+        if let Some(id) = &self.id {
+            template.push(Attribute::Id(id.clone()));
+        }
+        template
+    }
+
+    /// Opens a logged-in read/write session on the token the URI names.
+    fn session(&self) -> PyResult<Session> {
+        let context = context(&self.module_path)?;
+        let slots = context
+            .get_slots_with_token()
+            .map_err(|e| token_error("slot listing", e))?;
+        let mut selected = None;
+        for slot in slots {
+            if self.slot_id.is_some_and(|id| id != slot.id()) {
+                continue;
+            }
+            if self.token.is_some() || self.serial.is_some() {
+                let info = context
+                    .get_token_info(slot)
+                    .map_err(|e| token_error("token info", e))?;
+                if self.token.as_deref().is_some_and(|t| t != info.label())
+                    || self
+                        .serial
+                        .as_deref()
+                        .is_some_and(|s| s != info.serial_number())
+                {
+                    continue;
+                }
+            }
+            selected = Some(slot);
+            break;
+        }
+        let slot = selected
+            .ok_or_else(|| value_error("No PKCS#11 token matches the key URI".to_string()))?;
+
+        let session = context
+            .open_rw_session(slot)
+            .map_err(|e| token_error("open session", e))?;
+        if let Some(pin) = &self.pin {
+            match session.login(UserType::User, Some(&AuthPin::from(pin.as_str()))) {
+                Ok(()) | Err(Pkcs11Error::Pkcs11(RvError::UserAlreadyLoggedIn, _)) => {}
+                Err(e) => return Err(token_error("login", e)),
+            }
+        }
+        Ok(session)
+    }
+}
+
+/// One initialized context per module; PKCS#11 modules may only be initialized once per process.
+fn context(module_path: &str) -> PyResult<Pkcs11> {
+    static CONTEXTS: OnceLock<Mutex<HashMap<String, Pkcs11>>> = OnceLock::new();
+    let mut contexts = CONTEXTS
+        .get_or_init(|| Mutex::new(HashMap::new()))
+        .lock()
+        .map_err(|_| pyo3::exceptions::PyRuntimeError::new_err("PKCS#11 context lock poisoned"))?;
+    if let Some(context) = contexts.get(module_path) {
+        return Ok(context.clone());
+    }
+    let context = Pkcs11::new(module_path).map_err(|e| token_error("module load", e))?;
+    context
+        .initialize(CInitializeArgs::new(CInitializeFlags::OS_LOCKING_OK))
+        .map_err(|e| token_error("initialize", e))?;
+    contexts.insert(module_path.to_string(), context.clone());
+    Ok(context)
+}
+
+fn find_objects(
+    session: &Session,
+    key: &KeyUri,
+    class: ObjectClass,
+) -> PyResult<Vec<ObjectHandle>> {
+    session
+        .find_objects(&key.template(class))
+        .map_err(|e| token_error("object search", e))
+}
+
+/// The single object of `class` the URI names.
+fn find_object(session: &Session, key: &KeyUri, class: ObjectClass) -> PyResult<ObjectHandle> {
+    match find_objects(session, key, class)?.as_slice() {
+        [handle] => Ok(*handle),
+        [] => Err(value_error(format!(
+            "No PKCS#11 {} matches the key URI",
+            class
+        ))),
+        _ => Err(value_error(format!(
+            "More than one PKCS#11 {} matches the key URI",
+            class
+        ))),
+    }
+}
+
+/// Identifying attributes for new objects: the label and id from the URI.
+fn new_object_attributes(key: &KeyUri) -> Vec<Attribute> {
+    let mut attributes = vec![Attribute::Token(true)];
+    if let Some(object) = &key.object {
+        attributes.push(Attribute::Label(object.as_bytes().to_vec()));
+    }
+    if let Some(id) = &key.id {
+        attributes.push(Attribute::Id(id.clone()));
+    }
+    attributes
+}
+
+fn ensure_absent(session: &Session, key: &KeyUri, class: ObjectClass) -> PyResult<()> {
+    if find_objects(session, key, class)?.is_empty() {
+        Ok(())
+    } else {
+        Err(value_error(format!(
+            "A PKCS#11 {} matching the key URI already exists",
+            class
+        )))
+    }
+}
+
+/// SEC1 public key of a P-256 public key object. CKA_EC_POINT is a DER OCTET STRING holding
+/// the point, though some tokens return the bare point.
+fn ec_point(session: &Session, handle: ObjectHandle) -> PyResult<Vec<u8>> {
+    let attributes = session
+        .get_attributes(handle, &[AttributeType::EcPoint])
+        .map_err(|e| token_error("attribute read", e))?;
+    let point = attributes
+        .into_iter()
+        .find_map(|attr| match attr {
+            Attribute::EcPoint(point) => Some(point),
+            _ => None,
+        })
+        .ok_or_else(|| value_error("PKCS#11 key has no EC point".to_string()))?;
+    match point.as_slice() {
+        [0x04, len, rest @ ..] if *len as usize == rest.len() && rest.first() == Some(&0x04) => {
+            Ok(rest.to_vec())
+        }
+        _ => Ok(point),
+    }
+}
+
+impl KeyProvider for Pkcs11Provider {
+    fn name(&self) -> &'static str {
+        "pkcs11"
+    }
+
+    fn generate_key(&self, label: &str) -> PyResult<Vec<u8>> {
+        let key = KeyUri::parse(label)?;
+        let session = key.session()?;
+        ensure_absent(&session, &key, ObjectClass::PRIVATE_KEY)?;
+
+        let mut public_template = new_object_attributes(&key);
+        public_template.extend([
+            Attribute::EcParams(P256_EC_PARAMS.to_vec()),
+            Attribute::Verify(true),
+        ]);
+        let mut private_template = new_object_attributes(&key);
+        private_template.extend([
+            Attribute::Private(true),
+            Attribute::Sensitive(true),
+            Attribute::Extractable(false),
+            Attribute::Sign(true),
+        ]);
+        let (public, _) = session
+            .generate_key_pair(
+                &Mechanism::EccKeyPairGen,
+                &public_template,
+                &private_template,
+            )
+            .map_err(|e| token_error("key generation", e))?;
+        ec_point(&session, public)
+    }
+
+    fn public_key(&self, label: &str) -> PyResult<Vec<u8>> {
+        let key = KeyUri::parse(label)?;
+        let session = key.session()?;
+        let public = find_object(&session, &key, ObjectClass::PUBLIC_KEY)?;
+        ec_point(&session, public)
+    }
+
+    fn delete_key(&self, label: &str) -> PyResult<bool> {
+        let key = KeyUri::parse(label)?;
+        let session = key.session()?;
+        let mut deleted = false;
+        for class in [
+            ObjectClass::PRIVATE_KEY,
+            ObjectClass::PUBLIC_KEY,
+            ObjectClass::SECRET_KEY,
+        ] {
+            for handle in find_objects(&session, &key, class)? {
+                session
+                    .destroy_object(handle)
+                    .map_err(|e| token_error("object deletion", e))?;
+                deleted = true;
+            }
+        }
+        Ok(deleted)
+    }
+}
+
+impl Signer for Pkcs11Provider {
+    fn signature_algorithm(&self) -> &'static str {
+        "ecdsa-p256-sha256"
+    }
+
+    fn sign(&self, label: &str, msg: &[u8]) -> PyResult<Vec<u8>> {
+        let key = KeyUri::parse(label)?;
+        let session = key.session()?;
+        let private = find_object(&session, &key, ObjectClass::PRIVATE_KEY)?;
+        // CKM_ECDSA signs a precomputed digest and returns r || s
+        let digest = Sha256::digest(msg);
+        let raw = session
+            .sign(&Mechanism::Ecdsa, private, &digest)
+            .map_err(|e| token_error("signing", e))?;
+        let signature = P256Signature::from_slice(&raw)
+            .map_err(|e| value_error(format!("Token returned an invalid signature: {:?}", e)))?;
+        Ok(signature.to_der().as_bytes().to_vec())
+    }
+}
+
+/// Creates a non-extractable AES-256 wrapping key on a PKCS#11 token under the given URI
+#[pyfunction]
+pub fn hsm_generate_wrapping_key(py: Python<'_>, uri: &str) -> PyResult<()> {
+    crate::readonly::check_writable("HSM key generation")?;
+    let key = KeyUri::parse(uri)?;
+    py.allow_threads(|| {
+        let session = key.session()?;
+        ensure_absent(&session, &key, ObjectClass::SECRET_KEY)?;
+        let mut template = new_object_attributes(&key);
+        template.extend([
+            Attribute::Class(ObjectClass::SECRET_KEY),
+            Attribute::KeyType(KeyType::AES),
+            Attribute::ValueLen(32.into()),
+            Attribute::Private(true),
+            Attribute::Sensitive(true),
+            Attribute::Extractable(false),
+            Attribute::Encrypt(true),
+            Attribute::Decrypt(true),
+        ]);
+        session
+            .generate_key(&Mechanism::AesKeyGen, &template)
+            .map_err(|e| token_error("key generation", e))?;
+        Ok(())
+    })
+}
+
+/// Encrypts key material under a token-resident AES key. Returns nonce | ciphertext | tag
+#[pyfunction]
+#[pyo3(signature = (uri, key_material, aad=Vec::new()))]
+pub fn hsm_wrap_key(
+    py: Python<'_>,
+    uri: &str,
+    key_material: Vec<u8>,
+    aad: Vec<u8>,
+) -> PyResult<Vec<u8>> {
+    let key = KeyUri::parse(uri)?;
+    let key_material = Zeroizing::new(key_material);
+    py.allow_threads(|| {
+        let session = key.session()?;
+        let wrapping_key = find_object(&session, &key, ObjectClass::SECRET_KEY)?;
+        let mut nonce = [0u8; GCM_NONCE_LEN];
+        rand::thread_rng().fill_bytes(&mut nonce);
+        let mut iv = nonce;
+        let params = GcmParams::new(&mut iv, &aad, ((GCM_TAG_LEN * 8) as u64).into())
+            .map_err(|e| token_error("GCM setup", e))?;
+        let ciphertext = session
+            .encrypt(&Mechanism::AesGcm(params), wrapping_key, &key_material)
+            .map_err(|e| token_error("wrap", e))?;
+        let mut out = Vec::with_capacity(GCM_NONCE_LEN + ciphertext.len());
+        out.extend_from_slice(&nonce);
+        out.extend_from_slice(&ciphertext);
+        Ok(out)
+    })
+}
+
+/// Decrypts key material wrapped by hsm_wrap_key; the unwrapping happens on the token
+#[pyfunction]
+#[pyo3(signature = (uri, wrapped, aad=Vec::new()))]
+pub fn hsm_unwrap_key(
+    py: Python<'_>,
+    uri: &str,
+    wrapped: Vec<u8>,
+    aad: Vec<u8>,
+) -> PyResult<Vec<u8>> {
+    let key = KeyUri::parse(uri)?;
+    if wrapped.len() < GCM_NONCE_LEN + GCM_TAG_LEN {
+        return Err(value_error("Wrapped key is too short".to_string()));
+    }
+    py.allow_threads(|| {
+        let session = key.session()?;
+        let wrapping_key = find_object(&session, &key, ObjectClass::SECRET_KEY)?;
+        let (nonce, ciphertext) = wrapped.split_at(GCM_NONCE_LEN);
+        let mut iv = [0u8; GCM_NONCE_LEN];
+        iv.copy_from_slice(nonce);
+        let params = GcmParams::new(&mut iv, &aad, ((GCM_TAG_LEN * 8) as u64).into())
+            .map_err(|e| token_error("GCM setup", e))?;
+        session
+            .decrypt(&Mechanism::AesGcm(params), wrapping_key, ciphertext)
+            .map_err(|e| match e {
+                Pkcs11Error::Pkcs11(RvError::EncryptedDataInvalid, _)
+                | Pkcs11Error::Pkcs11(RvError::EncryptedDataLenRange, _) => {
+                    value_error("Unwrap failed: wrong key, AAD or corrupted data".to_string())
+                }
+                e => token_error("unwrap", e),
+            })
+    })
+}
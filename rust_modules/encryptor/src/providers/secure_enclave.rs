@@ -0,0 +1,114 @@
+use pyo3::prelude::*;
+use security_framework::item::{
+    ItemClass, ItemSearchOptions, KeyClass, Location, Reference, SearchResult,
+};
+use security_framework::key::{Algorithm, GenerateKeyOptions, KeyType, SecKey, Token};
+
+use super::{KeyProvider, Signer};
+
+// OSStatus returned by keychain searches that match nothing.
+const ERR_SEC_ITEM_NOT_FOUND: i32 = -25300;
+
+/// Apple Secure Enclave provider: non-extractable P-256 keys in the data protection keychain.
+pub(crate) struct SecureEnclaveProvider;
+
+fn find_private_key(label: &str) -> PyResult<Option<SecKey>> {
+    let results = ItemSearchOptions::new()
+        .class(ItemClass::key())
+        .key_class(KeyClass::private())
+        .label(label)
+        .load_refs(true)
+        .search();
+    match results {
+        Ok(items) => Ok(items.into_iter().find_map(|item| match item {
+            SearchResult::Ref(Reference::Key(key)) => Some(key),
+            _ => None,
+        })),
+        Err(e) if e.code() == ERR_SEC_ITEM_NOT_FOUND => Ok(None),
+        Err(e) => Err(pyo3::exceptions::PyRuntimeError::new_err(format!(
+            "Keychain search failed: {}",
+            e
+        ))),
+    }
+}
+
+fn private_key(label: &str) -> PyResult<SecKey> {
+    find_private_key(label)?.ok_or_else(|| {
+        pyo3::exceptions::PyValueError::new_err(format!(
+            "No Secure Enclave key labeled '{}'",
+            label
+        ))
+    })
+}
+
+/// Uncompressed SEC1 (X9.63) encoding of the public half of a Secure Enclave key.
+fn public_key_bytes(key: &SecKey) -> PyResult<Vec<u8>> {
+    key.public_key()
+        .and_then(|public| public.external_representation())
+        .map(|data| data.bytes().to_vec())
+        .ok_or_else(|| {
+            pyo3::exceptions::PyRuntimeError::new_err("Secure Enclave public key unavailable")
+        })
+}
+
+impl KeyProvider for SecureEnclaveProvider {
+    fn name(&self) -> &'static str {
+        "secure-enclave"
+    }
+
+    fn generate_key(&self, label: &str) -> PyResult<Vec<u8>> {
+        if find_private_key(label)?.is_some() {
+            return Err(pyo3::exceptions::PyValueError::new_err(format!(
+                "A Secure Enclave key labeled '{}' already exists",
+                label
+            )));
+        }
+        let mut options = GenerateKeyOptions::default();
+        options
+            .set_key_type(KeyType::ec_sec_prime_random())
+            .set_size_in_bits(256)
+            .set_label(label)
+            .set_token(Token::SecureEnclave)
+            .set_location(Location::DataProtectionKeychain);
+        let key = SecKey::new(&options).map_err(|e| {
+            pyo3::exceptions::PyRuntimeError::new_err(format!(
+                "Secure Enclave key generation failed: {}",
+                e
+            ))
+        })?;
+        public_key_bytes(&key)
+    }
+
+    fn public_key(&self, label: &str) -> PyResult<Vec<u8>> {
+        public_key_bytes(&private_key(label)?)
+    }
+
+    fn delete_key(&self, label: &str) -> PyResult<bool> {
+        match find_private_key(label)? {
+            Some(key) => key.delete().map(|_| true).map_err(|e| {
+                pyo3::exceptions::PyRuntimeError::new_err(format!(
+                    "Secure Enclave key deletion failed: {}",
+                    e
+                ))
+            }),
+            None => Ok(false),
+        }
+    }
+}
+
+impl Signer for SecureEnclaveProvider {
+    fn signature_algorithm(&self) -> &'static str {
+        "ecdsa-p256-sha256"
+    }
+
+    fn sign(&self, label: &str, msg: &[u8]) -> PyResult<Vec<u8>> {
+        private_key(label)?
+            .create_signature(Algorithm::ECDSASignatureMessageX962SHA256, msg)
+            .map_err(|e| {
+                pyo3::exceptions::PyRuntimeError::new_err(format!(
+                    "Secure Enclave signing failed: {}",
+                    e
+                ))
+            })
+    }
+}
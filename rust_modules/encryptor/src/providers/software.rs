@@ -0,0 +1,75 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, MutexGuard, OnceLock};
+
+use pyo3::prelude::*;
+
+use super::{KeyProvider, Signer};
+use crate::handles::{KeyAlgorithm, StoredKey};
+use crate::params::FalconParams;
+use crate::usage::{KeyOperation, KeyUsagePolicy};
+
+/// In-process provider: labeled Falcon-1024 keys held in Rust memory like a `SecretKeyHandle`.
+pub(crate) struct SoftwareProvider;
+
+const PARAMS: FalconParams = FalconParams::Falcon1024;
+
+// Process-wide, like the rest of the module's state. Signing clones a key out, so the lock is
+// never held while one is in use.
+fn labels() -> MutexGuard<'static, HashMap<String, Arc<StoredKey>>> {
+    static LABELS: OnceLock<Mutex<HashMap<String, Arc<StoredKey>>>> = OnceLock::new();
+    LABELS
+        .get_or_init(|| Mutex::new(HashMap::new()))
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+fn key_for(label: &str) -> PyResult<Arc<StoredKey>> {
+    labels().get(label).cloned().ok_or_else(|| {
+        pyo3::exceptions::PyValueError::new_err(format!("No software key labeled '{}'", label))
+    })
+}
+
+impl KeyProvider for SoftwareProvider {
+    fn name(&self) -> &'static str {
+        "software"
+    }
+
+    fn generate_key(&self, label: &str) -> PyResult<Vec<u8>> {
+        let mut labels = labels();
+        if labels.contains_key(label) {
+            return Err(pyo3::exceptions::PyValueError::new_err(format!(
+                "A software key labeled '{}' already exists",
+                label
+            )));
+        }
+        let key = StoredKey::generate(
+            KeyAlgorithm::Falcon(PARAMS),
+            false,
+            false,
+            KeyUsagePolicy::default(),
+        );
+        let public_key = key.public_key.clone();
+        labels.insert(label.to_string(), Arc::new(key));
+        Ok(public_key)
+    }
+
+    fn public_key(&self, label: &str) -> PyResult<Vec<u8>> {
+        Ok(key_for(label)?.public_key.clone())
+    }
+
+    fn delete_key(&self, label: &str) -> PyResult<bool> {
+        Ok(labels().remove(label).is_some())
+    }
+}
+
+impl Signer for SoftwareProvider {
+    fn signature_algorithm(&self) -> &'static str {
+        "falcon1024"
+    }
+
+    fn sign(&self, label: &str, msg: &[u8]) -> PyResult<Vec<u8>> {
+        let key = key_for(label)?;
+        key.authorize(KeyOperation::Sign, true)?;
+        crate::falcon_sign(msg, &key.secret_key, PARAMS)
+    }
+}
@@ -0,0 +1,195 @@
+use std::marker::PhantomData;
+use std::ops::Deref;
+
+use pyo3::prelude::*;
+use pyo3::types::PyBytes;
+use zeroize::Zeroizing;
+
+// Key arguments that also accept pyca/cryptography key objects. Objects are turned into the
+// bytes this crate uses through cryptography's own standard serializations: raw 32-byte keys
+// for Ed25519 / X25519 (RFC 8032 seed, RFC 7748 scalar), PKCS#8 and SubjectPublicKeyInfo DER
+// for RSA. cryptography is only imported when a non-bytes argument shows up, so it stays an
+// optional dependency. Converting the other way is left to Python (core.crypto.pyca_adapters).
+const SERIALIZATION: &str = "cryptography.hazmat.primitives.serialization";
+
+/// A classical algorithm whose keys pyca/cryptography can hold.
+pub(crate) trait ClassicalAlgorithm {
+    const NAME: &'static str;
+    /// Module and class names of the cryptography private / public key types.
+    const MODULE: &'static str;
+    const PRIVATE_CLASS: &'static str;
+    const PUBLIC_CLASS: &'static str;
+    /// Raw encoding if true, otherwise DER (PKCS#8 / SubjectPublicKeyInfo).
+    const RAW: bool;
+}
+
+pub(crate) struct Ed25519;
+pub(crate) struct X25519;
+pub(crate) struct Rsa;
+
+impl ClassicalAlgorithm for Ed25519 {
+    const NAME: &'static str = "Ed25519";
+    const MODULE: &'static str = "cryptography.hazmat.primitives.asymmetric.ed25519";
+    const PRIVATE_CLASS: &'static str = "Ed25519PrivateKey";
+    const PUBLIC_CLASS: &'static str = "Ed25519PublicKey";
+    const RAW: bool = true;
+}
+
+impl ClassicalAlgorithm for X25519 {
+    const NAME: &'static str = "X25519";
+    const MODULE: &'static str = "cryptography.hazmat.primitives.asymmetric.x25519";
+    const PRIVATE_CLASS: &'static str = "X25519PrivateKey";
+    const PUBLIC_CLASS: &'static str = "X25519PublicKey";
+    const RAW: bool = true;
+}
+
+impl ClassicalAlgorithm for Rsa {
+    const NAME: &'static str = "RSA";
+    const MODULE: &'static str = "cryptography.hazmat.primitives.asymmetric.rsa";
+    const PRIVATE_CLASS: &'static str = "RSAPrivateKey";
+    const PUBLIC_CLASS: &'static str = "RSAPublicKey";
+    const RAW: bool = false;
+}
+
+/// Whether `ob` is an instance of `class` in `module`; false if cryptography isn't installed.
+fn is_instance(ob: &Bound<'_, PyAny>, module: &str, class: &str) -> PyResult<bool> {
+    match ob.py().import(module) {
+        Ok(module) => ob.is_instance(&module.getattr(class)?),
+        Err(_) => Ok(false),
+    }
+}
+
+fn serialize_private<A: ClassicalAlgorithm>(ob: &Bound<'_, PyAny>) -> PyResult<Zeroizing<Vec<u8>>> {
+    let serialization = ob.py().import(SERIALIZATION)?;
+    let (encoding, format) = if A::RAW {
+        ("Raw", "Raw")
+    } else {
+        ("DER", "PKCS8")
+    };
+    let encoded = ob.call_method1(
+        "private_bytes",
+        (
+            serialization.getattr("Encoding")?.getattr(encoding)?,
+            serialization.getattr("PrivateFormat")?.getattr(format)?,
+            serialization.getattr("NoEncryption")?.call0()?,
+        ),
+    )?;
+    Ok(Zeroizing::new(
+        encoded.downcast::<PyBytes>()?.as_bytes().to_vec(),
+    ))
+}
+
+fn serialize_public<A: ClassicalAlgorithm>(ob: &Bound<'_, PyAny>) -> PyResult<Vec<u8>> {
+    let serialization = ob.py().import(SERIALIZATION)?;
+    let (encoding, format) = if A::RAW {
+        ("Raw", "Raw")
+    } else {
+        ("DER", "SubjectPublicKeyInfo")
+    };
+    let encoded = ob.call_method1(
+        "public_bytes",
+        (
+            serialization.getattr("Encoding")?.getattr(encoding)?,
+            serialization.getattr("PublicFormat")?.getattr(format)?,
+        ),
+    )?;
+    Ok(encoded.downcast::<PyBytes>()?.as_bytes().to_vec())
+}
+
+/// Serializes any supported cryptography private key, for arguments whose algorithm is only
+/// known at run time. None if `ob` isn't one.
+pub(crate) fn any_private_key(ob: &Bound<'_, PyAny>) -> PyResult<Option<Zeroizing<Vec<u8>>>> {
+    if is_instance(ob, Ed25519::MODULE, Ed25519::PRIVATE_CLASS)? {
+        serialize_private::<Ed25519>(ob).map(Some)
+    } else if is_instance(ob, X25519::MODULE, X25519::PRIVATE_CLASS)? {
+        serialize_private::<X25519>(ob).map(Some)
+    } else if is_instance(ob, Rsa::MODULE, Rsa::PRIVATE_CLASS)? {
+        serialize_private::<Rsa>(ob).map(Some)
+    } else {
+        Ok(None)
+    }
+}
+
+/// Secret key argument: bytes (wiped after the call) or a cryptography private key of
+/// algorithm `A`.
+pub(crate) struct PrivateKeyArg<A: ClassicalAlgorithm> {
+    bytes: Zeroizing<Vec<u8>>,
+    _algorithm: PhantomData<A>,
+}
+
+/// Public key argument: bytes or a cryptography public key of algorithm `A`.
+pub(crate) struct PublicKeyArg<A: ClassicalAlgorithm> {
+    bytes: Vec<u8>,
+    _algorithm: PhantomData<A>,
+}
+
+impl<A: ClassicalAlgorithm> Deref for PrivateKeyArg<A> {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        &self.bytes
+    }
+}
+
+impl<A: ClassicalAlgorithm> Deref for PublicKeyArg<A> {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        &self.bytes
+    }
+}
+
+fn wrong_type(ob: &Bound<'_, PyAny>, expected: &str) -> PyErr {
+    let got = ob
+        .get_type()
+        .name()
+        .map(|name| name.to_string())
+        .unwrap_or_else(|_| "object".to_string());
+    pyo3::exceptions::PyTypeError::new_err(format!("Expected {}, got {}", expected, got))
+}
+
+impl<'py, A: ClassicalAlgorithm> FromPyObject<'py> for PrivateKeyArg<A> {
+    fn extract_bound(ob: &Bound<'py, PyAny>) -> PyResult<Self> {
+        let bytes = if let Ok(bytes) = ob.extract::<Vec<u8>>() {
+            Zeroizing::new(bytes)
+        } else if is_instance(ob, A::MODULE, A::PRIVATE_CLASS)? {
+            serialize_private::<A>(ob)?
+        } else {
+            return Err(wrong_type(
+                ob,
+                &format!(
+                    "{} secret key bytes or a cryptography {}",
+                    A::NAME,
+                    A::PRIVATE_CLASS
+                ),
+            ));
+        };
+        Ok(PrivateKeyArg {
+            bytes,
+            _algorithm: PhantomData,
+        })
+    }
+}
+
+impl<'py, A: ClassicalAlgorithm> FromPyObject<'py> for PublicKeyArg<A> {
+    fn extract_bound(ob: &Bound<'py, PyAny>) -> PyResult<Self> {
+        let bytes = if let Ok(bytes) = ob.extract::<Vec<u8>>() {
+            bytes
+        } else if is_instance(ob, A::MODULE, A::PUBLIC_CLASS)? {
+            serialize_public::<A>(ob)?
+        } else {
+            return Err(wrong_type(
+                ob,
+                &format!(
+                    "{} public key bytes or a cryptography {}",
+                    A::NAME,
+                    A::PUBLIC_CLASS
+                ),
+            ));
+        };
+        Ok(PublicKeyArg {
+            bytes,
+            _algorithm: PhantomData,
+        })
+    }
+}
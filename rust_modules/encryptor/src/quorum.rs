@@ -0,0 +1,312 @@
+use std::collections::{BTreeMap, HashSet};
+
+use pyo3::prelude::*;
+use sha3::{Digest, Sha3_256};
+
+use crate::handles::SigningKeyArg;
+use crate::limits;
+use crate::schemes::SignatureScheme;
+use crate::wire::{put_var16, put_var8, Reader};
+
+// Quorum certificate layout (v1):
+//   magic "RQQC" | version u8 | policy id (32) | decision digest (var8)
+//   | signer bitmap (var8, bit i = trustee i) | one signature (var16) per set bit, in order
+// Public keys are not repeated; the certificate is only meaningful together with its policy.
+pub(crate) const MAGIC: &[u8; 4] = b"RQQC";
+pub(crate) const VERSION: u8 = 1;
+const POLICY_LABEL: &[u8] = b"reliquary/quorum-policy/v1";
+const VOTE_LABEL: &[u8] = b"reliquary/quorum-vote/v1";
+const MAX_TRUSTEES: usize = 255;
+
+struct Trustee {
+    name: String,
+    scheme: SignatureScheme,
+    public_key: Vec<u8>,
+}
+
+/// M-of-N approval policy: an ordered list of trustees (name, signature scheme, public key) and
+/// the number of distinct trustee signatures a decision needs. Votes and certificates are bound
+/// to the policy id, so they can't be replayed under a different trustee set or threshold.
+#[pyclass(frozen, module = "reliquary_encryptor")]
+pub struct QuorumPolicy {
+    trustees: Vec<Trustee>,
+    threshold: usize,
+    context: Vec<u8>,
+    policy_id: [u8; 32],
+}
+
+impl QuorumPolicy {
+    fn trustee_index(&self, name: &str) -> PyResult<usize> {
+        self.trustees
+            .iter()
+            .position(|t| t.name == name)
+            .ok_or_else(|| {
+                pyo3::exceptions::PyValueError::new_err(format!("Unknown trustee '{}'", name))
+            })
+    }
+
+    fn vote_message(&self, digest: &[u8]) -> PyResult<Vec<u8>> {
+        let mut msg = VOTE_LABEL.to_vec();
+        msg.extend_from_slice(&self.policy_id);
+        put_var8(&mut msg, digest).map_err(pyo3::exceptions::PyValueError::new_err)?;
+        Ok(msg)
+    }
+}
+
+fn check_digest(digest: &[u8]) -> PyResult<()> {
+    if digest.is_empty() || digest.len() > 64 {
+        return Err(pyo3::exceptions::PyValueError::new_err(
+            "Decision digest must be 1 to 64 bytes",
+        ));
+    }
+    Ok(())
+}
+
+#[pymethods]
+impl QuorumPolicy {
+    /// `trustees` is a list of (name, scheme, public_key); scheme is one of "falcon512",
+    /// "falcon1024", "dilithium5" or "ed25519"
+    #[new]
+    #[pyo3(signature = (trustees, threshold, context = Vec::new()))]
+    fn new(
+        trustees: Vec<(String, String, Vec<u8>)>,
+        threshold: usize,
+        context: Vec<u8>,
+    ) -> PyResult<Self> {
+        if trustees.is_empty() || trustees.len() > MAX_TRUSTEES {
+            return Err(pyo3::exceptions::PyValueError::new_err(format!(
+                "A quorum policy needs 1 to {} trustees",
+                MAX_TRUSTEES
+            )));
+        }
+        if threshold == 0 || threshold > trustees.len() {
+            return Err(pyo3::exceptions::PyValueError::new_err(format!(
+                "Threshold must be between 1 and {}",
+                trustees.len()
+            )));
+        }
+
+        let mut seen_names = HashSet::new();
+        let mut seen_keys = HashSet::new();
+        let mut parsed = Vec::with_capacity(trustees.len());
+        for (name, scheme, public_key) in trustees {
+            if !seen_names.insert(name.clone()) {
+                return Err(pyo3::exceptions::PyValueError::new_err(format!(
+                    "Duplicate trustee name '{}'",
+                    name
+                )));
+            }
+            if !seen_keys.insert(public_key.clone()) {
+                return Err(pyo3::exceptions::PyValueError::new_err(format!(
+                    "Trustee '{}' reuses another trustee's public key",
+                    name
+                )));
+            }
+            parsed.push(Trustee {
+                name,
+                scheme: SignatureScheme::parse(&scheme)?,
+                public_key,
+            });
+        }
+
+        let mut encoded = POLICY_LABEL.to_vec();
+        encoded.push(threshold as u8);
+        put_var16(&mut encoded, &context).map_err(pyo3::exceptions::PyValueError::new_err)?;
+        encoded.push(parsed.len() as u8);
+        for t in &parsed {
+            put_var8(&mut encoded, t.name.as_bytes())
+                .map_err(pyo3::exceptions::PyValueError::new_err)?;
+            encoded.push(t.scheme.wire_id());
+            put_var16(&mut encoded, &t.public_key)
+                .map_err(pyo3::exceptions::PyValueError::new_err)?;
+        }
+
+        Ok(QuorumPolicy {
+            trustees: parsed,
+            threshold,
+            context,
+            policy_id: Sha3_256::digest(&encoded).into(),
+        })
+    }
+
+    #[getter]
+    pub(crate) fn threshold(&self) -> usize {
+        self.threshold
+    }
+
+    #[getter]
+    fn context(&self) -> Vec<u8> {
+        self.context.clone()
+    }
+
+    /// Trustee names in policy order
+    #[getter]
+    fn trustees(&self) -> Vec<String> {
+        self.trustees.iter().map(|t| t.name.clone()).collect()
+    }
+
+    /// SHA3-256 of the canonical policy encoding
+    #[getter]
+    fn policy_id(&self) -> Vec<u8> {
+        self.policy_id.to_vec()
+    }
+
+    /// Exact bytes a trustee signs to approve `digest`, for trustees whose keys live in an
+    /// external signer (e.g. a key provider)
+    fn signing_message(&self, digest: Vec<u8>) -> PyResult<Vec<u8>> {
+        check_digest(&digest)?;
+        self.vote_message(&digest)
+    }
+}
+
+/// Produces one trustee's vote on a decision digest
+#[pyfunction]
+pub fn sign_quorum_vote(
+    py: Python<'_>,
+    policy: &QuorumPolicy,
+    trustee: &str,
+    digest: Vec<u8>,
+    sk_bytes: SigningKeyArg,
+) -> PyResult<Vec<u8>> {
+    check_digest(&digest)?;
+    let trustee = &policy.trustees[policy.trustee_index(trustee)?];
+    let msg = policy.vote_message(&digest)?;
+    py.allow_threads(|| trustee.scheme.sign(&msg, &sk_bytes))
+}
+
+/// Verifies trustee votes and packs them into a quorum certificate. `votes` is a list of
+/// (trustee_name, signature). Raises ValueError if any vote is invalid or duplicated, or if
+/// fewer than `policy.threshold` trustees voted
+#[pyfunction]
+pub fn aggregate_quorum_certificate(
+    py: Python<'_>,
+    policy: &QuorumPolicy,
+    digest: Vec<u8>,
+    votes: Vec<(String, Vec<u8>)>,
+) -> PyResult<Vec<u8>> {
+    check_digest(&digest)?;
+    let msg = policy.vote_message(&digest)?;
+    let mut by_index = BTreeMap::new();
+    for (name, signature) in votes {
+        let index = policy.trustee_index(&name)?;
+        if by_index.insert(index, signature).is_some() {
+            return Err(pyo3::exceptions::PyValueError::new_err(format!(
+                "Duplicate vote from trustee '{}'",
+                name
+            )));
+        }
+    }
+
+    py.allow_threads(|| {
+        for (&index, signature) in &by_index {
+            let trustee = &policy.trustees[index];
+            if !trustee
+                .scheme
+                .verify(&msg, signature, &trustee.public_key)?
+            {
+                return Err(pyo3::exceptions::PyValueError::new_err(format!(
+                    "Invalid vote signature from trustee '{}'",
+                    trustee.name
+                )));
+            }
+        }
+        if by_index.len() < policy.threshold {
+            return Err(pyo3::exceptions::PyValueError::new_err(format!(
+                "Quorum not reached: {} of {} required votes",
+                by_index.len(),
+                policy.threshold
+            )));
+        }
+        let mut bitmap = vec![0u8; policy.trustees.len().div_ceil(8)];
+        for &index in by_index.keys() {
+            bitmap[index / 8] |= 1 << (index % 8);
+        }
+        let mut cert = MAGIC.to_vec();
+        cert.push(VERSION);
+        cert.extend_from_slice(&policy.policy_id);
+        put_var8(&mut cert, &digest).map_err(pyo3::exceptions::PyValueError::new_err)?;
+        put_var8(&mut cert, &bitmap).map_err(pyo3::exceptions::PyValueError::new_err)?;
+        for signature in by_index.values() {
+            put_var16(&mut cert, signature).map_err(pyo3::exceptions::PyValueError::new_err)?;
+        }
+        Ok(cert)
+    })
+}
+
+/// Checks a certificate against its policy and digest; see `verify_quorum_certificate`.
+pub(crate) fn check_certificate(
+    policy: &QuorumPolicy,
+    certificate: &[u8],
+    digest: &[u8],
+) -> PyResult<(bool, Vec<String>)> {
+    limits::check_input("Quorum certificate", certificate.len())
+        .map_err(pyo3::exceptions::PyValueError::new_err)?;
+    let mut r = Reader::new(certificate);
+    if r.bytes(4)
+        .map_err(pyo3::exceptions::PyValueError::new_err)?
+        != MAGIC
+    {
+        return Err(pyo3::exceptions::PyValueError::new_err(
+            "Not a quorum certificate",
+        ));
+    }
+    let version = r.u8().map_err(pyo3::exceptions::PyValueError::new_err)?;
+    if version != VERSION {
+        return Err(pyo3::exceptions::PyValueError::new_err(format!(
+            "Unsupported quorum certificate version {}",
+            version
+        )));
+    }
+    if r.bytes(32)
+        .map_err(pyo3::exceptions::PyValueError::new_err)?
+        != policy.policy_id
+    {
+        return Ok((false, Vec::new()));
+    }
+    let cert_digest = r.var8().map_err(pyo3::exceptions::PyValueError::new_err)?;
+    let bitmap = r.var8().map_err(pyo3::exceptions::PyValueError::new_err)?;
+    if bitmap.len() != policy.trustees.len().div_ceil(8) {
+        return Err(pyo3::exceptions::PyValueError::new_err(
+            "Signer bitmap does not match the policy".to_string(),
+        ));
+    }
+
+    let msg = policy.vote_message(cert_digest)?;
+    let mut signers = Vec::new();
+    let mut valid = cert_digest == digest;
+    for (index, trustee) in policy.trustees.iter().enumerate() {
+        if bitmap[index / 8] & (1 << (index % 8)) == 0 {
+            continue;
+        }
+        let signature = r.var16().map_err(pyo3::exceptions::PyValueError::new_err)?;
+        valid &= trustee
+            .scheme
+            .verify(&msg, signature, &trustee.public_key)?;
+        signers.push(trustee.name.clone());
+    }
+    if !r.is_empty() {
+        return Err(pyo3::exceptions::PyValueError::new_err(
+            "Trailing bytes after quorum certificate".to_string(),
+        ));
+    }
+    // Bits beyond the trustee count must be clear
+    let extra_bits = bitmap
+        .iter()
+        .map(|b| b.count_ones() as usize)
+        .sum::<usize>();
+    valid &= extra_bits == signers.len();
+    Ok((valid && signers.len() >= policy.threshold, signers))
+}
+
+/// Verifies a quorum certificate against its policy and the decision digest. Returns
+/// (valid, signer_names); valid requires every signature to check out and at least
+/// `policy.threshold` distinct signers
+#[pyfunction]
+pub fn verify_quorum_certificate(
+    py: Python<'_>,
+    policy: &QuorumPolicy,
+    certificate: Vec<u8>,
+    digest: Vec<u8>,
+) -> PyResult<(bool, Vec<String>)> {
+    py.allow_threads(|| check_certificate(policy, &certificate, &digest))
+}
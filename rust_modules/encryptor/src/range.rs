@@ -0,0 +1,229 @@
+use std::sync::OnceLock;
+
+use bulletproofs::{BulletproofGens, RangeProof};
+use curve25519_dalek::ristretto::{CompressedRistretto, RistrettoPoint};
+use curve25519_dalek::scalar::Scalar;
+use merlin::Transcript;
+use pyo3::prelude::*;
+
+use crate::batch::parallel_map;
+use crate::pedersen::{self, decode_blinding, decode_commitment};
+
+// Bulletproofs range proofs on Pedersen commitments over Ristretto, for statements like "my
+// trust score is at least the threshold" or "the requested amount is at most the limit"
+// without revealing the value. Commitments are those of `pedersen`, V = v*B + r*B_blinding.
+// Bounds are shifted into
+// the commitment: lower <= v is a range proof that V - lower*B opens to something in
+// [0, 2^bits), and v <= upper one that upper*B - V does (blinding -r); with both bounds the
+// two proofs are aggregated into one. Values are integers, so fractional scores are scaled
+// first (e.g. to thousandths).
+//
+// Proof layout: "RQRP" | 1 | bits u8 | bounds u8 (1 lower, 2 upper, 3 both) | dalek proof.
+// The transcript covers the commitment, the bounds and a caller-chosen context, so a proof
+// can't be replayed against another commitment, threshold or verifier.
+const PROOF_MAGIC: &[u8; 4] = b"RQRP";
+const FORMAT_VERSION: u8 = 1;
+const HEADER_BYTES: usize = 7;
+const LOWER: u8 = 1;
+const UPPER: u8 = 2;
+
+/// (proof, commitment, lower, upper) for range_verify_batch
+type BatchItem = (Vec<u8>, Vec<u8>, Option<u64>, Option<u64>);
+
+fn bulletproof_generators() -> &'static BulletproofGens {
+    static GENS: OnceLock<BulletproofGens> = OnceLock::new();
+    GENS.get_or_init(|| BulletproofGens::new(64, 2))
+}
+
+fn value_error(message: impl Into<String>) -> PyErr {
+    pyo3::exceptions::PyValueError::new_err(message.into())
+}
+
+fn check_bits(bits: usize) -> PyResult<()> {
+    if [8, 16, 32, 64].contains(&bits) {
+        Ok(())
+    } else {
+        Err(value_error("bits must be 8, 16, 32 or 64"))
+    }
+}
+
+fn bounds_flags(lower: Option<u64>, upper: Option<u64>) -> PyResult<u8> {
+    match (lower, upper) {
+        (None, None) => Err(value_error("A range proof needs a lower or an upper bound")),
+        (Some(lower), Some(upper)) if lower > upper => {
+            Err(value_error("The lower bound is above the upper bound"))
+        }
+        _ => Ok(u8::from(lower.is_some()) * LOWER + u8::from(upper.is_some()) * UPPER),
+    }
+}
+
+fn transcript(
+    commitment: &RistrettoPoint,
+    lower: Option<u64>,
+    upper: Option<u64>,
+    context: &[u8],
+) -> Transcript {
+    let mut transcript = Transcript::new(b"reliquary-range-proof");
+    transcript.append_message(b"context", context);
+    transcript.append_message(b"commitment", commitment.compress().as_bytes());
+    for (label, bound) in [(&b"lower"[..], lower), (&b"upper"[..], upper)] {
+        if let Some(bound) = bound {
+            transcript.append_u64(label, bound);
+        }
+    }
+    transcript
+}
+
+/// The commitments the shifted values open to: V - lower*B and upper*B - V.
+fn shifted(
+    commitment: &RistrettoPoint,
+    lower: Option<u64>,
+    upper: Option<u64>,
+) -> Vec<RistrettoPoint> {
+    let base = pedersen::generators().B;
+    lower
+        .map(|lower| commitment - Scalar::from(lower) * base)
+        .into_iter()
+        .chain(upper.map(|upper| Scalar::from(upper) * base - commitment))
+        .collect()
+}
+
+fn verify_one(
+    proof: &[u8],
+    commitment: &RistrettoPoint,
+    lower: Option<u64>,
+    upper: Option<u64>,
+    context: &[u8],
+) -> bool {
+    let Ok(flags) = bounds_flags(lower, upper) else {
+        return false;
+    };
+    if proof.len() < HEADER_BYTES
+        || &proof[..4] != PROOF_MAGIC
+        || proof[4] != FORMAT_VERSION
+        || proof[6] != flags
+        || check_bits(proof[5] as usize).is_err()
+    {
+        return false;
+    }
+    let Ok(range_proof) = RangeProof::from_bytes(&proof[HEADER_BYTES..]) else {
+        return false;
+    };
+    let commitments: Vec<CompressedRistretto> = shifted(commitment, lower, upper)
+        .iter()
+        .map(RistrettoPoint::compress)
+        .collect();
+    range_proof
+        .verify_multiple(
+            bulletproof_generators(),
+            pedersen::generators(),
+            &mut transcript(commitment, lower, upper, context),
+            &commitments,
+            proof[5] as usize,
+        )
+        .is_ok()
+}
+
+/// Pedersen commitment to `value` for range proofs, the same as `pedersen_commit`. Returns
+/// (commitment, blinding); the blinding is needed to prove about the value
+#[pyfunction]
+#[pyo3(signature = (value, blinding = None))]
+pub fn range_commit(value: u64, blinding: Option<Vec<u8>>) -> PyResult<(Vec<u8>, Vec<u8>)> {
+    pedersen::pedersen_commit(value, blinding)
+}
+
+/// Range proof for the commitment to `value` with `blinding`; see `range_prove`.
+pub(crate) fn prove(
+    py: Python<'_>,
+    value: u64,
+    blinding: Scalar,
+    lower: Option<u64>,
+    upper: Option<u64>,
+    bits: usize,
+    context: &[u8],
+) -> PyResult<Vec<u8>> {
+    check_bits(bits)?;
+    let flags = bounds_flags(lower, upper)?;
+    let limit = if bits == 64 {
+        u64::MAX
+    } else {
+        (1 << bits) - 1
+    };
+    let gaps = lower
+        .map(|lower| value.checked_sub(lower).map(|gap| (gap, blinding)))
+        .into_iter()
+        .chain(upper.map(|upper| upper.checked_sub(value).map(|gap| (gap, -blinding))))
+        .map(|gap| gap.filter(|(gap, _)| *gap <= limit))
+        .collect::<Option<Vec<_>>>()
+        .ok_or_else(|| value_error("Value is outside the proven range"))?;
+    let (values, blindings): (Vec<u64>, Vec<Scalar>) = gaps.into_iter().unzip();
+
+    py.allow_threads(|| {
+        let commitment = pedersen::commit(value, blinding);
+        let (proof, _) = RangeProof::prove_multiple(
+            bulletproof_generators(),
+            pedersen::generators(),
+            &mut transcript(&commitment, lower, upper, context),
+            &values,
+            &blindings,
+            bits,
+        )
+        .map_err(|e| value_error(format!("Range proof failed: {}", e)))?;
+        let mut out = PROOF_MAGIC.to_vec();
+        out.extend_from_slice(&[FORMAT_VERSION, bits as u8, flags]);
+        out.extend_from_slice(&proof.to_bytes());
+        Ok(out)
+    })
+}
+
+/// Proves that the value committed with `blinding` is at least `lower` and/or at most
+/// `upper`, each gap below 2^bits. Raises ValueError if the value is out of range
+#[pyfunction]
+#[pyo3(signature = (value, blinding, lower = None, upper = None, bits = 32, context = Vec::new()))]
+pub fn range_prove(
+    py: Python<'_>,
+    value: u64,
+    blinding: Vec<u8>,
+    lower: Option<u64>,
+    upper: Option<u64>,
+    bits: usize,
+    context: Vec<u8>,
+) -> PyResult<Vec<u8>> {
+    let blinding = decode_blinding(&blinding)?;
+    prove(py, value, blinding, lower, upper, bits, &context)
+}
+
+/// Verifies a range proof for `commitment` and the same bounds and context it was made
+/// with. Raises ValueError for a malformed commitment; a bad proof is False
+#[pyfunction]
+#[pyo3(signature = (proof, commitment, lower = None, upper = None, context = Vec::new()))]
+pub fn range_verify(
+    py: Python<'_>,
+    proof: Vec<u8>,
+    commitment: Vec<u8>,
+    lower: Option<u64>,
+    upper: Option<u64>,
+    context: Vec<u8>,
+) -> PyResult<bool> {
+    let commitment = decode_commitment(&commitment)?;
+    Ok(py.allow_threads(|| verify_one(&proof, &commitment, lower, upper, &context)))
+}
+
+/// Verifies many agents' range proofs in one call, spread over all cores. `items` are
+/// (proof, commitment, lower, upper) tuples sharing one context. Returns one bool per item;
+/// a malformed commitment is False
+#[pyfunction]
+#[pyo3(signature = (items, context = Vec::new()))]
+pub fn range_verify_batch(
+    py: Python<'_>,
+    items: Vec<BatchItem>,
+    context: Vec<u8>,
+) -> PyResult<Vec<bool>> {
+    py.allow_threads(|| {
+        parallel_map(items.len(), |i| {
+            let (proof, commitment, lower, upper) = &items[i];
+            Ok(decode_commitment(commitment)
+                .is_ok_and(|commitment| verify_one(proof, &commitment, *lower, *upper, &context)))
+        })
+    })
+}
@@ -0,0 +1,532 @@
+use std::collections::VecDeque;
+
+use aes_gcm::aead::{Aead, KeyInit, Payload};
+use aes_gcm::{Aes256Gcm, Nonce};
+use hkdf::Hkdf;
+use hmac::{Hmac, Mac};
+use pqcrypto_traits::kem::{PublicKey as KemPublicKey, SecretKey as KemSecretKey};
+use pyo3::prelude::*;
+use sha2::Sha256;
+use zeroize::Zeroizing;
+
+use crate::fingerprint::{fingerprint, FINGERPRINT_BYTES};
+use crate::params::{with_kyber, KyberParams};
+use crate::wire::{put_var16, Reader};
+
+// Double ratchet for long-lived agent channels, with Kyber encapsulation in place of the
+// Diffie-Hellman ratchet. A party starts a new sending chain by generating a fresh Kyber key
+// pair and encapsulating to the peer's latest ratchet public key; the shared secret is mixed
+// into the root key, which yields the chain key. Chain keys advance with HMAC for every message
+// and each message key is used once, so old messages stay safe after a compromise (forward
+// secrecy) and fresh key pairs lock an attacker out again after the next round trip
+// (post-compromise security).
+//
+// Message layout:
+//   magic "RQRM" | version u8 | sender ratchet public key var16 | KEM ciphertext var16
+//   | previous chain length u32 | message number u32 | AES-256-GCM ct || tag
+// The header followed by the caller's associated data is the GCM associated data.
+//
+// Session state layout (contains secret keys):
+//   magic "RQRS" | version u8 | kem id u8 | root key (32) | our public key var16
+//   | our secret key var16 | peer public key var16 (empty until known) | sending KEM ciphertext
+//   var16 | flags u8 | [sending chain key (32)] | [receiving chain key (32)] | sent u32
+//   | received u32 | previous chain length u32 | skipped count u16
+//   | skipped: peer key fingerprint (32) | message number u32 | message key (32)
+const MESSAGE_MAGIC: &[u8; 4] = b"RQRM";
+const STATE_MAGIC: &[u8; 4] = b"RQRS";
+const VERSION: u8 = 1;
+const KEY_BYTES: usize = 32;
+const ROOT_LABEL: &[u8] = b"reliquary/ratchet/v1/root";
+const MESSAGE_LABEL: &[u8] = b"reliquary/ratchet/v1/message";
+// Most message keys one message may make us skip, and most skipped keys kept overall.
+const MAX_SKIP: u32 = 1000;
+const MAX_SKIPPED_KEYS: usize = 2000;
+
+const FLAG_SENDING_CHAIN: u8 = 1;
+const FLAG_RECEIVING_CHAIN: u8 = 2;
+const FLAG_NEEDS_SEND_STEP: u8 = 4;
+
+type Key = Zeroizing<[u8; KEY_BYTES]>;
+
+#[derive(Clone)]
+struct State {
+    params: KyberParams,
+    root_key: Key,
+    our_pk: Vec<u8>,
+    our_sk: Zeroizing<Vec<u8>>,
+    their_pk: Option<Vec<u8>>,
+    send_ct: Vec<u8>,
+    send_chain: Option<Key>,
+    recv_chain: Option<Key>,
+    sent: u32,
+    received: u32,
+    previous: u32,
+    // We hold a new peer ratchet key and start a new sending chain on the next encrypt
+    needs_send_step: bool,
+    skipped: VecDeque<([u8; FINGERPRINT_BYTES], u32, Key)>,
+}
+
+fn key_from(bytes: &[u8]) -> Key {
+    let mut key = Zeroizing::new([0u8; KEY_BYTES]);
+    key.copy_from_slice(bytes);
+    key
+}
+
+/// Mixes a KEM shared secret into the root key: (new root key, chain key).
+fn kdf_root(root_key: &[u8], shared_secret: &[u8]) -> (Key, Key) {
+    let mut out = Zeroizing::new([0u8; 2 * KEY_BYTES]);
+    Hkdf::<Sha256>::new(Some(root_key), shared_secret)
+        .expand(ROOT_LABEL, out.as_mut())
+        .expect("64 bytes is a valid HKDF-SHA256 output length");
+    (key_from(&out[..KEY_BYTES]), key_from(&out[KEY_BYTES..]))
+}
+
+/// Advances a chain key: (message key, next chain key).
+fn kdf_chain(chain_key: &[u8]) -> (Key, Key) {
+    let step = |byte: u8| {
+        let mut mac =
+            <Hmac<Sha256> as Mac>::new_from_slice(chain_key).expect("HMAC accepts any key size");
+        mac.update(&[byte]);
+        key_from(&mac.finalize().into_bytes())
+    };
+    (step(1), step(2))
+}
+
+/// AES-256-GCM key and nonce for one message key. Each message key is used once, so the
+/// derived nonce never repeats under its key.
+fn message_cipher(message_key: &[u8]) -> (Aes256Gcm, [u8; 12]) {
+    let mut out = Zeroizing::new([0u8; KEY_BYTES + 12]);
+    Hkdf::<Sha256>::new(None, message_key)
+        .expand(MESSAGE_LABEL, out.as_mut())
+        .expect("44 bytes is a valid HKDF-SHA256 output length");
+    let nonce = out[KEY_BYTES..].try_into().expect("12-byte slice");
+    (Aes256Gcm::new(out[..KEY_BYTES].into()), nonce)
+}
+
+fn kyber_keypair(params: KyberParams) -> (Vec<u8>, Zeroizing<Vec<u8>>) {
+    with_kyber!(params, kyber => {
+        let (pk, sk) = kyber::keypair();
+        (pk.as_bytes().to_vec(), Zeroizing::new(sk.as_bytes().to_vec()))
+    })
+}
+
+struct Message<'a> {
+    header: &'a [u8],
+    ratchet_pk: &'a [u8],
+    kem_ciphertext: &'a [u8],
+    previous: u32,
+    number: u32,
+    ciphertext: &'a [u8],
+}
+
+fn parse_message(message: &[u8]) -> Result<Message<'_>, String> {
+    let mut r = Reader::new(message);
+    if r.bytes(MESSAGE_MAGIC.len()).ok() != Some(&MESSAGE_MAGIC[..]) {
+        return Err("Not a ratchet message".to_string());
+    }
+    let version = r.u8()?;
+    if version != VERSION {
+        return Err(format!("Unsupported ratchet message version {}", version));
+    }
+    let ratchet_pk = r.var16()?;
+    let kem_ciphertext = r.var16()?;
+    let previous = r.u32()?;
+    let number = r.u32()?;
+    let header = &message[..r.position()];
+    Ok(Message {
+        header,
+        ratchet_pk,
+        kem_ciphertext,
+        previous,
+        number,
+        ciphertext: r.rest(),
+    })
+}
+
+impl State {
+    /// Starts a new sending chain: fresh key pair, encapsulation to the peer's ratchet key.
+    fn send_step(&mut self) -> Result<(), String> {
+        let their_pk = self
+            .their_pk
+            .as_ref()
+            .ok_or("Session has no peer ratchet key yet")?;
+        let (pk, sk) = kyber_keypair(self.params);
+        let (shared_secret, ct) = crate::kyber_encapsulate(their_pk, self.params)
+            .map_err(|_| "KEM encapsulation failed")?;
+        let shared_secret = Zeroizing::new(shared_secret);
+        let (root_key, chain_key) = kdf_root(self.root_key.as_ref(), &shared_secret);
+        self.root_key = root_key;
+        self.send_chain = Some(chain_key);
+        self.send_ct = ct;
+        self.our_pk = pk;
+        self.our_sk = sk;
+        self.previous = self.sent;
+        self.sent = 0;
+        self.needs_send_step = false;
+        Ok(())
+    }
+
+    /// Stores the keys of receiving-chain messages before `until` for out-of-order delivery.
+    fn skip_to(&mut self, until: u32) -> Result<(), String> {
+        let Some(chain_key) = self.recv_chain.clone() else {
+            return Ok(());
+        };
+        if until.saturating_sub(self.received) > MAX_SKIP {
+            return Err("Ratchet message skips too many messages".to_string());
+        }
+        let their = fingerprint(self.their_pk.as_deref().unwrap_or_default());
+        let mut chain_key = chain_key;
+        while self.received < until {
+            let (message_key, next) = kdf_chain(chain_key.as_ref());
+            self.skipped.push_back((their, self.received, message_key));
+            if self.skipped.len() > MAX_SKIPPED_KEYS {
+                self.skipped.pop_front();
+            }
+            chain_key = next;
+            self.received += 1;
+        }
+        self.recv_chain = Some(chain_key);
+        Ok(())
+    }
+
+    fn encrypt(&mut self, plaintext: &[u8], ad: &[u8]) -> Result<Vec<u8>, String> {
+        if self.needs_send_step {
+            self.send_step()?;
+        }
+        let chain_key = self
+            .send_chain
+            .as_ref()
+            .ok_or("The responder can't send before the initiator's first message arrives")?;
+        let (message_key, next) = kdf_chain(chain_key.as_ref());
+        self.send_chain = Some(next);
+
+        let mut out = MESSAGE_MAGIC.to_vec();
+        out.push(VERSION);
+        put_var16(&mut out, &self.our_pk)?;
+        put_var16(&mut out, &self.send_ct)?;
+        out.extend_from_slice(&self.previous.to_be_bytes());
+        out.extend_from_slice(&self.sent.to_be_bytes());
+        self.sent = self
+            .sent
+            .checked_add(1)
+            .ok_or("Ratchet chain is exhausted")?;
+
+        let (cipher, nonce) = message_cipher(message_key.as_ref());
+        let ct = cipher
+            .encrypt(
+                Nonce::from_slice(&nonce),
+                Payload {
+                    msg: plaintext,
+                    aad: &[out.as_slice(), ad].concat(),
+                },
+            )
+            .map_err(|e| format!("Encryption error: {:?}", e))?;
+        out.extend_from_slice(&ct);
+        Ok(out)
+    }
+
+    fn decrypt(&mut self, message: &[u8], ad: &[u8]) -> Result<Vec<u8>, String> {
+        let parsed = parse_message(message)?;
+        let message_key = self.message_key(&parsed)?;
+        let (cipher, nonce) = message_cipher(message_key.as_ref());
+        cipher
+            .decrypt(
+                Nonce::from_slice(&nonce),
+                Payload {
+                    msg: parsed.ciphertext,
+                    aad: &[parsed.header, ad].concat(),
+                },
+            )
+            .map_err(|_| "Ratchet message authentication failed".to_string())
+    }
+
+    fn message_key(&mut self, message: &Message<'_>) -> Result<Key, String> {
+        let sender = fingerprint(message.ratchet_pk);
+        if let Some(i) = self
+            .skipped
+            .iter()
+            .position(|(fp, n, _)| *fp == sender && *n == message.number)
+        {
+            let (_, _, key) = self.skipped.remove(i).expect("index from position");
+            return Ok(key);
+        }
+        if self.their_pk.as_deref() != Some(message.ratchet_pk) {
+            if message.ratchet_pk.len() != self.params.public_key_bytes() {
+                return Err("Ratchet message carries a malformed public key".to_string());
+            }
+            self.skip_to(message.previous)?;
+            let shared_secret = Zeroizing::new(
+                crate::kyber_decapsulate(message.kem_ciphertext, &self.our_sk, self.params)
+                    .map_err(|_| "KEM decapsulation failed")?,
+            );
+            let (root_key, chain_key) = kdf_root(self.root_key.as_ref(), &shared_secret);
+            self.root_key = root_key;
+            self.recv_chain = Some(chain_key);
+            self.their_pk = Some(message.ratchet_pk.to_vec());
+            self.received = 0;
+            self.needs_send_step = true;
+        }
+        if message.number < self.received {
+            return Err("Ratchet message is a replay or its key was discarded".to_string());
+        }
+        self.skip_to(message.number)?;
+        let chain_key = self
+            .recv_chain
+            .as_ref()
+            .ok_or("Session has no receiving chain")?;
+        let (message_key, next) = kdf_chain(chain_key.as_ref());
+        self.recv_chain = Some(next);
+        self.received += 1;
+        Ok(message_key)
+    }
+
+    fn to_bytes(&self) -> Result<Vec<u8>, String> {
+        let mut out = STATE_MAGIC.to_vec();
+        out.push(VERSION);
+        out.push(self.params.wire_id());
+        out.extend_from_slice(self.root_key.as_ref());
+        put_var16(&mut out, &self.our_pk)?;
+        put_var16(&mut out, &self.our_sk)?;
+        put_var16(&mut out, self.their_pk.as_deref().unwrap_or_default())?;
+        put_var16(&mut out, &self.send_ct)?;
+        let mut flags = 0;
+        if self.send_chain.is_some() {
+            flags |= FLAG_SENDING_CHAIN;
+        }
+        if self.recv_chain.is_some() {
+            flags |= FLAG_RECEIVING_CHAIN;
+        }
+        if self.needs_send_step {
+            flags |= FLAG_NEEDS_SEND_STEP;
+        }
+        out.push(flags);
+        for key in [&self.send_chain, &self.recv_chain].into_iter().flatten() {
+            out.extend_from_slice(key.as_ref());
+        }
+        for n in [self.sent, self.received, self.previous] {
+            out.extend_from_slice(&n.to_be_bytes());
+        }
+        out.extend_from_slice(&(self.skipped.len() as u16).to_be_bytes());
+        for (fp, n, key) in &self.skipped {
+            out.extend_from_slice(fp);
+            out.extend_from_slice(&n.to_be_bytes());
+            out.extend_from_slice(key.as_ref());
+        }
+        Ok(out)
+    }
+
+    fn from_bytes(state: &[u8]) -> Result<Self, String> {
+        let mut r = Reader::new(state);
+        if r.bytes(STATE_MAGIC.len()).ok() != Some(&STATE_MAGIC[..]) {
+            return Err("Not a ratchet session state".to_string());
+        }
+        let version = r.u8()?;
+        if version != VERSION {
+            return Err(format!("Unsupported ratchet state version {}", version));
+        }
+        let id = r.u8()?;
+        let params = KyberParams::from_wire_id(id)
+            .ok_or_else(|| format!("Unknown ratchet KEM id {}", id))?;
+        let root_key = key_from(r.bytes(KEY_BYTES)?);
+        let our_pk = r.var16()?.to_vec();
+        let our_sk = Zeroizing::new(r.var16()?.to_vec());
+        let their_pk = Some(r.var16()?.to_vec()).filter(|pk| !pk.is_empty());
+        let send_ct = r.var16()?.to_vec();
+        if our_pk.len() != params.public_key_bytes()
+            || our_sk.len() != params.secret_key_bytes()
+            || their_pk
+                .as_ref()
+                .is_some_and(|pk| pk.len() != params.public_key_bytes())
+        {
+            return Err("Ratchet state keys don't match its parameter set".to_string());
+        }
+        let flags = r.u8()?;
+        let mut chain = |flag: u8| -> Result<Option<Key>, String> {
+            Ok(if flags & flag != 0 {
+                Some(key_from(r.bytes(KEY_BYTES)?))
+            } else {
+                None
+            })
+        };
+        let send_chain = chain(FLAG_SENDING_CHAIN)?;
+        let recv_chain = chain(FLAG_RECEIVING_CHAIN)?;
+        let (sent, received, previous) = (r.u32()?, r.u32()?, r.u32()?);
+        let count = r.u16()? as usize;
+        let mut skipped = VecDeque::with_capacity(count);
+        for _ in 0..count {
+            let fp = r
+                .bytes(FINGERPRINT_BYTES)?
+                .try_into()
+                .expect("32-byte slice");
+            skipped.push_back((fp, r.u32()?, key_from(r.bytes(KEY_BYTES)?)));
+        }
+        if !r.is_empty() {
+            return Err("Trailing data after ratchet state".to_string());
+        }
+        Ok(State {
+            params,
+            root_key,
+            our_pk,
+            our_sk,
+            their_pk,
+            send_ct,
+            send_chain,
+            recv_chain,
+            sent,
+            received,
+            previous,
+            needs_send_step: flags & FLAG_NEEDS_SEND_STEP != 0,
+            skipped,
+        })
+    }
+}
+
+fn check_secret(shared_secret: &[u8]) -> PyResult<()> {
+    if shared_secret.len() < KEY_BYTES {
+        return Err(pyo3::exceptions::PyValueError::new_err(format!(
+            "Initial shared secret must be at least {} bytes",
+            KEY_BYTES
+        )));
+    }
+    Ok(())
+}
+
+/// Forward-secret session over a channel between two agents. Both sides start from a secret
+/// established with Kyber (e.g. `encapsulate_kyber` to the responder's key): the initiator
+/// with `RatchetSession.initiate` and the responder's ratchet public key, the responder with
+/// `RatchetSession.respond` and the matching key pair. The state holds secret keys; persist
+/// `to_bytes()` only under encryption
+#[pyclass(module = "reliquary_encryptor")]
+pub struct RatchetSession {
+    state: State,
+}
+
+#[pymethods]
+impl RatchetSession {
+    /// Starts the initiating side; it can send immediately
+    #[staticmethod]
+    #[pyo3(signature = (shared_secret, peer_ratchet_pk, params = KyberParams::Kyber1024))]
+    fn initiate(
+        py: Python<'_>,
+        shared_secret: Vec<u8>,
+        peer_ratchet_pk: Vec<u8>,
+        params: KyberParams,
+    ) -> PyResult<Self> {
+        let shared_secret = Zeroizing::new(shared_secret);
+        check_secret(&shared_secret)?;
+        crate::validate::check_kyber_public_key(&peer_ratchet_pk, params)
+            .map_err(pyo3::exceptions::PyValueError::new_err)?;
+        let (root_key, _) = kdf_root(&[0u8; KEY_BYTES], &shared_secret);
+        let mut state = State {
+            params,
+            root_key,
+            our_pk: Vec::new(),
+            our_sk: Zeroizing::new(Vec::new()),
+            their_pk: Some(peer_ratchet_pk),
+            send_ct: Vec::new(),
+            send_chain: None,
+            recv_chain: None,
+            sent: 0,
+            received: 0,
+            previous: 0,
+            needs_send_step: true,
+            skipped: VecDeque::new(),
+        };
+        py.allow_threads(|| state.send_step())
+            .map_err(pyo3::exceptions::PyValueError::new_err)?;
+        Ok(RatchetSession { state })
+    }
+
+    /// Starts the responding side with the key pair whose public half the initiator used; it
+    /// can send once the first message has been decrypted
+    #[staticmethod]
+    #[pyo3(signature = (shared_secret, ratchet_pk, ratchet_sk, params = KyberParams::Kyber1024))]
+    fn respond(
+        shared_secret: Vec<u8>,
+        ratchet_pk: Vec<u8>,
+        ratchet_sk: Vec<u8>,
+        params: KyberParams,
+    ) -> PyResult<Self> {
+        let shared_secret = Zeroizing::new(shared_secret);
+        let ratchet_sk = Zeroizing::new(ratchet_sk);
+        check_secret(&shared_secret)?;
+        crate::validate::check_kyber_public_key(&ratchet_pk, params)
+            .map_err(pyo3::exceptions::PyValueError::new_err)?;
+        if ratchet_sk.len() != params.secret_key_bytes() {
+            return Err(pyo3::exceptions::PyValueError::new_err(format!(
+                "Invalid secret key length. Expected {}, got {}",
+                params.secret_key_bytes(),
+                ratchet_sk.len()
+            )));
+        }
+        let (root_key, _) = kdf_root(&[0u8; KEY_BYTES], &shared_secret);
+        Ok(RatchetSession {
+            state: State {
+                params,
+                root_key,
+                our_pk: ratchet_pk,
+                our_sk: ratchet_sk,
+                their_pk: None,
+                send_ct: Vec::new(),
+                send_chain: None,
+                recv_chain: None,
+                sent: 0,
+                received: 0,
+                previous: 0,
+                needs_send_step: false,
+                skipped: VecDeque::new(),
+            },
+        })
+    }
+
+    /// Restores a session saved with `to_bytes`
+    #[staticmethod]
+    fn from_bytes(state: Vec<u8>) -> PyResult<Self> {
+        let state = Zeroizing::new(state);
+        Ok(RatchetSession {
+            state: State::from_bytes(&state).map_err(pyo3::exceptions::PyValueError::new_err)?,
+        })
+    }
+
+    /// Serialized session state, including secret keys
+    fn to_bytes(&self) -> PyResult<Vec<u8>> {
+        self.state
+            .to_bytes()
+            .map_err(pyo3::exceptions::PyValueError::new_err)
+    }
+
+    /// Encrypts one message under the next message key; `ad` is authenticated but not sent
+    #[pyo3(signature = (plaintext, ad = Vec::new()))]
+    fn encrypt(&mut self, py: Python<'_>, plaintext: Vec<u8>, ad: Vec<u8>) -> PyResult<Vec<u8>> {
+        let mut next = self.state.clone();
+        let message = py
+            .allow_threads(|| next.encrypt(&plaintext, &ad))
+            .map_err(pyo3::exceptions::PyValueError::new_err)?;
+        self.state = next;
+        Ok(message)
+    }
+
+    /// Decrypts a message from the peer, in or out of order. A message that fails leaves the
+    /// session unchanged
+    #[pyo3(signature = (message, ad = Vec::new()))]
+    fn decrypt(&mut self, py: Python<'_>, message: Vec<u8>, ad: Vec<u8>) -> PyResult<Vec<u8>> {
+        let mut next = self.state.clone();
+        let plaintext = py
+            .allow_threads(|| next.decrypt(&message, &ad))
+            .map_err(pyo3::exceptions::PyValueError::new_err)?;
+        self.state = next;
+        Ok(plaintext)
+    }
+
+    /// Our current ratchet public key
+    #[getter]
+    fn ratchet_public_key(&self) -> Vec<u8> {
+        self.state.our_pk.clone()
+    }
+
+    /// Number of stored keys for messages that have not arrived yet
+    #[getter]
+    fn skipped_keys(&self) -> usize {
+        self.state.skipped.len()
+    }
+}
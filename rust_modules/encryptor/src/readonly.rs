@@ -0,0 +1,76 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Once;
+
+use pyo3::prelude::*;
+
+// Process-wide read-only mode for forensic inspection. While it is on, every operation that
+// would change persistent state (LMS state files, provider and HSM keys) fails with
+// ReadOnlyMode before touching anything. Setting RELIQUARY_READ_ONLY=1 in the environment
+// turns it on and locks it for the life of the process; a locked mode can't be turned off.
+static ENABLED: AtomicBool = AtomicBool::new(false);
+static LOCKED: AtomicBool = AtomicBool::new(false);
+static FROM_ENV: Once = Once::new();
+
+pyo3::create_exception!(
+    reliquary_encryptor,
+    ReadOnlyMode,
+    pyo3::exceptions::PyPermissionError,
+    "Raised when a write is attempted while read-only (forensic) mode is on"
+);
+
+fn init_from_env() {
+    FROM_ENV.call_once(|| {
+        let value = std::env::var("RELIQUARY_READ_ONLY").unwrap_or_default();
+        if matches!(value.to_ascii_lowercase().as_str(), "1" | "true" | "yes") {
+            ENABLED.store(true, Ordering::SeqCst);
+            LOCKED.store(true, Ordering::SeqCst);
+        }
+    });
+}
+
+pub(crate) fn is_enabled() -> bool {
+    init_from_env();
+    ENABLED.load(Ordering::SeqCst)
+}
+
+/// Fails with ReadOnlyMode if read-only mode is on; `operation` names the refused write.
+pub(crate) fn check_writable(operation: &str) -> PyResult<()> {
+    if is_enabled() {
+        return Err(ReadOnlyMode::new_err(format!(
+            "{} refused: read-only mode is on",
+            operation
+        )));
+    }
+    Ok(())
+}
+
+/// Turns process-wide read-only mode on or off. With `lock`, it stays on until the process
+/// exits; turning a locked mode off raises ReadOnlyMode
+#[pyfunction]
+#[pyo3(signature = (enabled, lock = false))]
+pub fn set_read_only_mode(enabled: bool, lock: bool) -> PyResult<()> {
+    init_from_env();
+    if !enabled && LOCKED.load(Ordering::SeqCst) {
+        return Err(ReadOnlyMode::new_err(
+            "Read-only mode is locked for this process",
+        ));
+    }
+    ENABLED.store(enabled, Ordering::SeqCst);
+    if enabled && lock {
+        LOCKED.store(true, Ordering::SeqCst);
+    }
+    Ok(())
+}
+
+/// Whether process-wide read-only mode is on
+#[pyfunction]
+pub fn read_only_mode() -> bool {
+    is_enabled()
+}
+
+/// Whether read-only mode is locked on for the rest of the process
+#[pyfunction]
+pub fn read_only_mode_locked() -> bool {
+    init_from_env();
+    LOCKED.load(Ordering::SeqCst)
+}
@@ -0,0 +1,581 @@
+use std::collections::HashSet;
+
+use aes_gcm::aead::{Aead, AeadCore, KeyInit, OsRng, Payload};
+use aes_gcm::{Aes256Gcm, Nonce};
+use hkdf::Hkdf;
+use hmac::{Hmac, Mac};
+use pyo3::prelude::*;
+use rand::RngCore;
+use sha2::Sha256;
+use zeroize::Zeroizing;
+
+use crate::envelope::MAGIC;
+use crate::fingerprint::{fingerprint, kyber_public_key_from_secret, FINGERPRINT_BYTES};
+use crate::handles::DecryptionKeyArg;
+use crate::limits;
+use crate::params::KyberParams;
+use crate::shamir;
+use crate::wire::Reader;
+
+// Multi-recipient envelope layout (v2):
+//   magic "RQEV" | version 2 | threshold u8 | slot count u8 | slots | header MAC (32)
+//   | nonce (12) | AES-256-GCM ct || tag
+//   slot: recipient fingerprint (32) | kem id u8 | kem ciphertext | share index u8
+//         | wrap nonce (12) | wrapped share (32 + 16)
+// The random data-encryption key (DEK) is Shamir-split with the envelope's threshold (with
+// threshold 1 every share is the DEK itself) and each share is wrapped under a key derived
+// from its recipient's KEM secret, with the slot fields before the wrap nonce as associated
+// data. The payload is encrypted under a key derived from the DEK alone, so the access
+// structure can change without touching the bulk ciphertext; instead an HMAC keyed from the
+// DEK authenticates everything before it (header and slots) and is recomputed on every change.
+pub(crate) const VERSION: u8 = 2;
+const NONCE_BYTES: usize = 12;
+const TAG_BYTES: usize = 16;
+const MAC_BYTES: usize = 32;
+const DEK_BYTES: usize = 32;
+const WRAP_LABEL: &[u8] = b"reliquary/envelope/v2/wrap";
+const PAYLOAD_LABEL: &[u8] = b"reliquary/envelope/v2/aes-256-gcm";
+const MAC_LABEL: &[u8] = b"reliquary/envelope/v2/header-mac";
+
+/// Removed recipient fingerprints for one pruned envelope.
+pub(crate) type PruneReport = (Vec<u8>, Vec<Vec<u8>>);
+
+struct Slot<'a> {
+    fingerprint: &'a [u8],
+    params: KyberParams,
+    kem_ciphertext: &'a [u8],
+    index: u8,
+    /// Slot bytes up to the wrap nonce; associated data for the wrapped share.
+    bound: &'a [u8],
+    wrap_nonce: &'a [u8],
+    wrapped_share: &'a [u8],
+    raw: &'a [u8],
+}
+
+struct MultiEnvelope<'a> {
+    threshold: u8,
+    /// Everything covered by the header MAC.
+    header: &'a [u8],
+    slots: Vec<Slot<'a>>,
+    mac: &'a [u8],
+    /// Payload nonce and ciphertext; never rewritten after sealing.
+    body: &'a [u8],
+}
+
+fn parse(envelope: &[u8]) -> Result<MultiEnvelope<'_>, String> {
+    limits::check_input("Envelope", envelope.len())?;
+    let mut r = Reader::new(envelope);
+    if r.bytes(4).map_err(|_| "Not a Reliquary envelope")? != MAGIC {
+        return Err("Not a Reliquary envelope".to_string());
+    }
+    let version = r.u8()?;
+    if version != VERSION {
+        return Err("Not a multi-recipient envelope".to_string());
+    }
+    let threshold = r.u8()?;
+    let count = r.u8()?;
+    if threshold == 0 || threshold > count {
+        return Err("Envelope threshold is out of range".to_string());
+    }
+    limits::check_items("Envelope", count as usize)?;
+    let mut slots = Vec::with_capacity(count as usize);
+    for _ in 0..count {
+        let start = r.position();
+        let fingerprint = r.bytes(FINGERPRINT_BYTES)?;
+        let id = r.u8()?;
+        let params = KyberParams::from_wire_id(id)
+            .ok_or_else(|| format!("Unknown envelope KEM id {}", id))?;
+        let kem_ciphertext = r.bytes(params.ciphertext_bytes())?;
+        let index = r.u8()?;
+        let bound = &envelope[start..r.position()];
+        let wrap_nonce = r.bytes(NONCE_BYTES)?;
+        let wrapped_share = r.bytes(DEK_BYTES + TAG_BYTES)?;
+        slots.push(Slot {
+            fingerprint,
+            params,
+            kem_ciphertext,
+            index,
+            bound,
+            wrap_nonce,
+            wrapped_share,
+            raw: &envelope[start..r.position()],
+        });
+    }
+    let header = &envelope[..r.position()];
+    let mac = r.bytes(MAC_BYTES)?;
+    let body = r.rest();
+    if body.len() < NONCE_BYTES + TAG_BYTES {
+        return Err("Envelope is truncated".to_string());
+    }
+    Ok(MultiEnvelope {
+        threshold,
+        header,
+        slots,
+        mac,
+        body,
+    })
+}
+
+fn derive(ikm: &[u8], salt: Option<&[u8]>, label: &[u8]) -> Zeroizing<[u8; 32]> {
+    let mut key = Zeroizing::new([0u8; 32]);
+    Hkdf::<Sha256>::new(salt, ikm)
+        .expand(label, key.as_mut())
+        .expect("32 bytes is a valid HKDF-SHA256 output length");
+    key
+}
+
+fn header_mac(dek: &[u8], header: &[u8]) -> Hmac<Sha256> {
+    let key = derive(dek, None, MAC_LABEL);
+    let mut mac =
+        <Hmac<Sha256> as Mac>::new_from_slice(key.as_ref()).expect("HMAC accepts any key size");
+    mac.update(header);
+    mac
+}
+
+/// Appends a slot wrapping `share` for one recipient.
+fn put_slot(
+    out: &mut Vec<u8>,
+    share: &[u8],
+    index: u8,
+    pk_bytes: &[u8],
+    params: KyberParams,
+) -> PyResult<()> {
+    let (shared_secret, kem_ciphertext) = crate::kyber_encapsulate(pk_bytes, params)?;
+    let shared_secret = Zeroizing::new(shared_secret);
+    let start = out.len();
+    out.extend_from_slice(&fingerprint(pk_bytes));
+    out.push(params.wire_id());
+    out.extend_from_slice(&kem_ciphertext);
+    out.push(index);
+
+    let key = derive(&shared_secret, Some(&out[start..]), WRAP_LABEL);
+    let nonce = Aes256Gcm::generate_nonce(&mut OsRng);
+    let wrapped = Aes256Gcm::new(key.as_ref().into())
+        .encrypt(
+            &nonce,
+            Payload {
+                msg: share,
+                aad: &out[start..],
+            },
+        )
+        .map_err(|e| {
+            pyo3::exceptions::PyValueError::new_err(format!("Encryption error: {:?}", e))
+        })?;
+    out.extend_from_slice(&nonce);
+    out.extend_from_slice(&wrapped);
+    Ok(())
+}
+
+fn new_header(threshold: u8, slot_count: usize) -> Result<Vec<u8>, String> {
+    let count = u8::try_from(slot_count).map_err(|_| "An envelope holds at most 255 recipients")?;
+    if threshold == 0 || threshold > count {
+        return Err(format!(
+            "Threshold must be between 1 and the number of recipients ({})",
+            count
+        ));
+    }
+    let mut header = MAGIC.to_vec();
+    header.push(VERSION);
+    header.push(threshold);
+    header.push(count);
+    Ok(header)
+}
+
+/// MACs a finished header and appends the (unchanged) payload.
+fn assemble(mut header: Vec<u8>, dek: &[u8], body: &[u8]) -> Vec<u8> {
+    let mac = header_mac(dek, &header).finalize().into_bytes();
+    header.extend_from_slice(&mac);
+    header.extend_from_slice(body);
+    header
+}
+
+/// Fresh slots for `recipient_pks`, sharing `dek` with the given threshold.
+fn put_slots(
+    header: &mut Vec<u8>,
+    dek: &[u8],
+    threshold: u8,
+    recipient_pks: &[Vec<u8>],
+    params: KyberParams,
+) -> PyResult<()> {
+    let xs: Vec<u8> = (1..=recipient_pks.len() as u8).collect();
+    let shares =
+        shamir::split(dek, threshold, &xs).map_err(pyo3::exceptions::PyValueError::new_err)?;
+    for ((pk, share), x) in recipient_pks.iter().zip(&shares).zip(xs) {
+        put_slot(header, share, x, pk, params)?;
+    }
+    Ok(())
+}
+
+pub(crate) fn seal(
+    data: &[u8],
+    recipient_pks: &[Vec<u8>],
+    params: KyberParams,
+    threshold: u8,
+) -> PyResult<Vec<u8>> {
+    let mut header = new_header(threshold, recipient_pks.len())
+        .map_err(pyo3::exceptions::PyValueError::new_err)?;
+    let mut dek = Zeroizing::new([0u8; DEK_BYTES]);
+    rand::thread_rng().fill_bytes(dek.as_mut());
+    put_slots(&mut header, dek.as_ref(), threshold, recipient_pks, params)?;
+
+    let key = derive(dek.as_ref(), None, PAYLOAD_LABEL);
+    let nonce = Aes256Gcm::generate_nonce(&mut OsRng);
+    let ciphertext = Aes256Gcm::new(key.as_ref().into())
+        .encrypt(
+            &nonce,
+            Payload {
+                msg: data,
+                aad: &[MAGIC.as_slice(), &[VERSION]].concat(),
+            },
+        )
+        .map_err(|e| {
+            pyo3::exceptions::PyValueError::new_err(format!("Encryption error: {:?}", e))
+        })?;
+    let body = [nonce.as_slice(), &ciphertext].concat();
+    Ok(assemble(header, dek.as_ref(), &body))
+}
+
+/// Whether `sk_bytes` is the secret key for the slot's recipient.
+fn addressed_to(slot: &Slot, sk_bytes: &[u8]) -> bool {
+    sk_bytes.len() == slot.params.secret_key_bytes()
+        && fingerprint(kyber_public_key_from_secret(sk_bytes, slot.params)) == slot.fingerprint
+}
+
+/// Unwraps the share in the slot addressed to `sk_bytes`, if there is one.
+fn unwrap_share(parsed: &MultiEnvelope, sk_bytes: &[u8]) -> Result<Option<Share>, String> {
+    let Some(slot) = parsed
+        .slots
+        .iter()
+        .find(|slot| addressed_to(slot, sk_bytes))
+    else {
+        return Ok(None);
+    };
+    let shared_secret = Zeroizing::new(
+        crate::kyber_decapsulate(slot.kem_ciphertext, sk_bytes, slot.params)
+            .map_err(|_| "KEM decapsulation failed")?,
+    );
+    let key = derive(&shared_secret, Some(slot.bound), WRAP_LABEL);
+    let share = Aes256Gcm::new(key.as_ref().into())
+        .decrypt(
+            Nonce::from_slice(slot.wrap_nonce),
+            Payload {
+                msg: slot.wrapped_share,
+                aad: slot.bound,
+            },
+        )
+        .map_err(|_| "Recipient slot authentication failed")?;
+    Ok(Some((slot.index, Zeroizing::new(share))))
+}
+
+/// One recipient slot as seen by the structural validator:
+/// (fingerprint, KEM parameters, KEM ciphertext, share index).
+pub(crate) type SlotLayout<'a> = (&'a [u8], KyberParams, &'a [u8], u8);
+
+/// Threshold and recipient slots of a multi-recipient envelope, without opening anything.
+pub(crate) fn layout(envelope: &[u8]) -> Result<(u8, Vec<SlotLayout<'_>>), String> {
+    let parsed = parse(envelope)?;
+    Ok((
+        parsed.threshold,
+        parsed
+            .slots
+            .iter()
+            .map(|slot| {
+                (
+                    slot.fingerprint,
+                    slot.params,
+                    slot.kem_ciphertext,
+                    slot.index,
+                )
+            })
+            .collect(),
+    ))
+}
+
+/// (threshold, recipient slots matched by `secret_keys`, total slots), by fingerprint only:
+/// nothing is decapsulated or unwrapped.
+pub(crate) fn key_availability(
+    envelope: &[u8],
+    secret_keys: &[&[u8]],
+) -> Result<(u8, usize, usize), String> {
+    let parsed = parse(envelope)?;
+    let matched = parsed
+        .slots
+        .iter()
+        .filter(|slot| secret_keys.iter().any(|sk| addressed_to(slot, sk)))
+        .count();
+    Ok((parsed.threshold, matched, parsed.slots.len()))
+}
+
+/// A recovered share: (x coordinate, share bytes).
+type Share = (u8, Zeroizing<Vec<u8>>);
+
+/// Recovers the DEK from at least `threshold` recipient keys and checks the header MAC.
+/// Also returns the `threshold` shares it interpolated, for minting new ones.
+fn recover_dek(
+    parsed: &MultiEnvelope,
+    secret_keys: &[&[u8]],
+) -> Result<(Zeroizing<Vec<u8>>, Vec<Share>), String> {
+    let mut shares: Vec<Share> = Vec::new();
+    for sk in secret_keys {
+        if shares.len() == parsed.threshold as usize {
+            break;
+        }
+        if let Some((index, share)) = unwrap_share(parsed, sk)? {
+            if !shares.iter().any(|(x, _)| *x == index) {
+                shares.push((index, share));
+            }
+        }
+    }
+    if shares.is_empty() {
+        return Err("Envelope has no recipient slot for this key".to_string());
+    }
+    if shares.len() < parsed.threshold as usize {
+        return Err(format!(
+            "Envelope needs {} recipient keys, got {}",
+            parsed.threshold,
+            shares.len()
+        ));
+    }
+    let dek = shamir::interpolate(&points(&shares), 0)?;
+    header_mac(&dek, parsed.header)
+        .verify_slice(parsed.mac)
+        .map_err(|_| "Envelope authentication failed (wrong key or tampered envelope)")?;
+    Ok((dek, shares))
+}
+
+fn points(shares: &[Share]) -> Vec<(u8, &[u8])> {
+    shares.iter().map(|(x, y)| (*x, y.as_slice())).collect()
+}
+
+pub(crate) fn open_with(envelope: &[u8], secret_keys: &[&[u8]]) -> Result<Vec<u8>, String> {
+    let parsed = parse(envelope)?;
+    let (dek, _) = recover_dek(&parsed, secret_keys)?;
+    let key = derive(&dek, None, PAYLOAD_LABEL);
+    let (nonce, ciphertext) = parsed.body.split_at(NONCE_BYTES);
+    Aes256Gcm::new(key.as_ref().into())
+        .decrypt(
+            Nonce::from_slice(nonce),
+            Payload {
+                msg: ciphertext,
+                aad: &envelope[..MAGIC.len() + 1],
+            },
+        )
+        .map_err(|_| "Envelope authentication failed (wrong key or tampered envelope)".to_string())
+}
+
+pub(crate) fn open(envelope: &[u8], sk_bytes: &[u8]) -> Result<Vec<u8>, String> {
+    open_with(envelope, &[sk_bytes])
+}
+
+/// Keeps the slots for which `keep` returns true and re-MACs the header; the payload and the
+/// kept slots are copied verbatim.
+fn retain(
+    envelope: &[u8],
+    secret_keys: &[&[u8]],
+    keep: impl Fn(&[u8]) -> bool,
+) -> Result<PruneReport, String> {
+    let parsed = parse(envelope)?;
+    let (kept, removed): (Vec<&Slot>, Vec<&Slot>) =
+        parsed.slots.iter().partition(|slot| keep(slot.fingerprint));
+    if removed.is_empty() {
+        return Ok((envelope.to_vec(), Vec::new()));
+    }
+    if kept.len() < parsed.threshold as usize {
+        return Err(format!(
+            "Removing {} recipient(s) would leave fewer than the threshold of {}",
+            removed.len(),
+            parsed.threshold
+        ));
+    }
+    let (dek, _) = recover_dek(&parsed, secret_keys)?;
+    let mut header = new_header(parsed.threshold, kept.len())?;
+    for slot in kept {
+        header.extend_from_slice(slot.raw);
+    }
+    Ok((
+        assemble(header, &dek, parsed.body),
+        removed
+            .iter()
+            .map(|slot| slot.fingerprint.to_vec())
+            .collect(),
+    ))
+}
+
+pub(crate) fn as_slices<K: AsRef<[u8]>>(keys: &[K]) -> Vec<&[u8]> {
+    keys.iter().map(|k| k.as_ref()).collect()
+}
+
+/// Encrypts `data` once for several recipients. A fresh data key is Shamir-split so that any
+/// `threshold` of the recipients' Kyber secret keys open the envelope; with the default
+/// threshold of 1 any single key does, via `decrypt_with_secret_key`
+#[pyfunction]
+#[pyo3(signature = (data, recipient_pks, params = KyberParams::Kyber1024, threshold = 1))]
+pub fn encrypt_to_recipients(
+    py: Python<'_>,
+    data: Vec<u8>,
+    recipient_pks: Vec<Vec<u8>>,
+    params: KyberParams,
+    threshold: u8,
+) -> PyResult<Vec<u8>> {
+    py.allow_threads(|| seal(&data, &recipient_pks, params, threshold))
+}
+
+/// Opens a multi-recipient envelope with at least `threshold` recipient secret keys. Keys
+/// without a slot in the envelope are ignored
+#[pyfunction]
+pub fn decrypt_with_secret_keys(
+    py: Python<'_>,
+    envelope: Vec<u8>,
+    kyber_sks: Vec<DecryptionKeyArg>,
+) -> PyResult<Vec<u8>> {
+    py.allow_threads(|| {
+        open_with(&envelope, &as_slices(&kyber_sks))
+            .map_err(pyo3::exceptions::PyValueError::new_err)
+    })
+}
+
+/// Lists the recipient key fingerprints of a multi-recipient envelope, in slot order
+#[pyfunction]
+pub fn envelope_recipients(envelope: Vec<u8>) -> PyResult<Vec<Vec<u8>>> {
+    let parsed = parse(&envelope).map_err(pyo3::exceptions::PyValueError::new_err)?;
+    Ok(parsed
+        .slots
+        .iter()
+        .map(|slot| slot.fingerprint.to_vec())
+        .collect())
+}
+
+/// Number of recipient keys needed to open a multi-recipient envelope
+#[pyfunction]
+pub fn envelope_threshold(envelope: Vec<u8>) -> PyResult<u8> {
+    let parsed = parse(&envelope).map_err(pyo3::exceptions::PyValueError::new_err)?;
+    Ok(parsed.threshold)
+}
+
+/// Removes the recipient slots whose fingerprints aren't in `active_fingerprints` and
+/// re-authenticates the envelope header; the payload is left untouched. `kyber_sk` must
+/// belong to one of the recipients so the data key can be recovered (threshold-1 envelopes;
+/// see `remove_recipients` otherwise). Returns (envelope, removed_fingerprints); the envelope
+/// is returned unchanged when nothing was removed. Refuses to drop below the threshold
+#[pyfunction]
+pub fn prune_recipients(
+    py: Python<'_>,
+    envelope: Vec<u8>,
+    active_fingerprints: Vec<Vec<u8>>,
+    kyber_sk: DecryptionKeyArg,
+) -> PyResult<PruneReport> {
+    let active: HashSet<Vec<u8>> = active_fingerprints.into_iter().collect();
+    py.allow_threads(|| {
+        retain(&envelope, &[&kyber_sk], |fp| active.contains(fp))
+            .map_err(pyo3::exceptions::PyValueError::new_err)
+    })
+}
+
+/// `prune_recipients` over many envelopes with the same active set. Returns one
+/// (envelope, removed_fingerprints) tuple per input; raises on the first envelope that can't
+/// be pruned, naming its index
+#[pyfunction]
+pub fn prune_recipients_bulk(
+    py: Python<'_>,
+    envelopes: Vec<Vec<u8>>,
+    active_fingerprints: Vec<Vec<u8>>,
+    kyber_sk: DecryptionKeyArg,
+) -> PyResult<Vec<PruneReport>> {
+    let active: HashSet<Vec<u8>> = active_fingerprints.into_iter().collect();
+    py.allow_threads(|| {
+        envelopes
+            .iter()
+            .enumerate()
+            .map(|(i, envelope)| {
+                retain(envelope, &[&kyber_sk], |fp| active.contains(fp)).map_err(|e| {
+                    pyo3::exceptions::PyValueError::new_err(format!("Envelope {}: {}", i, e))
+                })
+            })
+            .collect()
+    })
+}
+
+/// Removes the slots of the given recipient fingerprints, unlocking with at least `threshold`
+/// of `kyber_sks`. Only the header changes. Removed recipients that kept an earlier copy of the
+/// envelope can still open that copy
+#[pyfunction]
+pub fn remove_recipients(
+    py: Python<'_>,
+    envelope: Vec<u8>,
+    fingerprints: Vec<Vec<u8>>,
+    kyber_sks: Vec<DecryptionKeyArg>,
+) -> PyResult<Vec<u8>> {
+    let removed: HashSet<Vec<u8>> = fingerprints.into_iter().collect();
+    py.allow_threads(|| {
+        let (envelope, _) = retain(&envelope, &as_slices(&kyber_sks), |fp| {
+            !removed.contains(fp)
+        })
+        .map_err(pyo3::exceptions::PyValueError::new_err)?;
+        Ok(envelope)
+    })
+}
+
+/// Adds recipients to a multi-recipient envelope under its current threshold. Existing slots
+/// and the payload are kept as-is; new shares are minted from the same sharing polynomial,
+/// which needs at least `threshold` of the current recipients' `kyber_sks`
+#[pyfunction]
+#[pyo3(signature = (envelope, recipient_pks, kyber_sks, params = KyberParams::Kyber1024))]
+pub fn add_recipients(
+    py: Python<'_>,
+    envelope: Vec<u8>,
+    recipient_pks: Vec<Vec<u8>>,
+    kyber_sks: Vec<DecryptionKeyArg>,
+    params: KyberParams,
+) -> PyResult<Vec<u8>> {
+    py.allow_threads(|| {
+        let parsed = parse(&envelope).map_err(pyo3::exceptions::PyValueError::new_err)?;
+        for pk in &recipient_pks {
+            let fp = fingerprint(pk);
+            if parsed.slots.iter().any(|slot| slot.fingerprint == fp) {
+                return Err(pyo3::exceptions::PyValueError::new_err(
+                    "Recipient is already in the envelope",
+                ));
+            }
+        }
+        let (dek, shares) = recover_dek(&parsed, &as_slices(&kyber_sks))
+            .map_err(pyo3::exceptions::PyValueError::new_err)?;
+
+        let mut header = new_header(parsed.threshold, parsed.slots.len() + recipient_pks.len())
+            .map_err(pyo3::exceptions::PyValueError::new_err)?;
+        for slot in &parsed.slots {
+            header.extend_from_slice(slot.raw);
+        }
+        let mut unused = (1..=u8::MAX).filter(|x| parsed.slots.iter().all(|s| s.index != *x));
+        for pk in &recipient_pks {
+            let x = unused.next().expect("fewer than 255 slots");
+            let share = shamir::interpolate(&points(&shares), x)
+                .map_err(pyo3::exceptions::PyValueError::new_err)?;
+            put_slot(&mut header, &share, x, pk, params)?;
+        }
+        Ok(assemble(header, &dek, parsed.body))
+    })
+}
+
+/// Replaces the whole access structure: the data key is re-shared to `recipient_pks` with a
+/// new `threshold`, unlocking with at least the current threshold of `kyber_sks`. Every slot is
+/// rebuilt; the payload ciphertext is reused unchanged
+#[pyfunction]
+#[pyo3(signature = (envelope, recipient_pks, threshold, kyber_sks, params = KyberParams::Kyber1024))]
+pub fn rewrap_recipients(
+    py: Python<'_>,
+    envelope: Vec<u8>,
+    recipient_pks: Vec<Vec<u8>>,
+    threshold: u8,
+    kyber_sks: Vec<DecryptionKeyArg>,
+    params: KyberParams,
+) -> PyResult<Vec<u8>> {
+    py.allow_threads(|| {
+        let parsed = parse(&envelope).map_err(pyo3::exceptions::PyValueError::new_err)?;
+        let (dek, _) = recover_dek(&parsed, &as_slices(&kyber_sks))
+            .map_err(pyo3::exceptions::PyValueError::new_err)?;
+        let mut header = new_header(threshold, recipient_pks.len())
+            .map_err(pyo3::exceptions::PyValueError::new_err)?;
+        put_slots(&mut header, &dek, threshold, &recipient_pks, params)?;
+        Ok(assemble(header, &dek, parsed.body))
+    })
+}
@@ -0,0 +1,260 @@
+use curve25519_dalek::constants::RISTRETTO_BASEPOINT_POINT;
+use curve25519_dalek::ristretto::{CompressedRistretto, RistrettoPoint};
+use curve25519_dalek::scalar::Scalar;
+use pyo3::prelude::*;
+use rand::RngCore;
+use sha2::{Digest, Sha512};
+use zeroize::Zeroizing;
+
+use crate::classical::fixed;
+use crate::handles::SigningKeyArg;
+use crate::wire::Reader;
+
+// Linkable ring signatures (bLSAG) over Ristretto255. The key image I = x * Hp(context, P) is
+// deterministic per signer and context, so two votes by the same trustee in one poll share a
+// tag while votes in different polls (contexts) stay unlinkable.
+// Signature layout:
+//   magic "RQRS" | version u8 | key image (32) | c0 (32) | ring size u8 | responses (32 each)
+const MAGIC: &[u8; 4] = b"RQRS";
+const VERSION: u8 = 1;
+const KEY_BYTES: usize = 32;
+const TAG_OFFSET: usize = 4 + 1;
+const HASH_POINT_LABEL: &[u8] = b"reliquary/ring/v1/hash-to-point";
+const CHALLENGE_LABEL: &[u8] = b"reliquary/ring/v1/challenge";
+const MESSAGE_LABEL: &[u8] = b"reliquary/ring/v1/message";
+
+fn random_scalar() -> Scalar {
+    let mut wide = Zeroizing::new([0u8; 64]);
+    rand::thread_rng().fill_bytes(wide.as_mut());
+    Scalar::from_bytes_mod_order_wide(&wide)
+}
+
+fn decode_point(bytes: &[u8]) -> Option<RistrettoPoint> {
+    CompressedRistretto::from_slice(bytes).ok()?.decompress()
+}
+
+fn hash_to_point(context: &[u8], public_key: &[u8]) -> RistrettoPoint {
+    let mut hasher = Sha512::new().chain_update(HASH_POINT_LABEL);
+    put_len_prefixed(&mut hasher, context);
+    RistrettoPoint::from_uniform_bytes(&hasher.chain_update(public_key).finalize().into())
+}
+
+fn put_len_prefixed(hasher: &mut Sha512, bytes: &[u8]) {
+    hasher.update((bytes.len() as u64).to_be_bytes());
+    hasher.update(bytes);
+}
+
+/// Binds the message to the context, the exact ring (order included) and the key image.
+fn message_digest(message: &[u8], ring: &[Vec<u8>], context: &[u8], image: &[u8]) -> Vec<u8> {
+    let mut hasher = Sha512::new().chain_update(MESSAGE_LABEL);
+    put_len_prefixed(&mut hasher, context);
+    hasher.update((ring.len() as u64).to_be_bytes());
+    for pk in ring {
+        hasher.update(pk);
+    }
+    hasher.update(image);
+    put_len_prefixed(&mut hasher, message);
+    hasher.finalize().to_vec()
+}
+
+fn challenge(digest: &[u8], l: &RistrettoPoint, r: &RistrettoPoint) -> Scalar {
+    Scalar::from_bytes_mod_order_wide(
+        &Sha512::new()
+            .chain_update(CHALLENGE_LABEL)
+            .chain_update(digest)
+            .chain_update(l.compress().as_bytes())
+            .chain_update(r.compress().as_bytes())
+            .finalize()
+            .into(),
+    )
+}
+
+fn check_ring_size(size: usize) -> Result<(), String> {
+    if size == 0 || size > u8::MAX as usize {
+        return Err("A ring holds between 1 and 255 public keys".to_string());
+    }
+    Ok(())
+}
+
+/// Signature size for a ring of `size` keys: the fixed header plus one response per member.
+pub(crate) fn signature_len(size: usize) -> Result<usize, String> {
+    check_ring_size(size)?;
+    Ok(TAG_OFFSET + 2 * KEY_BYTES + 1 + KEY_BYTES * size)
+}
+
+fn check_ring(ring: &[Vec<u8>]) -> Result<Vec<RistrettoPoint>, String> {
+    check_ring_size(ring.len())?;
+    let points = ring
+        .iter()
+        .map(|pk| decode_point(pk).ok_or("Invalid ring public key"))
+        .collect::<Result<Vec<_>, _>>()?;
+    for (i, pk) in ring.iter().enumerate() {
+        if ring[..i].contains(pk) {
+            return Err("Duplicate public key in ring".to_string());
+        }
+    }
+    Ok(points)
+}
+
+fn sign(
+    message: &[u8],
+    ring: &[Vec<u8>],
+    sk: &[u8; KEY_BYTES],
+    context: &[u8],
+) -> Result<Vec<u8>, String> {
+    let points = check_ring(ring)?;
+    let x = Option::<Scalar>::from(Scalar::from_canonical_bytes(*sk))
+        .ok_or("Invalid ring secret key")?;
+    let own = (RISTRETTO_BASEPOINT_POINT * x).compress();
+    let signer = ring
+        .iter()
+        .position(|pk| pk.as_slice() == own.as_bytes())
+        .ok_or("Signer's public key is not in the ring")?;
+    let n = ring.len();
+    let hashed: Vec<RistrettoPoint> = ring.iter().map(|pk| hash_to_point(context, pk)).collect();
+    let image = (hashed[signer] * x).compress();
+    let digest = message_digest(message, ring, context, image.as_bytes());
+    let image = image.decompress().expect("freshly compressed point");
+
+    let alpha = random_scalar();
+    let mut c = vec![Scalar::ZERO; n];
+    let mut r = vec![Scalar::ZERO; n];
+    c[(signer + 1) % n] = challenge(
+        &digest,
+        &(RISTRETTO_BASEPOINT_POINT * alpha),
+        &(hashed[signer] * alpha),
+    );
+    let mut i = (signer + 1) % n;
+    while i != signer {
+        r[i] = random_scalar();
+        c[(i + 1) % n] = challenge(
+            &digest,
+            &(RISTRETTO_BASEPOINT_POINT * r[i] + points[i] * c[i]),
+            &(hashed[i] * r[i] + image * c[i]),
+        );
+        i = (i + 1) % n;
+    }
+    r[signer] = alpha - c[signer] * x;
+
+    let mut out = Vec::with_capacity(TAG_OFFSET + 2 * KEY_BYTES + 1 + n * KEY_BYTES);
+    out.extend_from_slice(MAGIC);
+    out.push(VERSION);
+    out.extend_from_slice(image.compress().as_bytes());
+    out.extend_from_slice(c[0].as_bytes());
+    out.push(n as u8);
+    for response in &r {
+        out.extend_from_slice(response.as_bytes());
+    }
+    Ok(out)
+}
+
+fn scalar(bytes: &[u8]) -> Option<Scalar> {
+    Scalar::from_canonical_bytes(bytes.try_into().ok()?).into()
+}
+
+fn verify(
+    message: &[u8],
+    signature: &[u8],
+    ring: &[Vec<u8>],
+    context: &[u8],
+) -> Result<bool, String> {
+    let points = check_ring(ring)?;
+    let mut reader = Reader::new(signature);
+    if reader.bytes(4).map_err(|_| "Not a ring signature")? != MAGIC {
+        return Err("Not a ring signature".to_string());
+    }
+    let version = reader.u8()?;
+    if version != VERSION {
+        return Err(format!("Unsupported ring signature version {}", version));
+    }
+    let image_bytes = reader.bytes(KEY_BYTES)?;
+    let (Some(image), Some(c0)) = (decode_point(image_bytes), scalar(reader.bytes(KEY_BYTES)?))
+    else {
+        return Ok(false);
+    };
+    if reader.u8()? as usize != ring.len() {
+        return Ok(false);
+    }
+    let mut responses = Vec::with_capacity(ring.len());
+    for _ in 0..ring.len() {
+        let Some(r) = scalar(reader.bytes(KEY_BYTES)?) else {
+            return Ok(false);
+        };
+        responses.push(r);
+    }
+    if !reader.is_empty() {
+        return Err("Trailing bytes after ring signature".to_string());
+    }
+
+    let digest = message_digest(message, ring, context, image_bytes);
+    let mut c = c0;
+    for ((point, pk), r) in points.iter().zip(ring).zip(&responses) {
+        c = challenge(
+            &digest,
+            &(RISTRETTO_BASEPOINT_POINT * r + point * c),
+            &(hash_to_point(context, pk) * r + image * c),
+        );
+    }
+    Ok(c == c0)
+}
+
+/// Generates a ring-signature keypair (Ristretto255). Returns (public_key, secret_key)
+#[pyfunction]
+pub fn generate_ring_keys() -> (Vec<u8>, Vec<u8>) {
+    let x = random_scalar();
+    (
+        (RISTRETTO_BASEPOINT_POINT * x)
+            .compress()
+            .to_bytes()
+            .to_vec(),
+        x.to_bytes().to_vec(),
+    )
+}
+
+/// Signs `message` on behalf of `ring` (a list of ring public keys that must include the
+/// signer's). Verifiers learn that some member signed, not which one. `context` scopes the
+/// linking tag, e.g. a poll id: one member signing twice in the same context yields the same
+/// `ring_signature_tag`
+#[pyfunction]
+#[pyo3(signature = (message, ring, sk_bytes, context = Vec::new()))]
+pub fn ring_sign(
+    py: Python<'_>,
+    message: Vec<u8>,
+    ring: Vec<Vec<u8>>,
+    sk_bytes: SigningKeyArg,
+    context: Vec<u8>,
+) -> PyResult<Vec<u8>> {
+    let sk = Zeroizing::new(fixed::<KEY_BYTES>(&sk_bytes, "ring secret key")?);
+    py.allow_threads(|| {
+        sign(&message, &ring, &sk, &context).map_err(pyo3::exceptions::PyValueError::new_err)
+    })
+}
+
+/// Verifies a ring signature against the same ring (in the same order) and context it was
+/// made with
+#[pyfunction]
+#[pyo3(signature = (message, signature, ring, context = Vec::new()))]
+pub fn ring_verify(
+    py: Python<'_>,
+    message: Vec<u8>,
+    signature: Vec<u8>,
+    ring: Vec<Vec<u8>>,
+    context: Vec<u8>,
+) -> PyResult<bool> {
+    py.allow_threads(|| {
+        verify(&message, &signature, &ring, &context)
+            .map_err(pyo3::exceptions::PyValueError::new_err)
+    })
+}
+
+/// The signature's 32-byte linking tag (key image). Within one context, equal tags mean the
+/// same ring member signed both; record it alongside the vote to reject double votes
+#[pyfunction]
+pub fn ring_signature_tag(signature: Vec<u8>) -> PyResult<Vec<u8>> {
+    if signature.len() < TAG_OFFSET + KEY_BYTES || &signature[..4] != MAGIC {
+        return Err(pyo3::exceptions::PyValueError::new_err(
+            "Not a ring signature",
+        ));
+    }
+    Ok(signature[TAG_OFFSET..TAG_OFFSET + KEY_BYTES].to_vec())
+}
@@ -0,0 +1,110 @@
+use pyo3::prelude::*;
+
+use crate::params::FalconParams;
+
+/// Signature schemes usable in multi-party structures (bundles, certificates). Each carries a
+/// one-byte wire id so serialized structures stay self-describing.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum SignatureScheme {
+    Falcon512,
+    Falcon1024,
+    Dilithium5,
+    Ed25519,
+    MlDsa87,
+}
+
+impl SignatureScheme {
+    pub(crate) fn parse(name: &str) -> PyResult<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "falcon512" => Ok(SignatureScheme::Falcon512),
+            "falcon1024" => Ok(SignatureScheme::Falcon1024),
+            "dilithium5" => Ok(SignatureScheme::Dilithium5),
+            "ed25519" => Ok(SignatureScheme::Ed25519),
+            "ml-dsa-87" => Ok(SignatureScheme::MlDsa87),
+            _ => Err(pyo3::exceptions::PyValueError::new_err(format!(
+                "Unknown signature scheme '{}'",
+                name
+            ))),
+        }
+    }
+
+    pub(crate) fn name(&self) -> &'static str {
+        match self {
+            SignatureScheme::Falcon512 => "falcon512",
+            SignatureScheme::Falcon1024 => "falcon1024",
+            SignatureScheme::Dilithium5 => "dilithium5",
+            SignatureScheme::Ed25519 => "ed25519",
+            SignatureScheme::MlDsa87 => "ml-dsa-87",
+        }
+    }
+
+    pub(crate) fn wire_id(&self) -> u8 {
+        match self {
+            SignatureScheme::Falcon512 => 1,
+            SignatureScheme::Falcon1024 => 2,
+            SignatureScheme::Dilithium5 => 3,
+            SignatureScheme::Ed25519 => 4,
+            SignatureScheme::MlDsa87 => 5,
+        }
+    }
+
+    pub(crate) fn from_wire_id(id: u8) -> Option<Self> {
+        match id {
+            1 => Some(SignatureScheme::Falcon512),
+            2 => Some(SignatureScheme::Falcon1024),
+            3 => Some(SignatureScheme::Dilithium5),
+            4 => Some(SignatureScheme::Ed25519),
+            5 => Some(SignatureScheme::MlDsa87),
+            _ => None,
+        }
+    }
+
+    /// Upper bound on a detached signature's length; exact for all but Falcon.
+    pub(crate) fn max_signature_bytes(&self) -> usize {
+        match self {
+            SignatureScheme::Falcon512 => FalconParams::Falcon512.signature_bytes(),
+            SignatureScheme::Falcon1024 => FalconParams::Falcon1024.signature_bytes(),
+            SignatureScheme::Dilithium5 => crate::dilithium::SIGNATURE_BYTES,
+            SignatureScheme::Ed25519 => crate::classical::ED25519_SIGNATURE_BYTES,
+            SignatureScheme::MlDsa87 => crate::mldsa::SIGNATURE_BYTES,
+        }
+    }
+
+    /// Detached signature over `msg`.
+    pub(crate) fn sign(&self, msg: &[u8], sk_bytes: &[u8]) -> PyResult<Vec<u8>> {
+        match self {
+            SignatureScheme::Falcon512 => {
+                crate::falcon_sign_detached(msg, sk_bytes, FalconParams::Falcon512)
+            }
+            SignatureScheme::Falcon1024 => {
+                crate::falcon_sign_detached(msg, sk_bytes, FalconParams::Falcon1024)
+            }
+            SignatureScheme::Dilithium5 => crate::dilithium::dilithium_sign(msg, sk_bytes),
+            SignatureScheme::Ed25519 => crate::classical::ed25519_sign(msg, sk_bytes),
+            SignatureScheme::MlDsa87 => crate::mldsa::ml_dsa_sign(msg, sk_bytes, &[], false),
+        }
+    }
+
+    /// Verifies a detached signature. Malformed signatures verify as false; malformed public
+    /// keys raise ValueError.
+    pub(crate) fn verify(&self, msg: &[u8], signature: &[u8], pk_bytes: &[u8]) -> PyResult<bool> {
+        match self {
+            SignatureScheme::Falcon512 => {
+                crate::falcon_verify_detached(msg, signature, pk_bytes, FalconParams::Falcon512)
+            }
+            SignatureScheme::Falcon1024 => {
+                crate::falcon_verify_detached(msg, signature, pk_bytes, FalconParams::Falcon1024)
+            }
+            SignatureScheme::Dilithium5 => {
+                crate::dilithium::dilithium_verify(msg, signature, pk_bytes)
+            }
+            SignatureScheme::Ed25519 => {
+                if signature.len() != 64 {
+                    return Ok(false);
+                }
+                crate::classical::ed25519_verify(msg, signature, pk_bytes)
+            }
+            SignatureScheme::MlDsa87 => crate::mldsa::ml_dsa_verify(msg, signature, pk_bytes, &[]),
+        }
+    }
+}
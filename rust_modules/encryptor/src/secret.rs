@@ -0,0 +1,132 @@
+#[cfg(unix)]
+use std::collections::hash_map::Entry;
+#[cfg(unix)]
+use std::collections::HashMap;
+use std::ops::Deref;
+#[cfg(unix)]
+use std::sync::{Mutex, MutexGuard, OnceLock};
+
+use zeroize::Zeroize;
+
+/// Fixed-size buffer for secret key bytes. The pages are locked into RAM where the platform
+/// allows it (so the key can't be written to swap) and the bytes are wiped before the lock is
+/// released on drop. Pages shared with other buffers stay locked until the last of them is
+/// dropped. Locking is best-effort: it fails quietly under RLIMIT_MEMLOCK, which
+/// `locked()` reports.
+pub(crate) struct LockedBytes {
+    bytes: Box<[u8]>,
+    locked: bool,
+}
+
+impl LockedBytes {
+    /// Copies `bytes` into locked memory. Wiping the source is up to the caller.
+    pub(crate) fn new(bytes: &[u8]) -> Self {
+        let mut boxed = vec![0u8; bytes.len()].into_boxed_slice();
+        let locked = lock(&boxed);
+        boxed.copy_from_slice(bytes);
+        LockedBytes {
+            bytes: boxed,
+            locked,
+        }
+    }
+
+    pub(crate) fn locked(&self) -> bool {
+        self.locked
+    }
+}
+
+impl Deref for LockedBytes {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        &self.bytes
+    }
+}
+
+impl AsRef<[u8]> for LockedBytes {
+    fn as_ref(&self) -> &[u8] {
+        &self.bytes
+    }
+}
+
+impl Drop for LockedBytes {
+    fn drop(&mut self) {
+        self.bytes.zeroize();
+        if self.locked {
+            unlock(&self.bytes);
+        }
+    }
+}
+
+// mlock doesn't nest: one munlock undoes any number of mlocks on a page. Two buffers can share a
+// page, so each locked page carries a count of the buffers on it and is only unlocked when the
+// last of them is dropped. Process-wide, like the page locks themselves.
+#[cfg(unix)]
+fn page_locks() -> MutexGuard<'static, HashMap<usize, usize>> {
+    static PAGE_LOCKS: OnceLock<Mutex<HashMap<usize, usize>>> = OnceLock::new();
+    PAGE_LOCKS
+        .get_or_init(|| Mutex::new(HashMap::new()))
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+#[cfg(unix)]
+fn page_size() -> usize {
+    // SAFETY: sysconf has no preconditions.
+    unsafe { libc::sysconf(libc::_SC_PAGESIZE) as usize }
+}
+
+/// Start addresses of the pages `bytes` touches.
+#[cfg(unix)]
+fn pages(bytes: &[u8]) -> impl Iterator<Item = usize> {
+    let size = page_size();
+    let start = bytes.as_ptr() as usize;
+    (start & !(size - 1)..start + bytes.len()).step_by(size)
+}
+
+#[cfg(unix)]
+fn lock(bytes: &[u8]) -> bool {
+    if bytes.is_empty() {
+        return false;
+    }
+    let mut counts = page_locks();
+    let mut newly_locked = Vec::new();
+    for page in pages(bytes).filter(|page| !counts.contains_key(page)) {
+        // SAFETY: the page holds part of a live allocation owned by the caller.
+        if unsafe { libc::mlock(page as *const libc::c_void, page_size()) } != 0 {
+            for page in newly_locked {
+                // SAFETY: locked just above and still allocated.
+                unsafe { libc::munlock(page as *const libc::c_void, page_size()) };
+            }
+            return false;
+        }
+        newly_locked.push(page);
+    }
+    for page in pages(bytes) {
+        *counts.entry(page).or_insert(0) += 1;
+    }
+    true
+}
+
+#[cfg(unix)]
+fn unlock(bytes: &[u8]) {
+    let mut counts = page_locks();
+    for page in pages(bytes) {
+        if let Entry::Occupied(mut count) = counts.entry(page) {
+            *count.get_mut() -= 1;
+            if *count.get() == 0 {
+                count.remove();
+                // SAFETY: the page was locked by `lock` and still holds part of this allocation.
+                unsafe { libc::munlock(page as *const libc::c_void, page_size()) };
+            }
+        }
+    }
+}
+
+#[cfg(not(unix))]
+fn lock(_bytes: &[u8]) -> bool {
+    false
+}
+
+#[cfg(not(unix))]
+fn unlock(_bytes: &[u8]) {}
@@ -0,0 +1,107 @@
+//! Shamir secret sharing over GF(2^8) (AES polynomial), applied byte-wise. Shares are
+//! identified by a non-zero x coordinate; the secret is the polynomial's value at x = 0.
+
+use rand::RngCore;
+use zeroize::Zeroizing;
+
+/// Branch-free multiplication modulo x^8 + x^4 + x^3 + x + 1.
+fn mul(mut a: u8, mut b: u8) -> u8 {
+    let mut product = 0u8;
+    for _ in 0..8 {
+        product ^= a & 0u8.wrapping_sub(b & 1);
+        let carry = 0u8.wrapping_sub(a >> 7);
+        a = (a << 1) ^ (0x1b & carry);
+        b >>= 1;
+    }
+    product
+}
+
+/// a^254 = a^-1 for a != 0.
+fn inv(a: u8) -> u8 {
+    let mut result = 1u8;
+    let mut base = a;
+    let mut exp = 254u8;
+    while exp > 0 {
+        if exp & 1 == 1 {
+            result = mul(result, base);
+        }
+        base = mul(base, base);
+        exp >>= 1;
+    }
+    result
+}
+
+fn check_xs(xs: &[u8]) -> Result<(), String> {
+    for (i, &x) in xs.iter().enumerate() {
+        if x == 0 {
+            return Err("Share index 0 is reserved for the secret".to_string());
+        }
+        if xs[..i].contains(&x) {
+            return Err(format!("Duplicate share index {}", x));
+        }
+    }
+    Ok(())
+}
+
+/// Splits `secret` into one share per x coordinate in `xs`; any `threshold` of them recover it.
+pub(crate) fn split(
+    secret: &[u8],
+    threshold: u8,
+    xs: &[u8],
+) -> Result<Vec<Zeroizing<Vec<u8>>>, String> {
+    if threshold == 0 || threshold as usize > xs.len() {
+        return Err(format!(
+            "Threshold must be between 1 and the number of shares ({})",
+            xs.len()
+        ));
+    }
+    check_xs(xs)?;
+    // coefficients[k][byte] for k = 1..threshold; the constant term is the secret itself.
+    let mut coefficients = Zeroizing::new(vec![0u8; (threshold as usize - 1) * secret.len()]);
+    rand::thread_rng().fill_bytes(&mut coefficients);
+    Ok(xs
+        .iter()
+        .map(|&x| {
+            let share = secret
+                .iter()
+                .enumerate()
+                .map(|(byte, &s)| {
+                    // Horner's rule from the highest coefficient down.
+                    let mut y = 0u8;
+                    for k in (0..threshold as usize - 1).rev() {
+                        y = mul(y, x) ^ coefficients[k * secret.len() + byte];
+                    }
+                    mul(y, x) ^ s
+                })
+                .collect();
+            Zeroizing::new(share)
+        })
+        .collect())
+}
+
+/// Evaluates the polynomial through `shares` (x, y) at `x`. With `threshold` shares this
+/// recovers the secret at x = 0, or mints a consistent new share at any other x.
+pub(crate) fn interpolate(shares: &[(u8, &[u8])], x: u8) -> Result<Zeroizing<Vec<u8>>, String> {
+    let Some(&(_, first)) = shares.first() else {
+        return Err("No shares given".to_string());
+    };
+    if shares.iter().any(|(_, y)| y.len() != first.len()) {
+        return Err("Shares have different lengths".to_string());
+    }
+    let xs: Vec<u8> = shares.iter().map(|&(xi, _)| xi).collect();
+    check_xs(&xs)?;
+    let mut out = Zeroizing::new(vec![0u8; first.len()]);
+    for (i, &(xi, yi)) in shares.iter().enumerate() {
+        // Lagrange basis polynomial l_i evaluated at x (subtraction is XOR in GF(2^8)).
+        let mut basis = 1u8;
+        for (j, &xj) in xs.iter().enumerate() {
+            if i != j {
+                basis = mul(basis, mul(x ^ xj, inv(xi ^ xj)));
+            }
+        }
+        for (o, &y) in out.iter_mut().zip(yi) {
+            *o ^= mul(basis, y);
+        }
+    }
+    Ok(out)
+}
@@ -0,0 +1,77 @@
+use pyo3::prelude::*;
+
+use crate::armor::{self, ArmorType};
+use crate::params::{FalconParams, KyberParams};
+use crate::schemes::SignatureScheme;
+
+// DER-encoded ECDSA P-256: SEQUENCE of two INTEGERs, each up to 33 bytes
+const P256_DER_SIGNATURE_MAX_BYTES: usize = 72;
+
+/// Maximum signature size in bytes for a signature scheme, named as `SignatureScheme` names it
+/// ("falcon512", "falcon1024", "dilithium5", "ed25519", "ml-dsa-87") or one of "bls" (alias
+/// "bls12381"), "bls-unchained-g1-rfc9380" (time-lock beacon rounds), "ecdsa-p256-sha256",
+/// "lms" (for a tree of `height`), "ring" (for `ring_size` keys, which is required) and
+/// "rsabssa-sha384" (blind signatures under a `bits` RSA key). Falcon and ECDSA signatures
+/// vary in length, so this is an upper bound; `sign_falcon` returns a signed message of at
+/// most message length + this size
+#[pyfunction]
+#[pyo3(signature = (scheme, ring_size = None, height = 10, bits = 2048))]
+pub fn signature_size(
+    scheme: &str,
+    ring_size: Option<usize>,
+    height: u32,
+    bits: usize,
+) -> PyResult<usize> {
+    let value_error = pyo3::exceptions::PyValueError::new_err::<String>;
+    match scheme.to_ascii_lowercase().as_str() {
+        "bls" | "bls12381" => Ok(crate::bls::SIGNATURE_BYTES),
+        "bls-unchained-g1-rfc9380" => Ok(crate::timelock::BEACON_SIGNATURE_BYTES),
+        "ecdsa-p256-sha256" => Ok(P256_DER_SIGNATURE_MAX_BYTES),
+        "lms" => crate::lms::signature_size(height).map_err(value_error),
+        "ring" => {
+            let size = ring_size.ok_or_else(|| {
+                value_error("Ring signature size depends on ring_size".to_string())
+            })?;
+            crate::ring::signature_len(size).map_err(value_error)
+        }
+        "rsabssa-sha384" => {
+            crate::blind::check_key_size(bits)?;
+            Ok(bits / 8)
+        }
+        _ => Ok(SignatureScheme::parse(scheme)?.max_signature_bytes()),
+    }
+}
+
+/// Exact size of the envelope `encrypt_to_public_key` produces for `plaintext_len` bytes under
+/// the given Kyber profile; with `armored=True`, the size of its header-less ASCII armor
+#[pyfunction]
+#[pyo3(signature = (plaintext_len, profile = KyberParams::Kyber1024, armored = false))]
+pub fn estimate_sealed_size(plaintext_len: usize, profile: KyberParams, armored: bool) -> usize {
+    let sealed = crate::envelope::sealed_len(profile, plaintext_len);
+    if armored {
+        armor::encoded_len(ArmorType::Envelope, sealed, 0)
+    } else {
+        sealed
+    }
+}
+
+/// Upper bound on the size of an `auth_encapsulate_kyber` ciphertext
+#[pyfunction]
+#[pyo3(signature = (
+    kyber_params = KyberParams::Kyber1024,
+    falcon_params = FalconParams::Falcon1024,
+))]
+pub fn estimate_auth_encapsulation_size(
+    kyber_params: KyberParams,
+    falcon_params: FalconParams,
+) -> usize {
+    crate::akem::max_encapsulation_len(kyber_params, falcon_params)
+}
+
+/// Exact length of `armor(data, kind, headers)` for `data_len` bytes of input. `header_bytes`
+/// is the total length of the header lines ("Key: value\n" each)
+#[pyfunction]
+#[pyo3(signature = (data_len, kind, header_bytes = 0))]
+pub fn estimate_armored_size(data_len: usize, kind: ArmorType, header_bytes: usize) -> usize {
+    armor::encoded_len(kind, data_len, header_bytes)
+}
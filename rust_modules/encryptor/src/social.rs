@@ -0,0 +1,402 @@
+use std::collections::HashSet;
+
+use aes_gcm::aead::{Aead, AeadCore, KeyInit, OsRng, Payload};
+use aes_gcm::{Aes256Gcm, Nonce};
+use hkdf::Hkdf;
+use pyo3::prelude::*;
+use rand::RngCore;
+use sha2::Sha256;
+use sha3::{Digest, Sha3_256};
+use zeroize::Zeroizing;
+
+use crate::fingerprint::{fingerprint, FINGERPRINT_BYTES};
+use crate::handles::DecryptionKeyArg;
+use crate::params::KyberParams;
+use crate::shamir;
+use crate::wire::{put_var8, Reader};
+
+// Social recovery: a master secret is encrypted under a random data key (DEK), the DEK is
+// Shamir-split among guardians and each share is sealed to one guardian's Kyber key.
+//
+// Recovery descriptor (kept by the owner, or anywhere: it opens nothing on its own):
+//   magic "RQSD" | version u8 | recovery id (16) | threshold u8 | guardian count u8
+//   | label var8 | per guardian: Kyber key fingerprint (32) | share commitment (32)
+//   | nonce (12) | AES-256-GCM(secret)
+// Guardian package (one per guardian):
+//   magic "RQSP" | version u8 | recovery id (16) | share index u8 | guardian fingerprint (32)
+//   | kem id u8 | kem ciphertext | nonce (12) | AES-256-GCM(share)
+// Released share (what a guardian hands back after opening its package):
+//   magic "RQSS" | version u8 | recovery id (16) | share index u8 | share (32)
+// Share i is committed to as SHA3-256(COMMIT_LABEL | recovery id | i | share), so the recoverer
+// can drop wrong or forged shares before combining. DEK shares are uniformly random 32-byte
+// values, so the commitments reveal nothing usable. The AAD of the secret and of each share is
+// everything before its nonce; share wrap keys are HKDF(Kyber shared secret, salt = that AAD).
+const DESCRIPTOR_MAGIC: &[u8; 4] = b"RQSD";
+const PACKAGE_MAGIC: &[u8; 4] = b"RQSP";
+const SHARE_MAGIC: &[u8; 4] = b"RQSS";
+const VERSION: u8 = 1;
+const ID_BYTES: usize = 16;
+const DEK_BYTES: usize = 32;
+const NONCE_BYTES: usize = 12;
+const COMMITMENT_BYTES: usize = 32;
+const SECRET_LABEL: &[u8] = b"reliquary/social-recovery/v1/secret";
+const WRAP_LABEL: &[u8] = b"reliquary/social-recovery/v1/wrap";
+const COMMIT_LABEL: &[u8] = b"reliquary/social-recovery/v1/commitment";
+
+/// (descriptor, guardian packages in guardian order)
+type RecoveryKit = (Vec<u8>, Vec<Vec<u8>>);
+/// (recovery_id, threshold, label, guardian fingerprints)
+type DescriptorInfo = (Vec<u8>, u8, String, Vec<Vec<u8>>);
+
+fn derive(ikm: &[u8], salt: Option<&[u8]>, label: &[u8]) -> Zeroizing<[u8; 32]> {
+    let mut key = Zeroizing::new([0u8; 32]);
+    Hkdf::<Sha256>::new(salt, ikm)
+        .expand(label, key.as_mut())
+        .expect("32 bytes is a valid HKDF-SHA256 output length");
+    key
+}
+
+fn commitment(recovery_id: &[u8], index: u8, share: &[u8]) -> [u8; COMMITMENT_BYTES] {
+    let mut hash = Sha3_256::new();
+    hash.update(COMMIT_LABEL);
+    hash.update(recovery_id);
+    hash.update([index]);
+    hash.update(share);
+    hash.finalize().into()
+}
+
+fn check_header<'a>(r: &mut Reader<'a>, magic: &[u8; 4], what: &str) -> Result<(), String> {
+    if r.bytes(4).map_err(|_| format!("Not a {}", what))? != magic {
+        return Err(format!("Not a {}", what));
+    }
+    let version = r.u8()?;
+    if version != VERSION {
+        return Err(format!("Unsupported {} version {}", what, version));
+    }
+    Ok(())
+}
+
+struct Guardian<'a> {
+    fingerprint: &'a [u8],
+    commitment: &'a [u8],
+}
+
+struct Descriptor<'a> {
+    /// Everything before the nonce; the AAD of the secret.
+    header: &'a [u8],
+    recovery_id: &'a [u8],
+    threshold: u8,
+    label: &'a [u8],
+    guardians: Vec<Guardian<'a>>,
+    nonce: &'a [u8],
+    ciphertext: &'a [u8],
+}
+
+fn parse_descriptor(bytes: &[u8]) -> Result<Descriptor<'_>, String> {
+    let mut r = Reader::new(bytes);
+    check_header(&mut r, DESCRIPTOR_MAGIC, "recovery descriptor")?;
+    let recovery_id = r.bytes(ID_BYTES)?;
+    let threshold = r.u8()?;
+    let count = r.u8()?;
+    if threshold == 0 || threshold > count {
+        return Err("Malformed recovery descriptor threshold".to_string());
+    }
+    let label = r.var8()?;
+    let guardians = (0..count)
+        .map(|_| {
+            Ok(Guardian {
+                fingerprint: r.bytes(FINGERPRINT_BYTES)?,
+                commitment: r.bytes(COMMITMENT_BYTES)?,
+            })
+        })
+        .collect::<Result<Vec<_>, String>>()?;
+    let header = &bytes[..r.position()];
+    Ok(Descriptor {
+        header,
+        recovery_id,
+        threshold,
+        label,
+        guardians,
+        nonce: r.bytes(NONCE_BYTES)?,
+        ciphertext: r.rest(),
+    })
+}
+
+struct Package<'a> {
+    /// Everything before the nonce; the AAD of the share.
+    header: &'a [u8],
+    recovery_id: &'a [u8],
+    index: u8,
+    fingerprint: &'a [u8],
+    params: KyberParams,
+    kem_ciphertext: &'a [u8],
+    nonce: &'a [u8],
+    wrapped_share: &'a [u8],
+}
+
+fn parse_package(bytes: &[u8]) -> Result<Package<'_>, String> {
+    let mut r = Reader::new(bytes);
+    check_header(&mut r, PACKAGE_MAGIC, "guardian package")?;
+    let recovery_id = r.bytes(ID_BYTES)?;
+    let index = r.u8()?;
+    let fingerprint = r.bytes(FINGERPRINT_BYTES)?;
+    let id = r.u8()?;
+    let params = KyberParams::from_wire_id(id).ok_or_else(|| format!("Unknown KEM id {}", id))?;
+    let kem_ciphertext = r.bytes(params.ciphertext_bytes())?;
+    let header = &bytes[..r.position()];
+    Ok(Package {
+        header,
+        recovery_id,
+        index,
+        fingerprint,
+        params,
+        kem_ciphertext,
+        nonce: r.bytes(NONCE_BYTES)?,
+        wrapped_share: r.rest(),
+    })
+}
+
+fn parse_released(bytes: &[u8]) -> Result<(&[u8], u8, &[u8]), String> {
+    let mut r = Reader::new(bytes);
+    check_header(&mut r, SHARE_MAGIC, "released guardian share")?;
+    let recovery_id = r.bytes(ID_BYTES)?;
+    let index = r.u8()?;
+    let share = r.bytes(DEK_BYTES)?;
+    if !r.is_empty() {
+        return Err("Trailing data after released guardian share".to_string());
+    }
+    Ok((recovery_id, index, share))
+}
+
+/// Whether a released share belongs to the descriptor and matches its commitment.
+fn share_matches(descriptor: &Descriptor, released: &[u8]) -> bool {
+    let Ok((recovery_id, index, share)) = parse_released(released) else {
+        return false;
+    };
+    let Some(guardian) = (index as usize)
+        .checked_sub(1)
+        .and_then(|i| descriptor.guardians.get(i))
+    else {
+        return false;
+    };
+    recovery_id == descriptor.recovery_id
+        && commitment(recovery_id, index, share).as_slice() == guardian.commitment
+}
+
+fn seal(
+    secret: &[u8],
+    guardian_pks: &[Vec<u8>],
+    threshold: u8,
+    params: KyberParams,
+    label: &str,
+) -> PyResult<RecoveryKit> {
+    let invalid = |msg: String| pyo3::exceptions::PyValueError::new_err(msg);
+    let count = u8::try_from(guardian_pks.len())
+        .map_err(|_| invalid("At most 255 guardians are supported".to_string()))?;
+    let fingerprints: Vec<[u8; FINGERPRINT_BYTES]> =
+        guardian_pks.iter().map(|pk| fingerprint(pk)).collect();
+    if fingerprints.iter().collect::<HashSet<_>>().len() != fingerprints.len() {
+        return Err(invalid(
+            "Each guardian key may only be given once".to_string(),
+        ));
+    }
+    let mut recovery_id = [0u8; ID_BYTES];
+    rand::thread_rng().fill_bytes(&mut recovery_id);
+    let mut dek = Zeroizing::new([0u8; DEK_BYTES]);
+    rand::thread_rng().fill_bytes(dek.as_mut());
+    let xs: Vec<u8> = (1..=count).collect();
+    let shares = shamir::split(dek.as_ref(), threshold, &xs).map_err(invalid)?;
+
+    let mut descriptor = DESCRIPTOR_MAGIC.to_vec();
+    descriptor.push(VERSION);
+    descriptor.extend_from_slice(&recovery_id);
+    descriptor.extend_from_slice(&[threshold, count]);
+    put_var8(&mut descriptor, label.as_bytes()).map_err(invalid)?;
+    for ((fp, share), &x) in fingerprints.iter().zip(&shares).zip(&xs) {
+        descriptor.extend_from_slice(fp);
+        descriptor.extend_from_slice(&commitment(&recovery_id, x, share));
+    }
+    let nonce = Aes256Gcm::generate_nonce(&mut OsRng);
+    let ciphertext = Aes256Gcm::new(derive(dek.as_ref(), None, SECRET_LABEL).as_ref().into())
+        .encrypt(
+            &nonce,
+            Payload {
+                msg: secret,
+                aad: &descriptor,
+            },
+        )
+        .map_err(|e| invalid(format!("Encryption error: {:?}", e)))?;
+    descriptor.extend_from_slice(&nonce);
+    descriptor.extend_from_slice(&ciphertext);
+
+    let mut packages = Vec::with_capacity(guardian_pks.len());
+    for (((pk, fp), share), &x) in guardian_pks.iter().zip(&fingerprints).zip(&shares).zip(&xs) {
+        let (shared_secret, kem_ciphertext) = crate::kyber_encapsulate(pk, params)?;
+        let shared_secret = Zeroizing::new(shared_secret);
+        let mut package = PACKAGE_MAGIC.to_vec();
+        package.push(VERSION);
+        package.extend_from_slice(&recovery_id);
+        package.push(x);
+        package.extend_from_slice(fp);
+        package.push(params.wire_id());
+        package.extend_from_slice(&kem_ciphertext);
+        let key = derive(&shared_secret, Some(&package), WRAP_LABEL);
+        let nonce = Aes256Gcm::generate_nonce(&mut OsRng);
+        let wrapped = Aes256Gcm::new(key.as_ref().into())
+            .encrypt(
+                &nonce,
+                Payload {
+                    msg: share.as_slice(),
+                    aad: &package,
+                },
+            )
+            .map_err(|e| invalid(format!("Encryption error: {:?}", e)))?;
+        package.extend_from_slice(&nonce);
+        package.extend_from_slice(&wrapped);
+        packages.push(package);
+    }
+    Ok((descriptor, packages))
+}
+
+/// Splits `secret` among guardians for social recovery: any `threshold` of the guardians
+/// holding the Kyber secret keys for `guardian_pks` can restore it. Returns (descriptor,
+/// packages): the recovery descriptor, which holds the encrypted secret and opens nothing on
+/// its own, and one encrypted share package per guardian, in the order of `guardian_pks`
+#[pyfunction]
+#[pyo3(signature = (secret, guardian_pks, threshold, params = KyberParams::Kyber1024, label = ""))]
+pub fn create_social_recovery(
+    py: Python<'_>,
+    secret: Vec<u8>,
+    guardian_pks: Vec<Vec<u8>>,
+    threshold: u8,
+    params: KyberParams,
+    label: &str,
+) -> PyResult<RecoveryKit> {
+    let secret = Zeroizing::new(secret);
+    py.allow_threads(|| seal(&secret, &guardian_pks, threshold, params, label))
+}
+
+/// Describes a recovery descriptor as (recovery_id, threshold, label, guardian_fingerprints);
+/// fingerprints are in share index order, starting at index 1
+#[pyfunction]
+pub fn social_recovery_info(descriptor: Vec<u8>) -> PyResult<DescriptorInfo> {
+    let parsed = parse_descriptor(&descriptor).map_err(pyo3::exceptions::PyValueError::new_err)?;
+    Ok((
+        parsed.recovery_id.to_vec(),
+        parsed.threshold,
+        String::from_utf8_lossy(parsed.label).into_owned(),
+        parsed
+            .guardians
+            .iter()
+            .map(|g| g.fingerprint.to_vec())
+            .collect(),
+    ))
+}
+
+/// Guardian side: opens a share package with the guardian's Kyber secret key (bytes or a
+/// handle) and returns the released share to hand to whoever is recovering
+#[pyfunction]
+pub fn open_guardian_package(
+    py: Python<'_>,
+    package: Vec<u8>,
+    guardian_sk: DecryptionKeyArg,
+) -> PyResult<Vec<u8>> {
+    py.allow_threads(|| {
+        let parsed = parse_package(&package).map_err(pyo3::exceptions::PyValueError::new_err)?;
+        let params = guardian_sk.kyber_params(parsed.params)?;
+        if params != parsed.params {
+            return Err(pyo3::exceptions::PyValueError::new_err(
+                "Guardian key uses different Kyber parameters than the package",
+            ));
+        }
+        let shared_secret = Zeroizing::new(
+            crate::kyber_decapsulate(parsed.kem_ciphertext, &guardian_sk, params)
+                .map_err(|_| pyo3::exceptions::PyValueError::new_err("KEM decapsulation failed"))?,
+        );
+        let key = derive(&shared_secret, Some(parsed.header), WRAP_LABEL);
+        let share = Zeroizing::new(
+            Aes256Gcm::new(key.as_ref().into())
+                .decrypt(
+                    Nonce::from_slice(parsed.nonce),
+                    Payload {
+                        msg: parsed.wrapped_share,
+                        aad: parsed.header,
+                    },
+                )
+                .map_err(|_| {
+                    pyo3::exceptions::PyValueError::new_err(
+                        "Guardian package authentication failed (wrong key or tampered package)",
+                    )
+                })?,
+        );
+        let mut released = SHARE_MAGIC.to_vec();
+        released.push(VERSION);
+        released.extend_from_slice(parsed.recovery_id);
+        released.push(parsed.index);
+        released.extend_from_slice(&share);
+        Ok(released)
+    })
+}
+
+/// Describes a guardian package as (recovery_id, share_index, guardian_fingerprint)
+#[pyfunction]
+pub fn guardian_package_info(package: Vec<u8>) -> PyResult<(Vec<u8>, u8, Vec<u8>)> {
+    let parsed = parse_package(&package).map_err(pyo3::exceptions::PyValueError::new_err)?;
+    Ok((
+        parsed.recovery_id.to_vec(),
+        parsed.index,
+        parsed.fingerprint.to_vec(),
+    ))
+}
+
+/// True if a released share belongs to the descriptor and matches the guardian's commitment
+#[pyfunction]
+pub fn verify_released_share(descriptor: Vec<u8>, share: Vec<u8>) -> PyResult<bool> {
+    let parsed = parse_descriptor(&descriptor).map_err(pyo3::exceptions::PyValueError::new_err)?;
+    Ok(share_matches(&parsed, &share))
+}
+
+/// Restores the master secret from released guardian shares. Shares that don't match the
+/// descriptor's commitments are skipped, so a wrong or malicious guardian can't corrupt the
+/// result; raises ValueError when fewer than `threshold` valid shares remain
+#[pyfunction]
+pub fn recover_social_secret(
+    py: Python<'_>,
+    descriptor: Vec<u8>,
+    shares: Vec<Vec<u8>>,
+) -> PyResult<Vec<u8>> {
+    let shares: Vec<Zeroizing<Vec<u8>>> = shares.into_iter().map(Zeroizing::new).collect();
+    py.allow_threads(|| {
+        (|| -> Result<Vec<u8>, String> {
+            let parsed = parse_descriptor(&descriptor)?;
+            let mut seen = HashSet::new();
+            let mut points: Vec<(u8, &[u8])> = Vec::new();
+            for released in &shares {
+                if share_matches(&parsed, released) {
+                    let (_, index, share) = parse_released(released)?;
+                    if seen.insert(index) {
+                        points.push((index, share));
+                    }
+                }
+            }
+            if points.len() < parsed.threshold as usize {
+                return Err(format!(
+                    "Need {} valid guardian shares, got {}",
+                    parsed.threshold,
+                    points.len()
+                ));
+            }
+            let dek = shamir::interpolate(&points[..parsed.threshold as usize], 0)?;
+            Aes256Gcm::new(derive(&dek, None, SECRET_LABEL).as_ref().into())
+                .decrypt(
+                    Nonce::from_slice(parsed.nonce),
+                    Payload {
+                        msg: parsed.ciphertext,
+                        aad: parsed.header,
+                    },
+                )
+                .map_err(|_| "Recovery descriptor authentication failed".to_string())
+        })()
+        .map_err(pyo3::exceptions::PyValueError::new_err)
+    })
+}
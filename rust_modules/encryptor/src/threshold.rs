@@ -0,0 +1,308 @@
+use std::collections::BTreeMap;
+
+use aes_gcm::aead::{Aead, AeadCore, KeyInit, OsRng, Payload};
+use aes_gcm::{Aes256Gcm, Nonce};
+use curve25519_dalek::constants::RISTRETTO_BASEPOINT_POINT;
+use curve25519_dalek::ristretto::RistrettoPoint;
+use curve25519_dalek::scalar::Scalar;
+use curve25519_dalek::traits::IsIdentity;
+use hkdf::Hkdf;
+use pyo3::prelude::*;
+use sha2::{Digest, Sha256, Sha512};
+use zeroize::Zeroizing;
+
+use crate::dkg::{key_share_is_valid, parse_group_key, parse_key_share, GroupKey};
+use crate::vss::{lagrange_coefficients, random_scalar, read_point, scalar};
+use crate::wire::Reader;
+
+// Threshold decryption under a DKG group key (see dkg.rs), so unwrapping a vault KEK needs
+// `threshold` trustees and no single node can decrypt alone. This is hashed ElGamal: the sender
+// picks r, publishes R = r*G and encrypts under a key derived from r*Y, where Y is the group
+// public key. Trustee i answers with the partial D_i = x_i*R plus a Chaum-Pedersen proof that
+// log_G(x_i*G) == log_R(D_i), so a bad partial is detected and skipped instead of silently
+// corrupting the result; any threshold valid partials give r*Y = sum_i lambda_i * D_i.
+// Layouts:
+//   ciphertext: magic "RQTE" | version u8 | session id (16) | R (32) | nonce (12)
+//               | AES-256-GCM(plaintext)
+//   partial:    magic "RQTP" | version u8 | session id (16) | index u8 | D_i (32)
+//               | proof: c (32) | z (32)
+// The AES key is HKDF-SHA256(salt = R || Y, ikm = r*Y); the AAD is the ciphertext header up
+// to the nonce followed by the caller's associated data.
+const CIPHERTEXT_MAGIC: &[u8; 4] = b"RQTE";
+const PARTIAL_MAGIC: &[u8; 4] = b"RQTP";
+const VERSION: u8 = 1;
+const SESSION_BYTES: usize = 16;
+const NONCE_BYTES: usize = 12;
+const KEY_LABEL: &[u8] = b"reliquary/threshold/v1/aes-256-gcm";
+const PROOF_LABEL: &[u8] = b"reliquary/threshold/v1/partial-proof";
+
+struct Ciphertext<'a> {
+    header: &'a [u8],
+    session: &'a [u8],
+    r: RistrettoPoint,
+    nonce: &'a [u8],
+    body: &'a [u8],
+}
+
+fn parse_ciphertext(bytes: &[u8]) -> Result<Ciphertext<'_>, String> {
+    let mut r = Reader::new(bytes);
+    if r.bytes(4).map_err(|_| "Not a threshold ciphertext")? != CIPHERTEXT_MAGIC {
+        return Err("Not a threshold ciphertext".to_string());
+    }
+    let version = r.u8()?;
+    if version != VERSION {
+        return Err(format!(
+            "Unsupported threshold ciphertext version {}",
+            version
+        ));
+    }
+    let session = r.bytes(SESSION_BYTES)?;
+    let point = read_point(&mut r, "Ciphertext R")?;
+    if point.is_identity() {
+        return Err("Ciphertext R is the identity".to_string());
+    }
+    let header = &bytes[..r.position()];
+    Ok(Ciphertext {
+        header,
+        session,
+        r: point,
+        nonce: r.bytes(NONCE_BYTES)?,
+        body: r.rest(),
+    })
+}
+
+struct Partial {
+    session: Vec<u8>,
+    index: u8,
+    d: RistrettoPoint,
+    c: Scalar,
+    z: Scalar,
+}
+
+fn parse_partial(bytes: &[u8]) -> Result<Partial, String> {
+    let mut r = Reader::new(bytes);
+    if r.bytes(4).map_err(|_| "Not a threshold partial")? != PARTIAL_MAGIC {
+        return Err("Not a threshold partial".to_string());
+    }
+    let version = r.u8()?;
+    if version != VERSION {
+        return Err(format!("Unsupported threshold partial version {}", version));
+    }
+    let session = r.bytes(SESSION_BYTES)?.to_vec();
+    let index = r.u8()?;
+    let d = read_point(&mut r, "Partial")?;
+    let c = scalar(r.bytes(32)?, "proof challenge")?;
+    let z = scalar(r.bytes(32)?, "proof response")?;
+    if !r.is_empty() {
+        return Err("Trailing data after threshold partial".to_string());
+    }
+    Ok(Partial {
+        session,
+        index,
+        d,
+        c,
+        z,
+    })
+}
+
+fn aes_key(r: &RistrettoPoint, group: &GroupKey, shared: &RistrettoPoint) -> Zeroizing<[u8; 32]> {
+    let mut salt = r.compress().to_bytes().to_vec();
+    salt.extend_from_slice(group.public_key().compress().as_bytes());
+    let ikm = Zeroizing::new(shared.compress().to_bytes());
+    let mut key = Zeroizing::new([0u8; 32]);
+    Hkdf::<Sha256>::new(Some(&salt), ikm.as_ref())
+        .expand(KEY_LABEL, key.as_mut())
+        .expect("32 bytes is a valid HKDF-SHA256 output length");
+    key
+}
+
+fn proof_challenge(
+    ciphertext: &Ciphertext<'_>,
+    index: u8,
+    public_share: &RistrettoPoint,
+    d: &RistrettoPoint,
+    a1: &RistrettoPoint,
+    a2: &RistrettoPoint,
+) -> Scalar {
+    let mut hasher = Sha512::new();
+    hasher.update(PROOF_LABEL);
+    hasher.update(ciphertext.header);
+    hasher.update([index]);
+    for point in [public_share, d, a1, a2] {
+        hasher.update(point.compress().as_bytes());
+    }
+    Scalar::from_hash(hasher)
+}
+
+fn partial_is_valid(group: &GroupKey, ciphertext: &Ciphertext<'_>, partial: &Partial) -> bool {
+    if partial.session != group.session || !group.qualified.contains(&partial.index) {
+        return false;
+    }
+    let public_share = group.public_share(partial.index);
+    let a1 = RISTRETTO_BASEPOINT_POINT * partial.z - public_share * partial.c;
+    let a2 = ciphertext.r * partial.z - partial.d * partial.c;
+    proof_challenge(
+        ciphertext,
+        partial.index,
+        &public_share,
+        &partial.d,
+        &a1,
+        &a2,
+    ) == partial.c
+}
+
+fn parse_inputs<'a>(
+    group_key: &[u8],
+    ciphertext: &'a [u8],
+) -> Result<(GroupKey, Ciphertext<'a>), String> {
+    let group = parse_group_key(group_key)?;
+    let ciphertext = parse_ciphertext(ciphertext)?;
+    if ciphertext.session != group.session.as_slice() {
+        return Err("Ciphertext is for another group key".to_string());
+    }
+    Ok((group, ciphertext))
+}
+
+/// Encrypts `plaintext` (typically a KEK or DEK) to a DKG group key. Decrypting needs partials
+/// from `threshold` holders of the group's key shares; `aad` must be given again to decrypt
+#[pyfunction]
+#[pyo3(signature = (group_key, plaintext, aad = Vec::new()))]
+pub fn threshold_encrypt(
+    group_key: Vec<u8>,
+    plaintext: Vec<u8>,
+    aad: Vec<u8>,
+) -> PyResult<Vec<u8>> {
+    let plaintext = Zeroizing::new(plaintext);
+    (|| -> Result<Vec<u8>, String> {
+        let group = parse_group_key(&group_key)?;
+        let r = Zeroizing::new(random_scalar());
+        let point = RISTRETTO_BASEPOINT_POINT * *r;
+        let key = aes_key(&point, &group, &(group.public_key() * *r));
+
+        let mut out = CIPHERTEXT_MAGIC.to_vec();
+        out.push(VERSION);
+        out.extend_from_slice(&group.session);
+        out.extend_from_slice(point.compress().as_bytes());
+        let mut full_aad = out.clone();
+        full_aad.extend_from_slice(&aad);
+        let nonce = Aes256Gcm::generate_nonce(&mut OsRng);
+        let body = Aes256Gcm::new(key.as_slice().into())
+            .encrypt(
+                &nonce,
+                Payload {
+                    msg: &plaintext,
+                    aad: &full_aad,
+                },
+            )
+            .map_err(|_| "Threshold encryption failed".to_string())?;
+        out.extend_from_slice(&nonce);
+        out.extend_from_slice(&body);
+        Ok(out)
+    })()
+    .map_err(pyo3::exceptions::PyValueError::new_err)
+}
+
+/// One trustee's contribution to decrypting `ciphertext`, computed with their DKG key share.
+/// The partial reveals nothing about the key share and carries a proof that it was computed
+/// correctly, so it may be sent over an untrusted channel to whoever combines the partials
+#[pyfunction]
+pub fn threshold_partial_decrypt(
+    group_key: Vec<u8>,
+    key_share: Vec<u8>,
+    ciphertext: Vec<u8>,
+) -> PyResult<Vec<u8>> {
+    let key_share = Zeroizing::new(key_share);
+    (|| {
+        let (group, ciphertext) = parse_inputs(&group_key, &ciphertext)?;
+        let (session, index, x) = parse_key_share(&key_share)?;
+        let x = Zeroizing::new(x);
+        if !key_share_is_valid(&group, &session, index, &x) {
+            return Err("Key share does not belong to this group key".to_string());
+        }
+        let public_share = group.public_share(index);
+        let d = ciphertext.r * *x;
+        let k = Zeroizing::new(random_scalar());
+        let a1 = RISTRETTO_BASEPOINT_POINT * *k;
+        let a2 = ciphertext.r * *k;
+        let c = proof_challenge(&ciphertext, index, &public_share, &d, &a1, &a2);
+        let z = *k + c * *x;
+
+        let mut out = PARTIAL_MAGIC.to_vec();
+        out.push(VERSION);
+        out.extend_from_slice(&group.session);
+        out.push(index);
+        out.extend_from_slice(d.compress().as_bytes());
+        out.extend_from_slice(c.as_bytes());
+        out.extend_from_slice(z.as_bytes());
+        Ok(out)
+    })()
+    .map_err(pyo3::exceptions::PyValueError::new_err)
+}
+
+/// Checks a partial's proof against the group key and ciphertext. False means the partial is
+/// corrupt, for another ciphertext, or from a participant outside the qualified set
+#[pyfunction]
+pub fn threshold_verify_partial(
+    group_key: Vec<u8>,
+    ciphertext: Vec<u8>,
+    partial: Vec<u8>,
+) -> PyResult<bool> {
+    let (group, ciphertext) =
+        parse_inputs(&group_key, &ciphertext).map_err(pyo3::exceptions::PyValueError::new_err)?;
+    Ok(parse_partial(&partial).is_ok_and(|p| partial_is_valid(&group, &ciphertext, &p)))
+}
+
+/// Combines partials into the plaintext. Invalid and duplicate partials are skipped; raises
+/// ValueError if fewer than threshold valid ones remain or the ciphertext fails to authenticate
+#[pyfunction]
+#[pyo3(signature = (group_key, ciphertext, partials, aad = Vec::new()))]
+pub fn threshold_decrypt(
+    py: Python<'_>,
+    group_key: Vec<u8>,
+    ciphertext: Vec<u8>,
+    partials: Vec<Vec<u8>>,
+    aad: Vec<u8>,
+) -> PyResult<Vec<u8>> {
+    py.allow_threads(|| {
+        let (group, ciphertext) = parse_inputs(&group_key, &ciphertext)?;
+        let mut valid = BTreeMap::new();
+        for bytes in &partials {
+            if let Ok(partial) = parse_partial(bytes) {
+                if !valid.contains_key(&partial.index)
+                    && partial_is_valid(&group, &ciphertext, &partial)
+                {
+                    valid.insert(partial.index, partial.d);
+                }
+            }
+            if valid.len() == group.threshold as usize {
+                break;
+            }
+        }
+        if valid.len() < group.threshold as usize {
+            return Err(format!(
+                "Need {} valid partials, got {}",
+                group.threshold,
+                valid.len()
+            ));
+        }
+        let indices: Vec<u8> = valid.keys().copied().collect();
+        let shared: RistrettoPoint = lagrange_coefficients(&indices)
+            .iter()
+            .zip(valid.values())
+            .map(|(lambda, d)| d * lambda)
+            .sum();
+        let key = aes_key(&ciphertext.r, &group, &shared);
+        let mut full_aad = ciphertext.header.to_vec();
+        full_aad.extend_from_slice(&aad);
+        Aes256Gcm::new(key.as_slice().into())
+            .decrypt(
+                Nonce::from_slice(ciphertext.nonce),
+                Payload {
+                    msg: ciphertext.body,
+                    aad: &full_aad,
+                },
+            )
+            .map_err(|_| "Threshold ciphertext does not authenticate".to_string())
+    })
+    .map_err(pyo3::exceptions::PyValueError::new_err)
+}
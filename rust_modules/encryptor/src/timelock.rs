@@ -0,0 +1,292 @@
+use std::mem::MaybeUninit;
+
+use aes_gcm::aead::{Aead, AeadCore, KeyInit, OsRng, Payload};
+use aes_gcm::{Aes256Gcm, Nonce};
+use blst::min_sig::{PublicKey, SecretKey, Signature};
+use blst::{
+    blst_fp12, blst_hash_to_g1, blst_p1, blst_p1_affine, blst_p1_to_affine, blst_p2,
+    blst_p2_affine, blst_p2_from_affine, blst_p2_generator, blst_p2_mult, blst_p2_to_affine,
+    BLST_ERROR,
+};
+use hkdf::Hkdf;
+use pyo3::prelude::*;
+use rand::RngCore;
+use sha2::{Digest, Sha256};
+use zeroize::Zeroizing;
+
+use crate::handles::SigningKeyArg;
+use crate::wire::Reader;
+
+// Time-lock encryption against a randomness beacon in the drand "bls-unchained-g1-rfc9380"
+// scheme (quicknet): the beacon publishes, for every round n, a BLS signature in G1 on
+// SHA-256(n as u64 big-endian) under a fixed G2 public key. Nobody, including the sender, can
+// produce that signature before the beacon does, so it serves as the decryption key for
+// round n in Boneh-Franklin identity-based encryption with the round as the identity:
+//   seal: r random, U = r*G2, K = e(H(n), r*PK)
+//   open: K = e(sig_n, U) = e(s*H(n), r*G2)
+// K is hashed into an AES-256-GCM key. Layout:
+//   magic "RQTL" | version u8 | scheme u8 | beacon key id (16) | round u64 | U (96)
+//   | nonce (12) | AES-256-GCM(plaintext)
+// The beacon key id is the first 16 bytes of SHA-256(public key), so opening with the wrong
+// beacon fails clearly. The AAD is everything before the nonce. The format is our own; it is
+// not the tlock/age format, though it opens with the same drand signatures.
+const MAGIC: &[u8; 4] = b"RQTL";
+const VERSION: u8 = 1;
+const SCHEME_UNCHAINED_G1: u8 = 1;
+const KEY_ID_BYTES: usize = 16;
+const G2_BYTES: usize = 96;
+pub(crate) const BEACON_SIGNATURE_BYTES: usize = 48;
+const NONCE_BYTES: usize = 12;
+const BEACON_DST: &[u8] = b"BLS_SIG_BLS12381G1_XMD:SHA-256_SSWU_RO_NUL_";
+const KEY_LABEL: &[u8] = b"reliquary/timelock/v1/aes-256-gcm";
+
+fn beacon_key(public_key: &[u8]) -> Result<PublicKey, String> {
+    // key_validate rejects the identity point and points outside the G2 subgroup
+    PublicKey::key_validate(public_key).map_err(|e| format!("Invalid beacon public key: {:?}", e))
+}
+
+fn key_id(public_key: &PublicKey) -> [u8; KEY_ID_BYTES] {
+    Sha256::digest(public_key.to_bytes())[..KEY_ID_BYTES]
+        .try_into()
+        .expect("16-byte slice")
+}
+
+fn round_message(round: u64) -> [u8; 32] {
+    Sha256::digest(round.to_be_bytes()).into()
+}
+
+/// H(n): the round's message hashed to G1 with the beacon's DST.
+fn round_point(round: u64) -> blst_p1_affine {
+    let msg = round_message(round);
+    let mut point = MaybeUninit::<blst_p1>::uninit();
+    let mut affine = MaybeUninit::<blst_p1_affine>::uninit();
+    // SAFETY: all pointers are valid for the stated lengths and the outputs are initialized
+    // by the calls before they are read.
+    unsafe {
+        blst_hash_to_g1(
+            point.as_mut_ptr(),
+            msg.as_ptr(),
+            msg.len(),
+            BEACON_DST.as_ptr(),
+            BEACON_DST.len(),
+            std::ptr::null(),
+            0,
+        );
+        blst_p1_to_affine(affine.as_mut_ptr(), point.as_ptr());
+        affine.assume_init()
+    }
+}
+
+/// scalar * point in G2, with the scalar as 32 big-endian bytes.
+fn g2_mul(point: &blst_p2, scalar_be: &[u8; 32]) -> blst_p2_affine {
+    let scalar_le = Zeroizing::new({
+        let mut le = *scalar_be;
+        le.reverse();
+        le
+    });
+    let mut product = MaybeUninit::<blst_p2>::uninit();
+    let mut affine = MaybeUninit::<blst_p2_affine>::uninit();
+    // SAFETY: the scalar buffer holds 256 bits and the outputs are initialized by the calls
+    // before they are read.
+    unsafe {
+        blst_p2_mult(product.as_mut_ptr(), point, scalar_le.as_ptr(), 256);
+        blst_p2_to_affine(affine.as_mut_ptr(), product.as_ptr());
+        affine.assume_init()
+    }
+}
+
+fn g2_from_affine(affine: &blst_p2_affine) -> blst_p2 {
+    let mut point = MaybeUninit::<blst_p2>::uninit();
+    // SAFETY: the output is initialized by the call before it is read.
+    unsafe {
+        blst_p2_from_affine(point.as_mut_ptr(), affine);
+        point.assume_init()
+    }
+}
+
+fn aes_key(u: &[u8], gt: &blst_fp12) -> Zeroizing<[u8; 32]> {
+    let ikm = Zeroizing::new(gt.to_bendian());
+    let mut key = Zeroizing::new([0u8; 32]);
+    Hkdf::<Sha256>::new(Some(u), ikm.as_ref())
+        .expand(KEY_LABEL, key.as_mut())
+        .expect("32 bytes is a valid HKDF-SHA256 output length");
+    key
+}
+
+fn verify_round(public_key: &PublicKey, round: u64, signature: &[u8]) -> Result<Signature, String> {
+    let sig = Signature::from_bytes(signature)
+        .map_err(|e| format!("Invalid beacon signature: {:?}", e))?;
+    match sig.verify(
+        true,
+        &round_message(round),
+        BEACON_DST,
+        &[],
+        public_key,
+        false,
+    ) {
+        BLST_ERROR::BLST_SUCCESS => Ok(sig),
+        _ => Err(format!(
+            "Signature is not the beacon's signature for round {}",
+            round
+        )),
+    }
+}
+
+struct Sealed<'a> {
+    header: &'a [u8],
+    key_id: &'a [u8],
+    round: u64,
+    u: &'a [u8],
+    nonce: &'a [u8],
+    body: &'a [u8],
+}
+
+fn parse(bytes: &[u8]) -> Result<Sealed<'_>, String> {
+    let mut r = Reader::new(bytes);
+    if r.bytes(4).map_err(|_| "Not a time-locked ciphertext")? != MAGIC {
+        return Err("Not a time-locked ciphertext".to_string());
+    }
+    let version = r.u8()?;
+    if version != VERSION {
+        return Err(format!("Unsupported time-lock version {}", version));
+    }
+    let scheme = r.u8()?;
+    if scheme != SCHEME_UNCHAINED_G1 {
+        return Err(format!("Unknown time-lock scheme id {}", scheme));
+    }
+    let key_id = r.bytes(KEY_ID_BYTES)?;
+    let round = r.u64()?;
+    let u = r.bytes(G2_BYTES)?;
+    let header = &bytes[..r.position()];
+    Ok(Sealed {
+        header,
+        key_id,
+        round,
+        u,
+        nonce: r.bytes(NONCE_BYTES)?,
+        body: r.rest(),
+    })
+}
+
+/// Encrypts `plaintext` so it can only be decrypted with the beacon's signature for `round`,
+/// i.e. not before the beacon reaches that round. `beacon_public_key` is the 96-byte G2 key of
+/// a drand bls-unchained-g1-rfc9380 chain (such as quicknet) or of a self-hosted beacon
+#[pyfunction]
+pub fn timelock_encrypt(
+    beacon_public_key: Vec<u8>,
+    round: u64,
+    plaintext: Vec<u8>,
+) -> PyResult<Vec<u8>> {
+    let plaintext = Zeroizing::new(plaintext);
+    (|| -> Result<Vec<u8>, String> {
+        let public_key = beacon_key(&beacon_public_key)?;
+        if round == 0 {
+            return Err("Beacon rounds start at 1".to_string());
+        }
+        let mut ikm = Zeroizing::new([0u8; 32]);
+        rand::thread_rng().fill_bytes(ikm.as_mut());
+        let r = SecretKey::key_gen(ikm.as_ref(), &[]).expect("32 bytes of key material");
+        let r = Zeroizing::new(r.to_bytes());
+        // SAFETY: blst_p2_generator returns a pointer to a static point.
+        let u = PublicKey::from(g2_mul(unsafe { &*blst_p2_generator() }, &r)).to_bytes();
+        let r_pk = g2_mul(&g2_from_affine(&public_key.into()), &r);
+        let gt = blst_fp12::miller_loop(&r_pk, &round_point(round)).final_exp();
+        let key = aes_key(&u, &gt);
+
+        let mut out = MAGIC.to_vec();
+        out.extend_from_slice(&[VERSION, SCHEME_UNCHAINED_G1]);
+        out.extend_from_slice(&key_id(&public_key));
+        out.extend_from_slice(&round.to_be_bytes());
+        out.extend_from_slice(&u);
+        let nonce = Aes256Gcm::generate_nonce(&mut OsRng);
+        let body = Aes256Gcm::new(key.as_slice().into())
+            .encrypt(
+                &nonce,
+                Payload {
+                    msg: &plaintext,
+                    aad: &out,
+                },
+            )
+            .map_err(|_| "Time-lock encryption failed".to_string())?;
+        out.extend_from_slice(&nonce);
+        out.extend_from_slice(&body);
+        Ok(out)
+    })()
+    .map_err(pyo3::exceptions::PyValueError::new_err)
+}
+
+/// Decrypts a time-locked ciphertext with the beacon's 48-byte signature for its round. Raises
+/// ValueError if the signature is not the beacon's for that round or the ciphertext is corrupt
+#[pyfunction]
+pub fn timelock_decrypt(
+    beacon_public_key: Vec<u8>,
+    ciphertext: Vec<u8>,
+    signature: Vec<u8>,
+) -> PyResult<Vec<u8>> {
+    (|| {
+        let public_key = beacon_key(&beacon_public_key)?;
+        let sealed = parse(&ciphertext)?;
+        if sealed.key_id != key_id(&public_key) {
+            return Err("Ciphertext was sealed to a different beacon".to_string());
+        }
+        let sig: blst_p1_affine = verify_round(&public_key, sealed.round, &signature)?.into();
+        let u: blst_p2_affine = PublicKey::key_validate(sealed.u)
+            .map_err(|_| "Ciphertext U is not a valid G2 point".to_string())?
+            .into();
+        let gt = blst_fp12::miller_loop(&u, &sig).final_exp();
+        let key = aes_key(sealed.u, &gt);
+        Aes256Gcm::new(key.as_slice().into())
+            .decrypt(
+                Nonce::from_slice(sealed.nonce),
+                Payload {
+                    msg: sealed.body,
+                    aad: sealed.header,
+                },
+            )
+            .map_err(|_| "Time-locked ciphertext does not authenticate".to_string())
+    })()
+    .map_err(pyo3::exceptions::PyValueError::new_err)
+}
+
+/// Returns the beacon round a time-locked ciphertext opens at
+#[pyfunction]
+pub fn timelock_round(ciphertext: Vec<u8>) -> PyResult<u64> {
+    parse(&ciphertext)
+        .map(|sealed| sealed.round)
+        .map_err(pyo3::exceptions::PyValueError::new_err)
+}
+
+/// Checks a beacon signature for a round, e.g. one fetched from a drand relay
+#[pyfunction]
+pub fn verify_beacon_signature(
+    beacon_public_key: Vec<u8>,
+    round: u64,
+    signature: Vec<u8>,
+) -> PyResult<bool> {
+    let public_key =
+        beacon_key(&beacon_public_key).map_err(pyo3::exceptions::PyValueError::new_err)?;
+    Ok(verify_round(&public_key, round, &signature).is_ok())
+}
+
+/// Generates keys for a self-hosted beacon. Returns (public_key, secret_key): the 96-byte G2
+/// public key and the 32-byte secret key
+#[pyfunction]
+pub fn generate_beacon_keys() -> (Vec<u8>, Vec<u8>) {
+    let mut ikm = Zeroizing::new([0u8; 32]);
+    rand::thread_rng().fill_bytes(ikm.as_mut());
+    let sk = SecretKey::key_gen(ikm.as_ref(), &[]).expect("32 bytes of key material");
+    (sk.sk_to_pk().to_bytes().to_vec(), sk.to_bytes().to_vec())
+}
+
+/// Signs a round as a self-hosted beacon. Publishing the signature opens every ciphertext
+/// sealed to that round, so it must only be released once the round's time has come
+#[pyfunction]
+pub fn sign_beacon_round(beacon_secret_key: SigningKeyArg, round: u64) -> PyResult<Vec<u8>> {
+    let sk = SecretKey::from_bytes(&beacon_secret_key).map_err(|e| {
+        pyo3::exceptions::PyValueError::new_err(format!("Invalid beacon secret key: {:?}", e))
+    })?;
+    Ok(sk
+        .sign(&round_message(round), BEACON_DST, &[])
+        .to_bytes()
+        .to_vec())
+}
@@ -0,0 +1,394 @@
+use cms::content_info::ContentInfo;
+use cms::signed_data::{SignedData, SignerIdentifier};
+use der::asn1::{Int, ObjectIdentifier, OctetString};
+use der::{Any, Decode, Encode};
+use ed25519_dalek::{Signature as Ed25519Signature, VerifyingKey as Ed25519VerifyingKey};
+use p256::ecdsa::signature::hazmat::PrehashVerifier;
+use p256::ecdsa::{Signature as P256Signature, VerifyingKey as P256VerifyingKey};
+use pyo3::prelude::*;
+use rsa::pkcs8::DecodePublicKey;
+use rsa::{Pkcs1v15Sign, RsaPublicKey};
+use sha2::{Digest, Sha256, Sha384, Sha512};
+use spki::AlgorithmIdentifier;
+use x509_cert::ext::pkix::{ExtendedKeyUsage, SubjectKeyIdentifier};
+use x509_cert::Certificate;
+use x509_tsp::{MessageImprint, TimeStampReq, TimeStampResp, TspVersion, TstInfo};
+
+use crate::pkcs::from_pem_or_der;
+
+// RFC 3161 time-stamp tokens: third-party evidence that some bytes (e.g. a signed tree head)
+// existed at a given time. create_timestamp_request builds the DER TimeStampReq a TSA expects
+// over HTTP (application/timestamp-query), timestamp_token_from_response takes the token out
+// of its reply, and verify_timestamp_token checks a token against the message and the TSA's
+// certificate, which the caller pins: the certificate must carry the critical timeStamping
+// extended key usage and cover the token's time, but no chain is built to a root. Checked:
+// the token is CMS SignedData over a TSTInfo whose message imprint matches the message (and
+// the nonce, when given), with one signer identified as that certificate, signed attributes
+// carrying the TSTInfo content type and digest, and a valid signature over them.
+//
+// Imprints may use SHA-256, SHA-384 or SHA-512. TSA keys may be RSA (PKCS #1 v1.5), ECDSA
+// P-256 or Ed25519. genTime is read to the second; TSAs that add fractional seconds aren't
+// supported.
+const ID_SIGNED_DATA: ObjectIdentifier = ObjectIdentifier::new_unwrap("1.2.840.113549.1.7.2");
+const ID_CT_TST_INFO: ObjectIdentifier = ObjectIdentifier::new_unwrap("1.2.840.113549.1.9.16.1.4");
+const ID_CONTENT_TYPE: ObjectIdentifier = ObjectIdentifier::new_unwrap("1.2.840.113549.1.9.3");
+const ID_MESSAGE_DIGEST: ObjectIdentifier = ObjectIdentifier::new_unwrap("1.2.840.113549.1.9.4");
+const ID_KP_TIME_STAMPING: ObjectIdentifier = ObjectIdentifier::new_unwrap("1.3.6.1.5.5.7.3.8");
+const ID_EXTENDED_KEY_USAGE: ObjectIdentifier = ObjectIdentifier::new_unwrap("2.5.29.37");
+const ID_SUBJECT_KEY_IDENTIFIER: ObjectIdentifier = ObjectIdentifier::new_unwrap("2.5.29.14");
+const RSA_ENCRYPTION: ObjectIdentifier = ObjectIdentifier::new_unwrap("1.2.840.113549.1.1.1");
+const EC_PUBLIC_KEY: ObjectIdentifier = ObjectIdentifier::new_unwrap("1.2.840.10045.2.1");
+const ED25519: ObjectIdentifier = ObjectIdentifier::new_unwrap("1.3.101.112");
+
+/// Digests allowed for message imprints and signer digests.
+#[derive(Clone, Copy)]
+enum TsaDigest {
+    Sha256,
+    Sha384,
+    Sha512,
+}
+
+impl TsaDigest {
+    const ALL: [TsaDigest; 3] = [TsaDigest::Sha256, TsaDigest::Sha384, TsaDigest::Sha512];
+
+    fn name(self) -> &'static str {
+        match self {
+            TsaDigest::Sha256 => "sha256",
+            TsaDigest::Sha384 => "sha384",
+            TsaDigest::Sha512 => "sha512",
+        }
+    }
+
+    fn oid(self) -> ObjectIdentifier {
+        match self {
+            TsaDigest::Sha256 => ObjectIdentifier::new_unwrap("2.16.840.1.101.3.4.2.1"),
+            TsaDigest::Sha384 => ObjectIdentifier::new_unwrap("2.16.840.1.101.3.4.2.2"),
+            TsaDigest::Sha512 => ObjectIdentifier::new_unwrap("2.16.840.1.101.3.4.2.3"),
+        }
+    }
+
+    fn from_name(name: &str) -> PyResult<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|digest| digest.name() == name)
+            .ok_or_else(|| {
+                pyo3::exceptions::PyValueError::new_err(format!(
+                    "Unsupported time-stamp hash algorithm {:?} (expected sha256, sha384 or sha512)",
+                    name
+                ))
+            })
+    }
+
+    fn from_oid(oid: &ObjectIdentifier) -> Result<Self, String> {
+        Self::ALL
+            .into_iter()
+            .find(|digest| digest.oid() == *oid)
+            .ok_or_else(|| format!("Unsupported time-stamp digest algorithm {}", oid))
+    }
+
+    fn digest(self, data: &[u8]) -> Vec<u8> {
+        match self {
+            TsaDigest::Sha256 => Sha256::digest(data).to_vec(),
+            TsaDigest::Sha384 => Sha384::digest(data).to_vec(),
+            TsaDigest::Sha512 => Sha512::digest(data).to_vec(),
+        }
+    }
+
+    fn pkcs1v15(self) -> Pkcs1v15Sign {
+        match self {
+            TsaDigest::Sha256 => Pkcs1v15Sign::new::<Sha256>(),
+            TsaDigest::Sha384 => Pkcs1v15Sign::new::<Sha384>(),
+            TsaDigest::Sha512 => Pkcs1v15Sign::new::<Sha512>(),
+        }
+    }
+}
+
+fn der_error(what: &str) -> impl Fn(der::Error) -> String + '_ {
+    move |e| format!("Malformed {}: {}", what, e)
+}
+
+fn nonce_int(nonce: u64) -> Result<Int, String> {
+    Int::from_der(&nonce.to_der().map_err(der_error("nonce"))?).map_err(der_error("nonce"))
+}
+
+/// Verified contents of an RFC 3161 time-stamp token, from verify_timestamp_token
+#[pyclass(frozen, module = "reliquary_encryptor")]
+pub struct TimestampInfo {
+    /// Time the TSA attests, in milliseconds since the Unix epoch
+    #[pyo3(get)]
+    gen_time: u64,
+    /// TSA policy OID under which the token was issued, dotted
+    #[pyo3(get)]
+    policy: String,
+    /// Serial number (big-endian bytes), unique per token from this TSA
+    #[pyo3(get)]
+    serial_number: Vec<u8>,
+    /// Hash algorithm of the message imprint
+    #[pyo3(get)]
+    hash_algorithm: &'static str,
+    #[pyo3(get)]
+    nonce: Option<u64>,
+    /// Stated accuracy of gen_time in milliseconds, if the TSA gave one
+    #[pyo3(get)]
+    accuracy_ms: Option<u64>,
+}
+
+#[pymethods]
+impl TimestampInfo {
+    fn __repr__(&self) -> String {
+        format!(
+            "TimestampInfo(gen_time={}, policy={}, serial_number={})",
+            self.gen_time,
+            self.policy,
+            hex::encode(&self.serial_number)
+        )
+    }
+}
+
+/// The TSA certificate's subject key identifier, if it has one.
+fn subject_key_identifier(cert: &Certificate) -> Option<Vec<u8>> {
+    let ext = cert
+        .tbs_certificate
+        .extensions
+        .iter()
+        .flatten()
+        .find(|ext| ext.extn_id == ID_SUBJECT_KEY_IDENTIFIER)?;
+    let ski = SubjectKeyIdentifier::from_der(ext.extn_value.as_bytes()).ok()?;
+    Some(ski.0.as_bytes().to_vec())
+}
+
+fn check_certificate(cert: &Certificate, gen_time: u64) -> Result<(), String> {
+    let tbs = &cert.tbs_certificate;
+    let time_stamping = tbs.extensions.iter().flatten().any(|ext| {
+        ext.extn_id == ID_EXTENDED_KEY_USAGE
+            && ext.critical
+            && ExtendedKeyUsage::from_der(ext.extn_value.as_bytes())
+                .is_ok_and(|eku| eku.0.contains(&ID_KP_TIME_STAMPING))
+    });
+    if !time_stamping {
+        return Err(
+            "TSA certificate lacks the critical timeStamping extended key usage".to_string(),
+        );
+    }
+    let millis = |time: x509_cert::time::Time| time.to_unix_duration().as_millis() as u64;
+    if gen_time < millis(tbs.validity.not_before) || gen_time > millis(tbs.validity.not_after) {
+        return Err("Token time is outside the TSA certificate's validity".to_string());
+    }
+    Ok(())
+}
+
+/// Checks `signature` over `signed` with the certificate's key.
+fn check_signature(
+    cert: &Certificate,
+    digest: TsaDigest,
+    signed: &[u8],
+    signature: &[u8],
+) -> Result<(), String> {
+    let spki = &cert.tbs_certificate.subject_public_key_info;
+    let spki_der = spki.to_der().map_err(der_error("TSA public key"))?;
+    let valid = match spki.algorithm.oid {
+        RSA_ENCRYPTION => {
+            let key = RsaPublicKey::from_public_key_der(&spki_der)
+                .map_err(|e| format!("Invalid TSA RSA key: {}", e))?;
+            key.verify(digest.pkcs1v15(), &digest.digest(signed), signature)
+                .is_ok()
+        }
+        EC_PUBLIC_KEY => {
+            let key = P256VerifyingKey::from_public_key_der(&spki_der)
+                .map_err(|_| "TSA EC keys must be on P-256".to_string())?;
+            P256Signature::from_der(signature)
+                .is_ok_and(|sig| key.verify_prehash(&digest.digest(signed), &sig).is_ok())
+        }
+        ED25519 => {
+            let key = spki
+                .subject_public_key
+                .as_bytes()
+                .and_then(|raw| raw.try_into().ok())
+                .and_then(|raw| Ed25519VerifyingKey::from_bytes(raw).ok())
+                .ok_or("Invalid TSA Ed25519 key")?;
+            Ed25519Signature::from_slice(signature)
+                .is_ok_and(|sig| key.verify_strict(signed, &sig).is_ok())
+        }
+        oid => {
+            return Err(format!(
+                "Unsupported TSA key algorithm {} (expected RSA, P-256 or Ed25519)",
+                oid
+            ))
+        }
+    };
+    if valid {
+        Ok(())
+    } else {
+        Err("Time-stamp token signature is invalid".to_string())
+    }
+}
+
+/// Single value of signed attribute `oid`.
+fn signed_attribute(
+    attrs: &x509_cert::attr::Attributes,
+    oid: ObjectIdentifier,
+) -> Result<&Any, String> {
+    let attr = attrs
+        .iter()
+        .find(|attr| attr.oid == oid)
+        .ok_or_else(|| format!("Time-stamp token lacks signed attribute {}", oid))?;
+    match attr.values.as_slice() {
+        [value] => Ok(value),
+        _ => Err(format!("Signed attribute {} must have one value", oid)),
+    }
+}
+
+fn verify_token(
+    token: &[u8],
+    message: &[u8],
+    cert: &[u8],
+    nonce: Option<u64>,
+) -> Result<TimestampInfo, String> {
+    let cert = Certificate::from_der(&from_pem_or_der(cert, "CERTIFICATE")?)
+        .map_err(der_error("TSA certificate"))?;
+    let info = ContentInfo::from_der(token).map_err(der_error("time-stamp token"))?;
+    if info.content_type != ID_SIGNED_DATA {
+        return Err("Time-stamp token is not CMS SignedData".to_string());
+    }
+    let signed_data = info
+        .content
+        .decode_as::<SignedData>()
+        .map_err(der_error("time-stamp token"))?;
+    let encap = &signed_data.encap_content_info;
+    let econtent = match (&encap.econtent, encap.econtent_type) {
+        (Some(econtent), ID_CT_TST_INFO) => econtent
+            .decode_as::<OctetString>()
+            .map_err(der_error("TSTInfo"))?,
+        _ => return Err("Time-stamp token does not carry a TSTInfo".to_string()),
+    };
+    let tst = TstInfo::from_der(econtent.as_bytes()).map_err(der_error("TSTInfo"))?;
+
+    let imprint = TsaDigest::from_oid(&tst.message_imprint.hash_algorithm.oid)?;
+    if tst.message_imprint.hashed_message.as_bytes() != imprint.digest(message) {
+        return Err("Time-stamp token is for a different message".to_string());
+    }
+    if let Some(expected) = nonce {
+        if tst.nonce != Some(nonce_int(expected)?) {
+            return Err("Time-stamp token nonce does not match the request".to_string());
+        }
+    }
+    let gen_time = tst.gen_time.to_unix_duration().as_millis() as u64;
+    check_certificate(&cert, gen_time)?;
+
+    let [signer] = signed_data.signer_infos.0.as_slice() else {
+        return Err("Time-stamp token must have exactly one signer".to_string());
+    };
+    let is_tsa = match &signer.sid {
+        SignerIdentifier::IssuerAndSerialNumber(id) => {
+            id.issuer == cert.tbs_certificate.issuer
+                && id.serial_number == cert.tbs_certificate.serial_number
+        }
+        SignerIdentifier::SubjectKeyIdentifier(ski) => {
+            subject_key_identifier(&cert).is_some_and(|own| own == ski.0.as_bytes())
+        }
+    };
+    if !is_tsa {
+        return Err("Time-stamp token was not signed by this TSA certificate".to_string());
+    }
+    let attrs = signer
+        .signed_attrs
+        .as_ref()
+        .ok_or("Time-stamp token lacks signed attributes")?;
+    let digest = TsaDigest::from_oid(&signer.digest_alg.oid)?;
+    let content_type = signed_attribute(attrs, ID_CONTENT_TYPE)?
+        .decode_as::<ObjectIdentifier>()
+        .map_err(der_error("content-type attribute"))?;
+    let message_digest = signed_attribute(attrs, ID_MESSAGE_DIGEST)?
+        .decode_as::<OctetString>()
+        .map_err(der_error("message-digest attribute"))?;
+    if content_type != ID_CT_TST_INFO
+        || message_digest.as_bytes() != digest.digest(econtent.as_bytes())
+    {
+        return Err("Signed attributes do not match the TSTInfo".to_string());
+    }
+    let signed = attrs.to_der().map_err(der_error("signed attributes"))?;
+    check_signature(&cert, digest, &signed, signer.signature.as_bytes())?;
+
+    let accuracy_ms = tst.accuracy.map(|accuracy| {
+        accuracy.seconds.unwrap_or(0) * 1000 + accuracy.millis.unwrap_or(0).max(0) as u64
+    });
+    Ok(TimestampInfo {
+        gen_time,
+        policy: tst.policy.to_string(),
+        serial_number: tst.serial_number.as_bytes().to_vec(),
+        hash_algorithm: imprint.name(),
+        nonce: tst
+            .nonce
+            .and_then(|n| u64::from_der(&n.to_der().ok()?).ok()),
+        accuracy_ms,
+    })
+}
+
+/// DER TimeStampReq for `message`, to POST to a TSA as application/timestamp-query. A nonce
+/// (e.g. random.getrandbits(64)) ties the reply to this request; cert_req asks the TSA to
+/// include its certificate in the token
+#[pyfunction]
+#[pyo3(signature = (message, nonce = None, hash_algorithm = "sha256", cert_req = true))]
+pub fn create_timestamp_request(
+    message: Vec<u8>,
+    nonce: Option<u64>,
+    hash_algorithm: &str,
+    cert_req: bool,
+) -> PyResult<Vec<u8>> {
+    let digest = TsaDigest::from_name(hash_algorithm)?;
+    let request = || -> Result<Vec<u8>, String> {
+        TimeStampReq {
+            version: TspVersion::V1,
+            message_imprint: MessageImprint {
+                hash_algorithm: AlgorithmIdentifier {
+                    oid: digest.oid(),
+                    parameters: None,
+                },
+                hashed_message: OctetString::new(digest.digest(&message))
+                    .map_err(der_error("message imprint"))?,
+            },
+            req_policy: None,
+            nonce: nonce.map(nonce_int).transpose()?,
+            cert_req,
+            extensions: None,
+        }
+        .to_der()
+        .map_err(der_error("time-stamp request"))
+    };
+    request().map_err(pyo3::exceptions::PyValueError::new_err)
+}
+
+/// The time-stamp token in a TSA's DER TimeStampResp. Raises ValueError if the TSA refused
+/// the request or the reply is malformed
+#[pyfunction]
+pub fn timestamp_token_from_response(response: Vec<u8>) -> PyResult<Vec<u8>> {
+    let token = || -> Result<Vec<u8>, String> {
+        let response = TimeStampResp::from_der(&response).map_err(der_error("TSA response"))?;
+        // granted (0) or grantedWithMods (1)
+        let status = response.status.status as u8;
+        match response.time_stamp_token {
+            Some(token) if status <= 1 => token.to_der().map_err(der_error("time-stamp token")),
+            _ => Err(format!(
+                "TSA rejected the time-stamp request (PKI status {})",
+                status
+            )),
+        }
+    };
+    token().map_err(pyo3::exceptions::PyValueError::new_err)
+}
+
+/// Verifies an RFC 3161 time-stamp token over `message` against the pinned TSA certificate
+/// (DER or PEM), and `nonce` if the request carried one. Returns the verified TimestampInfo;
+/// raises ValueError saying what failed otherwise
+#[pyfunction]
+#[pyo3(signature = (token, message, tsa_certificate, nonce = None))]
+pub fn verify_timestamp_token(
+    py: Python<'_>,
+    token: Vec<u8>,
+    message: Vec<u8>,
+    tsa_certificate: Vec<u8>,
+    nonce: Option<u64>,
+) -> PyResult<TimestampInfo> {
+    py.allow_threads(|| verify_token(&token, &message, &tsa_certificate, nonce))
+        .map_err(pyo3::exceptions::PyValueError::new_err)
+}
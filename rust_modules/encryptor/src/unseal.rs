@@ -0,0 +1,201 @@
+use pyo3::prelude::*;
+use sha3::{Digest, Sha3_256};
+
+use crate::envelope;
+use crate::handles::{DecryptionKeyArg, InspectionKeyArg};
+use crate::quorum::{check_certificate, QuorumPolicy};
+use crate::recipients;
+use crate::wire::put_var16;
+
+// Policy-gated unseal: an envelope is only opened when a quorum certificate under the given
+// policy approves the request digest (which names the exact envelope and context) and enough
+// recipient keys are present. The dry run performs the same checks without decapsulating or
+// unwrapping anything, so the data key is never derived.
+const REQUEST_LABEL: &[u8] = b"reliquary/unseal-request/v1";
+
+/// One evaluated check: (name, passed, detail).
+pub(crate) type TraceEntry = (String, bool, String);
+
+pub(crate) fn request_digest(envelope: &[u8], context: &[u8]) -> Result<Vec<u8>, String> {
+    let mut msg = REQUEST_LABEL.to_vec();
+    msg.extend_from_slice(&Sha3_256::digest(envelope));
+    put_var16(&mut msg, context)?;
+    Ok(Sha3_256::digest(&msg).to_vec())
+}
+
+fn entry(name: &str, passed: bool, detail: String) -> TraceEntry {
+    (name.to_string(), passed, detail)
+}
+
+fn key_check(envelope: &[u8], secret_keys: &[&[u8]]) -> (TraceEntry, TraceEntry) {
+    if envelope::is_multi_recipient(envelope) {
+        return match recipients::key_availability(envelope, secret_keys) {
+            Ok((threshold, matched, slots)) => (
+                entry(
+                    "envelope.format",
+                    true,
+                    format!("multi-recipient, {}-of-{}", threshold, slots),
+                ),
+                entry(
+                    "keys.available",
+                    matched >= threshold as usize,
+                    format!(
+                        "{} of {} required recipient keys present",
+                        matched, threshold
+                    ),
+                ),
+            ),
+            Err(e) => (
+                entry("envelope.format", false, e),
+                entry("keys.available", false, "envelope unreadable".to_string()),
+            ),
+        };
+    }
+    match envelope::parse(envelope) {
+        // Single-recipient envelopes carry no recipient fingerprint, so a key can only be
+        // matched by parameter set without decapsulating.
+        Ok(parsed) => {
+            let present = secret_keys
+                .iter()
+                .any(|sk| sk.len() == parsed.params.secret_key_bytes());
+            (
+                entry(
+                    "envelope.format",
+                    true,
+                    format!("single-recipient, {}", parsed.params.name()),
+                ),
+                entry(
+                    "keys.available",
+                    present,
+                    format!(
+                        "{} secret key {} (matched by parameter set only)",
+                        parsed.params.name(),
+                        if present { "present" } else { "missing" }
+                    ),
+                ),
+            )
+        }
+        Err(e) => (
+            entry("envelope.format", false, e),
+            entry("keys.available", false, "envelope unreadable".to_string()),
+        ),
+    }
+}
+
+fn evaluate(
+    envelope: &[u8],
+    secret_keys: &[&[u8]],
+    policy: &QuorumPolicy,
+    certificate: &[u8],
+    context: &[u8],
+) -> PyResult<Vec<TraceEntry>> {
+    let digest =
+        request_digest(envelope, context).map_err(pyo3::exceptions::PyValueError::new_err)?;
+    let quorum = match check_certificate(policy, certificate, &digest) {
+        Ok((true, signers)) => entry(
+            "quorum.certificate",
+            true,
+            format!(
+                "approved by {} of {} required trustees: {}",
+                signers.len(),
+                policy.threshold(),
+                signers.join(", ")
+            ),
+        ),
+        Ok((false, _)) => entry(
+            "quorum.certificate",
+            false,
+            format!(
+                "certificate does not carry {} valid trustee approvals for this request",
+                policy.threshold()
+            ),
+        ),
+        Err(_) => entry(
+            "quorum.certificate",
+            false,
+            "malformed quorum certificate".to_string(),
+        ),
+    };
+    let (format, keys) = key_check(envelope, secret_keys);
+    Ok(vec![format, quorum, keys])
+}
+
+fn allowed(trace: &[TraceEntry]) -> bool {
+    trace.iter().all(|(_, passed, _)| *passed)
+}
+
+/// Digest trustees vote on (with `sign_quorum_vote`) to approve unsealing this exact envelope
+/// for `context`
+#[pyfunction]
+#[pyo3(signature = (envelope, context = Vec::new()))]
+pub fn unseal_request_digest(envelope: Vec<u8>, context: Vec<u8>) -> PyResult<Vec<u8>> {
+    request_digest(&envelope, &context).map_err(pyo3::exceptions::PyValueError::new_err)
+}
+
+/// Would `unseal` succeed? Runs the envelope format, quorum certificate and key availability
+/// checks without decapsulating or deriving any key material. Returns (allowed, trace) where
+/// trace is a list of (check, passed, detail). Keys are matched by fingerprint, so a
+/// tampered slot still only shows up on the real unseal
+#[pyfunction]
+#[pyo3(signature = (envelope, kyber_sks, policy, certificate, context = Vec::new()))]
+pub fn unseal_dry_run(
+    py: Python<'_>,
+    envelope: Vec<u8>,
+    kyber_sks: Vec<InspectionKeyArg>,
+    policy: &QuorumPolicy,
+    certificate: Vec<u8>,
+    context: Vec<u8>,
+) -> PyResult<(bool, Vec<TraceEntry>)> {
+    py.allow_threads(|| {
+        let trace = evaluate(
+            &envelope,
+            &recipients::as_slices(&kyber_sks),
+            policy,
+            &certificate,
+            &context,
+        )?;
+        Ok((allowed(&trace), trace))
+    })
+}
+
+/// Opens an envelope only if a quorum certificate under `policy` approves
+/// `unseal_request_digest(envelope, context)` and enough recipient keys are given. Raises
+/// ValueError naming the failed checks otherwise
+#[pyfunction]
+#[pyo3(signature = (envelope, kyber_sks, policy, certificate, context = Vec::new()))]
+pub fn unseal(
+    py: Python<'_>,
+    envelope: Vec<u8>,
+    kyber_sks: Vec<DecryptionKeyArg>,
+    policy: &QuorumPolicy,
+    certificate: Vec<u8>,
+    context: Vec<u8>,
+) -> PyResult<Vec<u8>> {
+    py.allow_threads(|| {
+        let secret_keys = recipients::as_slices(&kyber_sks);
+        let trace = evaluate(&envelope, &secret_keys, policy, &certificate, &context)?;
+        if !allowed(&trace) {
+            let failed: Vec<String> = trace
+                .iter()
+                .filter(|(_, passed, _)| !passed)
+                .map(|(name, _, detail)| format!("{} ({})", name, detail))
+                .collect();
+            return Err(pyo3::exceptions::PyValueError::new_err(format!(
+                "Unseal denied: {}",
+                failed.join("; ")
+            )));
+        }
+        if envelope::is_multi_recipient(&envelope) {
+            recipients::open_with(&envelope, &secret_keys)
+                .map_err(pyo3::exceptions::PyValueError::new_err)
+        } else {
+            let parsed =
+                envelope::parse(&envelope).map_err(pyo3::exceptions::PyValueError::new_err)?;
+            let sk = secret_keys
+                .iter()
+                .find(|sk| sk.len() == parsed.params.secret_key_bytes())
+                .expect("key availability checked above");
+            envelope::open(&envelope, sk)
+        }
+    })
+}
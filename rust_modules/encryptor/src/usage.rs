@@ -0,0 +1,209 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use pyo3::prelude::*;
+
+use crate::handles::KeyAlgorithm;
+
+// Usage policies attached to key handles when they are created. The policy is immutable
+// afterwards and checked in Rust on every use of the handle, so a decryption-only key can't
+// sign and an expired or used-up key fails closed whatever the Python layer does.
+
+/// Something a key handle can be used for.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum KeyOperation {
+    Encapsulate,
+    Decapsulate,
+    Sign,
+    Verify,
+    Export,
+}
+
+impl KeyOperation {
+    fn parse(name: &str) -> PyResult<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "encapsulate" | "encrypt" => Ok(KeyOperation::Encapsulate),
+            "decapsulate" | "decrypt" => Ok(KeyOperation::Decapsulate),
+            "sign" => Ok(KeyOperation::Sign),
+            "verify" => Ok(KeyOperation::Verify),
+            "export" => Ok(KeyOperation::Export),
+            _ => Err(pyo3::exceptions::PyValueError::new_err(format!(
+                "Unknown key operation: {}",
+                name
+            ))),
+        }
+    }
+
+    pub(crate) fn name(self) -> &'static str {
+        match self {
+            KeyOperation::Encapsulate => "encapsulate",
+            KeyOperation::Decapsulate => "decapsulate",
+            KeyOperation::Sign => "sign",
+            KeyOperation::Verify => "verify",
+            KeyOperation::Export => "export",
+        }
+    }
+
+    fn applies_to(self, algorithm: KeyAlgorithm) -> bool {
+        matches!(
+            (self, algorithm),
+            (KeyOperation::Export, _)
+                | (
+                    KeyOperation::Encapsulate | KeyOperation::Decapsulate,
+                    KeyAlgorithm::Kyber(_)
+                )
+                | (
+                    KeyOperation::Sign | KeyOperation::Verify,
+                    KeyAlgorithm::Falcon(_)
+                )
+        )
+    }
+}
+
+fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(u64::MAX)
+}
+
+/// Restrictions on a key handle: which operations it may perform, until when (Unix seconds,
+/// exclusive) and how many times in total. Only decapsulations, signatures and exports count
+/// as uses; encapsulation and verification don't. None means unrestricted, except that
+/// "export" is only allowed when `operations` lists it
+#[pyclass(module = "reliquary_encryptor", frozen)]
+#[derive(Clone, Debug, Default)]
+pub struct KeyUsagePolicy {
+    operations: Option<Vec<KeyOperation>>,
+    not_after: Option<u64>,
+    max_uses: Option<u64>,
+}
+
+impl KeyUsagePolicy {
+    /// Rejects policies that name operations the key's algorithm can't perform.
+    pub(crate) fn check_algorithm(&self, algorithm: KeyAlgorithm) -> PyResult<()> {
+        for op in self.operations.iter().flatten() {
+            if !op.applies_to(algorithm) {
+                return Err(pyo3::exceptions::PyValueError::new_err(format!(
+                    "A {} key can't {}",
+                    algorithm.name(),
+                    op.name()
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    /// Checks `op` against the policy and, if `consume`, counts it against `max_uses`.
+    pub(crate) fn authorize(
+        &self,
+        op: KeyOperation,
+        uses: &AtomicU64,
+        consume: bool,
+    ) -> PyResult<()> {
+        self.authorize_uses(op, uses, consume as u64)
+    }
+
+    /// Like `authorize`, but counts `count` uses at once; either all are available or none
+    /// are taken.
+    pub(crate) fn authorize_uses(
+        &self,
+        op: KeyOperation,
+        uses: &AtomicU64,
+        count: u64,
+    ) -> PyResult<()> {
+        let allowed = match &self.operations {
+            Some(operations) => operations.contains(&op),
+            // Export is never implied; a policy has to grant it by name
+            None => op != KeyOperation::Export,
+        };
+        if !allowed {
+            return Err(pyo3::exceptions::PyPermissionError::new_err(format!(
+                "Key usage policy does not allow {}",
+                op.name()
+            )));
+        }
+        if let Some(not_after) = self.not_after {
+            if unix_now() >= not_after {
+                return Err(pyo3::exceptions::PyPermissionError::new_err(
+                    "Key has expired",
+                ));
+            }
+        }
+        if count == 0 {
+            return Ok(());
+        }
+        let limit = self.max_uses.unwrap_or(u64::MAX);
+        uses.fetch_update(Ordering::SeqCst, Ordering::SeqCst, |n| {
+            n.checked_add(count).filter(|&total| total <= limit)
+        })
+        .map(|_| ())
+        .map_err(|_| {
+            pyo3::exceptions::PyPermissionError::new_err(if count == 1 {
+                format!("Key has reached its limit of {} uses", limit)
+            } else {
+                format!("Key has fewer than {} of its {} uses left", count, limit)
+            })
+        })
+    }
+}
+
+#[pymethods]
+impl KeyUsagePolicy {
+    /// `operations` is a subset of "encapsulate", "decapsulate", "sign", "verify" and "export"
+    /// ("encrypt" / "decrypt" are accepted as aliases)
+    #[new]
+    #[pyo3(signature = (operations = None, not_after = None, max_uses = None))]
+    fn new(
+        operations: Option<Vec<String>>,
+        not_after: Option<u64>,
+        max_uses: Option<u64>,
+    ) -> PyResult<Self> {
+        let operations = operations
+            .map(|names| {
+                names
+                    .iter()
+                    .map(|name| KeyOperation::parse(name))
+                    .collect::<PyResult<Vec<_>>>()
+            })
+            .transpose()?;
+        Ok(KeyUsagePolicy {
+            operations,
+            not_after,
+            max_uses,
+        })
+    }
+
+    #[getter]
+    fn operations(&self) -> Option<Vec<&'static str>> {
+        self.operations
+            .as_ref()
+            .map(|ops| ops.iter().map(|op| op.name()).collect())
+    }
+
+    #[getter]
+    fn not_after(&self) -> Option<u64> {
+        self.not_after
+    }
+
+    #[getter]
+    fn max_uses(&self) -> Option<u64> {
+        self.max_uses
+    }
+
+    fn __repr__(&self) -> String {
+        fn py_opt<T: std::fmt::Display>(value: Option<T>) -> String {
+            value.map_or_else(|| "None".to_string(), |v| v.to_string())
+        }
+        let operations = self.operations().map(|ops| {
+            let quoted: Vec<String> = ops.iter().map(|op| format!("'{}'", op)).collect();
+            format!("[{}]", quoted.join(", "))
+        });
+        format!(
+            "KeyUsagePolicy(operations={}, not_after={}, max_uses={})",
+            py_opt(operations),
+            py_opt(self.not_after),
+            py_opt(self.max_uses)
+        )
+    }
+}
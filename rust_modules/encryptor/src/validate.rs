@@ -0,0 +1,444 @@
+use std::collections::HashSet;
+
+use curve25519_dalek::scalar::Scalar;
+use pyo3::prelude::*;
+
+use crate::params::{FalconParams, KyberParams};
+use crate::wire::Reader;
+use crate::{envelope, kms, limits, quorum, recipients, vrf};
+
+// Kyber modulus; every 12-bit packed public key coefficient must be reduced below it.
+const KYBER_Q: u16 = 3329;
+// Size of the matrix seed rho that trails the packed polynomial vector.
+const KYBER_SEED_BYTES: usize = 32;
+
+// Falcon modulus; the public key header byte is logn of the parameter set.
+const FALCON_Q: u16 = 12289;
+
+/// One structured finding: (severity, code, message). Severity is "error" for defects that make
+/// the input unusable and "warning" for legal but suspicious structure; codes are stable
+/// identifiers callers can match on.
+pub(crate) type Finding = (String, String, String);
+
+#[derive(Default)]
+struct Findings(Vec<Finding>);
+
+impl Findings {
+    fn error(&mut self, code: &str, message: impl Into<String>) {
+        self.0
+            .push(("error".to_string(), code.to_string(), message.into()));
+    }
+
+    fn warning(&mut self, code: &str, message: impl Into<String>) {
+        self.0
+            .push(("warning".to_string(), code.to_string(), message.into()));
+    }
+
+    /// The first error as a message about `what`, for callers that reject the input outright
+    /// instead of reporting on it. Warnings pass.
+    fn first_error(self, what: &str) -> Result<(), String> {
+        match self
+            .0
+            .into_iter()
+            .find(|(severity, _, _)| severity == "error")
+        {
+            Some((_, _, message)) => Err(format!("Invalid {}: {}", what, message)),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Checks that a Kyber public key is well formed: correct length, every packed
+/// coefficient reduced mod q, a nonzero vector t and a non-degenerate seed.
+pub(crate) fn check_kyber_public_key(pk_bytes: &[u8], params: KyberParams) -> Result<(), String> {
+    let mut findings = Findings::default();
+    kyber_public_key_findings(pk_bytes, params, &mut findings);
+    findings.first_error("public key")
+}
+
+/// Checks that a Kyber ciphertext has the expected size and is not a constant
+/// byte string. Every compressed coefficient value is valid, so no range check applies.
+pub(crate) fn check_kyber_ciphertext(ct_bytes: &[u8], params: KyberParams) -> Result<(), String> {
+    if ct_bytes.len() != params.ciphertext_bytes() {
+        return Err(format!(
+            "Invalid ciphertext length. Expected {}, got {}",
+            params.ciphertext_bytes(),
+            ct_bytes.len()
+        ));
+    }
+    if is_constant(ct_bytes) {
+        return Err("Invalid ciphertext: degenerate constant encoding".to_string());
+    }
+    Ok(())
+}
+
+/// Checks that a Falcon public key has the right header byte, that every 14-bit packed
+/// coefficient of h is reduced mod q and that h is nonzero.
+pub(crate) fn check_falcon_public_key(pk_bytes: &[u8], params: FalconParams) -> Result<(), String> {
+    let mut findings = Findings::default();
+    falcon_public_key_findings(pk_bytes, params, &mut findings);
+    findings.first_error("public key")
+}
+
+/// Unpacks 12-bit Kyber coefficients, two per three bytes.
+fn kyber_coefficients(polyvec: &[u8]) -> impl Iterator<Item = u16> + '_ {
+    polyvec.chunks_exact(3).flat_map(|chunk| {
+        [
+            u16::from(chunk[0]) | (u16::from(chunk[1] & 0x0f) << 8),
+            u16::from(chunk[1] >> 4) | (u16::from(chunk[2]) << 4),
+        ]
+    })
+}
+
+/// Unpacks 14-bit Falcon coefficients. 14n bits is a whole number of bytes for both parameter
+/// sets, so a key of the right length has no padding bits.
+fn falcon_coefficients(packed: &[u8]) -> Vec<u16> {
+    let mut coefficients = Vec::with_capacity(packed.len() * 8 / 14);
+    let mut acc: u32 = 0;
+    let mut acc_len = 0;
+    for &byte in packed {
+        acc = (acc << 8) | u32::from(byte);
+        acc_len += 8;
+        if acc_len >= 14 {
+            acc_len -= 14;
+            coefficients.push(((acc >> acc_len) & 0x3fff) as u16);
+        }
+    }
+    coefficients
+}
+
+fn is_constant(bytes: &[u8]) -> bool {
+    bytes.windows(2).all(|w| w[0] == w[1])
+}
+
+/// Validates a Kyber public key. Raises ValueError with the first error `validate_kyber_pk`
+/// reports
+#[pyfunction]
+#[pyo3(signature = (pk_bytes, params = KyberParams::Kyber1024))]
+pub fn validate_kyber_public_key(pk_bytes: Vec<u8>, params: KyberParams) -> PyResult<()> {
+    check_kyber_public_key(&pk_bytes, params).map_err(pyo3::exceptions::PyValueError::new_err)
+}
+
+/// Validates a Kyber ciphertext. Raises ValueError describing the defect
+#[pyfunction]
+#[pyo3(signature = (ct_bytes, params = KyberParams::Kyber1024))]
+pub fn validate_kyber_ciphertext(ct_bytes: Vec<u8>, params: KyberParams) -> PyResult<()> {
+    check_kyber_ciphertext(&ct_bytes, params).map_err(pyo3::exceptions::PyValueError::new_err)
+}
+
+/// Validates a Falcon public key. Raises ValueError with the first error `validate_falcon_pk`
+/// reports
+#[pyfunction]
+#[pyo3(signature = (pk_bytes, params = FalconParams::Falcon1024))]
+pub fn validate_falcon_public_key(pk_bytes: Vec<u8>, params: FalconParams) -> PyResult<()> {
+    check_falcon_public_key(&pk_bytes, params).map_err(pyo3::exceptions::PyValueError::new_err)
+}
+
+fn count_unreduced(coefficients: impl Iterator<Item = u16>, q: u16) -> Option<(usize, usize)> {
+    let mut first = None;
+    let mut count = 0;
+    for (i, c) in coefficients.enumerate() {
+        if c >= q {
+            first.get_or_insert(i);
+            count += 1;
+        }
+    }
+    first.map(|first| (count, first))
+}
+
+fn kyber_public_key_findings(pk_bytes: &[u8], params: KyberParams, findings: &mut Findings) {
+    if pk_bytes.len() != params.public_key_bytes() {
+        findings.error(
+            "length",
+            format!(
+                "Public key is {} bytes, {} expects {}",
+                pk_bytes.len(),
+                params.name(),
+                params.public_key_bytes()
+            ),
+        );
+        return;
+    }
+    let (polyvec, seed) = pk_bytes.split_at(pk_bytes.len() - KYBER_SEED_BYTES);
+    if let Some((count, first)) = count_unreduced(kyber_coefficients(polyvec), KYBER_Q) {
+        findings.error(
+            "coefficient_range",
+            format!(
+                "{} coefficients are not reduced mod {} (first at index {})",
+                count, KYBER_Q, first
+            ),
+        );
+    }
+    if polyvec.iter().all(|&b| b == 0) {
+        findings.error(
+            "zero_polynomial",
+            "Public key vector t is zero; ciphertexts would expose the shared secret",
+        );
+    }
+    if is_constant(seed) {
+        findings.error(
+            "degenerate_seed",
+            "Matrix seed rho is a constant byte string",
+        );
+    }
+}
+
+fn falcon_public_key_findings(pk_bytes: &[u8], params: FalconParams, findings: &mut Findings) {
+    if pk_bytes.len() != params.public_key_bytes() {
+        findings.error(
+            "length",
+            format!(
+                "Public key is {} bytes, {} expects {}",
+                pk_bytes.len(),
+                params.name(),
+                params.public_key_bytes()
+            ),
+        );
+        return;
+    }
+    if pk_bytes[0] != params.logn() {
+        findings.error(
+            "header",
+            format!(
+                "Header byte is 0x{:02x}, {} expects 0x{:02x}",
+                pk_bytes[0],
+                params.name(),
+                params.logn()
+            ),
+        );
+    }
+    let coefficients = falcon_coefficients(&pk_bytes[1..]);
+    if let Some((count, first)) = count_unreduced(coefficients.iter().copied(), FALCON_Q) {
+        findings.error(
+            "coefficient_range",
+            format!(
+                "{} coefficients are not reduced mod {} (first at index {})",
+                count, FALCON_Q, first
+            ),
+        );
+    }
+    if coefficients.iter().all(|&c| c == 0) {
+        findings.error("zero_polynomial", "Public key polynomial h is zero");
+    }
+}
+
+fn kem_ciphertext_findings(
+    what: &str,
+    ct_bytes: &[u8],
+    params: KyberParams,
+    findings: &mut Findings,
+) {
+    if let Err(e) = check_kyber_ciphertext(ct_bytes, params) {
+        findings.error("kem_ciphertext", format!("{}: {}", what, e));
+    }
+}
+
+fn envelope_findings(bytes: &[u8], findings: &mut Findings) {
+    if let Err(e) = limits::check_input("Envelope", bytes.len()) {
+        findings.error("input_limit", e);
+        return;
+    }
+    if bytes.len() <= envelope::MAGIC.len() || &bytes[..envelope::MAGIC.len()] != envelope::MAGIC {
+        findings.error("not_envelope", "Missing the RQEV envelope magic");
+        return;
+    }
+    match bytes[envelope::MAGIC.len()] {
+        envelope::VERSION => match envelope::parse(bytes) {
+            Ok(parsed) => kem_ciphertext_findings(
+                "KEM ciphertext",
+                parsed.kem_ciphertext,
+                parsed.params,
+                findings,
+            ),
+            Err(e) => findings.error("malformed", e),
+        },
+        recipients::VERSION => match recipients::layout(bytes) {
+            Ok((threshold, slots)) => {
+                let mut fingerprints = HashSet::new();
+                let mut indices = HashSet::new();
+                for (i, (fingerprint, params, kem_ciphertext, index)) in slots.iter().enumerate() {
+                    kem_ciphertext_findings(
+                        &format!("Slot {}", i),
+                        kem_ciphertext,
+                        *params,
+                        findings,
+                    );
+                    if *index == 0 {
+                        findings.error(
+                            "share_index",
+                            format!("Slot {} holds the share at x = 0, which is the key", i),
+                        );
+                    } else if !indices.insert(*index) {
+                        findings.error(
+                            "duplicate_share_index",
+                            format!("Slot {} repeats share index {}", i, index),
+                        );
+                    }
+                    if !fingerprints.insert(*fingerprint) {
+                        findings.warning(
+                            "duplicate_recipient",
+                            format!("Slot {} is addressed to a recipient listed earlier", i),
+                        );
+                    }
+                }
+                if indices.len() < threshold as usize {
+                    findings.error(
+                        "unrecoverable",
+                        format!(
+                            "Threshold is {} but only {} distinct shares are present",
+                            threshold,
+                            indices.len()
+                        ),
+                    );
+                }
+            }
+            Err(e) => findings.error("malformed", e),
+        },
+        kms::VERSION => {
+            if let Err(e) = kms::parse(bytes) {
+                findings.error("malformed", e);
+            }
+        }
+        version => findings.error(
+            "version",
+            format!("Unsupported envelope version {}", version),
+        ),
+    }
+}
+
+fn vrf_proof_findings(proof: &[u8], findings: &mut Findings) {
+    if proof.len() != vrf::PROOF_BYTES {
+        findings.error(
+            "length",
+            format!(
+                "Proof is {} bytes, expected {}",
+                proof.len(),
+                vrf::PROOF_BYTES
+            ),
+        );
+        return;
+    }
+    match vrf::decode_point(&proof[..32]) {
+        None => findings.error("gamma_encoding", "Gamma is not a valid curve point"),
+        Some(gamma) if gamma.is_small_order() => {
+            findings.error("gamma_small_order", "Gamma is a small-order point")
+        }
+        Some(_) => {}
+    }
+    let s: [u8; 32] = proof[48..].try_into().expect("32-byte slice");
+    if Option::<Scalar>::from(Scalar::from_canonical_bytes(s)).is_none() {
+        findings.error("scalar_range", "s is not reduced mod the group order");
+    }
+}
+
+fn quorum_certificate_findings(certificate: &[u8], findings: &mut Findings) {
+    if let Err(e) = limits::check_input("Quorum certificate", certificate.len()) {
+        findings.error("input_limit", e);
+        return;
+    }
+    let mut r = Reader::new(certificate);
+    if r.bytes(quorum::MAGIC.len()).ok() != Some(quorum::MAGIC.as_slice()) {
+        findings.error("not_certificate", "Missing the RQQC certificate magic");
+        return;
+    }
+    let mut read = || -> Result<(), String> {
+        let version = r.u8()?;
+        if version != quorum::VERSION {
+            findings.error(
+                "version",
+                format!("Unsupported quorum certificate version {}", version),
+            );
+            return Ok(());
+        }
+        r.bytes(32)?;
+        let digest = r.var8()?;
+        if digest.is_empty() || digest.len() > 64 {
+            findings.error(
+                "digest_length",
+                format!(
+                    "Decision digest is {} bytes, expected 1 to 64",
+                    digest.len()
+                ),
+            );
+        }
+        let bitmap = r.var8()?;
+        let signers: u32 = bitmap.iter().map(|b| b.count_ones()).sum();
+        if signers == 0 {
+            findings.error("no_signers", "Signer bitmap is empty");
+        }
+        limits::check_items("Quorum certificate", signers as usize)?;
+        for i in 0..signers {
+            if r.var16()?.is_empty() {
+                findings.error("empty_signature", format!("Signature {} is empty", i));
+            }
+        }
+        if !r.is_empty() {
+            findings.error(
+                "trailing_data",
+                "Data follows the last signature (bitmap and signatures disagree)",
+            );
+        }
+        Ok(())
+    };
+    if let Err(e) = read() {
+        findings.error("malformed", e);
+    }
+}
+
+/// Deep well-formedness check of a Kyber public key received from another party. Returns every
+/// finding as (severity, code, message); an empty list means the key is well formed
+#[pyfunction]
+#[pyo3(signature = (pk_bytes, params = KyberParams::Kyber1024))]
+pub fn validate_kyber_pk(pk_bytes: Vec<u8>, params: KyberParams) -> Vec<Finding> {
+    let mut findings = Findings::default();
+    kyber_public_key_findings(&pk_bytes, params, &mut findings);
+    findings.0
+}
+
+/// Deep well-formedness check of a Falcon public key. Returns every finding as
+/// (severity, code, message); an empty list means the key is well formed
+#[pyfunction]
+#[pyo3(signature = (pk_bytes, params = FalconParams::Falcon1024))]
+pub fn validate_falcon_pk(pk_bytes: Vec<u8>, params: FalconParams) -> Vec<Finding> {
+    let mut findings = Findings::default();
+    falcon_public_key_findings(&pk_bytes, params, &mut findings);
+    findings.0
+}
+
+/// Structural check of a single- or multi-recipient envelope: header, KEM ciphertexts, share
+/// indices and whether the threshold is reachable. Nothing is decrypted, so a clean result does
+/// not mean the envelope authenticates
+#[pyfunction]
+pub fn validate_envelope(envelope: Vec<u8>) -> Vec<Finding> {
+    let mut findings = Findings::default();
+    envelope_findings(&envelope, &mut findings);
+    findings.0
+}
+
+/// Structural check of a VRF proof (point encoding, small-order Gamma, canonical s), plus the
+/// prover's public key when given. Does not verify the proof
+#[pyfunction]
+#[pyo3(signature = (proof, pk = None))]
+pub fn validate_vrf_proof(proof: Vec<u8>, pk: Option<Vec<u8>>) -> Vec<Finding> {
+    let mut findings = Findings::default();
+    vrf_proof_findings(&proof, &mut findings);
+    if let Some(pk) = pk {
+        match vrf::decode_point(&pk) {
+            None => findings.error("public_key", "Public key is not a valid curve point"),
+            Some(point) if point.is_small_order() => {
+                findings.error("public_key", "Public key is a small-order point")
+            }
+            Some(_) => {}
+        }
+    }
+    findings.0
+}
+
+/// Structural check of a quorum certificate: header, digest, signer bitmap and one non-empty
+/// signature per signer. Signatures are checked against a policy by verify_quorum_certificate
+#[pyfunction]
+pub fn validate_quorum_certificate(certificate: Vec<u8>) -> Vec<Finding> {
+    let mut findings = Findings::default();
+    quorum_certificate_findings(&certificate, &mut findings);
+    findings.0
+}
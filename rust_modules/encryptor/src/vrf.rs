@@ -0,0 +1,147 @@
+use curve25519_dalek::constants::ED25519_BASEPOINT_POINT;
+use curve25519_dalek::edwards::{CompressedEdwardsY, EdwardsPoint};
+use curve25519_dalek::scalar::{clamp_integer, Scalar};
+use pyo3::prelude::*;
+use sha2::{Digest, Sha512};
+use zeroize::Zeroizing;
+
+use crate::classical::fixed;
+use crate::handles::SigningKeyArg;
+
+// ECVRF-EDWARDS25519-SHA512-TAI (RFC 9381, suite 0x03). Keys are ordinary Ed25519 keys.
+// Proof layout: Gamma (32) | c (16, little-endian) | s (32, little-endian).
+const SUITE: u8 = 0x03;
+const KEY_BYTES: usize = 32;
+const CHALLENGE_BYTES: usize = 16;
+pub(crate) const PROOF_BYTES: usize = 32 + CHALLENGE_BYTES + 32;
+
+/// ECVRF_encode_to_curve_try_and_increment.
+fn encode_to_curve(pk: &[u8], alpha: &[u8]) -> EdwardsPoint {
+    for ctr in 0..=u8::MAX {
+        let hash = Sha512::new()
+            .chain_update([SUITE, 0x01])
+            .chain_update(pk)
+            .chain_update(alpha)
+            .chain_update([ctr, 0x00])
+            .finalize();
+        let candidate = CompressedEdwardsY(hash[..32].try_into().expect("32-byte slice"));
+        if let Some(point) = candidate.decompress() {
+            return point.mul_by_cofactor();
+        }
+    }
+    // Each attempt succeeds with probability ~1/2; 256 consecutive failures don't happen.
+    unreachable!("encode_to_curve exhausted its counter")
+}
+
+fn challenge(points: [&EdwardsPoint; 5]) -> [u8; CHALLENGE_BYTES] {
+    let mut hasher = Sha512::new().chain_update([SUITE, 0x02]);
+    for point in points {
+        hasher.update(point.compress().as_bytes());
+    }
+    let hash = hasher.chain_update([0x00]).finalize();
+    hash[..CHALLENGE_BYTES].try_into().expect("16-byte slice")
+}
+
+fn challenge_scalar(c: &[u8; CHALLENGE_BYTES]) -> Scalar {
+    let mut wide = [0u8; 32];
+    wide[..CHALLENGE_BYTES].copy_from_slice(c);
+    Scalar::from_bytes_mod_order(wide)
+}
+
+fn output(gamma: &EdwardsPoint) -> Vec<u8> {
+    Sha512::new()
+        .chain_update([SUITE, 0x03])
+        .chain_update(gamma.mul_by_cofactor().compress().as_bytes())
+        .chain_update([0x00])
+        .finalize()
+        .to_vec()
+}
+
+pub(crate) fn decode_point(bytes: &[u8]) -> Option<EdwardsPoint> {
+    CompressedEdwardsY(bytes.try_into().ok()?).decompress()
+}
+
+pub(crate) fn prove(sk: &[u8; KEY_BYTES], alpha: &[u8]) -> (Vec<u8>, Vec<u8>) {
+    let expanded = Zeroizing::new(Sha512::digest(sk));
+    let x = Scalar::from_bytes_mod_order(clamp_integer(
+        expanded[..32].try_into().expect("32-byte slice"),
+    ));
+    let pk = (ED25519_BASEPOINT_POINT * x).compress();
+    let h = encode_to_curve(pk.as_bytes(), alpha);
+    let gamma = h * x;
+    let k = Scalar::from_bytes_mod_order_wide(
+        &Sha512::new()
+            .chain_update(&expanded[32..])
+            .chain_update(h.compress().as_bytes())
+            .finalize()
+            .into(),
+    );
+    let y = ED25519_BASEPOINT_POINT * x;
+    let c = challenge([&y, &h, &gamma, &(ED25519_BASEPOINT_POINT * k), &(h * k)]);
+    let s = k + challenge_scalar(&c) * x;
+
+    let mut proof = Vec::with_capacity(PROOF_BYTES);
+    proof.extend_from_slice(gamma.compress().as_bytes());
+    proof.extend_from_slice(&c);
+    proof.extend_from_slice(s.as_bytes());
+    (proof, output(&gamma))
+}
+
+/// Returns the VRF output if `proof` is valid for `alpha` under `pk`.
+pub(crate) fn verify(pk: &[u8], alpha: &[u8], proof: &[u8]) -> Option<Vec<u8>> {
+    if proof.len() != PROOF_BYTES {
+        return None;
+    }
+    let y = decode_point(pk)?;
+    if y.is_small_order() {
+        return None;
+    }
+    let gamma = decode_point(&proof[..32])?;
+    let c: [u8; CHALLENGE_BYTES] = proof[32..48].try_into().ok()?;
+    let s = Option::<Scalar>::from(Scalar::from_canonical_bytes(proof[48..].try_into().ok()?))?;
+    let h = encode_to_curve(pk, alpha);
+    let c_scalar = challenge_scalar(&c);
+    let u = ED25519_BASEPOINT_POINT * s - y * c_scalar;
+    let v = h * s - gamma * c_scalar;
+    (challenge([&y, &h, &gamma, &u, &v]) == c).then(|| output(&gamma))
+}
+
+/// Computes an ECVRF-EDWARDS25519-SHA512-TAI proof for `alpha` with an Ed25519 secret key.
+/// Returns (proof, output): the 80-byte proof and the 64-byte pseudorandom output, which is
+/// unique per key and input, so it can't be ground for a favourable leader election
+#[pyfunction]
+pub fn vrf_prove(
+    py: Python<'_>,
+    sk_bytes: SigningKeyArg,
+    alpha: Vec<u8>,
+) -> PyResult<(Vec<u8>, Vec<u8>)> {
+    let sk = Zeroizing::new(fixed::<KEY_BYTES>(&sk_bytes, "Ed25519 secret key")?);
+    Ok(py.allow_threads(|| prove(&sk, &alpha)))
+}
+
+/// Verifies a VRF proof against the prover's Ed25519 public key. Returns the 64-byte output
+/// if the proof is valid and None otherwise
+#[pyfunction]
+pub fn vrf_verify(
+    py: Python<'_>,
+    pk_bytes: Vec<u8>,
+    alpha: Vec<u8>,
+    proof: Vec<u8>,
+) -> Option<Vec<u8>> {
+    py.allow_threads(|| verify(&pk_bytes, &alpha, &proof))
+}
+
+/// Extracts the VRF output from a proof without verifying it
+#[pyfunction]
+pub fn vrf_proof_to_hash(proof: Vec<u8>) -> PyResult<Vec<u8>> {
+    if proof.len() != PROOF_BYTES {
+        return Err(pyo3::exceptions::PyValueError::new_err(format!(
+            "Invalid VRF proof length. Expected {}, got {}",
+            PROOF_BYTES,
+            proof.len()
+        )));
+    }
+    let gamma = decode_point(&proof[..32])
+        .ok_or_else(|| pyo3::exceptions::PyValueError::new_err("Invalid VRF proof"))?;
+    Ok(output(&gamma))
+}
@@ -0,0 +1,685 @@
+use aes_gcm::aead::{Aead, AeadCore, KeyInit, OsRng, Payload};
+use aes_gcm::{Aes256Gcm, Nonce};
+use curve25519_dalek::constants::RISTRETTO_BASEPOINT_POINT;
+use curve25519_dalek::ristretto::{CompressedRistretto, RistrettoPoint};
+use curve25519_dalek::scalar::Scalar;
+use curve25519_dalek::traits::Identity;
+use hkdf::Hkdf;
+use pyo3::prelude::*;
+use rand::RngCore;
+use sha2::{Digest, Sha256, Sha512};
+use zeroize::Zeroizing;
+
+use crate::wire::Reader;
+
+// Verifiable secret sharing over Ristretto255. The dealer Shamir-shares a random scalar k
+// (and, for Pedersen, a blinding scalar r) and publishes commitments to every polynomial
+// coefficient, so each trustee can check their share against the dealing without seeing any
+// other share. The secret itself is encrypted under a key derived from k. Layouts:
+//   dealing: magic "RQVD" | version u8 | scheme u8 | threshold u8 | count u8 | dealing id (16)
+//            | epoch u32 | commitments (threshold x 32) | nonce (12) | AES-256-GCM(secret)
+//   share:   magic "RQVS" | version u8 | scheme u8 | dealing id (16) | epoch u32 | index u8
+//            | s (32) | t (32, Pedersen only)
+// Feldman commitments are a_j*G; Pedersen commitments are a_j*G + b_j*H, which hide k
+// unconditionally. Share i checks out when s*G (+ t*H) == sum_j C_j * i^j. The secret's AAD is
+// the fixed header (without the epoch) plus C_0, so it stays bound to k across refreshes.
+//
+// Proactive refresh re-randomizes every share without reconstructing k. Each participant deals
+// a sharing of zero: a polynomial with constant term 0, whose commitments Z_1..Z_{t-1} are
+// published (Z_0 is the identity) along with one private delta per trustee:
+//   refresh: magic "RQVU" | version u8 | scheme u8 | dealing id (16) | epoch u32
+//            | refresh id (16) | commitments Z_1..Z_{threshold-1} (32 each)
+//   delta:   magic "RQVE" | version u8 | scheme u8 | refresh id (16) | index u8 | d (32)
+//            | e (32, Pedersen only)
+// Applying refreshes adds their Z_j into C_j (C_0 is unchanged) and bumps the epoch; each
+// trustee adds the deltas addressed to them after checking them against the refresh, so
+// shares from an earlier epoch no longer verify or combine with current ones.
+const DEALING_MAGIC: &[u8; 4] = b"RQVD";
+const SHARE_MAGIC: &[u8; 4] = b"RQVS";
+const REFRESH_MAGIC: &[u8; 4] = b"RQVU";
+const DELTA_MAGIC: &[u8; 4] = b"RQVE";
+const VERSION: u8 = 1;
+const FELDMAN: u8 = 1;
+const PEDERSEN: u8 = 2;
+const ID_BYTES: usize = 16;
+const POINT_BYTES: usize = 32;
+const NONCE_BYTES: usize = 12;
+const MAX_SECRET_BYTES: usize = 65536;
+const GENERATOR_LABEL: &[u8] = b"reliquary/vss/v1/pedersen-generator";
+const KEY_LABEL: &[u8] = b"reliquary/vss/v1/secret-key";
+
+/// Second generator for Pedersen commitments; nobody knows its discrete log with respect to G.
+fn pedersen_h() -> RistrettoPoint {
+    let hash: [u8; 64] = Sha512::digest(GENERATOR_LABEL).into();
+    RistrettoPoint::from_uniform_bytes(&hash)
+}
+
+pub(crate) fn random_scalar() -> Scalar {
+    let mut wide = Zeroizing::new([0u8; 64]);
+    rand::thread_rng().fill_bytes(wide.as_mut());
+    Scalar::from_bytes_mod_order_wide(&wide)
+}
+
+fn scheme_id(name: &str) -> Result<u8, String> {
+    match name {
+        "feldman" => Ok(FELDMAN),
+        "pedersen" => Ok(PEDERSEN),
+        _ => Err(format!(
+            "Unknown VSS scheme '{}' (use feldman or pedersen)",
+            name
+        )),
+    }
+}
+
+fn scheme_name(id: u8) -> &'static str {
+    if id == PEDERSEN {
+        "pedersen"
+    } else {
+        "feldman"
+    }
+}
+
+pub(crate) fn evaluate(coefficients: &[Scalar], x: u8) -> Scalar {
+    let x = Scalar::from(x);
+    coefficients
+        .iter()
+        .rev()
+        .fold(Scalar::ZERO, |acc, c| acc * x + c)
+}
+
+fn secret_key(k: &Scalar, dealing_id: &[u8]) -> Zeroizing<[u8; 32]> {
+    let mut key = Zeroizing::new([0u8; 32]);
+    Hkdf::<Sha256>::new(Some(dealing_id), k.as_bytes())
+        .expand(KEY_LABEL, key.as_mut())
+        .expect("32 bytes is a valid HKDF-SHA256 output length");
+    key
+}
+
+struct Dealing<'a> {
+    /// Everything up to and including the dealing id.
+    prefix: &'a [u8],
+    scheme: u8,
+    threshold: u8,
+    count: u8,
+    id: &'a [u8],
+    epoch: u32,
+    commitments: Vec<RistrettoPoint>,
+    aad: Vec<u8>,
+    nonce: &'a [u8],
+    ciphertext: &'a [u8],
+}
+
+fn read_header(r: &mut Reader<'_>, magic: &[u8; 4], what: &str) -> Result<u8, String> {
+    if r.bytes(4).map_err(|_| format!("Not a VSS {}", what))? != magic {
+        return Err(format!("Not a VSS {}", what));
+    }
+    let version = r.u8()?;
+    if version != VERSION {
+        return Err(format!("Unsupported VSS {} version {}", what, version));
+    }
+    let scheme = r.u8()?;
+    if scheme != FELDMAN && scheme != PEDERSEN {
+        return Err(format!("Unknown VSS scheme id {}", scheme));
+    }
+    Ok(scheme)
+}
+
+pub(crate) fn read_point(r: &mut Reader<'_>, what: &str) -> Result<RistrettoPoint, String> {
+    CompressedRistretto::from_slice(r.bytes(POINT_BYTES)?)
+        .expect("32-byte slice")
+        .decompress()
+        .ok_or_else(|| format!("{} is not a valid Ristretto point", what))
+}
+
+fn parse_dealing(bytes: &[u8]) -> Result<Dealing<'_>, String> {
+    let mut r = Reader::new(bytes);
+    let scheme = read_header(&mut r, DEALING_MAGIC, "dealing")?;
+    let threshold = r.u8()?;
+    let count = r.u8()?;
+    if threshold == 0 || threshold > count {
+        return Err("Dealing threshold is out of range".to_string());
+    }
+    let id = r.bytes(ID_BYTES)?;
+    let prefix = &bytes[..r.position()];
+    let epoch = r.u32()?;
+    let commitments = (0..threshold)
+        .map(|j| read_point(&mut r, &format!("Commitment {}", j)))
+        .collect::<Result<Vec<_>, _>>()?;
+    let mut aad = prefix.to_vec();
+    aad.extend_from_slice(commitments[0].compress().as_bytes());
+    Ok(Dealing {
+        prefix,
+        scheme,
+        threshold,
+        count,
+        id,
+        epoch,
+        commitments,
+        aad,
+        nonce: r.bytes(NONCE_BYTES)?,
+        ciphertext: r.rest(),
+    })
+}
+
+/// A trustee's share, or a refresh delta (where `id` is the refresh id and `epoch` is unused).
+struct Share {
+    scheme: u8,
+    id: [u8; ID_BYTES],
+    epoch: u32,
+    index: u8,
+    s: Scalar,
+    t: Scalar,
+}
+
+pub(crate) fn scalar(bytes: &[u8], what: &str) -> Result<Scalar, String> {
+    let bytes: [u8; 32] = bytes.try_into().expect("32-byte slice");
+    Option::from(Scalar::from_canonical_bytes(bytes))
+        .ok_or_else(|| format!("Share {} is not a canonical scalar", what))
+}
+
+fn parse_share(bytes: &[u8]) -> Result<Share, String> {
+    parse_values(bytes, SHARE_MAGIC, "share")
+}
+
+fn parse_delta(bytes: &[u8]) -> Result<Share, String> {
+    parse_values(bytes, DELTA_MAGIC, "refresh delta")
+}
+
+fn parse_values(bytes: &[u8], magic: &[u8; 4], what: &str) -> Result<Share, String> {
+    let mut r = Reader::new(bytes);
+    let scheme = read_header(&mut r, magic, what)?;
+    let id = r.bytes(ID_BYTES)?.try_into().expect("16-byte slice");
+    let epoch = if magic == SHARE_MAGIC { r.u32()? } else { 0 };
+    let index = r.u8()?;
+    if index == 0 {
+        return Err("Share index 0 is reserved for the secret".to_string());
+    }
+    let s = scalar(r.bytes(32)?, "value")?;
+    let t = if scheme == PEDERSEN {
+        scalar(r.bytes(32)?, "blinding value")?
+    } else {
+        Scalar::ZERO
+    };
+    if !r.is_empty() {
+        return Err(format!("Trailing data after VSS {}", what));
+    }
+    Ok(Share {
+        scheme,
+        id,
+        epoch,
+        index,
+        s,
+        t,
+    })
+}
+
+fn encode_values(
+    magic: &[u8; 4],
+    scheme: u8,
+    id: &[u8],
+    epoch: Option<u32>,
+    index: u8,
+    s: &Scalar,
+    t: &Scalar,
+) -> Vec<u8> {
+    let mut out = magic.to_vec();
+    out.extend_from_slice(&[VERSION, scheme]);
+    out.extend_from_slice(id);
+    if let Some(epoch) = epoch {
+        out.extend_from_slice(&epoch.to_be_bytes());
+    }
+    out.push(index);
+    out.extend_from_slice(s.as_bytes());
+    if scheme == PEDERSEN {
+        out.extend_from_slice(t.as_bytes());
+    }
+    out
+}
+
+fn encode_share(scheme: u8, id: &[u8], epoch: u32, index: u8, s: &Scalar, t: &Scalar) -> Vec<u8> {
+    encode_values(SHARE_MAGIC, scheme, id, Some(epoch), index, s, t)
+}
+
+/// sum_j C_j * x^j: the commitment to a polynomial's value at x.
+pub(crate) fn commitment_at(commitments: &[RistrettoPoint], x: u8) -> RistrettoPoint {
+    let x = Scalar::from(x);
+    let mut power = Scalar::ONE;
+    let mut out = RistrettoPoint::identity();
+    for commitment in commitments {
+        out += commitment * power;
+        power *= x;
+    }
+    out
+}
+
+/// Whether (s, t) opens sum_j C_j * x^j, i.e. lies on the committed polynomial at x.
+fn on_committed_polynomial(scheme: u8, commitments: &[RistrettoPoint], share: &Share) -> bool {
+    let expected = commitment_at(commitments, share.index);
+    let mut actual = RISTRETTO_BASEPOINT_POINT * share.s;
+    if scheme == PEDERSEN {
+        actual += pedersen_h() * share.t;
+    }
+    actual == expected
+}
+
+/// Whether `share` belongs to `dealing` at its current epoch and lies on the committed
+/// polynomial.
+fn share_is_valid(dealing: &Dealing<'_>, share: &Share) -> bool {
+    share.scheme == dealing.scheme
+        && share.id == dealing.id
+        && share.epoch == dealing.epoch
+        && share.index <= dealing.count
+        && on_committed_polynomial(dealing.scheme, &dealing.commitments, share)
+}
+
+/// Compressed commitments a_j*G + b_j*H to each coefficient pair.
+fn commit(a: &[Scalar], b: &[Scalar]) -> Vec<[u8; POINT_BYTES]> {
+    let h = pedersen_h();
+    a.iter()
+        .zip(b)
+        .map(|(aj, bj)| {
+            (RISTRETTO_BASEPOINT_POINT * aj + h * bj)
+                .compress()
+                .to_bytes()
+        })
+        .collect()
+}
+
+/// Lagrange basis coefficients at 0 for the given distinct indices.
+pub(crate) fn lagrange_coefficients(xs: &[u8]) -> Vec<Scalar> {
+    xs.iter()
+        .enumerate()
+        .map(|(i, &xi)| {
+            let xi = Scalar::from(xi);
+            let mut basis = Scalar::ONE;
+            for (j, &xj) in xs.iter().enumerate() {
+                if i != j {
+                    let xj = Scalar::from(xj);
+                    basis *= xj * (xj - xi).invert();
+                }
+            }
+            basis
+        })
+        .collect()
+}
+
+/// Lagrange interpolation at 0 over the shares' indices.
+fn interpolate(points: &[(u8, Scalar)]) -> Scalar {
+    let xs: Vec<u8> = points.iter().map(|&(x, _)| x).collect();
+    lagrange_coefficients(&xs)
+        .iter()
+        .zip(points)
+        .map(|(basis, (_, y))| basis * y)
+        .sum()
+}
+
+/// Splits `secret` into `shares` verifiable shares, any `threshold` of which recover it.
+/// Returns (dealing, shares): the dealing is public and lets every trustee check their share
+/// with vss_verify_share; scheme is "feldman" or "pedersen" (hiding commitments)
+#[pyfunction]
+#[pyo3(signature = (secret, threshold, shares, scheme = "feldman"))]
+pub fn vss_split(
+    secret: Vec<u8>,
+    threshold: u8,
+    shares: u8,
+    scheme: &str,
+) -> PyResult<(Vec<u8>, Vec<Vec<u8>>)> {
+    let secret = Zeroizing::new(secret);
+    (|| {
+        let scheme = scheme_id(scheme)?;
+        if secret.is_empty() || secret.len() > MAX_SECRET_BYTES {
+            return Err(format!("Secret must be 1 to {} bytes", MAX_SECRET_BYTES));
+        }
+        if threshold == 0 || threshold > shares {
+            return Err(format!(
+                "Threshold must be between 1 and the number of shares ({})",
+                shares
+            ));
+        }
+        let a = Zeroizing::new((0..threshold).map(|_| random_scalar()).collect::<Vec<_>>());
+        let b = Zeroizing::new(if scheme == PEDERSEN {
+            (0..threshold).map(|_| random_scalar()).collect::<Vec<_>>()
+        } else {
+            vec![Scalar::ZERO; threshold as usize]
+        });
+        let mut id = [0u8; ID_BYTES];
+        rand::thread_rng().fill_bytes(&mut id);
+
+        let mut out = DEALING_MAGIC.to_vec();
+        out.extend_from_slice(&[VERSION, scheme, threshold, shares]);
+        out.extend_from_slice(&id);
+        let mut aad = out.clone();
+        out.extend_from_slice(&0u32.to_be_bytes());
+        for (j, commitment) in commit(&a, &b).iter().enumerate() {
+            if j == 0 {
+                aad.extend_from_slice(commitment);
+            }
+            out.extend_from_slice(commitment);
+        }
+
+        let key = secret_key(&a[0], &id);
+        let nonce = Aes256Gcm::generate_nonce(&mut OsRng);
+        let ciphertext = Aes256Gcm::new(key.as_slice().into())
+            .encrypt(
+                &nonce,
+                Payload {
+                    msg: &secret,
+                    aad: &aad,
+                },
+            )
+            .expect("AES-GCM encryption of in-memory data cannot fail");
+        out.extend_from_slice(&nonce);
+        out.extend_from_slice(&ciphertext);
+
+        let shares = (1..=shares)
+            .map(|x| encode_share(scheme, &id, 0, x, &evaluate(&a, x), &evaluate(&b, x)))
+            .collect();
+        Ok((out, shares))
+    })()
+    .map_err(pyo3::exceptions::PyValueError::new_err)
+}
+
+/// Checks one share against the dealing's commitments without any other share. False means the
+/// share is corrupt or belongs to another dealing; a malformed dealing or share raises
+/// ValueError
+#[pyfunction]
+pub fn vss_verify_share(dealing: Vec<u8>, share: Vec<u8>) -> PyResult<bool> {
+    let dealing = parse_dealing(&dealing).map_err(pyo3::exceptions::PyValueError::new_err)?;
+    let share = parse_share(&share).map_err(pyo3::exceptions::PyValueError::new_err)?;
+    Ok(share_is_valid(&dealing, &share))
+}
+
+/// Positions in `shares` of every share that is malformed or fails verification, so a dealer
+/// or coordinator can identify corrupted shares at distribution time
+#[pyfunction]
+pub fn vss_check_shares(dealing: Vec<u8>, shares: Vec<Vec<u8>>) -> PyResult<Vec<usize>> {
+    let dealing = parse_dealing(&dealing).map_err(pyo3::exceptions::PyValueError::new_err)?;
+    Ok(shares
+        .iter()
+        .enumerate()
+        .filter(|(_, share)| !parse_share(share).is_ok_and(|s| share_is_valid(&dealing, &s)))
+        .map(|(i, _)| i)
+        .collect())
+}
+
+/// Recovers the secret from verified shares. Invalid shares are skipped; raises ValueError
+/// naming their positions if fewer than threshold distinct valid shares remain
+#[pyfunction]
+pub fn vss_combine(py: Python<'_>, dealing: Vec<u8>, shares: Vec<Vec<u8>>) -> PyResult<Vec<u8>> {
+    py.allow_threads(|| {
+        let dealing = parse_dealing(&dealing)?;
+        let mut valid: Vec<Share> = Vec::new();
+        let mut invalid = Vec::new();
+        for (i, bytes) in shares.iter().enumerate() {
+            match parse_share(bytes) {
+                Ok(share) if share_is_valid(&dealing, &share) => {
+                    if !valid.iter().any(|v| v.index == share.index) {
+                        valid.push(share);
+                    }
+                }
+                _ => invalid.push(i),
+            }
+        }
+        if valid.len() < dealing.threshold as usize {
+            return Err(format!(
+                "Only {} distinct shares verify, threshold is {} (invalid share positions: {:?})",
+                valid.len(),
+                dealing.threshold,
+                invalid
+            ));
+        }
+        valid.truncate(dealing.threshold as usize);
+        let k = Zeroizing::new(interpolate(
+            &valid.iter().map(|s| (s.index, s.s)).collect::<Vec<_>>(),
+        ));
+        let key = secret_key(&k, dealing.id);
+        Aes256Gcm::new(key.as_slice().into())
+            .decrypt(
+                Nonce::from_slice(dealing.nonce),
+                Payload {
+                    msg: dealing.ciphertext,
+                    aad: &dealing.aad,
+                },
+            )
+            .map_err(|_| {
+                "Dealing ciphertext does not authenticate under the shared key".to_string()
+            })
+    })
+    .map_err(pyo3::exceptions::PyValueError::new_err)
+}
+
+/// Returns (scheme, threshold, shares, epoch) recorded in a dealing; the epoch counts the
+/// refreshes applied to it
+#[pyfunction]
+pub fn vss_dealing_info(dealing: Vec<u8>) -> PyResult<(String, u8, u8, u32)> {
+    let dealing = parse_dealing(&dealing).map_err(pyo3::exceptions::PyValueError::new_err)?;
+    Ok((
+        scheme_name(dealing.scheme).to_string(),
+        dealing.threshold,
+        dealing.count,
+        dealing.epoch,
+    ))
+}
+
+struct Refresh<'a> {
+    scheme: u8,
+    dealing_id: &'a [u8],
+    epoch: u32,
+    id: [u8; ID_BYTES],
+    /// Z_1..Z_{threshold-1}; Z_0 is the identity.
+    commitments: Vec<RistrettoPoint>,
+}
+
+fn parse_refresh(bytes: &[u8]) -> Result<Refresh<'_>, String> {
+    let mut r = Reader::new(bytes);
+    let scheme = read_header(&mut r, REFRESH_MAGIC, "refresh")?;
+    let dealing_id = r.bytes(ID_BYTES)?;
+    let epoch = r.u32()?;
+    let id = r.bytes(ID_BYTES)?.try_into().expect("16-byte slice");
+    let rest = r.rest();
+    if !rest.len().is_multiple_of(POINT_BYTES) {
+        return Err("VSS refresh commitments are truncated".to_string());
+    }
+    let mut r = Reader::new(rest);
+    let commitments = (1..=rest.len() / POINT_BYTES)
+        .map(|j| read_point(&mut r, &format!("Refresh commitment {}", j)))
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(Refresh {
+        scheme,
+        dealing_id,
+        epoch,
+        id,
+        commitments,
+    })
+}
+
+impl Refresh<'_> {
+    /// Whether this refresh is for `dealing` at its current epoch.
+    fn check_against(&self, dealing: &Dealing<'_>) -> Result<(), String> {
+        if self.scheme != dealing.scheme || self.dealing_id != dealing.id {
+            return Err("Refresh is for a different dealing".to_string());
+        }
+        if self.epoch != dealing.epoch {
+            return Err(format!(
+                "Refresh is for epoch {}, the dealing is at epoch {}",
+                self.epoch, dealing.epoch
+            ));
+        }
+        if self.commitments.len() + 1 != dealing.threshold as usize {
+            return Err("Refresh polynomial degree does not match the threshold".to_string());
+        }
+        Ok(())
+    }
+
+    fn delta_is_valid(&self, delta: &Share) -> bool {
+        let mut commitments = vec![RistrettoPoint::identity()];
+        commitments.extend_from_slice(&self.commitments);
+        delta.scheme == self.scheme
+            && delta.id == self.id
+            && on_committed_polynomial(self.scheme, &commitments, delta)
+    }
+}
+
+/// Starts a proactive refresh round for `dealing`: deals a random sharing of zero. Returns
+/// (refresh, deltas) where the refresh is public and deltas[i] goes, privately, to the trustee
+/// holding share i + 1. Any number of participants may each contribute a refresh to a round
+#[pyfunction]
+pub fn vss_refresh(dealing: Vec<u8>) -> PyResult<(Vec<u8>, Vec<Vec<u8>>)> {
+    let dealing = parse_dealing(&dealing).map_err(pyo3::exceptions::PyValueError::new_err)?;
+    let zero_constant = |random: bool| {
+        Zeroizing::new(
+            (0..dealing.threshold)
+                .map(|j| {
+                    if j > 0 && random {
+                        random_scalar()
+                    } else {
+                        Scalar::ZERO
+                    }
+                })
+                .collect::<Vec<_>>(),
+        )
+    };
+    let d = zero_constant(true);
+    let e = zero_constant(dealing.scheme == PEDERSEN);
+    let mut id = [0u8; ID_BYTES];
+    rand::thread_rng().fill_bytes(&mut id);
+
+    let mut out = REFRESH_MAGIC.to_vec();
+    out.extend_from_slice(&[VERSION, dealing.scheme]);
+    out.extend_from_slice(dealing.id);
+    out.extend_from_slice(&dealing.epoch.to_be_bytes());
+    out.extend_from_slice(&id);
+    for commitment in &commit(&d, &e)[1..] {
+        out.extend_from_slice(commitment);
+    }
+    let deltas = (1..=dealing.count)
+        .map(|x| {
+            encode_values(
+                DELTA_MAGIC,
+                dealing.scheme,
+                &id,
+                None,
+                x,
+                &evaluate(&d, x),
+                &evaluate(&e, x),
+            )
+        })
+        .collect();
+    Ok((out, deltas))
+}
+
+/// Checks a refresh delta against its public refresh, so a trustee can reject a bad delta
+/// before applying it. False means the delta is corrupt or from another refresh
+#[pyfunction]
+pub fn vss_verify_delta(refresh: Vec<u8>, delta: Vec<u8>) -> PyResult<bool> {
+    let refresh = parse_refresh(&refresh).map_err(pyo3::exceptions::PyValueError::new_err)?;
+    let delta = parse_delta(&delta).map_err(pyo3::exceptions::PyValueError::new_err)?;
+    Ok(refresh.delta_is_valid(&delta))
+}
+
+fn distinct_refreshes<'a>(
+    dealing: &Dealing<'_>,
+    refreshes: &'a [Vec<u8>],
+) -> Result<Vec<Refresh<'a>>, String> {
+    if refreshes.is_empty() {
+        return Err("A refresh round needs at least one refresh".to_string());
+    }
+    let mut parsed: Vec<Refresh<'a>> = Vec::with_capacity(refreshes.len());
+    for (i, bytes) in refreshes.iter().enumerate() {
+        let refresh = parse_refresh(bytes).map_err(|e| format!("Refresh {}: {}", i, e))?;
+        refresh
+            .check_against(dealing)
+            .map_err(|e| format!("Refresh {}: {}", i, e))?;
+        if parsed.iter().any(|p| p.id == refresh.id) {
+            return Err(format!("Refresh {} is listed twice", i));
+        }
+        parsed.push(refresh);
+    }
+    Ok(parsed)
+}
+
+/// Applies a round of refreshes to the public dealing: adds their commitments and advances the
+/// epoch. The secret ciphertext is untouched, and shares must be refreshed with
+/// vss_refresh_share using the same refreshes
+#[pyfunction]
+pub fn vss_apply_refresh(dealing: Vec<u8>, refreshes: Vec<Vec<u8>>) -> PyResult<Vec<u8>> {
+    (|| -> Result<Vec<u8>, String> {
+        let parsed = parse_dealing(&dealing)?;
+        let epoch = parsed
+            .epoch
+            .checked_add(1)
+            .ok_or_else(|| "Dealing epoch is exhausted".to_string())?;
+        let mut commitments = parsed.commitments.clone();
+        for refresh in distinct_refreshes(&parsed, &refreshes)? {
+            for (c, z) in commitments[1..].iter_mut().zip(&refresh.commitments) {
+                *c += z;
+            }
+        }
+        let mut out = parsed.prefix.to_vec();
+        out.extend_from_slice(&epoch.to_be_bytes());
+        for commitment in &commitments {
+            out.extend_from_slice(commitment.compress().as_bytes());
+        }
+        out.extend_from_slice(parsed.nonce);
+        out.extend_from_slice(parsed.ciphertext);
+        Ok(out)
+    })()
+    .map_err(pyo3::exceptions::PyValueError::new_err)
+}
+
+/// Refreshes one trustee's share for the next epoch: checks the share against `dealing` (the
+/// pre-refresh dealing), checks the trustee's delta from every refresh in the round and adds
+/// them in. `deltas` must hold exactly one delta per refresh, in any order; a missing or
+/// invalid delta raises ValueError naming the refresh
+#[pyfunction]
+pub fn vss_refresh_share(
+    dealing: Vec<u8>,
+    share: Vec<u8>,
+    refreshes: Vec<Vec<u8>>,
+    deltas: Vec<Vec<u8>>,
+) -> PyResult<Vec<u8>> {
+    let share = Zeroizing::new(share);
+    (|| {
+        let dealing = parse_dealing(&dealing)?;
+        let mut share = parse_share(&share)?;
+        if !share_is_valid(&dealing, &share) {
+            return Err("Share does not verify against the dealing".to_string());
+        }
+        let refreshes = distinct_refreshes(&dealing, &refreshes)?;
+        if deltas.len() != refreshes.len() {
+            return Err(format!(
+                "Got {} deltas for {} refreshes",
+                deltas.len(),
+                refreshes.len()
+            ));
+        }
+        let deltas = deltas
+            .iter()
+            .map(|d| parse_delta(d))
+            .collect::<Result<Vec<_>, _>>()?;
+        for (i, refresh) in refreshes.iter().enumerate() {
+            let delta = deltas
+                .iter()
+                .find(|d| d.id == refresh.id && d.index == share.index)
+                .ok_or_else(|| format!("No delta for share {} from refresh {}", share.index, i))?;
+            if !refresh.delta_is_valid(delta) {
+                return Err(format!("Delta from refresh {} does not verify", i));
+            }
+            share.s += delta.s;
+            share.t += delta.t;
+        }
+        share.epoch = dealing
+            .epoch
+            .checked_add(1)
+            .ok_or_else(|| "Dealing epoch is exhausted".to_string())?;
+        Ok(encode_share(
+            share.scheme,
+            &share.id,
+            share.epoch,
+            share.index,
+            &share.s,
+            &share.t,
+        ))
+    })()
+    .map_err(pyo3::exceptions::PyValueError::new_err)
+}
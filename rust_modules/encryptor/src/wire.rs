@@ -0,0 +1,81 @@
+//! Length-prefixed binary encoding shared by the multi-party formats (bundles, certificates).
+//! All integers are big-endian.
+
+pub(crate) fn put_var8(out: &mut Vec<u8>, bytes: &[u8]) -> Result<(), String> {
+    let len = u8::try_from(bytes.len()).map_err(|_| "Field exceeds 255 bytes".to_string())?;
+    out.push(len);
+    out.extend_from_slice(bytes);
+    Ok(())
+}
+
+pub(crate) fn put_var16(out: &mut Vec<u8>, bytes: &[u8]) -> Result<(), String> {
+    let len = u16::try_from(bytes.len()).map_err(|_| "Field exceeds 65535 bytes".to_string())?;
+    out.extend_from_slice(&len.to_be_bytes());
+    out.extend_from_slice(bytes);
+    Ok(())
+}
+
+/// Cursor over a serialized structure; every read fails cleanly on truncated input.
+pub(crate) struct Reader<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Reader<'a> {
+    pub(crate) fn new(data: &'a [u8]) -> Self {
+        Reader { data, pos: 0 }
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.pos == self.data.len()
+    }
+
+    pub(crate) fn position(&self) -> usize {
+        self.pos
+    }
+
+    /// Everything not yet read.
+    pub(crate) fn rest(&mut self) -> &'a [u8] {
+        let out = &self.data[self.pos..];
+        self.pos = self.data.len();
+        out
+    }
+
+    pub(crate) fn bytes(&mut self, n: usize) -> Result<&'a [u8], String> {
+        if self.data.len() - self.pos < n {
+            return Err("Input is truncated".to_string());
+        }
+        let out = &self.data[self.pos..self.pos + n];
+        self.pos += n;
+        Ok(out)
+    }
+
+    pub(crate) fn u8(&mut self) -> Result<u8, String> {
+        Ok(self.bytes(1)?[0])
+    }
+
+    pub(crate) fn u16(&mut self) -> Result<u16, String> {
+        let b = self.bytes(2)?;
+        Ok(u16::from_be_bytes([b[0], b[1]]))
+    }
+
+    pub(crate) fn u32(&mut self) -> Result<u32, String> {
+        let b = self.bytes(4)?;
+        Ok(u32::from_be_bytes(b.try_into().expect("4-byte slice")))
+    }
+
+    pub(crate) fn u64(&mut self) -> Result<u64, String> {
+        let b = self.bytes(8)?;
+        Ok(u64::from_be_bytes(b.try_into().expect("8-byte slice")))
+    }
+
+    pub(crate) fn var8(&mut self) -> Result<&'a [u8], String> {
+        let len = self.u8()? as usize;
+        self.bytes(len)
+    }
+
+    pub(crate) fn var16(&mut self) -> Result<&'a [u8], String> {
+        let len = self.u16()? as usize;
+        self.bytes(len)
+    }
+}
@@ -0,0 +1,309 @@
+use std::sync::OnceLock;
+
+use ark_bn254::Fr;
+use ark_crypto_primitives::sponge::constraints::CryptographicSpongeVar;
+use ark_crypto_primitives::sponge::poseidon::constraints::PoseidonSpongeVar;
+use ark_crypto_primitives::sponge::poseidon::{
+    find_poseidon_ark_and_mds, PoseidonConfig, PoseidonSponge,
+};
+use ark_crypto_primitives::sponge::{CryptographicSponge, FieldBasedCryptographicSponge};
+use ark_ff::PrimeField;
+use ark_r1cs_std::fields::fp::FpVar;
+use ark_r1cs_std::prelude::*;
+use ark_relations::r1cs::{ConstraintSynthesizer, ConstraintSystemRef, SynthesisError};
+use sha2::{Digest, Sha256};
+
+// The statements behind zk context proofs, as R1CS over the BN254 scalar field. Commitments
+// are Poseidon (width 3, x^5, 8 full and 57 partial rounds, constants from the Grain LFSR of
+// the Poseidon paper), so they cost a few hundred constraints; they are not circomlib's
+// Poseidon. Byte strings (fingerprints, salts, nonces) enter as SHA-256 of a domain tag and
+// the bytes, reduced mod r.
+
+/// The Poseidon parameters every commitment and circuit here uses.
+fn poseidon_config() -> &'static PoseidonConfig<Fr> {
+    static CONFIG: OnceLock<PoseidonConfig<Fr>> = OnceLock::new();
+    CONFIG.get_or_init(|| {
+        let (ark, mds) = find_poseidon_ark_and_mds::<Fr>(Fr::MODULUS_BIT_SIZE as u64, 2, 8, 57, 0);
+        PoseidonConfig::new(8, 57, 5, mds, ark, 2, 1)
+    })
+}
+
+pub(crate) fn poseidon(inputs: &[Fr]) -> Fr {
+    let mut sponge = PoseidonSponge::new(poseidon_config());
+    sponge.absorb(&inputs);
+    sponge.squeeze_native_field_elements(1)[0]
+}
+
+fn poseidon_var(
+    cs: &ConstraintSystemRef<Fr>,
+    inputs: &[FpVar<Fr>],
+) -> Result<FpVar<Fr>, SynthesisError> {
+    let mut sponge = PoseidonSpongeVar::new(cs.clone(), poseidon_config());
+    sponge.absorb(&inputs)?;
+    Ok(sponge.squeeze_field_elements(1)?.remove(0))
+}
+
+/// `bytes` as a field element, domain-separated by `tag`.
+pub(crate) fn field_from_bytes(tag: &[u8], bytes: &[u8]) -> Fr {
+    let digest = Sha256::new()
+        .chain_update((tag.len() as u64).to_be_bytes())
+        .chain_update(tag)
+        .chain_update(bytes)
+        .finalize();
+    Fr::from_be_bytes_mod_order(&digest)
+}
+
+pub(crate) const FINGERPRINT_TAG: &[u8] = b"reliquary-zk-fingerprint";
+pub(crate) const SALT_TAG: &[u8] = b"reliquary-zk-salt";
+pub(crate) const BLINDING_TAG: &[u8] = b"reliquary-zk-blinding";
+pub(crate) const NONCE_TAG: &[u8] = b"reliquary-zk-nonce";
+pub(crate) const MEMBER_TAG: &[u8] = b"reliquary-zk-member-secret";
+
+/// Commitment to an enrolled device: Poseidon(fingerprint, salt).
+pub(crate) fn device_commitment(fingerprint: &[u8], salt: &[u8]) -> Fr {
+    poseidon(&[
+        field_from_bytes(FINGERPRINT_TAG, fingerprint),
+        field_from_bytes(SALT_TAG, salt),
+    ])
+}
+
+/// Digest of an enrolled set, padded with zeros to the circuit's capacity. Zero is no
+/// device's commitment (that would be a Poseidon preimage), so padding admits no one.
+pub(crate) fn set_digest(enrolled: &[Fr], capacity: usize) -> Fr {
+    let mut padded = enrolled.to_vec();
+    padded.resize(capacity, Fr::from(0u64));
+    poseidon(&padded)
+}
+
+/// Commitment to a timestamp (ms since the Unix epoch): Poseidon(timestamp, blinding).
+pub(crate) fn timestamp_commitment(timestamp: u64, blinding: &[u8]) -> Fr {
+    poseidon(&[
+        Fr::from(timestamp),
+        field_from_bytes(BLINDING_TAG, blinding),
+    ])
+}
+
+/// Leaf of the authorization tree: Poseidon(secret, attribute), where the attribute is the
+/// member's level or role code that membership proofs make claims about.
+pub(crate) fn member_leaf(secret: &[u8], attribute: u64) -> Fr {
+    poseidon(&[field_from_bytes(MEMBER_TAG, secret), Fr::from(attribute)])
+}
+
+/// Levels of a Merkle tree of `depth` over `leaves` (nodes are Poseidon(left, right)), from
+/// the leaves up to the root. Each level only holds its populated prefix; positions past it
+/// are the root of an all-zero subtree of that height, `zeros[level]`.
+pub(crate) struct MerkleTree {
+    levels: Vec<Vec<Fr>>,
+    zeros: Vec<Fr>,
+}
+
+impl MerkleTree {
+    pub(crate) fn new(leaves: Vec<Fr>, depth: usize) -> Self {
+        let mut zeros = vec![Fr::from(0u64)];
+        let mut levels = vec![leaves];
+        for level in 0..depth {
+            let zero = zeros[level];
+            let next = levels[level]
+                .chunks(2)
+                .map(|pair| poseidon(&[pair[0], pair.get(1).copied().unwrap_or(zero)]))
+                .collect();
+            zeros.push(poseidon(&[zero, zero]));
+            levels.push(next);
+        }
+        MerkleTree { levels, zeros }
+    }
+
+    pub(crate) fn root(&self) -> Fr {
+        let depth = self.levels.len() - 1;
+        self.levels[depth]
+            .first()
+            .copied()
+            .unwrap_or(self.zeros[depth])
+    }
+
+    /// Sibling of the leaf at `index` on each level, bottom up.
+    pub(crate) fn path(&self, index: usize) -> Vec<Fr> {
+        self.levels[..self.levels.len() - 1]
+            .iter()
+            .enumerate()
+            .map(|(level, nodes)| {
+                let sibling = (index >> level) ^ 1;
+                nodes.get(sibling).copied().unwrap_or(self.zeros[level])
+            })
+            .collect()
+    }
+}
+
+/// Root reached from `leaf` at `index` along `siblings`.
+pub(crate) fn merkle_root_from_path(leaf: Fr, index: u64, siblings: &[Fr]) -> Fr {
+    siblings
+        .iter()
+        .enumerate()
+        .fold(leaf, |node, (level, sibling)| match (index >> level) & 1 {
+            0 => poseidon(&[node, *sibling]),
+            _ => poseidon(&[*sibling, node]),
+        })
+}
+
+/// Binds `nonce` into the proof. Groth16 already commits to every public input, but a
+/// squaring constraint keeps the input tied to the circuit whatever the QAP reduction does.
+fn bind_nonce(nonce: &FpVar<Fr>) -> Result<(), SynthesisError> {
+    nonce.square().map(|_| ())
+}
+
+/// Enforces `value` < 2^64.
+fn enforce_u64(value: &FpVar<Fr>) -> Result<(), SynthesisError> {
+    for bit in &value.to_bits_le()?[64..] {
+        bit.enforce_equal(&Boolean::FALSE)?;
+    }
+    Ok(())
+}
+
+/// "My device is one of the enrolled set": public set digest and nonce; private fingerprint,
+/// salt and the set itself, padded to `capacity`.
+#[derive(Clone)]
+pub(crate) struct DeviceEnrolled {
+    pub(crate) capacity: usize,
+    pub(crate) fingerprint: Fr,
+    pub(crate) salt: Fr,
+    pub(crate) enrolled: Vec<Fr>,
+    pub(crate) nonce: Fr,
+}
+
+impl DeviceEnrolled {
+    /// All-zero witness of the right shape, for key generation.
+    pub(crate) fn blank(capacity: usize) -> Self {
+        DeviceEnrolled {
+            capacity,
+            fingerprint: Fr::from(0u64),
+            salt: Fr::from(0u64),
+            enrolled: Vec::new(),
+            nonce: Fr::from(0u64),
+        }
+    }
+}
+
+impl ConstraintSynthesizer<Fr> for DeviceEnrolled {
+    fn generate_constraints(self, cs: ConstraintSystemRef<Fr>) -> Result<(), SynthesisError> {
+        let digest =
+            FpVar::new_input(cs.clone(), || Ok(set_digest(&self.enrolled, self.capacity)))?;
+        let nonce = FpVar::new_input(cs.clone(), || Ok(self.nonce))?;
+        let mut enrolled = self.enrolled.clone();
+        enrolled.resize(self.capacity, Fr::from(0u64));
+        let enrolled = enrolled
+            .iter()
+            .map(|member| FpVar::new_witness(cs.clone(), || Ok(*member)))
+            .collect::<Result<Vec<_>, _>>()?;
+        let fingerprint = FpVar::new_witness(cs.clone(), || Ok(self.fingerprint))?;
+        let salt = FpVar::new_witness(cs.clone(), || Ok(self.salt))?;
+
+        poseidon_var(&cs, &enrolled)?.enforce_equal(&digest)?;
+        let commitment = poseidon_var(&cs, &[fingerprint, salt])?;
+        // The commitment is a root of prod (x - member)
+        let mut product = FpVar::one();
+        for member in &enrolled {
+            product *= &commitment - member;
+        }
+        product.enforce_equal(&FpVar::zero())?;
+        bind_nonce(&nonce)
+    }
+}
+
+/// "The committed timestamp lies in [not_before, not_after]": public commitment, bounds and
+/// nonce; private timestamp and blinding.
+#[derive(Clone)]
+pub(crate) struct TimestampWindow {
+    pub(crate) timestamp: u64,
+    pub(crate) blinding: Fr,
+    pub(crate) not_before: u64,
+    pub(crate) not_after: u64,
+    pub(crate) nonce: Fr,
+}
+
+impl TimestampWindow {
+    pub(crate) fn blank() -> Self {
+        TimestampWindow {
+            timestamp: 0,
+            blinding: Fr::from(0u64),
+            not_before: 0,
+            not_after: 0,
+            nonce: Fr::from(0u64),
+        }
+    }
+
+    fn commitment(&self) -> Fr {
+        poseidon(&[Fr::from(self.timestamp), self.blinding])
+    }
+}
+
+impl ConstraintSynthesizer<Fr> for TimestampWindow {
+    fn generate_constraints(self, cs: ConstraintSystemRef<Fr>) -> Result<(), SynthesisError> {
+        let commitment = FpVar::new_input(cs.clone(), || Ok(self.commitment()))?;
+        let not_before = FpVar::new_input(cs.clone(), || Ok(Fr::from(self.not_before)))?;
+        let not_after = FpVar::new_input(cs.clone(), || Ok(Fr::from(self.not_after)))?;
+        let nonce = FpVar::new_input(cs.clone(), || Ok(self.nonce))?;
+        let timestamp = FpVar::new_witness(cs.clone(), || Ok(Fr::from(self.timestamp)))?;
+        let blinding = FpVar::new_witness(cs.clone(), || Ok(self.blinding))?;
+
+        poseidon_var(&cs, &[timestamp.clone(), blinding])?.enforce_equal(&commitment)?;
+        // Both gaps fit in 64 bits, so neither wrapped around the field
+        enforce_u64(&(&timestamp - &not_before))?;
+        enforce_u64(&(&not_after - &timestamp))?;
+        bind_nonce(&nonce)
+    }
+}
+
+/// "I hold a leaf of the tree with this root whose attribute is at least min_attribute":
+/// public root, minimum and nonce; private secret, attribute, leaf index and path. The depth
+/// is fixed by the key, so an inner node can't pass for a leaf.
+#[derive(Clone)]
+pub(crate) struct MerkleMembership {
+    pub(crate) secret: Fr,
+    pub(crate) attribute: u64,
+    pub(crate) index: u64,
+    pub(crate) siblings: Vec<Fr>,
+    pub(crate) min_attribute: u64,
+    pub(crate) nonce: Fr,
+}
+
+impl MerkleMembership {
+    pub(crate) fn blank(depth: usize) -> Self {
+        MerkleMembership {
+            secret: Fr::from(0u64),
+            attribute: 0,
+            index: 0,
+            siblings: vec![Fr::from(0u64); depth],
+            min_attribute: 0,
+            nonce: Fr::from(0u64),
+        }
+    }
+
+    fn root(&self) -> Fr {
+        let leaf = poseidon(&[self.secret, Fr::from(self.attribute)]);
+        merkle_root_from_path(leaf, self.index, &self.siblings)
+    }
+}
+
+impl ConstraintSynthesizer<Fr> for MerkleMembership {
+    fn generate_constraints(self, cs: ConstraintSystemRef<Fr>) -> Result<(), SynthesisError> {
+        let root = FpVar::new_input(cs.clone(), || Ok(self.root()))?;
+        let min_attribute = FpVar::new_input(cs.clone(), || Ok(Fr::from(self.min_attribute)))?;
+        let nonce = FpVar::new_input(cs.clone(), || Ok(self.nonce))?;
+        let secret = FpVar::new_witness(cs.clone(), || Ok(self.secret))?;
+        let attribute = FpVar::new_witness(cs.clone(), || Ok(Fr::from(self.attribute)))?;
+
+        // attribute < 2^64 and attribute - min_attribute didn't wrap around the field
+        enforce_u64(&attribute)?;
+        enforce_u64(&(&attribute - &min_attribute))?;
+        let mut node = poseidon_var(&cs, &[secret, attribute])?;
+        for (level, sibling) in self.siblings.iter().enumerate() {
+            let sibling = FpVar::new_witness(cs.clone(), || Ok(*sibling))?;
+            let is_right = Boolean::new_witness(cs.clone(), || Ok((self.index >> level) & 1 == 1))?;
+            let left = is_right.select(&sibling, &node)?;
+            let right = is_right.select(&node, &sibling)?;
+            node = poseidon_var(&cs, &[left, right])?;
+        }
+        node.enforce_equal(&root)?;
+        bind_nonce(&nonce)
+    }
+}
@@ -0,0 +1,544 @@
+use ark_bn254::{Bn254, Fr};
+use ark_groth16::{Groth16, Proof, ProvingKey, VerifyingKey};
+use ark_relations::r1cs::ConstraintSynthesizer;
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+use ark_snark::SNARK;
+use pyo3::prelude::*;
+use rand::rngs::OsRng;
+
+use circuits::{DeviceEnrolled, MerkleMembership, MerkleTree, TimestampWindow};
+
+mod circuits;
+
+// Zero-knowledge context proofs: Groth16 over BN254 for predicates on context the verifier
+// never sees, e.g. "this device is one of the enrolled ones" (without saying which), "the
+// committed timestamp lies in this window" (without revealing it) or "I hold a leaf of the
+// authorization tree with this root, at or above this level" (without saying which leaf,
+// via a Merkle path inside the circuit). Each proof is bound to a
+// verifier-chosen nonce, so it can't be replayed against another challenge.
+//
+// zk_setup runs a circuit-specific trusted setup and returns (proving_key, verifying_key);
+// whoever runs it can forge proofs, so the verifier side should run it (or an MPC ceremony
+// should) and hand out the proving key. Encodings, all integers big-endian:
+//   key    "RQZK" | 1 | kind u8 (1 proving, 2 verifying) | circuit u8 | capacity u16
+//          | arkworks key (uncompressed proving key, compressed verifying key)
+//   proof  "RQZP" | 1 | circuit u8 | capacity u16 | compressed Groth16 proof (128 bytes)
+// Commitments, leaves and roots are 32-byte little-endian field elements. Capacity is the
+// enrolled set size a device_enrolled key supports (smaller sets are padded), the tree depth
+// of a merkle_membership key and 0 for timestamp_window.
+const KEY_MAGIC: &[u8; 4] = b"RQZK";
+const PROOF_MAGIC: &[u8; 4] = b"RQZP";
+const FORMAT_VERSION: u8 = 1;
+const PROVING_KEY: u8 = 1;
+const VERIFYING_KEY: u8 = 2;
+const HEADER_BYTES: usize = 9;
+const MAX_CAPACITY: usize = 1024;
+const DEFAULT_DEPTH: usize = 20;
+const MAX_DEPTH: usize = 32;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+enum Circuit {
+    DeviceEnrolled,
+    TimestampWindow,
+    MerkleMembership,
+}
+
+impl Circuit {
+    fn from_name(name: &str) -> PyResult<Self> {
+        match name {
+            "device_enrolled" => Ok(Circuit::DeviceEnrolled),
+            "timestamp_window" => Ok(Circuit::TimestampWindow),
+            "merkle_membership" => Ok(Circuit::MerkleMembership),
+            _ => Err(pyo3::exceptions::PyValueError::new_err(format!(
+                "Unknown zk circuit {:?} (expected \"device_enrolled\", \"timestamp_window\" \
+                 or \"merkle_membership\")",
+                name
+            ))),
+        }
+    }
+
+    fn id(self) -> u8 {
+        match self {
+            Circuit::DeviceEnrolled => 1,
+            Circuit::TimestampWindow => 2,
+            Circuit::MerkleMembership => 3,
+        }
+    }
+
+    fn from_id(id: u8) -> Result<Self, String> {
+        match id {
+            1 => Ok(Circuit::DeviceEnrolled),
+            2 => Ok(Circuit::TimestampWindow),
+            3 => Ok(Circuit::MerkleMembership),
+            _ => Err(format!("Unknown zk circuit id {}", id)),
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            Circuit::DeviceEnrolled => "device_enrolled",
+            Circuit::TimestampWindow => "timestamp_window",
+            Circuit::MerkleMembership => "merkle_membership",
+        }
+    }
+}
+
+/// The circuit and capacity a key or proof was made for.
+#[derive(Clone, Copy, PartialEq, Eq)]
+struct Shape {
+    circuit: Circuit,
+    capacity: usize,
+}
+
+impl Shape {
+    fn header(self, magic: &[u8; 4], kind: Option<u8>) -> Vec<u8> {
+        let mut out = magic.to_vec();
+        out.push(FORMAT_VERSION);
+        out.extend(kind);
+        out.push(self.circuit.id());
+        out.extend_from_slice(&(self.capacity as u16).to_be_bytes());
+        out
+    }
+
+    /// Splits off and checks the header of a key (`kind` given) or proof.
+    fn parse<'a>(
+        data: &'a [u8],
+        magic: &[u8; 4],
+        kind: Option<u8>,
+        what: &str,
+    ) -> Result<(Shape, &'a [u8]), String> {
+        let header = HEADER_BYTES - usize::from(kind.is_none());
+        if data.len() < header || &data[..4] != magic {
+            return Err(format!("Not a zk {}", what));
+        }
+        if data[4] != FORMAT_VERSION {
+            return Err(format!(
+                "Unsupported zk {} format version {}",
+                what, data[4]
+            ));
+        }
+        let rest = &data[5..];
+        let rest = match kind {
+            Some(kind) if rest[0] != kind => return Err(format!("Not a zk {}", what)),
+            Some(_) => &rest[1..],
+            None => rest,
+        };
+        let shape = Shape {
+            circuit: Circuit::from_id(rest[0])?,
+            capacity: u16::from_be_bytes([rest[1], rest[2]]) as usize,
+        };
+        Ok((shape, &rest[3..]))
+    }
+
+    fn expect(self, circuit: Circuit) -> Result<Self, String> {
+        if self.circuit == circuit {
+            Ok(self)
+        } else {
+            Err(format!(
+                "Key is for the {} circuit, not {}",
+                self.circuit.name(),
+                circuit.name()
+            ))
+        }
+    }
+}
+
+fn value_error(message: String) -> PyErr {
+    pyo3::exceptions::PyValueError::new_err(message)
+}
+
+fn serialization_error(what: &'static str) -> impl Fn(ark_serialize::SerializationError) -> String {
+    move |e| format!("Malformed zk {}: {}", what, e)
+}
+
+fn decode_proving_key(data: &[u8], circuit: Circuit) -> Result<(Shape, ProvingKey<Bn254>), String> {
+    let (shape, body) = Shape::parse(data, KEY_MAGIC, Some(PROVING_KEY), "proving key")?;
+    // The prover's own key: skipping point validation only risks the prover's proofs
+    let key = ProvingKey::deserialize_uncompressed_unchecked(body)
+        .map_err(serialization_error("proving key"))?;
+    Ok((shape.expect(circuit)?, key))
+}
+
+fn decode_verifying_key(
+    data: &[u8],
+    circuit: Circuit,
+) -> Result<(Shape, VerifyingKey<Bn254>), String> {
+    let (shape, body) = Shape::parse(data, KEY_MAGIC, Some(VERIFYING_KEY), "verifying key")?;
+    let key =
+        VerifyingKey::deserialize_compressed(body).map_err(serialization_error("verifying key"))?;
+    Ok((shape.expect(circuit)?, key))
+}
+
+fn prove<C: ConstraintSynthesizer<Fr>>(
+    shape: Shape,
+    key: &ProvingKey<Bn254>,
+    circuit: C,
+) -> Result<Vec<u8>, String> {
+    let proof = Groth16::<Bn254>::prove(key, circuit, &mut OsRng)
+        .map_err(|e| format!("zk proving failed: {}", e))?;
+    let mut out = shape.header(PROOF_MAGIC, None);
+    proof
+        .serialize_compressed(&mut out)
+        .expect("writing to a Vec");
+    Ok(out)
+}
+
+/// Whether `proof` is a valid proof for `shape` and `public_inputs` under `key`; malformed
+/// proofs are invalid.
+fn verify(shape: Shape, key: &VerifyingKey<Bn254>, proof: &[u8], public_inputs: &[Fr]) -> bool {
+    let Ok((proof_shape, body)) = Shape::parse(proof, PROOF_MAGIC, None, "proof") else {
+        return false;
+    };
+    let Ok(proof) = Proof::<Bn254>::deserialize_compressed(body) else {
+        return false;
+    };
+    proof_shape == shape && Groth16::<Bn254>::verify(key, public_inputs, &proof).unwrap_or(false)
+}
+
+fn encode_field(value: Fr) -> Vec<u8> {
+    let mut out = Vec::with_capacity(32);
+    value
+        .serialize_compressed(&mut out)
+        .expect("writing to a Vec");
+    out
+}
+
+fn decode_field(bytes: &[u8], what: &str) -> Result<Fr, String> {
+    if bytes.len() != 32 {
+        return Err(format!("{} must be 32 bytes, got {}", what, bytes.len()));
+    }
+    Fr::deserialize_compressed(bytes).map_err(|_| format!("{} is not a field element", what))
+}
+
+fn nonce_field(nonce: &[u8]) -> Fr {
+    circuits::field_from_bytes(circuits::NONCE_TAG, nonce)
+}
+
+/// Enrolled commitments as field elements, checked against the key's capacity.
+fn enrolled_set(shape: Shape, enrolled: &[Vec<u8>]) -> Result<Vec<Fr>, String> {
+    if enrolled.is_empty() || enrolled.len() > shape.capacity {
+        return Err(format!(
+            "The enrolled set must have 1 to {} devices, got {}",
+            shape.capacity,
+            enrolled.len()
+        ));
+    }
+    enrolled
+        .iter()
+        .map(|member| decode_field(member, "Device commitment"))
+        .collect()
+}
+
+fn check_depth(depth: usize) -> Result<(), String> {
+    if !(1..=MAX_DEPTH).contains(&depth) {
+        return Err(format!("depth must be between 1 and {}", MAX_DEPTH));
+    }
+    Ok(())
+}
+
+/// Tree over `leaves` (member leaves), checked against `depth`.
+fn merkle_tree(leaves: &[Vec<u8>], depth: usize) -> Result<MerkleTree, String> {
+    check_depth(depth)?;
+    if leaves.len() > 1 << depth {
+        return Err(format!(
+            "A tree of depth {} holds at most {} leaves, got {}",
+            depth,
+            1u64 << depth,
+            leaves.len()
+        ));
+    }
+    let leaves = leaves
+        .iter()
+        .map(|leaf| decode_field(leaf, "Leaf"))
+        .collect::<Result<_, _>>()?;
+    Ok(MerkleTree::new(leaves, depth))
+}
+
+fn check_window(not_before: u64, not_after: u64) -> Result<(), String> {
+    if not_before > not_after {
+        return Err("not_before is after not_after".to_string());
+    }
+    Ok(())
+}
+
+/// Generates (proving_key, verifying_key) for `circuit`: "device_enrolled" (enrolled sets of
+/// up to `capacity` devices, default 8), "timestamp_window" or "merkle_membership" (trees of
+/// depth `capacity`, default 20)
+#[pyfunction]
+#[pyo3(signature = (circuit, capacity = None))]
+pub fn zk_setup(
+    py: Python<'_>,
+    circuit: &str,
+    capacity: Option<usize>,
+) -> PyResult<(Vec<u8>, Vec<u8>)> {
+    let circuit = Circuit::from_name(circuit)?;
+    let capacity = match (circuit, capacity) {
+        (Circuit::DeviceEnrolled, None) => 8,
+        (Circuit::DeviceEnrolled, Some(n)) if (1..=MAX_CAPACITY).contains(&n) => n,
+        (Circuit::DeviceEnrolled, Some(_)) => {
+            return Err(value_error(format!(
+                "capacity must be between 1 and {}",
+                MAX_CAPACITY
+            )))
+        }
+        (Circuit::MerkleMembership, None) => DEFAULT_DEPTH,
+        (Circuit::MerkleMembership, Some(n)) => {
+            check_depth(n).map_err(value_error)?;
+            n
+        }
+        (_, None) => 0,
+        (_, Some(_)) => {
+            return Err(value_error(format!(
+                "The {} circuit takes no capacity",
+                circuit.name()
+            )))
+        }
+    };
+    let shape = Shape { circuit, capacity };
+    py.allow_threads(|| {
+        let keys = match circuit {
+            Circuit::DeviceEnrolled => Groth16::<Bn254>::circuit_specific_setup(
+                DeviceEnrolled::blank(capacity),
+                &mut OsRng,
+            ),
+            Circuit::TimestampWindow => {
+                Groth16::<Bn254>::circuit_specific_setup(TimestampWindow::blank(), &mut OsRng)
+            }
+            Circuit::MerkleMembership => Groth16::<Bn254>::circuit_specific_setup(
+                MerkleMembership::blank(capacity),
+                &mut OsRng,
+            ),
+        };
+        let (pk, vk) = keys.map_err(|e| value_error(format!("zk setup failed: {}", e)))?;
+        let mut proving_key = shape.header(KEY_MAGIC, Some(PROVING_KEY));
+        pk.serialize_uncompressed(&mut proving_key)
+            .expect("writing to a Vec");
+        let mut verifying_key = shape.header(KEY_MAGIC, Some(VERIFYING_KEY));
+        vk.serialize_compressed(&mut verifying_key)
+            .expect("writing to a Vec");
+        Ok((proving_key, verifying_key))
+    })
+}
+
+/// Commitment to a device, as enrolled: binds the fingerprint under a per-device salt
+#[pyfunction]
+pub fn zk_device_commitment(fingerprint: Vec<u8>, salt: Vec<u8>) -> Vec<u8> {
+    encode_field(circuits::device_commitment(&fingerprint, &salt))
+}
+
+/// Proves that (fingerprint, salt) commits to one of `enrolled` (device commitments), for
+/// the verifier's `nonce`. Raises ValueError if the device is not enrolled
+#[pyfunction]
+pub fn zk_prove_device_enrolled(
+    py: Python<'_>,
+    proving_key: Vec<u8>,
+    fingerprint: Vec<u8>,
+    salt: Vec<u8>,
+    enrolled: Vec<Vec<u8>>,
+    nonce: Vec<u8>,
+) -> PyResult<Vec<u8>> {
+    py.allow_threads(|| {
+        let (shape, key) = decode_proving_key(&proving_key, Circuit::DeviceEnrolled)?;
+        let enrolled = enrolled_set(shape, &enrolled)?;
+        if !enrolled.contains(&circuits::device_commitment(&fingerprint, &salt)) {
+            return Err("Device is not in the enrolled set".to_string());
+        }
+        let circuit = DeviceEnrolled {
+            capacity: shape.capacity,
+            fingerprint: circuits::field_from_bytes(circuits::FINGERPRINT_TAG, &fingerprint),
+            salt: circuits::field_from_bytes(circuits::SALT_TAG, &salt),
+            enrolled,
+            nonce: nonce_field(&nonce),
+        };
+        prove(shape, &key, circuit)
+    })
+    .map_err(value_error)
+}
+
+/// Verifies a device-enrolled proof against the enrolled set and nonce. Raises ValueError
+/// for a malformed key or set; a bad proof is False
+#[pyfunction]
+pub fn zk_verify_device_enrolled(
+    py: Python<'_>,
+    verifying_key: Vec<u8>,
+    proof: Vec<u8>,
+    enrolled: Vec<Vec<u8>>,
+    nonce: Vec<u8>,
+) -> PyResult<bool> {
+    py.allow_threads(|| {
+        let (shape, key) = decode_verifying_key(&verifying_key, Circuit::DeviceEnrolled)?;
+        let enrolled = enrolled_set(shape, &enrolled)?;
+        let public_inputs = [
+            circuits::set_digest(&enrolled, shape.capacity),
+            nonce_field(&nonce),
+        ];
+        Ok(verify(shape, &key, &proof, &public_inputs))
+    })
+    .map_err(value_error)
+}
+
+/// Commitment to a timestamp (ms since the Unix epoch) under a secret blinding value
+#[pyfunction]
+pub fn zk_timestamp_commitment(timestamp: u64, blinding: Vec<u8>) -> Vec<u8> {
+    encode_field(circuits::timestamp_commitment(timestamp, &blinding))
+}
+
+/// Proves that the timestamp committed with `blinding` lies in [not_before, not_after], for
+/// the verifier's `nonce`. Raises ValueError if it doesn't
+#[pyfunction]
+pub fn zk_prove_timestamp_window(
+    py: Python<'_>,
+    proving_key: Vec<u8>,
+    timestamp: u64,
+    blinding: Vec<u8>,
+    not_before: u64,
+    not_after: u64,
+    nonce: Vec<u8>,
+) -> PyResult<Vec<u8>> {
+    py.allow_threads(|| {
+        let (shape, key) = decode_proving_key(&proving_key, Circuit::TimestampWindow)?;
+        check_window(not_before, not_after)?;
+        if !(not_before..=not_after).contains(&timestamp) {
+            return Err("Timestamp is outside the window".to_string());
+        }
+        let circuit = TimestampWindow {
+            timestamp,
+            blinding: circuits::field_from_bytes(circuits::BLINDING_TAG, &blinding),
+            not_before,
+            not_after,
+            nonce: nonce_field(&nonce),
+        };
+        prove(shape, &key, circuit)
+    })
+    .map_err(value_error)
+}
+
+/// Verifies a timestamp-window proof for `commitment`. Raises ValueError for a malformed
+/// key, commitment or window; a bad proof is False
+#[pyfunction]
+pub fn zk_verify_timestamp_window(
+    py: Python<'_>,
+    verifying_key: Vec<u8>,
+    proof: Vec<u8>,
+    commitment: Vec<u8>,
+    not_before: u64,
+    not_after: u64,
+    nonce: Vec<u8>,
+) -> PyResult<bool> {
+    py.allow_threads(|| {
+        let (shape, key) = decode_verifying_key(&verifying_key, Circuit::TimestampWindow)?;
+        check_window(not_before, not_after)?;
+        let public_inputs = [
+            decode_field(&commitment, "Timestamp commitment")?,
+            Fr::from(not_before),
+            Fr::from(not_after),
+            nonce_field(&nonce),
+        ];
+        Ok(verify(shape, &key, &proof, &public_inputs))
+    })
+    .map_err(value_error)
+}
+
+/// Leaf of the authorization tree for a member's `secret` and `attribute` (e.g. a clearance
+/// level). The secret never leaves the member; the tree only holds leaves
+#[pyfunction]
+pub fn zk_member_leaf(secret: Vec<u8>, attribute: u64) -> Vec<u8> {
+    encode_field(circuits::member_leaf(&secret, attribute))
+}
+
+/// Root of the tree of `depth` over `leaves`, in order; the rest of the tree is empty
+#[pyfunction]
+#[pyo3(signature = (leaves, depth = DEFAULT_DEPTH))]
+pub fn zk_merkle_root(py: Python<'_>, leaves: Vec<Vec<u8>>, depth: usize) -> PyResult<Vec<u8>> {
+    py.allow_threads(|| Ok(encode_field(merkle_tree(&leaves, depth)?.root())))
+        .map_err(value_error)
+}
+
+/// Authentication path (siblings, bottom up) of the leaf at `index`, for the member to prove
+/// with. Hand each member only their own path
+#[pyfunction]
+#[pyo3(signature = (leaves, index, depth = DEFAULT_DEPTH))]
+pub fn zk_merkle_path(
+    py: Python<'_>,
+    leaves: Vec<Vec<u8>>,
+    index: usize,
+    depth: usize,
+) -> PyResult<Vec<Vec<u8>>> {
+    py.allow_threads(|| {
+        let tree = merkle_tree(&leaves, depth)?;
+        if index >= leaves.len() {
+            return Err(format!("No leaf at index {}", index));
+        }
+        Ok(tree.path(index).into_iter().map(encode_field).collect())
+    })
+    .map_err(value_error)
+}
+
+/// Proves that the leaf for (secret, attribute) sits at `index` of the tree that `path`
+/// leads to, with attribute >= `min_attribute`, for the verifier's `nonce`. Neither the leaf
+/// nor its index is revealed. Raises ValueError if the attribute is too low
+#[pyfunction]
+#[allow(clippy::too_many_arguments)]
+pub fn zk_prove_merkle_membership(
+    py: Python<'_>,
+    proving_key: Vec<u8>,
+    secret: Vec<u8>,
+    attribute: u64,
+    index: u64,
+    path: Vec<Vec<u8>>,
+    min_attribute: u64,
+    nonce: Vec<u8>,
+) -> PyResult<Vec<u8>> {
+    py.allow_threads(|| {
+        let (shape, key) = decode_proving_key(&proving_key, Circuit::MerkleMembership)?;
+        if path.len() != shape.capacity {
+            return Err(format!(
+                "The path must have {} siblings, got {}",
+                shape.capacity,
+                path.len()
+            ));
+        }
+        if index >> shape.capacity != 0 {
+            return Err(format!("Index {} is outside the tree", index));
+        }
+        if attribute < min_attribute {
+            return Err("Attribute is below the minimum".to_string());
+        }
+        let circuit = MerkleMembership {
+            secret: circuits::field_from_bytes(circuits::MEMBER_TAG, &secret),
+            attribute,
+            index,
+            siblings: path
+                .iter()
+                .map(|sibling| decode_field(sibling, "Path node"))
+                .collect::<Result<_, _>>()?,
+            min_attribute,
+            nonce: nonce_field(&nonce),
+        };
+        prove(shape, &key, circuit)
+    })
+    .map_err(value_error)
+}
+
+/// Verifies a membership proof against the tree's `root` and `min_attribute`. Raises
+/// ValueError for a malformed key or root; a bad proof is False
+#[pyfunction]
+pub fn zk_verify_merkle_membership(
+    py: Python<'_>,
+    verifying_key: Vec<u8>,
+    proof: Vec<u8>,
+    root: Vec<u8>,
+    min_attribute: u64,
+    nonce: Vec<u8>,
+) -> PyResult<bool> {
+    py.allow_threads(|| {
+        let (shape, key) = decode_verifying_key(&verifying_key, Circuit::MerkleMembership)?;
+        let public_inputs = [
+            decode_field(&root, "Root")?,
+            Fr::from(min_attribute),
+            nonce_field(&nonce),
+        ];
+        Ok(verify(shape, &key, &proof, &public_inputs))
+    })
+    .map_err(value_error)
+}
@@ -0,0 +1,459 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use pyo3::prelude::*;
+use sha2::{Digest, Sha256};
+
+use crate::scheme::{Hash, TreeVersion};
+use crate::serialize::{MerkleProof, MerkleRoot};
+use crate::tree::{hex_root, MerkleTree, StoredNodes};
+use crate::wire::{put_var8, Reader};
+
+// Audit log: every entry is hash-chained to the one before it as soon as it is appended, and
+// checkpoints periodically fold the entries since the last one into a Merkle tree. A
+// checkpoint records the tree size, the root and the chain head at that point, so one signed
+// checkpoint (sign_tree_head takes it directly) commits to the whole history: an entry is
+// proven against it with an inclusion proof, a later checkpoint extends it by a consistency
+// proof, and a run of entries shows continuity by linking up to its chain head.
+//
+// An entry's bytes are the Merkle leaf and the input to its chain hash:
+//   entry       index u64 | timestamp u64 | previous chain hash (32) | data
+//   checkpoint  "RQAC" | 1 | tree_size u64 | timestamp u64 | chain head (32) | root var8
+// Timestamps are milliseconds since the Unix epoch and never go backwards. The chain hash is
+// SHA-256 of the entry bytes whatever the tree hashes with, so entries check on their own; the
+// first entry links to 32 zero bytes.
+const CHECKPOINT_MAGIC: &[u8; 4] = b"RQAC";
+const FORMAT_VERSION: u8 = 1;
+const ENTRY_HEADER_BYTES: usize = 48;
+
+fn unix_millis() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(u64::MAX)
+}
+
+/// One audit log entry: its position, time, data and link to the entry before it
+#[pyclass(frozen, eq, module = "reliquary_merkle")]
+#[derive(Clone, PartialEq, Eq)]
+pub struct AuditEntry {
+    #[pyo3(get)]
+    index: u64,
+    /// Milliseconds since the Unix epoch
+    #[pyo3(get)]
+    timestamp: u64,
+    prev_hash: Hash,
+    #[pyo3(get)]
+    data: Vec<u8>,
+}
+
+impl AuditEntry {
+    fn encode(&self) -> Vec<u8> {
+        let mut out = Vec::with_capacity(ENTRY_HEADER_BYTES + self.data.len());
+        out.extend_from_slice(&self.index.to_be_bytes());
+        out.extend_from_slice(&self.timestamp.to_be_bytes());
+        out.extend_from_slice(&self.prev_hash);
+        out.extend_from_slice(&self.data);
+        out
+    }
+
+    fn hash(&self) -> Hash {
+        Sha256::digest(self.encode()).into()
+    }
+}
+
+#[pymethods]
+impl AuditEntry {
+    /// Chain hash of the entry before this one (zeros for the first)
+    #[getter]
+    fn prev_hash(&self) -> Vec<u8> {
+        self.prev_hash.to_vec()
+    }
+
+    /// This entry's chain hash, SHA-256 of to_bytes(); the next entry's prev_hash
+    #[getter]
+    fn chain_hash(&self) -> Vec<u8> {
+        self.hash().to_vec()
+    }
+
+    /// The entry's bytes: its Merkle leaf and the input to its chain hash
+    fn to_bytes(&self) -> Vec<u8> {
+        self.encode()
+    }
+
+    #[staticmethod]
+    fn from_bytes(data: Vec<u8>) -> PyResult<Self> {
+        let decode = || -> Result<Self, String> {
+            let mut r = Reader::new(&data);
+            Ok(AuditEntry {
+                index: r.u64()?,
+                timestamp: r.u64()?,
+                prev_hash: r.hashes(1)?[0],
+                data: data[ENTRY_HEADER_BYTES..].to_vec(),
+            })
+        };
+        decode().map_err(pyo3::exceptions::PyValueError::new_err)
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "AuditEntry(index={}, timestamp={}, data={} bytes)",
+            self.index,
+            self.timestamp,
+            self.data.len()
+        )
+    }
+}
+
+/// Checkpoint of an AuditLog: the Merkle root over its first tree_size entries and the chain
+/// head after them. Has .root and .tree_size, so sign_tree_head can sign it as it is
+#[pyclass(frozen, eq, module = "reliquary_merkle")]
+#[derive(Clone, PartialEq, Eq)]
+pub struct AuditCheckpoint {
+    #[pyo3(get)]
+    tree_size: usize,
+    /// Milliseconds since the Unix epoch
+    #[pyo3(get)]
+    timestamp: u64,
+    chain_head: Hash,
+    version: TreeVersion,
+    root: Hash,
+}
+
+impl AuditCheckpoint {
+    fn decode(data: &[u8]) -> Result<Self, String> {
+        let mut r = Reader::new(data);
+        r.header(CHECKPOINT_MAGIC, FORMAT_VERSION, "audit checkpoint")?;
+        let tree_size = r.size()?;
+        let timestamp = r.u64()?;
+        let chain_head = r.hashes(1)?[0];
+        let (version, root) = TreeVersion::of_root(r.var8()?).ok_or("Not a Merkle root")?;
+        r.finish("audit checkpoint")?;
+        if tree_size == 0 {
+            return Err("An audit checkpoint covers at least one entry".to_string());
+        }
+        Ok(AuditCheckpoint {
+            tree_size,
+            timestamp,
+            chain_head,
+            version,
+            root: root.try_into().expect("32-byte root hash"),
+        })
+    }
+}
+
+#[pymethods]
+impl AuditCheckpoint {
+    /// Merkle root over the first tree_size entries (version-tagged for v2)
+    #[getter]
+    fn root(&self) -> Vec<u8> {
+        self.version.encode_root(&self.root)
+    }
+
+    /// Chain hash of the last entry the checkpoint covers
+    #[getter]
+    fn chain_head(&self) -> Vec<u8> {
+        self.chain_head.to_vec()
+    }
+
+    /// root and tree_size as a MerkleRoot
+    fn tree_root(&self) -> MerkleRoot {
+        MerkleRoot::from_hash(self.version, self.tree_size, self.root)
+    }
+
+    /// Checks that `proof` (from AuditLog.prove) shows `entry` under this checkpoint
+    fn verify_entry(&self, entry: &AuditEntry, proof: &MerkleProof) -> bool {
+        proof.index() == Some(entry.index as usize)
+            && proof.tree_size() == self.tree_size
+            && proof.verify_inclusion(entry.encode(), self.root())
+    }
+
+    fn to_bytes(&self) -> Vec<u8> {
+        let mut out = CHECKPOINT_MAGIC.to_vec();
+        out.push(FORMAT_VERSION);
+        out.extend_from_slice(&(self.tree_size as u64).to_be_bytes());
+        out.extend_from_slice(&self.timestamp.to_be_bytes());
+        out.extend_from_slice(&self.chain_head);
+        put_var8(&mut out, &self.root()).expect("roots are at most 34 bytes");
+        out
+    }
+
+    #[staticmethod]
+    fn from_bytes(data: Vec<u8>) -> PyResult<Self> {
+        AuditCheckpoint::decode(&data).map_err(pyo3::exceptions::PyValueError::new_err)
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "AuditCheckpoint(tree_size={}, timestamp={}, root={})",
+            self.tree_size,
+            self.timestamp,
+            hex_root(Some(self.root))
+        )
+    }
+}
+
+/// Whether `entries` link up: consecutive indices, each prev_hash the chain hash of the entry
+/// before, timestamps never going backwards, and the first entry of a log linking to zeros.
+/// With `checkpoint`, the run must also end at the checkpoint's chain head.
+fn chain_links(entries: &[AuditEntry], checkpoint: Option<&AuditCheckpoint>) -> bool {
+    let Some(first) = entries.first() else {
+        return checkpoint.is_none();
+    };
+    if first.index == 0 && first.prev_hash != [0u8; 32] {
+        return false;
+    }
+    let linked = entries.windows(2).all(|pair| {
+        pair[1].index == pair[0].index + 1
+            && pair[1].prev_hash == pair[0].hash()
+            && pair[1].timestamp >= pair[0].timestamp
+    });
+    let last = entries.last().expect("non-empty");
+    linked
+        && checkpoint.is_none_or(|checkpoint| {
+            last.index + 1 == checkpoint.tree_size as u64
+                && last.hash() == checkpoint.chain_head
+                && last.timestamp <= checkpoint.timestamp
+        })
+}
+
+/// Append-only, hash-chained audit log with Merkle checkpoints. Entries chain as they are
+/// appended; checkpoint() (or every checkpoint_interval entries) commits them to the tree
+#[pyclass(module = "reliquary_merkle")]
+pub struct AuditLog {
+    entries: Vec<AuditEntry>,
+    /// Holds the entries up to the latest checkpoint.
+    tree: MerkleTree,
+    checkpoints: Vec<AuditCheckpoint>,
+    checkpoint_interval: Option<usize>,
+}
+
+impl AuditLog {
+    fn head(&self) -> Hash {
+        self.entries.last().map_or([0u8; 32], AuditEntry::hash)
+    }
+
+    fn last_timestamp(&self) -> u64 {
+        let entry = self.entries.last().map_or(0, |entry| entry.timestamp);
+        let checkpoint = self.checkpoints.last().map_or(0, |c| c.timestamp);
+        entry.max(checkpoint)
+    }
+
+    fn check_timestamp(&self, timestamp: Option<u64>) -> PyResult<u64> {
+        let last = self.last_timestamp();
+        match timestamp.unwrap_or_else(|| unix_millis().max(last)) {
+            t if t < last => Err(pyo3::exceptions::PyValueError::new_err(format!(
+                "Audit log timestamps must not go backwards (last at {} ms)",
+                last
+            ))),
+            t => Ok(t),
+        }
+    }
+
+    /// `checkpoint` if it is one of this log's, else ValueError.
+    fn own<'a>(&self, checkpoint: &'a AuditCheckpoint) -> PyResult<&'a AuditCheckpoint> {
+        if self.checkpoints.contains(checkpoint) {
+            Ok(checkpoint)
+        } else {
+            Err(pyo3::exceptions::PyValueError::new_err(
+                "Checkpoint is not from this audit log",
+            ))
+        }
+    }
+
+    fn latest(&self) -> PyResult<&AuditCheckpoint> {
+        self.checkpoints.last().ok_or_else(|| {
+            pyo3::exceptions::PyValueError::new_err("Audit log has no checkpoint yet")
+        })
+    }
+}
+
+#[pymethods]
+impl AuditLog {
+    /// `checkpoint_interval`: checkpoint automatically whenever that many entries are
+    /// pending. The tree's version and hash algorithm are as for MerkleTree
+    #[new]
+    #[pyo3(signature = (checkpoint_interval = None, version = 2, hash_algorithm = "sha256"))]
+    fn new(
+        checkpoint_interval: Option<usize>,
+        version: u8,
+        hash_algorithm: &str,
+    ) -> PyResult<Self> {
+        if checkpoint_interval == Some(0) {
+            return Err(pyo3::exceptions::PyValueError::new_err(
+                "checkpoint_interval must be at least 1",
+            ));
+        }
+        Ok(AuditLog {
+            entries: Vec::new(),
+            tree: MerkleTree::new(None, version, hash_algorithm)?,
+            checkpoints: Vec::new(),
+            checkpoint_interval,
+        })
+    }
+
+    /// Appends `data` as the next entry, stamped `timestamp` (ms since the Unix epoch;
+    /// defaults to now) and chained to the current head. Returns the entry
+    #[pyo3(signature = (data, timestamp = None))]
+    fn append(&mut self, data: Vec<u8>, timestamp: Option<u64>) -> PyResult<AuditEntry> {
+        let entry = AuditEntry {
+            index: self.entries.len() as u64,
+            timestamp: self.check_timestamp(timestamp)?,
+            prev_hash: self.head(),
+            data,
+        };
+        self.entries.push(entry.clone());
+        if self
+            .checkpoint_interval
+            .is_some_and(|interval| self.entries.len() - self.tree.size() >= interval)
+        {
+            self.checkpoint(Some(entry.timestamp))?;
+        }
+        Ok(entry)
+    }
+
+    /// Commits every entry so far to the tree and records a checkpoint of it
+    #[pyo3(signature = (timestamp = None))]
+    fn checkpoint(&mut self, timestamp: Option<u64>) -> PyResult<AuditCheckpoint> {
+        if self.entries.is_empty() {
+            return Err(pyo3::exceptions::PyValueError::new_err(
+                "An audit checkpoint covers at least one entry",
+            ));
+        }
+        let timestamp = self.check_timestamp(timestamp)?;
+        let version = self.tree.scheme();
+        let pending = &self.entries[self.tree.size()..];
+        self.tree.push_all(
+            pending
+                .iter()
+                .map(|entry| version.hash_leaf(&entry.encode())),
+        );
+        let checkpoint = AuditCheckpoint {
+            tree_size: self.tree.size(),
+            timestamp,
+            chain_head: self.head(),
+            version,
+            root: self.tree.root_hash_at(self.tree.size())?,
+        };
+        self.checkpoints.push(checkpoint.clone());
+        Ok(checkpoint)
+    }
+
+    /// Inclusion proof for entry `index` against `checkpoint` (default the latest); check it
+    /// with AuditCheckpoint.verify_entry
+    #[pyo3(signature = (index, checkpoint = None))]
+    fn prove(&self, index: usize, checkpoint: Option<AuditCheckpoint>) -> PyResult<MerkleProof> {
+        let checkpoint = match &checkpoint {
+            Some(checkpoint) => self.own(checkpoint)?,
+            None => self.latest()?,
+        };
+        if index >= checkpoint.tree_size {
+            return Err(pyo3::exceptions::PyIndexError::new_err(format!(
+                "Entry {} is not covered by the checkpoint ({} entries)",
+                index, checkpoint.tree_size
+            )));
+        }
+        self.tree.inclusion_proof_at(checkpoint.tree_size, index)
+    }
+
+    /// Consistency proof that checkpoint `old` is a prefix of `new` (default the latest);
+    /// check it with MerkleProof.verify_consistency(old.root, new.root)
+    #[pyo3(signature = (old, new = None))]
+    fn prove_consistency(
+        &self,
+        old: AuditCheckpoint,
+        new: Option<AuditCheckpoint>,
+    ) -> PyResult<MerkleProof> {
+        let new = match &new {
+            Some(new) => self.own(new)?,
+            None => self.latest()?,
+        };
+        let old = self.own(&old)?;
+        self.tree
+            .consistency_proof(old.tree_size, Some(new.tree_size))
+    }
+
+    /// Re-checks the whole log: every chain link and timestamp, and every checkpoint's root
+    /// and chain head
+    fn verify_chain(&self) -> PyResult<bool> {
+        if !chain_links(&self.entries, None) {
+            return Ok(false);
+        }
+        for checkpoint in &self.checkpoints {
+            let covered = &self.entries[..checkpoint.tree_size];
+            let root = self.tree.root_hash_at(checkpoint.tree_size)?;
+            if !chain_links(covered, Some(checkpoint)) || root != checkpoint.root {
+                return Ok(false);
+            }
+        }
+        let leaves = self.entries[..self.tree.size()].iter().enumerate();
+        for (index, entry) in leaves {
+            if self.tree.stored(0, index)? != self.tree.scheme().hash_leaf(&entry.encode()) {
+                return Ok(false);
+            }
+        }
+        Ok(true)
+    }
+
+    /// Entry `index`
+    fn entry(&self, index: usize) -> PyResult<AuditEntry> {
+        self.entries
+            .get(index)
+            .cloned()
+            .ok_or_else(|| pyo3::exceptions::PyIndexError::new_err("Entry index out of range"))
+    }
+
+    /// Entries `start..end` (default to the end), e.g. to hand to verify_audit_chain
+    #[pyo3(signature = (start = 0, end = None))]
+    fn entries(&self, start: usize, end: Option<usize>) -> PyResult<Vec<AuditEntry>> {
+        let end = end.unwrap_or(self.entries.len());
+        self.entries
+            .get(start..end)
+            .map(<[AuditEntry]>::to_vec)
+            .ok_or_else(|| pyo3::exceptions::PyIndexError::new_err("Entry range out of range"))
+    }
+
+    /// Every checkpoint, oldest first
+    fn checkpoints(&self) -> Vec<AuditCheckpoint> {
+        self.checkpoints.clone()
+    }
+
+    /// Latest checkpoint, or None
+    #[getter]
+    fn latest_checkpoint(&self) -> Option<AuditCheckpoint> {
+        self.checkpoints.last().cloned()
+    }
+
+    /// Number of entries not yet in a checkpoint
+    #[getter]
+    fn pending(&self) -> usize {
+        self.entries.len() - self.tree.size()
+    }
+
+    /// Chain hash of the last entry (zeros for an empty log)
+    #[getter]
+    fn chain_head(&self) -> Vec<u8> {
+        self.head().to_vec()
+    }
+
+    fn __len__(&self) -> usize {
+        self.entries.len()
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "AuditLog(entries={}, checkpoints={}, pending={})",
+            self.entries.len(),
+            self.checkpoints.len(),
+            self.pending()
+        )
+    }
+}
+
+/// Checks that `entries` form an unbroken run of an audit log: consecutive indices, each
+/// linked to the chain hash of the one before, timestamps never going backwards, and a first
+/// entry at index 0 linked to zeros. With `checkpoint`, the run must end at its chain head
+#[pyfunction]
+#[pyo3(signature = (entries, checkpoint = None))]
+pub fn verify_audit_chain(entries: Vec<AuditEntry>, checkpoint: Option<AuditCheckpoint>) -> bool {
+    chain_links(&entries, checkpoint.as_ref())
+}
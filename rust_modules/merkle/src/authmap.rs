@@ -0,0 +1,123 @@
+use std::collections::BTreeMap;
+
+use pyo3::prelude::*;
+use pyo3::types::PyDict;
+use sha2::{Digest, Sha256};
+
+use crate::scheme::Hash;
+use crate::sparse::{verify_sparse_proof, SparseMerkleTree, SparseProof};
+
+// Authenticated key-value map for committed state (device ID -> trust score, key -> policy).
+// Keys of any length are placed in a SparseMerkleTree at SHA-256(0x02 | key), so the map has
+// the tree's canonical root and its proofs of inclusion and of absence; the 0x02 prefix keeps
+// key paths apart from the tree's 0x00 leaf and 0x01 node hashes. A verifier needs only the
+// key, the claimed value (or None for absence), the proof and the root.
+
+const KEY_PREFIX: u8 = 0x02;
+
+fn key_path(key: &[u8]) -> Hash {
+    Sha256::new()
+        .chain_update([KEY_PREFIX])
+        .chain_update(key)
+        .finalize()
+        .into()
+}
+
+/// Authenticated map from byte keys to byte values, with a root commitment and proofs that a
+/// key holds a value or is absent; check them with verify_map_proof
+#[pyclass(module = "reliquary_merkle")]
+pub struct AuthenticatedMap {
+    tree: SparseMerkleTree,
+    keys: BTreeMap<Vec<u8>, Hash>,
+}
+
+#[pymethods]
+impl AuthenticatedMap {
+    /// `items` may be a dict or any iterable of (key, value) pairs
+    #[new]
+    #[pyo3(signature = (items = None))]
+    fn new(items: Option<&Bound<'_, PyAny>>) -> PyResult<Self> {
+        let mut map = AuthenticatedMap {
+            tree: SparseMerkleTree::new(None)?,
+            keys: BTreeMap::new(),
+        };
+        if let Some(items) = items {
+            let pairs = match items.downcast::<PyDict>() {
+                Ok(dict) => dict.items().into_any(),
+                Err(_) => items.clone(),
+            };
+            for pair in pairs.try_iter()? {
+                let (key, value): (Vec<u8>, Vec<u8>) = pair?.extract()?;
+                map.put(key, value)?;
+            }
+        }
+        Ok(map)
+    }
+
+    /// Value stored under `key`, or None
+    fn get(&self, key: Vec<u8>) -> PyResult<Option<Vec<u8>>> {
+        self.tree.get(key_path(&key).to_vec())
+    }
+
+    /// Sets the value under `key`, replacing any previous one
+    fn put(&mut self, key: Vec<u8>, value: Vec<u8>) -> PyResult<()> {
+        let path = key_path(&key);
+        self.tree.update(path.to_vec(), value)?;
+        self.keys.insert(key, path);
+        Ok(())
+    }
+
+    /// Removes `key`; returns whether it was present
+    fn delete(&mut self, key: Vec<u8>) -> PyResult<bool> {
+        self.keys.remove(&key);
+        self.tree.delete(key_path(&key).to_vec())
+    }
+
+    /// Root commitment (32 bytes; all zero for an empty map)
+    fn root(&self) -> Vec<u8> {
+        self.tree.root()
+    }
+
+    /// Proof for `key` as (siblings, leaf): of inclusion if the key is present, of absence
+    /// otherwise
+    fn prove(&self, key: Vec<u8>) -> SparseProof {
+        self.tree.proof(&key_path(&key))
+    }
+
+    /// (key, value) pairs in key order
+    fn items(&self) -> PyResult<Vec<(Vec<u8>, Vec<u8>)>> {
+        self.keys
+            .iter()
+            .map(|(key, path)| {
+                let value = self.tree.get(path.to_vec())?.expect("mapped key is stored");
+                Ok((key.clone(), value))
+            })
+            .collect()
+    }
+
+    fn __contains__(&self, key: Vec<u8>) -> bool {
+        self.keys.contains_key(&key)
+    }
+
+    fn __len__(&self) -> usize {
+        self.keys.len()
+    }
+
+    fn __repr__(&self) -> String {
+        let root: String = self.root().iter().map(|b| format!("{:02x}", b)).collect();
+        format!("AuthenticatedMap(len={}, root={})", self.keys.len(), root)
+    }
+}
+
+/// Verifies an AuthenticatedMap proof: that `key` maps to `value`, or with value=None that
+/// `key` is absent. Malformed proofs fail
+#[pyfunction]
+#[pyo3(signature = (key, value, proof, root))]
+pub fn verify_map_proof(
+    key: Vec<u8>,
+    value: Option<Vec<u8>>,
+    proof: SparseProof,
+    root: Vec<u8>,
+) -> bool {
+    verify_sparse_proof(key_path(&key).to_vec(), value, proof, root)
+}
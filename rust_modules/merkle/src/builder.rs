@@ -0,0 +1,152 @@
+use std::fs::File;
+use std::io::{BufReader, ErrorKind, Read};
+
+use pyo3::prelude::*;
+
+use crate::scheme::{Hash, TreeVersion};
+use crate::serialize::MerkleRoot;
+use crate::tree::hex_root;
+
+// Streaming root computation. Leaves are folded in as they arrive, keeping only the root of
+// each complete power-of-two subtree still waiting for a right-hand sibling: pending[l] holds
+// a subtree of 2^l leaves exactly when bit l of the leaf count is set, so memory is O(log n)
+// hashes however many leaves go in. root() closes the right edge the way create_merkle_root
+// does, duplicating (v1) or carrying up (v2) unpaired nodes, and gives the same root for the
+// same leaves.
+//
+// update_file reads length-prefixed records: a u32 big-endian byte count, then the record.
+
+/// Merkle root over leaves fed in one at a time, from any iterable, or from a file of
+/// length-prefixed records, without holding the whole dataset in memory
+#[pyclass(module = "reliquary_merkle")]
+pub struct RootBuilder {
+    version: TreeVersion,
+    len: usize,
+    pending: Vec<Option<Hash>>,
+}
+
+impl RootBuilder {
+    fn push(&mut self, leaf: &[u8]) {
+        let mut node = self.version.hash_leaf(leaf);
+        let mut level = 0;
+        while let Some(left) = self.pending.get_mut(level).and_then(Option::take) {
+            node = self.version.hash_pair(&left, &node);
+            level += 1;
+        }
+        if level == self.pending.len() {
+            self.pending.push(None);
+        }
+        self.pending[level] = Some(node);
+        self.len += 1;
+    }
+
+    fn root_hash(&self) -> Option<Hash> {
+        if self.len == 0 {
+            return None;
+        }
+        // `edge` is the unstored node on the right edge of each level, as in MerkleTree
+        let mut edge: Option<Hash> = None;
+        for level in 0.. {
+            let stored = self.len >> level;
+            let last = self.pending.get(level).copied().flatten();
+            if stored + edge.is_some() as usize <= 1 {
+                return edge.or(last);
+            }
+            edge = match (last, edge) {
+                (Some(last), None) => Some(self.version.hash_unpaired(&last)),
+                (Some(last), Some(edge)) => Some(self.version.hash_pair(&last, &edge)),
+                (None, Some(edge)) => Some(self.version.hash_unpaired(&edge)),
+                (None, None) => None,
+            };
+        }
+        unreachable!("a tree has finitely many levels")
+    }
+}
+
+#[pymethods]
+impl RootBuilder {
+    #[new]
+    #[pyo3(signature = (version = 1, hash_algorithm = "sha256"))]
+    fn new(version: u8, hash_algorithm: &str) -> PyResult<Self> {
+        Ok(RootBuilder {
+            version: TreeVersion::from_arg(version, hash_algorithm)?,
+            len: 0,
+            pending: Vec::new(),
+        })
+    }
+
+    /// Adds one leaf
+    fn update(&mut self, leaf: Vec<u8>) {
+        self.push(&leaf);
+    }
+
+    /// Adds every leaf from an iterable of bytes (a generator works), one at a time
+    fn update_many(&mut self, leaves: &Bound<'_, PyAny>) -> PyResult<()> {
+        for leaf in leaves.try_iter()? {
+            self.push(&leaf?.extract::<Vec<u8>>()?);
+        }
+        Ok(())
+    }
+
+    /// Adds each record of a file of u32 big-endian length-prefixed records, without holding
+    /// the GIL. Returns the number of records read
+    fn update_file(&mut self, py: Python<'_>, path: std::path::PathBuf) -> PyResult<usize> {
+        py.allow_threads(|| {
+            let mut file = BufReader::new(File::open(&path)?);
+            let mut record = Vec::new();
+            let mut count = 0;
+            loop {
+                let mut prefix = [0u8; 4];
+                match file.read_exact(&mut prefix) {
+                    Ok(()) => {}
+                    Err(e) if e.kind() == ErrorKind::UnexpectedEof => break,
+                    Err(e) => return Err(e.into()),
+                }
+                let len = u32::from_be_bytes(prefix) as u64;
+                record.clear();
+                // Read through take() so a corrupt length can't force a huge allocation
+                (&mut file).take(len).read_to_end(&mut record)?;
+                if record.len() as u64 != len {
+                    return Err(pyo3::exceptions::PyValueError::new_err(format!(
+                        "Record {} is truncated ({} of {} bytes)",
+                        count,
+                        record.len(),
+                        len
+                    )));
+                }
+                self.push(&record);
+                count += 1;
+            }
+            Ok(count)
+        })
+    }
+
+    /// Root over the leaves so far, as create_merkle_root would give it; b'' when empty
+    fn root(&self) -> Vec<u8> {
+        self.root_hash()
+            .map(|root| self.version.encode_root(&root))
+            .unwrap_or_default()
+    }
+
+    /// root() with the leaf count, as a MerkleRoot
+    fn tree_root(&self) -> PyResult<MerkleRoot> {
+        let root = self.root_hash().ok_or_else(|| {
+            pyo3::exceptions::PyValueError::new_err("A Merkle root covers at least one leaf")
+        })?;
+        Ok(MerkleRoot::from_hash(self.version, self.len, root))
+    }
+
+    fn __len__(&self) -> usize {
+        self.len
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "RootBuilder(version={}, hash_algorithm={}, len={}, root={})",
+            self.version.number(),
+            self.version.hash_algorithm().name(),
+            self.len,
+            hex_root(self.root_hash())
+        )
+    }
+}
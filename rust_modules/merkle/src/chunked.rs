@@ -0,0 +1,219 @@
+use std::fs::File;
+use std::io::Read;
+use std::path::PathBuf;
+
+use pyo3::prelude::*;
+
+use crate::hash_leaves;
+use crate::serialize::{MerkleProof, MerkleRoot};
+use crate::store::read_exact_at;
+use crate::tree::{hex_root, MerkleTree, StoredNodes};
+
+// Content-addressed files: a file is cut into fixed-size chunks (the last one may be
+// shorter) and each chunk is a leaf, so the root commits to the whole file and any chunk or
+// run of chunks can be served with a proof against it. A reader holding only the root can then
+// fetch a byte range of a large blob from an untrusted store and check each chunk as it
+// arrives, as in BitTorrent or BLAKE3's verified streaming. The reader must use the same
+// chunk size; the root's tree size gives the chunk count.
+//
+// Chunks are read in batches of BATCH_CHUNKS and hashed on all cores with the GIL released.
+// Only leaf hashes stay in memory. read_chunk / read_range re-read the file and refuse chunks
+// that no longer match the hashes taken when the tree was built.
+const DEFAULT_CHUNK_SIZE: usize = 1 << 16;
+const BATCH_CHUNKS: usize = 256;
+
+/// Merkle tree over a file's chunks, from merkle_file. Serves proofs for single chunks and
+/// byte ranges, and reads chunks back checked against the tree
+#[pyclass(module = "reliquary_merkle")]
+pub struct ChunkedFile {
+    path: PathBuf,
+    chunk_size: usize,
+    file_size: u64,
+    tree: MerkleTree,
+}
+
+impl ChunkedFile {
+    /// Indices of the chunks overlapping `length` bytes at `offset`.
+    fn chunks_in(&self, offset: u64, length: u64) -> PyResult<Vec<usize>> {
+        let end = offset
+            .checked_add(length)
+            .filter(|&end| end <= self.file_size);
+        let Some(end) = end.filter(|_| length > 0) else {
+            return Err(pyo3::exceptions::PyValueError::new_err(format!(
+                "Byte range must be non-empty and within the file ({} bytes)",
+                self.file_size
+            )));
+        };
+        let chunk_size = self.chunk_size as u64;
+        Ok(((offset / chunk_size) as usize..end.div_ceil(chunk_size) as usize).collect())
+    }
+
+    /// Reads chunk `index` from the file and checks it against its leaf hash.
+    fn read_verified(&self, file: &File, index: usize) -> PyResult<Vec<u8>> {
+        let start = index as u64 * self.chunk_size as u64;
+        let len = (self.file_size - start).min(self.chunk_size as u64) as usize;
+        let mut chunk = vec![0u8; len];
+        read_exact_at(file, &mut chunk, start)?;
+        if self.tree.scheme().hash_leaf(&chunk) != self.tree.stored(0, index)? {
+            return Err(pyo3::exceptions::PyValueError::new_err(format!(
+                "Chunk {} no longer matches the file",
+                index
+            )));
+        }
+        Ok(chunk)
+    }
+}
+
+#[pymethods]
+impl ChunkedFile {
+    /// File the tree was built from
+    #[getter]
+    fn path(&self) -> PathBuf {
+        self.path.clone()
+    }
+
+    #[getter]
+    fn chunk_size(&self) -> usize {
+        self.chunk_size
+    }
+
+    #[getter]
+    fn file_size(&self) -> u64 {
+        self.file_size
+    }
+
+    /// Number of chunks (leaves); 0 for an empty file
+    #[getter]
+    fn chunk_count(&self) -> usize {
+        self.tree.size()
+    }
+
+    /// Root over the chunks, as create_merkle_root over them would give it; b'' for an empty
+    /// file
+    fn root(&self) -> PyResult<Vec<u8>> {
+        self.tree.encoded_root_at(self.tree.size())
+    }
+
+    /// root() with the chunk count, as a MerkleRoot
+    fn tree_root(&self) -> PyResult<MerkleRoot> {
+        self.tree.tree_root_at(None)
+    }
+
+    /// (start, end) byte offsets of chunk `index`
+    fn chunk_range(&self, index: usize) -> PyResult<(u64, u64)> {
+        if index >= self.tree.size() {
+            return Err(pyo3::exceptions::PyIndexError::new_err(
+                "Chunk index out of range",
+            ));
+        }
+        let start = index as u64 * self.chunk_size as u64;
+        Ok((start, (start + self.chunk_size as u64).min(self.file_size)))
+    }
+
+    /// Inclusion proof for chunk `index`; check with MerkleProof.verify_inclusion
+    fn prove_chunk(&self, index: usize) -> PyResult<MerkleProof> {
+        self.tree.inclusion_proof(index)
+    }
+
+    /// Multiproof for the chunks overlapping `length` bytes at `offset`; its indices name the
+    /// chunks, and MerkleProof.verify_multi checks them together
+    fn prove_range(&self, offset: u64, length: u64) -> PyResult<MerkleProof> {
+        self.tree
+            .multi_proof_object(self.chunks_in(offset, length)?)
+    }
+
+    /// Chunk `index` read from the file, with its proof. Raises ValueError if the file has
+    /// changed under the chunk since the tree was built
+    fn read_chunk(&self, py: Python<'_>, index: usize) -> PyResult<(Vec<u8>, MerkleProof)> {
+        let proof = self.tree.inclusion_proof(index)?;
+        let chunk = py.allow_threads(|| self.read_verified(&File::open(&self.path)?, index))?;
+        Ok((chunk, proof))
+    }
+
+    /// The whole chunks overlapping `length` bytes at `offset`, read from the file and
+    /// checked, with prove_range's proof for them
+    fn read_range(
+        &self,
+        py: Python<'_>,
+        offset: u64,
+        length: u64,
+    ) -> PyResult<(Vec<Vec<u8>>, MerkleProof)> {
+        let indices = self.chunks_in(offset, length)?;
+        let proof = self.tree.multi_proof_object(indices.clone())?;
+        let chunks = py.allow_threads(|| {
+            let file = File::open(&self.path)?;
+            indices
+                .into_iter()
+                .map(|index| self.read_verified(&file, index))
+                .collect::<PyResult<_>>()
+        })?;
+        Ok((chunks, proof))
+    }
+
+    fn __len__(&self) -> usize {
+        self.tree.size()
+    }
+
+    fn __repr__(&self) -> PyResult<String> {
+        let root = match self.tree.size() {
+            0 => None,
+            size => Some(self.tree.root_hash_at(size)?),
+        };
+        Ok(format!(
+            "ChunkedFile(path={:?}, chunk_size={}, chunks={}, root={})",
+            self.path,
+            self.chunk_size,
+            self.tree.size(),
+            hex_root(root)
+        ))
+    }
+}
+
+/// Splits the file at `path` into `chunk_size`-byte chunks and builds a Merkle tree over
+/// them, hashing on all cores with the GIL released. Returns a ChunkedFile with the root and
+/// per-chunk and byte-range proofs
+#[pyfunction]
+#[pyo3(signature = (path, chunk_size = DEFAULT_CHUNK_SIZE, version = 1, hash_algorithm = "sha256"))]
+pub fn merkle_file(
+    py: Python<'_>,
+    path: PathBuf,
+    chunk_size: usize,
+    version: u8,
+    hash_algorithm: &str,
+) -> PyResult<ChunkedFile> {
+    if chunk_size == 0 {
+        return Err(pyo3::exceptions::PyValueError::new_err(
+            "chunk_size must be at least 1",
+        ));
+    }
+    let mut tree = MerkleTree::new(None, version, hash_algorithm)?;
+    let file_size = py.allow_threads(|| -> PyResult<u64> {
+        let mut file = File::open(&path)?;
+        let mut file_size = 0;
+        loop {
+            let mut batch = Vec::with_capacity(BATCH_CHUNKS);
+            while batch.len() < BATCH_CHUNKS {
+                let mut chunk = Vec::new();
+                (&mut file)
+                    .take(chunk_size as u64)
+                    .read_to_end(&mut chunk)?;
+                if chunk.is_empty() {
+                    break;
+                }
+                file_size += chunk.len() as u64;
+                batch.push(chunk);
+            }
+            let last = batch.len() < BATCH_CHUNKS;
+            tree.push_all(hash_leaves(tree.scheme(), &batch));
+            if last {
+                return Ok(file_size);
+            }
+        }
+    })?;
+    Ok(ChunkedFile {
+        path,
+        chunk_size,
+        file_size,
+        tree,
+    })
+}
@@ -0,0 +1,103 @@
+use pyo3::prelude::*;
+
+use crate::scheme::{Hash, TreeVersion};
+use crate::tree::{height, MerkleTree, StoredNodes};
+
+// Consistency proofs (as in Certificate Transparency) that the tree over the first old_size
+// leaves is a prefix of the tree over new_size leaves. Trees here are built level by level,
+// with the last node of an odd level duplicated (v1) or carried up (v2), so the proof follows
+// that shape: from the old tree's rightmost complete subtree up to the new root, listing each
+// left sibling (part of both trees) and each right sibling (new leaves only). The verifier
+// folds the same nodes twice, once closing the right edge where the old tree ended, and must
+// arrive at both roots.
+const HASH_BYTES: usize = 32;
+
+/// Consistency proof between the trees over `data_blocks[:old_size]` and all of
+/// `data_blocks`
+#[pyfunction]
+#[pyo3(signature = (data_blocks, old_size, version = 1, hash_algorithm = "sha256"))]
+pub fn create_consistency_proof(
+    data_blocks: Vec<Vec<u8>>,
+    old_size: usize,
+    version: u8,
+    hash_algorithm: &str,
+) -> PyResult<Vec<Vec<u8>>> {
+    let new_size = data_blocks.len();
+    let tree = MerkleTree::new(Some(data_blocks), version, hash_algorithm)?;
+    Ok(tree
+        .consistency(old_size, new_size)?
+        .into_iter()
+        .map(|node| node.to_vec())
+        .collect())
+}
+
+/// Verifies that `old_root` (a tree of `old_size` leaves) is a prefix of `new_root` (`new_size`
+/// leaves). Both roots must have the same version; sizes out of order fail the proof
+#[pyfunction]
+pub fn verify_consistency_proof(
+    old_size: usize,
+    new_size: usize,
+    old_root: Vec<u8>,
+    new_root: Vec<u8>,
+    proof: Vec<Vec<u8>>,
+) -> bool {
+    let (Some((version, old_hash)), Some((new_version, new_hash))) = (
+        TreeVersion::of_root(&old_root),
+        TreeVersion::of_root(&new_root),
+    ) else {
+        return false;
+    };
+    if version != new_version || old_size == 0 || old_size > new_size {
+        return false;
+    }
+    if old_size == new_size {
+        return proof.is_empty() && old_hash == new_hash;
+    }
+    if proof.iter().any(|node| node.len() != HASH_BYTES) {
+        return false;
+    }
+    let mut nodes = proof.iter().map(|node| {
+        let mut hash: Hash = [0; HASH_BYTES];
+        hash.copy_from_slice(node);
+        hash
+    });
+
+    let mut level = old_size.trailing_zeros() as usize;
+    let mut index = (old_size >> level) - 1;
+    let start = if index == 0 {
+        Hash::try_from(old_hash).ok()
+    } else {
+        nodes.next()
+    };
+    let Some(start) = start else {
+        return false;
+    };
+    let (mut old, mut new) = (start, start);
+    // Levels of the old tree above `level` still to fold; the old root is reached at zero
+    let mut old_levels = height(old_size) - level;
+    while level < height(new_size) {
+        if !index.is_multiple_of(2) {
+            let Some(left) = nodes.next() else {
+                return false;
+            };
+            old = version.hash_pair(&left, &old);
+            new = version.hash_pair(&left, &new);
+        } else {
+            if old_levels > 0 {
+                old = version.hash_unpaired(&old);
+            }
+            new = if (index + 1) << level < new_size {
+                let Some(right) = nodes.next() else {
+                    return false;
+                };
+                version.hash_pair(&new, &right)
+            } else {
+                version.hash_unpaired(&new)
+            };
+        }
+        old_levels = old_levels.saturating_sub(1);
+        index /= 2;
+        level += 1;
+    }
+    nodes.next().is_none() && old[..] == old_hash[..] && new[..] == new_hash[..]
+}
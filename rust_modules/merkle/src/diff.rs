@@ -0,0 +1,192 @@
+use pyo3::prelude::*;
+
+use crate::scheme::Hash;
+use crate::serialize::MerkleProof;
+use crate::snapshot::MerkleSnapshot;
+use crate::sparse::SparseProof;
+use crate::store::PersistentMerkleTree;
+use crate::tree::{height, MerkleTree, StoredNodes};
+
+// Diffs between two versions of a tree, for replica sync. Equal subtree hashes mean equal
+// leaves below, so the walk descends from the roots only into subtrees that differ and costs
+// O(k log n) node reads for k changes rather than a pass over every leaf. For indexed trees
+// the walk covers the leaves both trees have; leaves past the shorter tree are added (or
+// removed, when the new tree is the shorter one). Sparse trees have a canonical layout, so
+// two versions can be walked side by side too (see SparseMerkleTree.diff).
+//
+// Every change carries proofs against the roots it is about, so a replica can check a change
+// it was sent without trusting the sender's tree.
+
+fn change_kind(in_old: bool, in_new: bool) -> &'static str {
+    match (in_old, in_new) {
+        (false, _) => "added",
+        (_, false) => "removed",
+        _ => "changed",
+    }
+}
+
+/// A leaf that differs between two indexed trees. `kind` is "changed", "added" or "removed";
+/// the old_* fields are None for added leaves and the new_* fields for removed ones. Proofs
+/// are inclusion proofs against each tree's full root
+#[pyclass(frozen, module = "reliquary_merkle")]
+pub struct LeafChange {
+    #[pyo3(get)]
+    kind: &'static str,
+    #[pyo3(get)]
+    index: usize,
+    #[pyo3(get)]
+    old_leaf_hash: Option<Vec<u8>>,
+    #[pyo3(get)]
+    new_leaf_hash: Option<Vec<u8>>,
+    #[pyo3(get)]
+    old_proof: Option<MerkleProof>,
+    #[pyo3(get)]
+    new_proof: Option<MerkleProof>,
+}
+
+#[pymethods]
+impl LeafChange {
+    fn __repr__(&self) -> String {
+        format!("LeafChange(kind={}, index={})", self.kind, self.index)
+    }
+}
+
+/// A key that differs between two sparse trees. `kind` is "changed", "added" or "removed";
+/// the value missing on one side is None. Each proof is SparseMerkleTree.prove on that side:
+/// of inclusion where the key is present, of absence where it isn't
+#[pyclass(frozen, module = "reliquary_merkle")]
+pub struct KeyChange {
+    #[pyo3(get)]
+    kind: &'static str,
+    #[pyo3(get)]
+    key: Vec<u8>,
+    #[pyo3(get)]
+    old_value: Option<Vec<u8>>,
+    #[pyo3(get)]
+    new_value: Option<Vec<u8>>,
+    #[pyo3(get)]
+    old_proof: SparseProof,
+    #[pyo3(get)]
+    new_proof: SparseProof,
+}
+
+impl KeyChange {
+    pub(crate) fn new(
+        key: &Hash,
+        old: (Option<Vec<u8>>, SparseProof),
+        new: (Option<Vec<u8>>, SparseProof),
+    ) -> Self {
+        KeyChange {
+            kind: change_kind(old.0.is_some(), new.0.is_some()),
+            key: key.to_vec(),
+            old_value: old.0,
+            new_value: new.0,
+            old_proof: old.1,
+            new_proof: new.1,
+        }
+    }
+}
+
+#[pymethods]
+impl KeyChange {
+    fn __repr__(&self) -> String {
+        let key: String = self.key.iter().map(|b| format!("{:02x}", b)).collect();
+        format!("KeyChange(kind={}, key={})", self.kind, key)
+    }
+}
+
+type Nodes<'a> = &'a (dyn StoredNodes + Sync);
+
+/// Runs `f` on the stored nodes of any indexed tree class.
+fn with_nodes<R>(tree: &Bound<'_, PyAny>, f: impl FnOnce(Nodes<'_>) -> PyResult<R>) -> PyResult<R> {
+    if let Ok(tree) = tree.downcast::<MerkleTree>() {
+        return f(&*tree.try_borrow()?);
+    }
+    if let Ok(tree) = tree.downcast::<PersistentMerkleTree>() {
+        return f(&*tree.try_borrow()?);
+    }
+    if let Ok(tree) = tree.downcast::<MerkleSnapshot>() {
+        return f(&*tree.try_borrow()?);
+    }
+    Err(pyo3::exceptions::PyTypeError::new_err(
+        "Expected a MerkleTree, PersistentMerkleTree or MerkleSnapshot",
+    ))
+}
+
+/// Indices of the leaves under node `index` of `level` that differ, both trees cut to `size`.
+fn changed_leaves(
+    old: Nodes<'_>,
+    new: Nodes<'_>,
+    size: usize,
+    level: usize,
+    index: usize,
+    out: &mut Vec<usize>,
+) -> PyResult<()> {
+    if old.node_at(size, level, index)? == new.node_at(size, level, index)? {
+        return Ok(());
+    }
+    if level == 0 {
+        out.push(index);
+        return Ok(());
+    }
+    changed_leaves(old, new, size, level - 1, 2 * index, out)?;
+    if (2 * index + 1) << (level - 1) < size {
+        changed_leaves(old, new, size, level - 1, 2 * index + 1, out)?;
+    }
+    Ok(())
+}
+
+fn diff(old: Nodes<'_>, new: Nodes<'_>) -> PyResult<Vec<LeafChange>> {
+    if old.scheme() != new.scheme() {
+        return Err(pyo3::exceptions::PyValueError::new_err(
+            "Only trees with the same version and hash algorithm can be diffed",
+        ));
+    }
+    let common = old.size().min(new.size());
+    let mut changed = Vec::new();
+    if common > 0 {
+        changed_leaves(old, new, common, height(common), 0, &mut changed)?;
+    }
+    let side =
+        |tree: Nodes<'_>, index: usize| -> PyResult<(Option<Vec<u8>>, Option<MerkleProof>)> {
+            if index < tree.size() {
+                Ok((
+                    Some(tree.stored(0, index)?.to_vec()),
+                    Some(tree.inclusion_proof(index)?),
+                ))
+            } else {
+                Ok((None, None))
+            }
+        };
+    let indices = changed
+        .into_iter()
+        .chain(common..old.size().max(new.size()));
+    indices
+        .map(|index| {
+            let (old_leaf_hash, old_proof) = side(old, index)?;
+            let (new_leaf_hash, new_proof) = side(new, index)?;
+            Ok(LeafChange {
+                kind: change_kind(old_leaf_hash.is_some(), new_leaf_hash.is_some()),
+                index,
+                old_leaf_hash,
+                new_leaf_hash,
+                old_proof,
+                new_proof,
+            })
+        })
+        .collect()
+}
+
+/// Leaves that differ between two versions of an indexed tree (MerkleTree,
+/// PersistentMerkleTree or MerkleSnapshot, in any combination), in index order, each with
+/// proofs against both roots. Both trees must use the same version and hash algorithm
+#[pyfunction]
+pub fn diff_trees(
+    py: Python<'_>,
+    old: &Bound<'_, PyAny>,
+    new: &Bound<'_, PyAny>,
+) -> PyResult<Vec<LeafChange>> {
+    with_nodes(old, |old| {
+        with_nodes(new, |new| py.allow_threads(|| diff(old, new)))
+    })
+}
@@ -0,0 +1,498 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use ed25519_dalek::{Signature, VerifyingKey};
+use pqcrypto_falcon::{falcon1024, falcon512};
+use pqcrypto_traits::sign::{PublicKey, SignedMessage};
+use pyo3::prelude::*;
+
+use crate::scheme::{Hash, TreeVersion};
+use crate::serialize::MerkleProof;
+use crate::tree::hex_root;
+use crate::wire::Reader;
+
+// Gossip between transparency-log witnesses, to catch a log showing different trees to
+// different clients (a split view). The log signs every tree it shows, but can't link two
+// forks: no consistency proof leads from one to the other. Witnesses exchange GossipPackets,
+// each a signed tree head (core.crypto.tree_head's "RQTH" encoding) and optionally an earlier
+// head with the consistency proof between them, and feed the packets they receive into a
+// Witness for that log:
+// - two validly signed heads of the same size with different roots are a SplitView, which
+//   anyone holding the log's public key can check on its own;
+// - the heads a Witness can link to its latest head by consistency proofs are its view of
+//   the log; any other head is pending until a packet links it, and a log that can't produce
+//   the proof for a pending head has forked.
+//
+//   packet      "RQGP" | 1 | head u16-len | flag u8 | if flag 1: previous head u16-len
+//               | consistency proof u32-len (a serialized MerkleProof)
+//   split view  "RQSV" | 1 | first head u16-len | second head u16-len
+// Heads are verified natively (Ed25519 strict, or Falcon signed messages as sign_falcon
+// makes them), so gossip checks don't need the encryptor module.
+const PACKET_MAGIC: &[u8; 4] = b"RQGP";
+const SPLIT_VIEW_MAGIC: &[u8; 4] = b"RQSV";
+const FORMAT_VERSION: u8 = 1;
+const TREE_HEAD_MAGIC: &[u8; 4] = b"RQTH";
+const TREE_HEAD_FORMAT_VERSION: u8 = 1;
+const FALCON_512: u8 = 1;
+const FALCON_1024: u8 = 2;
+const ED25519: u8 = 3;
+
+/// A parsed signed tree head, kept with its encoding.
+#[derive(Clone, PartialEq, Eq)]
+struct TreeHead {
+    tree_size: usize,
+    timestamp: u64,
+    algorithm: u8,
+    version: TreeVersion,
+    root: Hash,
+    /// Length of the signed prefix of `bytes`.
+    signed_len: usize,
+    bytes: Vec<u8>,
+}
+
+impl TreeHead {
+    fn decode(data: &[u8]) -> Result<Self, String> {
+        let mut r = Reader::new(data);
+        r.header(
+            TREE_HEAD_MAGIC,
+            TREE_HEAD_FORMAT_VERSION,
+            "signed tree head",
+        )?;
+        let tree_size = r.size()?;
+        let timestamp = r.u64()?;
+        let algorithm = r.u8()?;
+        if !(FALCON_512..=ED25519).contains(&algorithm) {
+            return Err(format!(
+                "Unknown tree head signature algorithm id {}",
+                algorithm
+            ));
+        }
+        let (version, root) = TreeVersion::of_root(r.var8()?).ok_or("Not a Merkle root")?;
+        let root = root.try_into().expect("32-byte root hash");
+        let signed_len = r.position();
+        let signature_len = r.u16()? as usize;
+        r.bytes(signature_len)?;
+        r.finish("signed tree head")?;
+        if tree_size == 0 {
+            return Err("A tree head covers at least one leaf".to_string());
+        }
+        Ok(TreeHead {
+            tree_size,
+            timestamp,
+            algorithm,
+            version,
+            root,
+            signed_len,
+            bytes: data.to_vec(),
+        })
+    }
+
+    /// Serialized head bytes, or any object with to_bytes() (e.g. a SignedTreeHead).
+    fn extract(head: &Bound<'_, PyAny>) -> PyResult<Self> {
+        let bytes: Vec<u8> = match head.extract() {
+            Ok(bytes) => bytes,
+            Err(_) => head.call_method0("to_bytes")?.extract()?,
+        };
+        TreeHead::decode(&bytes).map_err(pyo3::exceptions::PyValueError::new_err)
+    }
+
+    fn same_tree(&self, other: &TreeHead) -> bool {
+        self.tree_size == other.tree_size
+            && self.version == other.version
+            && self.root == other.root
+    }
+
+    fn verify(&self, public_key: &[u8]) -> bool {
+        let message = &self.bytes[..self.signed_len];
+        let signature = &self.bytes[self.signed_len + 2..];
+        match self.algorithm {
+            ED25519 => {
+                let (Ok(key), Ok(signature)) = (
+                    <[u8; 32]>::try_from(public_key),
+                    Signature::from_slice(signature),
+                ) else {
+                    return false;
+                };
+                VerifyingKey::from_bytes(&key)
+                    .is_ok_and(|key| key.verify_strict(message, &signature).is_ok())
+            }
+            FALCON_512 => {
+                let (Ok(key), Ok(signed)) = (
+                    falcon512::PublicKey::from_bytes(public_key),
+                    falcon512::SignedMessage::from_bytes(signature),
+                ) else {
+                    return false;
+                };
+                falcon512::open(&signed, &key).is_ok_and(|opened| opened == message)
+            }
+            FALCON_1024 => {
+                let (Ok(key), Ok(signed)) = (
+                    falcon1024::PublicKey::from_bytes(public_key),
+                    falcon1024::SignedMessage::from_bytes(signature),
+                ) else {
+                    return false;
+                };
+                falcon1024::open(&signed, &key).is_ok_and(|opened| opened == message)
+            }
+            _ => false,
+        }
+    }
+}
+
+fn put_var16(out: &mut Vec<u8>, bytes: &[u8]) {
+    out.extend_from_slice(&(bytes.len() as u16).to_be_bytes());
+    out.extend_from_slice(bytes);
+}
+
+fn var16_head(r: &mut Reader<'_>) -> Result<TreeHead, String> {
+    let len = r.u16()? as usize;
+    TreeHead::decode(r.bytes(len)?)
+}
+
+/// A log's signed tree head as one witness passes it to another, optionally with an earlier
+/// head and the consistency proof from it
+#[pyclass(frozen, eq, module = "reliquary_merkle")]
+#[derive(Clone, PartialEq, Eq)]
+pub struct GossipPacket {
+    head: TreeHead,
+    previous: Option<(TreeHead, MerkleProof)>,
+}
+
+impl GossipPacket {
+    fn check(head: TreeHead, previous: Option<(TreeHead, MerkleProof)>) -> Result<Self, String> {
+        if let Some((old, proof)) = &previous {
+            if old.tree_size > head.tree_size || old.version != head.version {
+                return Err(
+                    "The previous head must be of the same tree scheme and no larger".to_string(),
+                );
+            }
+            if proof.old_size() != Some(old.tree_size) || proof.tree_size() != head.tree_size {
+                return Err(format!(
+                    "Expected a consistency proof from size {} to {}",
+                    old.tree_size, head.tree_size
+                ));
+            }
+        }
+        Ok(GossipPacket { head, previous })
+    }
+
+    fn decode(data: &[u8]) -> Result<Self, String> {
+        let mut r = Reader::new(data);
+        r.header(PACKET_MAGIC, FORMAT_VERSION, "gossip packet")?;
+        let head = var16_head(&mut r)?;
+        let previous = match r.u8()? {
+            0 => None,
+            1 => {
+                let old = var16_head(&mut r)?;
+                let len = r.u32()? as usize;
+                Some((old, MerkleProof::decode(r.bytes(len)?)?))
+            }
+            flag => return Err(format!("Unknown gossip packet flag {}", flag)),
+        };
+        r.finish("gossip packet")?;
+        GossipPacket::check(head, previous)
+    }
+
+    fn heads(&self) -> impl Iterator<Item = &TreeHead> {
+        self.previous.iter().map(|(old, _)| old).chain([&self.head])
+    }
+
+    fn verifies(&self, public_key: &[u8]) -> bool {
+        self.heads().all(|head| head.verify(public_key))
+            && self.previous.as_ref().is_none_or(|(old, proof)| {
+                old.timestamp <= self.head.timestamp
+                    && proof.verify_consistency(
+                        old.version.encode_root(&old.root),
+                        self.head.version.encode_root(&self.head.root),
+                    )
+            })
+    }
+}
+
+#[pymethods]
+impl GossipPacket {
+    /// Heads are serialized signed tree heads or SignedTreeHead objects; `consistency_proof`
+    /// (a consistency MerkleProof from the log) is required with `previous_head`
+    #[new]
+    #[pyo3(signature = (head, previous_head = None, consistency_proof = None))]
+    fn new(
+        head: &Bound<'_, PyAny>,
+        previous_head: Option<&Bound<'_, PyAny>>,
+        consistency_proof: Option<MerkleProof>,
+    ) -> PyResult<Self> {
+        let head = TreeHead::extract(head)?;
+        let previous = match (previous_head, consistency_proof) {
+            (None, None) => None,
+            (Some(old), Some(proof)) => Some((TreeHead::extract(old)?, proof)),
+            _ => {
+                return Err(pyo3::exceptions::PyValueError::new_err(
+                    "previous_head and consistency_proof go together",
+                ))
+            }
+        };
+        GossipPacket::check(head, previous).map_err(pyo3::exceptions::PyValueError::new_err)
+    }
+
+    /// The serialized signed tree head
+    #[getter]
+    fn head(&self) -> Vec<u8> {
+        self.head.bytes.clone()
+    }
+
+    #[getter]
+    fn previous_head(&self) -> Option<Vec<u8>> {
+        self.previous.as_ref().map(|(old, _)| old.bytes.clone())
+    }
+
+    #[getter]
+    fn consistency_proof(&self) -> Option<MerkleProof> {
+        self.previous.as_ref().map(|(_, proof)| proof.clone())
+    }
+
+    #[getter]
+    fn tree_size(&self) -> usize {
+        self.head.tree_size
+    }
+
+    /// Whether every head is signed with `public_key`, and the previous head (if any) is no
+    /// newer than the head and consistent with it
+    fn verify(&self, py: Python<'_>, public_key: Vec<u8>) -> bool {
+        py.allow_threads(|| self.verifies(&public_key))
+    }
+
+    fn to_bytes(&self) -> Vec<u8> {
+        let mut out = PACKET_MAGIC.to_vec();
+        out.push(FORMAT_VERSION);
+        put_var16(&mut out, &self.head.bytes);
+        match &self.previous {
+            None => out.push(0),
+            Some((old, proof)) => {
+                out.push(1);
+                put_var16(&mut out, &old.bytes);
+                let proof = proof.to_bytes();
+                out.extend_from_slice(&(proof.len() as u32).to_be_bytes());
+                out.extend_from_slice(&proof);
+            }
+        }
+        out
+    }
+
+    #[staticmethod]
+    fn from_bytes(data: Vec<u8>) -> PyResult<Self> {
+        GossipPacket::decode(&data).map_err(pyo3::exceptions::PyValueError::new_err)
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "GossipPacket(tree_size={}, root={}, previous_size={:?})",
+            self.head.tree_size,
+            hex_root(Some(self.head.root)),
+            self.previous.as_ref().map(|(old, _)| old.tree_size)
+        )
+    }
+}
+
+/// Two signed tree heads of the same size with different roots: proof that the log forked
+#[pyclass(frozen, eq, module = "reliquary_merkle")]
+#[derive(Clone, PartialEq, Eq)]
+pub struct SplitView {
+    first: TreeHead,
+    second: TreeHead,
+}
+
+impl SplitView {
+    fn check(first: TreeHead, second: TreeHead) -> Result<Self, String> {
+        if first.tree_size != second.tree_size || first.same_tree(&second) {
+            return Err(
+                "A split view needs two heads of one size with different roots".to_string(),
+            );
+        }
+        Ok(SplitView { first, second })
+    }
+}
+
+#[pymethods]
+impl SplitView {
+    #[new]
+    fn new(first: &Bound<'_, PyAny>, second: &Bound<'_, PyAny>) -> PyResult<Self> {
+        SplitView::check(TreeHead::extract(first)?, TreeHead::extract(second)?)
+            .map_err(pyo3::exceptions::PyValueError::new_err)
+    }
+
+    #[getter]
+    fn first(&self) -> Vec<u8> {
+        self.first.bytes.clone()
+    }
+
+    #[getter]
+    fn second(&self) -> Vec<u8> {
+        self.second.bytes.clone()
+    }
+
+    #[getter]
+    fn tree_size(&self) -> usize {
+        self.first.tree_size
+    }
+
+    /// Whether both heads are signed with `public_key`, i.e. the log itself forked
+    fn verify(&self, py: Python<'_>, public_key: Vec<u8>) -> bool {
+        py.allow_threads(|| self.first.verify(&public_key) && self.second.verify(&public_key))
+    }
+
+    fn to_bytes(&self) -> Vec<u8> {
+        let mut out = SPLIT_VIEW_MAGIC.to_vec();
+        out.push(FORMAT_VERSION);
+        put_var16(&mut out, &self.first.bytes);
+        put_var16(&mut out, &self.second.bytes);
+        out
+    }
+
+    #[staticmethod]
+    fn from_bytes(data: Vec<u8>) -> PyResult<Self> {
+        let decode = || -> Result<Self, String> {
+            let mut r = Reader::new(&data);
+            r.header(SPLIT_VIEW_MAGIC, FORMAT_VERSION, "split view")?;
+            let (first, second) = (var16_head(&mut r)?, var16_head(&mut r)?);
+            r.finish("split view")?;
+            SplitView::check(first, second)
+        };
+        decode().map_err(pyo3::exceptions::PyValueError::new_err)
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "SplitView(tree_size={}, roots={} / {})",
+            self.first.tree_size,
+            hex_root(Some(self.first.root)),
+            hex_root(Some(self.second.root))
+        )
+    }
+}
+
+/// One witness's view of one log: the heads it has verified, which of them it has linked by
+/// consistency proofs, and any split views among them
+#[pyclass(module = "reliquary_merkle")]
+pub struct Witness {
+    public_key: Vec<u8>,
+    /// First head seen at each size.
+    seen: BTreeMap<usize, TreeHead>,
+    /// Sizes of the seen heads proven to be prefixes of the latest one.
+    linked: BTreeSet<usize>,
+    /// The packet that brought in the latest linked head.
+    latest: Option<GossipPacket>,
+    split_views: Vec<SplitView>,
+}
+
+impl Witness {
+    fn is_linked(&self, head: &TreeHead) -> bool {
+        self.linked.contains(&head.tree_size) && self.seen[&head.tree_size].same_tree(head)
+    }
+
+    /// Records `head`, returning a split view if another head of its size has another root.
+    fn record(&mut self, head: &TreeHead) -> Option<SplitView> {
+        match self.seen.get(&head.tree_size) {
+            None => {
+                self.seen.insert(head.tree_size, head.clone());
+                None
+            }
+            Some(known) if known.same_tree(head) => None,
+            Some(known) => {
+                let split = SplitView::check(known.clone(), head.clone()).ok()?;
+                let new = !self
+                    .split_views
+                    .iter()
+                    .any(|s| s.second.same_tree(&split.second));
+                new.then(|| {
+                    self.split_views.push(split.clone());
+                    split
+                })
+            }
+        }
+    }
+}
+
+#[pymethods]
+impl Witness {
+    /// Witness for the log whose tree heads verify under `public_key`
+    #[new]
+    fn new(public_key: Vec<u8>) -> Self {
+        Witness {
+            public_key,
+            seen: BTreeMap::new(),
+            linked: BTreeSet::new(),
+            latest: None,
+            split_views: Vec::new(),
+        }
+    }
+
+    /// Checks `packet` and adds its heads to the view. The first packet is trusted as the
+    /// starting point; after that a packet links in if its head is already linked (its
+    /// previous head then is too) or its previous head is the latest one (its head becomes
+    /// the latest). Returns the SplitView the packet exposes, if any. Raises ValueError if
+    /// the packet does not verify against the log's key
+    fn observe(&mut self, py: Python<'_>, packet: &GossipPacket) -> PyResult<Option<SplitView>> {
+        if !py.allow_threads(|| packet.verifies(&self.public_key)) {
+            return Err(pyo3::exceptions::PyValueError::new_err(
+                "Gossip packet does not verify against the log's key",
+            ));
+        }
+        let mut split = None;
+        for head in packet.heads() {
+            split = split.or(self.record(head));
+        }
+        let previous = packet.previous.as_ref().map(|(old, _)| old);
+        let latest = self.latest.as_ref().map(|latest| &latest.head);
+        let extends_latest = self.seen[&packet.head.tree_size].same_tree(&packet.head)
+            && match (latest, previous) {
+                (None, _) => true,
+                (Some(latest), Some(old)) => latest.same_tree(old),
+                _ => false,
+            };
+        if extends_latest {
+            self.latest = Some(packet.clone());
+        }
+        if extends_latest || self.is_linked(&packet.head) {
+            for head in packet.heads() {
+                if self.seen[&head.tree_size].same_tree(head) {
+                    self.linked.insert(head.tree_size);
+                }
+            }
+        }
+        Ok(split)
+    }
+
+    /// The latest linked head, serialized, or None before the first packet
+    #[getter]
+    fn latest(&self) -> Option<Vec<u8>> {
+        self.latest.as_ref().map(|packet| packet.head.bytes.clone())
+    }
+
+    /// The packet to pass on to other witnesses: the latest head, with the previous head and
+    /// proof it was linked by
+    fn gossip(&self) -> Option<GossipPacket> {
+        self.latest.clone()
+    }
+
+    /// Verified heads not yet linked to the latest one, by size. Resolve each by asking the
+    /// log for a consistency proof between it and the latest head and observing the packet
+    fn pending(&self) -> Vec<Vec<u8>> {
+        self.seen
+            .iter()
+            .filter(|(size, _)| !self.linked.contains(size))
+            .map(|(_, head)| head.bytes.clone())
+            .collect()
+    }
+
+    /// Every split view found so far
+    fn split_views(&self) -> Vec<SplitView> {
+        self.split_views.clone()
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "Witness(latest_size={:?}, pending={}, split_views={})",
+            self.latest.as_ref().map(|packet| packet.head.tree_size),
+            self.seen.len() - self.linked.len(),
+            self.split_views.len()
+        )
+    }
+}
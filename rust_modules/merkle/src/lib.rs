@@ -1,55 +1,120 @@
 use pyo3::prelude::*;
 use pyo3::Bound; // Import Bound for the updated signature
+use rayon::prelude::*;
+use scheme::{Hash, TreeVersion};
 use sha2::{Digest, Sha256};
 
+mod audit;
+mod authmap;
+mod builder;
+mod chunked;
+mod consistency;
+mod diff;
+mod gossip;
+mod multiproof;
+mod positional;
+mod scheme;
+mod serialize;
+mod sizes;
+mod snapshot;
+mod sparse;
+mod store;
+mod tree;
+mod verkle;
+mod wire;
+
 /// A Python module for Reliquary's Merkle tree operations.
-#[pymodule]
+#[pymodule(gil_used = false)]
 fn reliquary_merkle(_py: Python<'_>, m: &Bound<'_, PyModule>) -> PyResult<()> {
     // Fixed: Changed signature for _py and m
     m.add_function(wrap_pyfunction!(create_merkle_root, m)?)?;
     m.add_function(wrap_pyfunction!(verify_merkle_proof, m)?)?;
+    m.add_function(wrap_pyfunction!(positional::create_positional_proof, m)?)?;
+    m.add_function(wrap_pyfunction!(positional::verify_positional_proof, m)?)?;
+    m.add_function(wrap_pyfunction!(consistency::create_consistency_proof, m)?)?;
+    m.add_function(wrap_pyfunction!(consistency::verify_consistency_proof, m)?)?;
+    m.add_function(wrap_pyfunction!(multiproof::create_multi_proof, m)?)?;
+    m.add_function(wrap_pyfunction!(multiproof::verify_multi_proof, m)?)?;
+    m.add_function(wrap_pyfunction!(scheme::merkle_root_version, m)?)?;
+    m.add_function(wrap_pyfunction!(scheme::merkle_root_hash_algorithm, m)?)?;
+    m.add_function(wrap_pyfunction!(sizes::proof_size, m)?)?;
+    m.add_function(wrap_pyfunction!(sparse::verify_sparse_proof, m)?)?;
+    m.add_function(wrap_pyfunction!(authmap::verify_map_proof, m)?)?;
+    m.add_function(wrap_pyfunction!(diff::diff_trees, m)?)?;
+    m.add_function(wrap_pyfunction!(chunked::merkle_file, m)?)?;
+    m.add_function(wrap_pyfunction!(verkle::verify_verkle_proof, m)?)?;
+    m.add_function(wrap_pyfunction!(audit::verify_audit_chain, m)?)?;
+    m.add_class::<tree::MerkleTree>()?;
+    m.add_class::<store::PersistentMerkleTree>()?;
+    m.add_class::<builder::RootBuilder>()?;
+    m.add_class::<snapshot::MerkleSnapshot>()?;
+    m.add_class::<chunked::ChunkedFile>()?;
+    m.add_class::<sparse::SparseMerkleTree>()?;
+    m.add_class::<authmap::AuthenticatedMap>()?;
+    m.add_class::<verkle::VerkleTree>()?;
+    m.add_class::<verkle::KzgSetup>()?;
+    m.add_class::<audit::AuditLog>()?;
+    m.add_class::<audit::AuditEntry>()?;
+    m.add_class::<audit::AuditCheckpoint>()?;
+    m.add_class::<gossip::GossipPacket>()?;
+    m.add_class::<gossip::SplitView>()?;
+    m.add_class::<gossip::Witness>()?;
+    m.add_class::<serialize::MerkleRoot>()?;
+    m.add_class::<serialize::MerkleProof>()?;
+    m.add_class::<diff::LeafChange>()?;
+    m.add_class::<diff::KeyChange>()?;
     Ok(())
 }
 
-/// Creates a Merkle root from a list of data blocks.
+/// Leaves or pairs per rayon task; below this many a level is hashed on the calling thread.
+const PARALLEL_CHUNK: usize = 4096;
+
+/// Leaf hashes of `data_blocks`, in order, spread over the rayon pool for large inputs.
+pub(crate) fn hash_leaves(version: TreeVersion, data_blocks: &[Vec<u8>]) -> Vec<Hash> {
+    data_blocks
+        .par_iter()
+        .with_min_len(PARALLEL_CHUNK)
+        .map(|block| version.hash_leaf(block))
+        .collect()
+}
+
+/// Creates a Merkle root from a list of data blocks. Version 2 builds the RFC 6962 tree (leaf
+/// / node prefixes, no duplicated nodes) and returns the root with its 0x02 version byte; it
+/// can also hash with "sha3-256", "blake3" or "shake256", which the root then records.
+/// Hashing runs on all cores with the GIL released.
 #[pyfunction]
-fn create_merkle_root(data_blocks: Vec<Vec<u8>>) -> PyResult<Vec<u8>> {
+#[pyo3(signature = (data_blocks, version = 1, hash_algorithm = "sha256"))]
+fn create_merkle_root(
+    py: Python<'_>,
+    data_blocks: Vec<Vec<u8>>,
+    version: u8,
+    hash_algorithm: &str,
+) -> PyResult<Vec<u8>> {
+    let version = TreeVersion::from_arg(version, hash_algorithm)?;
     if data_blocks.is_empty() {
         return Ok(vec![]);
     }
 
-    let mut hashes: Vec<Vec<u8>> = data_blocks
-        .into_iter()
-        .map(|block| {
-            let mut hasher = Sha256::new();
-            hasher.update(&block);
-            hasher.finalize().to_vec()
-        })
-        .collect();
-
-    while hashes.len() > 1 {
-        let mut next_level_hashes = Vec::new();
-        let mut i = 0;
-        while i < hashes.len() {
-            let left = &hashes[i];
-            let right = if i + 1 < hashes.len() {
-                &hashes[i + 1]
-            } else {
-                left // Duplicate the last hash if odd number of leaves
-            };
-
-            let mut hasher = Sha256::new();
-            hasher.update(left);
-            hasher.update(right);
-            next_level_hashes.push(hasher.finalize().to_vec());
-            i += 2;
+    let root = py.allow_threads(|| {
+        let mut hashes = hash_leaves(version, &data_blocks);
+        while hashes.len() > 1 {
+            hashes = hashes
+                .par_chunks(2)
+                .with_min_len(PARALLEL_CHUNK)
+                .map(|pair| match pair.get(1) {
+                    Some(right) => version.hash_pair(&pair[0], right),
+                    // Last node of an odd level: duplicated for v1, carried up for v2
+                    None => version.hash_unpaired(&pair[0]),
+                })
+                .collect();
         }
-        hashes = next_level_hashes;
-    }
-    Ok(hashes[0].clone())
+        hashes[0]
+    });
+    Ok(version.encode_root(&root))
 }
 
-/// Verifies a Merkle proof for a given data block and root.
+/// Verifies a Merkle proof for a given data block and root. v1 trees only; use
+/// verify_positional_proof for v2.
 #[pyfunction]
 fn verify_merkle_proof(data_block: Vec<u8>, proof: Vec<Vec<u8>>, root: Vec<u8>) -> PyResult<bool> {
     let mut current_hash: Vec<u8> = {
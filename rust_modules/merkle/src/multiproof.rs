@@ -0,0 +1,95 @@
+use pyo3::prelude::*;
+
+use crate::scheme::{Hash, TreeVersion};
+use crate::tree::{MerkleTree, StoredNodes};
+
+// Multiproofs: one proof for several leaves of the same tree. Paths from nearby leaves share
+// most of their nodes, so instead of one sibling list per leaf the proof carries each node the
+// leaves don't determine exactly once, level by level from the leaves up and left to right
+// within a level. Which nodes those are depends on where odd levels leave their last node
+// unpaired, so the proof is (tree_size, nodes) and the verifier replays the same walk from the
+// size and the leaf indices.
+const HASH_BYTES: usize = 32;
+
+/// (tree_size, nodes) as returned by get_multi_proof.
+type MultiProof = (usize, Vec<Vec<u8>>);
+
+/// Folds `known` nodes (ascending, distinct indices) of the leaf level of a tree of `size`
+/// leaves up to its root. `sibling(level, index)` supplies each node the known ones don't
+/// determine, in proof order; returning None abandons the walk.
+pub(crate) fn fold(
+    version: TreeVersion,
+    size: usize,
+    mut known: Vec<(usize, Hash)>,
+    mut sibling: impl FnMut(usize, usize) -> Option<Hash>,
+) -> Option<Hash> {
+    let mut level = 0;
+    let mut count = size;
+    while count > 1 {
+        let mut next = Vec::with_capacity(known.len());
+        let mut nodes = known.into_iter().peekable();
+        while let Some((index, node)) = nodes.next() {
+            let parent = if !index.is_multiple_of(2) {
+                version.hash_pair(&sibling(level, index - 1)?, &node)
+            } else if let Some((_, right)) = nodes.next_if(|(next, _)| *next == index + 1) {
+                version.hash_pair(&node, &right)
+            } else if index + 1 < count {
+                version.hash_pair(&node, &sibling(level, index + 1)?)
+            } else {
+                version.hash_unpaired(&node)
+            };
+            next.push((index / 2, parent));
+        }
+        known = next;
+        count = count.div_ceil(2);
+        level += 1;
+    }
+    known.first().map(|(_, root)| *root)
+}
+
+/// Multiproof for `data_blocks` at `indices` in the tree create_merkle_root builds
+#[pyfunction]
+#[pyo3(signature = (data_blocks, indices, version = 1, hash_algorithm = "sha256"))]
+pub fn create_multi_proof(
+    data_blocks: Vec<Vec<u8>>,
+    indices: Vec<usize>,
+    version: u8,
+    hash_algorithm: &str,
+) -> PyResult<MultiProof> {
+    let tree = MerkleTree::new(Some(data_blocks), version, hash_algorithm)?;
+    let (size, nodes) = tree.multi_proof(indices)?;
+    Ok((size, nodes.iter().map(|node| node.to_vec()).collect()))
+}
+
+/// Verifies a multiproof for (index, data_block) pairs against a v1 or v2 root. Repeated
+/// indices, indices outside the tree and leftover proof nodes fail the proof
+#[pyfunction]
+pub fn verify_multi_proof(
+    leaves_with_indices: Vec<(usize, Vec<u8>)>,
+    proof: MultiProof,
+    root: Vec<u8>,
+) -> bool {
+    let Some((version, root_hash)) = TreeVersion::of_root(&root) else {
+        return false;
+    };
+    let (size, nodes) = proof;
+    let mut leaves: Vec<(usize, Hash)> = leaves_with_indices
+        .iter()
+        .map(|(index, block)| (*index, version.hash_leaf(block)))
+        .collect();
+    leaves.sort_by_key(|(index, _)| *index);
+    if leaves.is_empty()
+        || leaves.windows(2).any(|pair| pair[0].0 == pair[1].0)
+        || leaves.last().is_some_and(|(index, _)| *index >= size)
+        || nodes.iter().any(|node| node.len() != HASH_BYTES)
+    {
+        return false;
+    }
+    let mut nodes = nodes.iter().map(|node| {
+        let mut hash: Hash = [0; HASH_BYTES];
+        hash.copy_from_slice(node);
+        hash
+    });
+    let computed = fold(version, size, leaves, |_, _| nodes.next());
+    nodes.next().is_none() && computed.is_some_and(|computed| computed[..] == root_hash[..])
+}
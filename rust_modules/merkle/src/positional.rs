@@ -0,0 +1,67 @@
+use pyo3::prelude::*;
+
+use crate::scheme::TreeVersion;
+use crate::tree::{path_steps, MerkleTree, StoredNodes};
+
+// Positional inclusion proofs: each step is (sibling, is_right), with is_right true when the
+// sibling is the right-hand input of the parent hash. The verifier takes the leaf's index and
+// the tree size as well and works out from them which levels have a sibling and on which
+// side, so a proof only verifies for the position it was made for: a sibling can't be moved to
+// the other side, and a proof from a tree of another size (e.g. one with its last leaf
+// repeated) fails on its length or its directions. verify_merkle_proof still sorts each pair
+// and stays for proofs issued before this format. The root's version byte picks the leaf and
+// node hashing, so one verifier handles v1 and v2 trees.
+const HASH_BYTES: usize = 32;
+
+/// Positional proof for `data_blocks[index]` in the tree create_merkle_root builds
+#[pyfunction]
+#[pyo3(signature = (data_blocks, index, version = 1, hash_algorithm = "sha256"))]
+pub fn create_positional_proof(
+    data_blocks: Vec<Vec<u8>>,
+    index: usize,
+    version: u8,
+    hash_algorithm: &str,
+) -> PyResult<Vec<(Vec<u8>, bool)>> {
+    let tree = MerkleTree::new(Some(data_blocks), version, hash_algorithm)?;
+    Ok(tree
+        .path(index)?
+        .into_iter()
+        .map(|(sibling, is_right)| (sibling.to_vec(), is_right))
+        .collect())
+}
+
+/// Verifies a positional proof of (sibling, is_right) steps for the data block at `index` of a
+/// tree of `tree_size` leaves against a v1 or v2 root. An index outside the tree, a proof
+/// whose length or directions don't fit that position, and siblings that aren't 32-byte
+/// hashes fail the proof
+#[pyfunction]
+pub fn verify_positional_proof(
+    data_block: Vec<u8>,
+    proof: Vec<(Vec<u8>, bool)>,
+    root: Vec<u8>,
+    index: usize,
+    tree_size: usize,
+) -> bool {
+    let Some((version, root_hash)) = TreeVersion::of_root(&root) else {
+        return false;
+    };
+    if index >= tree_size {
+        return false;
+    }
+    let steps = path_steps(version, tree_size, index);
+    if steps.len() != proof.len() {
+        return false;
+    }
+    let mut current = version.hash_leaf(&data_block);
+    for (step, (sibling, is_right)) in steps.iter().zip(proof) {
+        if sibling.len() != HASH_BYTES || is_right != step.is_right {
+            return false;
+        }
+        current = if is_right {
+            version.hash_pair(&current, &sibling)
+        } else {
+            version.hash_pair(&sibling, &current)
+        };
+    }
+    current[..] == root_hash[..]
+}
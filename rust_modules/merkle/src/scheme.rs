@@ -0,0 +1,239 @@
+use pyo3::prelude::*;
+use sha2::{Digest, Sha256};
+use sha3::digest::{ExtendableOutput, Update, XofReader};
+use sha3::{Sha3_256, Shake256};
+
+pub(crate) type Hash = [u8; 32];
+
+// Tree formats. v1 is the original: SHA-256 of the raw leaf and of left | right, with a bare
+// 32-byte root, and odd levels pair their last node with itself. That makes [a, b, c] and
+// [a, b, c, c] share a root (CVE-2012-2459), so v2 follows RFC 6962 throughout: 0x00 before
+// leaf data and 0x01 before a pair, so no internal node can be presented as a leaf, and the
+// last node of an odd level is carried up unhashed. Building levels that way gives the same
+// tree as RFC 6962's split at the largest power of two below the leaf count, and a leaf on
+// the right edge skips the levels it is carried past, so v2 proofs can be shorter than v1's.
+// v2 roots carry a version byte, 0x02 | hash (33 bytes), so a root's own bytes say how to
+// verify it.
+//
+// v2 trees can also hash with SHA3-256, BLAKE3 or SHAKE256 (32-byte output). Their roots add
+// the algorithm's id after the version byte, 0x02 | id | hash (34 bytes); SHA-256 roots keep
+// the 33-byte form. v1 roots have nowhere to record an algorithm, so v1 is SHA-256 only.
+const LEAF_PREFIX: u8 = 0x00;
+const NODE_PREFIX: u8 = 0x01;
+const V2_ROOT_TAG: u8 = 0x02;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub(crate) enum HashAlgorithm {
+    #[default]
+    Sha256,
+    Sha3_256,
+    Blake3,
+    Shake256,
+}
+
+impl HashAlgorithm {
+    const ALL: [HashAlgorithm; 4] = [
+        HashAlgorithm::Sha256,
+        HashAlgorithm::Sha3_256,
+        HashAlgorithm::Blake3,
+        HashAlgorithm::Shake256,
+    ];
+
+    pub(crate) fn from_name(name: &str) -> PyResult<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|algorithm| algorithm.name() == name)
+            .ok_or_else(|| {
+                pyo3::exceptions::PyValueError::new_err(format!(
+                    "Unsupported Merkle hash algorithm {:?} (expected sha256, sha3-256, blake3 or shake256)",
+                    name
+                ))
+            })
+    }
+
+    pub(crate) fn name(self) -> &'static str {
+        match self {
+            HashAlgorithm::Sha256 => "sha256",
+            HashAlgorithm::Sha3_256 => "sha3-256",
+            HashAlgorithm::Blake3 => "blake3",
+            HashAlgorithm::Shake256 => "shake256",
+        }
+    }
+
+    /// Id recorded in v2 roots; SHA-256 roots leave it out.
+    fn id(self) -> u8 {
+        match self {
+            HashAlgorithm::Sha256 => 1,
+            HashAlgorithm::Sha3_256 => 2,
+            HashAlgorithm::Blake3 => 3,
+            HashAlgorithm::Shake256 => 4,
+        }
+    }
+
+    fn from_id(id: u8) -> Option<Self> {
+        Self::ALL.into_iter().find(|algorithm| algorithm.id() == id)
+    }
+
+    /// Hash of the concatenated `parts`.
+    pub(crate) fn digest(self, parts: &[&[u8]]) -> Hash {
+        match self {
+            HashAlgorithm::Sha256 => {
+                let mut hasher = Sha256::new();
+                for part in parts {
+                    Digest::update(&mut hasher, part);
+                }
+                hasher.finalize().into()
+            }
+            HashAlgorithm::Sha3_256 => {
+                let mut hasher = Sha3_256::new();
+                for part in parts {
+                    Digest::update(&mut hasher, part);
+                }
+                hasher.finalize().into()
+            }
+            HashAlgorithm::Blake3 => {
+                let mut hasher = blake3::Hasher::new();
+                for part in parts {
+                    hasher.update(part);
+                }
+                hasher.finalize().into()
+            }
+            HashAlgorithm::Shake256 => {
+                let mut hasher = Shake256::default();
+                for part in parts {
+                    Update::update(&mut hasher, part);
+                }
+                let mut hash = [0; 32];
+                hasher.finalize_xof().read(&mut hash);
+                hash
+            }
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum TreeVersion {
+    V1,
+    V2(HashAlgorithm),
+}
+
+impl TreeVersion {
+    pub(crate) fn from_arg(version: u8, hash_algorithm: &str) -> PyResult<Self> {
+        let algorithm = HashAlgorithm::from_name(hash_algorithm)?;
+        match (version, algorithm) {
+            (1, HashAlgorithm::Sha256) => Ok(TreeVersion::V1),
+            (1, _) => Err(pyo3::exceptions::PyValueError::new_err(format!(
+                "Hash algorithm {} needs tree version 2 (v1 roots can't record it)",
+                algorithm.name()
+            ))),
+            (2, _) => Ok(TreeVersion::V2(algorithm)),
+            _ => Err(pyo3::exceptions::PyValueError::new_err(format!(
+                "Unsupported Merkle tree version {} (expected 1 or 2)",
+                version
+            ))),
+        }
+    }
+
+    /// Version of a root and its hash, or None if the bytes aren't a root of either version.
+    pub(crate) fn of_root(root: &[u8]) -> Option<(Self, &[u8])> {
+        match root {
+            hash if hash.len() == 32 => Some((TreeVersion::V1, hash)),
+            [V2_ROOT_TAG, hash @ ..] if hash.len() == 32 => {
+                Some((TreeVersion::V2(HashAlgorithm::Sha256), hash))
+            }
+            [V2_ROOT_TAG, id, hash @ ..] if hash.len() == 32 => match HashAlgorithm::from_id(*id) {
+                Some(HashAlgorithm::Sha256) | None => None,
+                Some(algorithm) => Some((TreeVersion::V2(algorithm), hash)),
+            },
+            _ => None,
+        }
+    }
+
+    pub(crate) fn number(self) -> u8 {
+        match self {
+            TreeVersion::V1 => 1,
+            TreeVersion::V2(_) => 2,
+        }
+    }
+
+    pub(crate) fn hash_algorithm(self) -> HashAlgorithm {
+        match self {
+            TreeVersion::V1 => HashAlgorithm::Sha256,
+            TreeVersion::V2(algorithm) => algorithm,
+        }
+    }
+
+    pub(crate) fn hash_leaf(self, data: &[u8]) -> Hash {
+        match self {
+            TreeVersion::V1 => HashAlgorithm::Sha256.digest(&[data]),
+            TreeVersion::V2(algorithm) => algorithm.digest(&[&[LEAF_PREFIX], data]),
+        }
+    }
+
+    pub(crate) fn hash_pair(self, left: &[u8], right: &[u8]) -> Hash {
+        match self {
+            TreeVersion::V1 => HashAlgorithm::Sha256.digest(&[left, right]),
+            TreeVersion::V2(algorithm) => algorithm.digest(&[&[NODE_PREFIX], left, right]),
+        }
+    }
+
+    /// Parent of the last node of an odd level: v1 pairs it with itself, v2 carries it up.
+    pub(crate) fn hash_unpaired(self, node: &Hash) -> Hash {
+        match self {
+            TreeVersion::V1 => self.hash_pair(node, node),
+            TreeVersion::V2(_) => *node,
+        }
+    }
+
+    /// (version, hash id) as serialized trees and proofs record them.
+    pub(crate) fn to_wire(self) -> [u8; 2] {
+        [self.number(), self.hash_algorithm().id()]
+    }
+
+    pub(crate) fn from_wire(version: u8, hash_id: u8) -> Result<Self, String> {
+        let algorithm = HashAlgorithm::from_id(hash_id)
+            .ok_or_else(|| format!("Unknown Merkle hash algorithm id {}", hash_id))?;
+        match (version, algorithm) {
+            (1, HashAlgorithm::Sha256) => Ok(TreeVersion::V1),
+            (2, _) => Ok(TreeVersion::V2(algorithm)),
+            _ => Err(format!(
+                "Unsupported Merkle tree version {} with {}",
+                version,
+                algorithm.name()
+            )),
+        }
+    }
+
+    /// Root bytes as returned to callers: bare for v1, version-tagged for v2 (with the hash
+    /// algorithm's id unless it is SHA-256).
+    pub(crate) fn encode_root(self, hash: &Hash) -> Vec<u8> {
+        match self {
+            TreeVersion::V1 => hash.to_vec(),
+            TreeVersion::V2(HashAlgorithm::Sha256) => [&[V2_ROOT_TAG][..], hash].concat(),
+            TreeVersion::V2(algorithm) => [&[V2_ROOT_TAG, algorithm.id()][..], hash].concat(),
+        }
+    }
+}
+
+fn not_a_root() -> PyErr {
+    pyo3::exceptions::PyValueError::new_err(
+        "Not a Merkle root (expected 32 bytes, or 33 / 34 starting with 0x02)",
+    )
+}
+
+/// Tree version of a Merkle root: 1 for a bare 32-byte root, 2 for 0x02 | [hash id] | 32-byte
+/// hash
+#[pyfunction]
+pub fn merkle_root_version(root: Vec<u8>) -> PyResult<u8> {
+    TreeVersion::of_root(&root)
+        .map(|(version, _)| version.number())
+        .ok_or_else(not_a_root)
+}
+
+/// Hash algorithm a Merkle root was built with: "sha256", "sha3-256", "blake3" or "shake256"
+#[pyfunction]
+pub fn merkle_root_hash_algorithm(root: Vec<u8>) -> PyResult<&'static str> {
+    TreeVersion::of_root(&root)
+        .map(|(version, _)| version.hash_algorithm().name())
+        .ok_or_else(not_a_root)
+}
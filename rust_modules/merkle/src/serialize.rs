@@ -0,0 +1,408 @@
+use pyo3::prelude::*;
+
+use crate::consistency::verify_consistency_proof;
+use crate::multiproof::verify_multi_proof;
+use crate::positional::verify_positional_proof;
+use crate::scheme::{Hash, TreeVersion};
+use crate::tree::{hex_root, path_steps};
+use crate::wire::{put_var8, Reader};
+
+// Wire formats for handing roots, proofs and trees between services and storing them. Each
+// opens with a 4-byte magic and a format version byte; integers are big-endian, hashes are 32
+// raw bytes, and a tree's scheme is its version byte and hash id (as in v2 roots):
+//   root   "RQMR" | 1 | tree_size u64 | root var8 (the root's own, version-tagged bytes)
+//   proof  "RQMP" | 1 | kind u8 | tree version u8 | hash id u8 | tree_size u64 | body
+//          | node count u32 | nodes, where body is index u64 (inclusion, kind 1), old_size
+//          u64 (consistency, kind 2) or count u32 | indices u64 (multiproof, kind 3)
+//   tree   "RQMT" | 1 | tree version u8 | hash id u8 | leaf count u64 | leaf hashes
+// Encodings are canonical: from_bytes rejects anything to_bytes wouldn't write (unknown ids,
+// sizes a tree can't have, unsorted multiproof indices, trailing bytes), so equal objects
+// have equal bytes, and a proof only verifies against roots of the scheme it records.
+pub(crate) const ROOT_MAGIC: &[u8; 4] = b"RQMR";
+pub(crate) const PROOF_MAGIC: &[u8; 4] = b"RQMP";
+pub(crate) const TREE_MAGIC: &[u8; 4] = b"RQMT";
+pub(crate) const FORMAT_VERSION: u8 = 1;
+
+const INCLUSION: u8 = 1;
+const CONSISTENCY: u8 = 2;
+const MULTI: u8 = 3;
+
+/// Tree root with the number of leaves under it
+#[pyclass(eq, module = "reliquary_merkle")]
+#[derive(Clone, PartialEq, Eq)]
+pub struct MerkleRoot {
+    version: TreeVersion,
+    tree_size: usize,
+    hash: Hash,
+}
+
+impl MerkleRoot {
+    pub(crate) fn from_hash(version: TreeVersion, tree_size: usize, hash: Hash) -> Self {
+        MerkleRoot {
+            version,
+            tree_size,
+            hash,
+        }
+    }
+
+    fn parse(root: &[u8], tree_size: usize) -> Result<Self, String> {
+        let (version, hash) = TreeVersion::of_root(root).ok_or("Not a Merkle root")?;
+        if tree_size == 0 {
+            return Err("A Merkle root covers at least one leaf".to_string());
+        }
+        Ok(MerkleRoot::from_hash(
+            version,
+            tree_size,
+            hash.try_into().expect("32-byte root hash"),
+        ))
+    }
+
+    fn decode(data: &[u8]) -> Result<Self, String> {
+        let mut r = Reader::new(data);
+        r.header(ROOT_MAGIC, FORMAT_VERSION, "Merkle root")?;
+        let tree_size = r.size()?;
+        let root = MerkleRoot::parse(r.var8()?, tree_size)?;
+        r.finish("Merkle root")?;
+        Ok(root)
+    }
+}
+
+#[pymethods]
+impl MerkleRoot {
+    #[new]
+    fn new(root: Vec<u8>, tree_size: usize) -> PyResult<Self> {
+        MerkleRoot::parse(&root, tree_size).map_err(pyo3::exceptions::PyValueError::new_err)
+    }
+
+    /// Root bytes as create_merkle_root returns them
+    #[getter]
+    fn root(&self) -> Vec<u8> {
+        self.version.encode_root(&self.hash)
+    }
+
+    #[getter]
+    fn tree_size(&self) -> usize {
+        self.tree_size
+    }
+
+    #[getter]
+    fn version(&self) -> u8 {
+        self.version.number()
+    }
+
+    #[getter]
+    fn hash_algorithm(&self) -> &'static str {
+        self.version.hash_algorithm().name()
+    }
+
+    fn to_bytes(&self) -> Vec<u8> {
+        let mut out = ROOT_MAGIC.to_vec();
+        out.push(FORMAT_VERSION);
+        out.extend_from_slice(&(self.tree_size as u64).to_be_bytes());
+        put_var8(&mut out, &self.root()).expect("roots are at most 34 bytes");
+        out
+    }
+
+    #[staticmethod]
+    fn from_bytes(data: Vec<u8>) -> PyResult<Self> {
+        MerkleRoot::decode(&data).map_err(pyo3::exceptions::PyValueError::new_err)
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "MerkleRoot(version={}, hash_algorithm={}, tree_size={}, root={})",
+            self.version.number(),
+            self.version.hash_algorithm().name(),
+            self.tree_size,
+            hex_root(Some(self.hash))
+        )
+    }
+}
+
+/// What a MerkleProof proves, beyond the tree size it was taken at.
+#[derive(Clone, PartialEq, Eq)]
+pub(crate) enum ProofBody {
+    Inclusion { index: usize },
+    Consistency { old_size: usize },
+    Multi { indices: Vec<usize> },
+}
+
+/// Inclusion, consistency or multiproof together with the tree scheme and size it was taken
+/// from, as made by MerkleTree.prove_inclusion, prove_consistency and prove_multi
+#[pyclass(eq, module = "reliquary_merkle")]
+#[derive(Clone, PartialEq, Eq)]
+pub struct MerkleProof {
+    version: TreeVersion,
+    tree_size: usize,
+    body: ProofBody,
+    nodes: Vec<Hash>,
+}
+
+impl MerkleProof {
+    pub(crate) fn new(
+        version: TreeVersion,
+        tree_size: usize,
+        body: ProofBody,
+        nodes: Vec<Hash>,
+    ) -> Self {
+        MerkleProof {
+            version,
+            tree_size,
+            body,
+            nodes,
+        }
+    }
+
+    /// Whether `root` was built with this proof's tree version and hash.
+    fn matches(&self, root: &[u8]) -> bool {
+        TreeVersion::of_root(root).is_some_and(|(version, _)| version == self.version)
+    }
+
+    fn kind(&self) -> u8 {
+        match self.body {
+            ProofBody::Inclusion { .. } => INCLUSION,
+            ProofBody::Consistency { .. } => CONSISTENCY,
+            ProofBody::Multi { .. } => MULTI,
+        }
+    }
+
+    pub(crate) fn decode(data: &[u8]) -> Result<Self, String> {
+        let mut r = Reader::new(data);
+        r.header(PROOF_MAGIC, FORMAT_VERSION, "Merkle proof")?;
+        let kind = r.u8()?;
+        let version = TreeVersion::from_wire(r.u8()?, r.u8()?)?;
+        let tree_size = r.size()?;
+        if tree_size == 0 {
+            return Err("Merkle proofs need a tree of at least one leaf".to_string());
+        }
+        let body = match kind {
+            INCLUSION => {
+                let index = r.size()?;
+                if index >= tree_size {
+                    return Err("Leaf index out of range".to_string());
+                }
+                ProofBody::Inclusion { index }
+            }
+            CONSISTENCY => {
+                let old_size = r.size()?;
+                if old_size == 0 || old_size > tree_size {
+                    return Err("Consistency proofs need 0 < old_size <= tree_size".to_string());
+                }
+                ProofBody::Consistency { old_size }
+            }
+            MULTI => {
+                let count = r.u32()? as usize;
+                let indices = (0..count)
+                    .map(|_| r.size())
+                    .collect::<Result<Vec<_>, _>>()?;
+                if indices.is_empty()
+                    || indices.windows(2).any(|pair| pair[0] >= pair[1])
+                    || indices.last().is_some_and(|&index| index >= tree_size)
+                {
+                    return Err(
+                        "Multiproof indices must be ascending, distinct and in range".to_string(),
+                    );
+                }
+                ProofBody::Multi { indices }
+            }
+            _ => return Err(format!("Unknown Merkle proof kind {}", kind)),
+        };
+        let count = r.u32()? as usize;
+        let nodes = r.hashes(count)?;
+        if let ProofBody::Inclusion { index } = body {
+            if nodes.len() != path_steps(version, tree_size, index).len() {
+                return Err("Inclusion proof length does not match the tree size".to_string());
+            }
+        }
+        r.finish("Merkle proof")?;
+        Ok(MerkleProof::new(version, tree_size, body, nodes))
+    }
+}
+
+#[pymethods]
+impl MerkleProof {
+    /// "inclusion", "consistency" or "multi"
+    #[getter(kind)]
+    fn kind_name(&self) -> &'static str {
+        match self.body {
+            ProofBody::Inclusion { .. } => "inclusion",
+            ProofBody::Consistency { .. } => "consistency",
+            ProofBody::Multi { .. } => "multi",
+        }
+    }
+
+    #[getter]
+    fn version(&self) -> u8 {
+        self.version.number()
+    }
+
+    #[getter]
+    fn hash_algorithm(&self) -> &'static str {
+        self.version.hash_algorithm().name()
+    }
+
+    /// Size of the tree the proof was taken from (the newer tree for consistency proofs)
+    #[getter]
+    pub(crate) fn tree_size(&self) -> usize {
+        self.tree_size
+    }
+
+    /// Leaf index of an inclusion proof, else None
+    #[getter]
+    pub(crate) fn index(&self) -> Option<usize> {
+        match self.body {
+            ProofBody::Inclusion { index } => Some(index),
+            _ => None,
+        }
+    }
+
+    /// Older tree size of a consistency proof, else None
+    #[getter]
+    pub(crate) fn old_size(&self) -> Option<usize> {
+        match self.body {
+            ProofBody::Consistency { old_size } => Some(old_size),
+            _ => None,
+        }
+    }
+
+    /// Ascending leaf indices of a multiproof, else None
+    #[getter]
+    fn indices(&self) -> Option<Vec<usize>> {
+        match &self.body {
+            ProofBody::Multi { indices } => Some(indices.clone()),
+            _ => None,
+        }
+    }
+
+    /// Proof hashes, as the matching get_*_proof method returns them
+    #[getter]
+    fn nodes(&self) -> Vec<Vec<u8>> {
+        self.nodes.iter().map(|node| node.to_vec()).collect()
+    }
+
+    /// Checks an inclusion proof for `data_block` against `root`. Other kinds of proof, and
+    /// roots of another version or hash, fail
+    pub(crate) fn verify_inclusion(&self, data_block: Vec<u8>, root: Vec<u8>) -> bool {
+        let ProofBody::Inclusion { index } = self.body else {
+            return false;
+        };
+        if !self.matches(&root) {
+            return false;
+        }
+        let steps = path_steps(self.version, self.tree_size, index)
+            .iter()
+            .zip(&self.nodes)
+            .map(|(step, node)| (node.to_vec(), step.is_right))
+            .collect();
+        verify_positional_proof(data_block, steps, root, index, self.tree_size)
+    }
+
+    /// Checks an update proof from MerkleTree.update_leaf: that the leaf at the proof's index
+    /// was `old_data` under `old_root` and is `new_data` under `new_root`, with every other
+    /// leaf of the tree_size-leaf tree unchanged
+    fn verify_update(
+        &self,
+        old_data: Vec<u8>,
+        new_data: Vec<u8>,
+        old_root: Vec<u8>,
+        new_root: Vec<u8>,
+    ) -> bool {
+        let ProofBody::Inclusion { index } = self.body else {
+            return false;
+        };
+        if !self.matches(&old_root) || !self.matches(&new_root) {
+            return false;
+        }
+        let steps = path_steps(self.version, self.tree_size, index);
+        if steps.len() != self.nodes.len() {
+            return false;
+        }
+        let mut old = self.version.hash_leaf(&old_data);
+        let mut new = self.version.hash_leaf(&new_data);
+        for (step, node) in steps.iter().zip(&self.nodes) {
+            // A duplicated node is its own sibling, so it changes with the leaf
+            let (old_sibling, new_sibling) = match step.duplicate {
+                true => (old, new),
+                false => (*node, *node),
+            };
+            (old, new) = match step.is_right {
+                true => (
+                    self.version.hash_pair(&old, &old_sibling),
+                    self.version.hash_pair(&new, &new_sibling),
+                ),
+                false => (
+                    self.version.hash_pair(&old_sibling, &old),
+                    self.version.hash_pair(&new_sibling, &new),
+                ),
+            };
+        }
+        [(old, &old_root), (new, &new_root)]
+            .iter()
+            .all(|(hash, root)| TreeVersion::of_root(root).is_some_and(|(_, h)| h[..] == hash[..]))
+    }
+
+    /// Checks a consistency proof from `old_root` (old_size leaves) to `new_root` (tree_size
+    /// leaves)
+    pub(crate) fn verify_consistency(&self, old_root: Vec<u8>, new_root: Vec<u8>) -> bool {
+        let ProofBody::Consistency { old_size } = self.body else {
+            return false;
+        };
+        self.matches(&old_root)
+            && self.matches(&new_root)
+            && verify_consistency_proof(old_size, self.tree_size, old_root, new_root, self.nodes())
+    }
+
+    /// Checks a multiproof for (index, data_block) pairs, which must cover exactly the
+    /// proof's indices
+    fn verify_multi(&self, leaves_with_indices: Vec<(usize, Vec<u8>)>, root: Vec<u8>) -> bool {
+        let ProofBody::Multi { indices } = &self.body else {
+            return false;
+        };
+        let mut given: Vec<usize> = leaves_with_indices.iter().map(|(i, _)| *i).collect();
+        given.sort_unstable();
+        given == *indices
+            && self.matches(&root)
+            && verify_multi_proof(leaves_with_indices, (self.tree_size, self.nodes()), root)
+    }
+
+    pub(crate) fn to_bytes(&self) -> Vec<u8> {
+        let mut out = PROOF_MAGIC.to_vec();
+        out.push(FORMAT_VERSION);
+        out.push(self.kind());
+        out.extend_from_slice(&self.version.to_wire());
+        out.extend_from_slice(&(self.tree_size as u64).to_be_bytes());
+        match &self.body {
+            ProofBody::Inclusion { index } => out.extend_from_slice(&(*index as u64).to_be_bytes()),
+            ProofBody::Consistency { old_size } => {
+                out.extend_from_slice(&(*old_size as u64).to_be_bytes())
+            }
+            ProofBody::Multi { indices } => {
+                out.extend_from_slice(&(indices.len() as u32).to_be_bytes());
+                for index in indices {
+                    out.extend_from_slice(&(*index as u64).to_be_bytes());
+                }
+            }
+        }
+        out.extend_from_slice(&(self.nodes.len() as u32).to_be_bytes());
+        for node in &self.nodes {
+            out.extend_from_slice(node);
+        }
+        out
+    }
+
+    #[staticmethod]
+    fn from_bytes(data: Vec<u8>) -> PyResult<Self> {
+        MerkleProof::decode(&data).map_err(pyo3::exceptions::PyValueError::new_err)
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "MerkleProof(kind={}, version={}, hash_algorithm={}, tree_size={}, nodes={})",
+            self.kind_name(),
+            self.version.number(),
+            self.version.hash_algorithm().name(),
+            self.tree_size,
+            self.nodes.len()
+        )
+    }
+}
@@ -0,0 +1,20 @@
+use pyo3::prelude::*;
+
+const HASH_BYTES: usize = 32;
+
+/// Number of sibling hashes in a v1 inclusion proof for a tree of `tree_size` leaves. Odd
+/// levels duplicate their last node, so every leaf sits at depth ceil(log2(tree_size)).
+pub(crate) fn proof_len(tree_size: usize) -> usize {
+    if tree_size <= 1 {
+        0
+    } else {
+        (usize::BITS - (tree_size - 1).leading_zeros()) as usize
+    }
+}
+
+/// Size in bytes of the sibling hashes in an inclusion proof for a tree of `tree_size` leaves.
+/// Exact for v1; v2 proofs are at most this long, shorter for leaves carried up a level
+#[pyfunction]
+pub fn proof_size(tree_size: usize) -> usize {
+    proof_len(tree_size) * HASH_BYTES
+}
@@ -0,0 +1,235 @@
+use std::fs::{self, File};
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+use memmap2::Mmap;
+use pyo3::prelude::*;
+
+use crate::scheme::{Hash, TreeVersion};
+use crate::serialize::{MerkleProof, MerkleRoot};
+use crate::tree::{hex_root, StoredNodes};
+use crate::wire::Reader;
+
+// Read-only tree snapshots for proof servers. A snapshot is one file: "RQMN", format version,
+// tree version, hash algorithm id and the u64 leaf count, then every stored node level by
+// level from the leaves up (level l holds size >> l hashes, the same nodes MerkleTree keeps).
+// Where each level starts follows from the size, so opening a snapshot maps the file, checks
+// the header and the length and is done: nodes are read straight from the mapping, and every
+// process serving the same snapshot shares one copy in the page cache.
+//
+// Exports are written to `<path>.partial` and renamed into place, so a reader never maps a
+// half-written file and re-exporting to the same path leaves already-open maps untouched.
+const SNAPSHOT_MAGIC: &[u8; 4] = b"RQMN";
+const SNAPSHOT_FORMAT_VERSION: u8 = 1;
+const HEADER_BYTES: usize = 15;
+const HASH_BYTES: usize = 32;
+
+/// Writes the nodes of `tree` as a snapshot file at `path`.
+pub(crate) fn export(tree: &impl StoredNodes, path: &Path) -> PyResult<()> {
+    let mut partial = path.as_os_str().to_owned();
+    partial.push(".partial");
+    let file = File::create(&partial)?;
+    let mut out = BufWriter::new(&file);
+    out.write_all(SNAPSHOT_MAGIC)?;
+    out.write_all(&[SNAPSHOT_FORMAT_VERSION])?;
+    out.write_all(&tree.scheme().to_wire())?;
+    out.write_all(&(tree.size() as u64).to_be_bytes())?;
+    let mut level = 0;
+    while tree.size() >> level > 0 {
+        tree.copy_level(level, &mut out)?;
+        level += 1;
+    }
+    out.flush()?;
+    drop(out);
+    file.sync_all()?;
+    fs::rename(&partial, path)?;
+    Ok(())
+}
+
+/// Scheme, size and the start of each level, checked against the mapped bytes.
+fn level_offsets(data: &[u8]) -> Result<(TreeVersion, usize, Vec<usize>), String> {
+    let mut r = Reader::new(data);
+    r.header(SNAPSHOT_MAGIC, SNAPSHOT_FORMAT_VERSION, "Merkle snapshot")?;
+    let version = TreeVersion::from_wire(r.u8()?, r.u8()?)?;
+    let size = r.size()?;
+    let mut offsets = Vec::new();
+    let mut offset = HEADER_BYTES;
+    while size >> offsets.len() > 0 {
+        let count = size >> offsets.len();
+        offsets.push(offset);
+        r.bytes(count.checked_mul(HASH_BYTES).ok_or("Input is truncated")?)?;
+        offset += count * HASH_BYTES;
+    }
+    r.finish("Merkle snapshot")?;
+    Ok((version, size, offsets))
+}
+
+/// Read-only Merkle tree mapped from a snapshot file written by export_snapshot. Opening
+/// costs a header check however large the tree; roots and proofs match the exported tree
+#[pyclass(module = "reliquary_merkle")]
+pub struct MerkleSnapshot {
+    path: PathBuf,
+    version: TreeVersion,
+    size: usize,
+    offsets: Vec<usize>,
+    map: Mmap,
+}
+
+impl StoredNodes for MerkleSnapshot {
+    fn scheme(&self) -> TreeVersion {
+        self.version
+    }
+
+    fn size(&self) -> usize {
+        self.size
+    }
+
+    fn stored(&self, level: usize, index: usize) -> PyResult<Hash> {
+        let start = self.offsets[level] + index * HASH_BYTES;
+        Ok(self.map[start..start + HASH_BYTES]
+            .try_into()
+            .expect("32-byte node"))
+    }
+}
+
+impl MerkleSnapshot {
+    fn root_hash(&self) -> PyResult<Option<Hash>> {
+        match self.size {
+            0 => Ok(None),
+            size => self.root_hash_at(size).map(Some),
+        }
+    }
+}
+
+#[pymethods]
+impl MerkleSnapshot {
+    #[new]
+    fn new(path: PathBuf) -> PyResult<Self> {
+        let file = File::open(&path)?;
+        // SAFETY: snapshots are written once and renamed into place, never modified, so the
+        // mapped bytes don't change underneath us (replacing the file leaves this map alone).
+        let map = unsafe { Mmap::map(&file)? };
+        let (version, size, offsets) =
+            level_offsets(&map).map_err(pyo3::exceptions::PyValueError::new_err)?;
+        Ok(MerkleSnapshot {
+            path,
+            version,
+            size,
+            offsets,
+            map,
+        })
+    }
+
+    /// Snapshot file
+    #[getter(path)]
+    fn snapshot_path(&self) -> PathBuf {
+        self.path.clone()
+    }
+
+    /// Tree format version, 1 or 2
+    #[getter]
+    fn version(&self) -> u8 {
+        self.version.number()
+    }
+
+    /// Hash algorithm name, e.g. "sha256" or "blake3"
+    #[getter]
+    fn hash_algorithm(&self) -> &'static str {
+        self.version.hash_algorithm().name()
+    }
+
+    /// Root (version-tagged for v2); b'' for an empty tree, as with create_merkle_root
+    fn root(&self) -> PyResult<Vec<u8>> {
+        Ok(self
+            .root_hash()?
+            .map(|root| self.version.encode_root(&root))
+            .unwrap_or_default())
+    }
+
+    /// Leaf hash at `index` (hash of the leaf, 0x00-prefixed for v2)
+    fn leaf_hash(&self, index: usize) -> PyResult<Vec<u8>> {
+        if index >= self.size {
+            return Err(pyo3::exceptions::PyIndexError::new_err(
+                "Leaf index out of range",
+            ));
+        }
+        Ok(self.stored(0, index)?.to_vec())
+    }
+
+    /// Sibling hashes from the leaf at `index` up to the root, as MerkleTree.get_proof
+    fn get_proof(&self, index: usize) -> PyResult<Vec<Vec<u8>>> {
+        Ok(self
+            .path(index)?
+            .into_iter()
+            .map(|(sibling, _)| sibling.to_vec())
+            .collect())
+    }
+
+    /// The same path as get_proof with is_right flags; check with verify_positional_proof
+    fn get_positional_proof(&self, index: usize) -> PyResult<Vec<(Vec<u8>, bool)>> {
+        Ok(self
+            .path(index)?
+            .into_iter()
+            .map(|(sibling, is_right)| (sibling.to_vec(), is_right))
+            .collect())
+    }
+
+    /// Root of the tree of the first `size` leaves, as create_merkle_root would give it
+    fn root_at(&self, size: usize) -> PyResult<Vec<u8>> {
+        self.encoded_root_at(size)
+    }
+
+    /// Consistency proof that the tree at `old_size` is a prefix of the tree at `new_size`;
+    /// check with verify_consistency_proof
+    fn get_consistency_proof(&self, old_size: usize, new_size: usize) -> PyResult<Vec<Vec<u8>>> {
+        Ok(self
+            .consistency(old_size, new_size)?
+            .into_iter()
+            .map(|node| node.to_vec())
+            .collect())
+    }
+
+    /// One proof for all the leaves at `indices` as (tree_size, nodes); check with
+    /// verify_multi_proof
+    fn get_multi_proof(&self, indices: Vec<usize>) -> PyResult<(usize, Vec<Vec<u8>>)> {
+        let (size, nodes) = self.multi_proof(indices)?;
+        Ok((size, nodes.iter().map(|node| node.to_vec()).collect()))
+    }
+
+    /// get_positional_proof as a MerkleProof
+    fn prove_inclusion(&self, index: usize) -> PyResult<MerkleProof> {
+        self.inclusion_proof(index)
+    }
+
+    /// get_consistency_proof as a MerkleProof; new_size defaults to the snapshot's size
+    #[pyo3(signature = (old_size, new_size = None))]
+    fn prove_consistency(&self, old_size: usize, new_size: Option<usize>) -> PyResult<MerkleProof> {
+        self.consistency_proof(old_size, new_size)
+    }
+
+    /// get_multi_proof as a MerkleProof
+    fn prove_multi(&self, indices: Vec<usize>) -> PyResult<MerkleProof> {
+        self.multi_proof_object(indices)
+    }
+
+    /// Root of the first `size` leaves (default all) with that size, as a MerkleRoot
+    #[pyo3(signature = (size = None))]
+    fn tree_root(&self, size: Option<usize>) -> PyResult<MerkleRoot> {
+        self.tree_root_at(size)
+    }
+
+    fn __len__(&self) -> usize {
+        self.size
+    }
+
+    fn __repr__(&self) -> PyResult<String> {
+        Ok(format!(
+            "MerkleSnapshot(path={:?}, version={}, hash_algorithm={}, len={}, root={})",
+            self.path,
+            self.version.number(),
+            self.version.hash_algorithm().name(),
+            self.size,
+            hex_root(self.root_hash()?)
+        ))
+    }
+}
@@ -0,0 +1,383 @@
+use std::collections::HashMap;
+
+use pyo3::prelude::*;
+use sha2::{Digest, Sha256};
+
+use crate::diff::KeyChange;
+use crate::scheme::{Hash, HashAlgorithm, TreeVersion};
+
+// Sparse Merkle tree over 256-bit keys, stored compactly: a subtree holding one key is just
+// that key's leaf, wherever it sits, and an empty subtree hashes to 32 zero bytes, so paths are
+// about log2(len) deep rather than 256. Leaves hash 0x00 | key | SHA-256(value) and internal
+// nodes 0x01 | left | right (the v2 prefixes); bit i of the key, most significant first,
+// picks the side at depth i. The layout is canonical (deleting a key lifts a lone remaining
+// sibling leaf back up), so a key set has exactly one root.
+//
+// A proof is (siblings, leaf): sibling hashes from the bottom of the key's path up to the
+// root, and the leaf the path ends at as (key, value_hash), or None where it ends in an empty
+// subtree. The path ending at the key's own leaf proves inclusion; ending empty or at another
+// key's leaf proves the key is absent.
+const HASH_BYTES: usize = 32;
+const EMPTY: Hash = [0; HASH_BYTES];
+const SCHEME: TreeVersion = TreeVersion::V2(HashAlgorithm::Sha256);
+
+/// (siblings, leaf) as returned by SparseMerkleTree.prove.
+pub(crate) type SparseProof = (Vec<Vec<u8>>, Option<(Vec<u8>, Vec<u8>)>);
+
+enum Node {
+    Empty,
+    Leaf {
+        key: Hash,
+        value_hash: Hash,
+        hash: Hash,
+    },
+    Internal {
+        left: Box<Node>,
+        right: Box<Node>,
+        hash: Hash,
+    },
+}
+
+fn bit(key: &Hash, depth: usize) -> bool {
+    key[depth / 8] >> (7 - depth % 8) & 1 == 1
+}
+
+fn leaf_hash(key: &Hash, value_hash: &Hash) -> Hash {
+    SCHEME.hash_leaf(&[&key[..], value_hash].concat())
+}
+
+fn node_hash(left: &Hash, right: &Hash) -> Hash {
+    if *left == EMPTY && *right == EMPTY {
+        EMPTY
+    } else {
+        SCHEME.hash_pair(left, right)
+    }
+}
+
+impl Node {
+    fn leaf(key: Hash, value_hash: Hash) -> Node {
+        Node::Leaf {
+            key,
+            value_hash,
+            hash: leaf_hash(&key, &value_hash),
+        }
+    }
+
+    fn internal(left: Node, right: Node) -> Node {
+        let hash = node_hash(&left.hash(), &right.hash());
+        Node::Internal {
+            left: Box::new(left),
+            right: Box::new(right),
+            hash,
+        }
+    }
+
+    fn hash(&self) -> Hash {
+        match self {
+            Node::Empty => EMPTY,
+            Node::Leaf { hash, .. } | Node::Internal { hash, .. } => *hash,
+        }
+    }
+
+    /// Subtree at `depth` holding two leaves whose keys agree on every bit above it.
+    fn split(depth: usize, a: Node, a_key: &Hash, b: Node, b_key: &Hash) -> Node {
+        match (bit(a_key, depth), bit(b_key, depth)) {
+            (false, true) => Node::internal(a, b),
+            (true, false) => Node::internal(b, a),
+            (false, false) => {
+                Node::internal(Node::split(depth + 1, a, a_key, b, b_key), Node::Empty)
+            }
+            (true, true) => Node::internal(Node::Empty, Node::split(depth + 1, a, a_key, b, b_key)),
+        }
+    }
+
+    fn insert(self, depth: usize, key: Hash, value_hash: Hash) -> Node {
+        match self {
+            Node::Empty => Node::leaf(key, value_hash),
+            Node::Leaf { key: existing, .. } if existing == key => Node::leaf(key, value_hash),
+            Node::Leaf { key: existing, .. } => {
+                Node::split(depth, self, &existing, Node::leaf(key, value_hash), &key)
+            }
+            Node::Internal { left, right, .. } => {
+                if bit(&key, depth) {
+                    Node::internal(*left, right.insert(depth + 1, key, value_hash))
+                } else {
+                    Node::internal(left.insert(depth + 1, key, value_hash), *right)
+                }
+            }
+        }
+    }
+
+    /// (key, value_hash) of every leaf in the subtree.
+    fn leaves<'a>(&'a self, out: &mut Vec<(&'a Hash, &'a Hash)>) {
+        match self {
+            Node::Empty => {}
+            Node::Leaf {
+                key, value_hash, ..
+            } => out.push((key, value_hash)),
+            Node::Internal { left, right, .. } => {
+                left.leaves(out);
+                right.leaves(out);
+            }
+        }
+    }
+
+    /// Leaves under two subtrees at the same position that may differ. Internal nodes at the
+    /// same depth split on the same key bit, so matching children are compared pairwise and
+    /// equal hashes end the descent.
+    fn diff<'a>(
+        &'a self,
+        other: &'a Node,
+        old: &mut Vec<(&'a Hash, &'a Hash)>,
+        new: &mut Vec<(&'a Hash, &'a Hash)>,
+    ) {
+        if self.hash() == other.hash() {
+            return;
+        }
+        match (self, other) {
+            (
+                Node::Internal {
+                    left: a_left,
+                    right: a_right,
+                    ..
+                },
+                Node::Internal {
+                    left: b_left,
+                    right: b_right,
+                    ..
+                },
+            ) => {
+                a_left.diff(b_left, old, new);
+                a_right.diff(b_right, old, new);
+            }
+            _ => {
+                self.leaves(old);
+                other.leaves(new);
+            }
+        }
+    }
+
+    fn remove(self, depth: usize, key: &Hash) -> Node {
+        match self {
+            Node::Leaf { key: existing, .. } if existing == *key => Node::Empty,
+            Node::Internal { left, right, .. } => {
+                let (left, right) = if bit(key, depth) {
+                    (*left, right.remove(depth + 1, key))
+                } else {
+                    (left.remove(depth + 1, key), *right)
+                };
+                match (left, right) {
+                    (Node::Empty, Node::Empty) => Node::Empty,
+                    (Node::Empty, leaf @ Node::Leaf { .. })
+                    | (leaf @ Node::Leaf { .. }, Node::Empty) => leaf,
+                    (left, right) => Node::internal(left, right),
+                }
+            }
+            node => node,
+        }
+    }
+}
+
+/// Sparse Merkle tree keyed by 32-byte keys (hash longer identifiers first), with proofs of
+/// inclusion and of absence; check them with verify_sparse_proof
+#[pyclass(module = "reliquary_merkle")]
+pub struct SparseMerkleTree {
+    root: Node,
+    values: HashMap<Hash, Vec<u8>>,
+}
+
+fn to_key(key: &[u8]) -> PyResult<Hash> {
+    key.try_into().map_err(|_| {
+        pyo3::exceptions::PyValueError::new_err(format!(
+            "Sparse Merkle tree keys are 32 bytes, got {}",
+            key.len()
+        ))
+    })
+}
+
+impl SparseMerkleTree {
+    pub(crate) fn proof(&self, key: &Hash) -> SparseProof {
+        let mut siblings = Vec::new();
+        let mut node = &self.root;
+        let mut depth = 0;
+        while let Node::Internal { left, right, .. } = node {
+            let (next, sibling) = if bit(key, depth) {
+                (right, left)
+            } else {
+                (left, right)
+            };
+            siblings.push(sibling.hash().to_vec());
+            node = next;
+            depth += 1;
+        }
+        siblings.reverse();
+        let leaf = match node {
+            Node::Leaf {
+                key, value_hash, ..
+            } => Some((key.to_vec(), value_hash.to_vec())),
+            _ => None,
+        };
+        (siblings, leaf)
+    }
+}
+
+#[pymethods]
+impl SparseMerkleTree {
+    #[new]
+    #[pyo3(signature = (items = None))]
+    pub(crate) fn new(items: Option<Vec<(Vec<u8>, Vec<u8>)>>) -> PyResult<Self> {
+        let mut tree = SparseMerkleTree {
+            root: Node::Empty,
+            values: HashMap::new(),
+        };
+        for (key, value) in items.unwrap_or_default() {
+            tree.update(key, value)?;
+        }
+        Ok(tree)
+    }
+
+    /// Value stored under `key`, or None
+    pub(crate) fn get(&self, key: Vec<u8>) -> PyResult<Option<Vec<u8>>> {
+        Ok(self.values.get(&to_key(&key)?).cloned())
+    }
+
+    /// Sets the value under `key`, replacing any previous one
+    pub(crate) fn update(&mut self, key: Vec<u8>, value: Vec<u8>) -> PyResult<()> {
+        let key = to_key(&key)?;
+        let value_hash: Hash = Sha256::digest(&value).into();
+        let root = std::mem::replace(&mut self.root, Node::Empty);
+        self.root = root.insert(0, key, value_hash);
+        self.values.insert(key, value);
+        Ok(())
+    }
+
+    /// Removes `key`; returns whether it was present
+    pub(crate) fn delete(&mut self, key: Vec<u8>) -> PyResult<bool> {
+        let key = to_key(&key)?;
+        if self.values.remove(&key).is_none() {
+            return Ok(false);
+        }
+        let root = std::mem::replace(&mut self.root, Node::Empty);
+        self.root = root.remove(0, &key);
+        Ok(true)
+    }
+
+    /// Current root (32 bytes; all zero for an empty tree)
+    pub(crate) fn root(&self) -> Vec<u8> {
+        self.root.hash().to_vec()
+    }
+
+    /// Proof for `key` as (siblings, leaf): of inclusion if the key is present, of absence
+    /// otherwise
+    fn prove(&self, key: Vec<u8>) -> PyResult<SparseProof> {
+        Ok(self.proof(&to_key(&key)?))
+    }
+
+    /// Keys added, changed or removed between this tree and `new`, in key order, each with
+    /// a proof against both roots. Only subtrees whose hashes differ are visited
+    fn diff(&self, new: PyRef<'_, SparseMerkleTree>) -> Vec<KeyChange> {
+        let (mut old_leaves, mut new_leaves) = (Vec::new(), Vec::new());
+        self.root.diff(&new.root, &mut old_leaves, &mut new_leaves);
+        // A key can turn up on both sides unchanged when it moved between a lone leaf and a
+        // deeper subtree
+        let before: HashMap<&Hash, &Hash> = old_leaves.into_iter().collect();
+        let mut keys: Vec<&Hash> = new_leaves
+            .iter()
+            .filter(|(key, value_hash)| before.get(key) != Some(value_hash))
+            .map(|(key, _)| *key)
+            .chain(
+                before
+                    .keys()
+                    .filter(|key| !new.values.contains_key(**key))
+                    .copied(),
+            )
+            .collect();
+        keys.sort_unstable();
+        keys.into_iter()
+            .map(|key| {
+                KeyChange::new(
+                    key,
+                    (self.values.get(key).cloned(), self.proof(key)),
+                    (new.values.get(key).cloned(), new.proof(key)),
+                )
+            })
+            .collect()
+    }
+
+    fn __contains__(&self, key: Vec<u8>) -> PyResult<bool> {
+        Ok(self.values.contains_key(&to_key(&key)?))
+    }
+
+    fn __len__(&self) -> usize {
+        self.values.len()
+    }
+
+    fn __repr__(&self) -> String {
+        let root: String = self
+            .root
+            .hash()
+            .iter()
+            .map(|b| format!("{:02x}", b))
+            .collect();
+        format!("SparseMerkleTree(len={}, root={})", self.values.len(), root)
+    }
+}
+
+/// Verifies a SparseMerkleTree proof: that `key` maps to `value`, or with value=None that
+/// `key` is absent. Malformed proofs fail
+#[pyfunction]
+#[pyo3(signature = (key, value, proof, root))]
+pub fn verify_sparse_proof(
+    key: Vec<u8>,
+    value: Option<Vec<u8>>,
+    proof: SparseProof,
+    root: Vec<u8>,
+) -> bool {
+    let (siblings, leaf) = proof;
+    let (Ok(key), Ok(root)) = (Hash::try_from(&key[..]), Hash::try_from(&root[..])) else {
+        return false;
+    };
+    let depth = siblings.len();
+    if depth > 8 * HASH_BYTES || siblings.iter().any(|s| s.len() != HASH_BYTES) {
+        return false;
+    }
+    let leaf = match leaf {
+        Some((leaf_key, value_hash)) => {
+            let (Ok(leaf_key), Ok(value_hash)) = (
+                Hash::try_from(&leaf_key[..]),
+                Hash::try_from(&value_hash[..]),
+            ) else {
+                return false;
+            };
+            Some((leaf_key, value_hash))
+        }
+        None => None,
+    };
+    let mut current = match (&value, leaf) {
+        (Some(value), Some((leaf_key, value_hash))) => {
+            if leaf_key != key || value_hash != <Hash>::from(Sha256::digest(value)) {
+                return false;
+            }
+            leaf_hash(&key, &value_hash)
+        }
+        // Absent: the path ends at another key's leaf, which must sit on the same path
+        (None, Some((leaf_key, value_hash))) => {
+            if leaf_key == key || (0..depth).any(|d| bit(&leaf_key, d) != bit(&key, d)) {
+                return false;
+            }
+            leaf_hash(&leaf_key, &value_hash)
+        }
+        (None, None) => EMPTY,
+        (Some(_), None) => return false,
+    };
+    for (i, sibling) in siblings.iter().enumerate() {
+        let mut sibling_hash = EMPTY;
+        sibling_hash.copy_from_slice(sibling);
+        current = if bit(&key, depth - 1 - i) {
+            node_hash(&sibling_hash, &current)
+        } else {
+            node_hash(&current, &sibling_hash)
+        };
+    }
+    current == root
+}
@@ -0,0 +1,454 @@
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Read, Write};
+use std::path::{Path, PathBuf};
+
+use pyo3::prelude::*;
+
+use crate::hash_leaves;
+use crate::scheme::{Hash, TreeVersion};
+use crate::serialize::{MerkleProof, MerkleRoot};
+use crate::snapshot;
+use crate::tree::{hex_root, StoredNodes};
+use crate::wire::Reader;
+
+// Disk-backed tree store. A store is a directory holding `meta` ("RQMS", format version, tree
+// version, hash algorithm id) and one append-only file per level, `level-00`, `level-01`, ...,
+// each a run of 32-byte hashes: level l holds the roots of the complete 2^l-leaf subtrees,
+// exactly the nodes MerkleTree keeps in memory. Nodes are never rewritten, and a root or
+// proof reads O(log n) of them with positioned reads, so a tree of hundreds of millions of
+// leaves opens instantly and never has to fit in RAM.
+//
+// Appends collect in a buffer per level, written out by sync(), on drop, or once a level has
+// FLUSH_NODES waiting. A crash can leave a torn hash at the end of a file or levels that
+// disagree; opening cuts every file back to whole hashes and then makes level l + 1 hold
+// exactly len(level l) / 2 nodes, dropping extras and rebuilding missing ones from below, so
+// the leaves are the source of truth. A `lock` file in the directory is held for the store's
+// lifetime so two writers (in this or another process) can't interleave appends.
+const STORE_MAGIC: &[u8; 4] = b"RQMS";
+const STORE_FORMAT_VERSION: u8 = 1;
+const HASH_BYTES: usize = 32;
+/// Nodes buffered per level before they are written out.
+const FLUSH_NODES: usize = 1 << 15;
+
+struct Level {
+    file: File,
+    on_disk: usize,
+    buffer: Vec<Hash>,
+}
+
+impl Level {
+    fn open(path: &Path) -> io::Result<Self> {
+        let file = OpenOptions::new()
+            .create(true)
+            .truncate(false)
+            .read(true)
+            .append(true)
+            .open(path)?;
+        let on_disk = (file.metadata()?.len() / HASH_BYTES as u64) as usize;
+        // Drop a hash torn by a crash mid-write
+        file.set_len((on_disk * HASH_BYTES) as u64)?;
+        Ok(Level {
+            file,
+            on_disk,
+            buffer: Vec::new(),
+        })
+    }
+
+    fn len(&self) -> usize {
+        self.on_disk + self.buffer.len()
+    }
+
+    fn get(&self, index: usize) -> io::Result<Hash> {
+        if let Some(&node) = index
+            .checked_sub(self.on_disk)
+            .and_then(|i| self.buffer.get(i))
+        {
+            return Ok(node);
+        }
+        let mut node = [0u8; HASH_BYTES];
+        read_exact_at(&self.file, &mut node, (index * HASH_BYTES) as u64)?;
+        Ok(node)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        if self.buffer.is_empty() {
+            return Ok(());
+        }
+        (&self.file).write_all(self.buffer.as_flattened())?;
+        self.on_disk += self.buffer.len();
+        self.buffer.clear();
+        Ok(())
+    }
+}
+
+#[cfg(unix)]
+pub(crate) fn read_exact_at(file: &File, buf: &mut [u8], offset: u64) -> io::Result<()> {
+    std::os::unix::fs::FileExt::read_exact_at(file, buf, offset)
+}
+
+#[cfg(windows)]
+pub(crate) fn read_exact_at(file: &File, mut buf: &mut [u8], mut offset: u64) -> io::Result<()> {
+    while !buf.is_empty() {
+        match std::os::windows::fs::FileExt::seek_read(file, buf, offset)? {
+            0 => return Err(io::ErrorKind::UnexpectedEof.into()),
+            n => {
+                buf = &mut buf[n..];
+                offset += n as u64;
+            }
+        }
+    }
+    Ok(())
+}
+
+fn level_path(dir: &Path, level: usize) -> PathBuf {
+    dir.join(format!("level-{:02}", level))
+}
+
+fn encode_meta(version: TreeVersion) -> Vec<u8> {
+    let mut out = STORE_MAGIC.to_vec();
+    out.push(STORE_FORMAT_VERSION);
+    out.extend_from_slice(&version.to_wire());
+    out
+}
+
+fn decode_meta(data: &[u8]) -> Result<TreeVersion, String> {
+    let mut r = Reader::new(data);
+    r.header(STORE_MAGIC, STORE_FORMAT_VERSION, "Merkle tree store")?;
+    let version = TreeVersion::from_wire(r.u8()?, r.u8()?)?;
+    r.finish("Merkle tree store")?;
+    Ok(version)
+}
+
+/// Reads the store's scheme, or records the requested one (default v1, SHA-256) in a new
+/// store. A scheme that is asked for must match the stored one.
+fn open_meta(
+    dir: &Path,
+    version: Option<u8>,
+    hash_algorithm: Option<&str>,
+) -> PyResult<TreeVersion> {
+    let path = dir.join("meta");
+    let stored = match fs::read(&path) {
+        Ok(data) => Some(decode_meta(&data).map_err(pyo3::exceptions::PyValueError::new_err)?),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => None,
+        Err(e) => return Err(e.into()),
+    };
+    let requested = TreeVersion::from_arg(
+        version.unwrap_or(stored.map_or(1, |s| s.number())),
+        hash_algorithm.unwrap_or(stored.map_or("sha256", |s| s.hash_algorithm().name())),
+    )?;
+    match stored {
+        Some(stored) if stored != requested => {
+            Err(pyo3::exceptions::PyValueError::new_err(format!(
+                "Store holds a version {} {} tree, not version {} {}",
+                stored.number(),
+                stored.hash_algorithm().name(),
+                requested.number(),
+                requested.hash_algorithm().name()
+            )))
+        }
+        Some(stored) => Ok(stored),
+        None => {
+            // Write then rename, so a crash never leaves a torn meta file
+            let partial = dir.join("meta.partial");
+            let mut file = File::create(&partial)?;
+            file.write_all(&encode_meta(requested))?;
+            file.sync_all()?;
+            fs::rename(&partial, &path)?;
+            Ok(requested)
+        }
+    }
+}
+
+fn lock_store(dir: &Path) -> PyResult<File> {
+    let lock = OpenOptions::new()
+        .create(true)
+        .truncate(false)
+        .write(true)
+        .open(dir.join("lock"))?;
+    lock.try_lock().map_err(|_| {
+        pyo3::exceptions::PyRuntimeError::new_err("Merkle tree store is in use by another writer")
+    })?;
+    Ok(lock)
+}
+
+/// Append-only Merkle tree kept in a directory on disk, with the same roots and proofs as
+/// MerkleTree. Reopening the directory picks the tree up where it was left, and proofs read
+/// only the nodes they need. The directory is locked while the store is open
+#[pyclass(module = "reliquary_merkle")]
+pub struct PersistentMerkleTree {
+    path: PathBuf,
+    version: TreeVersion,
+    levels: Vec<Level>,
+    _lock: File,
+}
+
+impl StoredNodes for PersistentMerkleTree {
+    fn scheme(&self) -> TreeVersion {
+        self.version
+    }
+
+    fn size(&self) -> usize {
+        self.levels.first().map_or(0, Level::len)
+    }
+
+    fn stored(&self, level: usize, index: usize) -> PyResult<Hash> {
+        Ok(self.levels[level].get(index)?)
+    }
+
+    /// Streams the level file rather than reading node by node.
+    fn copy_level(&self, level: usize, out: &mut dyn Write) -> PyResult<()> {
+        let nodes = &self.levels[level];
+        let file = File::open(level_path(&self.path, level))?;
+        io::copy(&mut file.take((nodes.on_disk * HASH_BYTES) as u64), out)?;
+        out.write_all(nodes.buffer.as_flattened())?;
+        Ok(())
+    }
+}
+
+impl PersistentMerkleTree {
+    /// Opens every level file and repairs what a crash may have left behind (see the top of
+    /// this module).
+    fn open_levels(&mut self) -> PyResult<()> {
+        self.levels.push(Level::open(&level_path(&self.path, 0))?);
+        let mut level = 1;
+        loop {
+            let expected = self.levels[level - 1].len() / 2;
+            let path = level_path(&self.path, level);
+            if expected == 0 {
+                // Levels above the tree's height can only be leftovers
+                while level_path(&self.path, level).exists() {
+                    fs::remove_file(level_path(&self.path, level))?;
+                    level += 1;
+                }
+                return Ok(());
+            }
+            let mut nodes = Level::open(&path)?;
+            if nodes.len() > expected {
+                nodes.file.set_len((expected * HASH_BYTES) as u64)?;
+                nodes.on_disk = expected;
+            }
+            let below = &self.levels[level - 1];
+            for index in nodes.len()..expected {
+                let (left, right) = (below.get(2 * index)?, below.get(2 * index + 1)?);
+                nodes.buffer.push(self.version.hash_pair(&left, &right));
+            }
+            nodes.flush()?;
+            self.levels.push(nodes);
+            level += 1;
+        }
+    }
+
+    fn push(&mut self, leaf: Hash) -> PyResult<()> {
+        let mut node = leaf;
+        let mut level = 0;
+        loop {
+            if self.levels.len() == level {
+                self.levels
+                    .push(Level::open(&level_path(&self.path, level))?);
+            }
+            let nodes = &mut self.levels[level];
+            nodes.buffer.push(node);
+            let len = nodes.len();
+            if nodes.buffer.len() >= FLUSH_NODES {
+                nodes.flush()?;
+            }
+            if len % 2 == 1 {
+                return Ok(());
+            }
+            node = self
+                .version
+                .hash_pair(&nodes.get(len - 2)?, &nodes.get(len - 1)?);
+            level += 1;
+        }
+    }
+
+    fn root_hash(&self) -> PyResult<Option<Hash>> {
+        match self.size() {
+            0 => Ok(None),
+            size => self.root_hash_at(size).map(Some),
+        }
+    }
+}
+
+impl Drop for PersistentMerkleTree {
+    fn drop(&mut self) {
+        // Best effort; call sync() to see write errors and to make appends durable
+        for level in &mut self.levels {
+            let _ = level.flush();
+        }
+    }
+}
+
+#[pymethods]
+impl PersistentMerkleTree {
+    /// Opens the store in directory `path`, creating it if needed. `version` and
+    /// `hash_algorithm` default to the stored scheme (v1, SHA-256 for a new store); a
+    /// different scheme than the stored one raises ValueError
+    #[new]
+    #[pyo3(signature = (path, version = None, hash_algorithm = None))]
+    fn new(
+        py: Python<'_>,
+        path: PathBuf,
+        version: Option<u8>,
+        hash_algorithm: Option<&str>,
+    ) -> PyResult<Self> {
+        fs::create_dir_all(&path)?;
+        let lock = lock_store(&path)?;
+        let version = open_meta(&path, version, hash_algorithm)?;
+        let mut tree = PersistentMerkleTree {
+            path,
+            version,
+            levels: Vec::new(),
+            _lock: lock,
+        };
+        py.allow_threads(|| tree.open_levels())?;
+        Ok(tree)
+    }
+
+    /// Store directory
+    #[getter(path)]
+    fn store_path(&self) -> PathBuf {
+        self.path.clone()
+    }
+
+    /// Tree format version, 1 or 2
+    #[getter]
+    fn version(&self) -> u8 {
+        self.version.number()
+    }
+
+    /// Hash algorithm name, e.g. "sha256" or "blake3"
+    #[getter]
+    fn hash_algorithm(&self) -> &'static str {
+        self.version.hash_algorithm().name()
+    }
+
+    /// Appends a leaf and returns its index
+    fn append(&mut self, leaf: Vec<u8>) -> PyResult<usize> {
+        self.push(self.version.hash_leaf(&leaf))?;
+        Ok(self.size() - 1)
+    }
+
+    /// Appends several leaves in order, hashing them on all cores with the GIL released
+    fn extend(&mut self, py: Python<'_>, leaves: Vec<Vec<u8>>) -> PyResult<()> {
+        let version = self.version;
+        py.allow_threads(|| {
+            for leaf in hash_leaves(version, &leaves) {
+                self.push(leaf)?;
+            }
+            Ok(())
+        })
+    }
+
+    /// Writes out buffered nodes and flushes them to stable storage. Appends since the last
+    /// sync may be lost in a crash; the store reopens at a consistent earlier size
+    fn sync(&mut self, py: Python<'_>) -> PyResult<()> {
+        py.allow_threads(|| {
+            for level in &mut self.levels {
+                level.flush()?;
+                level.file.sync_data()?;
+            }
+            Ok(())
+        })
+    }
+
+    /// Current root (version-tagged for v2); b'' for an empty tree, as with create_merkle_root
+    fn root(&self) -> PyResult<Vec<u8>> {
+        Ok(self
+            .root_hash()?
+            .map(|root| self.version.encode_root(&root))
+            .unwrap_or_default())
+    }
+
+    /// Leaf hash at `index` (hash of the leaf, 0x00-prefixed for v2)
+    fn leaf_hash(&self, index: usize) -> PyResult<Vec<u8>> {
+        if index >= self.size() {
+            return Err(pyo3::exceptions::PyIndexError::new_err(
+                "Leaf index out of range",
+            ));
+        }
+        Ok(self.stored(0, index)?.to_vec())
+    }
+
+    /// Sibling hashes from the leaf at `index` up to the root, as MerkleTree.get_proof
+    fn get_proof(&self, index: usize) -> PyResult<Vec<Vec<u8>>> {
+        Ok(self
+            .path(index)?
+            .into_iter()
+            .map(|(sibling, _)| sibling.to_vec())
+            .collect())
+    }
+
+    /// The same path as get_proof with is_right flags; check with verify_positional_proof
+    fn get_positional_proof(&self, index: usize) -> PyResult<Vec<(Vec<u8>, bool)>> {
+        Ok(self
+            .path(index)?
+            .into_iter()
+            .map(|(sibling, is_right)| (sibling.to_vec(), is_right))
+            .collect())
+    }
+
+    /// Root of the tree of the first `size` leaves, as create_merkle_root would give it
+    fn root_at(&self, size: usize) -> PyResult<Vec<u8>> {
+        self.encoded_root_at(size)
+    }
+
+    /// Consistency proof that the tree at `old_size` is a prefix of the tree at `new_size`;
+    /// check with verify_consistency_proof
+    fn get_consistency_proof(&self, old_size: usize, new_size: usize) -> PyResult<Vec<Vec<u8>>> {
+        Ok(self
+            .consistency(old_size, new_size)?
+            .into_iter()
+            .map(|node| node.to_vec())
+            .collect())
+    }
+
+    /// One proof for all the leaves at `indices` as (tree_size, nodes); check with
+    /// verify_multi_proof
+    fn get_multi_proof(&self, indices: Vec<usize>) -> PyResult<(usize, Vec<Vec<u8>>)> {
+        let (size, nodes) = self.multi_proof(indices)?;
+        Ok((size, nodes.iter().map(|node| node.to_vec()).collect()))
+    }
+
+    /// get_positional_proof as a MerkleProof
+    fn prove_inclusion(&self, index: usize) -> PyResult<MerkleProof> {
+        self.inclusion_proof(index)
+    }
+
+    /// get_consistency_proof as a MerkleProof; new_size defaults to the current size
+    #[pyo3(signature = (old_size, new_size = None))]
+    fn prove_consistency(&self, old_size: usize, new_size: Option<usize>) -> PyResult<MerkleProof> {
+        self.consistency_proof(old_size, new_size)
+    }
+
+    /// get_multi_proof as a MerkleProof
+    fn prove_multi(&self, indices: Vec<usize>) -> PyResult<MerkleProof> {
+        self.multi_proof_object(indices)
+    }
+
+    /// Root of the first `size` leaves (default all) with that size, as a MerkleRoot
+    #[pyo3(signature = (size = None))]
+    fn tree_root(&self, size: Option<usize>) -> PyResult<MerkleRoot> {
+        self.tree_root_at(size)
+    }
+
+    /// Writes the tree to a snapshot file for MerkleSnapshot, with the GIL released
+    fn export_snapshot(&self, py: Python<'_>, path: PathBuf) -> PyResult<()> {
+        py.allow_threads(|| snapshot::export(self, &path))
+    }
+
+    fn __len__(&self) -> usize {
+        self.size()
+    }
+
+    fn __repr__(&self) -> PyResult<String> {
+        Ok(format!(
+            "PersistentMerkleTree(path={:?}, version={}, hash_algorithm={}, len={}, root={})",
+            self.path,
+            self.version.number(),
+            self.version.hash_algorithm().name(),
+            self.size(),
+            hex_root(self.root_hash()?)
+        ))
+    }
+}
@@ -0,0 +1,525 @@
+use std::io::Write;
+use std::path::PathBuf;
+
+use pyo3::prelude::*;
+
+use crate::hash_leaves;
+use crate::multiproof;
+use crate::scheme::{Hash, TreeVersion};
+use crate::serialize::{MerkleProof, MerkleRoot, ProofBody, FORMAT_VERSION, TREE_MAGIC};
+use crate::snapshot;
+use crate::wire::Reader;
+
+/// A tree's stored nodes, wherever they are kept: `stored(level, index)` is the root of the
+/// complete subtree over leaves `index << level .. (index + 1) << level`, for every such
+/// subtree within `size()` leaves. Everything else (right edges of the tree or of any earlier
+/// size, and every proof) is derived from those, so the in-memory tree and the disk store
+/// share one implementation.
+pub(crate) trait StoredNodes {
+    fn scheme(&self) -> TreeVersion;
+
+    fn size(&self) -> usize;
+
+    fn stored(&self, level: usize, index: usize) -> PyResult<Hash>;
+
+    /// Node `index` of `level` in the tree of the first `size` leaves (size <= len). Nodes
+    /// over complete pairs are stored; the right edge of a smaller tree is rebuilt from them.
+    fn node_at(&self, size: usize, level: usize, index: usize) -> PyResult<Hash> {
+        if (index + 1) << level <= size {
+            return self.stored(level, index);
+        }
+        let left = self.node_at(size, level - 1, 2 * index)?;
+        if (2 * index + 1) << (level - 1) >= size {
+            return Ok(self.scheme().hash_unpaired(&left));
+        }
+        let right = self.node_at(size, level - 1, 2 * index + 1)?;
+        Ok(self.scheme().hash_pair(&left, &right))
+    }
+
+    fn root_hash_at(&self, size: usize) -> PyResult<Hash> {
+        self.node_at(size, height(size), 0)
+    }
+
+    /// (sibling, is_right) from the leaf at `index` up to the root, one per `path_steps`.
+    fn path(&self, index: usize) -> PyResult<Vec<(Hash, bool)>> {
+        self.path_at(self.size(), index)
+    }
+
+    /// path() in the tree of the first `size` leaves (size <= len).
+    fn path_at(&self, size: usize, index: usize) -> PyResult<Vec<(Hash, bool)>> {
+        if index >= size {
+            return Err(pyo3::exceptions::PyIndexError::new_err(
+                "Leaf index out of range",
+            ));
+        }
+        path_steps(self.scheme(), size, index)
+            .into_iter()
+            .map(|step| {
+                let node = if step.duplicate {
+                    step.index
+                } else {
+                    step.index ^ 1
+                };
+                Ok((self.node_at(size, step.level, node)?, step.is_right))
+            })
+            .collect()
+    }
+
+    /// Nodes proving the tree of `old_size` leaves is a prefix of the tree of `new_size`. The
+    /// walk starts at the old tree's rightmost complete subtree (omitted when that is the
+    /// whole old tree) and goes up the new tree: left siblings are shared with the old tree,
+    /// right siblings only count towards the new root, and unpaired nodes are left out.
+    fn consistency(&self, old_size: usize, new_size: usize) -> PyResult<Vec<Hash>> {
+        if old_size == 0 || old_size > new_size || new_size > self.size() {
+            return Err(pyo3::exceptions::PyValueError::new_err(
+                "Consistency proofs need 0 < old_size <= new_size <= len(tree)",
+            ));
+        }
+        let mut proof = Vec::new();
+        if old_size == new_size {
+            return Ok(proof);
+        }
+        let mut level = old_size.trailing_zeros() as usize;
+        let mut index = (old_size >> level) - 1;
+        if index != 0 {
+            proof.push(self.node_at(new_size, level, index)?);
+        }
+        while level < height(new_size) {
+            if !index.is_multiple_of(2) {
+                proof.push(self.node_at(new_size, level, index - 1)?);
+            } else if (index + 1) << level < new_size {
+                proof.push(self.node_at(new_size, level, index + 1)?);
+            }
+            index /= 2;
+            level += 1;
+        }
+        Ok(proof)
+    }
+
+    /// Nodes proving the leaves at `indices` together, in the order `multiproof::fold` asks
+    /// for them, with the tree size they were taken from.
+    fn multi_proof(&self, mut indices: Vec<usize>) -> PyResult<(usize, Vec<Hash>)> {
+        indices.sort_unstable();
+        indices.dedup();
+        let size = self.size();
+        if indices.is_empty() {
+            return Err(pyo3::exceptions::PyValueError::new_err(
+                "A multiproof needs at least one leaf index",
+            ));
+        }
+        if indices.last().is_some_and(|&index| index >= size) {
+            return Err(pyo3::exceptions::PyIndexError::new_err(
+                "Leaf index out of range",
+            ));
+        }
+        let leaves = indices
+            .into_iter()
+            .map(|index| Ok((index, self.stored(0, index)?)))
+            .collect::<PyResult<_>>()?;
+        let mut proof = Vec::new();
+        let mut failed = None;
+        multiproof::fold(self.scheme(), size, leaves, |level, index| {
+            match self.node_at(size, level, index) {
+                Ok(node) => {
+                    proof.push(node);
+                    Some(node)
+                }
+                Err(e) => {
+                    failed = Some(e);
+                    None
+                }
+            }
+        });
+        match failed {
+            Some(e) => Err(e),
+            None => Ok((size, proof)),
+        }
+    }
+
+    /// path() as a MerkleProof, which records the scheme and size and serializes.
+    fn inclusion_proof(&self, index: usize) -> PyResult<MerkleProof> {
+        self.inclusion_proof_at(self.size(), index)
+    }
+
+    fn inclusion_proof_at(&self, size: usize, index: usize) -> PyResult<MerkleProof> {
+        let nodes = self
+            .path_at(size, index)?
+            .into_iter()
+            .map(|(node, _)| node)
+            .collect();
+        Ok(MerkleProof::new(
+            self.scheme(),
+            size,
+            ProofBody::Inclusion { index },
+            nodes,
+        ))
+    }
+
+    fn consistency_proof(&self, old_size: usize, new_size: Option<usize>) -> PyResult<MerkleProof> {
+        let new_size = new_size.unwrap_or(self.size());
+        let nodes = self.consistency(old_size, new_size)?;
+        Ok(MerkleProof::new(
+            self.scheme(),
+            new_size,
+            ProofBody::Consistency { old_size },
+            nodes,
+        ))
+    }
+
+    fn multi_proof_object(&self, mut indices: Vec<usize>) -> PyResult<MerkleProof> {
+        let (size, nodes) = self.multi_proof(indices.clone())?;
+        indices.sort_unstable();
+        indices.dedup();
+        Ok(MerkleProof::new(
+            self.scheme(),
+            size,
+            ProofBody::Multi { indices },
+            nodes,
+        ))
+    }
+
+    /// Root of the first `size` leaves (default all) with that size.
+    fn tree_root_at(&self, size: Option<usize>) -> PyResult<MerkleRoot> {
+        let size = size.unwrap_or(self.size());
+        if size == 0 || size > self.size() {
+            return Err(pyo3::exceptions::PyIndexError::new_err(
+                "Tree size out of range",
+            ));
+        }
+        Ok(MerkleRoot::from_hash(
+            self.scheme(),
+            size,
+            self.root_hash_at(size)?,
+        ))
+    }
+
+    /// Root bytes of the first `size` leaves, b'' for none.
+    fn encoded_root_at(&self, size: usize) -> PyResult<Vec<u8>> {
+        if size > self.size() {
+            return Err(pyo3::exceptions::PyIndexError::new_err(
+                "Tree size out of range",
+            ));
+        }
+        if size == 0 {
+            return Ok(Vec::new());
+        }
+        Ok(self.scheme().encode_root(&self.root_hash_at(size)?))
+    }
+
+    /// Writes every stored node of `level` in order (a snapshot level, see `snapshot`).
+    fn copy_level(&self, level: usize, out: &mut dyn Write) -> PyResult<()> {
+        for index in 0..self.size() >> level {
+            out.write_all(&self.stored(level, index)?)?;
+        }
+        Ok(())
+    }
+}
+
+/// Merkle tree with the same shape and root as `create_merkle_root` for its tree version and
+/// hash algorithm: odd levels duplicate their last node in v1 and carry it up in v2.
+///
+/// `levels[0]` holds the leaf hashes and `levels[l + 1]` the parents of complete pairs in
+/// `levels[l]`, so an append touches one node per level it completes and a leaf update one
+/// node per level above it. Nodes on the right edge
+/// of a level with an odd count aren't stored; `StoredNodes` rebuilds them from the nodes
+/// below when a root or proof needs them.
+#[pyclass(module = "reliquary_merkle")]
+pub struct MerkleTree {
+    version: TreeVersion,
+    levels: Vec<Vec<Hash>>,
+}
+
+impl StoredNodes for MerkleTree {
+    fn scheme(&self) -> TreeVersion {
+        self.version
+    }
+
+    fn size(&self) -> usize {
+        self.__len__()
+    }
+
+    fn stored(&self, level: usize, index: usize) -> PyResult<Hash> {
+        Ok(self.levels[level][index])
+    }
+}
+
+impl MerkleTree {
+    fn push(&mut self, leaf: Hash) {
+        let mut node = leaf;
+        let mut level = 0;
+        loop {
+            if self.levels.len() == level {
+                self.levels.push(Vec::new());
+            }
+            self.levels[level].push(node);
+            let nodes = &self.levels[level];
+            if nodes.len() % 2 == 1 {
+                return;
+            }
+            node = self
+                .version
+                .hash_pair(&nodes[nodes.len() - 2], &nodes[nodes.len() - 1]);
+            level += 1;
+        }
+    }
+
+    /// Replaces leaf `index` and rehashes its stored ancestors; those on the right edge aren't
+    /// stored, and nor is anything above them.
+    fn set_leaf(&mut self, index: usize, leaf: Hash) {
+        self.levels[0][index] = leaf;
+        let mut node = index;
+        for level in 1..self.levels.len() {
+            node /= 2;
+            if node >= self.levels[level].len() {
+                return;
+            }
+            let below = &self.levels[level - 1];
+            self.levels[level][node] = self
+                .version
+                .hash_pair(&below[2 * node], &below[2 * node + 1]);
+        }
+    }
+
+    /// Appends leaves that are already hashed.
+    pub(crate) fn push_all(&mut self, leaves: impl IntoIterator<Item = Hash>) {
+        for leaf in leaves {
+            self.push(leaf);
+        }
+    }
+
+    fn decode(data: &[u8]) -> Result<Self, String> {
+        let mut r = Reader::new(data);
+        r.header(TREE_MAGIC, FORMAT_VERSION, "Merkle tree")?;
+        let mut tree = MerkleTree {
+            version: TreeVersion::from_wire(r.u8()?, r.u8()?)?,
+            levels: Vec::new(),
+        };
+        let count = r.size()?;
+        tree.push_all(r.hashes(count)?);
+        r.finish("Merkle tree")?;
+        Ok(tree)
+    }
+
+    fn root_hash(&self) -> Option<Hash> {
+        match self.__len__() {
+            0 => None,
+            len => self.root_hash_at(len).ok(),
+        }
+    }
+}
+
+#[pymethods]
+impl MerkleTree {
+    #[new]
+    #[pyo3(signature = (leaves = None, version = 1, hash_algorithm = "sha256"))]
+    pub(crate) fn new(
+        leaves: Option<Vec<Vec<u8>>>,
+        version: u8,
+        hash_algorithm: &str,
+    ) -> PyResult<Self> {
+        let mut tree = MerkleTree {
+            version: TreeVersion::from_arg(version, hash_algorithm)?,
+            levels: Vec::new(),
+        };
+        tree.push_all(hash_leaves(tree.version, &leaves.unwrap_or_default()));
+        Ok(tree)
+    }
+
+    /// Tree format version, 1 or 2
+    #[getter]
+    fn version(&self) -> u8 {
+        self.version.number()
+    }
+
+    /// Hash algorithm name, e.g. "sha256" or "blake3"
+    #[getter]
+    fn hash_algorithm(&self) -> &'static str {
+        self.version.hash_algorithm().name()
+    }
+
+    /// Appends a leaf and returns its index
+    fn append(&mut self, leaf: Vec<u8>) -> usize {
+        self.push(self.version.hash_leaf(&leaf));
+        self.levels[0].len() - 1
+    }
+
+    /// Appends several leaves in order, hashing them on all cores with the GIL released
+    fn extend(&mut self, py: Python<'_>, leaves: Vec<Vec<u8>>) {
+        let version = self.version;
+        py.allow_threads(|| self.push_all(hash_leaves(version, &leaves)));
+    }
+
+    /// Replaces the leaf at `index` with `new_data`, rehashing only the nodes on its path, and
+    /// returns (new root, update proof). The proof is the leaf's inclusion proof in the
+    /// updated tree; MerkleProof.verify_update checks it against the old and new leaf and root
+    fn update_leaf(&mut self, index: usize, new_data: Vec<u8>) -> PyResult<(Vec<u8>, MerkleProof)> {
+        if index >= self.__len__() {
+            return Err(pyo3::exceptions::PyIndexError::new_err(
+                "Leaf index out of range",
+            ));
+        }
+        self.set_leaf(index, self.version.hash_leaf(&new_data));
+        Ok((self.root(), self.inclusion_proof(index)?))
+    }
+
+    /// Current root (version-tagged for v2); b'' for an empty tree, as with create_merkle_root
+    fn root(&self) -> Vec<u8> {
+        self.root_hash()
+            .map(|root| self.version.encode_root(&root))
+            .unwrap_or_default()
+    }
+
+    /// Leaf hash at `index` (hash of the leaf, 0x00-prefixed for v2)
+    fn leaf_hash(&self, index: usize) -> PyResult<Vec<u8>> {
+        self.levels
+            .first()
+            .and_then(|leaves| leaves.get(index))
+            .map(|hash| hash.to_vec())
+            .ok_or_else(|| pyo3::exceptions::PyIndexError::new_err("Leaf index out of range"))
+    }
+
+    /// Sibling hashes from the leaf at `index` up to the root. Where a v1 level duplicates its
+    /// last node the sibling is the node itself, so every v1 proof has proof_size(len) bytes;
+    /// v2 carries that node up instead and its proof skips the level
+    fn get_proof(&self, index: usize) -> PyResult<Vec<Vec<u8>>> {
+        Ok(self
+            .path(index)?
+            .into_iter()
+            .map(|(sibling, _)| sibling.to_vec())
+            .collect())
+    }
+
+    /// The same path as get_proof, with each sibling paired with is_right (true when it is
+    /// the right-hand input of its parent); check with verify_positional_proof
+    fn get_positional_proof(&self, index: usize) -> PyResult<Vec<(Vec<u8>, bool)>> {
+        Ok(self
+            .path(index)?
+            .into_iter()
+            .map(|(sibling, is_right)| (sibling.to_vec(), is_right))
+            .collect())
+    }
+
+    /// Root of the tree of the first `size` leaves, as create_merkle_root would give it
+    fn root_at(&self, size: usize) -> PyResult<Vec<u8>> {
+        self.encoded_root_at(size)
+    }
+
+    /// Consistency proof that the tree at `old_size` is a prefix of the tree at `new_size`;
+    /// check with verify_consistency_proof
+    fn get_consistency_proof(&self, old_size: usize, new_size: usize) -> PyResult<Vec<Vec<u8>>> {
+        Ok(self
+            .consistency(old_size, new_size)?
+            .into_iter()
+            .map(|node| node.to_vec())
+            .collect())
+    }
+
+    /// One proof for all the leaves at `indices` (any order; repeats are ignored) as
+    /// (tree_size, nodes); check with verify_multi_proof
+    fn get_multi_proof(&self, indices: Vec<usize>) -> PyResult<(usize, Vec<Vec<u8>>)> {
+        let (size, nodes) = self.multi_proof(indices)?;
+        Ok((size, nodes.iter().map(|node| node.to_vec()).collect()))
+    }
+
+    /// get_positional_proof as a MerkleProof, which records the tree's scheme and size and
+    /// serializes with to_bytes
+    fn prove_inclusion(&self, index: usize) -> PyResult<MerkleProof> {
+        self.inclusion_proof(index)
+    }
+
+    /// get_consistency_proof as a MerkleProof; new_size defaults to the current size
+    #[pyo3(signature = (old_size, new_size = None))]
+    fn prove_consistency(&self, old_size: usize, new_size: Option<usize>) -> PyResult<MerkleProof> {
+        self.consistency_proof(old_size, new_size)
+    }
+
+    /// get_multi_proof as a MerkleProof
+    fn prove_multi(&self, indices: Vec<usize>) -> PyResult<MerkleProof> {
+        self.multi_proof_object(indices)
+    }
+
+    /// Root of the first `size` leaves (default all) with that size, as a MerkleRoot
+    #[pyo3(signature = (size = None))]
+    fn tree_root(&self, size: Option<usize>) -> PyResult<MerkleRoot> {
+        self.tree_root_at(size)
+    }
+
+    /// Writes the tree to a snapshot file for MerkleSnapshot, with the GIL released
+    fn export_snapshot(&self, py: Python<'_>, path: PathBuf) -> PyResult<()> {
+        py.allow_threads(|| snapshot::export(self, &path))
+    }
+
+    /// Scheme and leaf hashes; from_bytes rebuilds the same tree (the leaf data isn't kept)
+    fn to_bytes(&self) -> Vec<u8> {
+        let mut out = TREE_MAGIC.to_vec();
+        out.push(FORMAT_VERSION);
+        out.extend_from_slice(&self.version.to_wire());
+        out.extend_from_slice(&(self.__len__() as u64).to_be_bytes());
+        for leaf in self.levels.first().into_iter().flatten() {
+            out.extend_from_slice(leaf);
+        }
+        out
+    }
+
+    #[staticmethod]
+    fn from_bytes(data: Vec<u8>) -> PyResult<Self> {
+        MerkleTree::decode(&data).map_err(pyo3::exceptions::PyValueError::new_err)
+    }
+
+    fn __len__(&self) -> usize {
+        self.levels.first().map_or(0, Vec::len)
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "MerkleTree(version={}, hash_algorithm={}, len={}, root={})",
+            self.version.number(),
+            self.version.hash_algorithm().name(),
+            self.__len__(),
+            hex_root(self.root_hash())
+        )
+    }
+}
+
+/// Level of the root in a tree of `size` leaves.
+pub(crate) fn height(size: usize) -> usize {
+    size.next_power_of_two().trailing_zeros() as usize
+}
+
+/// One level of the path from a leaf to the root that contributes a proof node.
+pub(crate) struct PathStep {
+    pub(crate) level: usize,
+    /// Index of the path's node within `level`.
+    pub(crate) index: usize,
+    /// Whether the sibling is the right-hand input of the parent.
+    pub(crate) is_right: bool,
+    /// Whether the node is the last of an odd v1 level and so its own sibling.
+    pub(crate) duplicate: bool,
+}
+
+/// Steps of the path from leaf `index` (< `size`) to the root, one per proof node. The last
+/// node of an odd level has no sibling: v1 lists the node itself, v2 carries it up and skips
+/// the level, so v2 leaves on the right edge have shorter proofs.
+pub(crate) fn path_steps(version: TreeVersion, size: usize, index: usize) -> Vec<PathStep> {
+    let mut steps = Vec::with_capacity(height(size));
+    let mut index = index;
+    for level in 0..height(size) {
+        let count = ((size - 1) >> level) + 1;
+        let duplicate = index ^ 1 >= count;
+        if !duplicate || version == TreeVersion::V1 {
+            steps.push(PathStep {
+                level,
+                index,
+                is_right: index.is_multiple_of(2),
+                duplicate,
+            });
+        }
+        index /= 2;
+    }
+    steps
+}
+
+pub(crate) fn hex_root(root: Option<Hash>) -> String {
+    root.map(|root| root.iter().map(|b| format!("{:02x}", b)).collect())
+        .unwrap_or_default()
+}
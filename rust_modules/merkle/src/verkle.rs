@@ -0,0 +1,468 @@
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+use blstrs::{pairing, G1Affine, G1Projective, G2Affine, G2Projective, Scalar};
+use ff::{Field, PrimeField};
+use group::{prime::PrimeCurveAffine, Curve, Group};
+use pyo3::prelude::*;
+use pyo3::types::PyType;
+use rayon::prelude::*;
+use sha2::{Digest, Sha512};
+
+use crate::wire::Reader;
+
+// Verkle tree: a width-W tree whose nodes are KZG commitments over BLS12-381 instead of
+// hashes, so a proof carries one 48-byte commitment and one 48-byte opening per level rather
+// than W - 1 siblings. With W = 256 a tree of 2^32 leaves has four levels and ~400-byte proofs,
+// against ~1 KB for a binary Merkle proof; that's the point for constrained verifiers.
+//
+// Each node commits to the polynomial taking its W child values on the W-th roots of unity;
+// the setup holds the Lagrange basis for that domain at a secret tau in G1, plus [tau]G2.
+// Leaf values are SHA-512(0x00 | leaf) and node values SHA-512(0x01 | compressed commitment),
+// both reduced mod r, so a commitment can't pass for a leaf. Missing children are zero. The
+// root is the top commitment, 48 bytes; a tree of up to W leaves has a single level.
+//
+// Proving a child value f_m is the quotient (p(X) - f_m) / (X - w^m), evaluated on the domain
+// and committed; the verifier checks e(C - f_m.G1, G2) == e(pi, [tau]G2 - w^m.G2), so it
+// needs only [tau]G2 from the setup. Commitments are linear in the values, so an append
+// updates one commitment per level by the change in one value.
+//
+// KzgSetup.generate derives tau from a secret (or at random) and is only as trustworthy as
+// whoever saw it; production verifiers should load the output of a multi-party ceremony with
+// KzgSetup.from_bytes. There is no pure-Python fallback: verifying needs pairings.
+//   setup  "RQKS" | 1 | log2 width u8 | width Lagrange points in G1 (48 each) | [tau]G2 (96)
+//   proof  "RQVP" | 1 | log2 width u8 | index u64 | depth u8 | per level, leaf first: the
+//          node's commitment (48, except at the top, which is the root) | opening (48)
+const SETUP_MAGIC: &[u8; 4] = b"RQKS";
+const PROOF_MAGIC: &[u8; 4] = b"RQVP";
+const FORMAT_VERSION: u8 = 1;
+const G1_BYTES: usize = 48;
+const G2_BYTES: usize = 96;
+const DEFAULT_WIDTH: usize = 256;
+const MAX_LOG_WIDTH: u8 = 12;
+const LEAF_PREFIX: u8 = 0x00;
+const NODE_PREFIX: u8 = 0x01;
+
+/// SHA-512 of `prefix | data`, reduced mod the group order.
+fn hash_to_scalar(prefix: u8, data: &[u8]) -> Scalar {
+    let digest = Sha512::new()
+        .chain_update([prefix])
+        .chain_update(data)
+        .finalize();
+    let radix = Scalar::from(u64::MAX) + Scalar::ONE;
+    digest.chunks_exact(8).fold(Scalar::ZERO, |acc, limb| {
+        acc * radix + Scalar::from(u64::from_be_bytes(limb.try_into().expect("8-byte limb")))
+    })
+}
+
+fn node_value(commitment: &G1Projective) -> Scalar {
+    hash_to_scalar(NODE_PREFIX, &commitment.to_affine().to_compressed())
+}
+
+fn g1_from(bytes: &[u8]) -> Option<G1Projective> {
+    let point: Option<G1Affine> = G1Affine::from_compressed(bytes.try_into().ok()?).into();
+    point.map(G1Projective::from)
+}
+
+/// Evaluation domain and setup points for width-W nodes.
+struct Kzg {
+    log_width: u8,
+    /// Lagrange basis of the domain at tau, in G1.
+    lagrange: Vec<G1Projective>,
+    tau_g2: G2Affine,
+    /// `omega[i]` = w^i.
+    omega: Vec<Scalar>,
+    /// `inv_gap[k]` = 1 / (w^k - 1) for k >= 1; `inv_gap[0]` is unused.
+    inv_gap: Vec<Scalar>,
+}
+
+impl Kzg {
+    fn new(log_width: u8, lagrange: Vec<G1Projective>, tau_g2: G2Affine) -> Self {
+        let width = 1usize << log_width;
+        let root = (0..Scalar::S - log_width as u32).fold(Scalar::ROOT_OF_UNITY, |w, _| w.square());
+        let mut omega = Vec::with_capacity(width);
+        let mut power = Scalar::ONE;
+        for _ in 0..width {
+            omega.push(power);
+            power *= root;
+        }
+        let inv_gap = omega
+            .iter()
+            .map(|w| (w - Scalar::ONE).invert().unwrap_or(Scalar::ZERO))
+            .collect();
+        Kzg {
+            log_width,
+            lagrange,
+            tau_g2,
+            omega,
+            inv_gap,
+        }
+    }
+
+    fn width(&self) -> usize {
+        1 << self.log_width
+    }
+
+    /// Opening of a node with child values `values` at position `m`.
+    fn open(&self, values: &[Scalar], m: usize) -> G1Projective {
+        let width = self.width();
+        let f_m = values.get(m).copied().unwrap_or(Scalar::ZERO);
+        // 1 / (w^j - w^m) = w^-m / (w^(j-m) - 1)
+        let scale = self.omega[(width - m) % width];
+        let mut quotient = vec![Scalar::ZERO; width];
+        let mut at_m = Scalar::ZERO;
+        for j in (0..width).filter(|&j| j != m) {
+            let gap = (j + width - m) % width;
+            let f_j = values.get(j).copied().unwrap_or(Scalar::ZERO);
+            quotient[j] = (f_j - f_m) * scale * self.inv_gap[gap];
+            at_m -= self.omega[gap] * quotient[j];
+        }
+        quotient[m] = at_m;
+        G1Projective::multi_exp(&self.lagrange, &quotient)
+    }
+
+    /// Whether `opening` proves that `commitment` has value `value` at position `m`.
+    fn check(
+        &self,
+        commitment: &G1Projective,
+        m: usize,
+        value: &Scalar,
+        opening: &G1Affine,
+    ) -> bool {
+        let lhs = (commitment - G1Projective::generator() * value).to_affine();
+        let shifted = (G2Projective::from(self.tau_g2) - G2Projective::generator() * self.omega[m])
+            .to_affine();
+        pairing(&lhs, &G2Affine::generator()) == pairing(opening, &shifted)
+    }
+}
+
+/// Public parameters for VerkleTree: the KZG commitment key for one node width. Build one with
+/// generate or load a ceremony's with from_bytes; provers and verifiers must share it
+#[pyclass(frozen, module = "reliquary_merkle")]
+#[derive(Clone)]
+pub struct KzgSetup {
+    kzg: Arc<Kzg>,
+}
+
+#[pymethods]
+impl KzgSetup {
+    /// Setup for nodes of `width` children (a power of two from 2 to 4096). tau is derived
+    /// from `secret` if given, for reproducible test setups, and drawn at random otherwise.
+    /// Whoever knows tau can forge proofs: use a ceremony's setup where that matters
+    #[classmethod]
+    #[pyo3(signature = (width = DEFAULT_WIDTH, secret = None))]
+    fn generate(
+        _cls: &Bound<'_, PyType>,
+        py: Python<'_>,
+        width: usize,
+        secret: Option<Vec<u8>>,
+    ) -> PyResult<Self> {
+        if !width.is_power_of_two() || !(2..=1 << MAX_LOG_WIDTH).contains(&width) {
+            return Err(pyo3::exceptions::PyValueError::new_err(
+                "KZG setup width must be a power of two from 2 to 4096",
+            ));
+        }
+        let log_width = width.trailing_zeros() as u8;
+        let tau = match secret {
+            Some(secret) => {
+                hash_to_scalar(NODE_PREFIX, &[b"RQKS tau".as_slice(), &secret].concat())
+            }
+            None => Scalar::random(rand::rngs::OsRng),
+        };
+        let kzg = py.allow_threads(|| -> PyResult<Kzg> {
+            // L_i(tau) = w^i (tau^W - 1) / (W (tau - w^i))
+            let domain = Kzg::new(log_width, Vec::new(), G2Affine::identity());
+            let vanishing = tau.pow_vartime([width as u64]) - Scalar::ONE;
+            let scale = Option::<Scalar>::from((Scalar::from(width as u64)).invert())
+                .expect("width is invertible")
+                * vanishing;
+            let lagrange = domain
+                .omega
+                .par_iter()
+                .map(|w| {
+                    let inverse = Option::<Scalar>::from((tau - w).invert()).ok_or_else(|| {
+                        pyo3::exceptions::PyValueError::new_err(
+                            "Secret gives a degenerate setup; use another",
+                        )
+                    })?;
+                    Ok(G1Projective::generator() * (scale * w * inverse))
+                })
+                .collect::<PyResult<_>>()?;
+            let tau_g2 = (G2Projective::generator() * tau).to_affine();
+            Ok(Kzg::new(log_width, lagrange, tau_g2))
+        })?;
+        Ok(KzgSetup { kzg: Arc::new(kzg) })
+    }
+
+    /// Children per node
+    #[getter]
+    fn width(&self) -> usize {
+        self.kzg.width()
+    }
+
+    fn to_bytes(&self) -> Vec<u8> {
+        let mut out = Vec::with_capacity(6 + self.kzg.width() * G1_BYTES + G2_BYTES);
+        out.extend_from_slice(SETUP_MAGIC);
+        out.push(FORMAT_VERSION);
+        out.push(self.kzg.log_width);
+        let mut affine = vec![G1Affine::identity(); self.kzg.width()];
+        G1Projective::batch_normalize(&self.kzg.lagrange, &mut affine);
+        for point in affine {
+            out.extend_from_slice(&point.to_compressed());
+        }
+        out.extend_from_slice(&self.kzg.tau_g2.to_compressed());
+        out
+    }
+
+    /// Loads a setup written by to_bytes, checking every point is in its group
+    #[classmethod]
+    fn from_bytes(_cls: &Bound<'_, PyType>, py: Python<'_>, data: Vec<u8>) -> PyResult<Self> {
+        let decode = || -> Result<Kzg, String> {
+            let mut reader = Reader::new(&data);
+            reader.header(SETUP_MAGIC, FORMAT_VERSION, "KZG setup")?;
+            let log_width = reader.u8()?;
+            if !(1..=MAX_LOG_WIDTH).contains(&log_width) {
+                return Err(format!("Unsupported KZG setup width 2^{}", log_width));
+            }
+            let raw = reader.bytes((G1_BYTES << log_width) + G2_BYTES)?;
+            reader.finish("KZG setup")?;
+            let (g1, g2) = raw.split_at(G1_BYTES << log_width);
+            let lagrange = g1
+                .par_chunks(G1_BYTES)
+                .map(g1_from)
+                .collect::<Option<_>>()
+                .ok_or("KZG setup holds an invalid G1 point")?;
+            let tau_g2 = Option::from(G2Affine::from_compressed(g2.try_into().expect("96 bytes")))
+                .ok_or("KZG setup holds an invalid G2 point")?;
+            Ok(Kzg::new(log_width, lagrange, tau_g2))
+        };
+        let kzg = py
+            .allow_threads(decode)
+            .map_err(pyo3::exceptions::PyValueError::new_err)?;
+        Ok(KzgSetup { kzg: Arc::new(kzg) })
+    }
+
+    fn __repr__(&self) -> String {
+        format!("KzgSetup(width={})", self.kzg.width())
+    }
+}
+
+/// Append-only Verkle tree: KZG commitments instead of hashes, for proofs of a few hundred
+/// bytes at any size. Proofs from get_proof check with verify_verkle_proof
+#[pyclass(module = "reliquary_merkle")]
+pub struct VerkleTree {
+    kzg: Arc<Kzg>,
+    /// `values[0]` are the leaf values and `values[l + 1]` those of `commitments[l]`, kept
+    /// once a level has more than one node.
+    values: Vec<Vec<Scalar>>,
+    /// `commitments[l][k]` commits to `values[l][k * W..(k + 1) * W]`.
+    commitments: Vec<Vec<G1Projective>>,
+}
+
+impl VerkleTree {
+    /// Sets `values[level][i]` for each entry of `changes` (appending new positions in
+    /// order) and carries the changed commitments up to the root.
+    fn apply(&mut self, mut changes: BTreeMap<usize, Scalar>) {
+        let width = self.kzg.width();
+        for level in 0.. {
+            if level == self.values.len() {
+                self.values.push(Vec::new());
+                self.commitments.push(Vec::new());
+            }
+            let values = &mut self.values[level];
+            let mut deltas: BTreeMap<usize, (Vec<G1Projective>, Vec<Scalar>)> = BTreeMap::new();
+            for (i, value) in changes {
+                let old = match values.get_mut(i) {
+                    Some(slot) => std::mem::replace(slot, value),
+                    None => {
+                        values.push(value);
+                        Scalar::ZERO
+                    }
+                };
+                let delta = deltas.entry(i / width).or_default();
+                delta.0.push(self.kzg.lagrange[i % width]);
+                delta.1.push(value - old);
+            }
+            let updated: Vec<(usize, G1Projective)> = deltas
+                .into_par_iter()
+                .map(|(k, (points, scalars))| (k, G1Projective::multi_exp(&points, &scalars)))
+                .collect();
+            let commitments = &mut self.commitments[level];
+            for &(k, delta) in &updated {
+                match commitments.get_mut(k) {
+                    Some(commitment) => *commitment += delta,
+                    None => commitments.push(delta),
+                }
+            }
+            let commitments = &self.commitments[level];
+            changes = if level + 1 < self.values.len() {
+                updated.iter().map(|&(k, _)| k).collect::<Vec<_>>()
+            } else if commitments.len() > 1 {
+                (0..commitments.len()).collect()
+            } else {
+                return;
+            }
+            .into_par_iter()
+            .map(|k| (k, node_value(&commitments[k])))
+            .collect();
+        }
+    }
+
+    fn size(&self) -> usize {
+        self.values.first().map_or(0, Vec::len)
+    }
+
+    fn push_leaves(&mut self, leaves: &[Vec<u8>]) {
+        let start = self.size();
+        let values: Vec<Scalar> = leaves
+            .par_iter()
+            .map(|leaf| hash_to_scalar(LEAF_PREFIX, leaf))
+            .collect();
+        self.apply((start..).zip(values).collect());
+    }
+}
+
+#[pymethods]
+impl VerkleTree {
+    #[new]
+    #[pyo3(signature = (setup, leaves = None))]
+    fn new(py: Python<'_>, setup: KzgSetup, leaves: Option<Vec<Vec<u8>>>) -> Self {
+        let mut tree = VerkleTree {
+            kzg: setup.kzg,
+            values: Vec::new(),
+            commitments: Vec::new(),
+        };
+        if let Some(leaves) = leaves {
+            py.allow_threads(|| tree.push_leaves(&leaves));
+        }
+        tree
+    }
+
+    #[getter]
+    fn setup(&self) -> KzgSetup {
+        KzgSetup {
+            kzg: self.kzg.clone(),
+        }
+    }
+
+    /// Appends a leaf; returns its index
+    fn append(&mut self, leaf: Vec<u8>) -> usize {
+        self.push_leaves(&[leaf]);
+        self.size() - 1
+    }
+
+    /// Appends leaves in order, updating each commitment once per call
+    fn extend(&mut self, py: Python<'_>, leaves: Vec<Vec<u8>>) {
+        py.allow_threads(|| self.push_leaves(&leaves));
+    }
+
+    /// Root commitment (48 bytes), or b'' for an empty tree
+    fn root(&self) -> Vec<u8> {
+        match self.commitments.last() {
+            Some(top) => top[0].to_affine().to_compressed().to_vec(),
+            None => Vec::new(),
+        }
+    }
+
+    /// Number of commitment levels a proof crosses
+    #[getter]
+    fn depth(&self) -> usize {
+        self.commitments.len()
+    }
+
+    /// Proof that leaf `index` is in the tree; check it with verify_verkle_proof
+    fn get_proof(&self, py: Python<'_>, index: usize) -> PyResult<Vec<u8>> {
+        if index >= self.size() {
+            return Err(pyo3::exceptions::PyIndexError::new_err(
+                "Leaf index out of range",
+            ));
+        }
+        let width = self.kzg.width();
+        let depth = self.commitments.len();
+        let openings: Vec<G1Projective> = py.allow_threads(|| {
+            (0..depth)
+                .into_par_iter()
+                .map(|level| {
+                    let node = index >> (level * self.kzg.log_width as usize);
+                    let start = (node / width) * width;
+                    let end = (start + width).min(self.values[level].len());
+                    self.kzg.open(&self.values[level][start..end], node % width)
+                })
+                .collect()
+        });
+        let mut out = Vec::with_capacity(15 + depth * 2 * G1_BYTES);
+        out.extend_from_slice(PROOF_MAGIC);
+        out.push(FORMAT_VERSION);
+        out.push(self.kzg.log_width);
+        out.extend_from_slice(&(index as u64).to_be_bytes());
+        out.push(depth as u8);
+        for (level, opening) in openings.iter().enumerate() {
+            if level + 1 < depth {
+                let node = index >> ((level + 1) * self.kzg.log_width as usize);
+                out.extend_from_slice(&self.commitments[level][node].to_affine().to_compressed());
+            }
+            out.extend_from_slice(&opening.to_affine().to_compressed());
+        }
+        Ok(out)
+    }
+
+    fn __len__(&self) -> usize {
+        self.size()
+    }
+
+    fn __repr__(&self) -> String {
+        let root: String = self.root().iter().map(|b| format!("{:02x}", b)).collect();
+        format!(
+            "VerkleTree(width={}, size={}, root={})",
+            self.kzg.width(),
+            self.size(),
+            if root.is_empty() { "None" } else { &root }
+        )
+    }
+}
+
+/// Verifies a VerkleTree proof that `data_block` is the leaf at the index it names, under
+/// `root` and `setup`. Malformed proofs, and proofs for another node width, fail
+#[pyfunction]
+pub fn verify_verkle_proof(
+    py: Python<'_>,
+    data_block: Vec<u8>,
+    proof: Vec<u8>,
+    root: Vec<u8>,
+    setup: KzgSetup,
+) -> bool {
+    let kzg = &setup.kzg;
+    let check = || -> Result<bool, String> {
+        let mut reader = Reader::new(&proof);
+        reader.header(PROOF_MAGIC, FORMAT_VERSION, "Verkle proof")?;
+        if reader.u8()? != kzg.log_width {
+            return Ok(false);
+        }
+        let index = reader.u64()?;
+        let depth = reader.u8()? as usize;
+        let bits = depth * kzg.log_width as usize;
+        if depth == 0 || (bits < 64 && index >> bits != 0) {
+            return Ok(false);
+        }
+        let Some(root) = g1_from(&root) else {
+            return Ok(false);
+        };
+        let mut value = hash_to_scalar(LEAF_PREFIX, &data_block);
+        for level in 0..depth {
+            let commitment = match level + 1 < depth {
+                true => g1_from(reader.bytes(G1_BYTES)?).ok_or("Invalid commitment")?,
+                false => root,
+            };
+            let opening = g1_from(reader.bytes(G1_BYTES)?).ok_or("Invalid opening")?;
+            let shift = level * kzg.log_width as usize;
+            let m = (index.checked_shr(shift as u32).unwrap_or(0) as usize) % kzg.width();
+            if !kzg.check(&commitment, m, &value, &opening.to_affine()) {
+                return Ok(false);
+            }
+            value = node_value(&commitment);
+        }
+        reader.finish("Verkle proof")?;
+        Ok(true)
+    };
+    py.allow_threads(check).unwrap_or(false)
+}
@@ -0,0 +1,100 @@
+//! Binary encoding for the serialized trees, roots and proofs. All integers are big-endian.
+
+pub(crate) fn put_var8(out: &mut Vec<u8>, bytes: &[u8]) -> Result<(), String> {
+    let len = u8::try_from(bytes.len()).map_err(|_| "Field exceeds 255 bytes".to_string())?;
+    out.push(len);
+    out.extend_from_slice(bytes);
+    Ok(())
+}
+
+/// Cursor over a serialized structure; every read fails cleanly on truncated input.
+pub(crate) struct Reader<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Reader<'a> {
+    pub(crate) fn new(data: &'a [u8]) -> Self {
+        Reader { data, pos: 0 }
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.pos == self.data.len()
+    }
+
+    /// Bytes read so far.
+    pub(crate) fn position(&self) -> usize {
+        self.pos
+    }
+
+    pub(crate) fn bytes(&mut self, n: usize) -> Result<&'a [u8], String> {
+        if self.data.len() - self.pos < n {
+            return Err("Input is truncated".to_string());
+        }
+        let out = &self.data[self.pos..self.pos + n];
+        self.pos += n;
+        Ok(out)
+    }
+
+    pub(crate) fn u8(&mut self) -> Result<u8, String> {
+        Ok(self.bytes(1)?[0])
+    }
+
+    pub(crate) fn u16(&mut self) -> Result<u16, String> {
+        let b = self.bytes(2)?;
+        Ok(u16::from_be_bytes(b.try_into().expect("2-byte slice")))
+    }
+
+    pub(crate) fn u32(&mut self) -> Result<u32, String> {
+        let b = self.bytes(4)?;
+        Ok(u32::from_be_bytes(b.try_into().expect("4-byte slice")))
+    }
+
+    pub(crate) fn u64(&mut self) -> Result<u64, String> {
+        let b = self.bytes(8)?;
+        Ok(u64::from_be_bytes(b.try_into().expect("8-byte slice")))
+    }
+
+    /// A u64 that must also fit in usize.
+    pub(crate) fn size(&mut self) -> Result<usize, String> {
+        usize::try_from(self.u64()?).map_err(|_| "Size does not fit this platform".to_string())
+    }
+
+    pub(crate) fn var8(&mut self) -> Result<&'a [u8], String> {
+        let len = self.u8()? as usize;
+        self.bytes(len)
+    }
+
+    /// `count` 32-byte hashes, checked against the remaining input before allocating.
+    pub(crate) fn hashes(&mut self, count: usize) -> Result<Vec<[u8; 32]>, String> {
+        let raw = self.bytes(count.checked_mul(32).ok_or("Input is truncated")?)?;
+        Ok(raw
+            .chunks_exact(32)
+            .map(|chunk| chunk.try_into().expect("32-byte chunk"))
+            .collect())
+    }
+
+    /// Checks the magic and format version that open every serialized structure.
+    pub(crate) fn header(
+        &mut self,
+        magic: &[u8; 4],
+        version: u8,
+        what: &str,
+    ) -> Result<(), String> {
+        if self.bytes(4).ok() != Some(&magic[..]) {
+            return Err(format!("Not a serialized {}", what));
+        }
+        match self.u8()? {
+            v if v == version => Ok(()),
+            v => Err(format!("Unsupported {} format version {}", what, v)),
+        }
+    }
+
+    pub(crate) fn finish(&self, what: &str) -> Result<(), String> {
+        if self.is_empty() {
+            Ok(())
+        } else {
+            Err(format!("Trailing data after {}", what))
+        }
+    }
+}
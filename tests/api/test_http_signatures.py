@@ -0,0 +1,61 @@
+# tests/api/test_http_signatures.py
+
+import pytest
+
+pytest.importorskip("fastapi")
+reliquary_encryptor = pytest.importorskip("reliquary_encryptor")
+from apps.api.middleware.http_signatures import (HTTPSignatureError, sign_request,
+                                                 signature_base, verify_request)
+
+# --- RFC 9421 request signatures over method, path, Content-Digest and created/expires ---
+
+BODY = b'{"vault_id":"v1","action":"unseal"}'
+NOW = 1_760_000_000
+
+def test_ed25519_request_signature():
+    pk, sk = reliquary_encryptor.generate_ed25519_keys()
+    keys = {"agent-7": (pk, "ed25519")}
+    headers = sign_request("POST", "/vaults/v1/unseal", {"Content-Type": "application/json"},
+                           BODY, "agent-7", sk, created=NOW)
+    assert headers["Signature-Input"].startswith(
+        'sig1=("@method" "@path" "content-digest");created=1760000000;expires=1760000300')
+
+    verified = verify_request("POST", "/vaults/v1/unseal", headers, BODY, keys,
+                              clock=lambda: NOW + 10)
+    assert verified.key_id == "agent-7" and verified.expires == NOW + 300
+
+    # Method, path and body are all bound to the signature
+    with pytest.raises(HTTPSignatureError, match="does not verify"):
+        verify_request("PUT", "/vaults/v1/unseal", headers, BODY, keys, clock=lambda: NOW)
+    with pytest.raises(HTTPSignatureError, match="does not verify"):
+        verify_request("POST", "/vaults/v2/unseal", headers, BODY, keys, clock=lambda: NOW)
+    with pytest.raises(HTTPSignatureError, match="Content-Digest"):
+        verify_request("POST", "/vaults/v1/unseal", headers, BODY + b" ", keys,
+                       clock=lambda: NOW)
+    with pytest.raises(HTTPSignatureError, match="expired"):
+        verify_request("POST", "/vaults/v1/unseal", headers, BODY, keys, max_age=3600,
+                       clock=lambda: NOW + 301)
+    with pytest.raises(HTTPSignatureError, match="not trusted"):
+        verify_request("POST", "/vaults/v1/unseal", headers, BODY, {}, clock=lambda: NOW)
+
+def test_falcon_and_required_components():
+    pk, sk = reliquary_encryptor.generate_falcon_keys()
+    headers = sign_request("GET", "/vaults?owner=alice", {"Host": "API.example.com"}, b"",
+                           "pq-1", sk, algorithm="falcon1024",
+                           components=("@method", "@authority", "@path", "@query"),
+                           created=NOW)
+    keys = {"pq-1": (pk, "falcon1024")}
+    with pytest.raises(HTTPSignatureError, match="content-digest"):
+        verify_request("GET", "/vaults?owner=alice", headers, b"", keys, clock=lambda: NOW)
+    verified = verify_request("GET", "/vaults?owner=alice", headers, b"", keys,
+                              required_components=("@method", "@path", "@query"),
+                              clock=lambda: NOW)
+    assert verified.algorithm == "falcon1024"
+
+def test_signature_base_layout():
+    base = signature_base("post", "/foo?param=Value", {"Content-Digest": "sha-256=:abc=:"},
+                          ["@method", "@path", "@query", "content-digest"],
+                          '("@method" "@path" "@query" "content-digest");created=1')
+    assert base == (b'"@method": POST\n"@path": /foo\n"@query": ?param=Value\n'
+                    b'"content-digest": sha-256=:abc=:\n'
+                    b'"@signature-params": ("@method" "@path" "@query" "content-digest");created=1')
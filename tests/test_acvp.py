@@ -0,0 +1,227 @@
+# tests/test_acvp.py
+
+import hashlib
+import hmac
+import json
+from pathlib import Path
+
+import pytest
+
+reliquary_encryptor = pytest.importorskip("reliquary_encryptor")
+
+
+def run(vector_set):
+    request = [{"acvVersion": "1.0"}, dict(vector_set, vsId=1, revision="1.0")]
+    response = json.loads(reliquary_encryptor.process_acvp(json.dumps(request)))
+    assert response[0] == {"acvVersion": "1.0"}
+    assert response[1]["vsId"] == 1
+    return {test["tcId"]: test
+            for group in response[1]["testGroups"] for test in group["tests"]}
+
+
+def group(tg_id, test_type, tests, **params):
+    return dict(params, tgId=tg_id, testType=test_type, tests=tests)
+
+# --- SHA-2 / SHA-3 ---
+
+def test_sha2_aft_known_answer():
+    results = run({"algorithm": "SHA2-256", "testGroups": [group(1, "AFT", [
+        {"tcId": 1, "msg": "616263", "len": 24},
+        {"tcId": 2, "msg": "00", "len": 0},
+    ])]})
+    assert results[1]["md"] == "BA7816BF8F01CFEA414140DE5DAE2223B00361A396177A9CB410FF61F20015AD"
+    assert results[2]["md"] == hashlib.sha256(b"").hexdigest().upper()
+
+@pytest.mark.parametrize("algorithm,name", [
+    ("SHA2-384", "sha384"), ("SHA2-512/256", "sha512_256"), ("SHA3-256", "sha3_256"),
+])
+def test_hash_aft_matches_hashlib(algorithm, name):
+    if name not in hashlib.algorithms_available:
+        pytest.skip(f"hashlib has no {name}")
+    msg = bytes(range(200))
+    results = run({"algorithm": algorithm, "testGroups": [group(1, "AFT", [
+        {"tcId": 1, "msg": msg.hex(), "len": len(msg) * 8},
+    ])]})
+    assert results[1]["md"] == hashlib.new(name, msg).hexdigest().upper()
+
+def test_sha2_mct():
+    seed = bytes(range(32))
+    results = run({"algorithm": "SHA2-256", "testGroups": [group(1, "MCT", [
+        {"tcId": 1, "msg": seed.hex(), "len": 256},
+    ])]})
+    expected = []
+    for _ in range(100):
+        md = [seed, seed, seed]
+        for _ in range(1000):
+            md = md[1:] + [hashlib.sha256(b"".join(md)).digest()]
+        seed = md[-1]
+        expected.append({"md": seed.hex().upper()})
+    assert results[1]["resultsArray"] == expected
+
+def test_sha3_mct():
+    md = bytes(range(32))
+    results = run({"algorithm": "SHA3-256", "testGroups": [group(1, "MCT", [
+        {"tcId": 1, "msg": md.hex(), "len": 256},
+    ])]})
+    expected = []
+    for _ in range(100):
+        for _ in range(1000):
+            md = hashlib.sha3_256(md).digest()
+        expected.append({"md": md.hex().upper()})
+    assert results[1]["resultsArray"] == expected
+
+def test_bit_oriented_messages_rejected():
+    with pytest.raises(ValueError, match="tcId 1: bit-oriented"):
+        run({"algorithm": "SHA2-256", "testGroups": [group(1, "AFT", [
+            {"tcId": 1, "msg": "80", "len": 1},
+        ])]})
+
+# --- HMAC ---
+
+def test_hmac_rfc4231_and_truncation():
+    key, msg = b"Jefe".hex(), b"what do ya want for nothing?".hex()
+    results = run({"algorithm": "HMAC-SHA2-256", "testGroups": [
+        group(1, "AFT", [{"tcId": 1, "key": key, "msg": msg}], macLen=256),
+        group(2, "AFT", [{"tcId": 2, "key": key, "msg": msg}], macLen=128),
+    ]})
+    full = "5BDCC146BF60754E6A042426089575C75A003F089D2739839DEC58B964EC3843"
+    assert results[1]["mac"] == full
+    assert results[2]["mac"] == full[:32]
+
+def test_hmac_sha3_matches_stdlib():
+    key, msg = bytes(range(100)), b"acvp"
+    results = run({"algorithm": "HMAC-SHA3-512", "testGroups": [
+        group(1, "AFT", [{"tcId": 1, "key": key.hex(), "msg": msg.hex()}], macLen=512),
+    ]})
+    assert results[1]["mac"] == hmac.new(key, msg, "sha3_512").hexdigest().upper()
+
+# --- AES-GCM ---
+
+def test_aes_gcm_encrypt_and_decrypt():
+    aead = pytest.importorskip("cryptography.hazmat.primitives.ciphers.aead")
+    key, iv, aad, pt = bytes(range(32)), bytes(12), b"header", b"plaintext bytes"
+    sealed = aead.AESGCM(key).encrypt(iv, pt, aad)
+    ct, tag = sealed[:-16], sealed[-16:]
+    params = {"keyLen": 256, "ivLen": 96, "ivGen": "external", "tagLen": 128,
+              "payloadLen": len(pt) * 8, "aadLen": len(aad) * 8}
+    results = run({"algorithm": "ACVP-AES-GCM", "testGroups": [
+        group(1, "AFT", [{"tcId": 1, "key": key.hex(), "iv": iv.hex(), "aad": aad.hex(),
+                          "pt": pt.hex()}], direction="encrypt", **params),
+        group(2, "AFT", [
+            {"tcId": 2, "key": key.hex(), "iv": iv.hex(), "aad": aad.hex(),
+             "ct": ct.hex(), "tag": tag.hex()},
+            {"tcId": 3, "key": key.hex(), "iv": iv.hex(), "aad": aad.hex(),
+             "ct": ct.hex(), "tag": bytes(16).hex()},
+        ], direction="decrypt", **params),
+    ]})
+    assert results[1] == {"tcId": 1, "ct": ct.hex().upper(), "tag": tag.hex().upper()}
+    assert results[2] == {"tcId": 2, "pt": pt.hex().upper()}
+    assert results[3] == {"tcId": 3, "testPassed": False}
+
+def test_aes_gcm_internal_iv_and_short_tag():
+    key = bytes(range(16))
+    results = run({"algorithm": "ACVP-AES-GCM", "testGroups": [group(1, "AFT", [
+        {"tcId": 1, "key": key.hex(), "aad": "", "pt": "00112233"},
+    ], direction="encrypt", keyLen=128, ivLen=96, ivGen="internal", tagLen=96)]})
+    assert len(bytes.fromhex(results[1]["iv"])) == 12
+    assert len(bytes.fromhex(results[1]["tag"])) == 12
+
+def test_aes_gcm_unsupported_tag_length():
+    with pytest.raises(ValueError, match="tagLen 64"):
+        run({"algorithm": "ACVP-AES-GCM", "testGroups": [group(1, "AFT", [
+            {"tcId": 1, "key": bytes(16).hex(), "iv": bytes(12).hex(), "aad": "", "pt": ""},
+        ], direction="encrypt", keyLen=128, ivLen=96, tagLen=64)]})
+
+# --- KDA HKDF ---
+
+def kda_group(test_type, tests):
+    return group(1, test_type, tests, kdfConfiguration={
+        "kdfType": "hkdf", "saltMethod": "random", "saltLen": 256,
+        "fixedInfoPattern": "literal[0102]||uPartyInfo||vPartyInfo||l",
+        "fixedInfoEncoding": "concatenation", "hmacAlg": "SHA2-256", "l": 256,
+    })
+
+def kda_test(tc_id, **extra):
+    return dict(extra, tcId=tc_id,
+                kdfParameter={"kdfType": "hkdf", "salt": "AA" * 32, "z": "BB" * 32, "l": 256},
+                fixedInfoPartyU={"partyId": "01" * 16},
+                fixedInfoPartyV={"partyId": "02" * 16, "ephemeralData": "03" * 8})
+
+def expected_dkm():
+    fixed_info = bytes.fromhex("0102" + "01" * 16 + "02" * 16 + "03" * 8) + (256).to_bytes(4, "big")
+    prk = hmac.new(bytes.fromhex("AA" * 32), bytes.fromhex("BB" * 32), "sha256").digest()
+    return hmac.new(prk, fixed_info + b"\x01", "sha256").digest()
+
+def test_kda_hkdf_aft_and_val():
+    dkm = expected_dkm()
+    aft = run({"algorithm": "KDA", "mode": "HKDF", "testGroups": [kda_group("AFT", [kda_test(1)])]})
+    assert aft[1]["dkm"] == dkm.hex().upper()
+
+    val = run({"algorithm": "KDA", "mode": "HKDF", "testGroups": [kda_group("VAL", [
+        kda_test(1, dkm=dkm.hex()),
+        kda_test(2, dkm=bytes(32).hex()),
+    ])]})
+    assert val[1]["testPassed"] is True
+    assert val[2]["testPassed"] is False
+
+# --- ML-KEM / ML-DSA: pinned NIST ACVP-Server vectors, no reference implementation needed ---
+
+FIPS_VECTORS = json.loads(
+    (Path(__file__).parent / "vectors" / "acvp_ml_kem_ml_dsa.json").read_text())["vectorSets"]
+# Answer fields per (mode, function); everything else in a test case is prompt
+ANSWERS = {
+    ("keyGen", None): {"ek", "dk", "pk", "sk"},
+    ("encapDecap", "encapsulation"): {"c", "k"},
+    ("encapDecap", "decapsulation"): {"k"},
+    ("sigGen", None): {"signature"},
+    ("sigVer", None): {"testPassed"},
+}
+
+
+@pytest.mark.parametrize("entry", FIPS_VECTORS, ids=[e["source"] for e in FIPS_VECTORS])
+def test_fips203_fips204_vectors(entry):
+    vs = entry["vectorSet"]
+    prompt = dict(vs, testGroups=[])
+    expected = {}
+    for tg in vs["testGroups"]:
+        answers = ANSWERS[vs["mode"], tg.get("function")]
+        tests = []
+        for test in tg["tests"]:
+            tests.append({k: v for k, v in test.items() if k not in answers | {"reason"}})
+            expected[test["tcId"]] = {k: v for k, v in test.items() if k in answers}
+        prompt["testGroups"].append(dict(tg, tests=tests))
+
+    results = run(prompt)
+    assert results.keys() == expected.keys()
+    for tc_id, answer in expected.items():
+        assert results[tc_id] == dict(answer, tcId=tc_id), tc_id
+
+
+def test_fips_vectors_cover_both_outcomes():
+    sig_ver = next(e for e in FIPS_VECTORS if e["vectorSet"]["mode"] == "sigVer")
+    outcomes = {t["testPassed"] for g in sig_ver["vectorSet"]["testGroups"] for t in g["tests"]}
+    assert outcomes == {True, False}
+
+
+def test_ml_kem_ml_dsa_parameter_sets():
+    with pytest.raises(ValueError, match="unsupported parameterSet 'ML-KEM-512'"):
+        run({"algorithm": "ML-KEM", "mode": "keyGen", "testGroups": [group(
+            1, "AFT", [{"tcId": 1, "d": "00" * 32, "z": "00" * 32}],
+            parameterSet="ML-KEM-512")]})
+    with pytest.raises(ValueError, match="only pure ML-DSA is supported"):
+        run({"algorithm": "ML-DSA", "mode": "sigVer", "testGroups": [group(
+            1, "AFT", [{"tcId": 1, "message": "00", "signature": "00", "pk": "00"}],
+            parameterSet="ML-DSA-87", signatureInterface="external", preHash="preHash")]})
+
+# --- Requests ---
+
+def test_bare_vector_set_and_algorithm_listing():
+    request = {"vsId": 7, "algorithm": "SHA2-256", "testGroups": [
+        group(3, "AFT", [{"tcId": 9, "msg": "616263", "len": 24}])]}
+    response = json.loads(reliquary_encryptor.process_acvp(json.dumps(request)))
+    assert response["vsId"] == 7 and response["testGroups"][0]["tgId"] == 3
+    assert {"KDA", "ML-KEM", "ML-DSA"} <= set(reliquary_encryptor.acvp_algorithms())
+
+def test_unsupported_algorithm():
+    with pytest.raises(ValueError, match="unsupported algorithm 'SLH-DSA'"):
+        run({"algorithm": "SLH-DSA", "mode": "keyGen", "testGroups": []})
@@ -0,0 +1,83 @@
+# tests/test_akem.py
+
+import pytest
+
+reliquary_encryptor = pytest.importorskip("reliquary_encryptor")
+
+# --- Authenticated Kyber encapsulation: sender, recipient and context binding ---
+
+KyberParams = reliquary_encryptor.KyberParams
+FalconParams = reliquary_encryptor.FalconParams
+CONTEXT = b"vault-sync/v1"
+
+
+@pytest.fixture(scope="module")
+def parties():
+    recipient = reliquary_encryptor.generate_kyber_keys(KyberParams.Kyber1024)
+    sender = reliquary_encryptor.generate_falcon_keys(FalconParams.Falcon1024)
+    return recipient, sender
+
+
+def test_round_trip(parties):
+    (recipient_pk, recipient_sk), (sender_pk, sender_sk) = parties
+    secret, auth_ct = reliquary_encryptor.auth_encapsulate_kyber(
+        recipient_pk, sender_sk, sender_pk, CONTEXT)
+    assert len(secret) == 32 and auth_ct[:5] == b"RQAK\x01"
+    assert reliquary_encryptor.auth_decapsulate_kyber(
+        auth_ct, recipient_sk, sender_pk, CONTEXT) == secret
+    # The bound secret differs from the raw KEM secret for the same ciphertext
+    kem_ct = auth_ct[7:7 + 1568]
+    assert reliquary_encryptor.decapsulate_kyber(kem_ct, recipient_sk) != secret
+
+    small_pk, small_sk = reliquary_encryptor.generate_falcon_keys(FalconParams.Falcon512)
+    secret, auth_ct = reliquary_encryptor.auth_encapsulate_kyber(
+        recipient_pk, small_sk, small_pk, b"", falcon_params=FalconParams.Falcon512)
+    assert reliquary_encryptor.auth_decapsulate_kyber(
+        auth_ct, recipient_sk, small_pk, b"") == secret
+
+
+def test_wrong_context(parties):
+    (recipient_pk, recipient_sk), (sender_pk, sender_sk) = parties
+    _, auth_ct = reliquary_encryptor.auth_encapsulate_kyber(
+        recipient_pk, sender_sk, sender_pk, CONTEXT)
+    for context in (b"vault-sync/v2", b"", CONTEXT + b"\x00"):
+        with pytest.raises(ValueError, match="Sender authentication failed"):
+            reliquary_encryptor.auth_decapsulate_kyber(auth_ct, recipient_sk, sender_pk, context)
+
+
+def test_wrong_sender_or_recipient(parties):
+    (recipient_pk, recipient_sk), (sender_pk, sender_sk) = parties
+    _, auth_ct = reliquary_encryptor.auth_encapsulate_kyber(
+        recipient_pk, sender_sk, sender_pk, CONTEXT)
+    other_sender_pk, other_sender_sk = reliquary_encryptor.generate_falcon_keys()
+    with pytest.raises(ValueError, match="Sender authentication failed"):
+        reliquary_encryptor.auth_decapsulate_kyber(auth_ct, recipient_sk, other_sender_pk,
+                                                   CONTEXT)
+    _, other_recipient_sk = reliquary_encryptor.generate_kyber_keys(KyberParams.Kyber1024)
+    with pytest.raises(ValueError, match="Sender authentication failed"):
+        reliquary_encryptor.auth_decapsulate_kyber(auth_ct, other_recipient_sk, sender_pk,
+                                                   CONTEXT)
+    # A secret key paired with someone else's public key is caught at encapsulation
+    with pytest.raises(ValueError, match="does not match the secret key"):
+        reliquary_encryptor.auth_encapsulate_kyber(recipient_pk, other_sender_sk, sender_pk,
+                                                   CONTEXT)
+
+
+def test_tampered_ciphertexts(parties):
+    (recipient_pk, recipient_sk), (sender_pk, sender_sk) = parties
+    _, auth_ct = reliquary_encryptor.auth_encapsulate_kyber(
+        recipient_pk, sender_sk, sender_pk, CONTEXT)
+    flipped = bytearray(auth_ct)
+    flipped[7] ^= 1
+    with pytest.raises(ValueError, match="Sender authentication failed"):
+        reliquary_encryptor.auth_decapsulate_kyber(bytes(flipped), recipient_sk, sender_pk,
+                                                   CONTEXT)
+    with pytest.raises(ValueError, match="Not an authenticated KEM ciphertext"):
+        reliquary_encryptor.auth_decapsulate_kyber(b"XXXX" + auth_ct[4:], recipient_sk,
+                                                   sender_pk, CONTEXT)
+    with pytest.raises(ValueError, match="Unknown Falcon parameter set"):
+        reliquary_encryptor.auth_decapsulate_kyber(auth_ct[:6] + b"\x04" + auth_ct[7:],
+                                                   recipient_sk, sender_pk, CONTEXT)
+    with pytest.raises(ValueError, match="truncated"):
+        reliquary_encryptor.auth_decapsulate_kyber(auth_ct[:7 + 1568], recipient_sk,
+                                                   sender_pk, CONTEXT)
@@ -0,0 +1,65 @@
+# tests/test_anomaly_detection.py
+
+import time
+
+import pytest
+
+anomaly = pytest.importorskip("zk.anomaly")
+trust_engine = pytest.importorskip("zk.trust_engine")
+
+# --- Streaming anomaly signals: EWMA failure spikes and rare usage hours ---
+
+WINDOW = 300
+NOON = 1_700_049_600  # 12:00 UTC
+
+class Sink:
+    def __init__(self):
+        self.entries = []
+
+    def add_entry(self, entry):
+        self.entries.append(entry)
+
+def test_failure_spike_flagged_once_per_window():
+    sink = Sink()
+    detector = anomaly.AnomalyDetector(window_seconds=WINDOW, audit_logger=sink)
+    # A steady baseline of one failed unseal per window
+    for i in range(10):
+        assert detector.record("unseal", key_id="k1", success=False,
+                               timestamp=NOON + i * WINDOW) == []
+
+    burst = NOON + 10 * WINDOW
+    raised = [flag for i in range(8)
+              for flag in detector.record("unseal", key_id="k1", tenant="acme",
+                                          success=False, timestamp=burst + i)]
+    spikes = [flag for flag in raised if flag.kind == anomaly.AnomalyKind.FAILURE_SPIKE]
+    assert [(flag.subject_type, flag.subject) for flag in spikes] == [("key", "k1")]
+    assert spikes[0].observed == 5.0
+    assert [entry["event"] for entry in sink.entries] == ["anomaly_detected"]
+    assert detector.active_flags(key_id="k1", now=burst + 60) == spikes
+    assert detector.active_flags(key_id="k1", now=burst + 7200) == []
+
+def test_unusual_hour_after_warmup():
+    detector = anomaly.AnomalyDetector(min_hour_observations=20)
+    for day in range(30):
+        assert detector.record("sign", tenant="acme", timestamp=NOON + day * 86400) == []
+    flags = detector.record("sign", tenant="acme", timestamp=NOON + 15 * 3600)
+    assert [(flag.kind, flag.observed) for flag in flags] == [(anomaly.AnomalyKind.UNUSUAL_HOUR, 3.0)]
+
+def test_flags_feed_trust_engine_and_replay(tmp_path):
+    detector = anomaly.AnomalyDetector(min_hour_observations=20)
+    engine = trust_engine.TrustScoringEngine(tmp_path, anomaly_detector=detector,
+                                             audit_log_path=tmp_path / "trust_scoring.log")
+    context = {"device_verified": True, "timestamp_verified": True, "location_verified": True,
+               "pattern_verified": True, "key_id": "k1"}
+    baseline = engine.evaluate_trust("dave", context)
+
+    # Usage history twelve hours of day away from now, then one use now
+    now = time.time()
+    for day in range(1, 31):
+        detector.record("unseal", key_id="k1", timestamp=now - day * 86400 + 12 * 3600)
+    assert detector.record("unseal", key_id="k1", timestamp=now - 1)
+    flagged = engine.evaluate_trust("dave", context)
+
+    assert flagged.trust_metrics.risk_indicators == baseline.trust_metrics.risk_indicators - 15.0
+    assert "anomaly_flags" in [hit["rule"] for hit in flagged.explanation.rule_hits]
+    assert engine.replay_decision(flagged.decision_record)["reproduced"]
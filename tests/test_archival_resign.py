@@ -0,0 +1,106 @@
+# tests/test_archival_resign.py
+
+import pytest
+
+reliquary_encryptor = pytest.importorskip("reliquary_encryptor")
+from core.archival import (ArchivalError, ArchivalPolicy, ArchivalResigner,
+                           DirectoryBundleStore, InMemoryBundleStore, verify_archival_chain)
+from core.heartbeat import SECONDS_PER_DAY
+
+# --- Archival re-signing of signature bundles ---
+
+YEAR = 365 * SECONDS_PER_DAY
+SIGNED_AT = 1_600_000_000
+
+
+class ListLogger:
+    def __init__(self):
+        self.entries = []
+
+    def add_entry(self, entry):
+        self.entries.append(entry)
+
+
+@pytest.fixture(scope="module")
+def keys():
+    ed_pk, ed_sk = reliquary_encryptor.generate_ed25519_keys()
+    falcon_pk, falcon_sk = reliquary_encryptor.generate_falcon_keys()
+    return {"ed25519": (ed_pk, ed_sk), "falcon1024": (falcon_pk, falcon_sk)}
+
+
+def bundle(keys, message, scheme="ed25519", years=5):
+    pk, sk = keys[scheme]
+    return reliquary_encryptor.create_signature_bundle(
+        message, sk, pk, scheme, "records-service",
+        not_before=SIGNED_AT, not_after=int(SIGNED_AT + years * YEAR))
+
+
+def resigner(keys, store, **kwargs):
+    pk, sk = keys["falcon1024"]
+    return ArchivalResigner(store, sk, pk, **kwargs)
+
+
+def test_deprecated_and_expiring_bundles_are_countersigned(keys):
+    store = InMemoryBundleStore()
+    deed = bundle(keys, b"deed")
+    store.put("deed", deed, b"deed")
+    store.put("fresh", bundle(keys, b"fresh", "falcon1024", years=20), b"fresh")
+    store.put("old-falcon", bundle(keys, b"old", "falcon1024", years=1), b"old")
+    audit = ListLogger()
+    maintainer = resigner(keys, store, audit_logger=audit)
+
+    now = SIGNED_AT + 1 * YEAR - 30 * SECONDS_PER_DAY
+    planned = maintainer.run(dry_run=True, now=now)
+    assert {(r.bundle_id, r.action, tuple(r.reasons)) for r in planned} == {
+        ("deed", "would_resign", ("deprecated_scheme",)),
+        ("old-falcon", "would_resign", ("expiring",)),
+    }
+    assert store.get("deed") == deed and not audit.entries
+
+    results = maintainer.run(now=now)
+    assert sorted(r.action for r in results) == ["resigned", "resigned"]
+    assert [e["bundle_id"] for e in sorted(audit.entries, key=lambda e: e["bundle_id"])] == \
+        ["deed", "old-falcon"]
+    assert maintainer.scan(now=now) == []
+
+    # Years later the ed25519 layer is outside its own window, but it was in force when the
+    # falcon layer covered it
+    later = SIGNED_AT + 5.5 * YEAR
+    plain = reliquary_encryptor.verify_signature_bundle(store.get("deed"), b"deed", int(later))
+    assert [layer[5] for layer in plain] == [False, True]
+    chain = verify_archival_chain(store.get("deed"), b"deed", later)
+    assert [(layer[1], layer[5]) for layer in chain] == [("ed25519", True), ("falcon1024", True)]
+
+
+def test_lapsed_or_broken_bundles_are_not_resigned(keys):
+    store = InMemoryBundleStore()
+    store.put("lapsed", bundle(keys, b"lapsed", "falcon1024", years=1), b"lapsed")
+    store.put("tampered", bundle(keys, b"original"), b"tampered")
+    maintainer = resigner(keys, store)
+
+    results = {r.bundle_id: r for r in maintainer.run(now=SIGNED_AT + 2 * YEAR)}
+    assert results["lapsed"].action == "skipped"
+    assert results["lapsed"].reasons == ["lapsed"]
+    assert results["tampered"].action == "skipped"
+    assert "broken" in results["tampered"].reasons
+    assert len(reliquary_encryptor.verify_signature_bundle(store.get("lapsed"), b"lapsed")) == 1
+
+
+def test_policy_and_directory_store(keys, tmp_path):
+    with pytest.raises(ArchivalError, match="deprecated"):
+        resigner(keys, InMemoryBundleStore(),
+                 policy=ArchivalPolicy(deprecated_schemes=frozenset({"falcon1024"})))
+
+    (tmp_path / "contracts").mkdir()
+    (tmp_path / "contracts" / "lease.pdf").write_bytes(b"lease")
+    (tmp_path / "contracts" / "lease.pdf.rqsb").write_bytes(bundle(keys, b"lease"))
+    (tmp_path / "orphan.rqsb").write_bytes(bundle(keys, b"orphan"))
+    store = DirectoryBundleStore(str(tmp_path))
+
+    results = resigner(keys, store).run(now=SIGNED_AT + YEAR)
+    assert [(r.bundle_id, r.action, r.layers) for r in results] == \
+        [("contracts/lease.pdf", "resigned", 2)]
+    resigned = (tmp_path / "contracts" / "lease.pdf.rqsb").read_bytes()
+    report = reliquary_encryptor.verify_signature_bundle(resigned, b"lease", int(SIGNED_AT + YEAR))
+    assert [(layer[0], layer[1], layer[5]) for layer in report] == [
+        ("records-service", "ed25519", True), ("archival-resigner", "falcon1024", True)]
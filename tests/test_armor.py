@@ -0,0 +1,73 @@
+# tests/test_armor.py
+
+import pytest
+
+reliquary_encryptor = pytest.importorskip("reliquary_encryptor")
+
+# --- ASCII armor: type labels, headers, line wrapping and CRC-24 checksums ---
+
+ArmorType = reliquary_encryptor.ArmorType
+ARMOR_TYPES = [ArmorType.PublicKey, ArmorType.SecretKey, ArmorType.Signature,
+               ArmorType.Ciphertext, ArmorType.Envelope, ArmorType.MerkleProof, ArmorType.Share]
+DATA = bytes(range(256)) * 2
+
+
+@pytest.mark.parametrize("kind", ARMOR_TYPES)
+def test_round_trip(kind):
+    text = reliquary_encryptor.armor(DATA, kind, {"Version": "1", "Comment": "test"})
+    lines = text.splitlines()
+    assert lines[0] == f"-----BEGIN RELIQUARY {kind.label}-----"
+    assert lines[-1] == f"-----END RELIQUARY {kind.label}-----"
+    assert lines[1:3] == ["Comment: test", "Version: 1"]
+    assert reliquary_encryptor.dearmor(text) == (kind, {"Comment": "test", "Version": "1"}, DATA)
+    assert reliquary_encryptor.dearmor(text, kind)[2] == DATA
+
+
+def test_line_wrapping_and_surrounding_text():
+    text = reliquary_encryptor.armor(DATA, ArmorType.Signature)
+    body = text.splitlines()[2:-2]
+    assert all(len(line) == 64 for line in body[:-1]) and 0 < len(body[-1]) <= 64
+    # Email bodies add text around the block and may use CRLF
+    wrapped = "Hi,\r\n\r\n" + text.replace("\n", "\r\n") + "\r\nRegards\r\n"
+    assert reliquary_encryptor.dearmor(wrapped)[2] == DATA
+    assert reliquary_encryptor.dearmor(reliquary_encryptor.armor(b"", ArmorType.Share))[2] == b""
+
+
+def test_checksum_failures():
+    text = reliquary_encryptor.armor(DATA, ArmorType.Ciphertext)
+    lines = text.splitlines(keepends=True)
+    checksum = next(n for n, line in enumerate(lines) if line.startswith("="))
+
+    # One altered body character decodes to different bytes
+    body = lines[2]
+    altered = lines[:2] + [("B" if body[0] == "A" else "A") + body[1:]] + lines[3:]
+    with pytest.raises(ValueError, match="CRC-24 checksum mismatch"):
+        reliquary_encryptor.dearmor("".join(altered))
+    # A checksum from other data
+    other = reliquary_encryptor.armor(DATA[:-1], ArmorType.Ciphertext).splitlines(keepends=True)
+    swapped = lines[:checksum] + [next(line for line in other if line.startswith("="))]
+    with pytest.raises(ValueError, match="CRC-24 checksum mismatch"):
+        reliquary_encryptor.dearmor("".join(swapped + lines[checksum + 1:]))
+    with pytest.raises(ValueError, match="missing its CRC-24 checksum"):
+        reliquary_encryptor.dearmor("".join(lines[:checksum] + lines[checksum + 1:]))
+    with pytest.raises(ValueError, match="data after checksum"):
+        reliquary_encryptor.dearmor("".join(lines[:checksum + 1] + ["AAAA\n"] + lines[-1:]))
+
+
+def test_malformed_blocks():
+    text = reliquary_encryptor.armor(DATA, ArmorType.PublicKey)
+    with pytest.raises(ValueError, match="Expected armored SECRET KEY, found PUBLIC KEY"):
+        reliquary_encryptor.dearmor(text, ArmorType.SecretKey)
+    with pytest.raises(ValueError, match="No RELIQUARY armor block"):
+        reliquary_encryptor.dearmor("nothing here")
+    with pytest.raises(ValueError, match="Unknown armor type 'PRIVATE KEY'"):
+        reliquary_encryptor.dearmor(text.replace("PUBLIC KEY", "PRIVATE KEY"))
+    with pytest.raises(ValueError, match="does not match BEGIN"):
+        reliquary_encryptor.dearmor(text.replace("END RELIQUARY PUBLIC", "END RELIQUARY SECRET"))
+    with pytest.raises(ValueError, match="missing its END line"):
+        reliquary_encryptor.dearmor(text.rsplit("-----END", 1)[0])
+
+    with pytest.raises(ValueError, match="Invalid armor header name"):
+        reliquary_encryptor.armor(DATA, ArmorType.PublicKey, {"Bad:Name": "x"})
+    with pytest.raises(ValueError, match="must be a single line"):
+        reliquary_encryptor.armor(DATA, ArmorType.PublicKey, {"Comment": "two\nlines"})
@@ -0,0 +1,102 @@
+# tests/test_audit_log.py
+
+import hashlib
+import struct
+
+import pytest
+
+reliquary_merkle = pytest.importorskip("reliquary_merkle")
+from core.crypto.tree_head import sign_tree_head, verify_tree_head
+
+# --- AuditLog: hash-chained entries with Merkle checkpoints ---
+
+T0 = 1_700_000_000_000
+EVENTS = [f'{{"event": "vault_read", "n": {n}}}'.encode() for n in range(10)]
+
+
+def filled_log(**kwargs):
+    log = reliquary_merkle.AuditLog(**kwargs)
+    for n, event in enumerate(EVENTS):
+        log.append(event, T0 + n)
+    return log
+
+
+def test_entries_chain_and_encode():
+    log = filled_log()
+    first, second = log.entry(0), log.entry(1)
+    assert first.prev_hash == bytes(32) and second.prev_hash == first.chain_hash
+    assert first.to_bytes() == struct.pack(">QQ", 0, T0) + bytes(32) + EVENTS[0]
+    assert first.chain_hash == hashlib.sha256(first.to_bytes()).digest()
+    assert reliquary_merkle.AuditEntry.from_bytes(second.to_bytes()) == second
+    assert log.chain_head == log.entry(9).chain_hash and len(log) == 10
+    assert log.pending == 10 and log.latest_checkpoint is None
+    with pytest.raises(ValueError, match="go backwards"):
+        log.append(b"late", T0)
+    with pytest.raises(ValueError, match="truncated"):
+        reliquary_merkle.AuditEntry.from_bytes(bytes(47))
+
+
+def test_checkpoints_prove_entries():
+    log = filled_log()
+    early = log.checkpoint(T0 + 20)
+    assert (early.tree_size, early.chain_head, log.pending) == (10, log.chain_head, 0)
+    assert early.root == reliquary_merkle.create_merkle_root(
+        [entry.to_bytes() for entry in log.entries()], 2)
+    log.append(b"after", T0 + 30)
+    late = log.checkpoint(T0 + 40)
+    assert log.checkpoints() == [early, late]
+
+    # Proofs against the latest checkpoint, or an earlier one a client still holds
+    for checkpoint in (early, late):
+        proof = log.prove(4, checkpoint)
+        assert checkpoint.verify_entry(log.entry(4), proof)
+        assert not checkpoint.verify_entry(log.entry(5), proof)
+    assert not early.verify_entry(log.entry(4), log.prove(4))
+    assert log.prove_consistency(early).verify_consistency(early.root, late.root)
+    with pytest.raises(IndexError, match="not covered"):
+        log.prove(10, early)
+    with pytest.raises(ValueError, match="not from this audit log"):
+        log.prove(0, filled_log().checkpoint(T0 + 21))
+    with pytest.raises(ValueError, match="no checkpoint"):
+        reliquary_merkle.AuditLog().prove(0)
+    with pytest.raises(ValueError, match="at least one entry"):
+        reliquary_merkle.AuditLog().checkpoint()
+
+
+def test_chain_continuity():
+    log = filled_log()
+    checkpoint = log.checkpoint(T0 + 20)
+    entries = log.entries()
+    assert log.verify_chain()
+    assert reliquary_merkle.verify_audit_chain(entries, checkpoint)
+    # A run from the middle links up to the checkpoint on its own
+    assert reliquary_merkle.verify_audit_chain(log.entries(6), checkpoint)
+    assert not reliquary_merkle.verify_audit_chain(entries[:-1], checkpoint)
+    assert not reliquary_merkle.verify_audit_chain(entries[:4] + entries[5:])
+    assert not reliquary_merkle.verify_audit_chain([entries[1], entries[0]])
+
+    # Rewriting an entry, even with its original chain links, breaks the chain after it
+    forged = reliquary_merkle.AuditEntry.from_bytes(entries[3].to_bytes()[:48] + b"forged")
+    assert not reliquary_merkle.verify_audit_chain(entries[:3] + [forged] + entries[4:])
+    assert reliquary_merkle.verify_audit_chain([])
+    assert not reliquary_merkle.verify_audit_chain([], checkpoint)
+
+
+def test_automatic_checkpoints_and_signing():
+    log = filled_log(checkpoint_interval=4, hash_algorithm="blake3")
+    assert [c.tree_size for c in log.checkpoints()] == [4, 8] and log.pending == 2
+    checkpoint = log.latest_checkpoint
+    assert checkpoint.timestamp == T0 + 7
+    assert reliquary_merkle.AuditCheckpoint.from_bytes(checkpoint.to_bytes()) == checkpoint
+    assert checkpoint.tree_root() == reliquary_merkle.MerkleRoot(checkpoint.root, 8)
+
+    reliquary_encryptor = pytest.importorskip("reliquary_encryptor")
+    pk, sk = reliquary_encryptor.generate_ed25519_keys()
+    head = sign_tree_head(checkpoint, sk, "ed25519", timestamp=checkpoint.timestamp)
+    assert verify_tree_head(head, pk) and head.root == checkpoint.root
+
+    with pytest.raises(ValueError, match="checkpoint_interval"):
+        reliquary_merkle.AuditLog(checkpoint_interval=0)
+    with pytest.raises(ValueError, match="at least one entry"):
+        reliquary_merkle.AuditCheckpoint.from_bytes(
+            checkpoint.to_bytes()[:5] + bytes(8) + checkpoint.to_bytes()[13:])
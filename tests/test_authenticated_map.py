@@ -0,0 +1,50 @@
+# tests/test_authenticated_map.py
+
+import hashlib
+
+import pytest
+
+reliquary_merkle = pytest.importorskip("reliquary_merkle")
+from core.crypto import pure_verify
+
+# --- AuthenticatedMap: committed key-value state with inclusion and absence proofs ---
+
+TRUST = {b"device-17": b"82", b"device-4": b"40", b"device-250": b"97"}
+
+
+def test_get_put_delete_and_items():
+    state = reliquary_merkle.AuthenticatedMap(TRUST)
+    assert len(state) == 3 and b"device-4" in state
+    assert state.get(b"device-17") == b"82" and state.get(b"device-1") is None
+    state.put(b"device-17", b"85")
+    state.put(b"policy/export", b"deny")
+    assert state.items() == sorted({**TRUST, b"device-17": b"85", b"policy/export": b"deny"}.items())
+    assert state.delete(b"policy/export") and not state.delete(b"policy/export")
+    # Canonical: the root only depends on the contents, however they got there
+    assert state.root() == reliquary_merkle.AuthenticatedMap(
+        [(b"device-250", b"97"), (b"device-4", b"40"), (b"device-17", b"85")]).root()
+    assert reliquary_merkle.AuthenticatedMap().root() == bytes(32)
+
+
+def test_proofs_of_values_and_absence():
+    state = reliquary_merkle.AuthenticatedMap(TRUST)
+    root = state.root()
+    for verify in (reliquary_merkle.verify_map_proof, pure_verify.verify_map_proof):
+        for key, value in TRUST.items():
+            proof = state.prove(key)
+            assert verify(key, value, proof, root)
+            assert not verify(key, b"0", proof, root)
+            assert not verify(key, None, proof, root)
+        absent = state.prove(b"device-1")
+        assert verify(b"device-1", None, absent, root)
+        assert not verify(b"device-1", b"82", absent, root)
+
+
+def test_keys_are_hashed_apart_from_tree_nodes():
+    # A map is a sparse tree over SHA-256(0x02 | key); the raw path is not a valid key
+    state = reliquary_merkle.AuthenticatedMap(TRUST)
+    path = hashlib.sha256(b"\x02device-4").digest()
+    tree = reliquary_merkle.SparseMerkleTree([(hashlib.sha256(b"\x02" + k).digest(), v)
+                                              for k, v in TRUST.items()])
+    assert tree.root() == state.root()
+    assert not reliquary_merkle.verify_map_proof(path, b"40", state.prove(path), state.root())
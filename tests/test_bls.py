@@ -0,0 +1,91 @@
+# tests/test_bls.py
+
+import pytest
+
+reliquary_encryptor = pytest.importorskip("reliquary_encryptor")
+
+# --- BLS12-381 signatures: key generation, proofs of possession and aggregation ---
+
+# EIP-2333 test case 0: KeyGen on this seed yields the master secret key
+EIP2333_SEED = bytes.fromhex(
+    "c55257c360c07c72029aebc1b53c05ed0362ada38ead3e3e9efa3708e53495531f09a6987599d18264c1e1c92f"
+    "2cf141630c7a3c4ab7c81b2f001698e7463b04")
+EIP2333_MASTER_SK = 6083874454709270928345386274498605044986640685124978867557563392430687146096
+
+
+@pytest.fixture(scope="module")
+def agents():
+    return [reliquary_encryptor.generate_bls_keys() for _ in range(3)]
+
+
+def test_key_generation():
+    pk, sk = reliquary_encryptor.generate_bls_keys(EIP2333_SEED)
+    assert int.from_bytes(sk, "big") == EIP2333_MASTER_SK
+    assert len(pk) == 48 and len(sk) == 32
+    assert reliquary_encryptor.generate_bls_keys(EIP2333_SEED) == (pk, sk)
+    # Fresh keys without ikm
+    assert reliquary_encryptor.generate_bls_keys() != reliquary_encryptor.generate_bls_keys()
+    with pytest.raises(ValueError, match="at least 32 bytes"):
+        reliquary_encryptor.generate_bls_keys(b"\x01" * 31)
+
+
+def test_sign_and_verify(agents):
+    pk, sk = agents[0]
+    signature = reliquary_encryptor.sign_bls(b"decision", sk)
+    assert len(signature) == 96
+    assert reliquary_encryptor.sign_bls(b"decision", sk) == signature
+    assert reliquary_encryptor.verify_bls(b"decision", signature, pk)
+    assert not reliquary_encryptor.verify_bls(b"other decision", signature, pk)
+    assert not reliquary_encryptor.verify_bls(b"decision", signature, agents[1][0])
+    assert not reliquary_encryptor.verify_bls(b"decision", b"\x00" * 96, pk)
+    with pytest.raises(ValueError, match="Invalid BLS public key"):
+        reliquary_encryptor.verify_bls(b"decision", signature, b"\x00" * 48)
+    with pytest.raises(ValueError, match="Invalid BLS secret key"):
+        reliquary_encryptor.sign_bls(b"decision", b"\xff" * 32)
+
+
+def test_proof_of_possession(agents):
+    pk, sk = agents[0]
+    proof = reliquary_encryptor.prove_bls_possession(sk)
+    assert reliquary_encryptor.verify_bls_possession(pk, proof)
+    assert not reliquary_encryptor.verify_bls_possession(agents[1][0], proof)
+    # Proofs live in their own domain: a signature over the public key isn't one
+    assert not reliquary_encryptor.verify_bls_possession(
+        pk, reliquary_encryptor.sign_bls(pk, sk))
+
+
+def test_fast_aggregate_same_message(agents):
+    public_keys = [pk for pk, _ in agents]
+    signatures = [reliquary_encryptor.sign_bls(b"decision", sk) for _, sk in agents]
+    aggregate = reliquary_encryptor.aggregate_bls_signatures(signatures)
+    assert len(aggregate) == 96
+    assert reliquary_encryptor.aggregate_bls_signatures(signatures[::-1]) == aggregate
+    assert reliquary_encryptor.fast_aggregate_verify_bls(b"decision", aggregate, public_keys)
+    assert not reliquary_encryptor.fast_aggregate_verify_bls(b"other decision", aggregate,
+                                                             public_keys)
+    # Every signer's key is needed, and no extra ones
+    assert not reliquary_encryptor.fast_aggregate_verify_bls(b"decision", aggregate,
+                                                             public_keys[:2])
+    assert not reliquary_encryptor.fast_aggregate_verify_bls(b"decision", aggregate, [])
+    # One agent signing something else spoils the aggregate
+    mixed = reliquary_encryptor.aggregate_bls_signatures(
+        signatures[:2] + [reliquary_encryptor.sign_bls(b"other decision", agents[2][1])])
+    assert not reliquary_encryptor.fast_aggregate_verify_bls(b"decision", mixed, public_keys)
+
+
+def test_aggregate_distinct_messages(agents):
+    messages = [f"vote {n}".encode() for n in range(len(agents))]
+    public_keys = [pk for pk, _ in agents]
+    aggregate = reliquary_encryptor.aggregate_bls_signatures(
+        [reliquary_encryptor.sign_bls(msg, sk) for msg, (_, sk) in zip(messages, agents)])
+    assert reliquary_encryptor.aggregate_verify_bls(messages, aggregate, public_keys)
+    assert not reliquary_encryptor.aggregate_verify_bls(messages[::-1], aggregate, public_keys)
+    with pytest.raises(ValueError, match="same length"):
+        reliquary_encryptor.aggregate_verify_bls(messages[:2], aggregate, public_keys)
+
+
+def test_aggregation_rejections():
+    with pytest.raises(ValueError, match="at least one signature"):
+        reliquary_encryptor.aggregate_bls_signatures([])
+    with pytest.raises(ValueError, match="Invalid BLS signature"):
+        reliquary_encryptor.aggregate_bls_signatures([b"\x00" * 95])
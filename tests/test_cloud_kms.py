@@ -0,0 +1,125 @@
+# tests/test_cloud_kms.py
+
+import os
+from types import SimpleNamespace
+
+import pytest
+
+pytest.importorskip("reliquary_encryptor")
+
+import reliquary_encryptor
+
+from core.cloud_kms import (AwsKmsBackend, AzureKeyVaultBackend, CloudKmsEnvelope,
+                            GcpKmsBackend, KmsError)
+
+# --- DEKs wrapped by cloud KMS backends, stored in RQEV envelopes ---
+
+def _xor(data, key):
+    return bytes(b ^ key[i % len(key)] for i, b in enumerate(data))
+
+
+class FakeAwsKms:
+    """boto3-style KMS client whose 'keys' are XOR pads bound to the encryption context."""
+
+    def __init__(self):
+        self.keys = {"alias/vault": os.urandom(32)}
+
+    def _pad(self, key_id, context):
+        if key_id not in self.keys:
+            raise RuntimeError("NotFoundException")
+        return _xor(self.keys[key_id], repr(sorted(context.items())).encode().ljust(32, b"."))
+
+    def encrypt(self, KeyId, Plaintext, EncryptionContext):
+        return {"CiphertextBlob": _xor(Plaintext, self._pad(KeyId, EncryptionContext))}
+
+    def decrypt(self, KeyId, CiphertextBlob, EncryptionContext):
+        return {"Plaintext": _xor(CiphertextBlob, self._pad(KeyId, EncryptionContext))}
+
+    def generate_data_key(self, KeyId, NumberOfBytes, EncryptionContext):
+        dek = os.urandom(NumberOfBytes)
+        return {"Plaintext": dek, **self.encrypt(KeyId, dek, EncryptionContext)}
+
+
+class FakeGcpKms:
+    def __init__(self):
+        self.pad = os.urandom(32)
+
+    def encrypt(self, request):
+        return SimpleNamespace(ciphertext=request["name"].encode() + b"|" +
+                               _xor(request["plaintext"], self.pad))
+
+    def decrypt(self, request):
+        name, _, body = request["ciphertext"].partition(b"|")
+        assert name == request["name"].encode()
+        return SimpleNamespace(plaintext=_xor(body, self.pad))
+
+
+class FakeAzureCrypto:
+    def __init__(self, key_id):
+        self.pad = os.urandom(32)
+        self.calls = []
+
+    def wrap_key(self, algorithm, key):
+        self.calls.append(algorithm)
+        return SimpleNamespace(encrypted_key=_xor(key, self.pad))
+
+    def unwrap_key(self, algorithm, encrypted_key):
+        return SimpleNamespace(key=_xor(encrypted_key, self.pad))
+
+
+class AuditSink:
+    def __init__(self):
+        self.entries = []
+
+    def add_entry(self, entry):
+        self.entries.append(entry)
+
+
+def _envelopes(audit=None):
+    return CloudKmsEnvelope([AwsKmsBackend(client=FakeAwsKms()),
+                             GcpKmsBackend(client=FakeGcpKms()),
+                             AzureKeyVaultBackend(client_factory=FakeAzureCrypto)],
+                            audit_logger=audit)
+
+
+@pytest.mark.parametrize("provider,key_id", [
+    ("aws-kms", "alias/vault"),
+    ("gcp-kms", "projects/p/locations/global/keyRings/r/cryptoKeys/vault"),
+    ("azure-keyvault", "https://vault.vault.azure.net/keys/vault/1"),
+])
+def test_kms_envelope_roundtrip_uses_standard_envelope_format(provider, key_id):
+    envelopes = _envelopes()
+    envelope = envelopes.encrypt(b"secret payload", provider, key_id, aad=b"vault-7")
+
+    assert envelope[:5] == b"RQEV\x03"
+    assert reliquary_encryptor.kms_envelope_info(envelope)[:2] == (provider, key_id)
+    assert reliquary_encryptor.validate_envelope(envelope) == []
+    assert envelopes.decrypt(envelope, aad=b"vault-7") == b"secret payload"
+
+    with pytest.raises((KmsError, ValueError)):
+        envelopes.decrypt(envelope, aad=b"vault-8")
+    with pytest.raises(ValueError, match="cloud KMS"):
+        _, sk = reliquary_encryptor.generate_kyber_keys()
+        reliquary_encryptor.decrypt_with_secret_key(envelope, sk)
+
+
+def test_kms_envelope_rejects_tampering_and_unknown_providers():
+    audit = AuditSink()
+    envelopes = _envelopes(audit)
+    envelope = bytearray(envelopes.encrypt(b"payload", "aws-kms", "alias/vault"))
+    envelope[-1] ^= 1
+    with pytest.raises(ValueError, match="authentication failed"):
+        envelopes.decrypt(bytes(envelope))
+
+    with pytest.raises(KmsError, match="No backend"):
+        CloudKmsEnvelope([GcpKmsBackend(client=FakeGcpKms())]).decrypt(
+            envelopes.encrypt(b"payload", "aws-kms", "alias/vault"))
+    with pytest.raises(KmsError, match="AWS KMS"):
+        envelopes.encrypt(b"payload", "aws-kms", "alias/missing")
+
+    moved = envelopes.rewrap(envelopes.encrypt(b"payload", "aws-kms", "alias/vault"),
+                             "gcp-kms", "projects/p/locations/global/keyRings/r/cryptoKeys/k")
+    assert reliquary_encryptor.kms_envelope_info(moved)[0] == "gcp-kms"
+    assert envelopes.decrypt(moved) == b"payload"
+    assert {"event": "kms_envelope_sealed", "kms_provider": "gcp-kms",
+            "kms_key_id": "projects/p/locations/global/keyRings/r/cryptoKeys/k"} in audit.entries
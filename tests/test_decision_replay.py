@@ -0,0 +1,48 @@
+# tests/test_decision_replay.py
+
+import copy
+
+import pytest
+
+trust_engine = pytest.importorskip("zk.trust_engine")
+
+# --- Replayable decisions: records re-execute against the policy version they were made under ---
+
+CONTEXT = {
+    "device_verified": True,
+    "device_fingerprint": "laptop-1",
+    "timestamp_verified": True,
+    "location_verified": False,
+    "pattern_verified": True,
+    "current_timestamp": 1_700_000_030,
+    "last_access_time": 1_700_000_000,
+}
+
+@pytest.fixture
+def engine(tmp_path):
+    return trust_engine.TrustScoringEngine(tmp_path, audit_log_path=tmp_path / "trust_scoring.log")
+
+def test_replay_reproduces_under_archived_policy(engine):
+    engine.evaluate_trust("bob", CONTEXT)
+    record = engine.evaluate_trust("bob", CONTEXT).decision_record
+
+    # Later policy changes don't affect replay of earlier decisions
+    engine.trust_weights[trust_engine.TrustFactor.DEVICE_CONSISTENCY] = 0.5
+    assert engine.current_policy().version != record["policy_version"]
+    report = engine.replay_decision(record)
+    assert report["reproduced"], report
+    assert report["replayed"] == record["output"]
+
+def test_replay_flags_divergence(engine):
+    record = engine.evaluate_trust("carol", CONTEXT).decision_record
+
+    tampered = copy.deepcopy(record)
+    tampered["inputs"]["context"]["location_verified"] = True
+    report = engine.replay_decision(tampered)
+    assert not report["reproduced"]
+    assert "inputs_digest" in report["divergences"]
+    assert "overall_trust_score" in report["divergences"]
+
+    missing = dict(record, policy_version="0" * 16)
+    report = engine.replay_decision(missing)
+    assert report["divergences"] == ["policy_unavailable"]
@@ -0,0 +1,47 @@
+# tests/test_dilithium_backends.py
+
+import hashlib
+import json
+from pathlib import Path
+
+import pytest
+
+reliquary_encryptor = pytest.importorskip("reliquary_encryptor")
+
+KAT_PATH = Path(__file__).parent / "vectors" / "dilithium5_kat.json"
+
+# --- Known-answer vector shared by the PQClean C and pure-Rust backends ---
+# Signing is deterministic, so whichever backend this build selected must reproduce the pinned
+# signature byte for byte. Run this file against a default build and a
+# `--no-default-features --features rand_core,getrandom` build to cross-check both.
+
+@pytest.fixture(scope="module")
+def kat():
+    with open(KAT_PATH) as f:
+        vector = json.load(f)
+    return {key: bytes.fromhex(value) if key != "algorithm" else value for key, value in vector.items()}
+
+def test_backend_is_reported():
+    assert reliquary_encryptor.dilithium_backend() in ("pqclean-c", "pure-rust")
+
+def test_kat_signature_matches(kat):
+    signature = bytes(reliquary_encryptor.sign_dilithium(kat["message"], kat["secret_key"]))
+    assert hashlib.sha256(signature).hexdigest() == kat["signature_sha256"].hex()
+    assert reliquary_encryptor.verify_dilithium(kat["message"], signature, kat["public_key"])
+
+def test_kat_rejects_tampering(kat):
+    signature = bytearray(reliquary_encryptor.sign_dilithium(kat["message"], kat["secret_key"]))
+    signature[0] ^= 0x01
+    assert not reliquary_encryptor.verify_dilithium(kat["message"], bytes(signature), kat["public_key"])
+    assert not reliquary_encryptor.verify_dilithium(b"other message", bytes(signature), kat["public_key"])
+
+def test_generated_keys_roundtrip():
+    public_key, secret_key = reliquary_encryptor.generate_dilithium_keys()
+    signature = reliquary_encryptor.sign_dilithium(b"audit entry", secret_key)
+    assert reliquary_encryptor.verify_dilithium(b"audit entry", signature, public_key)
+
+def test_invalid_key_lengths():
+    with pytest.raises(ValueError):
+        reliquary_encryptor.sign_dilithium(b"msg", b"\x00" * 10)
+    with pytest.raises(ValueError):
+        reliquary_encryptor.verify_dilithium(b"msg", b"\x00" * 4595, b"\x00" * 10)
@@ -0,0 +1,75 @@
+# tests/test_dkg.py
+
+import pytest
+
+reliquary_encryptor = pytest.importorskip("reliquary_encryptor")
+from core.crypto.dkg import create_dkg_participant, dkg_result, new_dkg_session_id, run_local_dkg
+
+# --- Distributed key generation: no trusted dealer ---
+
+def _participants(threshold=3, count=5):
+    session = new_dkg_session_id()
+    return [create_dkg_participant(session, i, threshold, count) for i in range(1, count + 1)]
+
+
+def _tamper(message):
+    data = bytearray(message)
+    data[-32] ^= 0x01
+    return bytes(data)
+
+
+def test_local_dkg_agrees_on_group_key():
+    results = run_local_dkg(_participants())
+    assert sorted(results) == [1, 2, 3, 4, 5]
+    group_keys = {r.group_key for r in results.values()}
+    assert len(group_keys) == 1 and len({r.key_share for r in results.values()}) == 5
+    group_key = group_keys.pop()
+    assert reliquary_encryptor.dkg_group_key_info(group_key)[:2] == (3, 5)
+    assert all(reliquary_encryptor.dkg_verify_key_share(group_key, r.key_share)
+               for r in results.values())
+
+    # Key shares are bound to their session
+    other = run_local_dkg(_participants())[1]
+    assert not reliquary_encryptor.dkg_verify_key_share(group_key, other.key_share)
+    assert other.public_key != results[1].public_key
+
+
+@pytest.mark.parametrize("justified", [True, False])
+def test_complaints_and_disqualification(justified):
+    parties = _participants()
+    sent = {p.index: p.round1() for p in parties}
+    for sender, (commitment, shares) in sent.items():
+        for recipient, share in shares.items():
+            if (sender, recipient) == (2, 4):
+                share = _tamper(share)
+            parties[recipient - 1].receive_commitment(commitment)
+            parties[recipient - 1].receive_share(share)
+
+    complaints = [c for p in parties for c in p.complaints()]
+    assert len(complaints) == 1
+    with pytest.raises(ValueError, match="complain and resolve"):
+        parties[3].finish()
+    justifications = [j for p in parties for j in p.justify(complaints)] if justified else []
+    for p in parties:
+        assert p.resolve(complaints, justifications) == ([] if justified else [2])
+
+    results = [dkg_result(p) for p in parties if justified or p.index != 2]
+    assert len({r.group_key for r in results}) == 1
+    assert results[0].qualified == ([1, 2, 3, 4, 5] if justified else [1, 3, 4, 5])
+    assert all(reliquary_encryptor.dkg_verify_key_share(r.group_key, r.key_share)
+               for r in results)
+
+
+def test_invalid_messages():
+    a, b = _participants(threshold=2, count=2)
+    commitment, shares = a.round1()
+    with pytest.raises(ValueError, match="already"):
+        a.round1()
+    assert not b.receive_commitment(_tamper(commitment))
+    assert b.disqualified == [1]
+
+    stranger = _participants(threshold=2, count=2)[1]
+    with pytest.raises(ValueError, match="another DKG session"):
+        stranger.receive_share(shares[2])
+    with pytest.raises(ValueError, match="addressed to participant 2"):
+        a.receive_share(shares[2])
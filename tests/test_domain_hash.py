@@ -0,0 +1,51 @@
+# tests/test_domain_hash.py
+
+import hashlib
+
+import pytest
+
+from core.crypto.domain_hash import (DomainHash, DomainLabelCollision, register_hash_context,
+                                     registered_hash_contexts)
+
+# --- Domain-separated hashing with registered labels ---
+
+register_hash_context("tests/records", __name__, "record digests")
+register_hash_context("tests/records-v2", __name__)
+
+
+def test_labels_and_parts_separate_domains():
+    digest = DomainHash.hash("tests/records", b"vault-1", "entry")
+    assert digest == DomainHash("tests/records").update(b"vault-1").update("entry").digest()
+    assert len(digest) == 32 and digest != hashlib.sha3_256(b"vault-1entry").digest()
+    assert digest != DomainHash.hash("tests/records-v2", b"vault-1", "entry")
+    assert digest != DomainHash.hash("tests/records", b"vault-1entry")
+    assert digest != DomainHash.hash("tests/records", b"vault-", b"1entry")
+    assert digest != DomainHash.hash("tests/records", b"vault-1", "entry", algorithm="sha256")
+    assert len(DomainHash.hash("tests/records", algorithm="sha512")) == 64
+
+    partial = DomainHash("tests/records").update(b"vault-1")
+    assert partial.copy().update("entry").digest() == digest
+    assert partial.hexdigest() == DomainHash.hash("tests/records", b"vault-1").hex()
+
+
+def test_registry_prevents_collisions():
+    assert register_hash_context("tests/records", __name__).description == "record digests"
+    with pytest.raises(DomainLabelCollision, match=__name__):
+        register_hash_context("tests/records", "vaults.storage")
+    for bad in ["", "Tests/Upper", "/leading", "has space"]:
+        with pytest.raises(ValueError):
+            register_hash_context(bad, __name__)
+    assert "tests/records" in registered_hash_contexts()
+
+    with pytest.raises(KeyError, match="not registered"):
+        DomainHash("tests/unregistered")
+    with pytest.raises(ValueError, match="Unsupported"):
+        DomainHash("tests/records", algorithm="md5")
+    with pytest.raises(TypeError):
+        DomainHash("tests/records").update(42)
+
+
+def test_blake3_derive_key_contexts():
+    pytest.importorskip("blake3")
+    a = DomainHash.hash("tests/records", b"x", algorithm="blake3")
+    assert a != DomainHash.hash("tests/records-v2", b"x", algorithm="blake3")
@@ -0,0 +1,78 @@
+# tests/test_dual_escrow.py
+
+import pytest
+
+reliquary_encryptor = pytest.importorskip("reliquary_encryptor")
+
+# --- Dual-control key escrow: both KEKs required, role binding and per-custodian checks ---
+
+KEY = bytes(range(48))
+KEK_A = b"\x11" * 32
+KEK_B = b"\x22" * 32
+AAD = b"vault-7/master-key"
+
+
+@pytest.fixture
+def escrow():
+    return reliquary_encryptor.escrow_wrap_dual(KEY, KEK_A, KEK_B, "security-officer",
+                                                "operations-lead", AAD)
+
+
+def test_round_trip(escrow):
+    assert escrow[:5] == b"RQDE\x01"
+    assert reliquary_encryptor.escrow_roles(escrow) == ("security-officer", "operations-lead")
+    assert reliquary_encryptor.escrow_unwrap_dual(escrow, KEK_A, KEK_B, AAD) == KEY
+    # Fresh shares every time; the key never appears in the escrow
+    again = reliquary_encryptor.escrow_wrap_dual(KEY, KEK_A, KEK_B, aad=AAD)
+    assert again != escrow and KEY not in escrow
+    assert reliquary_encryptor.escrow_roles(again) == ("custodian-a", "custodian-b")
+    assert reliquary_encryptor.escrow_unwrap_dual(again, KEK_A, KEK_B, AAD) == KEY
+
+
+def test_one_wrong_kek(escrow):
+    wrong = b"\x33" * 32
+    with pytest.raises(ValueError, match="Unwrap with the security-officer KEK failed"):
+        reliquary_encryptor.escrow_unwrap_dual(escrow, wrong, KEK_B, AAD)
+    with pytest.raises(ValueError, match="Unwrap with the operations-lead KEK failed"):
+        reliquary_encryptor.escrow_unwrap_dual(escrow, KEK_A, wrong, AAD)
+    # The KEKs are tied to their roles, so swapping them fails too
+    with pytest.raises(ValueError, match="Unwrap with the security-officer KEK failed"):
+        reliquary_encryptor.escrow_unwrap_dual(escrow, KEK_B, KEK_A, AAD)
+    with pytest.raises(ValueError, match="KEK for operations-lead must be 32 bytes"):
+        reliquary_encryptor.escrow_unwrap_dual(escrow, KEK_A, KEK_B[:16], AAD)
+    with pytest.raises(ValueError, match="Unwrap with the security-officer KEK failed"):
+        reliquary_encryptor.escrow_unwrap_dual(escrow, KEK_A, KEK_B, b"vault-8/master-key")
+
+
+def test_verify_kek_per_custodian(escrow):
+    assert reliquary_encryptor.escrow_verify_kek(escrow, "security-officer", KEK_A, AAD)
+    assert reliquary_encryptor.escrow_verify_kek(escrow, "operations-lead", KEK_B, AAD)
+    assert not reliquary_encryptor.escrow_verify_kek(escrow, "security-officer", KEK_B, AAD)
+    assert not reliquary_encryptor.escrow_verify_kek(escrow, "operations-lead", KEK_B, b"")
+    with pytest.raises(ValueError, match="Escrow has no role 'auditor'"):
+        reliquary_encryptor.escrow_verify_kek(escrow, "auditor", KEK_A, AAD)
+
+
+def test_tampering(escrow):
+    # Renaming a role breaks both shares' authentication
+    header_len = 5 + 1 + len(b"security-officer")
+    renamed = escrow[:header_len - 1] + b"x" + escrow[header_len:]
+    with pytest.raises(ValueError, match="Unwrap with the security-officex KEK failed"):
+        reliquary_encryptor.escrow_unwrap_dual(renamed, KEK_A, KEK_B, AAD)
+    with pytest.raises(ValueError, match="Trailing data after escrow"):
+        reliquary_encryptor.escrow_unwrap_dual(escrow + b"\x00", KEK_A, KEK_B, AAD)
+    with pytest.raises(ValueError, match="Not a dual-control escrow"):
+        reliquary_encryptor.escrow_roles(b"XXXX" + escrow[4:])
+
+
+def test_wrap_rejections():
+    with pytest.raises(ValueError, match="two different KEKs"):
+        reliquary_encryptor.escrow_wrap_dual(KEY, KEK_A, KEK_A)
+    with pytest.raises(ValueError, match="two distinct, non-empty role names"):
+        reliquary_encryptor.escrow_wrap_dual(KEY, KEK_A, KEK_B, "admin", "admin")
+    with pytest.raises(ValueError, match="two distinct, non-empty role names"):
+        reliquary_encryptor.escrow_wrap_dual(KEY, KEK_A, KEK_B, "", "admin")
+    with pytest.raises(ValueError, match="Escrowed key must be 1 to 4096 bytes"):
+        reliquary_encryptor.escrow_wrap_dual(b"", KEK_A, KEK_B)
+    with pytest.raises(ValueError, match="KEK for custodian-b must be 32 bytes"):
+        reliquary_encryptor.escrow_wrap_dual(KEY, KEK_A, KEK_B + b"\x00")
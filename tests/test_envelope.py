@@ -0,0 +1,53 @@
+# tests/test_envelope.py
+
+import pytest
+
+reliquary_encryptor = pytest.importorskip("reliquary_encryptor")
+
+# --- Single-recipient envelopes: sealing, opening and tamper detection ---
+
+KyberParams = reliquary_encryptor.KyberParams
+MESSAGE = b"vault record"
+
+
+@pytest.fixture(scope="module")
+def keys():
+    return reliquary_encryptor.generate_kyber_keys(KyberParams.Kyber1024)
+
+
+def test_round_trip(keys):
+    pk, sk = keys
+    envelope = reliquary_encryptor.encrypt_to_public_key(MESSAGE, pk)
+    assert envelope[:5] == b"RQEV\x01"
+    # magic | version | kem id | kem ciphertext | nonce | ciphertext | tag
+    assert len(envelope) == 6 + 1568 + 12 + len(MESSAGE) + 16
+    assert reliquary_encryptor.decrypt_with_secret_key(envelope, sk) == MESSAGE
+    assert reliquary_encryptor.encrypt_to_public_key(MESSAGE, pk) != envelope
+
+    # The parameter set is read from the header
+    small_pk, small_sk = reliquary_encryptor.generate_kyber_keys(KyberParams.Kyber512)
+    small = reliquary_encryptor.encrypt_to_public_key(b"", small_pk, KyberParams.Kyber512)
+    assert reliquary_encryptor.decrypt_with_secret_key(small, small_sk) == b""
+
+
+def test_wrong_key_and_tampering(keys):
+    pk, sk = keys
+    envelope = reliquary_encryptor.encrypt_to_public_key(MESSAGE, pk)
+    _, other_sk = reliquary_encryptor.generate_kyber_keys(KyberParams.Kyber1024)
+    with pytest.raises(ValueError, match="authentication failed"):
+        reliquary_encryptor.decrypt_with_secret_key(envelope, other_sk)
+    # Every region is covered: the KEM ciphertext, nonce and AES-GCM body
+    for offset in (6, 6 + 1568, len(envelope) - 1):
+        tampered = bytearray(envelope)
+        tampered[offset] ^= 1
+        with pytest.raises(ValueError, match="authentication failed"):
+            reliquary_encryptor.decrypt_with_secret_key(bytes(tampered), sk)
+
+    with pytest.raises(ValueError, match="Not a Reliquary envelope"):
+        reliquary_encryptor.decrypt_with_secret_key(b"XXXX" + envelope[4:], sk)
+    with pytest.raises(ValueError, match="Unsupported envelope version 9"):
+        reliquary_encryptor.decrypt_with_secret_key(envelope[:4] + b"\x09" + envelope[5:], sk)
+    with pytest.raises(ValueError, match="Unknown envelope KEM id"):
+        reliquary_encryptor.decrypt_with_secret_key(envelope[:5] + b"\xff" + envelope[6:], sk)
+    with pytest.raises(ValueError, match="truncated"):
+        reliquary_encryptor.decrypt_with_secret_key(envelope[:6 + 1568 + 27], sk)
@@ -0,0 +1,51 @@
+# tests/test_envelope_rewrap.py
+
+import pytest
+
+reliquary_encryptor = pytest.importorskip("reliquary_encryptor")
+
+# --- decrypt_and_rewrap: opening an envelope and re-sealing it under the current key ---
+
+KyberParams = reliquary_encryptor.KyberParams
+MESSAGE = b"vault record"
+
+
+@pytest.fixture(scope="module")
+def keys():
+    return reliquary_encryptor.generate_kyber_keys(KyberParams.Kyber1024)
+
+
+def test_rewrap_current_envelope_is_left_alone(keys):
+    pk, sk = keys
+    envelope = reliquary_encryptor.encrypt_to_public_key(MESSAGE, pk)
+    assert reliquary_encryptor.decrypt_and_rewrap(envelope, sk, pk) == (MESSAGE, None)
+
+
+def test_rewrap_to_new_key_or_params(keys):
+    pk, sk = keys
+    envelope = reliquary_encryptor.encrypt_to_public_key(MESSAGE, pk)
+
+    new_pk, new_sk = reliquary_encryptor.generate_kyber_keys(KyberParams.Kyber1024)
+    plaintext, upgraded = reliquary_encryptor.decrypt_and_rewrap(envelope, sk, new_pk)
+    assert plaintext == MESSAGE
+    assert reliquary_encryptor.decrypt_with_secret_key(upgraded, new_sk) == MESSAGE
+    with pytest.raises(ValueError, match="authentication failed"):
+        reliquary_encryptor.decrypt_with_secret_key(upgraded, sk)
+
+    # A different parameter set is stale too
+    mlkem_pk, mlkem_sk = reliquary_encryptor.generate_kyber_keys(KyberParams.MlKem1024)
+    _, upgraded = reliquary_encryptor.decrypt_and_rewrap(envelope, sk, mlkem_pk,
+                                                         KyberParams.MlKem1024)
+    assert upgraded[5] != envelope[5]
+    assert reliquary_encryptor.decrypt_with_secret_key(upgraded, mlkem_sk) == MESSAGE
+
+
+def test_rewrap_rejections(keys):
+    pk, sk = keys
+    envelope = reliquary_encryptor.encrypt_to_public_key(MESSAGE, pk)
+    with pytest.raises(ValueError, match="Invalid public key length"):
+        reliquary_encryptor.decrypt_and_rewrap(envelope, sk, pk[:-1])
+    # A failed open never produces a re-sealed envelope
+    _, other_sk = reliquary_encryptor.generate_kyber_keys(KyberParams.Kyber1024)
+    with pytest.raises(ValueError, match="authentication failed"):
+        reliquary_encryptor.decrypt_and_rewrap(envelope, other_sk, pk)
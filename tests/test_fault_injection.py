@@ -0,0 +1,62 @@
+# tests/test_fault_injection.py
+
+import pytest
+
+reliquary_encryptor = pytest.importorskip("reliquary_encryptor")
+from core.crypto.fault_injection import crypto_faults, fault_injection_available
+
+if not fault_injection_available():
+    pytest.skip("reliquary_encryptor built without the fault-injection feature",
+                allow_module_level=True)
+
+# --- Fault injection in crypto operations ---
+
+
+def _run_decrypts(envelope, sk, count):
+    outcomes = []
+    for _ in range(count):
+        try:
+            reliquary_encryptor.decrypt_with_secret_key(envelope, sk)
+            outcomes.append("ok")
+        except reliquary_encryptor.InjectedFault:
+            outcomes.append("fault")
+    return outcomes
+
+
+def test_errors_are_deterministic_per_seed():
+    pk, sk = reliquary_encryptor.generate_kyber_keys()
+    envelope = reliquary_encryptor.encrypt_to_public_key(b"payload", pk)
+    with crypto_faults({"decrypt": {"error": 0.5}}, seed=7) as log:
+        first = _run_decrypts(envelope, sk, 20)
+        assert log.count("decrypt", "error") == first.count("fault")
+    with crypto_faults({"decrypt": {"error": 0.5}}, seed=7):
+        assert _run_decrypts(envelope, sk, 20) == first
+    assert "ok" in first and "fault" in first
+    # Rules are gone after the block
+    assert _run_decrypts(envelope, sk, 5) == ["ok"] * 5
+    assert reliquary_encryptor.injected_faults() == []
+
+
+def test_corruption_is_flagged():
+    pk, sk = reliquary_encryptor.generate_ed25519_keys()
+    with crypto_faults({"sign": {"corrupt": 1.0}, "verify": {"corrupt": 1.0}}) as log:
+        signature = reliquary_encryptor.sign_ed25519(b"msg", sk)
+        assert log.is_corrupted(signature)
+        # Verification results are flipped: the corrupted signature now "verifies"...
+        assert reliquary_encryptor.verify_ed25519(b"msg", signature, pk)
+        assert log.summary() == {"sign": {"corrupt": 1}, "verify": {"corrupt": 1}}
+    # ...and fails once injection is off
+    assert not reliquary_encryptor.verify_ed25519(b"msg", signature, pk)
+    assert not reliquary_encryptor.is_injected_corruption(signature)
+
+
+def test_delays_and_rule_validation():
+    key = bytes(32)
+    with crypto_faults({"*": {"delay": 1.0, "delay_ms": 1}}) as log:
+        ciphertext, nonce = reliquary_encryptor.encrypt_data(b"data", key)
+        assert reliquary_encryptor.decrypt_data(ciphertext, nonce, key) == b"data"
+        assert log.faults == [("encrypt", "delay"), ("decrypt", "delay")]
+    with pytest.raises(ValueError, match="sum to at most 1"):
+        reliquary_encryptor.set_fault("sign", error=0.7, corrupt=0.7)
+    with pytest.raises(ValueError, match="Unknown operation"):
+        reliquary_encryptor.set_fault("hash", error=1.0)
@@ -0,0 +1,89 @@
+# tests/test_forensics.py
+
+import hashlib
+import json
+import os
+
+import pytest
+
+reliquary_encryptor = pytest.importorskip("reliquary_encryptor")
+from core.merkle_logging import MerkleLogWriter
+from core.read_only import ReadOnlyMode, disable_read_only_mode, is_read_only
+from vaults.forensics import ForensicCollector
+from vaults.storage.local import LocalFileStorage
+from vaults.transactions import KeyCounterStore
+
+# --- Read-only forensic mode: every write path refuses, every read is hashed ---
+
+@pytest.fixture
+def system(tmp_path):
+    storage = LocalFileStorage(str(tmp_path / "vaults"))
+    kyber_pk, _ = reliquary_encryptor.generate_kyber_keys()
+    storage.save_vault("v1", reliquary_encryptor.encrypt_to_public_key(b"secret", kyber_pk))
+    audit = MerkleLogWriter(str(tmp_path / "audit" / "vault.log"))
+    for i in range(3):
+        audit.add_entry({"event": "seal", "n": i})
+    counters = KeyCounterStore(str(tmp_path / "keystore" / "counters.json"))
+    counters.set("vault-key", 1)
+    yield storage, audit, counters, tmp_path
+    disable_read_only_mode()
+
+
+def _snapshot(root):
+    return {os.path.join(d, n): os.path.getsize(os.path.join(d, n))
+            for d, _, names in os.walk(root) for n in names}
+
+
+def test_write_paths_refuse_in_read_only_mode(system):
+    storage, audit, counters, root = system
+    before = _snapshot(root)
+    ForensicCollector(case_id="IR-1", lock=False)
+    assert is_read_only()
+
+    with pytest.raises(ReadOnlyMode):
+        storage.save_vault("v2", b"data")
+    with pytest.raises(ReadOnlyMode):
+        storage.delete_vault("v1")
+    with pytest.raises(ReadOnlyMode):
+        audit.add_entry({"event": "tamper"})
+    with pytest.raises(ReadOnlyMode):
+        counters.set("vault-key", 2)
+    with pytest.raises(ReadOnlyMode):
+        reliquary_encryptor.generate_lms_keys(str(root / "lms.state"))
+    # Opening a writer on a new path doesn't create its directory
+    MerkleLogWriter(str(root / "new" / "x.log"))
+    assert _snapshot(root) == before
+    assert not (root / "new").exists()
+
+    disable_read_only_mode()
+    storage.save_vault("v2", b"data")
+
+
+def test_collector_hashes_everything_it_reads(system):
+    storage, audit, counters, root = system
+    collector = ForensicCollector(case_id="IR-2", lock=False)
+
+    data = collector.read_container(storage, "v1")
+    keystore = collector.read_directory(str(root / "keystore"))
+    entries, details = collector.read_audit_log(audit.log_file_path)
+    assert keystore == {"counters.json": (root / "keystore" / "counters.json").read_bytes()}
+    assert [e["n"] for e in entries] == [0, 1, 2]
+    assert details["entry_count"] == 3 and details["root_file_matches"] is True
+
+    report = collector.report()
+    assert report["case_id"] == "IR-2" and report["read_only"]
+    kinds = [(r["kind"], os.path.basename(r["source"])) for r in report["records"]]
+    assert kinds == [("container", "v1"), ("file", "counters.json"),
+                     ("file", "vault.log.merkle"), ("audit_log", "vault.log")]
+    container = report["records"][0]
+    assert container["sha256"] == hashlib.sha256(data).hexdigest()
+    assert container["sha3_256"] == hashlib.sha3_256(data).hexdigest()
+    assert not [f for f in container["details"]["envelope_findings"] if f[0] == "error"]
+    assert report["digest"] == hashlib.sha256(
+        json.dumps(report["records"], sort_keys=True).encode()).hexdigest()
+
+    # A log edited behind the writer's back is recorded as not matching, not raised
+    with open(audit.log_file_path, "ab") as f:
+        f.write(b'{"event": "forged", "timestamp": "2026-01-01T00:00:00Z"}\n')
+    _, details = collector.read_audit_log(audit.log_file_path)
+    assert details["entry_count"] == 4 and details["root_file_matches"] is False
@@ -0,0 +1,116 @@
+# tests/test_gossip.py
+
+import pytest
+
+reliquary_merkle = pytest.importorskip("reliquary_merkle")
+reliquary_encryptor = pytest.importorskip("reliquary_encryptor")
+from core.crypto.tree_head import sign_tree_head
+
+# --- Gossip packets and witnesses: split-view detection ---
+
+T0 = 1_700_000_000_000
+RECORDS = [f"log entry {n}".encode() for n in range(12)]
+
+
+@pytest.fixture(scope="module")
+def log_keys():
+    return reliquary_encryptor.generate_ed25519_keys()
+
+
+def head_at(tree, size, sk, algorithm="ed25519"):
+    return sign_tree_head(tree.tree_root(size), sk, algorithm, timestamp=T0 + size)
+
+
+def packet(tree, size, sk, previous_size=None):
+    head = head_at(tree, size, sk)
+    if previous_size is None:
+        return reliquary_merkle.GossipPacket(head)
+    return reliquary_merkle.GossipPacket(head, head_at(tree, previous_size, sk),
+                                         tree.prove_consistency(previous_size, size))
+
+
+def test_packets_verify_and_encode(log_keys):
+    pk, sk = log_keys
+    tree = reliquary_merkle.MerkleTree(RECORDS, 2)
+    gossip = packet(tree, 8, sk, 4)
+    assert gossip.verify(pk) and gossip.tree_size == 8
+    assert gossip.head == head_at(tree, 8, sk).to_bytes()
+    assert gossip.consistency_proof == tree.prove_consistency(4, 8)
+    assert reliquary_merkle.GossipPacket.from_bytes(gossip.to_bytes()) == gossip
+    assert reliquary_merkle.GossipPacket(gossip.head).verify(pk)
+    assert not gossip.verify(reliquary_encryptor.generate_ed25519_keys()[0])
+
+    # The proof must lead from the previous head to the head, and the heads must be in order
+    with pytest.raises(ValueError, match="from size 4 to 8"):
+        reliquary_merkle.GossipPacket(head_at(tree, 8, sk), head_at(tree, 4, sk),
+                                      tree.prove_consistency(5, 8))
+    with pytest.raises(ValueError, match="no larger"):
+        reliquary_merkle.GossipPacket(head_at(tree, 4, sk), head_at(tree, 8, sk),
+                                      tree.prove_consistency(4, 8))
+    with pytest.raises(ValueError, match="go together"):
+        reliquary_merkle.GossipPacket(head_at(tree, 8, sk), head_at(tree, 4, sk))
+    other = reliquary_merkle.MerkleTree(RECORDS[:3] + [b"x"] + RECORDS[4:], 2)
+    assert not reliquary_merkle.GossipPacket(
+        head_at(tree, 8, sk), head_at(other, 4, sk), tree.prove_consistency(4, 8)).verify(pk)
+    with pytest.raises(ValueError, match="Trailing data"):
+        reliquary_merkle.GossipPacket.from_bytes(gossip.to_bytes() + b"\x00")
+
+
+def test_falcon_heads():
+    pk, sk = reliquary_encryptor.generate_falcon_keys(reliquary_encryptor.FalconParams.Falcon512)
+    tree = reliquary_merkle.MerkleTree(RECORDS, 2, "blake3")
+    gossip = reliquary_merkle.GossipPacket(head_at(tree, 12, sk, "falcon-512"))
+    assert gossip.verify(pk)
+    assert not gossip.verify(reliquary_encryptor.generate_falcon_keys(
+        reliquary_encryptor.FalconParams.Falcon512)[0])
+
+
+def test_witness_detects_a_split_view(log_keys):
+    pk, sk = log_keys
+    honest = reliquary_merkle.MerkleTree(RECORDS, 2)
+    # The log shows a second tree, sharing the first 6 entries, to some clients
+    forked = reliquary_merkle.MerkleTree(RECORDS[:6] + [b"rewritten"] + RECORDS[7:], 2)
+
+    witness = reliquary_merkle.Witness(pk)
+    assert witness.observe(packet(honest, 4, sk)) is None
+    assert witness.observe(packet(honest, 8, sk, 4)) is None
+    assert witness.latest == head_at(honest, 8, sk).to_bytes() and witness.pending() == []
+
+    # Consistent with what the witness has seen up to size 6, but not at 8
+    assert witness.observe(packet(forked, 6, sk, 4)) is None
+    split = witness.observe(packet(forked, 8, sk, 6))
+    assert split is not None and split.tree_size == 8 and split.verify(pk)
+    assert split.first == head_at(honest, 8, sk).to_bytes()
+    assert split.second == head_at(forked, 8, sk).to_bytes()
+    assert witness.split_views() == [split]
+    assert reliquary_merkle.SplitView.from_bytes(split.to_bytes()) == split
+    assert not split.verify(reliquary_encryptor.generate_ed25519_keys()[0])
+    # The same fork seen again is not reported twice, and the forked head never links
+    assert witness.observe(packet(forked, 8, sk)) is None
+    assert witness.latest == head_at(honest, 8, sk).to_bytes()
+
+    with pytest.raises(ValueError, match="different roots"):
+        reliquary_merkle.SplitView(head_at(honest, 8, sk), head_at(honest, 8, sk))
+    with pytest.raises(ValueError, match="log's key"):
+        witness.observe(packet(honest, 12, reliquary_encryptor.generate_ed25519_keys()[1]))
+
+
+def test_witness_links_pending_heads(log_keys):
+    pk, sk = log_keys
+    tree = reliquary_merkle.MerkleTree(RECORDS, 2)
+    witness = reliquary_merkle.Witness(pk)
+    witness.observe(packet(tree, 8, sk))
+
+    # Heads gossiped without a proof stay pending until the log links them
+    witness.observe(packet(tree, 12, sk))
+    witness.observe(packet(tree, 2, sk))
+    assert witness.pending() == [head_at(tree, size, sk).to_bytes() for size in (2, 12)]
+    witness.observe(packet(tree, 12, sk, 8))
+    assert witness.latest == head_at(tree, 12, sk).to_bytes()
+    assert witness.pending() == [head_at(tree, 2, sk).to_bytes()]
+    witness.observe(packet(tree, 12, sk, 2))
+    assert witness.pending() == []
+
+    # A witness passes on the packet that linked its latest head
+    assert witness.gossip() == packet(tree, 12, sk, 8)
+    assert reliquary_merkle.Witness(pk).gossip() is None
@@ -0,0 +1,78 @@
+# tests/test_heartbeat.py
+
+import pytest
+
+reliquary_encryptor = pytest.importorskip("reliquary_encryptor")
+from core.heartbeat import (HeartbeatError, HeartbeatMonitor, LivenessPolicy, TrustedClock,
+                            sign_heartbeat)
+
+# --- Signed heartbeats, trusted time and the dead-man release condition ---
+
+DAY = 86400.0
+
+class AuditSink:
+    def __init__(self):
+        self.entries = []
+
+    def add_entry(self, entry):
+        self.entries.append(entry)
+
+class FakeTime:
+    def __init__(self, t):
+        self.t = t
+
+    def __call__(self):
+        return self.t
+
+@pytest.fixture(scope="module")
+def falcon_keys():
+    return reliquary_encryptor.generate_falcon_keys()
+
+def test_heartbeats_and_release_condition(falcon_keys):
+    pk, sk = falcon_keys
+    source = FakeTime(1_760_000_000.0)
+    sink = AuditSink()
+    monitor = HeartbeatMonitor(TrustedClock([source, source], min_sources=2), audit_logger=sink)
+    monitor.register_owner("alice", pk)
+    policy = LivenessPolicy("alice", silence_days=30)
+
+    source.t += 10 * DAY
+    monitor.record_heartbeat(sign_heartbeat("alice", 1, sk, issued_at=source.t))
+    source.t += 29 * DAY
+    assert not monitor.check_liveness(policy).release_allowed
+
+    # Replayed or forged heartbeats don't reset the clock
+    with pytest.raises(HeartbeatError, match="sequence"):
+        monitor.record_heartbeat(sign_heartbeat("alice", 1, sk, issued_at=source.t))
+    forged = sign_heartbeat("alice", 2, sk, issued_at=source.t)
+    forged.sequence = 3
+    with pytest.raises(HeartbeatError, match="signature"):
+        monitor.record_heartbeat(forged)
+    with pytest.raises(HeartbeatError, match="trusted time"):
+        monitor.record_heartbeat(sign_heartbeat("alice", 2, sk, issued_at=source.t - DAY))
+
+    source.t += 2 * DAY
+    status = monitor.check_liveness(policy)
+    assert status.release_allowed and status.silence_seconds == pytest.approx(31 * DAY)
+    assert [e["event"] for e in sink.entries] == [
+        "heartbeat_owner_registered", "heartbeat_recorded", "liveness_release_condition_met"]
+
+    # The audit log alone reproduces the decision
+    replayed = HeartbeatMonitor(TrustedClock([source], min_sources=1))
+    assert replayed.replay(sink.entries) == 1
+    assert replayed.check_liveness(policy).last_heartbeat_at == status.last_heartbeat_at
+
+def test_trusted_clock_fails_closed():
+    honest, skewed = FakeTime(1000.0), FakeTime(1000.0 + 90 * DAY)
+    with pytest.raises(HeartbeatError, match="disagree"):
+        TrustedClock([honest, skewed], min_sources=2).now()
+
+    def broken():
+        raise OSError("unreachable")
+    with pytest.raises(HeartbeatError, match="required time sources"):
+        TrustedClock([honest, broken], min_sources=2).now()
+
+    clock = TrustedClock([honest], min_sources=1)
+    assert clock.now() == 1000.0
+    honest.t = 500.0
+    assert clock.now() == 1000.0
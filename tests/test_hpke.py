@@ -0,0 +1,131 @@
+# tests/test_hpke.py
+
+import json
+from pathlib import Path
+
+import pytest
+
+reliquary_encryptor = pytest.importorskip("reliquary_encryptor")
+
+# --- HPKE (RFC 9180): Appendix A vectors, hybrid-KEM round trips and auth-mode binding ---
+
+VECTORS_PATH = Path(__file__).parent / "vectors" / "rfc9180_hpke.json"
+BASE, AUTH = 0, 2
+INFO = b"reliquary-hpke-test"
+
+
+def vectors(mode, aead_id):
+    found = [v for v in json.loads(VECTORS_PATH.read_text())["vectors"]
+             if v["mode"] == mode and v["aead_id"] == aead_id]
+    assert found
+    return found
+
+
+def suite_for(v):
+    return reliquary_encryptor.HpkeSuite.from_ids(v["kem_id"], v["kdf_id"], v["aead_id"])
+
+
+def sender_pk(v):
+    return bytes.fromhex(v["pkSm"]) if "pkSm" in v else None
+
+
+def recipient_args(v):
+    return bytes.fromhex(v["enc"]), bytes.fromhex(v["skRm"])
+
+
+@pytest.mark.parametrize("mode", [BASE, AUTH])
+def test_rfc9180_single_shot_open(mode):
+    for v in vectors(mode, 0x0001):
+        suite = suite_for(v)
+        enc, sk_r = recipient_args(v)
+        first = v["encryptions"][0]
+        assert suite.open(enc, sk_r, bytes.fromhex(first["ct"]), bytes.fromhex(v["info"]),
+                          bytes.fromhex(first["aad"]), sender_pk(v)) == bytes.fromhex(first["pt"])
+
+
+@pytest.mark.parametrize("mode", [BASE, AUTH])
+def test_rfc9180_context_open_in_sequence(mode):
+    for v in vectors(mode, 0x0001):
+        enc, sk_r = recipient_args(v)
+        context = suite_for(v).setup_recipient(enc, sk_r, bytes.fromhex(v["info"]), sender_pk(v))
+        # Sequence numbers 0-256 cross the byte boundary of the nonce XOR
+        for seq, e in enumerate(v["encryptions"]):
+            assert context.sequence_number == seq
+            assert context.open(bytes.fromhex(e["ct"]), bytes.fromhex(e["aad"])) == \
+                bytes.fromhex(e["pt"])
+        assert context.sequence_number == 257
+
+
+def test_rfc9180_context_rejects_out_of_order():
+    v = vectors(BASE, 0x0001)[0]
+    enc, sk_r = recipient_args(v)
+    context = suite_for(v).setup_recipient(enc, sk_r, bytes.fromhex(v["info"]))
+    second = v["encryptions"][1]
+    with pytest.raises(ValueError):
+        context.open(bytes.fromhex(second["ct"]), bytes.fromhex(second["aad"]))
+    # The failed open didn't advance the context
+    first = v["encryptions"][0]
+    assert context.sequence_number == 0
+    assert context.open(bytes.fromhex(first["ct"]), bytes.fromhex(first["aad"])) == \
+        bytes.fromhex(first["pt"])
+
+
+@pytest.mark.parametrize("mode", [BASE, AUTH])
+@pytest.mark.parametrize("aead_id", [0x0001, 0xFFFF])
+def test_rfc9180_receive_export(mode, aead_id):
+    for v in vectors(mode, aead_id):
+        suite = suite_for(v)
+        enc, sk_r = recipient_args(v)
+        for e in v["exports"]:
+            assert suite.receive_export(enc, sk_r, bytes.fromhex(e["exporter_context"]), e["L"],
+                                        bytes.fromhex(v["info"]), sender_pk(v)) == \
+                bytes.fromhex(e["exported_value"])
+
+
+@pytest.mark.parametrize("kem", ["kyber768", "x25519-kyber768"])
+def test_hybrid_round_trip(kem):
+    suite = reliquary_encryptor.HpkeSuite(kem, "hkdf-sha256", "aes-256-gcm")
+    pk_r, sk_r = suite.generate_keypair()
+    enc, ct = suite.seal(pk_r, b"quarterly key rotation", INFO, b"aad")
+    assert suite.open(enc, sk_r, ct, INFO, b"aad") == b"quarterly key rotation"
+
+    context_enc, sender = suite.setup_sender(pk_r, INFO)
+    recipient = suite.setup_recipient(context_enc, sk_r, INFO)
+    for n in range(3):
+        assert recipient.open(sender.seal(f"message {n}".encode())) == f"message {n}".encode()
+    assert sender.export(b"channel binding", 32) == recipient.export(b"channel binding", 32)
+
+    # A different info derives a different key schedule
+    with pytest.raises(ValueError):
+        suite.open(enc, sk_r, ct, b"other info", b"aad")
+
+    export_enc, secret = suite.send_export(pk_r, b"exporter", 48, INFO)
+    assert len(secret) == 48
+    assert suite.receive_export(export_enc, sk_r, b"exporter", 48, INFO) == secret
+    with pytest.raises(ValueError, match="auth mode needs the x25519 KEM"):
+        suite.seal(pk_r, b"msg", INFO, sender_sk=sk_r)
+
+
+def test_wrong_info_or_sender_is_rejected():
+    suite = reliquary_encryptor.HpkeSuite("x25519", "hkdf-sha256", "aes-128-gcm")
+    pk_r, sk_r = suite.generate_keypair()
+    pk_s, sk_s = suite.generate_keypair()
+    impostor_pk, _ = suite.generate_keypair()
+
+    enc, ct = suite.seal(pk_r, b"payload", INFO, b"aad", sender_sk=sk_s)
+    assert suite.open(enc, sk_r, ct, INFO, b"aad", sender_pk=pk_s) == b"payload"
+    with pytest.raises(ValueError):
+        suite.open(enc, sk_r, ct, b"other info", b"aad", sender_pk=pk_s)
+    with pytest.raises(ValueError):
+        suite.open(enc, sk_r, ct, INFO, b"aad", sender_pk=impostor_pk)
+    # An auth-mode message doesn't open in base mode, nor the other way round
+    with pytest.raises(ValueError):
+        suite.open(enc, sk_r, ct, INFO, b"aad")
+    enc, ct = suite.seal(pk_r, b"payload", INFO, b"aad")
+    with pytest.raises(ValueError):
+        suite.open(enc, sk_r, ct, INFO, b"aad", sender_pk=pk_s)
+
+    enc, secret = suite.send_export(pk_r, b"ctx", 32, INFO, sender_sk=sk_s)
+    assert suite.receive_export(enc, sk_r, b"ctx", 32, INFO, sender_pk=pk_s) == secret
+    assert suite.receive_export(enc, sk_r, b"ctx", 32, INFO, sender_pk=impostor_pk) != secret
+    assert suite.receive_export(enc, sk_r, b"ctx", 32, b"other info", sender_pk=pk_s) != secret
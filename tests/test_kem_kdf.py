@@ -0,0 +1,47 @@
+# tests/test_kem_kdf.py
+
+import hashlib
+import hmac
+
+import pytest
+
+reliquary_encryptor = pytest.importorskip("reliquary_encryptor")
+
+# --- Kyber encapsulation with a derived key: HKDF-SHA3-256 bound to ciphertext and info ---
+
+KyberParams = reliquary_encryptor.KyberParams
+INFO = b"myapp/file-key/v1"
+
+
+def hkdf_sha3_256(ikm, salt, info):
+    prk = hmac.new(salt, ikm, hashlib.sha3_256).digest()
+    return hmac.new(prk, info + b"\x01", hashlib.sha3_256).digest()
+
+
+@pytest.mark.parametrize("params", [KyberParams.Kyber768, KyberParams.Kyber1024,
+                                    KyberParams.MlKem1024])
+def test_derived_key_matches_rfc5869(params):
+    pk, sk = reliquary_encryptor.generate_kyber_keys(params)
+    key, ct = reliquary_encryptor.encapsulate_kyber(pk, params, info=INFO)
+    assert len(key) == 32
+    assert reliquary_encryptor.decapsulate_kyber(ct, sk, params, info=INFO) == key
+
+    # Salt is the ciphertext; info is "reliquary/kem/v1/" | parameter set name | 0x00 | info
+    shared_secret = reliquary_encryptor.decapsulate_kyber(ct, sk, params)
+    assert shared_secret != key
+    label = b"reliquary/kem/v1/" + params.name.encode() + b"\x00" + INFO
+    assert hkdf_sha3_256(shared_secret, ct, label) == key
+
+
+def test_info_separates_keys():
+    pk, sk = reliquary_encryptor.generate_kyber_keys(KyberParams.Kyber1024)
+    key, ct = reliquary_encryptor.encapsulate_kyber(pk, info=INFO)
+    assert reliquary_encryptor.decapsulate_kyber(ct, sk, info=b"myapp/file-key/v2") != key
+    assert reliquary_encryptor.decapsulate_kyber(ct, sk, info=INFO + b"\x00") != key
+    # Decapsulating without info gives the raw secret, not the derived key
+    assert reliquary_encryptor.decapsulate_kyber(ct, sk) != key
+
+    with pytest.raises(ValueError, match="non-empty domain-separation label"):
+        reliquary_encryptor.encapsulate_kyber(pk, info=b"")
+    with pytest.raises(ValueError, match="non-empty domain-separation label"):
+        reliquary_encryptor.decapsulate_kyber(ct, sk, info=b"")
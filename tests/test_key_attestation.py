@@ -0,0 +1,99 @@
+# tests/test_key_attestation.py
+
+import hashlib
+import os
+import time
+
+import pytest
+
+reliquary_encryptor = pytest.importorskip("reliquary_encryptor")
+
+# --- Key generation attestation: signed records of when, where and how a key was made ---
+
+KeyAttestation = reliquary_encryptor.KeyAttestation
+HOST_ID = b"machine-0042\x00vault-node-a"
+
+
+@pytest.fixture(scope="module")
+def device():
+    label = f"attestation-device-{os.urandom(4).hex()}"
+    return label, reliquary_encryptor.provider_generate_key("software", label)
+
+
+@pytest.mark.parametrize("algorithm", ["kyber768", "falcon512"])
+def test_generate_and_verify(device, algorithm):
+    label, device_pk = device
+    before = int(time.time())
+    handle, record = reliquary_encryptor.generate_attested_key(algorithm, label,
+                                                               host_id=HOST_ID)
+    attestation = KeyAttestation(record)
+    assert bytes(attestation) == record
+    assert attestation.algorithm == algorithm == handle.algorithm
+    assert attestation.public_key_fingerprint == reliquary_encryptor.key_fingerprint(
+        handle.public_key)
+    assert before <= attestation.generated_at <= int(time.time())
+    assert attestation.entropy_source == "os-csprng/getrandom"
+    assert attestation.host_fingerprint == hashlib.sha3_256(
+        b"reliquary/host-fingerprint/v1" + HOST_ID).digest()
+    assert attestation.self_test_passed and attestation.self_tests == "pairwise-consistency"
+    assert (attestation.signer_provider, attestation.signer_label,
+            attestation.signature_algorithm) == ("software", label, "falcon1024")
+    assert attestation.verify(handle.public_key, device_pk)
+
+
+def test_verification_failures(device):
+    label, device_pk = device
+    handle, record = reliquary_encryptor.generate_attested_key("kyber768", label)
+    attestation = KeyAttestation(record)
+    # Another key, or another device, doesn't match
+    other_handle, _ = reliquary_encryptor.generate_attested_key("kyber768", label)
+    assert not attestation.verify(other_handle.public_key, device_pk)
+    other_device = reliquary_encryptor.provider_generate_key(
+        "software", f"attestation-other-{os.urandom(4).hex()}")
+    assert not attestation.verify(handle.public_key, other_device)
+
+    # The signature covers every field: move generated_at by one second
+    # magic | version | var8 "kyber768" | fingerprint | generated_at
+    offset = 5 + 1 + len(b"kyber768") + 32
+    backdated = bytearray(record)
+    backdated[offset + 7] ^= 1
+    assert not KeyAttestation(bytes(backdated)).verify(handle.public_key, device_pk)
+
+    # A failed self-test status never verifies, whatever the signature says
+    status = offset + 8 + 1 + len(b"os-csprng/getrandom") + 32
+    assert record[status] == 1
+    failed = KeyAttestation(record[:status] + b"\x00" + record[status + 1:])
+    assert not failed.self_test_passed
+    assert not failed.verify(handle.public_key, device_pk)
+
+
+def test_record_travels_with_backup(device):
+    label, device_pk = device
+    policy = reliquary_encryptor.KeyUsagePolicy(operations=["sign", "export"])
+    handle, record = reliquary_encryptor.generate_attested_key("falcon512", label,
+                                                               policy=policy, backup=True)
+    blob = reliquary_encryptor.export_key_encrypted(handle, kek=bytes(32), attestation=record)
+    assert reliquary_encryptor.encrypted_key_attestation(blob) == record
+    pk = reliquary_encryptor.encrypted_key_info(blob)[1]
+    assert KeyAttestation(reliquary_encryptor.encrypted_key_attestation(blob)).verify(
+        pk, device_pk)
+
+    other, _ = reliquary_encryptor.generate_attested_key("falcon512", label, policy=policy,
+                                                         backup=True)
+    with pytest.raises(ValueError, match="Attestation record is for a different key"):
+        reliquary_encryptor.export_key_encrypted(other, kek=bytes(32), attestation=record)
+
+
+def test_malformed_records(device):
+    label, _ = device
+    _, record = reliquary_encryptor.generate_attested_key("kyber768", label)
+    with pytest.raises(ValueError, match="Not a key generation attestation"):
+        KeyAttestation(b"XXXX" + record[4:])
+    with pytest.raises(ValueError, match="Unsupported attestation version 2"):
+        KeyAttestation(record[:4] + b"\x02" + record[5:])
+    with pytest.raises(ValueError, match="Trailing data after attestation"):
+        KeyAttestation(record + b"\x00")
+    with pytest.raises(ValueError):
+        KeyAttestation(record[:-1])
+    with pytest.raises(ValueError):
+        reliquary_encryptor.generate_attested_key("kyber768", f"missing-{os.urandom(4).hex()}")
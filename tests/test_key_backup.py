@@ -0,0 +1,109 @@
+# tests/test_key_backup.py
+
+import pytest
+
+reliquary_encryptor = pytest.importorskip("reliquary_encryptor")
+
+# --- Encrypted key backups: passphrase and KEK protection, wrong secrets and tampering ---
+
+SecretKeyHandle = reliquary_encryptor.SecretKeyHandle
+# Cheap Argon2id costs keep the tests fast; the defaults are RFC 9106's
+COSTS = {"memory_kib": 1024, "iterations": 1, "parallelism": 1}
+KEK = bytes(range(32))
+BACKUP_POLICY = reliquary_encryptor.KeyUsagePolicy(operations=["sign", "export"])
+
+
+@pytest.fixture(scope="module")
+def signer():
+    return SecretKeyHandle.generate("falcon512", BACKUP_POLICY, backup=True)
+
+
+def test_passphrase_round_trip(signer):
+    blob = reliquary_encryptor.export_key_encrypted(signer, "correct horse", **COSTS)
+    assert blob[:6] == b"RQKB\x01\x01"
+    assert reliquary_encryptor.encrypted_key_info(blob) == ("falcon512", signer.public_key)
+    assert reliquary_encryptor.encrypted_key_attestation(blob) is None
+
+    restored = reliquary_encryptor.import_key_encrypted(blob, "correct horse")
+    assert restored.algorithm == "falcon512" and restored.public_key == signer.public_key
+    signature = reliquary_encryptor.sign_falcon(
+        b"msg", restored, reliquary_encryptor.FalconParams.Falcon512)
+    assert reliquary_encryptor.verify_falcon(b"msg", signature, signer.public_key,
+                                             reliquary_encryptor.FalconParams.Falcon512)
+    # A fresh salt every time
+    assert reliquary_encryptor.export_key_encrypted(signer, "correct horse", **COSTS) != blob
+
+
+def test_wrong_passphrase(signer):
+    blob = reliquary_encryptor.export_key_encrypted(signer, "correct horse", **COSTS)
+    for wrong in ("correct horse ", "Correct horse", ""):
+        with pytest.raises(ValueError, match="Key backup decryption failed"):
+            reliquary_encryptor.import_key_encrypted(blob, wrong)
+    with pytest.raises(ValueError, match="passphrase-protected; pass passphrase only"):
+        reliquary_encryptor.import_key_encrypted(blob, kek=KEK)
+    with pytest.raises(ValueError, match="passphrase-protected; pass passphrase only"):
+        reliquary_encryptor.import_key_encrypted(blob)
+
+
+def test_kek_round_trip_and_wrong_kek(signer):
+    blob = reliquary_encryptor.export_key_encrypted(signer, kek=KEK)
+    assert blob[:6] == b"RQKB\x01\x02"
+    assert reliquary_encryptor.import_key_encrypted(blob, kek=KEK).public_key == \
+        signer.public_key
+    with pytest.raises(ValueError, match="Key backup decryption failed"):
+        reliquary_encryptor.import_key_encrypted(blob, kek=KEK[::-1])
+    with pytest.raises(ValueError, match="KEK must be 32 bytes"):
+        reliquary_encryptor.import_key_encrypted(blob, kek=KEK[:16])
+    with pytest.raises(ValueError, match="KEK-protected; pass kek only"):
+        reliquary_encryptor.import_key_encrypted(blob, "correct horse")
+    with pytest.raises(ValueError, match="Pass exactly one of passphrase or kek"):
+        reliquary_encryptor.export_key_encrypted(signer, "correct horse", kek=KEK)
+    with pytest.raises(ValueError, match="Pass exactly one of passphrase or kek"):
+        reliquary_encryptor.export_key_encrypted(signer)
+
+
+def test_header_is_authenticated(signer):
+    blob = reliquary_encryptor.export_key_encrypted(signer, kek=KEK)
+    # magic | format | kdf | var8 "falcon512" | var16 public key | nonce | ciphertext
+    pk_start = 6 + 1 + len(b"falcon512") + 2
+    swapped = bytearray(blob)
+    swapped[pk_start] ^= 1
+    with pytest.raises(ValueError, match="Key backup decryption failed"):
+        reliquary_encryptor.import_key_encrypted(bytes(swapped), kek=KEK)
+    # Lowering the Argon2 costs of a passphrase backup changes the derived key
+    blob = reliquary_encryptor.export_key_encrypted(signer, "correct horse", memory_kib=2048,
+                                                    iterations=1, parallelism=1)
+    cheaper = blob[:6] + (1024).to_bytes(4, "big") + blob[10:]
+    with pytest.raises(ValueError, match="Key backup decryption failed"):
+        reliquary_encryptor.import_key_encrypted(cheaper, "correct horse")
+    with pytest.raises(ValueError, match="Not an encrypted key backup"):
+        reliquary_encryptor.import_key_encrypted(b"XXXX" + blob[4:], "correct horse")
+
+
+def test_export_needs_permission():
+    # Handles are not backed up unless they opted in at creation
+    for handle in (SecretKeyHandle.generate("kyber768"),
+                   SecretKeyHandle.generate("falcon512", BACKUP_POLICY)):
+        with pytest.raises(PermissionError, match="not created with backup=True"):
+            reliquary_encryptor.export_key_encrypted(handle, kek=KEK)
+    # ... and the policy must grant export by name; the default policy doesn't
+    for policy in (None, reliquary_encryptor.KeyUsagePolicy(operations=["sign"])):
+        handle = SecretKeyHandle.generate("falcon512", policy, backup=True)
+        with pytest.raises(PermissionError, match="does not allow export"):
+            reliquary_encryptor.export_key_encrypted(handle, kek=KEK)
+    with pytest.raises(TypeError, match="Expected a SecretKeyHandle"):
+        reliquary_encryptor.export_key_encrypted(b"raw key bytes", kek=KEK)
+    with pytest.raises(ValueError, match="Argon2 iterations"):
+        reliquary_encryptor.export_key_encrypted(
+            SecretKeyHandle.generate("falcon512", BACKUP_POLICY, backup=True), "correct horse",
+            memory_kib=1024, iterations=0, parallelism=1)
+
+
+def test_restored_handle_needs_its_own_opt_in(signer):
+    blob = reliquary_encryptor.export_key_encrypted(signer, kek=KEK)
+    restored = reliquary_encryptor.import_key_encrypted(blob, kek=KEK)
+    with pytest.raises(PermissionError, match="not created with backup=True"):
+        reliquary_encryptor.export_key_encrypted(restored, kek=KEK)
+    restored = reliquary_encryptor.import_key_encrypted(blob, kek=KEK, policy=BACKUP_POLICY,
+                                                        backup=True)
+    assert reliquary_encryptor.export_key_encrypted(restored, kek=KEK)[:6] == b"RQKB\x01\x02"
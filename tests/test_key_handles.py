@@ -0,0 +1,117 @@
+# tests/test_key_handles.py
+
+import threading
+
+import pytest
+
+reliquary_encryptor = pytest.importorskip("reliquary_encryptor")
+
+SecretKeyHandle = reliquary_encryptor.SecretKeyHandle
+Falcon512 = reliquary_encryptor.FalconParams.Falcon512
+SIGN_AND_EXPORT = reliquary_encryptor.KeyUsagePolicy(operations=["sign", "export"])
+
+# --- SecretKeyHandle: creation, export rules, destroy and usage accounting ---
+
+
+def test_handle_creation_and_description():
+    handle = SecretKeyHandle.generate()
+    assert (handle.algorithm, handle.exportable) == ("kyber1024", False)
+
+    shared_secret, ciphertext = reliquary_encryptor.encapsulate_kyber(handle.public_key)
+    assert reliquary_encryptor.decapsulate_kyber(ciphertext, handle) == shared_secret
+
+    signer = SecretKeyHandle.generate("falcon512")
+    assert signer.algorithm == "falcon512"
+    signature = reliquary_encryptor.sign_falcon(b"msg", signer)
+    assert reliquary_encryptor.verify_falcon(b"msg", signature, signer.public_key, Falcon512)
+    # A handle only serves its own algorithm
+    with pytest.raises(ValueError, match="expected a Kyber key"):
+        reliquary_encryptor.decapsulate_kyber(ciphertext, signer)
+    with pytest.raises(ValueError, match="expected a Falcon key"):
+        reliquary_encryptor.sign_falcon(b"msg", handle)
+
+    with pytest.raises(ValueError, match="Invalid secret key length"):
+        SecretKeyHandle.from_bytes("kyber1024", handle.public_key, b"short")
+    with pytest.raises(ValueError, match="Unsupported key algorithm"):
+        SecretKeyHandle.from_bytes("rsa", handle.public_key, b"")
+
+
+def test_export_rules():
+    handle = SecretKeyHandle.generate()
+    with pytest.raises(PermissionError, match="not exportable"):
+        handle.export()
+
+    public_key, secret_key = reliquary_encryptor.generate_kyber_keys()
+    export_policy = reliquary_encryptor.KeyUsagePolicy(operations=["decapsulate", "export"])
+    exportable = SecretKeyHandle.from_bytes("kyber1024", public_key, secret_key,
+                                            export_policy, exportable=True)
+    assert exportable.exportable
+    assert exportable.export() == secret_key
+    assert exportable.uses == 1
+    # Exportable, but the default policy never grants export
+    implicit = SecretKeyHandle.from_bytes("kyber1024", public_key, secret_key, exportable=True)
+    with pytest.raises(PermissionError, match="does not allow export"):
+        implicit.export()
+    # Exportable, but the policy doesn't allow it
+    restricted = SecretKeyHandle.from_bytes(
+        "kyber1024", public_key, secret_key,
+        reliquary_encryptor.KeyUsagePolicy(operations=["decapsulate"]), exportable=True)
+    with pytest.raises(PermissionError, match="does not allow export"):
+        restricted.export()
+    # Backups don't make a key exportable in the clear
+    backup_only = SecretKeyHandle.generate("falcon512", SIGN_AND_EXPORT, backup=True)
+    with pytest.raises(PermissionError, match="not exportable"):
+        backup_only.export()
+
+
+def test_destroy():
+    handle = SecretKeyHandle.generate("falcon1024", SIGN_AND_EXPORT, exportable=True)
+    handle.destroy()
+    for use in (lambda: reliquary_encryptor.sign_falcon(b"msg", handle),
+                lambda: handle.public_key,
+                handle.export):
+        with pytest.raises(ValueError, match="has been destroyed"):
+            use()
+
+
+def test_uses_are_counted():
+    policy = reliquary_encryptor.KeyUsagePolicy(max_uses=2)
+    signer = SecretKeyHandle.generate("falcon512", policy)
+    reliquary_encryptor.sign_falcon(b"msg", signer)
+    reliquary_encryptor.sign_falcon(b"second", signer)
+    with pytest.raises(PermissionError, match="limit of 2 uses"):
+        reliquary_encryptor.sign_falcon(b"third", signer)
+
+    # Encapsulating to the public key never uses the handle up
+    kem = SecretKeyHandle.generate("kyber1024", policy)
+    encapsulated = [reliquary_encryptor.encapsulate_kyber(kem.public_key) for _ in range(5)]
+    for shared_secret, ciphertext in encapsulated[:2]:
+        assert reliquary_encryptor.decapsulate_kyber(ciphertext, kem) == shared_secret
+    with pytest.raises(PermissionError, match="limit of 2 uses"):
+        reliquary_encryptor.decapsulate_kyber(encapsulated[2][1], kem)
+
+
+def test_handles_are_usable_during_long_operations():
+    """Other handles stay usable, and a handle can be destroyed, while signatures run."""
+    signers = [SecretKeyHandle.generate("falcon1024") for _ in range(4)]
+    spare = SecretKeyHandle.generate()
+    outcomes = []
+
+    def sign_until_destroyed(handle):
+        try:
+            while True:
+                reliquary_encryptor.sign_falcon(b"msg", handle)
+        except ValueError as e:
+            outcomes.append(str(e))
+
+    threads = [threading.Thread(target=sign_until_destroyed, args=(h,)) for h in signers]
+    for thread in threads:
+        thread.start()
+    assert spare.algorithm == "kyber1024"
+    spare.destroy()
+    for handle in signers:
+        handle.destroy()
+    for thread in threads:
+        thread.join()
+    assert len(outcomes) == len(signers)
+    assert all(outcome.endswith("has been destroyed") for outcome in outcomes)
@@ -0,0 +1,105 @@
+# tests/test_key_hierarchy.py
+
+import hashlib
+import hmac
+
+import pytest
+
+reliquary_encryptor = pytest.importorskip("reliquary_encryptor")
+
+# --- Hierarchical key derivation: labeled paths, subtrees and rotation epochs ---
+
+MASTER = bytes(range(32))
+
+
+def reference_derive(path, epochs=None):
+    epochs = epochs or {}
+    key = hmac.new(b"reliquary/hierarchy/v1/root", MASTER, hashlib.sha3_256).digest()
+    absolute = []
+    for segment in path.split("/"):
+        absolute.append(segment)
+        info = (b"reliquary/hierarchy/v1/child"
+                + epochs.get("/".join(absolute), 0).to_bytes(4, "big")
+                + len(segment).to_bytes(2, "big") + segment.encode())
+        key = hmac.new(key, info + b"\x01", hashlib.sha3_256).digest()
+    return key
+
+
+def test_derivation_matches_hkdf():
+    hierarchy = reliquary_encryptor.KeyHierarchy(MASTER)
+    assert hierarchy.path == "" and hierarchy.epochs == {}
+    for path in ("tenant", "tenant/42", "tenant/42/records/7"):
+        assert hierarchy.derive(path) == reference_derive(path)
+    assert hierarchy.derive("tenant/42") != hierarchy.derive("tenant/43")
+    # Same master key, same keys
+    assert reliquary_encryptor.KeyHierarchy(MASTER).derive("tenant/42") == \
+        hierarchy.derive("tenant/42")
+
+
+def test_child_is_scoped():
+    hierarchy = reliquary_encryptor.KeyHierarchy(MASTER)
+    tenant = hierarchy.child("tenant/42")
+    assert tenant.path == "tenant/42"
+    assert tenant.derive("records/7") == hierarchy.derive("tenant/42/records/7")
+    assert tenant.child("records").derive("7") == hierarchy.derive("tenant/42/records/7")
+    # Relative paths can't climb out of the subtree
+    assert tenant.derive("..") != hierarchy.derive("tenant")
+
+
+def test_rotate_revokes_subtree():
+    hierarchy = reliquary_encryptor.KeyHierarchy(MASTER)
+    before = {path: hierarchy.derive(path) for path in
+              ("tenant", "tenant/42", "tenant/42/records/7", "tenant/43/records/7")}
+    assert hierarchy.epoch("tenant/42") == 0
+    assert hierarchy.rotate("tenant/42") == 1
+    assert hierarchy.rotate("tenant/42") == 2
+    assert hierarchy.epochs == {"tenant/42": 2}
+
+    # The rotated node and everything beneath it change; its parent and siblings don't
+    assert hierarchy.derive("tenant") == before["tenant"]
+    assert hierarchy.derive("tenant/43/records/7") == before["tenant/43/records/7"]
+    assert hierarchy.derive("tenant/42") != before["tenant/42"]
+    assert hierarchy.derive("tenant/42/records/7") != before["tenant/42/records/7"]
+    assert hierarchy.derive("tenant/42/records/7") == reference_derive(
+        "tenant/42/records/7", {"tenant/42": 2})
+
+
+def test_rotation_through_a_child_and_persisted_epochs():
+    hierarchy = reliquary_encryptor.KeyHierarchy(MASTER)
+    hierarchy.rotate("tenant/42/records")
+    tenant = hierarchy.child("tenant/42")
+    # Children see the epochs of their own subtree, keyed by absolute path
+    assert tenant.epochs == {"tenant/42/records": 1}
+    assert tenant.epoch("records") == 1
+    assert tenant.derive("records/7") == hierarchy.derive("tenant/42/records/7")
+    assert hierarchy.child("tenant/43").epochs == {}
+
+    # A child's rotation stays with the child
+    assert tenant.rotate("records/7") == 1
+    assert hierarchy.epoch("tenant/42/records/7") == 0
+    assert tenant.derive("records/7") != hierarchy.derive("tenant/42/records/7")
+
+    # Keys are re-derived from the master key plus the persisted epochs
+    restored = reliquary_encryptor.KeyHierarchy(MASTER, hierarchy.epochs)
+    assert restored.derive("tenant/42/records/7") == hierarchy.derive("tenant/42/records/7")
+    assert reliquary_encryptor.KeyHierarchy(MASTER).derive("tenant/42/records/7") != \
+        hierarchy.derive("tenant/42/records/7")
+
+
+def test_rejections():
+    with pytest.raises(ValueError, match="at least 32 bytes"):
+        reliquary_encryptor.KeyHierarchy(MASTER[:31])
+    with pytest.raises(ValueError, match="Empty segment"):
+        reliquary_encryptor.KeyHierarchy(MASTER, {"tenant//42": 1})
+    hierarchy = reliquary_encryptor.KeyHierarchy(MASTER)
+    with pytest.raises(ValueError, match="must not be empty"):
+        hierarchy.derive("")
+    with pytest.raises(ValueError, match="Empty segment"):
+        hierarchy.rotate("tenant/")
+    with pytest.raises(ValueError, match="deeper than 32 segments"):
+        hierarchy.derive("/".join(["a"] * 33))
+    with pytest.raises(ValueError, match="longer than 255 bytes"):
+        hierarchy.child("a" * 256)
+    exhausted = reliquary_encryptor.KeyHierarchy(MASTER, {"tenant": 2**32 - 1})
+    with pytest.raises(ValueError, match="exhausted its rotations"):
+        exhausted.rotate("tenant")
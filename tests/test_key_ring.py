@@ -0,0 +1,111 @@
+# tests/test_key_ring.py
+
+import pytest
+
+reliquary_encryptor = pytest.importorskip("reliquary_encryptor")
+
+# --- Versioned key ring: rotation, rewrapping, retirement and persistence ---
+
+KeyRing = reliquary_encryptor.KeyRing
+AAD = b"record-7"
+
+
+def test_encrypt_uses_current_version():
+    ring = KeyRing()
+    assert ring.current is None and ring.versions == []
+    with pytest.raises(ValueError, match="no current version"):
+        ring.encrypt(b"data")
+    assert ring.add_version() == 1
+    ciphertext = ring.encrypt(b"data", AAD)
+    assert ciphertext[:5] == b"RQKR\x01" and KeyRing.key_version(ciphertext) == 1
+    assert ring.decrypt(ciphertext, AAD) == b"data"
+    with pytest.raises(ValueError, match="Decryption failed"):
+        ring.decrypt(ciphertext, b"record-8")
+
+    # A staged version decrypts but isn't used for encryption until made current
+    assert ring.add_version(make_current=False) == 2
+    assert ring.current == 1 and KeyRing.key_version(ring.encrypt(b"data")) == 1
+    ring.set_current(2)
+    assert KeyRing.key_version(ring.encrypt(b"data")) == 2
+
+
+def test_rewrap_then_retire():
+    ring = KeyRing()
+    ring.add_version()
+    old = [ring.encrypt(f"record {n}".encode(), AAD) for n in range(3)]
+    ring.add_version()
+
+    rewrapped = [ring.rewrap(ciphertext, AAD) for ciphertext in old]
+    assert [KeyRing.key_version(ciphertext) for ciphertext in rewrapped] == [2, 2, 2]
+    # Rewrapping a current-version ciphertext still gives a fresh one
+    again = ring.rewrap(rewrapped[0], AAD)
+    assert again != rewrapped[0] and ring.decrypt(again, AAD) == b"record 0"
+    with pytest.raises(ValueError, match="Decryption failed"):
+        ring.rewrap(old[0], b"other aad")
+
+    ring.retire(1)
+    assert ring.versions == [2] and ring.retired == [1]
+    assert [ring.decrypt(ciphertext, AAD) for ciphertext in rewrapped] == [
+        b"record 0", b"record 1", b"record 2"]
+    # Anything left under the retired version is gone for good
+    with pytest.raises(ValueError, match="Key version 1 has been retired"):
+        ring.decrypt(old[0], AAD)
+    with pytest.raises(ValueError, match="Key version 1 has been retired"):
+        ring.rewrap(old[0], AAD)
+    # Retired numbers are never reused
+    assert ring.add_version() == 3
+
+
+def test_retire_rejections():
+    ring = KeyRing()
+    ring.add_version()
+    with pytest.raises(ValueError, match="Key version 1 is current"):
+        ring.retire(1)
+    with pytest.raises(ValueError, match="Unknown key version 5"):
+        ring.retire(5)
+    ring.add_version()
+    ring.retire(1)
+    with pytest.raises(ValueError, match="Key version 1 has been retired"):
+        ring.retire(1)
+    with pytest.raises(ValueError, match="Key version 1 has been retired"):
+        ring.set_current(1)
+
+
+def test_header_is_authenticated():
+    ring = KeyRing()
+    ring.add_version()
+    ring.add_version(make_current=False)
+    ciphertext = ring.encrypt(b"data")
+    # Relabelling the ciphertext to the other live version fails authentication
+    relabelled = ciphertext[:5] + (2).to_bytes(4, "big") + ciphertext[9:]
+    with pytest.raises(ValueError, match="Decryption failed"):
+        ring.decrypt(relabelled)
+    with pytest.raises(ValueError, match="Not a key ring ciphertext"):
+        KeyRing.key_version(b"RQKR\x01")
+    with pytest.raises(ValueError, match="Unsupported key ring ciphertext format 2"):
+        ring.decrypt(ciphertext[:4] + b"\x02" + ciphertext[5:])
+
+
+def test_persistence():
+    ring = KeyRing()
+    ring.add_version()
+    ring.add_version(bytes(range(32)))
+    ciphertext = ring.encrypt(b"data")
+    ring.add_version(make_current=False)
+    ring.retire(1)
+    restored = KeyRing(ring.export_keys(), ring.current, ring.retired)
+    assert restored.export_keys()[2] == bytes(range(32))
+    assert (restored.current, restored.versions, restored.retired) == (2, [2, 3], [1])
+    assert restored.decrypt(ciphertext) == b"data"
+    assert restored.add_version() == 4
+    # Without `current`, the highest live version is current
+    assert KeyRing(ring.export_keys()).current == 3
+
+    with pytest.raises(ValueError, match="Key versions start at 1"):
+        KeyRing({0: bytes(32)})
+    with pytest.raises(ValueError, match="both live and retired"):
+        KeyRing({1: bytes(32)}, retired=[1])
+    with pytest.raises(ValueError, match="Key version 1 must be 32 bytes"):
+        KeyRing({1: bytes(16)})
+    with pytest.raises(ValueError, match="Current version 2 is not in the key ring"):
+        KeyRing({1: bytes(32)}, current=2)
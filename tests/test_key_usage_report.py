@@ -0,0 +1,66 @@
+# tests/test_key_usage_report.py
+
+import csv
+import io
+import json
+
+from core.key_usage import KeyUsageReporter
+from core.merkle_logging import MerkleLogWriter
+
+# --- Per-key usage aggregation and JSON/CSV export ---
+
+def _entries():
+    entries = []
+    for day in range(1, 11):
+        for n in range(3):
+            entries.append({"event": "encrypt", "key_id": "kek-1", "bytes": 100,
+                            "caller": f"agent-{n}", "timestamp": f"2026-03-{day:02d}T10:00:00Z"})
+    entries += [{"event": "decrypt", "key_id": "kek-1", "bytes": 40, "caller": "agent-9",
+                 "status": "denied", "timestamp": "2026-03-11T03:00:00Z"} for _ in range(25)]
+    entries += [
+        {"event": "kms_envelope_opened", "kms_provider": "aws", "kms_key_id": "arn:kms/2",
+         "timestamp": "2026-03-05T12:00:00Z"},
+        {"event": "anomaly_detected", "kind": "unusual_hour", "subject_type": "key",
+         "subject": "arn:kms/2", "timestamp": "2026-03-05T12:00:01Z"},
+        {"event": "api_startup", "timestamp": "2026-03-05T12:00:00Z"},
+        {"event": "encrypt", "key_id": "old-key", "timestamp": "2025-12-01T00:00:00Z"},
+    ]
+    return entries
+
+
+def test_report_aggregates_per_key():
+    report = KeyUsageReporter(_entries()).report(start="2026-01-01T00:00:00Z")
+    assert [k["key_id"] for k in report["keys"]] == ["arn:kms/2", "kek-1"]
+    kek = report["keys"][1]
+    assert kek["operations"] == {"decrypt": 25, "encrypt": 30}
+    assert kek["bytes_by_operation"] == {"decrypt": 1000, "encrypt": 3000}
+    assert (kek["distinct_callers"], kek["failures"]) == (4, 25)
+    assert kek["heatmap"]["2026-03-01"] == 3 and kek["heatmap"]["2026-03-11"] == 25
+    assert "volume_spike" in kek["anomaly_flags"]
+    assert kek["first_seen"].startswith("2026-03-01T10:00")
+
+    kms = report["keys"][0]
+    assert kms["operations"] == {"kms_envelope_opened": 1}
+    assert kms["anomaly_flags"] == ["unusual_hour"]
+
+    weekly = KeyUsageReporter(_entries()).report(end="2026-03-08T00:00:00Z", days=90,
+                                                 bucket="week")
+    assert [k["key_id"] for k in weekly["keys"]] == ["arn:kms/2", "kek-1"]
+    assert sum(weekly["keys"][1]["heatmap"].values()) == 21
+
+
+def test_json_and_csv_export(tmp_path):
+    log = MerkleLogWriter(str(tmp_path / "audit.log"))
+    for entry in _entries()[:6]:
+        log.add_entry({k: v for k, v in entry.items() if k != "timestamp"})
+    reporter = KeyUsageReporter(log)
+    data = json.loads(reporter.report(fmt="json"))
+    assert data["keys"][0]["total_operations"] == 6 and data["entries_scanned"] == 6
+
+    rows = list(csv.DictReader(io.StringIO(reporter.report(fmt="csv"))))
+    assert rows == [{"key_id": "kek-1", "total_operations": "6", "failures": "0",
+                     "bytes_total": "600", "distinct_callers": "3",
+                     "first_seen": rows[0]["first_seen"], "last_seen": rows[0]["last_seen"],
+                     "operations": "encrypt=6", "anomaly_flags": ""}]
+    assert KeyUsageReporter.from_log_file(log.log_file_path).report(fmt="csv") == \
+        reporter.report(fmt="csv")
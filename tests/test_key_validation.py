@@ -0,0 +1,78 @@
+# tests/test_key_validation.py
+
+import pytest
+
+reliquary_encryptor = pytest.importorskip("reliquary_encryptor")
+
+# --- Structural validation of Kyber/Falcon keys and Kyber ciphertexts at ingestion ---
+
+KyberParams = reliquary_encryptor.KyberParams
+FalconParams = reliquary_encryptor.FalconParams
+KYBER_SETS = [KyberParams.Kyber512, KyberParams.Kyber768, KyberParams.Kyber1024,
+              KyberParams.MlKem1024]
+FALCON_SETS = [FalconParams.Falcon512, FalconParams.Falcon1024]
+
+
+@pytest.mark.parametrize("params", KYBER_SETS)
+def test_kyber_public_key(params):
+    pk, sk = reliquary_encryptor.generate_kyber_keys(params)
+    reliquary_encryptor.validate_kyber_public_key(pk, params)
+
+    with pytest.raises(ValueError, match="Invalid public key"):
+        reliquary_encryptor.validate_kyber_public_key(pk[:-1], params)
+    # The last 32 bytes are the matrix seed rho
+    with pytest.raises(ValueError, match="seed"):
+        reliquary_encryptor.validate_kyber_public_key(pk[:-32] + b"\x07" * 32, params)
+    with pytest.raises(ValueError, match="zero"):
+        reliquary_encryptor.validate_kyber_public_key(bytes(len(pk) - 32) + pk[-32:], params)
+    # Three bytes pack two 12-bit coefficients: 1 and 3328 pass, 1 and 3329 = q don't
+    reliquary_encryptor.validate_kyber_public_key(b"\x01\x00\xd0" + pk[3:], params)
+    unreduced = b"\x01\x10\xd0" + pk[3:]
+    with pytest.raises(ValueError, match="not reduced mod 3329"):
+        reliquary_encryptor.validate_kyber_public_key(unreduced, params)
+
+    # Encapsulation applies the same checks before touching the key
+    with pytest.raises(ValueError, match="not reduced mod 3329"):
+        reliquary_encryptor.encapsulate_kyber(unreduced, params)
+    _, ct = reliquary_encryptor.encapsulate_kyber(pk, params)
+    reliquary_encryptor.decapsulate_kyber(ct, sk, params)
+
+
+@pytest.mark.parametrize("params", KYBER_SETS)
+def test_kyber_ciphertext(params):
+    pk, sk = reliquary_encryptor.generate_kyber_keys(params)
+    _, ct = reliquary_encryptor.encapsulate_kyber(pk, params)
+    reliquary_encryptor.validate_kyber_ciphertext(ct, params)
+    with pytest.raises(ValueError, match="Invalid ciphertext length"):
+        reliquary_encryptor.validate_kyber_ciphertext(ct + b"\x00", params)
+    with pytest.raises(ValueError, match="degenerate constant encoding"):
+        reliquary_encryptor.validate_kyber_ciphertext(b"\xaa" * len(ct), params)
+    with pytest.raises(ValueError, match="degenerate constant encoding"):
+        reliquary_encryptor.decapsulate_kyber(bytes(len(ct)), sk, params)
+
+
+@pytest.mark.parametrize("params", FALCON_SETS)
+def test_falcon_public_key(params):
+    pk, sk = reliquary_encryptor.generate_falcon_keys(params)
+    reliquary_encryptor.validate_falcon_public_key(pk, params)
+    assert pk[0] == params.logn
+
+    with pytest.raises(ValueError, match="Invalid public key"):
+        reliquary_encryptor.validate_falcon_public_key(pk[:-1], params)
+    with pytest.raises(ValueError, match="Header byte"):
+        reliquary_encryptor.validate_falcon_public_key(bytes([pk[0] ^ 0x03]) + pk[1:], params)
+    with pytest.raises(ValueError, match="zero"):
+        reliquary_encryptor.validate_falcon_public_key(pk[:1] + bytes(len(pk) - 1), params)
+    # The first 14 bits are coefficient 0: 12288 passes, 12289 = q doesn't
+    reliquary_encryptor.validate_falcon_public_key(pk[:1] + b"\xc0\x00" + pk[3:], params)
+    with pytest.raises(ValueError, match="not reduced mod 12289"):
+        reliquary_encryptor.validate_falcon_public_key(pk[:1] + b"\xc0\x04" + pk[3:], params)
+
+    # Verification applies the same checks to the signer's key
+    signature = reliquary_encryptor.sign_falcon(b"msg", sk, params)
+    with pytest.raises(ValueError, match="Header byte"):
+        reliquary_encryptor.verify_falcon(b"msg", signature, b"\x00" + pk[1:], params)
+    # A key for the other parameter set is the wrong length
+    other = FALCON_SETS[1] if params == FALCON_SETS[0] else FALCON_SETS[0]
+    with pytest.raises(ValueError, match="Invalid public key"):
+        reliquary_encryptor.validate_falcon_public_key(pk, other)
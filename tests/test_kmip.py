@@ -0,0 +1,129 @@
+# tests/test_kmip.py
+
+import os
+
+import pytest
+
+from core.kmip import KmipClient, KmipError, KmipKeyRing, Operation
+from core.kmip.ttlv import (Item, ItemType, Tag, byte_string, decode, enumeration, integer,
+                            structure, text)
+
+# --- KMIP 2.x client against an in-memory KMS, and a KeyRing whose keys stay in the KMS ---
+
+class FakeKms:
+    """Answers KMIP requests from a socket-like interface."""
+
+    def __init__(self):
+        self.objects = {}
+        self.names = {}
+        self.states = {}
+        self.requests = []
+        self.outbox = b""
+
+    def sendall(self, data):
+        request = decode(data)
+        header = request.require(Tag.REQUEST_HEADER).require(Tag.PROTOCOL_VERSION)
+        assert header.require(Tag.PROTOCOL_VERSION_MAJOR).value == 2
+        batch = request.require(Tag.BATCH_ITEM)
+        operation = Operation(batch.require(Tag.OPERATION).value)
+        self.requests.append(operation)
+        try:
+            payload = self.handle(operation, batch.require(Tag.REQUEST_PAYLOAD))
+            result = [enumeration(Tag.RESULT_STATUS, 0), structure(Tag.RESPONSE_PAYLOAD, *payload)]
+        except KeyError as e:
+            result = [enumeration(Tag.RESULT_STATUS, 1), enumeration(Tag.RESULT_REASON, 1),
+                      text(Tag.RESULT_MESSAGE, f"Item not found: {e}")]
+        self.outbox += structure(
+            Tag.RESPONSE_MESSAGE,
+            structure(Tag.RESPONSE_HEADER, integer(Tag.BATCH_COUNT, 1)),
+            structure(Tag.BATCH_ITEM, enumeration(Tag.OPERATION, operation), *result)).encode()
+
+    def recv(self, n):
+        data, self.outbox = self.outbox[:n], self.outbox[n:]
+        return data
+
+    def close(self):
+        pass
+
+    def store(self, key, name):
+        uid = str(len(self.objects) + 1)
+        self.objects[uid] = key
+        self.states[uid] = "pre-active"
+        if name:
+            self.names[name] = uid
+        return uid
+
+    def handle(self, operation, payload):
+        uid_item = payload.find(Tag.UNIQUE_IDENTIFIER)
+        uid = uid_item.value if uid_item else None
+        attributes = payload.find(Tag.ATTRIBUTES)
+        name_item = attributes.find(Tag.NAME) if attributes else None
+        name = name_item.require(Tag.NAME_VALUE).value if name_item else None
+        if operation == Operation.CREATE:
+            uid = self.store(os.urandom(attributes.require(Tag.CRYPTOGRAPHIC_LENGTH).value // 8),
+                             name)
+        elif operation == Operation.REGISTER:
+            key_block = payload.require(Tag.SYMMETRIC_KEY).require(Tag.KEY_BLOCK)
+            uid = self.store(key_block.require(Tag.KEY_VALUE).require(Tag.KEY_MATERIAL).value, name)
+        elif operation == Operation.REKEY:
+            old = self.objects[uid]
+            moved = [n for n, u in self.names.items() if u == uid]
+            uid = self.store(os.urandom(len(old)), None)
+            self.states[uid] = "active"
+            self.names.update({n: uid for n in moved})
+        elif operation == Operation.GET:
+            return [text(Tag.UNIQUE_IDENTIFIER, uid), structure(
+                Tag.SYMMETRIC_KEY, structure(Tag.KEY_BLOCK, enumeration(Tag.KEY_FORMAT_TYPE, 1),
+                    structure(Tag.KEY_VALUE, byte_string(Tag.KEY_MATERIAL, self.objects[uid]))))]
+        elif operation == Operation.LOCATE:
+            return [text(Tag.UNIQUE_IDENTIFIER, self.names[name])] if name in self.names else []
+        elif operation in (Operation.ACTIVATE, Operation.REVOKE):
+            self.objects[uid]
+            self.states[uid] = "active" if operation == Operation.ACTIVATE else "deactivated"
+        elif operation == Operation.DESTROY:
+            del self.objects[uid]
+        return [text(Tag.UNIQUE_IDENTIFIER, uid)]
+
+def test_ttlv_encoding_matches_spec():
+    # KMIP spec example: an Integer 8 with tag 0x420020
+    assert integer(0x420020, 8).encode() == bytes.fromhex("42002002000000040000000800000000")
+    assert text(0x420020, "Hello World").encode() == \
+        bytes.fromhex("420020070000000B48656C6C6F20576F726C640000000000")
+    nested = structure(Tag.ATTRIBUTES, enumeration(Tag.CRYPTOGRAPHIC_ALGORITHM, 3),
+                       byte_string(Tag.KEY_MATERIAL, b"\x01\x02"))
+    assert decode(nested.encode()) == nested
+
+def test_kms_backed_key_ring():
+    reliquary_encryptor = pytest.importorskip("reliquary_encryptor")
+    kms = FakeKms()
+    client = KmipClient("kms.example.com", username="svc", password="pw", connect=lambda: kms)
+    ring = KmipKeyRing(client, "vault-data")
+    assert ring.create() == 1
+    ciphertext = ring.fetch().encrypt(b"secret", b"vault-1")
+
+    assert ring.rotate() == 2
+    assert client.locate("vault-data") == [ring.versions[2]]
+    local = ring.fetch()
+    assert local.current == 2
+    rewrapped = local.rewrap(ciphertext, b"vault-1")
+
+    # Reopen from the persisted manifest, then retire v1 in the KMS
+    reopened = KmipKeyRing(client, "vault-data", ring.manifest())
+    reopened.retire(1)
+    assert reopened.fetch().decrypt(rewrapped, b"vault-1") == b"secret"
+    with pytest.raises(ValueError, match="retired"):
+        reopened.fetch().decrypt(ciphertext, b"vault-1")
+    assert kms.requests.count(Operation.DESTROY) == 1
+
+    with pytest.raises(KmipError, match="Item not found"):
+        client.get_key("missing")
+
+def test_register_existing_key():
+    reliquary_encryptor = pytest.importorskip("reliquary_encryptor")
+    kms = FakeKms()
+    ring = KmipKeyRing(KmipClient("kms", connect=lambda: kms), "migrated")
+    key = os.urandom(32)
+    assert ring.register(key) == 1
+    assert ring.fetch().export_keys() == {1: key}
+    with pytest.raises(KmipError, match="current"):
+        ring.retire(1)
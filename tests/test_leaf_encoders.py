@@ -0,0 +1,64 @@
+# tests/test_leaf_encoders.py
+
+import pytest
+
+from core.merkle_logging import (LeafEncodingError, MerkleTree, encode_leaf,
+                                 register_leaf_encoder, registered_leaf_encoders,
+                                 verify_exported_proof)
+from core.merkle_logging.merkle import create_merkle_root
+
+# --- Pluggable leaf encoders for Merkle trees over Python objects ---
+
+RECORDS = [{"vault": "v1", "op": "seal", "n": 1}, {"op": "open", "vault": "v1", "n": 2},
+           {"vault": "v2", "tags": ["a", "é"], "n": 3}]
+
+
+def test_json_encoder_is_canonical():
+    reordered = [dict(reversed(list(r.items()))) for r in RECORDS]
+    assert create_merkle_root(RECORDS, "json") == create_merkle_root(reordered, "json")
+    assert encode_leaf({"b": 1, "a": "é"}, "json") == '{"a":"é","b":1}'.encode()
+    for bad in [{1: "x"}, [float("nan")], {"when": object()}]:
+        with pytest.raises(LeafEncodingError):
+            encode_leaf(bad, "json")
+
+
+def test_utf8_and_raw_encoders():
+    words = ["alpha", "beta", "gamma"]
+    assert MerkleTree(words, "utf8").root == MerkleTree([w.encode() for w in words]).root
+    with pytest.raises(LeafEncodingError, match="Leaf 1"):
+        MerkleTree([b"bytes", "text"])
+    with pytest.raises(KeyError, match="Unknown leaf encoder"):
+        MerkleTree(words, "yaml")
+
+
+def test_exported_proofs_record_the_encoder():
+    for size in range(1, 8):
+        records = [dict(RECORDS[i % 3], n=i) for i in range(size)]
+        tree = MerkleTree(records, leaf_encoder="json")
+        for i, record in enumerate(records):
+            exported = tree.export_proof(i)
+            assert exported["leaf_encoder"] == "json"
+            assert verify_exported_proof(record, exported, root=tree.root)
+            assert tree.verify_object(record, i, tree.get_proof(i))
+            assert not verify_exported_proof(dict(record, n=-1), exported)
+            if size > 1:
+                moved = dict(exported, leaf_index=(i + 1) % size)
+                assert not verify_exported_proof(record, moved)
+
+
+def test_custom_encoders_cannot_collide():
+    name = "test-upper"
+    if name not in registered_leaf_encoders():
+        register_leaf_encoder(name, lambda obj: obj.upper().encode())
+    with pytest.raises(ValueError, match="already registered"):
+        register_leaf_encoder(name, lambda obj: obj.encode())
+    with pytest.raises(ValueError, match="already registered"):
+        register_leaf_encoder("json", lambda obj: b"")
+    assert MerkleTree(["a", "b"], name).root == MerkleTree(["A", "B"], "utf8").root
+
+
+def test_cbor_encoder_is_canonical():
+    pytest.importorskip("cbor2")
+    reordered = [dict(reversed(list(r.items()))) for r in RECORDS]
+    assert create_merkle_root(RECORDS, "cbor") == create_merkle_root(reordered, "cbor")
+    assert create_merkle_root(RECORDS, "cbor") != create_merkle_root(RECORDS, "json")
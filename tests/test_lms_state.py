@@ -0,0 +1,36 @@
+# tests/test_lms_state.py
+
+import os
+import tempfile
+
+import pytest
+
+reliquary_encryptor = pytest.importorskip("reliquary_encryptor")
+
+# --- Stateful LMS signing: the leaf index lives only in the Rust-managed state file ---
+
+@pytest.fixture(scope="module")
+def lms_key():
+    directory = tempfile.mkdtemp()
+    state_path = os.path.join(directory, "signer.lms")
+    public_key = reliquary_encryptor.generate_lms_keys(state_path, 5)
+    return state_path, public_key
+
+def test_state_survives_reopen_and_exhausts(lms_key):
+    state_path, public_key = lms_key
+    signer = reliquary_encryptor.LmsSigner(state_path)
+    assert signer.remaining_signatures == 32
+    signature = signer.sign(b"firmware-1.2.3")
+    assert reliquary_encryptor.verify_lms(b"firmware-1.2.3", signature, public_key)
+    assert not reliquary_encryptor.verify_lms(b"firmware-1.2.4", signature, public_key)
+
+    with pytest.raises(RuntimeError):
+        reliquary_encryptor.LmsSigner(state_path)
+    del signer
+
+    signer = reliquary_encryptor.LmsSigner(state_path)
+    assert signer.remaining_signatures == 31
+    for _ in range(31):
+        signer.sign(b"artifact")
+    with pytest.raises(ValueError, match="exhausted"):
+        signer.sign(b"artifact")
@@ -0,0 +1,55 @@
+# tests/test_mceliece.py
+
+import pytest
+
+reliquary_encryptor = pytest.importorskip("reliquary_encryptor")
+
+# --- Classic McEliece (mceliece8192128f) with buffer-protocol key objects ---
+
+
+@pytest.fixture(scope="module")
+def keys():
+    return reliquary_encryptor.generate_mceliece_keys()
+
+
+def test_key_sizes_and_buffers(keys):
+    pk_size, sk_size, ct_size = reliquary_encryptor.mceliece_key_sizes()
+    assert (pk_size, sk_size, ct_size) == (1357824, 14120, 208)
+    pk, sk = keys
+    assert len(pk) == pk_size and len(sk) == sk_size
+
+    view = memoryview(pk)
+    assert view.readonly and view.nbytes == pk_size and view.format == "B"
+    assert view.tobytes() == pk.to_bytes()
+    with pytest.raises(TypeError):
+        view[0] = 0
+    view.release()
+
+
+def test_round_trip_with_any_buffer(keys):
+    pk, sk = keys
+    shared_secret, ciphertext = reliquary_encryptor.encapsulate_mceliece(pk)
+    assert len(shared_secret) == 32 and len(ciphertext) == 208
+    assert reliquary_encryptor.decapsulate_mceliece(ciphertext, sk) == shared_secret
+    # bytes, memoryview and bytearray work as well as KeyBuffer
+    assert reliquary_encryptor.decapsulate_mceliece(ciphertext, sk.to_bytes()) == shared_secret
+    assert reliquary_encryptor.decapsulate_mceliece(ciphertext, memoryview(sk)) == shared_secret
+    other_secret, other_ct = reliquary_encryptor.encapsulate_mceliece(
+        bytearray(pk.to_bytes()))
+    assert other_secret != shared_secret
+    assert reliquary_encryptor.decapsulate_mceliece(other_ct, sk) == other_secret
+
+
+def test_rejections(keys):
+    pk, sk = keys
+    shared_secret, ciphertext = reliquary_encryptor.encapsulate_mceliece(pk)
+    # Implicit rejection: a tampered ciphertext yields an unrelated secret, not an error
+    tampered = bytes([ciphertext[0] ^ 1]) + ciphertext[1:]
+    assert reliquary_encryptor.decapsulate_mceliece(tampered, sk) != shared_secret
+
+    with pytest.raises(ValueError, match="Invalid public key length"):
+        reliquary_encryptor.encapsulate_mceliece(pk.to_bytes()[:-1])
+    with pytest.raises(ValueError, match="Invalid ciphertext length"):
+        reliquary_encryptor.decapsulate_mceliece(ciphertext[:-1], sk)
+    with pytest.raises(ValueError, match="Invalid secret key length"):
+        reliquary_encryptor.decapsulate_mceliece(ciphertext, sk.to_bytes() + b"\x00")
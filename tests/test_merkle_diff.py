@@ -0,0 +1,71 @@
+# tests/test_merkle_diff.py
+
+import hashlib
+
+import pytest
+
+reliquary_merkle = pytest.importorskip("reliquary_merkle")
+
+BLOCKS = [f"entry {n}".encode() for n in range(45)]
+
+
+def key(n):
+    return hashlib.sha256(f"key {n}".encode()).digest()
+
+
+def test_indexed_diff_reports_changes_with_proofs(tmp_path):
+    old = reliquary_merkle.MerkleTree(BLOCKS[:40], 2)
+    edited = list(BLOCKS)
+    edited[3] = b"tampered"
+    edited[31] = b"rewritten"
+    new = reliquary_merkle.PersistentMerkleTree(tmp_path / "replica", 2)
+    new.extend(edited)
+
+    changes = reliquary_merkle.diff_trees(old, new)
+    assert [(c.kind, c.index) for c in changes] == (
+        [("changed", 3), ("changed", 31)] + [("added", i) for i in range(40, 45)])
+    for change in changes:
+        assert change.new_proof.verify_inclusion(edited[change.index], new.root())
+        assert change.new_leaf_hash == new.leaf_hash(change.index)
+        if change.kind == "changed":
+            assert change.old_proof.verify_inclusion(BLOCKS[change.index], old.root())
+        else:
+            assert change.old_proof is None and change.old_leaf_hash is None
+
+    # The other way round the tail is removed, and a snapshot diffs like any tree
+    new.export_snapshot(tmp_path / "replica.snap")
+    snap = reliquary_merkle.MerkleSnapshot(tmp_path / "replica.snap")
+    back = reliquary_merkle.diff_trees(snap, old)
+    assert [c.kind for c in back] == ["changed"] * 2 + ["removed"] * 5
+    assert back[-1].new_proof is None
+    assert reliquary_merkle.diff_trees(old, reliquary_merkle.MerkleTree(BLOCKS[:40], 2)) == []
+
+
+def test_indexed_diff_rejects_mismatched_trees():
+    with pytest.raises(ValueError, match="same version and hash algorithm"):
+        reliquary_merkle.diff_trees(reliquary_merkle.MerkleTree(BLOCKS),
+                                    reliquary_merkle.MerkleTree(BLOCKS, 2))
+    with pytest.raises(TypeError):
+        reliquary_merkle.diff_trees(reliquary_merkle.MerkleTree(BLOCKS), BLOCKS)
+
+
+def test_sparse_diff_reports_key_changes_with_proofs():
+    old = reliquary_merkle.SparseMerkleTree([(key(n), b"v%d" % n) for n in range(30)])
+    new = reliquary_merkle.SparseMerkleTree([(key(n), b"v%d" % n) for n in range(30)])
+    new.update(key(4), b"changed")
+    new.delete(key(9))
+    new.delete(key(17))
+    new.update(key(50), b"new")
+
+    changes = old.diff(new)
+    expected = {key(4): "changed", key(9): "removed", key(17): "removed", key(50): "added"}
+    assert {c.key: c.kind for c in changes} == expected
+    assert [c.key for c in changes] == sorted(expected)
+    for c in changes:
+        assert reliquary_merkle.verify_sparse_proof(c.key, c.old_value, c.old_proof, old.root())
+        assert reliquary_merkle.verify_sparse_proof(c.key, c.new_value, c.new_proof, new.root())
+    assert old.diff(old) == []
+    # Deleting a key lifts its lone sibling, which must not show up as a change
+    pair = reliquary_merkle.SparseMerkleTree([(key(1), b"a"), (key(2), b"b")])
+    single = reliquary_merkle.SparseMerkleTree([(key(1), b"a")])
+    assert [(c.key, c.kind) for c in pair.diff(single)] == [(key(2), "removed")]
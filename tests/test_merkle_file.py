@@ -0,0 +1,71 @@
+# tests/test_merkle_file.py
+
+import os
+
+import pytest
+
+reliquary_merkle = pytest.importorskip("reliquary_merkle")
+
+
+@pytest.fixture
+def blob(tmp_path):
+    path = tmp_path / "blob.bin"
+    path.write_bytes(os.urandom(10 * 1000 + 123))
+    return path
+
+
+def chunks_of(data, size):
+    return [data[i:i + size] for i in range(0, len(data), size)]
+
+
+@pytest.mark.parametrize("scheme", [(1, "sha256"), (2, "blake3")])
+def test_root_matches_chunk_tree(blob, scheme):
+    data = blob.read_bytes()
+    tree = reliquary_merkle.merkle_file(blob, 1000, *scheme)
+    assert (tree.chunk_count, tree.file_size, len(tree)) == (11, len(data), 11)
+    assert tree.root() == reliquary_merkle.create_merkle_root(chunks_of(data, 1000), *scheme)
+    assert tree.chunk_range(10) == (10000, len(data))
+    # A tree over more than one read batch
+    small = reliquary_merkle.merkle_file(blob, 7, *scheme)
+    assert small.root() == reliquary_merkle.create_merkle_root(chunks_of(data, 7), *scheme)
+
+
+def test_verified_chunk_and_range_reads(blob):
+    data = blob.read_bytes()
+    tree = reliquary_merkle.merkle_file(blob, 1000, 2)
+    root = tree.root()
+    chunk, proof = tree.read_chunk(4)
+    assert chunk == data[4000:5000]
+    assert proof == tree.prove_chunk(4)
+    assert proof.verify_inclusion(chunk, root)
+
+    # Bytes 2500..7100 span chunks 2 to 7
+    chunks, proof = tree.read_range(2500, 4600)
+    assert proof.indices == [2, 3, 4, 5, 6, 7]
+    assert b"".join(chunks) == data[2000:8000]
+    assert proof == tree.prove_range(2500, 4600)
+    assert proof.verify_multi(list(zip(proof.indices, chunks)), root)
+    chunks[1] = bytes(1000)
+    assert not proof.verify_multi(list(zip(proof.indices, chunks)), root)
+
+    for offset, length in [(0, 0), (len(data) - 1, 2)]:
+        with pytest.raises(ValueError, match="within the file"):
+            tree.prove_range(offset, length)
+
+
+def test_changed_file_and_bad_arguments(blob, tmp_path):
+    tree = reliquary_merkle.merkle_file(blob, 1000)
+    with open(blob, "r+b") as f:
+        f.seek(3500)
+        f.write(b"\xff" * 4)
+    with pytest.raises(ValueError, match="Chunk 3 no longer matches"):
+        tree.read_range(0, 5000)
+    assert tree.read_chunk(2)[0] == blob.read_bytes()[2000:3000]
+
+    empty = tmp_path / "empty.bin"
+    empty.write_bytes(b"")
+    assert reliquary_merkle.merkle_file(empty).root() == b""
+    with pytest.raises(ValueError, match="chunk_size"):
+        reliquary_merkle.merkle_file(blob, 0)
+    with pytest.raises(FileNotFoundError):
+        reliquary_merkle.merkle_file(tmp_path / "missing.bin")
@@ -0,0 +1,99 @@
+# tests/test_merkle_serialization.py
+
+import hashlib
+import struct
+
+import pytest
+
+reliquary_merkle = pytest.importorskip("reliquary_merkle")
+
+BLOCKS = [f"entry {n}".encode() for n in range(13)]
+
+# --- Wire format (layouts pinned so stored proofs stay readable) ---
+
+
+def test_root_layout():
+    root = reliquary_merkle.MerkleTree([b"a"]).tree_root()
+    digest = hashlib.sha256(b"a").digest()
+    assert root.to_bytes() == b"RQMR\x01" + struct.pack(">Q", 1) + b"\x20" + digest
+    tagged = reliquary_merkle.MerkleTree([b"a"], 2, "blake3").tree_root().to_bytes()
+    assert tagged[13:16] == b"\x22\x02\x03"
+
+
+def test_inclusion_proof_layout():
+    tree = reliquary_merkle.MerkleTree(BLOCKS[:3], 2, "sha3-256")
+    proof = tree.prove_inclusion(2)
+    # Leaf 2 of 3 is carried up past the first level, so its proof has one node
+    expected = (b"RQMP\x01" + b"\x01" + b"\x02\x02" + struct.pack(">QQI", 3, 2, 1)
+                + b"".join(tree.get_proof(2)))
+    assert proof.to_bytes() == expected
+
+# --- Round trips ---
+
+
+@pytest.mark.parametrize("scheme", [(1, "sha256"), (2, "sha256"), (2, "blake3"), (2, "shake256")])
+def test_proofs_round_trip_and_verify(scheme):
+    tree = reliquary_merkle.MerkleTree(BLOCKS, *scheme)
+    root = tree.root()
+    for i, block in enumerate(BLOCKS):
+        proof = reliquary_merkle.MerkleProof.from_bytes(tree.prove_inclusion(i).to_bytes())
+        assert proof == tree.prove_inclusion(i)
+        assert (proof.kind, proof.index, proof.tree_size) == ("inclusion", i, len(BLOCKS))
+        assert proof.verify_inclusion(block, root)
+        assert not proof.verify_inclusion(BLOCKS[i - 1], root)
+    for old_size in range(1, len(BLOCKS) + 1):
+        proof = reliquary_merkle.MerkleProof.from_bytes(tree.prove_consistency(old_size).to_bytes())
+        assert proof.old_size == old_size
+        assert proof.verify_consistency(tree.root_at(old_size), root)
+    proof = reliquary_merkle.MerkleProof.from_bytes(tree.prove_multi([9, 2, 4, 2]).to_bytes())
+    assert proof.indices == [2, 4, 9]
+    assert proof.verify_multi([(i, BLOCKS[i]) for i in (9, 4, 2)], root)
+    assert not proof.verify_multi([(i, BLOCKS[i]) for i in (2, 4)], root)
+
+
+def test_tree_and_root_round_trip():
+    tree = reliquary_merkle.MerkleTree(BLOCKS, 2, "blake3")
+    restored = reliquary_merkle.MerkleTree.from_bytes(tree.to_bytes())
+    assert (len(restored), restored.version, restored.hash_algorithm) == (13, 2, "blake3")
+    assert restored.root() == tree.root()
+    assert restored.get_consistency_proof(5, 13) == tree.get_consistency_proof(5, 13)
+    restored.append(b"entry 13")
+    assert restored.root() == reliquary_merkle.create_merkle_root(BLOCKS + [b"entry 13"], 2, "blake3")
+
+    head = tree.tree_root(7)
+    assert reliquary_merkle.MerkleRoot.from_bytes(head.to_bytes()) == head
+    assert (head.root, head.tree_size, head.hash_algorithm) == (tree.root_at(7), 7, "blake3")
+    assert reliquary_merkle.MerkleRoot(tree.root(), 13) == tree.tree_root()
+
+# --- Binding and malformed input ---
+
+
+def test_proofs_only_verify_against_their_scheme():
+    blake3 = reliquary_merkle.MerkleTree(BLOCKS, 2, "blake3")
+    sha256 = reliquary_merkle.MerkleTree(BLOCKS, 2)
+    proof = blake3.prove_inclusion(3)
+    assert not proof.verify_inclusion(BLOCKS[3], sha256.root())
+    assert not proof.verify_consistency(blake3.root(), blake3.root())
+    assert not blake3.prove_consistency(4).verify_consistency(sha256.root_at(4), blake3.root())
+
+
+def test_malformed_encodings_rejected():
+    encoded = reliquary_merkle.MerkleTree(BLOCKS, 2, "blake3").prove_multi([1, 5]).to_bytes()
+    cases = {
+        encoded[:-1]: "truncated",
+        encoded + b"\x00": "Trailing data",
+        b"RQMT" + encoded[4:]: "Not a serialized Merkle proof",
+        encoded[:4] + b"\x02" + encoded[5:]: "format version 2",
+        encoded[:5] + b"\x09" + encoded[6:]: "kind 9",
+        encoded[:7] + b"\x07" + encoded[8:]: "hash algorithm id 7",
+        encoded[:6] + b"\x01" + encoded[7:]: "version 1 with blake3",
+    }
+    for data, message in cases.items():
+        with pytest.raises(ValueError, match=message):
+            reliquary_merkle.MerkleProof.from_bytes(data)
+    # Indices must be ascending, so each multiproof has a single encoding
+    swapped = encoded[:20] + encoded[28:36] + encoded[20:28] + encoded[36:]
+    with pytest.raises(ValueError, match="ascending"):
+        reliquary_merkle.MerkleProof.from_bytes(swapped)
+    with pytest.raises(ValueError, match="at least one leaf"):
+        reliquary_merkle.MerkleRoot(bytes(32), 0)
@@ -0,0 +1,69 @@
+# tests/test_merkle_snapshot.py
+
+import struct
+
+import pytest
+
+reliquary_merkle = pytest.importorskip("reliquary_merkle")
+
+BLOCKS = [f"entry {n}".encode() for n in range(29)]
+
+
+@pytest.mark.parametrize("scheme", [(1, "sha256"), (2, "blake3")])
+def test_snapshot_serves_the_same_proofs(tmp_path, scheme):
+    tree = reliquary_merkle.MerkleTree(BLOCKS, *scheme)
+    tree.export_snapshot(tmp_path / "tree.snap")
+    snap = reliquary_merkle.MerkleSnapshot(tmp_path / "tree.snap")
+    assert (len(snap), snap.version, snap.hash_algorithm) == (len(BLOCKS), *scheme)
+    assert snap.root() == tree.root()
+    for i in range(len(BLOCKS)):
+        assert snap.leaf_hash(i) == tree.leaf_hash(i)
+        assert snap.get_positional_proof(i) == tree.get_positional_proof(i)
+        assert snap.prove_inclusion(i) == tree.prove_inclusion(i)
+    for old_size in range(1, len(BLOCKS) + 1):
+        assert snap.root_at(old_size) == tree.root_at(old_size)
+        assert snap.prove_consistency(old_size) == tree.prove_consistency(old_size)
+    assert snap.get_multi_proof([28, 3, 4]) == tree.get_multi_proof([28, 3, 4])
+    assert snap.tree_root() == tree.tree_root()
+    with pytest.raises(IndexError):
+        snap.leaf_hash(len(BLOCKS))
+
+
+def test_layout_and_persistent_store_export(tmp_path):
+    store = reliquary_merkle.PersistentMerkleTree(tmp_path / "store", 2, "sha3-256")
+    store.extend(BLOCKS[:20])
+    store.sync()
+    store.extend(BLOCKS[20:])  # still buffered when exported
+    store.export_snapshot(tmp_path / "tree.snap")
+    data = (tmp_path / "tree.snap").read_bytes()
+    assert data[:15] == b"RQMN\x01\x02\x02" + struct.pack(">Q", len(BLOCKS))
+    # Levels hold 29, 14, 7, 3 and 1 nodes
+    assert len(data) == 15 + 32 * (29 + 14 + 7 + 3 + 1)
+    snap = reliquary_merkle.MerkleSnapshot(tmp_path / "tree.snap")
+    assert snap.root() == reliquary_merkle.create_merkle_root(BLOCKS, 2, "sha3-256")
+    assert not (tmp_path / "tree.snap.partial").exists()
+
+    # Re-exporting replaces the file; an open snapshot keeps serving the old tree
+    store.append(b"late entry")
+    store.export_snapshot(tmp_path / "tree.snap")
+    assert len(snap) == len(BLOCKS)
+    assert len(reliquary_merkle.MerkleSnapshot(tmp_path / "tree.snap")) == len(BLOCKS) + 1
+
+
+def test_malformed_snapshots_rejected(tmp_path):
+    path = tmp_path / "tree.snap"
+    reliquary_merkle.MerkleTree(BLOCKS).export_snapshot(path)
+    data = path.read_bytes()
+    cases = {
+        data[:-1]: "truncated",
+        data + b"\x00": "Trailing data",
+        b"RQMT" + data[4:]: "Not a serialized Merkle snapshot",
+        data[:4] + b"\x07" + data[5:]: "format version 7",
+        data[:7] + struct.pack(">Q", 1 << 60) + data[15:]: "truncated",
+    }
+    for bad, message in cases.items():
+        path.write_bytes(bad)
+        with pytest.raises(ValueError, match=message):
+            reliquary_merkle.MerkleSnapshot(path)
+    with pytest.raises(FileNotFoundError):
+        reliquary_merkle.MerkleSnapshot(tmp_path / "missing.snap")
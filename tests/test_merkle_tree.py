@@ -0,0 +1,334 @@
+# tests/test_merkle_tree.py
+
+import hashlib
+
+import pytest
+
+reliquary_merkle = pytest.importorskip("reliquary_merkle")
+from core.merkle_logging import MerkleLogWriter, MerkleTree as PyMerkleTree
+
+# --- Incremental MerkleTree in reliquary_merkle ---
+
+
+def _walk(leaf, index, proof):
+    current = hashlib.sha256(leaf).digest()
+    for sibling in proof:
+        pair = current + sibling if index % 2 == 0 else sibling + current
+        current = hashlib.sha256(pair).digest()
+        index //= 2
+    return current
+
+
+def test_appends_match_full_rebuild():
+    tree = reliquary_merkle.MerkleTree()
+    assert len(tree) == 0 and tree.root() == b""
+    blocks = []
+    for n in range(1, 34):
+        blocks.append(f"entry {n}".encode())
+        assert tree.append(blocks[-1]) == n - 1
+        assert tree.root() == reliquary_merkle.create_merkle_root(blocks)
+        assert tree.root() == PyMerkleTree(blocks).root
+    assert len(tree) == 33
+    assert reliquary_merkle.MerkleTree(blocks).root() == tree.root()
+
+
+def test_proofs_cover_every_leaf():
+    blocks = [bytes([i]) * 3 for i in range(11)]
+    tree = reliquary_merkle.MerkleTree()
+    tree.extend(blocks)
+    for i, block in enumerate(blocks):
+        proof = tree.get_proof(i)
+        assert len(proof) * 32 == reliquary_merkle.proof_size(len(tree))
+        assert _walk(block, i, proof) == tree.root()
+        assert tree.leaf_hash(i) == hashlib.sha256(block).digest()
+    # The last leaf of 11 is unpaired: its first sibling is itself
+    assert tree.get_proof(10)[0] == tree.leaf_hash(10)
+    with pytest.raises(IndexError):
+        tree.get_proof(11)
+
+
+def test_log_writer_root_is_unchanged(tmp_path):
+    writer = MerkleLogWriter(str(tmp_path / "audit.log"))
+    for i in range(5):
+        writer.add_entry({"event": "access", "n": i})
+    entries = [entry.to_bytes() for entry in writer._entries_cache]
+    assert writer._current_root == PyMerkleTree(entries).root
+    reopened = MerkleLogWriter(str(tmp_path / "audit.log"))
+    assert reopened._current_root == writer._current_root
+    assert reopened.verify_log_integrity()
+
+# --- Positional proofs ---
+
+
+def test_positional_proofs_round_trip():
+    blocks = [f"entry {n}".encode() for n in range(7)]
+    root = reliquary_merkle.create_merkle_root(blocks)
+    tree = reliquary_merkle.MerkleTree(blocks)
+    for i, block in enumerate(blocks):
+        proof = reliquary_merkle.create_positional_proof(blocks, i)
+        assert proof == tree.get_positional_proof(i)
+        assert [sibling for sibling, _ in proof] == tree.get_proof(i)
+        assert [is_right for _, is_right in proof] == [(i >> level) % 2 == 0 for level in range(len(proof))]
+        assert reliquary_merkle.verify_positional_proof(block, proof, root, i, 7)
+        assert not reliquary_merkle.verify_positional_proof(blocks[(i + 1) % 7], proof, root, i, 7)
+
+
+def test_positional_proof_rejects_moved_siblings():
+    # Sorted-pair proofs verify whichever side a sibling is on; positional ones don't
+    blocks = [b"a", b"b", b"c", b"d"]
+    root = reliquary_merkle.create_merkle_root(blocks)
+    proof = reliquary_merkle.create_positional_proof(blocks, 1)
+    flipped = [(sibling, not is_right) for sibling, is_right in proof]
+    verify = reliquary_merkle.verify_positional_proof
+    assert verify(b"b", proof, root, 1, 4)
+    assert not verify(b"b", flipped, root, 1, 4)
+    assert not verify(b"b", proof[:1], root, 1, 4)
+    assert not verify(b"b", [(proof[0][0] + b"\0", True)] + proof[1:], root, 1, 4)
+    with pytest.raises(IndexError):
+        reliquary_merkle.create_positional_proof(blocks, 4)
+
+
+@pytest.mark.parametrize("version", [1, 2])
+def test_positional_proof_is_bound_to_index_and_tree_size(version):
+    three, four = [b"a", b"b", b"c"], [b"a", b"b", b"c", b"c"]
+    verify = reliquary_merkle.verify_positional_proof
+    root = reliquary_merkle.create_merkle_root(three, version)
+    proof = reliquary_merkle.create_positional_proof(three, 2, version)
+    assert verify(b"c", proof, root, 2, 3)
+    # A proof for the repeated last leaf of [a, b, c, c] claims an index outside the tree
+    repeated = reliquary_merkle.create_positional_proof(four, 3, version)
+    assert not verify(b"c", repeated, root, 3, 3)
+    assert not verify(b"c", repeated, root, 2, 3)
+    # Directions and length must fit the claimed position
+    assert not verify(b"c", proof, root, 0, 3)
+    assert not verify(b"c", proof, root, 2, 2)
+    assert not verify(b"c", proof + [(proof[-1][0], True)], root, 2, 3)
+    assert not verify(b"a", reliquary_merkle.create_positional_proof(three, 0, version), root, 0, 0)
+
+# --- v2 trees (RFC 6962 leaf / node prefixes, version-tagged roots) ---
+
+
+def test_v2_tree_matches_create_merkle_root():
+    blocks = [f"entry {n}".encode() for n in range(9)]
+    tree = reliquary_merkle.MerkleTree(blocks, version=2)
+    root = reliquary_merkle.create_merkle_root(blocks, version=2)
+    assert tree.version == 2 and tree.root() == root
+    assert len(root) == 33 and root[0] == 2
+    assert reliquary_merkle.merkle_root_version(root) == 2
+    assert reliquary_merkle.merkle_root_version(reliquary_merkle.create_merkle_root(blocks)) == 1
+    assert tree.leaf_hash(0) == hashlib.sha256(b"\x00" + blocks[0]).digest()
+    for i, block in enumerate(blocks):
+        proof = tree.get_positional_proof(i)
+        # The ninth leaf is carried up to the root's level and only needs the first eight
+        assert len(proof) == (1 if i == 8 else 4)
+        assert len(proof) * 32 <= reliquary_merkle.proof_size(len(tree))
+        assert reliquary_merkle.verify_positional_proof(block, proof, root, i, len(tree))
+
+
+def _rfc6962_root(blocks):
+    """MTH from RFC 6962 section 2.1: split at the largest power of two below n"""
+    if len(blocks) == 1:
+        return hashlib.sha256(b"\x00" + blocks[0]).digest()
+    k = 1 << ((len(blocks) - 1).bit_length() - 1)
+    return hashlib.sha256(b"\x01" + _rfc6962_root(blocks[:k]) + _rfc6962_root(blocks[k:])).digest()
+
+
+def test_v2_is_the_rfc6962_tree():
+    for n in range(1, 20):
+        blocks = [f"entry {i}".encode() for i in range(n)]
+        expected = b"\x02" + _rfc6962_root(blocks)
+        assert reliquary_merkle.create_merkle_root(blocks, version=2) == expected
+        assert reliquary_merkle.MerkleTree(blocks, version=2).root() == expected
+        builder = reliquary_merkle.RootBuilder(version=2)
+        builder.update_many(blocks)
+        assert builder.root() == expected
+
+
+def test_v2_roots_do_not_collide_on_a_repeated_last_leaf():
+    # CVE-2012-2459: duplicating the last node of odd levels makes these two trees share a root
+    three, four = [b"a", b"b", b"c"], [b"a", b"b", b"c", b"c"]
+    create = reliquary_merkle.create_merkle_root
+    assert create(three) == create(four)
+    for hash_algorithm in ("sha256", "sha3-256", "blake3", "shake256"):
+        assert create(three, 2, hash_algorithm) != create(four, 2, hash_algorithm)
+    proof = reliquary_merkle.MerkleTree(four, version=2).get_positional_proof(3)
+    assert not reliquary_merkle.verify_positional_proof(b"c", proof, create(three, 2), 2, 3)
+
+
+def test_v2_rejects_internal_node_as_leaf():
+    blocks = [b"a", b"b", b"c", b"d"]
+    for version, accepted in ((1, True), (2, False)):
+        tree = reliquary_merkle.MerkleTree(blocks, version=version)
+        inner = tree.leaf_hash(0) + tree.leaf_hash(1)
+        proof = tree.get_positional_proof(0)[1:]
+        assert reliquary_merkle.verify_positional_proof(inner, proof, tree.root(), 0, 2) is accepted
+
+
+def test_unknown_version_rejected():
+    with pytest.raises(ValueError, match="version 3"):
+        reliquary_merkle.create_merkle_root([b"a"], version=3)
+    with pytest.raises(ValueError):
+        reliquary_merkle.merkle_root_version(b"\x03" + bytes(32))
+
+# --- Hash algorithms (v2 trees record theirs in the root) ---
+
+HASH_ALGORITHMS = ["sha256", "sha3-256", "blake3", "shake256"]
+
+
+@pytest.mark.parametrize("hash_algorithm", HASH_ALGORITHMS)
+def test_hash_algorithm_trees_and_proofs(hash_algorithm):
+    blocks = [f"entry {n}".encode() for n in range(11)]
+    tree = reliquary_merkle.MerkleTree(blocks, version=2, hash_algorithm=hash_algorithm)
+    root = reliquary_merkle.create_merkle_root(blocks, 2, hash_algorithm)
+    assert tree.root() == root and tree.hash_algorithm == hash_algorithm
+    assert len(root) == (33 if hash_algorithm == "sha256" else 34)
+    assert reliquary_merkle.merkle_root_version(root) == 2
+    assert reliquary_merkle.merkle_root_hash_algorithm(root) == hash_algorithm
+    for i, block in enumerate(blocks):
+        proof = tree.get_positional_proof(i)
+        assert reliquary_merkle.verify_positional_proof(block, proof, root, i, len(blocks))
+    old_root = tree.root_at(4)
+    assert reliquary_merkle.verify_consistency_proof(4, 11, old_root, root, tree.get_consistency_proof(4, 11))
+    leaves = [(1, blocks[1]), (8, blocks[8])]
+    assert reliquary_merkle.verify_multi_proof(leaves, tree.get_multi_proof([1, 8]), root)
+
+
+def test_hash_algorithm_leaf_hashes():
+    expected = {"sha3-256": hashlib.sha3_256(b"\x00leaf").digest(),
+                "shake256": hashlib.shake_256(b"\x00leaf").digest(32)}
+    for hash_algorithm, leaf_hash in expected.items():
+        tree = reliquary_merkle.MerkleTree([b"leaf"], version=2, hash_algorithm=hash_algorithm)
+        assert tree.leaf_hash(0) == leaf_hash
+
+
+def test_proofs_are_bound_to_the_root_hash_algorithm():
+    blocks = [b"a", b"b", b"c"]
+    roots = {h: reliquary_merkle.create_merkle_root(blocks, 2, h) for h in HASH_ALGORITHMS}
+    assert len({root[-32:] for root in roots.values()}) == len(HASH_ALGORITHMS)
+    proof = reliquary_merkle.create_positional_proof(blocks, 0, 2, "blake3")
+    relabelled = b"\x02\x02" + roots["blake3"][2:]
+    assert reliquary_merkle.verify_positional_proof(b"a", proof, roots["blake3"], 0, 3)
+    assert not reliquary_merkle.verify_positional_proof(b"a", proof, relabelled, 0, 3)
+    tree = reliquary_merkle.MerkleTree(blocks, 2, "blake3")
+    other = reliquary_merkle.MerkleTree(blocks[:2], 2, "shake256").root()
+    assert not reliquary_merkle.verify_consistency_proof(2, 3, other, roots["blake3"],
+                                                         tree.get_consistency_proof(2, 3))
+
+
+def test_hash_algorithm_errors():
+    with pytest.raises(ValueError, match="needs tree version 2"):
+        reliquary_merkle.MerkleTree([b"a"], hash_algorithm="blake3")
+    with pytest.raises(ValueError, match="Unsupported Merkle hash algorithm"):
+        reliquary_merkle.create_merkle_root([b"a"], 2, "md5")
+    # SHA-256 roots only have the 33-byte form, and unknown ids aren't roots
+    for root in (b"\x02\x01" + bytes(32), b"\x02\x09" + bytes(32)):
+        with pytest.raises(ValueError, match="Not a Merkle root"):
+            reliquary_merkle.merkle_root_hash_algorithm(root)
+
+# --- Consistency proofs ---
+
+
+def test_consistency_proofs_between_all_sizes():
+    blocks = [f"entry {n}".encode() for n in range(19)]
+    for version in (1, 2):
+        tree = reliquary_merkle.MerkleTree(blocks, version)
+        for new_size in range(1, len(blocks) + 1):
+            new_root = reliquary_merkle.create_merkle_root(blocks[:new_size], version)
+            assert tree.root_at(new_size) == new_root
+            for old_size in range(1, new_size + 1):
+                old_root = tree.root_at(old_size)
+                proof = tree.get_consistency_proof(old_size, new_size)
+                assert proof == reliquary_merkle.create_consistency_proof(blocks[:new_size], old_size, version)
+                assert reliquary_merkle.verify_consistency_proof(old_size, new_size, old_root, new_root, proof)
+    assert tree.root_at(0) == b""
+
+
+def test_consistency_proof_rejects_rewritten_history():
+    blocks = [bytes([i]) * 4 for i in range(13)]
+    tree = reliquary_merkle.MerkleTree(blocks)
+    proof = tree.get_consistency_proof(6, 13)
+    forged = reliquary_merkle.create_merkle_root(blocks[:5] + [b"forged"])
+    verify = reliquary_merkle.verify_consistency_proof
+    assert verify(6, 13, tree.root_at(6), tree.root(), proof)
+    assert not verify(6, 13, forged, tree.root(), proof)
+    assert not verify(7, 13, tree.root_at(6), tree.root(), proof)
+    assert not verify(6, 13, tree.root_at(6), tree.root(), proof[:-1])
+    assert not verify(6, 13, tree.root_at(6), tree.root(), [bytes(32)] + proof[1:])
+    for old_size, new_size in ((0, 5), (6, 5), (5, 14)):
+        with pytest.raises(ValueError):
+            tree.get_consistency_proof(old_size, new_size)
+
+
+def test_log_writer_consistency_proof(tmp_path):
+    from core.crypto.verifier import verify_consistency_proof
+    writer = MerkleLogWriter(str(tmp_path / "audit.log"))
+    for i in range(3):
+        writer.add_entry({"event": "access", "n": i})
+    seen_root = writer.merkle_root
+    for i in range(3, 8):
+        writer.add_entry({"event": "access", "n": i})
+    exported = writer.get_consistency_proof(3)
+    assert (exported["old_size"], exported["new_size"]) == (3, 8)
+    assert bytes.fromhex(exported["old_root"]) == seen_root
+    assert bytes.fromhex(exported["new_root"]) == writer.merkle_root
+    assert verify_consistency_proof(3, 8, seen_root, writer.merkle_root,
+                                    [bytes.fromhex(p) for p in exported["proof"]])
+
+# --- Multiproofs ---
+
+
+def test_multi_proofs_for_leaf_subsets():
+    blocks = [f"record {n}".encode() for n in range(37)]
+    for version in (1, 2):
+        tree = reliquary_merkle.MerkleTree(blocks, version)
+        for indices in ([0], [36], [3, 4, 5], [0, 17, 36], list(range(0, 37, 3)), list(range(37))):
+            proof = tree.get_multi_proof(indices)
+            assert proof == reliquary_merkle.create_multi_proof(blocks, indices, version)
+            leaves = [(i, blocks[i]) for i in indices]
+            assert reliquary_merkle.verify_multi_proof(leaves, proof, tree.root())
+            assert reliquary_merkle.verify_multi_proof(leaves[::-1], proof, tree.root())
+    # Every leaf: nothing left to prove beyond the leaves themselves
+    assert tree.get_multi_proof(list(range(37))) == (37, [])
+
+
+def test_multi_proof_is_smaller_than_single_proofs():
+    blocks = [f"record {n}".encode() for n in range(1000)]
+    tree = reliquary_merkle.MerkleTree(blocks)
+    indices = list(range(200, 500))
+    size, nodes = tree.get_multi_proof(indices)
+    assert size == 1000
+    assert len(nodes) < 20 < sum(len(tree.get_proof(i)) for i in indices)
+    # Repeats and order don't change the proof
+    assert tree.get_multi_proof(indices[::-1] + indices[:5]) == (size, nodes)
+
+
+def test_multi_proof_rejects_wrong_leaves():
+    blocks = [bytes([i]) * 4 for i in range(21)]
+    tree = reliquary_merkle.MerkleTree(blocks)
+    proof = tree.get_multi_proof([2, 9, 20])
+    verify = reliquary_merkle.verify_multi_proof
+    leaves = [(2, blocks[2]), (9, blocks[9]), (20, blocks[20])]
+    assert verify(leaves, proof, tree.root())
+    assert not verify([(2, blocks[9]), (9, blocks[2]), (20, blocks[20])], proof, tree.root())
+    assert not verify(leaves[:2], proof, tree.root())
+    assert not verify(leaves + [(2, blocks[2])], proof, tree.root())
+    assert not verify(leaves, (20, proof[1]), tree.root())
+    with pytest.raises(IndexError):
+        tree.get_multi_proof([21])
+    with pytest.raises(ValueError):
+        tree.get_multi_proof([])
+
+# --- Parallel construction ---
+
+
+@pytest.mark.parametrize("size", [4095, 4096, 4097, 3 * 4096 + 5])
+def test_parallel_root_matches_serial(size):
+    from core.crypto import pure_verify
+    blocks = [n.to_bytes(4, "big") for n in range(size)]
+    for version, hash_algorithm in ((1, "sha256"), (2, "blake3")):
+        root = reliquary_merkle.create_merkle_root(blocks, version, hash_algorithm)
+        assert root == pure_verify.create_merkle_root(blocks, version, hash_algorithm)
+        tree = reliquary_merkle.MerkleTree(blocks[:7], version, hash_algorithm)
+        tree.extend(blocks[7:])
+        assert tree.root() == root
+
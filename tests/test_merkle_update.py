@@ -0,0 +1,47 @@
+# tests/test_merkle_update.py
+
+import pytest
+
+reliquary_merkle = pytest.importorskip("reliquary_merkle")
+
+# --- MerkleTree.update_leaf: replace one leaf in place, with a proof of the change ---
+
+RECORDS = [f"vault record {n}".encode() for n in range(13)]
+
+
+@pytest.mark.parametrize("scheme", [(1, "sha256"), (2, "blake3")])
+def test_updates_match_a_rebuild(scheme):
+    records = list(RECORDS)
+    tree = reliquary_merkle.MerkleTree(records, *scheme)
+    for index in [0, 5, 12, 12, 7]:
+        old_root, old_record = tree.root(), records[index]
+        records[index] = f"re-encrypted {index} after {old_record[-2:]!r}".encode()
+        root, proof = tree.update_leaf(index, records[index])
+        assert root == tree.root() == reliquary_merkle.create_merkle_root(records, *scheme)
+        assert proof == tree.prove_inclusion(index)
+        assert proof.verify_update(old_record, records[index], old_root, root)
+    # Later appends build on the updated nodes
+    tree.append(b"new record")
+    assert tree.root() == reliquary_merkle.create_merkle_root(records + [b"new record"], *scheme)
+
+
+def test_update_proof_binds_both_versions():
+    # Leaf 12 is the last of an odd level, so its own hash stands in as its sibling
+    for index in (3, 12):
+        tree = reliquary_merkle.MerkleTree(RECORDS, 2)
+        old_root = tree.root()
+        root, proof = tree.update_leaf(index, b"rotated")
+        assert proof.verify_update(RECORDS[index], b"rotated", old_root, root)
+        assert not proof.verify_update(b"other", b"rotated", old_root, root)
+        assert not proof.verify_update(RECORDS[index], b"other", old_root, root)
+        assert not proof.verify_update(RECORDS[index], b"rotated", root, old_root)
+        assert not proof.verify_update(RECORDS[index], b"rotated", tree.root_at(12), root)
+    assert not tree.prove_multi([3]).verify_update(RECORDS[3], b"rotated", old_root, root)
+
+
+def test_update_out_of_range():
+    tree = reliquary_merkle.MerkleTree(RECORDS)
+    with pytest.raises(IndexError):
+        tree.update_leaf(13, b"x")
+    with pytest.raises(IndexError):
+        reliquary_merkle.MerkleTree().update_leaf(0, b"x")
@@ -0,0 +1,130 @@
+# tests/test_ml_kem_ml_dsa.py
+
+import os
+
+import pytest
+
+reliquary_encryptor = pytest.importorskip("reliquary_encryptor")
+from core.crypto.pq_migration import (envelope_needs_migration, migrate_envelope,
+                                      migrate_multi_recipient_envelope,
+                                      migrate_signature_bundle)
+from core.crypto.pure_verify import envelope_info
+
+ML_KEM = reliquary_encryptor.KyberParams.MlKem1024
+KYBER = reliquary_encryptor.KyberParams.Kyber1024
+
+
+def pyca(name):
+    return pytest.importorskip(f"cryptography.hazmat.primitives.asymmetric.{name}")
+
+# --- ML-KEM-1024 (FIPS 203) ---
+
+def test_ml_kem_sizes_and_round_trip():
+    pk, sk = reliquary_encryptor.generate_kyber_keys(ML_KEM)
+    assert (len(pk), len(sk)) == (1568, 3168)
+    ss, ct = reliquary_encryptor.encapsulate_kyber(pk, ML_KEM)
+    assert len(ct) == 1568
+    assert reliquary_encryptor.decapsulate_kyber(ct, sk, ML_KEM) == ss
+
+def test_ml_kem_seed_keys_match_pyca():
+    mlkem = pyca("mlkem")
+    seed = os.urandom(64)
+    pk, sk = reliquary_encryptor.ml_kem_keys_from_seed(seed)
+    reference = mlkem.MLKEM1024PrivateKey.from_seed_bytes(seed)
+    assert pk == reference.public_key().public_bytes_raw()
+
+    shared, ct = reference.public_key().encapsulate()
+    assert reliquary_encryptor.decapsulate_kyber(ct, sk, ML_KEM) == shared
+    shared, ct = reliquary_encryptor.encapsulate_kyber(pk, ML_KEM)
+    assert reference.decapsulate(ct) == shared
+
+def test_ml_kem_implicit_rejection_matches_pyca():
+    mlkem = pyca("mlkem")
+    seed = bytes(range(64))
+    _, sk = reliquary_encryptor.ml_kem_keys_from_seed(seed)
+    reference = mlkem.MLKEM1024PrivateKey.from_seed_bytes(seed)
+    _, ct = reference.public_key().encapsulate()
+    tampered = bytes([ct[0] ^ 1]) + ct[1:]
+    assert reliquary_encryptor.decapsulate_kyber(tampered, sk, ML_KEM) == reference.decapsulate(tampered)
+
+def test_ml_kem_seed_length():
+    with pytest.raises(ValueError, match="seed"):
+        reliquary_encryptor.ml_kem_keys_from_seed(bytes(32))
+
+# --- ML-DSA-87 (FIPS 204) ---
+
+def test_ml_dsa_sizes_and_context():
+    pk, sk = reliquary_encryptor.generate_ml_dsa_keys()
+    assert (len(pk), len(sk)) == (2592, 4896)
+    sig = reliquary_encryptor.sign_ml_dsa(b"record", sk, b"ctx")
+    assert len(sig) == reliquary_encryptor.signature_size("ml-dsa-87") == 4627
+    assert reliquary_encryptor.verify_ml_dsa(b"record", sig, pk, b"ctx")
+    assert not reliquary_encryptor.verify_ml_dsa(b"record", sig, pk)
+    assert not reliquary_encryptor.verify_ml_dsa(b"record!", sig, pk, b"ctx")
+    assert not reliquary_encryptor.verify_ml_dsa(b"record", sig[:-1], pk, b"ctx")
+    with pytest.raises(ValueError, match="context"):
+        reliquary_encryptor.sign_ml_dsa(b"record", sk, bytes(256))
+
+def test_ml_dsa_deterministic_signing():
+    _, sk = reliquary_encryptor.ml_dsa_keys_from_seed(bytes(32))
+    sign = reliquary_encryptor.sign_ml_dsa
+    assert sign(b"m", sk, deterministic=True) == sign(b"m", sk, deterministic=True)
+    assert sign(b"m", sk) != sign(b"m", sk)
+
+def test_ml_dsa_interop_with_pyca():
+    mldsa = pyca("mldsa")
+    seed = os.urandom(32)
+    pk, sk = reliquary_encryptor.ml_dsa_keys_from_seed(seed)
+    reference = mldsa.MLDSA87PrivateKey.from_seed_bytes(seed)
+    assert pk == reference.public_key().public_bytes_raw()
+
+    reference.public_key().verify(reliquary_encryptor.sign_ml_dsa(b"msg", sk, b"ctx"), b"msg", b"ctx")
+    assert reliquary_encryptor.verify_ml_dsa(b"msg", reference.sign(b"msg", b"ctx"), pk, b"ctx")
+    assert reliquary_encryptor.verify_ml_dsa(b"msg", reference.sign(b"msg"), pk)
+
+def test_pkcs8_and_spki_round_trip():
+    for pk, sk, algorithm in (
+        reliquary_encryptor.ml_dsa_keys_from_seed(bytes(32)) + ("ml-dsa-87",),
+        reliquary_encryptor.ml_kem_keys_from_seed(bytes(64)) + ("ml-kem-1024",),
+    ):
+        der = reliquary_encryptor.export_secret_key_pkcs8(sk, algorithm)
+        assert reliquary_encryptor.import_secret_key_pkcs8(der) == (algorithm, sk, pk)
+        spki = reliquary_encryptor.export_public_key_spki(pk, algorithm, pem=True)
+        assert reliquary_encryptor.import_public_key_spki(spki) == (algorithm, pk)
+
+# --- Migration from round-3 parameter sets ---
+
+def test_envelope_migration():
+    kyber_pk, kyber_sk = reliquary_encryptor.generate_kyber_keys(KYBER)
+    ml_kem_pk, ml_kem_sk = reliquary_encryptor.generate_kyber_keys(ML_KEM)
+    envelope = reliquary_encryptor.encrypt_to_public_key(b"archive", kyber_pk)
+    assert envelope_needs_migration(envelope)
+
+    migrated = migrate_envelope(envelope, kyber_sk, ml_kem_pk)
+    assert envelope_info(migrated)["kem"] == "ml-kem-1024"
+    assert not envelope_needs_migration(migrated)
+    assert reliquary_encryptor.decrypt_with_secret_key(migrated, ml_kem_sk) == b"archive"
+    assert migrate_envelope(migrated, ml_kem_sk, ml_kem_pk) == migrated
+
+def test_multi_recipient_envelope_migration():
+    old = [reliquary_encryptor.generate_kyber_keys(KYBER) for _ in range(3)]
+    new = [reliquary_encryptor.generate_kyber_keys(ML_KEM) for _ in range(3)]
+    envelope = reliquary_encryptor.encrypt_to_recipients(
+        b"shared", [pk for pk, _ in old], KYBER, 2)
+    migrated = migrate_multi_recipient_envelope(
+        envelope, [sk for _, sk in old[:2]], [pk for pk, _ in new])
+    info = envelope_info(migrated)
+    assert info["threshold"] == 2
+    assert {slot["kem"] for slot in info["recipients"]} == {"ml-kem-1024"}
+    assert not envelope_needs_migration(migrated)
+    assert reliquary_encryptor.decrypt_with_secret_keys(migrated, [sk for _, sk in new[1:]]) == b"shared"
+
+def test_signature_bundle_migration():
+    dilithium_pk, dilithium_sk = reliquary_encryptor.generate_dilithium_keys()
+    ml_dsa_pk, ml_dsa_sk = reliquary_encryptor.generate_ml_dsa_keys()
+    bundle = reliquary_encryptor.create_signature_bundle(
+        b"deed", dilithium_sk, dilithium_pk, "dilithium5", "records")
+    migrated = migrate_signature_bundle(bundle, ml_dsa_sk, ml_dsa_pk, "records-2026")
+    layers = reliquary_encryptor.verify_signature_bundle(migrated, b"deed")
+    assert [(layer[0], layer[1], layer[-1]) for layer in layers] == [
+        ("records", "dilithium5", True), ("records-2026", "ml-dsa-87", True)]
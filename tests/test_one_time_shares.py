@@ -0,0 +1,77 @@
+# tests/test_one_time_shares.py
+
+import os
+
+import pytest
+
+reliquary_encryptor = pytest.importorskip("reliquary_encryptor")
+from core.crypto.one_time_shares import (FileTombstoneStore, InMemoryTombstoneStore,
+                                         OneTimeShareRecovery, SharesBurned,
+                                         create_one_time_shares, verify_tombstone_chain)
+
+# --- One-time share envelopes with burn-on-read tombstones ---
+
+
+@pytest.fixture(scope="module")
+def signer():
+    label = f"tombstone-signer-{os.urandom(4).hex()}"
+    return label, reliquary_encryptor.provider_generate_key("software", label)
+
+
+def test_single_use_envelope_burns(signer):
+    label, public_key = signer
+    envelope_id, shares = create_one_time_shares(b"recovery phrase", 2, 3)
+    recovery = OneTimeShareRecovery(InMemoryTombstoneStore(), label)
+
+    assert recovery.combine([shares[2], shares[0]]) == b"recovery phrase"
+    assert recovery.status(envelope_id) == {"envelope_id": envelope_id, "use_count": 1,
+                                            "max_uses": 1, "burned": True}
+    with pytest.raises(SharesBurned):
+        recovery.combine(shares[1:])
+
+    [tombstone] = recovery.evidence(envelope_id)
+    assert tombstone.envelope_id.hex() == envelope_id
+    assert tombstone.share_indices == [3, 1]
+    assert tombstone.verify(public_key)
+    assert verify_tombstone_chain([bytes(tombstone)], public_key, envelope_id)
+
+    # The module itself refuses a burned envelope, whatever the Python layer does
+    with pytest.raises(ValueError, match="is burned"):
+        reliquary_encryptor.combine_one_time_shares(shares[:2], label, "software",
+                                                    bytes(tombstone))
+
+
+def test_multi_use_chain_on_disk(signer, tmp_path):
+    label, public_key = signer
+    envelope_id, shares = create_one_time_shares(b"break-glass", 1, 2, max_uses=3)
+    store = FileTombstoneStore(str(tmp_path / "tombstones.jsonl"))
+    for _ in range(3):
+        assert OneTimeShareRecovery(store, label).combine([shares[1]]) == b"break-glass"
+    with pytest.raises(SharesBurned):
+        OneTimeShareRecovery(store, label).combine([shares[0]])
+
+    chain = store.chain(envelope_id)
+    assert [reliquary_encryptor.ShareTombstone(t).use_count for t in chain] == [1, 2, 3]
+    assert verify_tombstone_chain(chain, public_key)
+    assert not verify_tombstone_chain(chain[1:], public_key)
+    assert not verify_tombstone_chain([chain[0], chain[2]], public_key)
+    other_key = reliquary_encryptor.generate_falcon_keys()[0]
+    assert not verify_tombstone_chain(chain, other_key)
+
+
+def test_bad_shares_leave_no_tombstone(signer):
+    label, _ = signer
+    _, shares = create_one_time_shares(b"secret", 2, 3)
+    _, others = create_one_time_shares(b"secret", 2, 3)
+    store = InMemoryTombstoneStore()
+    recovery = OneTimeShareRecovery(store, label)
+
+    with pytest.raises(ValueError, match="needs 2 shares"):
+        recovery.combine(shares[:1])
+    with pytest.raises(ValueError, match="different envelopes"):
+        recovery.combine([shares[0], others[1]])
+    tampered = bytearray(shares[1])
+    tampered[30] ^= 1
+    with pytest.raises(ValueError, match="do not open"):
+        recovery.combine([shares[0], bytes(tampered)])
+    assert store.chain(reliquary_encryptor.one_time_share_info(shares[0])[0].hex()) == []
@@ -0,0 +1,85 @@
+# tests/test_pedersen.py
+
+import pytest
+
+reliquary_encryptor = pytest.importorskip("reliquary_encryptor")
+
+# --- Pedersen commitments, openings and opening proofs ---
+
+CONTEXT = b"audit/batch-17"
+# Order of the Ristretto group; blindings are scalars mod L, little-endian
+L = 2**252 + 27742317777372353535851937790883648493
+
+
+def test_commit_and_open():
+    commitment, blinding = reliquary_encryptor.pedersen_commit(870)
+    assert len(commitment) == 32 and len(blinding) == 32
+    assert reliquary_encryptor.pedersen_commit(870, blinding) == (commitment, blinding)
+    # Range proofs commit the same way
+    assert reliquary_encryptor.range_commit(870, blinding) == (commitment, blinding)
+
+    assert reliquary_encryptor.pedersen_verify_opening(commitment, 870, blinding)
+    assert not reliquary_encryptor.pedersen_verify_opening(commitment, 871, blinding)
+    _, other = reliquary_encryptor.pedersen_commit(870)
+    assert not reliquary_encryptor.pedersen_verify_opening(commitment, 870, other)
+
+    # Balances add up without opening the parts
+    a, ra = reliquary_encryptor.pedersen_commit(1_200)
+    b, rb = reliquary_encryptor.pedersen_commit(300)
+    total = reliquary_encryptor.pedersen_add([a, b])
+    r_sum = (int.from_bytes(ra, "little") + int.from_bytes(rb, "little")) % L
+    assert reliquary_encryptor.pedersen_verify_opening(total, 1_500, r_sum.to_bytes(32, "little"))
+
+    with pytest.raises(ValueError, match="canonical scalar"):
+        reliquary_encryptor.pedersen_commit(1, b"\xff" * 32)
+    with pytest.raises(ValueError, match="Ristretto point"):
+        reliquary_encryptor.pedersen_verify_opening(b"\xff" * 32, 1, blinding)
+
+
+def test_opening_proof():
+    commitment, blinding = reliquary_encryptor.pedersen_commit(42)
+    proof = reliquary_encryptor.pedersen_prove_opening(42, blinding, CONTEXT)
+    assert proof[:4] == b"RQPO" and len(proof) == 101
+    assert reliquary_encryptor.pedersen_verify_proof(proof, commitment, CONTEXT)
+    # Bound to the context and the commitment; a wrong value proves nothing
+    assert not reliquary_encryptor.pedersen_verify_proof(proof, commitment, b"other")
+    other, _ = reliquary_encryptor.pedersen_commit(42)
+    assert not reliquary_encryptor.pedersen_verify_proof(proof, other, CONTEXT)
+    wrong = reliquary_encryptor.pedersen_prove_opening(43, blinding, CONTEXT)
+    assert not reliquary_encryptor.pedersen_verify_proof(wrong, commitment, CONTEXT)
+    assert not reliquary_encryptor.pedersen_verify_proof(proof[:-1], commitment, CONTEXT)
+    assert not reliquary_encryptor.pedersen_verify_proof(proof[:-1] + b"\xff", commitment,
+                                                         CONTEXT)
+
+
+def test_pedersen_key():
+    secret = bytes(range(32))
+    scoring = reliquary_encryptor.PedersenKey.from_bytes(secret)
+    audit = reliquary_encryptor.PedersenKey.from_bytes(secret)
+
+    # Services holding the same key agree on commitments without exchanging blindings
+    label = "trust/agent-7/2025-06"
+    commitment = scoring.commit(870, label)
+    assert audit.commit(870, label) == commitment
+    assert scoring.commit(870, "trust/agent-8/2025-06") != commitment
+    unrelated = reliquary_encryptor.PedersenKey.generate()
+    assert unrelated.commit(870, label) != commitment
+
+    proof = scoring.prove_opening(870, label, CONTEXT)
+    assert reliquary_encryptor.pedersen_verify_proof(proof, commitment, CONTEXT)
+    at_least = scoring.prove_range(870, label, lower=750, context=CONTEXT)
+    assert reliquary_encryptor.range_verify(at_least, commitment, lower=750, context=CONTEXT)
+    with pytest.raises(ValueError, match="outside the proven range"):
+        scoring.prove_range(870, label, upper=800)
+
+    # Opening discloses this commitment's blinding only
+    blinding = audit.open(label)
+    assert reliquary_encryptor.pedersen_verify_opening(commitment, 870, blinding)
+    assert reliquary_encryptor.pedersen_commit(870, blinding)[0] == commitment
+
+    with pytest.raises(ValueError, match="32 bytes"):
+        reliquary_encryptor.PedersenKey.from_bytes(b"short")
+    scoring.destroy()
+    assert scoring.destroyed and repr(scoring) == "PedersenKey(destroyed)"
+    with pytest.raises(ValueError, match="destroyed"):
+        scoring.commit(870, label)
@@ -0,0 +1,82 @@
+# tests/test_persistent_merkle_tree.py
+
+import pytest
+
+reliquary_merkle = pytest.importorskip("reliquary_merkle")
+
+BLOCKS = [f"entry {n}".encode() for n in range(37)]
+
+
+def test_reopen_keeps_tree(tmp_path):
+    store = tmp_path / "audit"
+    tree = reliquary_merkle.PersistentMerkleTree(store, 2, "blake3")
+    tree.extend(BLOCKS[:20])
+    assert tree.append(BLOCKS[20]) == 20
+    tree.sync()
+    del tree
+
+    tree = reliquary_merkle.PersistentMerkleTree(store)
+    assert (len(tree), tree.version, tree.hash_algorithm) == (21, 2, "blake3")
+    assert tree.root() == reliquary_merkle.create_merkle_root(BLOCKS[:21], 2, "blake3")
+    tree.extend(BLOCKS[21:])
+    del tree  # unsynced appends are still written out when the store is dropped
+    tree = reliquary_merkle.PersistentMerkleTree(store)
+    assert tree.root() == reliquary_merkle.create_merkle_root(BLOCKS, 2, "blake3")
+
+
+@pytest.mark.parametrize("scheme", [(1, "sha256"), (2, "sha3-256")])
+def test_proofs_match_in_memory_tree(tmp_path, scheme):
+    tree = reliquary_merkle.PersistentMerkleTree(tmp_path / "store", *scheme)
+    tree.extend(BLOCKS)
+    memory = reliquary_merkle.MerkleTree(BLOCKS, *scheme)
+    assert tree.root() == memory.root()
+    for i in range(len(BLOCKS)):
+        assert tree.leaf_hash(i) == memory.leaf_hash(i)
+        assert tree.get_positional_proof(i) == memory.get_positional_proof(i)
+        assert tree.prove_inclusion(i) == memory.prove_inclusion(i)
+    for old_size in range(1, len(BLOCKS) + 1):
+        assert tree.root_at(old_size) == memory.root_at(old_size)
+        assert tree.prove_consistency(old_size) == memory.prove_consistency(old_size)
+    assert tree.get_consistency_proof(9, 30) == memory.get_consistency_proof(9, 30)
+    assert tree.get_multi_proof([36, 0, 17]) == memory.get_multi_proof([36, 0, 17])
+    assert tree.tree_root(11) == memory.tree_root(11)
+    with pytest.raises(IndexError):
+        tree.get_proof(len(BLOCKS))
+
+
+def test_recovers_from_torn_writes(tmp_path):
+    store = tmp_path / "store"
+    tree = reliquary_merkle.PersistentMerkleTree(store)
+    tree.extend(BLOCKS)
+    tree.sync()
+    del tree
+    # A crash mid-append: a torn leaf hash, and a parent level that never got written
+    with open(store / "level-00", "ab") as f:
+        f.write(b"\x01" * 17)
+    (store / "level-02").write_bytes((store / "level-02").read_bytes()[:32 * 3 + 5])
+    tree = reliquary_merkle.PersistentMerkleTree(store)
+    assert len(tree) == len(BLOCKS)
+    assert tree.root() == reliquary_merkle.create_merkle_root(BLOCKS)
+    assert (store / "level-02").stat().st_size == 32 * (len(BLOCKS) // 4)
+    del tree
+    # Leaves lost while upper levels survived: the leaves win
+    (store / "level-00").write_bytes((store / "level-00").read_bytes()[:32 * 5])
+    tree = reliquary_merkle.PersistentMerkleTree(store)
+    assert tree.root() == reliquary_merkle.create_merkle_root(BLOCKS[:5])
+    assert not (store / "level-03").exists()
+    tree.extend(BLOCKS[5:])
+    assert tree.root() == reliquary_merkle.create_merkle_root(BLOCKS)
+
+
+def test_store_is_locked_and_scheme_checked(tmp_path):
+    store = tmp_path / "store"
+    tree = reliquary_merkle.PersistentMerkleTree(store, 2)
+    with pytest.raises(RuntimeError, match="in use"):
+        reliquary_merkle.PersistentMerkleTree(store)
+    del tree
+    with pytest.raises(ValueError, match="version 2 sha256 tree, not version 2 blake3"):
+        reliquary_merkle.PersistentMerkleTree(store, hash_algorithm="blake3")
+    assert reliquary_merkle.PersistentMerkleTree(store, 2).version == 2
+    (store / "meta").write_bytes(b"RQMS\x01\x02\x09")
+    with pytest.raises(ValueError, match="hash algorithm id 9"):
+        reliquary_merkle.PersistentMerkleTree(store)
@@ -0,0 +1,81 @@
+# tests/test_policy_manager.py
+
+import json
+
+import pytest
+
+reliquary_encryptor = pytest.importorskip("reliquary_encryptor")
+from core.rules.policy_manager import PolicyError, PolicyManager
+
+# --- Signed policy bundles: verified, compiled and swapped atomically with rollback ---
+
+def bundle(version, min_score):
+    return json.dumps({
+        "version": version,
+        "default_action": "deny",
+        "rules": [{
+            "id": "trusted",
+            "type": "access_control",
+            "priority": 10,
+            "conditions": [{"field": "trust.score", "operator": "greater_than", "value": min_score}],
+            "actions": [{"type": "allow"}]
+        }]
+    }).encode()
+
+@pytest.fixture(scope="module")
+def falcon_keys():
+    return reliquary_encryptor.generate_falcon_keys()
+
+def signed(data, sk):
+    return data, reliquary_encryptor.sign_falcon(data, sk)
+
+def test_swap_rollback_and_notify(falcon_keys):
+    pk, sk = falcon_keys
+    manager = PolicyManager(pk)
+    events = []
+    manager.subscribe(lambda old, new, reason: events.append((old, new, reason)))
+
+    manager.load(*signed(bundle(1, 50), sk))
+    assert manager.evaluate({"trust": {"score": 60}}).action == "allow"
+    manager.load(*signed(bundle(2, 70), sk))
+    decision = manager.evaluate({"trust": {"score": 60}})
+    assert (decision.action, decision.rule_id, decision.policy_version) == ("deny", None, 2)
+
+    # Replaying an older signed bundle is refused; rollback is explicit
+    with pytest.raises(PolicyError, match="not newer"):
+        manager.load(*signed(bundle(1, 50), sk))
+    assert manager.rollback().version == 1
+    assert manager.evaluate({"trust": {"score": 60}}).action == "allow"
+    assert events == [(None, 1, "load"), (1, 2, "load"), (2, 1, "rollback")]
+
+def test_rejected_bundles_leave_active_policy(falcon_keys):
+    pk, sk = falcon_keys
+    manager = PolicyManager(pk)
+    manager.load(*signed(bundle(1, 50), sk))
+
+    data, signature = signed(bundle(2, 0), sk)
+    with pytest.raises(PolicyError, match="signature"):
+        manager.load(data.replace(b'"value": 0', b'"value": 1'), signature)
+    broken = json.loads(bundle(3, 50))
+    broken["rules"][0]["conditions"][0]["operator"] = "matches"
+    with pytest.raises(PolicyError, match="failed validation"):
+        manager.load(*signed(json.dumps(broken).encode(), sk))
+    assert manager.active.version == 1
+
+def test_shadow_candidate_never_affects_outcome(falcon_keys):
+    pk, sk = falcon_keys
+    audit = []
+    manager = PolicyManager(pk, audit_logger=type("Sink", (), {"add_entry": lambda _, e: audit.append(e)})())
+    manager.load(*signed(bundle(1, 50), sk))
+    manager.set_shadow(*signed(bundle(2, 70), sk))
+
+    assert manager.evaluate({"trust": {"score": 60}}).action == "allow"
+    assert manager.evaluate({"trust": {"score": 90}}).action == "allow"
+    assert manager.shadow_stats == {"evaluations": 2, "divergences": 1, "errors": 0}
+    assert len(audit) == 1
+    assert (audit[0]["active_action"], audit[0]["candidate_action"]) == ("allow", "deny")
+    assert "score" not in json.dumps(audit[0])
+
+    assert manager.promote_shadow().version == 2
+    assert manager.shadow is None
+    assert manager.evaluate({"trust": {"score": 60}}).action == "deny"
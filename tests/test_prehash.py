@@ -0,0 +1,81 @@
+# tests/test_prehash.py
+
+import hashlib
+
+import pytest
+
+reliquary_encryptor = pytest.importorskip("reliquary_encryptor")
+
+# --- Prehashed Falcon signatures: incremental digests, hash binding and context separation ---
+
+CONTEXT = b"release-artifacts"
+DATA = bytes(range(256)) * 4096
+
+
+@pytest.fixture(scope="module")
+def keys():
+    return reliquary_encryptor.generate_falcon_keys()
+
+
+def test_signing_digest_matches_hashlib(tmp_path):
+    digest = reliquary_encryptor.SigningDigest()
+    assert digest.algorithm == "sha3-512"
+    for start in range(0, len(DATA), 100_000):
+        digest.update(DATA[start:start + 100_000])
+    assert digest.bytes_hashed == len(DATA)
+    assert digest.digest() == hashlib.sha3_512(DATA).digest()
+
+    # digest() doesn't finalize; copy() forks the state
+    fork = digest.copy()
+    digest.update(b"more")
+    assert fork.digest() == hashlib.sha3_512(DATA).digest()
+    assert digest.digest() == hashlib.sha3_512(DATA + b"more").digest()
+
+    path = tmp_path / "artifact.bin"
+    path.write_bytes(DATA * 2)
+    from_file = reliquary_encryptor.SigningDigest("SHA-512")
+    assert from_file.algorithm == "sha512"
+    assert from_file.update_file(path) == 2 * len(DATA)
+    assert from_file.digest() == hashlib.sha512(DATA * 2).digest()
+    with pytest.raises(OSError):
+        from_file.update_file(tmp_path / "missing.bin")
+    with pytest.raises(ValueError, match="Unsupported prehash algorithm 'md5'"):
+        reliquary_encryptor.SigningDigest("md5")
+
+
+@pytest.mark.parametrize("algorithm,hasher", [("sha3-512", hashlib.sha3_512),
+                                              ("sha512", hashlib.sha512)])
+def test_sign_and_verify(keys, algorithm, hasher):
+    pk, sk = keys
+    digest = hasher(DATA).digest()
+    signature = reliquary_encryptor.sign_falcon_prehashed(digest, CONTEXT, sk, algorithm)
+    assert reliquary_encryptor.verify_falcon_prehashed(digest, CONTEXT, signature, pk, algorithm)
+    assert not reliquary_encryptor.verify_falcon_prehashed(
+        hasher(DATA + b"x").digest(), CONTEXT, signature, pk, algorithm)
+    assert not reliquary_encryptor.verify_falcon_prehashed(
+        digest, b"other-context", signature, pk, algorithm)
+    other_pk, _ = reliquary_encryptor.generate_falcon_keys()
+    assert not reliquary_encryptor.verify_falcon_prehashed(
+        digest, CONTEXT, signature, other_pk, algorithm)
+
+
+def test_hash_identifier_is_signed(keys):
+    pk, sk = keys
+    # The same 64 bytes presented as a digest under the other hash don't verify
+    digest = hashlib.sha3_512(DATA).digest()
+    signature = reliquary_encryptor.sign_falcon_prehashed(digest, CONTEXT, sk, "sha3-512")
+    assert not reliquary_encryptor.verify_falcon_prehashed(digest, CONTEXT, signature, pk,
+                                                           "sha512")
+    # An empty context is a context of its own
+    bare = reliquary_encryptor.sign_falcon_prehashed(digest, b"", sk)
+    assert reliquary_encryptor.verify_falcon_prehashed(digest, b"", bare, pk)
+    assert not reliquary_encryptor.verify_falcon_prehashed(digest, CONTEXT, bare, pk)
+
+
+def test_rejections(keys):
+    _, sk = keys
+    with pytest.raises(ValueError, match="Invalid sha3-512 digest length. Expected 64, got 32"):
+        reliquary_encryptor.sign_falcon_prehashed(hashlib.sha256(DATA).digest(), CONTEXT, sk)
+    with pytest.raises(ValueError, match="at most 255 bytes"):
+        reliquary_encryptor.sign_falcon_prehashed(bytes(64), b"c" * 256, sk)
+    reliquary_encryptor.sign_falcon_prehashed(bytes(64), b"c" * 255, sk)
@@ -0,0 +1,146 @@
+# tests/test_pure_verify.py
+
+import importlib
+import json
+import os
+from pathlib import Path
+from types import SimpleNamespace
+
+import pytest
+
+from core.crypto import pure_verify
+
+VECTORS_PATH = Path(__file__).parent / "vectors" / "verify_conformance.json"
+
+# --- Conformance of the native and pure-Python verify-only implementations ---
+# Every vector runs against the pure-Python fallback, and against the native modules when they
+# are installed, so the two can't drift apart.
+
+
+def _native():
+    encryptor = pytest.importorskip("reliquary_encryptor")
+    merkle = pytest.importorskip("reliquary_merkle")
+    return SimpleNamespace(verify_ed25519=encryptor.verify_ed25519,
+                           envelope_recipients=encryptor.envelope_recipients,
+                           envelope_threshold=encryptor.envelope_threshold,
+                           kms_envelope_info=encryptor.kms_envelope_info,
+                           create_merkle_root=merkle.create_merkle_root,
+                           verify_merkle_proof=merkle.verify_merkle_proof,
+                           verify_positional_proof=merkle.verify_positional_proof,
+                           verify_consistency_proof=merkle.verify_consistency_proof,
+                           verify_multi_proof=merkle.verify_multi_proof,
+                           verify_sparse_proof=merkle.verify_sparse_proof,
+                           verify_map_proof=merkle.verify_map_proof,
+                           merkle_root_version=merkle.merkle_root_version,
+                           merkle_root_hash_algorithm=merkle.merkle_root_hash_algorithm,
+                           proof_size=merkle.proof_size)
+
+
+@pytest.fixture(scope="module")
+def vectors():
+    with open(VECTORS_PATH) as f:
+        return json.load(f)
+
+
+BACKENDS = pytest.mark.parametrize("backend", ["pure-python", "native"])
+
+
+def _impl(backend):
+    return pure_verify if backend == "pure-python" else _native()
+
+
+def _check(expected, call):
+    if "error" in expected:
+        with pytest.raises(ValueError) as info:
+            call()
+        assert str(info.value) == expected["error"]
+    else:
+        assert call() == expected["result"]
+
+
+@BACKENDS
+def test_ed25519_vectors(backend, vectors):
+    impl = _impl(backend)
+    for v in vectors["ed25519"]:
+        _check(v, lambda: impl.verify_ed25519(bytes.fromhex(v["msg"]), bytes.fromhex(v["signature"]),
+                                              bytes.fromhex(v["public_key"])))
+
+
+@BACKENDS
+def test_merkle_vectors(backend, vectors):
+    impl = _impl(backend)
+    merkle = vectors["merkle"]
+    for v in merkle["roots"]:
+        hash_algorithm = v.get("hash_algorithm", "sha256")
+        root = bytes(impl.create_merkle_root([bytes.fromhex(b) for b in v["blocks"]], v.get("version", 1),
+                                             hash_algorithm))
+        assert root.hex() == v["root"]
+        if root:
+            assert impl.merkle_root_version(root) == v.get("version", 1)
+            assert impl.merkle_root_hash_algorithm(root) == hash_algorithm
+    for v in merkle["proofs"]:
+        assert impl.verify_merkle_proof(bytes.fromhex(v["block"]), [bytes.fromhex(p) for p in v["proof"]],
+                                        bytes.fromhex(v["root"])) == v["result"]
+    for v in merkle["positional_proofs"]:
+        proof = [(bytes.fromhex(sibling), is_right) for sibling, is_right in v["proof"]]
+        assert impl.verify_positional_proof(bytes.fromhex(v["block"]), proof, bytes.fromhex(v["root"]),
+                                            v["index"], v["tree_size"]) == v["result"]
+    for v in merkle["consistency_proofs"]:
+        assert impl.verify_consistency_proof(v["old_size"], v["new_size"], bytes.fromhex(v["old_root"]),
+                                             bytes.fromhex(v["new_root"]),
+                                             [bytes.fromhex(p) for p in v["proof"]]) == v["result"]
+    for v in merkle["multi_proofs"]:
+        leaves = [(index, bytes.fromhex(block)) for index, block in v["leaves"]]
+        proof = (v["tree_size"], [bytes.fromhex(p) for p in v["proof"]])
+        assert impl.verify_multi_proof(leaves, proof, bytes.fromhex(v["root"])) == v["result"]
+    for v in merkle["sparse_proofs"]:
+        leaf = v["leaf"] and (bytes.fromhex(v["leaf"][0]), bytes.fromhex(v["leaf"][1]))
+        proof = ([bytes.fromhex(s) for s in v["siblings"]], leaf)
+        value = None if v["value"] is None else bytes.fromhex(v["value"])
+        assert impl.verify_sparse_proof(bytes.fromhex(v["key"]), value, proof,
+                                        bytes.fromhex(v["root"])) == v["result"]
+    for v in merkle["map_proofs"]:
+        leaf = v["leaf"] and (bytes.fromhex(v["leaf"][0]), bytes.fromhex(v["leaf"][1]))
+        proof = ([bytes.fromhex(s) for s in v["siblings"]], leaf)
+        value = None if v["value"] is None else bytes.fromhex(v["value"])
+        assert impl.verify_map_proof(bytes.fromhex(v["key"]), value, proof,
+                                     bytes.fromhex(v["root"])) == v["result"]
+    for size, expected in merkle["proof_sizes"].items():
+        assert impl.proof_size(int(size)) == expected
+
+
+@BACKENDS
+def test_envelope_vectors(backend, vectors):
+    impl = _impl(backend)
+    for v in vectors["envelopes"]:
+        envelope = bytes.fromhex(v["envelope"])
+        _check(v["recipients"], lambda: [fp.hex() for fp in impl.envelope_recipients(envelope)])
+        _check(v["threshold"], lambda: impl.envelope_threshold(envelope))
+        _check(v["kms_info"], lambda: [t.hex() if isinstance(t, bytes) else t
+                                       for t in impl.kms_envelope_info(envelope)])
+
+
+def test_envelope_info(vectors):
+    by_name = {v["name"]: bytes.fromhex(v["envelope"]) for v in vectors["envelopes"]}
+    assert pure_verify.envelope_info(by_name["v1 kyber512"]) == {
+        "version": 1, "kem": "kyber512", "ciphertext_bytes": len(b"payload") + 16}
+    multi = pure_verify.envelope_info(by_name["v2 kyber512 2-of-2"])
+    assert multi["threshold"] == 2
+    assert [slot["share_index"] for slot in multi["recipients"]] == [1, 2]
+    assert {slot["kem"] for slot in multi["recipients"]} == {"kyber512"}
+    assert pure_verify.envelope_info(by_name["v3 kms"])["kms_key_id"] == "arn:key/1"
+    with pytest.raises(ValueError, match="Unsupported envelope version 7"):
+        pure_verify.envelope_info(b"RQEV\x07\x03")
+
+
+def test_verifier_falls_back_without_native_modules():
+    import core.crypto.verifier as verifier
+    os.environ["RELIQUARY_PURE_VERIFY"] = "1"
+    try:
+        forced = importlib.reload(verifier)
+        assert forced.verifier_backends() == {"encryptor": "pure-python", "merkle": "pure-python"}
+        assert forced.verify_ed25519 is pure_verify.verify_ed25519
+        assert forced.merkle_proof_size is pure_verify.proof_size
+    finally:
+        del os.environ["RELIQUARY_PURE_VERIFY"]
+        importlib.reload(verifier)
@@ -0,0 +1,64 @@
+# tests/test_pyca_adapters.py
+
+import pytest
+
+reliquary_encryptor = pytest.importorskip("reliquary_encryptor")
+from cryptography.hazmat.primitives import hashes
+from cryptography.hazmat.primitives.asymmetric import ed25519, padding, rsa, x25519
+
+from core.crypto.pyca_adapters import from_pyca_key, to_pyca_private_key, to_pyca_public_key
+
+# --- cryptography key objects in and out of the encryptor ---
+
+
+def test_ed25519_objects_sign_and_verify():
+    key = ed25519.Ed25519PrivateKey.generate()
+    signature = reliquary_encryptor.sign_ed25519(b"msg", key)
+    key.public_key().verify(signature, b"msg")
+    assert reliquary_encryptor.verify_ed25519(b"msg", signature, key.public_key())
+    assert reliquary_encryptor.ed25519_public_key(key) == key.public_key().public_bytes_raw()
+
+    pk, sk = reliquary_encryptor.generate_ed25519_keys()
+    ours = to_pyca_private_key(sk, "ed25519")
+    assert ours.public_key().public_bytes_raw() == pk
+    assert from_pyca_key(ours) == ("ed25519", sk)
+    to_pyca_public_key(pk, "ed25519").verify(reliquary_encryptor.sign_ed25519(b"m", sk), b"m")
+
+
+def test_x25519_objects_agree():
+    theirs = x25519.X25519PrivateKey.generate()
+    pk, sk = reliquary_encryptor.generate_x25519_keys()
+    shared = reliquary_encryptor.x25519_shared_secret(theirs, pk)
+    assert shared == theirs.exchange(to_pyca_public_key(pk, "x25519"))
+    assert shared == reliquary_encryptor.x25519_shared_secret(sk, theirs.public_key())
+    assert from_pyca_key(theirs.public_key()) == ("x25519", theirs.public_key().public_bytes_raw())
+
+
+def test_rsa_objects_in_blind_signatures():
+    key = rsa.generate_private_key(public_exponent=65537, key_size=2048)
+    blinded, inverse, prepared = reliquary_encryptor.blind_message(key.public_key(), b"token")
+    blind_signature = reliquary_encryptor.blind_sign(key, blinded)
+    signature = reliquary_encryptor.finalize_blind_signature(key.public_key(), prepared,
+                                                             blind_signature, inverse)
+    key.public_key().verify(signature, prepared,
+                            padding.PSS(padding.MGF1(hashes.SHA384()), 48), hashes.SHA384())
+
+    pk, sk = reliquary_encryptor.generate_blind_signing_keys()
+    assert from_pyca_key(to_pyca_private_key(sk, "rsa"))[1] == sk
+    assert from_pyca_key(to_pyca_public_key(pk, "rsa")) == ("rsa", pk)
+
+
+def test_key_arguments_check_the_algorithm():
+    key = ed25519.Ed25519PrivateKey.generate()
+    bundle = reliquary_encryptor.create_signature_bundle(
+        b"record", key, key.public_key().public_bytes_raw(), "ed25519")
+    assert reliquary_encryptor.verify_signature_bundle(bundle, b"record")[0][5]
+
+    with pytest.raises(TypeError, match="X25519PrivateKey"):
+        reliquary_encryptor.x25519_public_key(key)
+    with pytest.raises(TypeError, match="Ed25519PublicKey"):
+        reliquary_encryptor.verify_ed25519(b"m", bytes(64), key)
+    with pytest.raises(TypeError):
+        from_pyca_key(object())
+    with pytest.raises(ValueError, match="Unsupported algorithm"):
+        to_pyca_private_key(bytes(32), "ed448")
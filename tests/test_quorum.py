@@ -0,0 +1,106 @@
+# tests/test_quorum.py
+
+import pytest
+
+reliquary_encryptor = pytest.importorskip("reliquary_encryptor")
+
+# --- M-of-N quorum certificates: aggregation, binding to policy and digest, strict parsing ---
+
+DIGEST = bytes(range(32))
+# magic | version | policy id | digest length | digest | bitmap length
+BITMAP_OFFSET = 4 + 1 + 32 + 1 + len(DIGEST) + 1
+
+
+@pytest.fixture(scope="module")
+def trustees():
+    """(name, scheme, public_key, secret_key) for three trustees on different schemes."""
+    ed_a = reliquary_encryptor.generate_ed25519_keys()
+    ed_b = reliquary_encryptor.generate_ed25519_keys()
+    falcon = reliquary_encryptor.generate_falcon_keys(reliquary_encryptor.FalconParams.Falcon512)
+    return [("alice", "ed25519", *ed_a), ("bob", "ed25519", *ed_b),
+            ("carol", "falcon512", *falcon)]
+
+
+def make_policy(trustees, threshold=2, context=b""):
+    return reliquary_encryptor.QuorumPolicy(
+        [(name, scheme, pk) for name, scheme, pk, _ in trustees], threshold, context)
+
+
+def votes(policy, trustees, names, digest=DIGEST):
+    by_name = {name: sk for name, _, _, sk in trustees}
+    return [(name, reliquary_encryptor.sign_quorum_vote(policy, name, digest, by_name[name]))
+            for name in names]
+
+
+def test_certificate_round_trip(trustees):
+    policy = make_policy(trustees)
+    certificate = reliquary_encryptor.aggregate_quorum_certificate(
+        policy, DIGEST, votes(policy, trustees, ["carol", "alice"]))
+    # Signers come back in trustee order, whatever order the votes arrived in
+    assert reliquary_encryptor.verify_quorum_certificate(policy, certificate, DIGEST) == (
+        True, ["alice", "carol"])
+    everyone = reliquary_encryptor.aggregate_quorum_certificate(
+        policy, DIGEST, votes(policy, trustees, ["alice", "bob", "carol"]))
+    assert reliquary_encryptor.verify_quorum_certificate(policy, everyone, DIGEST)[0]
+
+
+def test_aggregation_rejections(trustees):
+    policy = make_policy(trustees)
+    alice, bob = votes(policy, trustees, ["alice", "bob"])
+    with pytest.raises(ValueError, match="Quorum not reached: 1 of 2"):
+        reliquary_encryptor.aggregate_quorum_certificate(policy, DIGEST, [alice])
+    with pytest.raises(ValueError, match="Duplicate vote from trustee 'alice'"):
+        reliquary_encryptor.aggregate_quorum_certificate(policy, DIGEST, [alice, bob, alice])
+    with pytest.raises(ValueError, match="Unknown trustee 'mallory'"):
+        reliquary_encryptor.aggregate_quorum_certificate(policy, DIGEST,
+                                                         [alice, ("mallory", bob[1])])
+    # bob's signature presented as alice's
+    with pytest.raises(ValueError, match="Invalid vote signature from trustee 'alice'"):
+        reliquary_encryptor.aggregate_quorum_certificate(policy, DIGEST,
+                                                         [("alice", bob[1]), bob])
+    # A vote on another digest doesn't count towards this one
+    other_vote = votes(policy, trustees, ["bob"], digest=b"\x01" * 32)[0]
+    with pytest.raises(ValueError, match="Invalid vote signature from trustee 'bob'"):
+        reliquary_encryptor.aggregate_quorum_certificate(policy, DIGEST, [alice, other_vote])
+
+
+def test_certificate_is_bound_to_policy_and_digest(trustees):
+    policy = make_policy(trustees)
+    certificate = reliquary_encryptor.aggregate_quorum_certificate(
+        policy, DIGEST, votes(policy, trustees, ["alice", "bob"]))
+    assert not reliquary_encryptor.verify_quorum_certificate(policy, certificate,
+                                                             b"\x01" * 32)[0]
+    # Same trustees under a higher threshold or another context is a different policy
+    for other in (make_policy(trustees, threshold=3), make_policy(trustees, context=b"other")):
+        assert reliquary_encryptor.verify_quorum_certificate(other, certificate, DIGEST) == (
+            False, [])
+    # Votes made under one policy don't aggregate under another
+    with pytest.raises(ValueError, match="Invalid vote signature"):
+        reliquary_encryptor.aggregate_quorum_certificate(
+            make_policy(trustees, context=b"other"), DIGEST, votes(policy, trustees,
+                                                                   ["alice", "bob"]))
+
+
+def test_tampered_certificates(trustees):
+    policy = make_policy(trustees)
+    certificate = reliquary_encryptor.aggregate_quorum_certificate(
+        policy, DIGEST, votes(policy, trustees, ["alice", "bob"]))
+    assert certificate[BITMAP_OFFSET] == 0b011
+
+    # Bits 3-7 of the bitmap are padding beyond the three trustees
+    padded = bytearray(certificate)
+    padded[BITMAP_OFFSET] |= 0b1000_0000
+    assert reliquary_encryptor.verify_quorum_certificate(policy, bytes(padded), DIGEST) == (
+        False, ["alice", "bob"])
+
+    # Claiming carol signed shifts the signatures onto the wrong trustees
+    claimed = bytearray(certificate)
+    claimed[BITMAP_OFFSET] = 0b101
+    assert not reliquary_encryptor.verify_quorum_certificate(policy, bytes(claimed), DIGEST)[0]
+
+    with pytest.raises(ValueError, match="Trailing bytes after quorum certificate"):
+        reliquary_encryptor.verify_quorum_certificate(policy, certificate + b"\x00", DIGEST)
+    with pytest.raises(ValueError):
+        reliquary_encryptor.verify_quorum_certificate(policy, certificate[:-1], DIGEST)
+    with pytest.raises(ValueError, match="Not a quorum certificate"):
+        reliquary_encryptor.verify_quorum_certificate(policy, b"XXXX" + certificate[4:], DIGEST)
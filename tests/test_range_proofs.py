@@ -0,0 +1,80 @@
+# tests/test_range_proofs.py
+
+import pytest
+
+reliquary_encryptor = pytest.importorskip("reliquary_encryptor")
+
+# --- Bulletproofs range proofs: trust score thresholds and amount limits ---
+
+CONTEXT = b"vault-42/access-request-9"
+
+
+def test_score_at_least_threshold():
+    score = 870  # trust score in thousandths
+    commitment, blinding = reliquary_encryptor.range_commit(score)
+    assert len(commitment) == 32 and len(blinding) == 32
+    assert reliquary_encryptor.range_commit(score, blinding) == (commitment, blinding)
+
+    proof = reliquary_encryptor.range_prove(score, blinding, lower=750, context=CONTEXT)
+    assert proof[:4] == b"RQRP"
+    assert reliquary_encryptor.range_verify(proof, commitment, lower=750, context=CONTEXT)
+    # Bound to the threshold, the commitment and the context
+    assert not reliquary_encryptor.range_verify(proof, commitment, lower=751, context=CONTEXT)
+    assert not reliquary_encryptor.range_verify(proof, commitment, upper=750, context=CONTEXT)
+    assert not reliquary_encryptor.range_verify(proof, commitment, lower=750)
+    other, _ = reliquary_encryptor.range_commit(score)
+    assert not reliquary_encryptor.range_verify(proof, other, lower=750, context=CONTEXT)
+
+    # The threshold itself passes; one below it can't be proven
+    edge_commitment, _ = reliquary_encryptor.range_commit(750, blinding)
+    edge = reliquary_encryptor.range_prove(750, blinding, lower=750, bits=8)
+    assert reliquary_encryptor.range_verify(edge, edge_commitment, lower=750)
+    with pytest.raises(ValueError, match="outside the proven range"):
+        reliquary_encryptor.range_prove(749, blinding, lower=750)
+    # A gap wider than the proof's bits can't be proven either
+    with pytest.raises(ValueError, match="outside the proven range"):
+        reliquary_encryptor.range_prove(1000, blinding, lower=0, bits=8)
+
+
+def test_amount_within_limits():
+    amount = 12_500
+    commitment, blinding = reliquary_encryptor.range_commit(amount)
+    at_most = reliquary_encryptor.range_prove(amount, blinding, upper=20_000)
+    assert reliquary_encryptor.range_verify(at_most, commitment, upper=20_000)
+    assert not reliquary_encryptor.range_verify(at_most, commitment, upper=12_499)
+    with pytest.raises(ValueError, match="outside the proven range"):
+        reliquary_encryptor.range_prove(amount, blinding, upper=12_499)
+
+    # Both bounds in one aggregated proof, with 64-bit gaps
+    both = reliquary_encryptor.range_prove(amount, blinding, lower=10_000, upper=2**64 - 1,
+                                           bits=64)
+    assert reliquary_encryptor.range_verify(both, commitment, lower=10_000, upper=2**64 - 1)
+    assert not reliquary_encryptor.range_verify(both, commitment, lower=10_000)
+
+    with pytest.raises(ValueError, match="lower or an upper bound"):
+        reliquary_encryptor.range_prove(amount, blinding)
+    with pytest.raises(ValueError, match="above the upper bound"):
+        reliquary_encryptor.range_prove(amount, blinding, lower=2, upper=1)
+    with pytest.raises(ValueError, match="bits must be"):
+        reliquary_encryptor.range_prove(amount, blinding, upper=20_000, bits=12)
+    with pytest.raises(ValueError, match="canonical scalar"):
+        reliquary_encryptor.range_commit(1, b"\xff" * 32)
+    with pytest.raises(ValueError, match="Ristretto point"):
+        reliquary_encryptor.range_verify(at_most, b"\xff" * 32, upper=20_000)
+
+
+def test_batch_verification():
+    items = []
+    for score in [910, 780, 800, 640, 990]:
+        commitment, blinding = reliquary_encryptor.range_commit(score)
+        threshold = 750 if score >= 750 else 600
+        proof = reliquary_encryptor.range_prove(score, blinding, lower=threshold, context=CONTEXT)
+        items.append((proof, commitment, threshold, None))
+    assert reliquary_encryptor.range_verify_batch(items, CONTEXT) == [True] * 5
+
+    # Claiming the higher threshold for the agent that proved 600, and a garbled commitment
+    items[3] = (items[3][0], items[3][1], 750, None)
+    items[4] = (items[4][0], b"\x00" * 31, 750, None)
+    assert reliquary_encryptor.range_verify_batch(items, CONTEXT) == [True, True, True, False,
+                                                                       False]
+    assert reliquary_encryptor.range_verify_batch([]) == []
@@ -0,0 +1,130 @@
+# tests/test_ratchet.py
+
+import pytest
+
+reliquary_encryptor = pytest.importorskip("reliquary_encryptor")
+
+# --- Kyber double ratchet: ordering, replays, forgeries, skip limits and persistence ---
+
+PARAMS = reliquary_encryptor.KyberParams.Kyber512
+MAX_SKIP = 1000
+
+
+def handshake():
+    """Returns (initiator, responder, responder ratchet key pair)."""
+    pk, sk = reliquary_encryptor.generate_kyber_keys(PARAMS)
+    ss, ct = reliquary_encryptor.encapsulate_kyber(pk, PARAMS)
+    initiator = reliquary_encryptor.RatchetSession.initiate(ss, pk, PARAMS)
+    responder = reliquary_encryptor.RatchetSession.respond(
+        reliquary_encryptor.decapsulate_kyber(ct, sk, PARAMS), pk, sk, PARAMS)
+    return initiator, responder, (pk, sk)
+
+
+def test_in_order_conversation():
+    alice, bob, _ = handshake()
+    with pytest.raises(ValueError, match="can't send before"):
+        bob.encrypt(b"too early")
+    for turn in range(3):
+        for n in range(3):
+            text = f"alice {turn}.{n}".encode()
+            assert bob.decrypt(alice.encrypt(text, b"ad"), b"ad") == text
+        reply = f"bob {turn}".encode()
+        assert alice.decrypt(bob.encrypt(reply)) == reply
+    # Every reply starts a new chain under a fresh ratchet key
+    before = alice.ratchet_public_key
+    bob.decrypt(alice.encrypt(b"next"))
+    assert alice.ratchet_public_key != before
+    # The associated data is authenticated
+    with pytest.raises(ValueError, match="authentication failed"):
+        bob.decrypt(alice.encrypt(b"bound"), b"other ad")
+
+
+def test_out_of_order_delivery():
+    alice, bob, _ = handshake()
+    first_chain = [alice.encrypt(f"m{n}".encode()) for n in range(4)]
+    assert bob.decrypt(first_chain[2]) == b"m2"
+    assert bob.skipped_keys == 2
+    bob_reply = bob.encrypt(b"ack")
+    assert alice.decrypt(bob_reply) == b"ack"
+    second_chain = [alice.encrypt(f"n{n}".encode()) for n in range(2)]
+
+    # Late messages from the old chain still open after the ratchet has moved on
+    assert bob.decrypt(second_chain[1]) == b"n1"
+    assert bob.decrypt(first_chain[3]) == b"m3"
+    assert bob.decrypt(first_chain[0]) == b"m0"
+    assert bob.decrypt(second_chain[0]) == b"n0"
+    assert bob.decrypt(first_chain[1]) == b"m1"
+    assert bob.skipped_keys == 0
+
+
+def test_replay_is_rejected():
+    alice, bob, _ = handshake()
+    messages = [alice.encrypt(f"m{n}".encode()) for n in range(3)]
+    assert bob.decrypt(messages[0]) == b"m0"
+    with pytest.raises(ValueError, match="replay"):
+        bob.decrypt(messages[0])
+    # A skipped key is used once too
+    assert bob.decrypt(messages[2]) == b"m2"
+    assert bob.decrypt(messages[1]) == b"m1"
+    for message in messages[1:]:
+        with pytest.raises(ValueError, match="replay"):
+            bob.decrypt(message)
+
+
+def test_forged_message_leaves_session_usable():
+    alice, bob, (pk, _) = handshake()
+    message = alice.encrypt(b"genuine")
+
+    # Tampered ciphertext
+    tampered = message[:-1] + bytes([message[-1] ^ 1])
+    with pytest.raises(ValueError, match="authentication failed"):
+        bob.decrypt(tampered)
+    # A well-formed message from an impostor who knows bob's ratchet key but not the shared
+    # secret: it decapsulates and would advance the ratchet, then fails authentication
+    impostor = reliquary_encryptor.RatchetSession.initiate(b"\x42" * 32, pk, PARAMS)
+    with pytest.raises(ValueError, match="authentication failed"):
+        bob.decrypt(impostor.encrypt(b"forged"))
+    with pytest.raises(ValueError):
+        bob.decrypt(b"RQRM\x01 truncated")
+    assert bob.skipped_keys == 0
+
+    assert bob.decrypt(message) == b"genuine"
+    assert alice.decrypt(bob.encrypt(b"still talking")) == b"still talking"
+
+
+def test_skip_limit():
+    alice, bob, _ = handshake()
+    messages = [alice.encrypt(f"m{n}".encode()) for n in range(MAX_SKIP + 2)]
+    with pytest.raises(ValueError, match="skips too many messages"):
+        bob.decrypt(messages[MAX_SKIP + 1])
+    assert bob.skipped_keys == 0
+    # Skipping exactly MAX_SKIP is allowed, and the skipped messages stay readable
+    assert bob.decrypt(messages[MAX_SKIP]) == f"m{MAX_SKIP}".encode()
+    assert bob.skipped_keys == MAX_SKIP
+    assert bob.decrypt(messages[7]) == b"m7"
+    assert bob.decrypt(messages[MAX_SKIP + 1]) == f"m{MAX_SKIP + 1}".encode()
+
+
+def test_state_round_trip():
+    alice, bob, _ = handshake()
+    messages = [alice.encrypt(f"m{n}".encode()) for n in range(3)]
+    assert bob.decrypt(messages[2]) == b"m2"
+
+    saved = bob.to_bytes()
+    assert saved[:4] == b"RQRS"
+    restored = reliquary_encryptor.RatchetSession.from_bytes(saved)
+    assert restored.to_bytes() == saved
+    assert restored.skipped_keys == 2
+    assert restored.ratchet_public_key == bob.ratchet_public_key
+    assert restored.decrypt(messages[0]) == b"m0"
+    assert alice.decrypt(restored.encrypt(b"from the restored side")) == b"from the restored side"
+
+    saved_alice = reliquary_encryptor.RatchetSession.from_bytes(alice.to_bytes())
+    assert restored.decrypt(saved_alice.encrypt(b"again")) == b"again"
+
+    with pytest.raises(ValueError):
+        reliquary_encryptor.RatchetSession.from_bytes(saved[:-1])
+    with pytest.raises(ValueError):
+        reliquary_encryptor.RatchetSession.from_bytes(saved + b"\x00")
+    with pytest.raises(ValueError):
+        reliquary_encryptor.RatchetSession.from_bytes(b"XXXX" + saved[4:])
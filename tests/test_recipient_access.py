@@ -0,0 +1,75 @@
+# tests/test_recipient_access.py
+
+import pytest
+
+reliquary_encryptor = pytest.importorskip("reliquary_encryptor")
+
+# --- Multi-recipient envelopes: changing recipients and thresholds without touching the payload ---
+
+MESSAGE = b"shared vault record"
+# AES-GCM nonce and tag around the payload at the end of the envelope
+PAYLOAD_BYTES = len(MESSAGE) + 28
+
+
+@pytest.fixture(scope="module")
+def recipients():
+    return [reliquary_encryptor.generate_kyber_keys() for _ in range(4)]
+
+
+def fingerprints(keys):
+    return [reliquary_encryptor.key_fingerprint(pk) for pk, _ in keys]
+
+
+def test_add_and_remove_recipients(recipients):
+    pks = [pk for pk, _ in recipients]
+    envelope = reliquary_encryptor.encrypt_to_recipients(MESSAGE, pks[:2])
+    assert reliquary_encryptor.envelope_threshold(envelope) == 1
+
+    added = reliquary_encryptor.add_recipients(envelope, pks[2:3], [recipients[0][1]])
+    assert reliquary_encryptor.envelope_recipients(added) == fingerprints(recipients[:3])
+    assert reliquary_encryptor.decrypt_with_secret_key(added, recipients[2][1]) == MESSAGE
+    assert added[-PAYLOAD_BYTES:] == envelope[-PAYLOAD_BYTES:]
+    with pytest.raises(ValueError, match="Recipient is already in the envelope"):
+        reliquary_encryptor.add_recipients(added, pks[:1], [recipients[0][1]])
+
+    removed = reliquary_encryptor.remove_recipients(added, fingerprints(recipients[1:2]),
+                                                    [recipients[0][1]])
+    assert reliquary_encryptor.envelope_recipients(removed) == \
+        fingerprints(recipients[:1] + recipients[2:3])
+    with pytest.raises(ValueError, match="no recipient slot for this key"):
+        reliquary_encryptor.decrypt_with_secret_key(removed, recipients[1][1])
+    assert removed[-PAYLOAD_BYTES:] == envelope[-PAYLOAD_BYTES:]
+
+
+def test_rewrap_recipients(recipients):
+    pks = [pk for pk, _ in recipients]
+    envelope = reliquary_encryptor.encrypt_to_recipients(MESSAGE, pks[:2])
+    # Hand the envelope to a 2-of-3 group that only partly overlaps the old one
+    rewrapped = reliquary_encryptor.rewrap_recipients(envelope, pks[1:], 2, [recipients[0][1]])
+    assert reliquary_encryptor.envelope_threshold(rewrapped) == 2
+    assert reliquary_encryptor.envelope_recipients(rewrapped) == fingerprints(recipients[1:])
+    assert reliquary_encryptor.decrypt_with_secret_keys(
+        rewrapped, [recipients[1][1], recipients[3][1]]) == MESSAGE
+    with pytest.raises(ValueError, match="needs 2 recipient keys, got 1"):
+        reliquary_encryptor.decrypt_with_secret_keys(rewrapped, [recipients[2][1]])
+    with pytest.raises(ValueError, match="no recipient slot for this key"):
+        reliquary_encryptor.decrypt_with_secret_keys(rewrapped, [recipients[0][1]])
+
+    # Re-wrapping a threshold envelope needs its threshold of current keys
+    with pytest.raises(ValueError, match="needs 2 recipient keys, got 1"):
+        reliquary_encryptor.rewrap_recipients(rewrapped, pks[:1], 1, [recipients[1][1]])
+    back = reliquary_encryptor.rewrap_recipients(
+        rewrapped, pks[:1], 1, [recipients[1][1], recipients[2][1]])
+    assert reliquary_encryptor.decrypt_with_secret_key(back, recipients[0][1]) == MESSAGE
+    assert back[-PAYLOAD_BYTES:] == envelope[-PAYLOAD_BYTES:]
+    with pytest.raises(ValueError, match="Threshold must be between 1 and the number of"):
+        reliquary_encryptor.rewrap_recipients(envelope, pks[:2], 3, [recipients[0][1]])
+
+
+def test_header_is_authenticated(recipients):
+    envelope = reliquary_encryptor.encrypt_to_recipients(MESSAGE, [pk for pk, _ in recipients])
+    # Raising the threshold byte without re-computing the header MAC is caught
+    tampered = envelope[:5] + b"\x02" + envelope[6:]
+    with pytest.raises(ValueError, match="authentication failed"):
+        reliquary_encryptor.decrypt_with_secret_keys(
+            tampered, [recipients[0][1], recipients[1][1]])
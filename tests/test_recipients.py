@@ -0,0 +1,62 @@
+# tests/test_recipients.py
+
+import pytest
+
+reliquary_encryptor = pytest.importorskip("reliquary_encryptor")
+
+# --- Multi-recipient envelopes: any recipient opens, and pruning of stale recipient slots ---
+
+MESSAGE = b"shared vault record"
+
+
+@pytest.fixture(scope="module")
+def recipients():
+    return [reliquary_encryptor.generate_kyber_keys() for _ in range(4)]
+
+
+def fingerprints(keys):
+    return [reliquary_encryptor.key_fingerprint(pk) for pk, _ in keys]
+
+
+def test_any_recipient_opens(recipients):
+    envelope = reliquary_encryptor.encrypt_to_recipients(MESSAGE, [pk for pk, _ in recipients])
+    assert reliquary_encryptor.envelope_recipients(envelope) == fingerprints(recipients)
+    for _, sk in recipients:
+        assert reliquary_encryptor.decrypt_with_secret_key(envelope, sk) == MESSAGE
+    _, outsider_sk = reliquary_encryptor.generate_kyber_keys()
+    with pytest.raises(ValueError, match="no recipient slot for this key"):
+        reliquary_encryptor.decrypt_with_secret_key(envelope, outsider_sk)
+
+
+def test_prune_recipients(recipients):
+    envelope = reliquary_encryptor.encrypt_to_recipients(MESSAGE, [pk for pk, _ in recipients])
+    active = fingerprints(recipients[:2])
+    pruned, removed = reliquary_encryptor.prune_recipients(envelope, active, recipients[0][1])
+    assert removed == fingerprints(recipients[2:])
+    assert reliquary_encryptor.envelope_recipients(pruned) == active
+    assert reliquary_encryptor.decrypt_with_secret_key(pruned, recipients[1][1]) == MESSAGE
+    with pytest.raises(ValueError, match="no recipient slot for this key"):
+        reliquary_encryptor.decrypt_with_secret_key(pruned, recipients[3][1])
+    # The payload ciphertext is reused as-is
+    assert pruned[-len(MESSAGE) - 28:] == envelope[-len(MESSAGE) - 28:]
+
+    # Nothing to prune leaves the envelope byte-for-byte
+    assert reliquary_encryptor.prune_recipients(pruned, active, recipients[0][1]) == (pruned, [])
+    with pytest.raises(ValueError, match="would leave fewer than the threshold of 1"):
+        reliquary_encryptor.prune_recipients(envelope, [], recipients[0][1])
+    with pytest.raises(ValueError, match="no recipient slot for this key"):
+        reliquary_encryptor.prune_recipients(pruned, active[:1], recipients[3][1])
+
+
+def test_prune_recipients_bulk(recipients):
+    pks = [pk for pk, _ in recipients]
+    envelopes = [reliquary_encryptor.encrypt_to_recipients(MESSAGE, pks),
+                 reliquary_encryptor.encrypt_to_recipients(MESSAGE, pks[:3])]
+    active = fingerprints(recipients[:2])
+    report = reliquary_encryptor.prune_recipients_bulk(envelopes, active, recipients[0][1])
+    assert [removed for _, removed in report] == [fingerprints(recipients[2:]),
+                                                  fingerprints(recipients[2:3])]
+    with pytest.raises(ValueError, match="Envelope 1: "):
+        reliquary_encryptor.prune_recipients_bulk(
+            [envelopes[0], reliquary_encryptor.encrypt_to_recipients(MESSAGE, pks[2:])],
+            active, recipients[0][1])
@@ -0,0 +1,100 @@
+# tests/test_release_escrow.py
+
+import pytest
+
+reliquary_encryptor = pytest.importorskip("reliquary_encryptor")
+from core.heartbeat import HeartbeatMonitor, LivenessPolicy, TrustedClock, sign_heartbeat
+from core.release_escrow import (EscrowError, Guardian, ReleaseEscrow, ReleaseState,
+                                 sign_release_transition)
+
+# --- Guardian-approved release stages, owner cancellation and cooling-off by trusted time ---
+
+DAY = 86400.0
+
+class AuditSink:
+    def __init__(self):
+        self.entries = []
+
+    def add_entry(self, entry):
+        self.entries.append(entry)
+
+class FakeTime:
+    def __init__(self, t):
+        self.t = t
+
+    def __call__(self):
+        return self.t
+
+@pytest.fixture(scope="module")
+def keys():
+    return {name: reliquary_encryptor.generate_ed25519_keys() for name in ("owner", "g1", "g2")}
+
+def setup(keys, sink):
+    source = FakeTime(1_760_000_000.0)
+    monitor = HeartbeatMonitor(TrustedClock([source], min_sources=1))
+    owner_pk, owner_sk = keys["owner"]
+    monitor.register_owner("alice", owner_pk, algorithm="ed25519")
+    guardians = [Guardian(name, keys[name][0], "ed25519") for name in ("g1", "g2")]
+    escrow = ReleaseEscrow(monitor, guardians, required_approvals=2, cooling_off_days=7,
+                           audit_logger=sink)
+    return source, monitor, escrow
+
+def approve(keys, release_id, state):
+    return {name: sign_release_transition(release_id, "alice", state, keys[name][1], "ed25519")
+            for name in ("g1", "g2")}
+
+def test_release_completes_after_cooling_off(keys):
+    sink = AuditSink()
+    source, monitor, escrow = setup(keys, sink)
+    policy = LivenessPolicy("alice", silence_days=30)
+
+    with pytest.raises(EscrowError, match="condition not met"):
+        escrow.request_release("r1", "bob", policy, approve(keys, "r1", ReleaseState.REQUESTED))
+    source.t += 31 * DAY
+    escrow.request_release("r1", "bob", policy, approve(keys, "r1", ReleaseState.REQUESTED))
+
+    # One guardian is not enough, and signatures for another step don't count
+    with pytest.raises(EscrowError, match="needs 2"):
+        escrow.advance("r1", {"g1": approve(keys, "r1", ReleaseState.NOTIFIED)["g1"]})
+    with pytest.raises(EscrowError, match="needs 2"):
+        escrow.advance("r1", approve(keys, "r1", ReleaseState.RELEASED))
+    escrow.advance("r1", approve(keys, "r1", ReleaseState.NOTIFIED))
+    escrow.advance("r1", approve(keys, "r1", ReleaseState.COOLING_OFF))
+
+    source.t += 6 * DAY
+    with pytest.raises(EscrowError, match="Cooling-off"):
+        escrow.advance("r1", approve(keys, "r1", ReleaseState.RELEASED))
+    source.t += DAY
+    assert escrow.advance("r1", approve(keys, "r1", ReleaseState.RELEASED)).state == \
+        ReleaseState.RELEASED
+
+    # The audit log alone rebuilds the release
+    _, _, rebuilt = setup(keys, AuditSink())
+    assert rebuilt.replay(sink.entries) == 4
+    assert rebuilt.get("r1").state == ReleaseState.RELEASED
+
+def test_owner_cancels_and_heartbeat_stops_release(keys):
+    source, monitor, escrow = setup(keys, AuditSink())
+    _, owner_sk = keys["owner"]
+    policy = LivenessPolicy("alice", silence_days=30)
+    source.t += 31 * DAY
+    for release_id in ("r1", "r2"):
+        escrow.request_release(release_id, "bob", policy,
+                               approve(keys, release_id, ReleaseState.REQUESTED))
+
+    with pytest.raises(EscrowError, match="Owner signature"):
+        escrow.cancel("r1", sign_release_transition(
+            "r1", "alice", ReleaseState.CANCELLED, keys["g1"][1], "ed25519"))
+    cancel = sign_release_transition("r1", "alice", ReleaseState.CANCELLED, owner_sk, "ed25519")
+    assert escrow.cancel("r1", cancel).state == ReleaseState.CANCELLED
+    with pytest.raises(EscrowError, match="already cancelled"):
+        escrow.advance("r1", approve(keys, "r1", ReleaseState.NOTIFIED))
+
+    escrow.advance("r2", approve(keys, "r2", ReleaseState.NOTIFIED))
+    escrow.advance("r2", approve(keys, "r2", ReleaseState.COOLING_OFF))
+    source.t += DAY
+    monitor.record_heartbeat(sign_heartbeat("alice", 1, owner_sk, "ed25519", issued_at=source.t))
+    source.t += 7 * DAY
+    with pytest.raises(EscrowError, match="heartbeat"):
+        escrow.advance("r2", approve(keys, "r2", ReleaseState.RELEASED))
+    assert escrow.get("r2").state == ReleaseState.CANCELLED
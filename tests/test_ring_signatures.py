@@ -0,0 +1,103 @@
+# tests/test_ring_signatures.py
+
+import pytest
+
+reliquary_encryptor = pytest.importorskip("reliquary_encryptor")
+
+# --- Linkable ring signatures (bLSAG): anonymity set, linking tags and ring binding ---
+
+POLL = b"poll-2026-q4"
+
+
+@pytest.fixture(scope="module")
+def members():
+    return [reliquary_encryptor.generate_ring_keys() for _ in range(4)]
+
+
+def ring_of(members):
+    return [pk for pk, _ in members]
+
+
+def test_sign_and_verify(members):
+    ring = ring_of(members)
+    for _, sk in members:
+        signature = reliquary_encryptor.ring_sign(b"yes", ring, sk, POLL)
+        assert signature[:4] == b"RQRS" and len(signature) == 4 + 1 + 32 + 32 + 1 + 4 * 32
+        assert reliquary_encryptor.ring_verify(b"yes", signature, ring, POLL)
+        assert not reliquary_encryptor.ring_verify(b"no", signature, ring, POLL)
+        assert not reliquary_encryptor.ring_verify(b"yes", signature, ring, b"other poll")
+
+    # A ring of one is a plain signature
+    pk, sk = members[0]
+    solo = reliquary_encryptor.ring_sign(b"solo", [pk], sk)
+    assert reliquary_encryptor.ring_verify(b"solo", solo, [pk])
+
+
+def test_same_signer_and_context_link(members):
+    ring = ring_of(members)
+    _, sk = members[1]
+    first = reliquary_encryptor.ring_sign(b"yes", ring, sk, POLL)
+    second = reliquary_encryptor.ring_sign(b"no", ring, sk, POLL)
+    assert first != second
+    tag = reliquary_encryptor.ring_signature_tag(first)
+    assert len(tag) == 32
+    assert reliquary_encryptor.ring_signature_tag(second) == tag
+    # The tag doesn't depend on who else is in the ring
+    smaller = reliquary_encryptor.ring_sign(b"yes", ring[:2], sk, POLL)
+    assert reliquary_encryptor.ring_signature_tag(smaller) == tag
+
+    other_member = reliquary_encryptor.ring_sign(b"yes", ring, members[2][1], POLL)
+    assert reliquary_encryptor.ring_signature_tag(other_member) != tag
+
+
+def test_signatures_in_different_contexts_are_unlinkable(members):
+    ring = ring_of(members)
+    _, sk = members[1]
+    tags = {bytes(reliquary_encryptor.ring_signature_tag(
+        reliquary_encryptor.ring_sign(b"yes", ring, sk, context)))
+        for context in (POLL, b"poll-2027-q1", b"")}
+    assert len(tags) == 3
+
+
+def test_signer_must_be_a_member(members):
+    ring = ring_of(members)
+    _, outsider_sk = reliquary_encryptor.generate_ring_keys()
+    with pytest.raises(ValueError, match="not in the ring"):
+        reliquary_encryptor.ring_sign(b"yes", ring, outsider_sk, POLL)
+    with pytest.raises(ValueError, match="Duplicate public key"):
+        reliquary_encryptor.ring_sign(b"yes", ring + ring[:1], members[0][1], POLL)
+    with pytest.raises(ValueError, match="between 1 and 255"):
+        reliquary_encryptor.ring_verify(b"yes", b"RQRS", [], POLL)
+    with pytest.raises(ValueError, match="Invalid ring public key"):
+        reliquary_encryptor.ring_sign(b"yes", ring[:3] + [b"\xff" * 32], members[0][1], POLL)
+
+    # A signature from another ring doesn't verify against this one
+    other_ring = [reliquary_encryptor.generate_ring_keys()[0] for _ in range(3)] + ring[:1]
+    foreign = reliquary_encryptor.ring_sign(b"yes", other_ring, members[0][1], POLL)
+    assert not reliquary_encryptor.ring_verify(b"yes", foreign, ring, POLL)
+
+
+def test_ring_tampering(members):
+    ring = ring_of(members)
+    signature = reliquary_encryptor.ring_sign(b"yes", ring, members[0][1], POLL)
+    # The ring order is part of what's signed
+    assert not reliquary_encryptor.ring_verify(b"yes", signature, ring[::-1], POLL)
+    assert not reliquary_encryptor.ring_verify(b"yes", signature, ring[1:] + ring[:1], POLL)
+    # Swapping a member out, or dropping one, breaks the signature too
+    replaced = ring[:3] + [reliquary_encryptor.generate_ring_keys()[0]]
+    assert not reliquary_encryptor.ring_verify(b"yes", signature, replaced, POLL)
+    assert not reliquary_encryptor.ring_verify(b"yes", signature, ring[:3], POLL)
+
+    # Tag swapped for another member's
+    other = reliquary_encryptor.ring_sign(b"yes", ring, members[1][1], POLL)
+    swapped = signature[:5] + reliquary_encryptor.ring_signature_tag(other) + signature[37:]
+    assert not reliquary_encryptor.ring_verify(b"yes", swapped, ring, POLL)
+    # A flipped response bit
+    flipped = bytearray(signature)
+    flipped[-1] ^= 1
+    assert not reliquary_encryptor.ring_verify(b"yes", bytes(flipped), ring, POLL)
+
+    with pytest.raises(ValueError, match="Trailing bytes"):
+        reliquary_encryptor.ring_verify(b"yes", signature + b"\x00", ring, POLL)
+    with pytest.raises(ValueError, match="Not a ring signature"):
+        reliquary_encryptor.ring_signature_tag(b"XXXX" + signature[4:])
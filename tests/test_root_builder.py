@@ -0,0 +1,51 @@
+# tests/test_root_builder.py
+
+import struct
+
+import pytest
+
+reliquary_merkle = pytest.importorskip("reliquary_merkle")
+
+# --- Streaming root computation (RootBuilder) ---
+
+
+def test_matches_create_merkle_root_at_every_size():
+    for version, hash_algorithm in ((1, "sha256"), (2, "sha256"), (2, "blake3")):
+        builder = reliquary_merkle.RootBuilder(version, hash_algorithm)
+        assert builder.root() == b"" and len(builder) == 0
+        blocks = []
+        for n in range(70):
+            blocks.append(f"entry {n}".encode())
+            builder.update(blocks[-1])
+            assert builder.root() == reliquary_merkle.create_merkle_root(blocks, version, hash_algorithm)
+        assert builder.tree_root() == reliquary_merkle.MerkleTree(blocks, version, hash_algorithm).tree_root()
+
+
+def test_update_many_takes_generators():
+    builder = reliquary_merkle.RootBuilder(2)
+    builder.update_many(n.to_bytes(8, "big") for n in range(10_000))
+    expected = reliquary_merkle.create_merkle_root([n.to_bytes(8, "big") for n in range(10_000)], 2)
+    assert (len(builder), builder.root()) == (10_000, expected)
+    with pytest.raises(TypeError):
+        builder.update_many([b"ok", "not bytes"])
+
+
+def test_update_file_reads_length_prefixed_records(tmp_path):
+    records = [b"", b"a", bytes(70_000)] + [f"entry {n}".encode() for n in range(500)]
+    path = tmp_path / "records.bin"
+    path.write_bytes(b"".join(struct.pack(">I", len(r)) + r for r in records))
+    builder = reliquary_merkle.RootBuilder()
+    builder.update(b"first")
+    assert builder.update_file(str(path)) == len(records)
+    assert builder.root() == reliquary_merkle.create_merkle_root([b"first"] + records)
+
+
+def test_update_file_errors(tmp_path):
+    truncated = tmp_path / "truncated.bin"
+    truncated.write_bytes(struct.pack(">I", 5) + b"abc")
+    with pytest.raises(ValueError, match="Record 0 is truncated"):
+        reliquary_merkle.RootBuilder().update_file(str(truncated))
+    with pytest.raises(FileNotFoundError):
+        reliquary_merkle.RootBuilder().update_file(str(tmp_path / "missing.bin"))
+    with pytest.raises(ValueError, match="at least one leaf"):
+        reliquary_merkle.RootBuilder().tree_root()
@@ -0,0 +1,97 @@
+# tests/test_rust_concurrency.py
+
+import sys
+import pytest
+from concurrent.futures import ThreadPoolExecutor
+
+reliquary_encryptor = pytest.importorskip("reliquary_encryptor")
+reliquary_merkle = pytest.importorskip("reliquary_merkle")
+
+THREADS = 16
+ROUNDS = 64
+
+# --- Stress concurrent calls from many threads (GIL released or free-threaded build) ---
+
+def test_concurrent_kyber_roundtrips():
+    """Many threads encapsulate/decapsulate against raw keys and key handles at once."""
+    public_key, secret_key = reliquary_encryptor.generate_kyber_keys()
+    handle = reliquary_encryptor.SecretKeyHandle.generate()
+
+    def roundtrip(i):
+        if i % 2:
+            shared_secret, ciphertext = reliquary_encryptor.encapsulate_kyber(public_key)
+            recovered = reliquary_encryptor.decapsulate_kyber(ciphertext, secret_key)
+        else:
+            shared_secret, ciphertext = reliquary_encryptor.encapsulate_kyber(handle.public_key)
+            recovered = reliquary_encryptor.decapsulate_kyber(ciphertext, handle)
+        return bytes(shared_secret) == bytes(recovered)
+
+    with ThreadPoolExecutor(max_workers=THREADS) as pool:
+        assert all(pool.map(roundtrip, range(ROUNDS)))
+    assert handle.uses == ROUNDS // 2
+    handle.destroy()
+
+def test_concurrent_handle_lifecycle():
+    """Handles created and destroyed concurrently never collide or leak into each other."""
+    def lifecycle(i):
+        handle = reliquary_encryptor.SecretKeyHandle.generate("falcon512")
+        public_key = handle.public_key
+        message = f"decision-{i}".encode()
+        signature = reliquary_encryptor.sign_falcon(message, handle)
+        valid = reliquary_encryptor.verify_falcon(
+            message, signature, public_key, reliquary_encryptor.FalconParams.Falcon512
+        )
+        handle.destroy()
+        return bytes(public_key), valid and handle.destroyed
+
+    with ThreadPoolExecutor(max_workers=THREADS) as pool:
+        results = list(pool.map(lifecycle, range(ROUNDS)))
+
+    assert all(ok for _, ok in results)
+    assert len({public_key for public_key, _ in results}) == ROUNDS
+
+def test_kyber_batches_match_single_operations():
+    """Batched Kyber calls keep input order and count every decapsulation against a handle."""
+    keypairs = reliquary_encryptor.generate_kyber_keys_batch(ROUNDS)
+    assert len({bytes(pk) for pk, _ in keypairs}) == ROUNDS
+    public_key, secret_key = keypairs[0]
+
+    encapsulated = reliquary_encryptor.encapsulate_kyber_batch([public_key] * ROUNDS)
+    ciphertexts = [ciphertext for _, ciphertext in encapsulated]
+    recovered = reliquary_encryptor.decapsulate_kyber_batch(ciphertexts, secret_key)
+    assert recovered == [shared_secret for shared_secret, _ in encapsulated]
+    assert recovered[1] == reliquary_encryptor.decapsulate_kyber(ciphertexts[1], secret_key)
+    with pytest.raises(ValueError, match="Public key 1"):
+        reliquary_encryptor.encapsulate_kyber_batch([public_key, b"short"])
+
+    handle = reliquary_encryptor.SecretKeyHandle.generate(
+        "kyber1024", reliquary_encryptor.KeyUsagePolicy(max_uses=ROUNDS))
+    encapsulated = reliquary_encryptor.encapsulate_kyber_batch([handle.public_key] * ROUNDS)
+    with pytest.raises(PermissionError):
+        reliquary_encryptor.decapsulate_kyber_batch([ct for _, ct in encapsulated] * 2, handle)
+    assert len(reliquary_encryptor.decapsulate_kyber_batch(
+        [ct for _, ct in encapsulated][:ROUNDS - 1], handle)) == ROUNDS - 1
+
+def test_concurrent_merkle_roots_are_deterministic():
+    """Merkle roots computed on many threads agree with a single-threaded computation."""
+    blocks = [f"audit-entry-{i}".encode() for i in range(257)]
+    expected = bytes(reliquary_merkle.create_merkle_root(blocks))
+
+    with ThreadPoolExecutor(max_workers=THREADS) as pool:
+        roots = list(pool.map(lambda _: bytes(reliquary_merkle.create_merkle_root(blocks)), range(ROUNDS)))
+
+    assert all(root == expected for root in roots)
+
+# --- Subinterpreters (unsupported: provider labels and limits are process-wide) ---
+
+def test_subinterpreter_import_fails_cleanly():
+    """Importing into a subinterpreter fails instead of sharing process-wide state with it."""
+    subinterpreters = pytest.importorskip("_xxsubinterpreters")
+    interp = subinterpreters.create()
+    try:
+        with pytest.raises(subinterpreters.RunFailedError, match="subinterpreters"):
+            subinterpreters.run_string(
+                interp, f"import sys; sys.path[:] = {sys.path!r}; import reliquary_encryptor"
+            )
+    finally:
+        subinterpreters.destroy(interp)
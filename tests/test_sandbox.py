@@ -0,0 +1,61 @@
+# tests/test_sandbox.py
+
+import os
+import signal
+
+import pytest
+
+reliquary_encryptor = pytest.importorskip("reliquary_encryptor")
+from core.merkle_logging.merkle import create_merkle_root, generate_merkle_proof
+from core.sandbox import (InputLimitError, ParseLimits, SandboxError, SandboxedVerifier,
+                          disable_hardened_parsing, enable_hardened_parsing, parse_json)
+
+# --- Hardened parsing limits and the out-of-process verification worker ---
+
+def test_parse_json_limits():
+    assert parse_json(b'{"a": [1, {"b": "c"}]}') == {"a": [1, {"b": "c"}]}
+    limits = ParseLimits(max_depth=4, max_items=10, max_string_bytes=8, max_input_bytes=200)
+    # Brackets inside strings don't count towards depth
+    assert parse_json('["[[[[[[", 1]', limits) == ["[[[[[[", 1]
+    for bad in ["[" * 5 + "]" * 5, "[" + ",".join(["1"] * 10) + "]", '["123456789"]',
+                '{"a": 1, "a": 2}', "[NaN]", "[" + " " * 200 + "]"]:
+        with pytest.raises(InputLimitError):
+            parse_json(bad, limits)
+
+
+def test_hardened_mode_caps_rust_parsers():
+    pk, _ = reliquary_encryptor.generate_kyber_keys()
+    envelope = reliquary_encryptor.encrypt_to_public_key(b"data", pk)
+    enable_hardened_parsing(ParseLimits(max_input_bytes=len(envelope) - 1))
+    try:
+        assert reliquary_encryptor.hardened_parsing() == (True, len(envelope) - 1, 64)
+        assert [f[1] for f in reliquary_encryptor.validate_envelope(envelope)] == ["input_limit"]
+    finally:
+        disable_hardened_parsing()
+    assert reliquary_encryptor.validate_envelope(envelope) == []
+
+
+def test_sandboxed_verifier_round_trip_and_recovery():
+    pk, _ = reliquary_encryptor.generate_kyber_keys()
+    envelope = reliquary_encryptor.encrypt_to_public_key(b"data", pk)
+    blocks = [b"a", b"b", b"c", b"d"]
+    with SandboxedVerifier(timeout=10) as verifier:
+        assert verifier.parse_json('{"x": [1, 2]}') == {"x": [1, 2]}
+        assert verifier.validate_envelope(envelope) == []
+        assert verifier.validate_envelope(b"junk")[0][1] == "not_envelope"
+        assert verifier.verify_merkle_proof(
+            b"b", generate_merkle_proof(blocks, 1), create_merkle_root(blocks), 1)
+        with pytest.raises(ValueError, match="InputLimitError"):
+            verifier.parse_json("[" * 100 + "]" * 100)
+        with pytest.raises(ValueError, match="Unknown sandbox operation"):
+            verifier.call("exec", data=b"")
+
+        # A dead worker is replaced on the next call
+        os.kill(verifier._process.pid, signal.SIGKILL)
+        verifier._process.wait()
+        assert verifier.parse_json("[3]") == [3]
+
+    verifier = SandboxedVerifier(timeout=0.001)
+    with pytest.raises(SandboxError, match="timed out"):
+        verifier.parse_json("[1]")
+    assert verifier._process is None
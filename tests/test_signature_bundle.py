@@ -0,0 +1,45 @@
+# tests/test_signature_bundle.py
+
+import pytest
+
+reliquary_encryptor = pytest.importorskip("reliquary_encryptor")
+
+# --- Countersigned signature bundles: layered signatures over the same message ---
+
+MESSAGE = b"decision record"
+NOW = 1_800_000_000
+
+
+@pytest.fixture(scope="module")
+def signers():
+    falcon = reliquary_encryptor.generate_falcon_keys()
+    ed = reliquary_encryptor.generate_ed25519_keys()
+    return {"agent": ("falcon1024", *falcon), "notary": ("ed25519", *ed)}
+
+
+def make_bundle(signers):
+    scheme, pk, sk = signers["agent"]
+    bundle = reliquary_encryptor.create_signature_bundle(MESSAGE, sk, pk, scheme, "agent")
+    scheme, pk, sk = signers["notary"]
+    return reliquary_encryptor.countersign_bundle(bundle, sk, pk, scheme, "notary")
+
+
+def test_countersigned_bundle(signers):
+    bundle = make_bundle(signers)
+    report = reliquary_encryptor.verify_signature_bundle(bundle, MESSAGE, NOW)
+    assert [(signer, scheme, valid) for signer, scheme, _, _, _, valid in report] == [
+        ("agent", "falcon1024", True), ("notary", "ed25519", True)]
+    assert report[0][2] == reliquary_encryptor.key_fingerprint(signers["agent"][1])
+    assert not any(layer[5] for layer in
+                   reliquary_encryptor.verify_signature_bundle(bundle, b"other", NOW))
+
+
+def test_countersign_rejections(signers):
+    scheme, pk, sk = signers["agent"]
+    bundle = reliquary_encryptor.create_signature_bundle(MESSAGE, sk, pk, scheme, "agent")
+    notary_scheme, notary_pk, notary_sk = signers["notary"]
+    with pytest.raises(ValueError, match="Public key does not match the signing key"):
+        reliquary_encryptor.countersign_bundle(
+            bundle, notary_sk, reliquary_encryptor.generate_ed25519_keys()[0], "ed25519")
+    with pytest.raises(ValueError, match="Unknown signature scheme 'rsa'"):
+        reliquary_encryptor.countersign_bundle(bundle, notary_sk, notary_pk, "rsa")
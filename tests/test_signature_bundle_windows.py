@@ -0,0 +1,80 @@
+# tests/test_signature_bundle_windows.py
+
+import pytest
+
+reliquary_encryptor = pytest.importorskip("reliquary_encryptor")
+
+# --- Signature bundles: signed per-layer validity windows and clock skew ---
+
+MESSAGE = b"decision record"
+NOW = 1_800_000_000
+HOUR = 3600
+
+
+@pytest.fixture(scope="module")
+def signers():
+    falcon = reliquary_encryptor.generate_falcon_keys()
+    ed = reliquary_encryptor.generate_ed25519_keys()
+    return {"agent": ("falcon1024", *falcon), "notary": ("ed25519", *ed)}
+
+
+def make_bundle(signers, window=(None, None), counter_window=(None, None)):
+    scheme, pk, sk = signers["agent"]
+    bundle = reliquary_encryptor.create_signature_bundle(
+        MESSAGE, sk, pk, scheme, "agent", *window)
+    scheme, pk, sk = signers["notary"]
+    return reliquary_encryptor.countersign_bundle(bundle, sk, pk, scheme, "notary",
+                                                  *counter_window)
+
+
+def test_unbounded_windows(signers):
+    report = reliquary_encryptor.verify_signature_bundle(make_bundle(signers), MESSAGE, NOW)
+    # Unbounded windows are reported as None and never expire
+    assert [layer[3:] for layer in report] == [(None, None, True), (None, None, True)]
+
+
+def test_validity_windows(signers):
+    bundle = make_bundle(signers, window=(NOW - HOUR, NOW + HOUR),
+                         counter_window=(NOW, None))
+    report = reliquary_encryptor.verify_signature_bundle(bundle, MESSAGE, NOW)
+    assert [layer[3:] for layer in report] == [(NOW - HOUR, NOW + HOUR, True),
+                                               (NOW, None, True)]
+
+    def validity(now, skew=0):
+        return [layer[5] for layer in
+                reliquary_encryptor.verify_signature_bundle(bundle, MESSAGE, now, skew)]
+
+    # Ends are inclusive
+    assert validity(NOW + HOUR) == [True, True]
+    assert validity(NOW + HOUR + 1) == [False, True]
+    assert validity(NOW - 1) == [True, False]
+    # Clock skew widens every window
+    assert validity(NOW - 1, skew=1) == [True, True]
+    assert validity(NOW + HOUR + 60, skew=60) == [True, True]
+    assert validity(NOW + HOUR + 61, skew=60) == [False, True]
+
+
+def test_windows_are_signed(signers):
+    bundle = make_bundle(signers, window=(NOW, NOW + HOUR))
+    agent_pk = signers["agent"][1]
+    # magic | version | digest | count | scheme | var8 "agent" | var16 public key | not_before
+    not_after = 4 + 1 + 64 + 1 + 1 + 1 + len(b"agent") + 2 + len(agent_pk) + 8
+    assert int.from_bytes(bundle[not_after:not_after + 8], "big") == NOW + HOUR
+
+    # Stretching the agent's window breaks its own signature and the countersignature over it
+    stretched = bundle[:not_after] + (NOW + 2 * HOUR).to_bytes(8, "big") + bundle[not_after + 8:]
+    report = reliquary_encryptor.verify_signature_bundle(stretched, MESSAGE, NOW)
+    assert report[0][4] == NOW + 2 * HOUR
+    assert [layer[5] for layer in report] == [False, False]
+
+
+def test_window_rejections(signers):
+    scheme, pk, sk = signers["agent"]
+    with pytest.raises(ValueError, match="not_before must not be later than not_after"):
+        reliquary_encryptor.create_signature_bundle(MESSAGE, sk, pk, scheme, "agent",
+                                                    NOW + 1, NOW)
+    bundle = reliquary_encryptor.create_signature_bundle(MESSAGE, sk, pk, scheme, "agent")
+    notary_scheme, notary_pk, notary_sk = signers["notary"]
+    with pytest.raises(ValueError, match="not_before must not be later than not_after"):
+        reliquary_encryptor.countersign_bundle(bundle, notary_sk, notary_pk, notary_scheme,
+                                               "notary", NOW + 1, NOW)
@@ -0,0 +1,86 @@
+# tests/test_signature_sizes.py
+
+import os
+import tempfile
+
+import pytest
+
+reliquary_encryptor = pytest.importorskip("reliquary_encryptor")
+
+# --- signature_size estimates against the lengths the signing functions actually produce ---
+
+signature_size = reliquary_encryptor.signature_size
+FalconParams = reliquary_encryptor.FalconParams
+MSG = b"size check"
+
+
+def test_fixed_size_schemes():
+    _, sk = reliquary_encryptor.generate_ed25519_keys()
+    assert len(reliquary_encryptor.sign_ed25519(MSG, sk)) == signature_size("ed25519")
+    _, sk = reliquary_encryptor.generate_dilithium_keys()
+    assert len(reliquary_encryptor.sign_dilithium(MSG, sk)) == signature_size("dilithium5")
+    _, sk = reliquary_encryptor.generate_ml_dsa_keys()
+    assert len(reliquary_encryptor.sign_ml_dsa(MSG, sk)) == signature_size("ml-dsa-87")
+    _, sk = reliquary_encryptor.generate_bls_keys()
+    assert len(reliquary_encryptor.sign_bls(MSG, sk)) == signature_size("bls")
+    assert signature_size("bls12381") == signature_size("BLS") == 96
+    _, sk = reliquary_encryptor.generate_beacon_keys()
+    assert len(reliquary_encryptor.sign_beacon_round(sk, 7)) == \
+        signature_size("bls-unchained-g1-rfc9380")
+
+
+@pytest.mark.parametrize("name,params", [("falcon512", FalconParams.Falcon512),
+                                         ("falcon1024", FalconParams.Falcon1024)])
+def test_falcon_is_an_upper_bound(name, params):
+    _, sk = reliquary_encryptor.generate_falcon_keys(params)
+    for _ in range(8):
+        signed = reliquary_encryptor.sign_falcon(MSG, sk, params)
+        assert len(signed) - len(MSG) <= signature_size(name)
+
+
+@pytest.mark.parametrize("ring_size", [1, 3, 8])
+def test_ring_signatures(ring_size):
+    members = [reliquary_encryptor.generate_ring_keys() for _ in range(ring_size)]
+    ring = [pk for pk, _ in members]
+    signature = reliquary_encryptor.ring_sign(MSG, ring, members[0][1])
+    assert len(signature) == signature_size("ring", ring_size=ring_size)
+
+
+def test_lms():
+    state_path = os.path.join(tempfile.mkdtemp(), "sizes.lms")
+    reliquary_encryptor.generate_lms_keys(state_path, 5)
+    signer = reliquary_encryptor.LmsSigner(state_path)
+    assert len(signer.sign(MSG)) == signature_size("lms", height=5)
+    # Heights only verifiers see are covered as well
+    assert signature_size("lms", height=25) - signature_size("lms", height=5) == 20 * 32
+
+
+@pytest.mark.parametrize("bits", [2048, 3072])
+def test_blind_rsa(bits):
+    pk, sk = reliquary_encryptor.generate_blind_signing_keys(bits)
+    blinded, inverse, prepared = reliquary_encryptor.blind_message(pk, MSG)
+    signature = reliquary_encryptor.finalize_blind_signature(
+        pk, prepared, reliquary_encryptor.blind_sign(sk, blinded), inverse)
+    assert len(signature) == signature_size("rsabssa-sha384", bits=bits)
+
+
+def test_ecdsa_p256_is_an_upper_bound():
+    ec = pytest.importorskip("cryptography.hazmat.primitives.asymmetric.ec")
+    hashes = pytest.importorskip("cryptography.hazmat.primitives.hashes")
+    key = ec.generate_private_key(ec.SECP256R1())
+    for _ in range(16):
+        signature = key.sign(MSG, ec.ECDSA(hashes.SHA256()))
+        assert len(signature) <= signature_size("ecdsa-p256-sha256")
+
+
+def test_rejections():
+    with pytest.raises(ValueError, match="depends on ring_size"):
+        signature_size("ring")
+    with pytest.raises(ValueError, match="between 1 and 255 public keys"):
+        signature_size("ring", ring_size=256)
+    with pytest.raises(ValueError, match="Unsupported LMS height 12"):
+        signature_size("lms", height=12)
+    with pytest.raises(ValueError, match="Unsupported RSA key size 1024"):
+        signature_size("rsabssa-sha384", bits=1024)
+    with pytest.raises(ValueError, match="Unknown signature scheme 'rsa'"):
+        signature_size("rsa")
@@ -0,0 +1,57 @@
+# tests/test_social_recovery.py
+
+import pytest
+
+reliquary_encryptor = pytest.importorskip("reliquary_encryptor")
+from core.crypto.social_recovery import (create_social_recovery, describe_recovery,
+                                         invalid_released_shares, recover_secret,
+                                         release_guardian_share)
+
+# --- Social recovery among guardian Kyber keys ---
+
+
+@pytest.fixture(scope="module")
+def guardians():
+    return {name: reliquary_encryptor.generate_kyber_keys()
+            for name in ("alice", "bob", "carol", "dave")}
+
+
+def test_any_threshold_of_guardians_recover(guardians):
+    kit = create_social_recovery(b"master seed", {n: pk for n, (pk, _) in guardians.items()}, 3,
+                                 label="vault 7")
+    info = describe_recovery(kit.descriptor)
+    assert info["recovery_id"] == kit.recovery_id
+    assert (info["threshold"], info["label"]) == (3, "vault 7")
+    assert len(info["guardian_fingerprints"]) == 4
+
+    released = {n: release_guardian_share(kit.packages[n], guardians[n][1]) for n in guardians}
+    assert recover_secret(kit.descriptor, [released["dave"], released["alice"],
+                                           released["carol"]]) == b"master seed"
+    with pytest.raises(ValueError, match="Need 3 valid guardian shares, got 2"):
+        recover_secret(kit.descriptor, [released["bob"], released["bob"], released["carol"]])
+
+
+def test_packages_only_open_for_their_guardian(guardians):
+    kit = create_social_recovery(b"s", {n: guardians[n][0] for n in ("alice", "bob")}, 2)
+    with pytest.raises(ValueError):
+        release_guardian_share(kit.packages["alice"], guardians["bob"][1])
+    tampered = bytearray(kit.packages["bob"])
+    tampered[-1] ^= 1
+    with pytest.raises(ValueError, match="authentication failed"):
+        release_guardian_share(bytes(tampered), guardians["bob"][1])
+    with pytest.raises(ValueError, match="only be given once"):
+        create_social_recovery(b"s", {"a": guardians["alice"][0], "b": guardians["alice"][0]}, 1)
+
+
+def test_bad_shares_are_skipped(guardians):
+    public = {n: pk for n, (pk, _) in guardians.items()}
+    kit = create_social_recovery(b"seed", public, 2)
+    other = create_social_recovery(b"seed", public, 2)
+    good = [release_guardian_share(kit.packages[n], guardians[n][1]) for n in ("alice", "bob")]
+    foreign = release_guardian_share(other.packages["carol"], guardians["carol"][1])
+    forged = bytearray(release_guardian_share(kit.packages["dave"], guardians["dave"][1]))
+    forged[-1] ^= 1
+
+    shares = [bytes(forged), foreign, b"junk"] + good
+    assert invalid_released_shares(kit.descriptor, shares) == [0, 1, 2]
+    assert recover_secret(kit.descriptor, shares) == b"seed"
@@ -0,0 +1,85 @@
+# tests/test_sparse_merkle_tree.py
+
+import hashlib
+
+import pytest
+
+reliquary_merkle = pytest.importorskip("reliquary_merkle")
+from core.crypto import pure_verify
+
+# --- SparseMerkleTree: inclusion and absence proofs over 256-bit keys ---
+
+
+def key(name):
+    return hashlib.sha256(name.encode()).digest()
+
+
+def test_get_update_delete():
+    tree = reliquary_merkle.SparseMerkleTree()
+    assert tree.root() == bytes(32) and len(tree) == 0
+    tree.update(key("alice"), b"read")
+    tree.update(key("bob"), b"write")
+    tree.update(key("alice"), b"admin")
+    assert tree.get(key("alice")) == b"admin" and tree.get(key("carol")) is None
+    assert key("bob") in tree and len(tree) == 2
+    assert tree.delete(key("bob")) and not tree.delete(key("bob"))
+    # Canonical layout: the root only depends on the current contents
+    assert tree.root() == reliquary_merkle.SparseMerkleTree([(key("alice"), b"admin")]).root()
+    with pytest.raises(ValueError, match="32 bytes"):
+        tree.update(b"short", b"")
+
+
+def test_root_is_independent_of_insertion_order():
+    items = [(key(f"credential {i}"), f"scope {i}".encode()) for i in range(200)]
+    forward = reliquary_merkle.SparseMerkleTree(items)
+    backward = reliquary_merkle.SparseMerkleTree(items[::-1])
+    assert forward.root() == backward.root()
+    for k, _ in items[100:]:
+        forward.delete(k)
+    assert forward.root() == reliquary_merkle.SparseMerkleTree(items[:100]).root()
+
+
+def test_inclusion_proofs():
+    items = [(key(f"credential {i}"), b"active") for i in range(50)]
+    tree = reliquary_merkle.SparseMerkleTree(items)
+    for k, value in items:
+        proof = tree.prove(k)
+        assert reliquary_merkle.verify_sparse_proof(k, value, proof, tree.root())
+        assert pure_verify.verify_sparse_proof(k, value, proof, tree.root())
+        assert not reliquary_merkle.verify_sparse_proof(k, b"revoked", proof, tree.root())
+        assert not reliquary_merkle.verify_sparse_proof(k, None, proof, tree.root())
+
+
+def test_revoked_credential_is_absent():
+    authorized = reliquary_merkle.SparseMerkleTree(
+        [(key(f"credential {i}"), b"active") for i in range(50)])
+    revoked = key("credential 7")
+    authorized.delete(revoked)
+    root = authorized.root()
+    proof = authorized.prove(revoked)
+    assert reliquary_merkle.verify_sparse_proof(revoked, None, proof, root)
+    assert pure_verify.verify_sparse_proof(revoked, None, proof, root)
+    # The same proof says nothing about other keys, and goes stale once the key is re-added
+    assert not reliquary_merkle.verify_sparse_proof(key("credential 8"), None, proof, root)
+    authorized.update(revoked, b"active")
+    assert not reliquary_merkle.verify_sparse_proof(revoked, None, proof, authorized.root())
+
+
+def test_keys_sharing_long_prefixes():
+    keys = [bytes(31) + bytes([i]) for i in range(8)]
+    tree = reliquary_merkle.SparseMerkleTree([(k, k) for k in keys[:4]])
+    for k in keys:
+        proof = tree.prove(k)
+        value = k if k in keys[:4] else None
+        assert reliquary_merkle.verify_sparse_proof(k, value, proof, tree.root())
+    assert len(tree.prove(keys[0])[0]) == 256
+
+
+def test_malformed_proofs_fail():
+    tree = reliquary_merkle.SparseMerkleTree([(key("a"), b"1"), (key("b"), b"2")])
+    siblings, leaf = tree.prove(key("a"))
+    verify = reliquary_merkle.verify_sparse_proof
+    assert not verify(key("a"), b"1", ([s[:31] for s in siblings], leaf), tree.root())
+    assert not verify(key("a"), b"1", (siblings, None), tree.root())
+    assert not verify(key("a")[:31], b"1", (siblings, leaf), tree.root())
+    assert not verify(key("a"), b"1", (siblings, leaf), tree.root()[:31])
@@ -0,0 +1,94 @@
+# tests/test_state_export.py
+
+import json
+import os
+
+import pytest
+
+reliquary_encryptor = pytest.importorskip("reliquary_encryptor")
+from core.merkle_logging import MerkleLogWriter
+from core.rules.policy_manager import PolicyManager
+from vaults.state_export import StateExportError, export_state, import_state, verify_state
+
+# --- Encrypted, signed state archive verified in full before it is restored ---
+
+@pytest.fixture(scope="module")
+def keys():
+    return reliquary_encryptor.generate_kyber_keys(), reliquary_encryptor.generate_falcon_keys()
+
+
+def _system(root, falcon_sk):
+    keystore = os.path.join(root, "keystore")
+    os.makedirs(os.path.join(keystore, "backups"))
+    with open(os.path.join(keystore, "counters.json"), "w") as f:
+        json.dump({"vault-key": 3}, f)
+    with open(os.path.join(keystore, "backups", "k1.bin"), "wb") as f:
+        f.write(b"\x00backup")
+    audit = MerkleLogWriter(os.path.join(root, "audit", "vault.log"))
+    for i in range(3):
+        audit.add_entry({"event": "seal", "n": i})
+    policies = os.path.join(root, "policies")
+    os.makedirs(policies)
+    bundle = json.dumps({"version": 1, "default_action": "deny", "rules": []}).encode()
+    with open(os.path.join(policies, "main.json"), "wb") as f:
+        f.write(bundle)
+    with open(os.path.join(policies, "main.json.sig"), "wb") as f:
+        f.write(reliquary_encryptor.sign_falcon(bundle, falcon_sk))
+    return keystore, audit, policies
+
+
+def test_export_import_round_trip(tmp_path, keys):
+    (kyber_pk, kyber_sk), (falcon_pk, falcon_sk) = keys
+    keystore, audit, policies = _system(str(tmp_path / "src"), falcon_sk)
+    archive = str(tmp_path / "state.json")
+    manifest = export_state(archive, kyber_pk, falcon_sk, keystore_dir=keystore,
+                            audit_logs=[audit.log_file_path], policy_dir=policies)
+    assert [c["entry_count"] for c in manifest["checkpoints"]] == [3]
+    assert {(r["section"], r["path"]) for r in manifest["files"]} == {
+        ("keystore", "counters.json"), ("keystore", "backups/k1.bin"),
+        ("audit", "vault.log"), ("audit", "vault.log.merkle"),
+        ("policies", "main.json"), ("policies", "main.json.sig")}
+
+    dest = tmp_path / "dest"
+    import_state(archive, kyber_sk, falcon_pk, keystore_dir=str(dest / "keystore"),
+                 audit_dir=str(dest / "audit"), policy_dir=str(dest / "policies"),
+                 policy_manager=PolicyManager(falcon_pk))
+    assert (dest / "keystore" / "backups" / "k1.bin").read_bytes() == b"\x00backup"
+    restored = MerkleLogWriter(str(dest / "audit" / "vault.log"))
+    assert restored.merkle_root == audit.merkle_root
+
+    # Restoring again over identical files is fine; over changed files needs overwrite
+    (dest / "keystore" / "counters.json").write_text('{"vault-key": 9}')
+    with pytest.raises(StateExportError, match="overwrite"):
+        import_state(archive, kyber_sk, falcon_pk, keystore_dir=str(dest / "keystore"))
+    import_state(archive, kyber_sk, falcon_pk, keystore_dir=str(dest / "keystore"),
+                 overwrite=True)
+    assert json.loads((dest / "keystore" / "counters.json").read_text()) == {"vault-key": 3}
+
+
+def test_tampering_is_rejected_before_anything_is_written(tmp_path, keys):
+    (kyber_pk, kyber_sk), (falcon_pk, falcon_sk) = keys
+    keystore, audit, policies = _system(str(tmp_path / "src"), falcon_sk)
+    archive = tmp_path / "state.json"
+    export_state(str(archive), kyber_pk, falcon_sk, keystore_dir=keystore,
+                 audit_logs=[audit.log_file_path], policy_dir=policies)
+    original = json.loads(archive.read_text())
+
+    manifest = json.loads(original["manifest"])
+    manifest["checkpoints"][0]["entry_count"] = 2
+    archive.write_text(json.dumps(dict(original, manifest=json.dumps(manifest, sort_keys=True))))
+    with pytest.raises(StateExportError, match="signature"):
+        import_state(str(archive), kyber_sk, falcon_pk, keystore_dir=str(tmp_path / "dest"))
+    assert not (tmp_path / "dest").exists()
+
+    # A policy bundle signed by someone else fails even though the archive itself is genuine
+    other_pk, _ = reliquary_encryptor.generate_falcon_keys()
+    archive.write_text(json.dumps(original))
+    with pytest.raises(StateExportError, match="main.json"):
+        verify_state(str(archive), kyber_sk, falcon_pk, policy_manager=PolicyManager(other_pk))
+
+    # The audit log is checked against its own Merkle root file at export time
+    with open(audit.log_file_path, "ab") as f:
+        f.write(b'{"event": "forged", "timestamp": "2026-01-01T00:00:00Z"}\n')
+    with pytest.raises(StateExportError, match="Merkle root file"):
+        export_state(str(archive), kyber_pk, falcon_sk, audit_logs=[audit.log_file_path])
@@ -0,0 +1,53 @@
+# tests/test_threshold_kek.py
+
+import pytest
+
+reliquary_encryptor = pytest.importorskip("reliquary_encryptor")
+from core.crypto.dkg import create_dkg_participant, new_dkg_session_id, run_local_dkg
+from core.crypto.threshold_kek import (invalid_partials, partial_unwrap, unwrap_kek_threshold,
+                                       wrap_kek_threshold)
+
+# --- Threshold KEK unwrap from DKG key shares ---
+
+@pytest.fixture(scope="module")
+def trustees():
+    session = new_dkg_session_id()
+    results = run_local_dkg([create_dkg_participant(session, i, 3, 5) for i in range(1, 6)])
+    return results[1].group_key, {i: r.key_share for i, r in results.items()}
+
+
+def test_any_threshold_of_trustees_unwrap(trustees):
+    group_key, shares = trustees
+    kek, wrapped = wrap_kek_threshold(group_key, context=b"vault-7")
+    partials = {i: partial_unwrap(group_key, share, wrapped) for i, share in shares.items()}
+    assert unwrap_kek_threshold(group_key, wrapped, [partials[i] for i in (1, 2, 3)],
+                                b"vault-7") == kek
+    assert unwrap_kek_threshold(group_key, wrapped, [partials[i] for i in (5, 1, 4)],
+                                b"vault-7") == kek
+
+    with pytest.raises(ValueError, match="Need 3 valid partials, got 2"):
+        unwrap_kek_threshold(group_key, wrapped, [partials[1], partials[2], partials[2]],
+                             b"vault-7")
+    with pytest.raises(ValueError, match="does not authenticate"):
+        unwrap_kek_threshold(group_key, wrapped, list(partials.values()), b"vault-8")
+
+
+def test_bad_partials_are_detected_and_skipped(trustees):
+    group_key, shares = trustees
+    kek, wrapped = wrap_kek_threshold(group_key, b"k" * 32)
+    partials = [partial_unwrap(group_key, shares[i], wrapped) for i in (1, 2, 3, 4)]
+    forged = bytearray(partials[0])
+    forged[30] ^= 0x01
+    # A partial for another ciphertext doesn't verify either
+    _, other = wrap_kek_threshold(group_key, b"k" * 32)
+    replayed = partial_unwrap(group_key, shares[2], other)
+    candidates = [bytes(forged), replayed, *partials[2:], partials[1]]
+    assert invalid_partials(group_key, wrapped, candidates) == [0, 1]
+    assert unwrap_kek_threshold(group_key, wrapped, candidates) == kek
+
+    # Key shares only work with their own group key
+    other_session = new_dkg_session_id()
+    other_group = run_local_dkg(
+        [create_dkg_participant(other_session, i, 2, 2) for i in (1, 2)])[1].group_key
+    with pytest.raises(ValueError, match="another group key"):
+        partial_unwrap(other_group, shares[1], wrapped)
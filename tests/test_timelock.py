@@ -0,0 +1,66 @@
+# tests/test_timelock.py
+
+import pytest
+
+reliquary_encryptor = pytest.importorskip("reliquary_encryptor")
+from core.timelock import (QUICKNET, LocalBeacon, TimelockError, TimelockNotReady,
+                           open_sealed, seal_until, sealed_round, unlock_time)
+
+# --- Time-lock encryption to beacon rounds ---
+
+GENESIS = 1_700_000_000
+
+
+@pytest.fixture(scope="module")
+def beacon():
+    return LocalBeacon.generate(genesis_time=GENESIS, period=30)
+
+
+def test_round_schedule():
+    chain = LocalBeacon.generate(genesis_time=GENESIS, period=30).chain
+    assert chain.round_at(GENESIS - 1) == 0
+    assert chain.round_at(GENESIS) == 1
+    assert chain.round_at(GENESIS + 59) == 2
+    assert chain.first_round_after(GENESIS + 30) == 2
+    assert chain.first_round_after(GENESIS + 31) == 3
+    assert chain.round_time(3) == GENESIS + 60
+    assert QUICKNET.round_time(QUICKNET.round_at(1760486400)) <= 1760486400
+
+
+def test_sealed_record_opens_only_with_its_round(beacon):
+    unlock = GENESIS + 3600
+    ciphertext = seal_until(b"last will", unlock, beacon.chain)
+    round_number = sealed_round(ciphertext)
+    assert unlock_time(ciphertext, beacon.chain) >= unlock
+    assert unlock_time(ciphertext, beacon.chain) - unlock < beacon.chain.period
+
+    with pytest.raises(TimelockNotReady) as not_ready:
+        open_sealed(ciphertext, beacon.chain, now=unlock - 1)
+    assert not_ready.value.round == round_number
+    with pytest.raises(TimelockNotReady):
+        beacon.signature(round_number, now=unlock - 1)
+
+    signature = beacon.signature(round_number, now=unlock)
+    assert open_sealed(ciphertext, beacon.chain, signature=signature) == b"last will"
+    earlier = beacon.signature(round_number - 1, now=unlock)
+    with pytest.raises(TimelockError, match="not the beacon's signature"):
+        open_sealed(ciphertext, beacon.chain, signature=earlier)
+
+
+def test_wrong_beacon_and_tampering_are_rejected(beacon):
+    ciphertext = seal_until(b"disclose later", GENESIS + 600, beacon.chain)
+    signature = beacon.signature(sealed_round(ciphertext), now=GENESIS + 600)
+    other = LocalBeacon.generate(genesis_time=GENESIS, period=30)
+    with pytest.raises(TimelockError, match="different beacon"):
+        open_sealed(ciphertext, other.chain, signature=signature)
+
+    tampered = bytearray(ciphertext)
+    tampered[-1] ^= 1
+    with pytest.raises(TimelockError, match="does not authenticate"):
+        open_sealed(bytes(tampered), beacon.chain, signature=signature)
+    with pytest.raises(TimelockError, match="Not a time-locked ciphertext"):
+        sealed_round(b"RQXX" + ciphertext[4:])
+
+
+def test_quicknet_key_is_valid():
+    assert sealed_round(seal_until(b"x", QUICKNET.round_time(10**7))) == 10**7
@@ -0,0 +1,72 @@
+# tests/test_tpm_keystore.py
+
+import hashlib
+import os
+
+import pytest
+
+from vaults.tpm_keystore import TpmError, TpmKeyStore, TpmUnsealError
+
+# --- TPM-sealed master key: PCR-bound sealing through tpm2-tools (simulated TPM) ---
+
+class FakeTpm:
+    """Stands in for tpm2-tools: sealed blobs only open while PCRs match the policy."""
+
+    def __init__(self):
+        self.pcrs = {i: bytes(32) for i in range(24)}
+        self.commands = []
+
+    def policy(self, selection):
+        bank, indices = selection.split(":")
+        return hashlib.sha256(b"".join(self.pcrs[int(i)] for i in indices.split(","))).digest()
+
+    def __call__(self, args, stdin, env):
+        self.commands.append(args[0])
+        opt = {args[i]: args[i + 1] for i in range(1, len(args) - 1) if args[i].startswith("-")}
+        if args[0] == "tpm2_createpolicy":
+            open(opt["-L"], "wb").write(self.policy(opt["-l"]))
+        elif args[0] == "tpm2_create":
+            assert "userwithauth" not in opt["-a"]
+            open(opt["-u"], "wb").write(open(opt["-L"], "rb").read())
+            open(opt["-r"], "wb").write(stdin)
+        elif args[0] == "tpm2_load":
+            open(opt["-c"], "wb").write(open(opt["-u"], "rb").read() + open(opt["-r"], "rb").read())
+        elif args[0] == "tpm2_unseal":
+            blob = open(opt["-c"], "rb").read()
+            if blob[:32] != self.policy(opt["-p"][len("pcr:"):]):
+                raise TpmError("tpm2_unseal failed: TPM_RC_POLICY_FAIL")
+            return blob[32:]
+        return b""
+
+    def extend(self, index, data):
+        self.pcrs[index] = hashlib.sha256(self.pcrs[index] + data).digest()
+
+def test_seal_unseal_and_pcr_binding(tmp_path):
+    tpm = FakeTpm()
+    store = TpmKeyStore(str(tmp_path / "tpm"), pcrs=(7, 0), runner=tpm)
+    assert not store.is_provisioned()
+    with pytest.raises(TpmError, match="No sealed master key"):
+        store.unseal_master_key()
+
+    master_key = store.load_or_create_master_key()
+    assert len(master_key) == 32 and store.is_provisioned()
+    assert sorted(os.listdir(tmp_path / "tpm")) == ["master_key.json", "master_key.priv",
+                                                    "master_key.pub"]
+    assert store.load_or_create_master_key() == master_key
+    tpm.extend(4, b"unrelated PCR")
+    assert store.unseal_master_key() == master_key
+
+    # A different boot chain (PCR 7 changed) can't get the key
+    tpm.extend(7, b"secure boot disabled")
+    with pytest.raises(TpmUnsealError, match="sha256:0,7"):
+        store.unseal_master_key()
+
+    # Resealing under the new state, e.g. after a planned firmware update
+    store.seal_master_key(master_key)
+    assert store.unseal_master_key() == master_key
+
+def test_rejects_invalid_input(tmp_path):
+    with pytest.raises(ValueError):
+        TpmKeyStore(str(tmp_path), pcrs=(24,))
+    with pytest.raises(ValueError):
+        TpmKeyStore(str(tmp_path), runner=FakeTpm()).seal_master_key(b"x" * 129)
@@ -0,0 +1,83 @@
+# tests/test_tree_head.py
+
+import hashlib
+import struct
+from dataclasses import replace
+from types import SimpleNamespace
+
+import pytest
+
+reliquary_encryptor = pytest.importorskip("reliquary_encryptor")
+reliquary_merkle = pytest.importorskip("reliquary_merkle")
+from core.crypto.tree_head import SignedTreeHead, sign_tree_head, verify_tree_head
+
+# --- Signed tree heads ---
+
+TIMESTAMP = 1_700_000_000_123
+BLOCKS = [f"entry {n}".encode() for n in range(9)]
+
+
+@pytest.fixture(scope="module")
+def keys():
+    return {
+        "ed25519": reliquary_encryptor.generate_ed25519_keys(),
+        "falcon-512": reliquary_encryptor.generate_falcon_keys(
+            reliquary_encryptor.FalconParams.Falcon512),
+        "falcon-1024": reliquary_encryptor.generate_falcon_keys(),
+    }
+
+
+def test_layout_is_pinned():
+    root = hashlib.sha256(b"root").digest()
+    head = SignedTreeHead(tree_size=5, root=root, timestamp=TIMESTAMP, algorithm="ed25519",
+                          signature=b"\xaa" * 64)
+    signed = b"RQTH\x01" + struct.pack(">QQB", 5, TIMESTAMP, 3) + b"\x20" + root
+    assert head.signing_bytes() == signed
+    assert head.to_bytes() == signed + b"\x00\x40" + b"\xaa" * 64
+    assert SignedTreeHead.from_bytes(head.to_bytes()) == head
+
+
+@pytest.mark.parametrize("algorithm", ["ed25519", "falcon-512", "falcon-1024"])
+def test_sign_and_verify(keys, algorithm):
+    pk, sk = keys[algorithm]
+    tree = reliquary_merkle.MerkleTree(BLOCKS, 2, "blake3")
+    head = sign_tree_head(tree.tree_root(), sk, algorithm, timestamp=TIMESTAMP)
+    assert (head.tree_size, head.root, head.timestamp) == (9, tree.root(), TIMESTAMP)
+    assert verify_tree_head(head, pk)
+    assert verify_tree_head(head.to_bytes(), pk, algorithm=algorithm)
+    # Every signed field is covered
+    assert not verify_tree_head(replace(head, tree_size=8), pk)
+    assert not verify_tree_head(replace(head, root=tree.root_at(8)), pk)
+    assert not verify_tree_head(replace(head, timestamp=TIMESTAMP + 1), pk)
+    other_pk, _ = keys["falcon-1024" if algorithm == "ed25519" else "ed25519"]
+    assert not verify_tree_head(head, other_pk)
+
+
+def test_algorithm_and_encoding_checks(keys):
+    pk, sk = keys["ed25519"]
+    head = sign_tree_head(SimpleNamespace(root=BLOCKS[0] * 4 + b"\x00" * 4, tree_size=3), sk,
+                          "ed25519")
+    assert verify_tree_head(head, pk)
+    assert not verify_tree_head(head, pk, algorithm="falcon-1024")
+    # Re-labelling the algorithm changes the signed bytes
+    relabelled = bytearray(head.to_bytes())
+    relabelled[21] = 2
+    assert not verify_tree_head(bytes(relabelled), pk)
+
+    encoded = head.to_bytes()
+    cases = {
+        encoded[:-1]: "truncated",
+        encoded + b"\x00": "Trailing data",
+        b"RQTX" + encoded[4:]: "Not a signed tree head",
+        encoded[:4] + b"\x02" + encoded[5:]: "format version 2",
+        encoded[:21] + b"\x09" + encoded[22:]: "algorithm id 9",
+        encoded[:5] + bytes(8) + encoded[13:]: "at least one leaf",
+    }
+    for data, message in cases.items():
+        with pytest.raises(ValueError, match=message):
+            SignedTreeHead.from_bytes(data)
+        assert not verify_tree_head(data, pk)
+    with pytest.raises(ValueError, match="Not a Merkle root"):
+        SignedTreeHead(tree_size=1, root=b"short", timestamp=0, algorithm="ed25519")
+    with pytest.raises(ValueError, match="Unsupported tree head signature algorithm"):
+        sign_tree_head(reliquary_merkle.MerkleTree(BLOCKS).tree_root(), sk, "rsa")
@@ -0,0 +1,138 @@
+# tests/test_tree_head_timestamp.py
+
+import json
+import threading
+from http.server import BaseHTTPRequestHandler, HTTPServer
+from pathlib import Path
+
+import pytest
+
+reliquary_encryptor = pytest.importorskip("reliquary_encryptor")
+from core.crypto.tree_head import (
+    SignedTreeHead,
+    TimestampedTreeHead,
+    request_timestamp,
+    timestamp_request,
+    verify_timestamped_tree_head,
+)
+
+# --- RFC 3161 time-stamp tokens over signed tree heads ---
+
+VECTORS_PATH = Path(__file__).parent / "vectors" / "rfc3161_tree_head.json"
+
+
+@pytest.fixture(scope="module")
+def vectors():
+    return json.loads(VECTORS_PATH.read_text())
+
+
+def token_for(vectors, tsa):
+    return bytes(reliquary_encryptor.timestamp_token_from_response(
+        bytes.fromhex(vectors[tsa]["response"])))
+
+
+def test_request_is_deterministic_for_a_nonce(vectors):
+    head = SignedTreeHead.from_bytes(bytes.fromhex(vectors["head"]))
+    assert timestamp_request(head, vectors["nonce"]).hex() == vectors["request"]
+    assert timestamp_request(head) != timestamp_request(head, vectors["nonce"])
+    with pytest.raises(ValueError, match="Unsupported time-stamp hash algorithm"):
+        reliquary_encryptor.create_timestamp_request(b"head", hash_algorithm="md5")
+
+
+@pytest.mark.parametrize("tsa", ["rsa", "p256"])
+def test_tokens_verify(vectors, tsa):
+    head = bytes.fromhex(vectors["head"])
+    certificate = vectors[tsa]["certificate"].encode()
+    info = reliquary_encryptor.verify_timestamp_token(
+        token_for(vectors, tsa), head, certificate, vectors["nonce"])
+    assert info.gen_time == vectors[tsa]["gen_time"]
+    assert (info.policy, info.hash_algorithm) == (vectors["policy"], "sha256")
+    assert (info.nonce, info.accuracy_ms) == (vectors["nonce"], 1500)
+    assert bytes(info.serial_number).hex() == vectors[tsa]["serial_number"]
+
+
+def test_token_checks(vectors):
+    head = bytes.fromhex(vectors["head"])
+    token = token_for(vectors, "rsa")
+    certificate = vectors["rsa"]["certificate"].encode()
+    verify = reliquary_encryptor.verify_timestamp_token
+    with pytest.raises(ValueError, match="different message"):
+        verify(token, head + b"x", certificate)
+    with pytest.raises(ValueError, match="nonce does not match"):
+        verify(token, head, certificate, vectors["nonce"] + 1)
+    with pytest.raises(ValueError, match="not signed by this TSA certificate"):
+        verify(token, head, vectors["p256"]["certificate"].encode())
+    with pytest.raises(ValueError, match="timeStamping extended key usage"):
+        verify(token, head, vectors["rsa_without_eku"].encode())
+    # Flip a byte of the RSA signature at the end of the token
+    with pytest.raises(ValueError, match="signature is invalid"):
+        verify(token[:-1] + bytes([token[-1] ^ 1]), head, certificate)
+    with pytest.raises(ValueError, match="Malformed"):
+        verify(token[:-10], head, certificate)
+    with pytest.raises(ValueError, match="PKI status 2"):
+        reliquary_encryptor.timestamp_token_from_response(
+            bytes.fromhex(vectors["rejected_response"]))
+
+
+def test_timestamped_tree_head(vectors):
+    head = SignedTreeHead.from_bytes(bytes.fromhex(vectors["head"]))
+    public_key = bytes.fromhex(vectors["public_key"])
+    stamped = TimestampedTreeHead(head, token_for(vectors, "p256"))
+    encoded = stamped.to_bytes()
+    assert TimestampedTreeHead.from_bytes(encoded) == stamped
+    certificate = vectors["p256"]["certificate"].encode()
+    assert verify_timestamped_tree_head(encoded, public_key, certificate) == \
+        vectors["p256"]["gen_time"]
+    assert verify_timestamped_tree_head(stamped, public_key, certificate, "falcon-1024") is None
+    assert verify_timestamped_tree_head(stamped, bytes(32), certificate) is None
+    assert verify_timestamped_tree_head(
+        stamped, public_key, vectors["rsa"]["certificate"].encode()) is None
+    assert verify_timestamped_tree_head(encoded + b"\x00", public_key, certificate) is None
+
+    # The token covers the whole serialized head, signature included
+    resigned = SignedTreeHead(head.tree_size, head.root, head.timestamp, head.algorithm,
+                              bytes(64))
+    assert verify_timestamped_tree_head(
+        TimestampedTreeHead(resigned, stamped.token), public_key, certificate) is None
+    with pytest.raises(ValueError, match="Trailing data"):
+        TimestampedTreeHead.from_bytes(encoded + b"\x00")
+    with pytest.raises(ValueError, match="truncated"):
+        TimestampedTreeHead.from_bytes(encoded[:-1])
+    with pytest.raises(ValueError, match="Time-stamp token"):
+        TimestampedTreeHead(head, b"")
+
+
+def test_request_timestamp_over_http(vectors):
+    head = SignedTreeHead.from_bytes(bytes.fromhex(vectors["head"]))
+    requests = []
+
+    class Tsa(BaseHTTPRequestHandler):
+        def do_POST(self):
+            requests.append((self.headers["Content-Type"],
+                             self.rfile.read(int(self.headers["Content-Length"]))))
+            reply = bytes.fromhex(vectors["rsa"]["response"])
+            self.send_response(200)
+            self.send_header("Content-Type", "application/timestamp-reply")
+            self.send_header("Content-Length", str(len(reply)))
+            self.end_headers()
+            self.wfile.write(reply)
+
+        def log_message(self, *args):
+            pass
+
+    server = HTTPServer(("127.0.0.1", 0), Tsa)
+    threading.Thread(target=server.serve_forever, daemon=True).start()
+    try:
+        url = f"http://127.0.0.1:{server.server_port}/"
+        certificate = vectors["rsa"]["certificate"].encode()
+        stamped = request_timestamp(head, url, certificate, nonce=vectors["nonce"])
+        assert requests == [("application/timestamp-query", bytes.fromhex(vectors["request"]))]
+        assert verify_timestamped_tree_head(
+            stamped, bytes.fromhex(vectors["public_key"]), certificate) == \
+            vectors["rsa"]["gen_time"]
+        # The stored reply answers another nonce, which a fresh request won't match
+        with pytest.raises(ValueError, match="nonce does not match"):
+            request_timestamp(head, url, certificate)
+    finally:
+        server.shutdown()
+        server.server_close()
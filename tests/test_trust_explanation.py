@@ -0,0 +1,51 @@
+# tests/test_trust_explanation.py
+
+import json
+
+import pytest
+
+trust_engine = pytest.importorskip("zk.trust_engine")
+
+# --- Decision traces: every point of the score is accounted for, and redaction holds ---
+
+CONTEXT = {
+    "device_verified": True,
+    "device_fingerprint": "laptop-1",
+    "timestamp_verified": False,
+    "location_verified": True,
+    "latitude": 52.5,
+    "longitude": 13.4,
+    "pattern_verified": False,
+    "current_timestamp": 1_700_000_030,
+    "last_access_time": 1_700_000_000,
+    "business_hours_ok": False,
+}
+
+@pytest.fixture
+def evaluation(tmp_path):
+    engine = trust_engine.TrustScoringEngine(
+        tmp_path, explanation_redaction=trust_engine.RedactionLevel.END_USER,
+        audit_log_path=tmp_path / "trust_scoring.log"
+    )
+    return engine.evaluate_trust("alice", CONTEXT)
+
+def test_contributions_add_up_to_score(evaluation):
+    explanation = evaluation.explanation
+    total = sum(signal["contribution"] for signal in explanation.signals)
+    assert total == pytest.approx(evaluation.overall_trust_score, abs=1e-3)
+    assert explanation.thresholds[-1]["met"]
+    assert explanation.thresholds[-1]["level"] == evaluation.risk_level.name
+    rules = {hit["rule"] for hit in explanation.rule_hits}
+    assert {"timestamp_not_verified", "rapid_access", "outside_business_hours"} <= rules
+
+def test_redaction_levels(evaluation):
+    explanation = evaluation.explanation
+    end_user = json.loads(evaluation.explanation_json)
+    assert set(end_user) == {"redaction", "risk_level", "reasons"}
+    assert "Repeated access attempts in a short time" in end_user["reasons"]
+
+    summary = explanation.to_dict(trust_engine.RedactionLevel.SUMMARY)
+    assert all("detail" not in hit for hit in summary["rule_hits"])
+    full = explanation.to_dict(trust_engine.RedactionLevel.FULL)
+    rapid = next(hit for hit in full["rule_hits"] if hit["rule"] == "rapid_access")
+    assert rapid["detail"]["seconds_since_last"] == 30
@@ -0,0 +1,130 @@
+# tests/test_unseal.py
+
+import pytest
+
+reliquary_encryptor = pytest.importorskip("reliquary_encryptor")
+
+# --- Policy-gated unseal and its dry run: quorum approval, key availability and traces ---
+
+MESSAGE = b"break-glass credentials"
+CONTEXT = b"incident-4711"
+
+
+@pytest.fixture(scope="module")
+def trustees():
+    return [(name, *reliquary_encryptor.generate_ed25519_keys()) for name in ("alice", "bob")]
+
+
+@pytest.fixture(scope="module")
+def policy(trustees):
+    return reliquary_encryptor.QuorumPolicy(
+        [(name, "ed25519", pk) for name, pk, _ in trustees], 2, b"unseal")
+
+
+@pytest.fixture(scope="module")
+def holders():
+    return [reliquary_encryptor.generate_kyber_keys() for _ in range(3)]
+
+
+def approve(policy, trustees, envelope, context=CONTEXT):
+    digest = reliquary_encryptor.unseal_request_digest(envelope, context)
+    votes = [(name, reliquary_encryptor.sign_quorum_vote(policy, name, digest, sk))
+             for name, _, sk in trustees]
+    return reliquary_encryptor.aggregate_quorum_certificate(policy, digest, votes)
+
+
+def checks(trace):
+    return {name: passed for name, passed, _ in trace}
+
+
+def test_dry_run_allows_and_unseal_opens(policy, trustees, holders):
+    envelope = reliquary_encryptor.encrypt_to_recipients(
+        MESSAGE, [pk for pk, _ in holders], threshold=2)
+    certificate = approve(policy, trustees, envelope)
+    sks = [sk for _, sk in holders[:2]]
+    allowed, trace = reliquary_encryptor.unseal_dry_run(envelope, sks, policy, certificate,
+                                                        CONTEXT)
+    assert allowed
+    assert [name for name, _, _ in trace] == ["envelope.format", "quorum.certificate",
+                                              "keys.available"]
+    assert trace[0][2] == "multi-recipient, 2-of-3"
+    assert trace[1][2] == "approved by 2 of 2 required trustees: alice, bob"
+    assert trace[2][2] == "2 of 2 required recipient keys present"
+    assert reliquary_encryptor.unseal(envelope, sks, policy, certificate, CONTEXT) == MESSAGE
+
+
+def test_dry_run_reports_each_failure(policy, trustees, holders):
+    envelope = reliquary_encryptor.encrypt_to_recipients(
+        MESSAGE, [pk for pk, _ in holders], threshold=2)
+    certificate = approve(policy, trustees, envelope)
+
+    # Too few keys
+    allowed, trace = reliquary_encryptor.unseal_dry_run(envelope, [holders[0][1]], policy,
+                                                        certificate, CONTEXT)
+    assert not allowed
+    assert checks(trace) == {"envelope.format": True, "quorum.certificate": True,
+                             "keys.available": False}
+    assert trace[2][2] == "1 of 2 required recipient keys present"
+
+    # The approval names the exact context and envelope
+    sks = [sk for _, sk in holders]
+    for other_envelope, context in ((envelope, b"incident-4712"),
+                                    (reliquary_encryptor.encrypt_to_recipients(
+                                        MESSAGE, [pk for pk, _ in holders], threshold=2),
+                                     CONTEXT)):
+        allowed, trace = reliquary_encryptor.unseal_dry_run(other_envelope, sks, policy,
+                                                            certificate, context)
+        assert not allowed and checks(trace)["quorum.certificate"] is False
+        assert checks(trace)["keys.available"]
+
+    allowed, trace = reliquary_encryptor.unseal_dry_run(envelope, sks, policy, b"garbage",
+                                                        CONTEXT)
+    assert not allowed and trace[1][2] == "malformed quorum certificate"
+
+    allowed, trace = reliquary_encryptor.unseal_dry_run(b"not an envelope", sks, policy,
+                                                        certificate, CONTEXT)
+    assert checks(trace) == {"envelope.format": False, "quorum.certificate": False,
+                             "keys.available": False}
+
+
+def test_unseal_denied_names_failed_checks(policy, trustees, holders):
+    envelope = reliquary_encryptor.encrypt_to_recipients(
+        MESSAGE, [pk for pk, _ in holders], threshold=2)
+    certificate = approve(policy, trustees, envelope, context=b"incident-4712")
+    with pytest.raises(ValueError, match=r"Unseal denied: quorum.certificate \(") as excinfo:
+        reliquary_encryptor.unseal(envelope, [holders[0][1]], policy, certificate, CONTEXT)
+    assert "keys.available (1 of 2 required recipient keys present)" in str(excinfo.value)
+
+
+def test_single_recipient_envelope(policy, trustees, holders):
+    pk, sk = holders[0]
+    envelope = reliquary_encryptor.encrypt_to_public_key(MESSAGE, pk)
+    certificate = approve(policy, trustees, envelope)
+    allowed, trace = reliquary_encryptor.unseal_dry_run(envelope, [sk], policy, certificate,
+                                                        CONTEXT)
+    assert allowed and trace[0][2] == "single-recipient, kyber1024"
+    assert reliquary_encryptor.unseal(envelope, [sk], policy, certificate, CONTEXT) == MESSAGE
+
+    small_sk = reliquary_encryptor.generate_kyber_keys(
+        reliquary_encryptor.KyberParams.Kyber512)[1]
+    allowed, trace = reliquary_encryptor.unseal_dry_run(envelope, [small_sk], policy,
+                                                        certificate, CONTEXT)
+    assert not allowed
+    assert trace[2][2] == "kyber1024 secret key missing (matched by parameter set only)"
+
+
+def test_dry_run_derives_nothing(policy, trustees, holders):
+    envelope = reliquary_encryptor.encrypt_to_recipients(
+        MESSAGE, [pk for pk, _ in holders], threshold=2)
+    # Corrupt the first slot's wrapped share. Slots start after magic | version | threshold |
+    # count; a slot is fingerprint | kem id | kem ct | index | nonce | wrapped share (48)
+    share_end = 7 + 32 + 1 + 1568 + 1 + 12 + 48
+    tampered = envelope[:share_end - 1] + bytes([envelope[share_end - 1] ^ 1]) + \
+        envelope[share_end:]
+    certificate = approve(policy, trustees, tampered)
+    sks = [sk for _, sk in holders[:2]]
+    # Only fingerprints are compared, so the dry run can't see the damage...
+    assert reliquary_encryptor.unseal_dry_run(tampered, sks, policy, certificate, CONTEXT)[0]
+    # ...which the real unseal catches when it unwraps the share
+    with pytest.raises(ValueError, match="authentication failed"):
+        reliquary_encryptor.unseal(tampered, sks, policy, certificate, CONTEXT)
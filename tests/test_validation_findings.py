@@ -0,0 +1,93 @@
+# tests/test_validation_findings.py
+
+import pytest
+
+reliquary_encryptor = pytest.importorskip("reliquary_encryptor")
+
+# --- Structured findings for keys, envelopes and certificates, and the raising validators ---
+
+KYBER = reliquary_encryptor.KyberParams.Kyber768
+FALCON = reliquary_encryptor.FalconParams.Falcon512
+
+
+def codes(findings):
+    return [(severity, code) for severity, code, _ in findings]
+
+
+def test_well_formed_material_has_no_findings():
+    kyber_pk, _ = reliquary_encryptor.generate_kyber_keys(KYBER)
+    falcon_pk, _ = reliquary_encryptor.generate_falcon_keys(FALCON)
+    assert reliquary_encryptor.validate_kyber_pk(kyber_pk, KYBER) == []
+    assert reliquary_encryptor.validate_falcon_pk(falcon_pk, FALCON) == []
+    reliquary_encryptor.validate_kyber_public_key(kyber_pk, KYBER)
+    reliquary_encryptor.validate_falcon_public_key(falcon_pk, FALCON)
+    envelope = reliquary_encryptor.encrypt_to_public_key(b"data", kyber_pk, KYBER)
+    assert reliquary_encryptor.validate_envelope(envelope) == []
+
+
+def test_out_of_range_coefficient():
+    kyber_pk, _ = reliquary_encryptor.generate_kyber_keys(KYBER)
+    # 0xfff packs two coefficients of 4095, above q = 3329
+    bad = b"\xff\xff\xff" + kyber_pk[3:]
+    findings = reliquary_encryptor.validate_kyber_pk(bad, KYBER)
+    assert codes(findings) == [("error", "coefficient_range")]
+    assert "2 coefficients are not reduced mod 3329 (first at index 0)" in findings[0][2]
+    with pytest.raises(ValueError, match="Invalid public key: 2 coefficients"):
+        reliquary_encryptor.validate_kyber_public_key(bad, KYBER)
+    with pytest.raises(ValueError, match="not reduced"):
+        reliquary_encryptor.encapsulate_kyber(bad, KYBER)
+
+    falcon_pk, _ = reliquary_encryptor.generate_falcon_keys(FALCON)
+    bad = falcon_pk[:1] + b"\xff\xff" + falcon_pk[3:]
+    assert codes(reliquary_encryptor.validate_falcon_pk(bad, FALCON)) == [
+        ("error", "coefficient_range")]
+    with pytest.raises(ValueError, match="not reduced mod 12289"):
+        reliquary_encryptor.validate_falcon_public_key(bad, FALCON)
+
+
+def test_wrong_falcon_header():
+    falcon_pk, _ = reliquary_encryptor.generate_falcon_keys(FALCON)
+    bad = b"\x0a" + falcon_pk[1:]
+    findings = reliquary_encryptor.validate_falcon_pk(bad, FALCON)
+    assert codes(findings) == [("error", "header")]
+    assert findings[0][2] == "Header byte is 0x0a, falcon512 expects 0x09"
+    with pytest.raises(ValueError, match="Header byte is 0x0a"):
+        reliquary_encryptor.validate_falcon_public_key(bad, FALCON)
+    # Every problem is reported, the raising variant stops at the first
+    bad = b"\x0a" + bytes(len(falcon_pk) - 1)
+    assert codes(reliquary_encryptor.validate_falcon_pk(bad, FALCON)) == [
+        ("error", "header"), ("error", "zero_polynomial")]
+
+
+def test_truncated_or_trailing_bytes():
+    kyber_pk, _ = reliquary_encryptor.generate_kyber_keys(KYBER)
+    falcon_pk, _ = reliquary_encryptor.generate_falcon_keys(FALCON)
+    assert codes(reliquary_encryptor.validate_kyber_pk(kyber_pk[:-1], KYBER)) == [
+        ("error", "length")]
+    assert codes(reliquary_encryptor.validate_falcon_pk(falcon_pk + b"\x00", FALCON)) == [
+        ("error", "length")]
+    with pytest.raises(ValueError, match="Public key is 898 bytes, falcon512 expects 897"):
+        reliquary_encryptor.validate_falcon_public_key(falcon_pk + b"\x00", FALCON)
+
+    _, ciphertext = reliquary_encryptor.encapsulate_kyber(kyber_pk, KYBER)
+    reliquary_encryptor.validate_kyber_ciphertext(ciphertext, KYBER)
+    with pytest.raises(ValueError, match="Invalid ciphertext length"):
+        reliquary_encryptor.validate_kyber_ciphertext(ciphertext[:-1], KYBER)
+
+    envelope = reliquary_encryptor.encrypt_to_public_key(b"data", kyber_pk, KYBER)
+    # Cut inside the KEM ciphertext
+    findings = reliquary_encryptor.validate_envelope(envelope[:100])
+    assert codes(findings) == [("error", "malformed")]
+    assert "truncated" in findings[0][2]
+
+    pk, sk = reliquary_encryptor.generate_ed25519_keys()
+    policy = reliquary_encryptor.QuorumPolicy([("alice", "ed25519", pk)], 1)
+    digest = bytes(32)
+    vote = reliquary_encryptor.sign_quorum_vote(policy, "alice", digest, sk)
+    certificate = reliquary_encryptor.aggregate_quorum_certificate(policy, digest,
+                                                                   [("alice", vote)])
+    assert reliquary_encryptor.validate_quorum_certificate(certificate) == []
+    assert codes(reliquary_encryptor.validate_quorum_certificate(certificate + b"\x00")) == [
+        ("error", "trailing_data")]
+    assert codes(reliquary_encryptor.validate_quorum_certificate(certificate[:-1])) == [
+        ("error", "malformed")]
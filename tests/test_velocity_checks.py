@@ -0,0 +1,53 @@
+# tests/test_velocity_checks.py
+
+
+import pytest
+
+velocity = pytest.importorskip("zk.velocity")
+trust_engine = pytest.importorskip("zk.trust_engine")
+
+# --- Velocity signals: impossible travel, IP/country hopping and IP reputation ---
+
+T0 = 1_700_000_000
+BERLIN = {"latitude": 52.52, "longitude": 13.405, "country": "DE"}
+NEW_YORK = {"latitude": 40.7128, "longitude": -74.006, "country": "US"}
+
+def observe(checker, subject, t, ip=None, place=BERLIN):
+    return checker.assess(subject, velocity.ContextObservation(timestamp=t, ip_address=ip, **place))
+
+def test_impossible_travel_and_country_velocity():
+    checker = velocity.VelocityChecker()
+    assert observe(checker, "k1", T0, "203.0.113.5") == []
+    signals = {s["signal"]: s for s in observe(checker, "k1", T0 + 1800, "198.51.100.7", NEW_YORK)}
+    assert set(signals) == {"impossible_travel", "country_velocity"}
+    assert signals["impossible_travel"]["distance_km"] == pytest.approx(6385, rel=0.01)
+    assert signals["country_velocity"]["countries"] == ["DE", "US"]
+
+    # A day later the same trip is plausible
+    assert observe(checker, "k2", T0) == []
+    assert observe(checker, "k2", T0 + 86400, place=NEW_YORK) == []
+
+def test_ip_velocity_and_reputation():
+    thresholds = velocity.VelocityThresholds(max_distinct_ips=2, ip_reputation_penalty=50.0)
+    checker = velocity.VelocityChecker(thresholds)
+    checker.reputation.set_network("192.0.2.0/24", 0.1, "abuse feed")
+    checker.reputation.set_network("192.0.2.128/25", 0.9, "corporate egress")
+
+    assert observe(checker, "k1", T0, "203.0.113.1") == []
+    assert observe(checker, "k1", T0 + 60, "192.0.2.200") == []
+    signals = {s["signal"]: s for s in observe(checker, "k1", T0 + 120, "192.0.2.10")}
+    assert signals["ip_velocity"]["distinct_ips"] == 3
+    assert (signals["ip_reputation"]["reason"], signals["ip_reputation"]["penalty"]) == ("abuse feed", 50.0)
+
+def test_signals_lower_trust_and_replay(tmp_path):
+    engine = trust_engine.TrustScoringEngine(tmp_path, velocity_checker=velocity.VelocityChecker(),
+                                             audit_log_path=tmp_path / "trust_scoring.log")
+    base = {"device_verified": True, "timestamp_verified": True, "location_verified": True,
+            "pattern_verified": True, "key_id": "k1"}
+    first = engine.evaluate_trust("erin", {**base, **BERLIN, "current_timestamp": T0})
+    second = engine.evaluate_trust("erin", {**base, **NEW_YORK, "current_timestamp": T0 + 600})
+
+    assert second.trust_metrics.risk_indicators == first.trust_metrics.risk_indicators - 60.0
+    rules = [hit["rule"] for hit in second.explanation.rule_hits]
+    assert "impossible_travel" in rules and "country_velocity" in rules
+    assert engine.replay_decision(second.decision_record)["reproduced"]
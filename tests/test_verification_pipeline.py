@@ -0,0 +1,56 @@
+# tests/test_verification_pipeline.py
+
+import hashlib
+
+import pytest
+
+reliquary_encryptor = pytest.importorskip("reliquary_encryptor")
+from core.merkle_logging import MerkleLogWriter, MerkleTree
+
+# --- Bulk verification with error aggregation and early abort ---
+
+def test_pipeline_reports_every_failure():
+    records = [f"record {i}".encode() for i in range(50)]
+    links = [bytes(32)] + [hashlib.sha256(r).digest() for r in records[:-1]]
+    links[20] = bytes(32)
+    pk, sk = reliquary_encryptor.generate_ed25519_keys()
+    signatures = [reliquary_encryptor.sign_ed25519(r, sk) for r in records]
+    signatures[3] = signatures[4]
+    tree = MerkleTree(records)
+    proofs = [[h for h, _ in tree.get_proof(i)] for i in range(len(records))]
+
+    pipeline = reliquary_encryptor.VerificationPipeline()
+    assert pipeline.add_hash_chain(records, links) == 0
+    assert pipeline.add_signatures(records, signatures, pk, scheme="ed25519",
+                                   label="entry signature") == 50
+    assert pipeline.add_merkle_proofs(records, proofs, list(range(50)), tree.root, 50) == 100
+    pipeline.add_merkle_proof(b"forged", proofs[7], 7, tree.root, 50)
+
+    report = pipeline.run()
+    assert (report.total, report.checked, report.passed) == (151, 151, 148)
+    assert not report.ok and not report.aborted
+    assert report.failures == [
+        (20, "chain", "Record does not link to record 19"),
+        (53, "entry signature", "ed25519 signature does not verify"),
+        (150, "merkle_proof", "Computed root does not match"),
+    ]
+
+    first = pipeline.run(continue_on_error=False)
+    assert first.failed_indices() == [20] and first.aborted and first.checked < 151
+    assert pipeline.run(max_failures=2).failed_indices() == [20, 53]
+
+
+def test_log_writer_bulk_proof_verification(tmp_path):
+    log = MerkleLogWriter(str(tmp_path / "audit.log"))
+    for i in range(9):
+        log.add_entry({"event": "seal", "n": i})
+    proofs = [log.get_entry_proof(i) for i in range(9)]
+    assert log.verify_entry_proofs(proofs)["failures"] == []
+
+    proofs[2] = dict(proofs[2], entry_index=3)
+    proofs[5] = dict(proofs[5], entry_index=42)
+    report = log.verify_entry_proofs(proofs)
+    assert [(f["index"], f["entry_index"], f["reason"]) for f in report["failures"]] == [
+        (2, 3, "Computed root does not match"), (5, 42, "Entry index is not in the log")]
+    assert report["passed"] == 7
+    assert log.verify_entry_proofs(proofs, continue_on_error=False)["aborted"]
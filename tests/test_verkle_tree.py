@@ -0,0 +1,86 @@
+# tests/test_verkle_tree.py
+
+import pytest
+
+reliquary_merkle = pytest.importorskip("reliquary_merkle")
+
+# --- VerkleTree: KZG vector commitments for short inclusion proofs ---
+
+BLOCKS = [f"entry {n}".encode() for n in range(300)]
+
+
+@pytest.fixture(scope="module")
+def setup():
+    return reliquary_merkle.KzgSetup.generate(16, secret=b"test setup")
+
+
+@pytest.mark.parametrize("size", [1, 16, 17, 300])
+def test_proofs_verify_at_every_depth(setup, size):
+    tree = reliquary_merkle.VerkleTree(setup, BLOCKS[:size])
+    root = tree.root()
+    assert len(tree) == size and len(root) == 48
+    for index in {0, size // 2, size - 1}:
+        proof = tree.get_proof(index)
+        # 15 header bytes, then a commitment and an opening per level below the root
+        assert len(proof) == 15 + 96 * tree.depth - 48
+        assert reliquary_merkle.verify_verkle_proof(BLOCKS[index], proof, root, setup)
+        assert not reliquary_merkle.verify_verkle_proof(b"forged", proof, root, setup)
+    assert tree.depth == {1: 1, 16: 1, 17: 2, 300: 3}[size]
+
+
+def test_appends_match_a_fresh_build(setup):
+    tree = reliquary_merkle.VerkleTree(setup)
+    assert tree.root() == b"" and tree.depth == 0
+    assert tree.append(BLOCKS[0]) == 0
+    tree.extend(BLOCKS[1:40])
+    for block in BLOCKS[40:70]:
+        tree.append(block)
+    assert tree.root() == reliquary_merkle.VerkleTree(setup, BLOCKS[:70]).root()
+    with pytest.raises(IndexError):
+        tree.get_proof(70)
+
+
+def test_proofs_bind_index_root_and_setup(setup):
+    tree = reliquary_merkle.VerkleTree(setup, BLOCKS[:40])
+    root, proof = tree.root(), tree.get_proof(21)
+    verify = reliquary_merkle.verify_verkle_proof
+    # The index is part of the proof: moving it breaks the openings
+    moved = proof[:6] + (22).to_bytes(8, "big") + proof[14:]
+    assert not verify(BLOCKS[21], moved, root, setup)
+    assert not verify(BLOCKS[21], proof, reliquary_merkle.VerkleTree(setup, BLOCKS[:41]).root(),
+                      setup)
+    other = reliquary_merkle.KzgSetup.generate(16, secret=b"another setup")
+    assert not verify(BLOCKS[21], proof, root, other)
+    assert not verify(BLOCKS[21], proof, root, reliquary_merkle.KzgSetup.generate(32))
+    tampered = bytearray(proof)
+    tampered[-1] ^= 1
+    for bad in (proof[:-1], proof + b"\x00", bytes(tampered), b"RQVP", b""):
+        assert not verify(BLOCKS[21], bad, root, setup)
+    assert not verify(BLOCKS[21], proof, b"\x00" * 48, setup)
+
+
+def test_setup_round_trip_and_checks(setup):
+    data = setup.to_bytes()
+    assert len(data) == 6 + 16 * 48 + 96 and setup.width == 16
+    loaded = reliquary_merkle.KzgSetup.from_bytes(data)
+    assert loaded.to_bytes() == data
+    # A setup carries no tau: proofs made under one verify under its reloaded copy
+    tree = reliquary_merkle.VerkleTree(setup, BLOCKS[:5])
+    assert reliquary_merkle.verify_verkle_proof(BLOCKS[3], tree.get_proof(3), tree.root(),
+                                                loaded)
+    assert reliquary_merkle.KzgSetup.generate(16, secret=b"test setup").to_bytes() == data
+    assert reliquary_merkle.KzgSetup.generate(16).to_bytes() != data
+
+    cases = {
+        data[:-1]: "truncated",
+        data + b"\x00": "Trailing data",
+        b"RQKX" + data[4:]: "Not a serialized KZG setup",
+        data[:5] + b"\x0d" + data[6:]: "width",
+        data[:6] + b"\x00" * 48 + data[54:]: "invalid G1 point",
+    }
+    for bad, message in cases.items():
+        with pytest.raises(ValueError, match=message):
+            reliquary_merkle.KzgSetup.from_bytes(bad)
+    for width in (1, 24, 8192):
+        with pytest.raises(ValueError, match="power of two"):
+            reliquary_merkle.KzgSetup.generate(width)
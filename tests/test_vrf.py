@@ -0,0 +1,35 @@
+# tests/test_vrf.py
+
+import pytest
+
+reliquary_encryptor = pytest.importorskip("reliquary_encryptor")
+
+# --- RFC 9381 ECVRF-EDWARDS25519-SHA512-TAI test vectors (Examples 16 and 17) ---
+VECTORS = [
+    {
+        "sk": "9d61b19deffd5a60ba844af492ec2cc44449c5697b326919703bac031cae7f60",
+        "pk": "d75a980182b10ab7d54bfed3c964073a0ee172f3daa62325af021a68f707511a",
+        "alpha": "",
+        "pi": "8657106690b5526245a92b003bb079ccd1a92130477671f6fc01ad16f26f723f26f8a57ccaed74ee1b190bed1f479d9727d2d0f9b005a6e456a35d4fb0daab1268a1b0db10836d9826a528ca76567805",
+        "beta": "90cf1df3b703cce59e2a35b925d411164068269d7b2d29f3301c03dd757876ff66b71dda49d2de59d03450451af026798e8f81cd2e333de5cdf4f3e140fdd8ae",
+    },
+    {
+        "sk": "4ccd089b28ff96da9db6c346ec114e0f5b8a319f35aba624da8cf6ed4fb8a6fb",
+        "pk": "3d4017c3e843895a92b70aa74d1b7ebc9c982ccf2ec4968cc0cd55f12af4660c",
+        "alpha": "72",
+        "pi": "f3141cd382dc42909d19ec5110469e4feae18300e94f304590abdced48aed5933bf0864a62558b3ed7f2fea45c92a465301b3bbf5e3e54ddf2d935be3b67926da3ef39226bbc355bdc9850112c8f4b02",
+        "beta": "eb4440665d3891d668e7e0fcaf587f1b4bd7fbfe99d0eb2211ccec90496310eb5e33821bc613efb94db5e5b54c70a848a0bef4553a41befc57663b56373a5031",
+    },
+]
+
+@pytest.mark.parametrize("vector", VECTORS)
+def test_rfc9381_vectors(vector):
+    v = {key: bytes.fromhex(value) for key, value in vector.items()}
+    proof, output = reliquary_encryptor.vrf_prove(v["sk"], v["alpha"])
+    assert bytes(proof) == v["pi"]
+    assert bytes(output) == v["beta"]
+    assert bytes(reliquary_encryptor.vrf_verify(v["pk"], v["alpha"], proof)) == v["beta"]
+
+def test_proof_rejected_for_other_input():
+    v = {key: bytes.fromhex(value) for key, value in VECTORS[0].items()}
+    assert reliquary_encryptor.vrf_verify(v["pk"], b"other", v["pi"]) is None
@@ -0,0 +1,84 @@
+# tests/test_vss.py
+
+import dataclasses
+
+import pytest
+
+reliquary_encryptor = pytest.importorskip("reliquary_encryptor")
+from core.crypto.key_sharding import (apply_refresh_to_dealing, apply_refresh_to_shard,
+                                      find_invalid_shards, generate_refresh,
+                                      reconstruct_key_verifiable, split_key_verifiable,
+                                      verify_shard)
+
+# --- Verifiable secret sharing: shares checked against public commitments ---
+
+def _corrupt(shard):
+    data = bytearray.fromhex(shard.data)
+    data[-1] ^= 0x01
+    return dataclasses.replace(shard, data=data.hex())
+
+
+@pytest.mark.parametrize("scheme", ["feldman", "pedersen"])
+def test_split_verify_and_reconstruct(scheme):
+    secret = b"\x42" * 32
+    dealing, shards = split_key_verifiable(secret, num_shares=5, threshold=3, scheme=scheme)
+    assert reliquary_encryptor.vss_dealing_info(dealing) == (scheme, 3, 5, 0)
+    assert all(verify_shard(dealing, shard) for shard in shards)
+    assert reconstruct_key_verifiable(dealing, shards[2:]) == secret
+    assert reconstruct_key_verifiable(dealing, [shards[4], shards[0], shards[3]]) == secret
+
+
+def test_corrupted_shares_are_identified():
+    secret = b"vault master key"
+    dealing, shards = split_key_verifiable(secret, num_shares=5, threshold=3)
+    bad = _corrupt(shards[1])
+    assert not verify_shard(dealing, bad)
+    assert find_invalid_shards(dealing, [shards[0], bad, shards[2]]) == [bad]
+
+    # A bad share is skipped as long as enough good ones remain
+    assert reconstruct_key_verifiable(dealing, [bad, shards[0], shards[2], shards[4]]) == secret
+    with pytest.raises(ValueError, match="shard_2"):
+        reconstruct_key_verifiable(dealing, [bad, shards[0], shards[2]])
+
+    # Shares from another dealing of the same secret don't verify against this one
+    other_dealing, other_shards = split_key_verifiable(secret, num_shares=5, threshold=3)
+    assert not verify_shard(dealing, other_shards[0])
+    assert reliquary_encryptor.vss_check_shares(
+        other_dealing, [bytes.fromhex(s.data) for s in shards[:2]] + [b"junk"]) == [0, 1, 2]
+
+
+@pytest.mark.parametrize("scheme", ["feldman", "pedersen"])
+def test_proactive_refresh(scheme):
+    secret = b"long-lived vault key"
+    dealing, shards = split_key_verifiable(secret, num_shares=5, threshold=3, scheme=scheme)
+    rounds = [generate_refresh(dealing) for _ in range(2)]
+    refreshes = [refresh for refresh, _ in rounds]
+    assert all(reliquary_encryptor.vss_verify_delta(refresh, deltas[i])
+               for refresh, deltas in rounds for i in range(5))
+
+    new_dealing = apply_refresh_to_dealing(dealing, refreshes)
+    new_shards = [apply_refresh_to_shard(dealing, shard, refreshes,
+                                         [deltas[i] for _, deltas in rounds])
+                  for i, shard in enumerate(shards)]
+    assert reliquary_encryptor.vss_dealing_info(new_dealing)[3] == 1
+    assert [s.data for s in new_shards] != [s.data for s in shards]
+    assert all(verify_shard(new_dealing, shard) for shard in new_shards)
+    assert reconstruct_key_verifiable(new_dealing, new_shards[1:4]) == secret
+
+    # Old shards neither verify nor mix with refreshed ones
+    assert not any(verify_shard(new_dealing, shard) for shard in shards)
+    with pytest.raises(ValueError):
+        reconstruct_key_verifiable(new_dealing, shards[:2] + new_shards[2:3])
+
+    # A tampered delta or a delta meant for another trustee is rejected
+    refresh, deltas = rounds[0]
+    bad = bytearray(deltas[0])
+    bad[-1] ^= 0x01
+    assert not reliquary_encryptor.vss_verify_delta(refresh, bytes(bad))
+    with pytest.raises(ValueError, match="does not verify"):
+        apply_refresh_to_shard(dealing, shards[0], [refresh], [bytes(bad)])
+    with pytest.raises(ValueError, match="No delta for share 1"):
+        apply_refresh_to_shard(dealing, shards[0], [refresh], [deltas[1]])
+    # Refreshes are tied to the epoch they were generated for
+    with pytest.raises(ValueError, match="epoch 0"):
+        apply_refresh_to_dealing(new_dealing, [refresh])
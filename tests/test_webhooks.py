@@ -0,0 +1,53 @@
+# tests/test_webhooks.py
+
+import pytest
+
+from observability.webhooks import (SIGNATURE_HEADER, WebhookSignatureError, WebhookSigner,
+                                    WebhookVerifier, parse_signature_header)
+
+# --- Stripe-style webhook signatures: HMAC and Falcon, key rotation by key id ---
+
+BODY = b'{"event":"vault.unsealed","vault_id":"v1"}'
+NOW = 1_760_000_000
+
+def test_hmac_rotation_and_tolerance():
+    old, new = b"o" * 32, b"n" * 32
+    signer = WebhookSigner()
+    signer.add_hmac_key("k1", old)
+    signer.add_hmac_key("k2", new)
+    header = signer.headers(BODY, timestamp=NOW)[SIGNATURE_HEADER]
+    timestamp, signatures = parse_signature_header(header)
+    assert timestamp == NOW and [(s, k) for s, k, _ in signatures] == [("v1", "k1"), ("v1", "k2")]
+
+    # Receivers on either key accept the body while both are active
+    assert WebhookVerifier({"k1": old}, clock=lambda: NOW).verify(BODY, header) == "k1"
+    assert WebhookVerifier({"k2": new}, clock=lambda: NOW + 60).verify(BODY, header) == "k2"
+
+    signer.remove_key("k1")
+    header = signer.sign(BODY, timestamp=NOW)
+    with pytest.raises(WebhookSignatureError, match="trusted key"):
+        WebhookVerifier({"k1": old}, clock=lambda: NOW).verify(BODY, header)
+
+    verifier = WebhookVerifier({"k2": new}, clock=lambda: NOW + 301)
+    with pytest.raises(WebhookSignatureError, match="tolerance"):
+        verifier.verify(BODY, header)
+    verifier.clock = lambda: NOW
+    with pytest.raises(WebhookSignatureError, match="trusted key"):
+        verifier.verify(BODY + b" ", header)
+    with pytest.raises(WebhookSignatureError, match="Missing"):
+        verifier.verify(BODY, None)
+
+def test_falcon_signatures():
+    reliquary_encryptor = pytest.importorskip("reliquary_encryptor")
+    pk, sk = reliquary_encryptor.generate_falcon_keys()
+    signer = WebhookSigner()
+    signer.add_falcon_key("pq1", sk)
+    signer.add_hmac_key("k1", b"s" * 32)
+    header = signer.sign(BODY, timestamp=NOW)
+
+    verifier = WebhookVerifier({"k1": b"s" * 32}, {"pq1": pk}, require_falcon=True,
+                               clock=lambda: NOW)
+    assert verifier.verify(BODY, header) == "pq1"
+    tampered = header.replace(f"t={NOW}", f"t={NOW + 1}")
+    with pytest.raises(WebhookSignatureError):
+        WebhookVerifier(falcon_public_keys={"pq1": pk}, clock=lambda: NOW).verify(BODY, tampered)
@@ -0,0 +1,95 @@
+# tests/test_zk_context_proofs.py
+
+import pytest
+
+reliquary_encryptor = pytest.importorskip("reliquary_encryptor")
+
+# --- Groth16 context proofs: enrolled device and timestamp window ---
+
+DEVICES = [(f"device-{n}".encode(), f"salt-{n}".encode()) for n in range(5)]
+NONCE = b"challenge-7f3a"
+
+
+@pytest.fixture(scope="module")
+def device_keys():
+    return reliquary_encryptor.zk_setup("device_enrolled", capacity=6)
+
+
+@pytest.fixture(scope="module")
+def window_keys():
+    return reliquary_encryptor.zk_setup("timestamp_window")
+
+
+def enrolled_set():
+    return [reliquary_encryptor.zk_device_commitment(*device) for device in DEVICES]
+
+
+def test_device_enrolled(device_keys):
+    pk, vk = device_keys
+    enrolled = enrolled_set()
+    proof = reliquary_encryptor.zk_prove_device_enrolled(pk, *DEVICES[3], enrolled, NONCE)
+    assert proof[:4] == b"RQZP" and len(proof) == 136
+    assert reliquary_encryptor.zk_verify_device_enrolled(vk, proof, enrolled, NONCE)
+    # Bound to the nonce and to the exact set
+    assert not reliquary_encryptor.zk_verify_device_enrolled(vk, proof, enrolled, b"other")
+    assert not reliquary_encryptor.zk_verify_device_enrolled(vk, proof, enrolled[:4], NONCE)
+    assert not reliquary_encryptor.zk_verify_device_enrolled(
+        vk, proof[:-1] + bytes([proof[-1] ^ 1]), enrolled, NONCE)
+
+    # The verifier never needs the fingerprint, and a set of one still works
+    solo = reliquary_encryptor.zk_prove_device_enrolled(pk, *DEVICES[0], enrolled[:1], NONCE)
+    assert reliquary_encryptor.zk_verify_device_enrolled(vk, solo, enrolled[:1], NONCE)
+
+
+def test_device_enrolled_rejections(device_keys):
+    pk, vk = device_keys
+    enrolled = enrolled_set()
+    with pytest.raises(ValueError, match="not in the enrolled set"):
+        reliquary_encryptor.zk_prove_device_enrolled(pk, DEVICES[0][0], b"wrong salt",
+                                                     enrolled, NONCE)
+    with pytest.raises(ValueError, match="1 to 6 devices"):
+        reliquary_encryptor.zk_verify_device_enrolled(vk, b"", enrolled * 2, NONCE)
+    with pytest.raises(ValueError, match="32 bytes"):
+        reliquary_encryptor.zk_verify_device_enrolled(vk, b"", [b"short"], NONCE)
+    # Keys are tagged with their kind and circuit
+    with pytest.raises(ValueError, match="Not a zk verifying key"):
+        reliquary_encryptor.zk_verify_device_enrolled(pk, b"", enrolled, NONCE)
+    with pytest.raises(ValueError, match="capacity"):
+        reliquary_encryptor.zk_setup("device_enrolled", capacity=0)
+    with pytest.raises(ValueError, match="Unknown zk circuit"):
+        reliquary_encryptor.zk_setup("age_over")
+
+
+def test_timestamp_window(window_keys, device_keys):
+    pk, vk = window_keys
+    timestamp, blinding = 1_760_000_123_456, b"blinding from the attesting device"
+    commitment = reliquary_encryptor.zk_timestamp_commitment(timestamp, blinding)
+    assert len(commitment) == 32
+    window = (timestamp - 60_000, timestamp + 60_000)
+    proof = reliquary_encryptor.zk_prove_timestamp_window(pk, timestamp, blinding, *window,
+                                                          NONCE)
+    assert reliquary_encryptor.zk_verify_timestamp_window(vk, proof, commitment, *window, NONCE)
+    assert not reliquary_encryptor.zk_verify_timestamp_window(vk, proof, commitment,
+                                                              timestamp, timestamp, NONCE)
+    assert not reliquary_encryptor.zk_verify_timestamp_window(vk, proof, commitment, *window,
+                                                              b"other")
+    other = reliquary_encryptor.zk_timestamp_commitment(timestamp, b"other blinding")
+    assert not reliquary_encryptor.zk_verify_timestamp_window(vk, proof, other, *window, NONCE)
+
+    # The window's ends are inclusive
+    edge = reliquary_encryptor.zk_prove_timestamp_window(pk, timestamp, blinding, timestamp,
+                                                         timestamp, NONCE)
+    assert reliquary_encryptor.zk_verify_timestamp_window(vk, edge, commitment, timestamp,
+                                                          timestamp, NONCE)
+    with pytest.raises(ValueError, match="outside the window"):
+        reliquary_encryptor.zk_prove_timestamp_window(pk, timestamp, blinding, timestamp + 1,
+                                                      timestamp + 2, NONCE)
+    with pytest.raises(ValueError, match="not_before is after not_after"):
+        reliquary_encryptor.zk_verify_timestamp_window(vk, proof, commitment, 2, 1, NONCE)
+
+    # A proof or key for one circuit says nothing about another
+    with pytest.raises(ValueError, match="timestamp_window circuit, not device_enrolled"):
+        reliquary_encryptor.zk_verify_device_enrolled(vk, proof, enrolled_set(), NONCE)
+    with pytest.raises(ValueError, match="device_enrolled circuit, not timestamp_window"):
+        reliquary_encryptor.zk_verify_timestamp_window(device_keys[1], proof, commitment,
+                                                       *window, NONCE)
@@ -0,0 +1,82 @@
+# tests/test_zk_merkle_membership.py
+
+import pytest
+
+reliquary_encryptor = pytest.importorskip("reliquary_encryptor")
+
+# --- Groth16 set membership against a Merkle root, with an attribute threshold ---
+
+DEPTH = 4
+NONCE = b"challenge-91c2"
+# (secret, clearance level) per member of the authorization tree
+MEMBERS = [(f"member-secret-{n}".encode(), level) for n, level in enumerate([1, 3, 2, 5, 4])]
+
+
+@pytest.fixture(scope="module")
+def keys():
+    return reliquary_encryptor.zk_setup("merkle_membership", capacity=DEPTH)
+
+
+def tree_leaves():
+    return [reliquary_encryptor.zk_member_leaf(*member) for member in MEMBERS]
+
+
+def test_membership(keys):
+    pk, vk = keys
+    leaves = tree_leaves()
+    root = reliquary_encryptor.zk_merkle_root(leaves, DEPTH)
+    assert len(root) == 32
+
+    secret, level = MEMBERS[3]
+    path = reliquary_encryptor.zk_merkle_path(leaves, 3, DEPTH)
+    assert len(path) == DEPTH
+    proof = reliquary_encryptor.zk_prove_merkle_membership(pk, secret, level, 3, path, 4, NONCE)
+    assert proof[:4] == b"RQZP" and len(proof) == 136
+    assert reliquary_encryptor.zk_verify_merkle_membership(vk, proof, root, 4, NONCE)
+    # Bound to the threshold, the root and the nonce
+    assert not reliquary_encryptor.zk_verify_merkle_membership(vk, proof, root, 5, NONCE)
+    assert not reliquary_encryptor.zk_verify_merkle_membership(vk, proof, root, 4, b"other")
+    other_root = reliquary_encryptor.zk_merkle_root(leaves[:4], DEPTH)
+    assert not reliquary_encryptor.zk_verify_merkle_membership(vk, proof, other_root, 4, NONCE)
+
+    # Plain membership is a threshold of 0; a wrong secret or index leads to another root
+    first_secret, first_level = MEMBERS[0]
+    first_path = reliquary_encryptor.zk_merkle_path(leaves, 0, DEPTH)
+    first = reliquary_encryptor.zk_prove_merkle_membership(pk, first_secret, first_level, 0,
+                                                           first_path, 0, NONCE)
+    assert reliquary_encryptor.zk_verify_merkle_membership(vk, first, root, 0, NONCE)
+    forged = reliquary_encryptor.zk_prove_merkle_membership(pk, b"not a member", 5, 3, path, 4,
+                                                            NONCE)
+    assert not reliquary_encryptor.zk_verify_merkle_membership(vk, forged, root, 4, NONCE)
+    moved = reliquary_encryptor.zk_prove_merkle_membership(pk, secret, level, 2, path, 4, NONCE)
+    assert not reliquary_encryptor.zk_verify_merkle_membership(vk, moved, root, 4, NONCE)
+    # Claiming a higher level than the leaf holds
+    inflated = reliquary_encryptor.zk_prove_merkle_membership(pk, first_secret, 5, 0, first_path,
+                                                              4, NONCE)
+    assert not reliquary_encryptor.zk_verify_merkle_membership(vk, inflated, root, 4, NONCE)
+
+
+def test_membership_rejections(keys):
+    pk, vk = keys
+    leaves = tree_leaves()
+    path = reliquary_encryptor.zk_merkle_path(leaves, 1, DEPTH)
+    with pytest.raises(ValueError, match="below the minimum"):
+        reliquary_encryptor.zk_prove_merkle_membership(pk, MEMBERS[1][0], 3, 1, path, 4, NONCE)
+    with pytest.raises(ValueError, match="4 siblings"):
+        reliquary_encryptor.zk_prove_merkle_membership(pk, MEMBERS[1][0], 3, 1, path[:3], 0,
+                                                       NONCE)
+    with pytest.raises(ValueError, match="outside the tree"):
+        reliquary_encryptor.zk_prove_merkle_membership(pk, MEMBERS[1][0], 3, 16, path, 0, NONCE)
+    with pytest.raises(ValueError, match="at most 16 leaves"):
+        reliquary_encryptor.zk_merkle_root(leaves * 4, DEPTH)
+    with pytest.raises(ValueError, match="No leaf at index 5"):
+        reliquary_encryptor.zk_merkle_path(leaves, 5, DEPTH)
+    with pytest.raises(ValueError, match="depth must be"):
+        reliquary_encryptor.zk_setup("merkle_membership", capacity=33)
+    with pytest.raises(ValueError, match="merkle_membership circuit, not device_enrolled"):
+        reliquary_encryptor.zk_verify_device_enrolled(vk, b"", [leaves[0]], NONCE)
+
+    # An empty tree has a root too, and the default depth is 20
+    assert reliquary_encryptor.zk_merkle_root([], DEPTH) != reliquary_encryptor.zk_merkle_root(
+        leaves, DEPTH)
+    assert len(reliquary_encryptor.zk_merkle_path(leaves, 0)) == 20
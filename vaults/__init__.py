@@ -9,6 +9,11 @@ including storage backends, data models, and trust management.
 from .vault import Vault
 from .manager import VaultManager
 from .storage.base import StorageBackend
+from .tpm_keystore import TpmKeyStore
+from .state_export import StateExportError, export_state, import_state, verify_state
+from .forensics import EvidenceRecord, ForensicCollector
+from .transactions import (AuditLogParticipant, ContainerParticipant, KeyCounterStore,
+                           TransactionCoordinator, TransactionError)
 
 # Version information
 __version__ = "1.0.0"
@@ -19,7 +24,19 @@ __email__ = "support@reliquary.io"
 __all__ = [
     "Vault",
     "VaultManager", 
-    "StorageBackend"
+    "StorageBackend",
+    "TpmKeyStore",
+    "TransactionCoordinator",
+    "TransactionError",
+    "ContainerParticipant",
+    "AuditLogParticipant",
+    "KeyCounterStore",
+    "export_state",
+    "import_state",
+    "verify_state",
+    "StateExportError",
+    "ForensicCollector",
+    "EvidenceRecord"
 ]
 
 # Package description
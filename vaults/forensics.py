@@ -0,0 +1,162 @@
+"""
+Forensic extraction for incident response.
+
+A ForensicCollector switches the process into read-only mode (locked by default, so nothing
+can switch it back) and then reads keystores, audit logs and containers. Every byte it reads
+is hashed with SHA-256 and SHA3-256 and recorded, in order, as chain-of-custody evidence;
+`report()` returns the records with a digest over all of them.
+
+Audit logs are checked against their .merkle root files, and containers against the envelope
+validators, but problems are recorded rather than raised: evidence of tampering is what the
+responder is looking for.
+"""
+
+import hashlib
+import json
+import logging
+import os
+import threading
+import time
+from dataclasses import asdict, dataclass, field
+from typing import Any, Dict, List, Tuple
+
+from core.read_only import enable_read_only_mode, is_read_only, is_read_only_locked
+from .state_export import MERKLE_SUFFIX, StateExportError, audit_checkpoint
+
+try:
+    import reliquary_encryptor
+except ImportError:
+    reliquary_encryptor = None
+
+
+@dataclass
+class EvidenceRecord:
+    """One read, with the digests of exactly the bytes that were read"""
+    kind: str  # "file", "container" or "audit_log"
+    source: str
+    size: int
+    sha256: str
+    sha3_256: str
+    read_at: float
+    details: Dict[str, Any] = field(default_factory=dict)
+
+
+class ForensicCollector:
+    """Reads system state in read-only mode and hashes everything it reads"""
+
+    def __init__(self, case_id: str = "", lock: bool = True):
+        """
+        Initialize the collector and turn on read-only mode.
+
+        Args:
+            case_id: Incident or case reference for the report
+            lock: Lock read-only mode on for the rest of the process
+        """
+        enable_read_only_mode(lock=lock)
+        self.case_id = case_id
+        self.records: List[EvidenceRecord] = []
+        self.logger = logging.getLogger(__name__)
+        self._lock = threading.Lock()
+
+    def _record(self, kind: str, source: str, data: bytes, **details: Any) -> EvidenceRecord:
+        record = EvidenceRecord(
+            kind=kind,
+            source=source,
+            size=len(data),
+            sha256=hashlib.sha256(data).hexdigest(),
+            sha3_256=hashlib.sha3_256(data).hexdigest(),
+            read_at=time.time(),
+            details=details
+        )
+        with self._lock:
+            self.records.append(record)
+        return record
+
+    def read_file(self, path: str) -> bytes:
+        """Read and record one file."""
+        with open(path, "rb") as f:
+            data = f.read()
+        self._record("file", os.path.abspath(path), data)
+        return data
+
+    def read_directory(self, directory: str) -> Dict[str, bytes]:
+        """
+        Read and record every file under a directory, e.g. a keystore.
+
+        Returns:
+            Contents by path relative to `directory`
+        """
+        files = {}
+        for root, dirs, names in os.walk(directory):
+            dirs.sort()
+            for name in sorted(names):
+                path = os.path.join(root, name)
+                files[os.path.relpath(path, directory)] = self.read_file(path)
+        return files
+
+    def read_container(self, storage: Any, container_id: str) -> bytes:
+        """Load and record one container from a storage backend (anything with load_vault)."""
+        data = storage.load_vault(container_id)
+        details: Dict[str, Any] = {"backend": type(storage).__name__}
+        if reliquary_encryptor is not None:
+            details["envelope_findings"] = [
+                list(finding) for finding in reliquary_encryptor.validate_envelope(data)]
+        self._record("container", container_id, data, **details)
+        return data
+
+    def read_audit_log(self, path: str) -> Tuple[List[Dict[str, Any]], Dict[str, Any]]:
+        """
+        Read and record a MerkleLogWriter log and its .merkle root file.
+
+        Returns:
+            (entries, details) where details holds the recomputed entry count and Merkle root,
+            whether they match the root file (None without one) and any parse error
+        """
+        with open(path, "rb") as f:
+            data = f.read()
+        entries, details = [], {}
+        for line in data.splitlines():
+            try:
+                if line.strip():
+                    entries.append(json.loads(line))
+            except ValueError:
+                details.setdefault("unparsable_lines", 0)
+                details["unparsable_lines"] += 1
+        try:
+            details.update(audit_checkpoint(data))
+        except StateExportError as e:
+            details["error"] = str(e)
+
+        root_file_path = path + MERKLE_SUFFIX
+        details["root_file_matches"] = None
+        if os.path.exists(root_file_path) and "error" not in details:
+            try:
+                stored = json.loads(self.read_file(root_file_path))
+                details["root_file_matches"] = (
+                    stored.get("root") == details["merkle_root"]
+                    and stored.get("entry_count") == details["entry_count"])
+            except ValueError:
+                details["root_file_matches"] = False
+        if details["root_file_matches"] is False:
+            self.logger.warning(f"Audit log {path} doesn't match its Merkle root file")
+        self._record("audit_log", os.path.abspath(path), data, **details)
+        return entries, details
+
+    def report(self) -> Dict[str, Any]:
+        """
+        Chain-of-custody report of everything read so far.
+
+        Returns:
+            {"case_id", "read_only", "read_only_locked", "generated_at", "records", "digest"}
+            where digest is the SHA-256 of the records as canonical JSON
+        """
+        with self._lock:
+            records = [asdict(record) for record in self.records]
+        return {
+            "case_id": self.case_id,
+            "read_only": is_read_only(),
+            "read_only_locked": is_read_only_locked(),
+            "generated_at": time.time(),
+            "records": records,
+            "digest": hashlib.sha256(json.dumps(records, sort_keys=True).encode()).hexdigest(),
+        }
@@ -0,0 +1,369 @@
+"""
+Signed, encrypted snapshot of the full system state for disaster recovery.
+
+An archive holds three sections, each a set of files with paths relative to the section:
+
+- keystore: every file under the keystore directory (sealed master key, key backups, counters)
+- audit: Merkle audit logs and their .merkle root files, with a checkpoint of each log
+- policies: signed policy bundles (<name>.json next to <name>.json.sig)
+
+The file contents are concatenated in manifest order and sealed to a recovery Kyber public key
+with encrypt_to_public_key. The manifest lists every file with its size and SHA-256, the Merkle
+root over those file records (core.merkle_logging), the audit checkpoints (entry count and
+Merkle root of each log) and the SHA-256 of the sealed payload, and is signed with Falcon::
+
+    {"manifest": "<manifest JSON>", "signature": "<base64>", "payload": "<base64 envelope>"}
+
+`import_state` verifies the signature, the payload digest, every file digest, the Merkle root,
+the audit checkpoints against the logs themselves and (given a PolicyManager) every policy
+signature before it writes anything; files are then replaced atomically.
+"""
+
+import base64
+import hashlib
+import json
+import logging
+import os
+import time
+from typing import Any, Dict, List, Optional, Sequence, Tuple
+
+from core.merkle_logging import MerkleLogEntry, MerkleTree
+from core.merkle_logging.merkle import create_merkle_root
+from core.read_only import ensure_writable
+from .transactions import write_atomic
+
+try:
+    import reliquary_encryptor
+except ImportError:
+    reliquary_encryptor = None
+
+FORMAT = "reliquary-state"
+FORMAT_VERSION = 1
+SECTIONS = ("keystore", "audit", "policies")
+MERKLE_SUFFIX = ".merkle"
+SIGNATURE_SUFFIX = ".sig"
+
+logger = logging.getLogger(__name__)
+
+
+class StateExportError(Exception):
+    """Raised when a state archive can't be created, verified or applied"""
+
+
+def _with_params(args: tuple, params: Any) -> tuple:
+    return args if params is None else args + (params,)
+
+
+def _require_encryptor():
+    if reliquary_encryptor is None:
+        raise StateExportError("reliquary_encryptor is required for state archives")
+
+
+def _collect(directory: str) -> List[Tuple[str, bytes]]:
+    """All files under `directory` as (relative path with '/' separators, contents)."""
+    files = []
+    for root, dirs, names in os.walk(directory):
+        dirs.sort()
+        for name in sorted(names):
+            if name.endswith(".tmp"):
+                continue  # Interrupted write_atomic leftovers
+            full_path = os.path.join(root, name)
+            with open(full_path, "rb") as f:
+                files.append((os.path.relpath(full_path, directory).replace(os.sep, "/"), f.read()))
+    return files
+
+
+def _record(section: str, path: str, data: bytes) -> Dict[str, Any]:
+    return {"section": section, "path": path, "size": len(data),
+            "sha256": hashlib.sha256(data).hexdigest()}
+
+
+def _merkle_root(records: List[Dict[str, Any]]) -> str:
+    return create_merkle_root([json.dumps(r, sort_keys=True).encode() for r in records]).hex()
+
+
+def _safe_path(path: Any) -> bool:
+    if not isinstance(path, str) or not path or "\\" in path or path.startswith("/"):
+        return False
+    return all(part not in ("", ".", "..") for part in path.split("/"))
+
+
+def audit_checkpoint(log_data: bytes) -> Dict[str, Any]:
+    """
+    Entry count and Merkle root of a MerkleLogWriter log, computed from its contents.
+
+    Raises:
+        StateExportError: If a line isn't a JSON log entry
+    """
+    entries = []
+    for line in log_data.splitlines():
+        line = line.strip()
+        if not line:
+            continue
+        try:
+            data = json.loads(line.decode("utf-8"))
+        except (ValueError, UnicodeDecodeError) as e:
+            raise StateExportError(f"Audit log entry {len(entries)} is not valid JSON: {e}")
+        entries.append(MerkleLogEntry(data, data.get("timestamp")).to_bytes())
+    root = MerkleTree(entries).root if entries else b""
+    return {"entry_count": len(entries), "merkle_root": root.hex() or None}
+
+
+def _check_root_file(name: str, checkpoint: Dict[str, Any], root_file: Optional[bytes]):
+    """A log's .merkle file, if present, must agree with the log itself."""
+    if root_file is None:
+        return
+    try:
+        stored = json.loads(root_file)
+    except ValueError:
+        raise StateExportError(f"Merkle root file of audit log {name} is not valid JSON")
+    if (stored.get("root") != checkpoint["merkle_root"]
+            or stored.get("entry_count") != checkpoint["entry_count"]):
+        raise StateExportError(f"Audit log {name} doesn't match its Merkle root file")
+
+
+def export_state(archive_path: str, recovery_public_key: bytes, signing_key: Any,
+                 keystore_dir: Optional[str] = None, audit_logs: Sequence[str] = (),
+                 policy_dir: Optional[str] = None, kyber_params: Any = None,
+                 falcon_params: Any = None, audit_logger: Any = None) -> Dict[str, Any]:
+    """
+    Write an encrypted, signed archive of the keystore, audit logs and policy bundles.
+
+    Args:
+        archive_path: Where to write the archive (replaced atomically)
+        recovery_public_key: Kyber public key the archive is sealed to
+        signing_key: Falcon secret key (or SecretKeyHandle) that signs the manifest
+        keystore_dir: Directory whose files make up the keystore section
+        audit_logs: MerkleLogWriter log files; each log's .merkle file is included too
+        policy_dir: Directory of signed policy bundles
+        kyber_params: KyberParams of the recovery key (Kyber-1024 by default)
+        falcon_params: FalconParams of the signing key (Falcon-1024 by default)
+        audit_logger: Sink with add_entry(dict) (e.g. MerkleLogWriter) for the export event
+
+    Returns:
+        The signed manifest
+
+    Raises:
+        StateExportError: If an audit log doesn't match its Merkle root file
+    """
+    _require_encryptor()
+    files: List[Tuple[str, str, bytes]] = []
+    if keystore_dir is not None:
+        files.extend(("keystore", path, data) for path, data in _collect(keystore_dir))
+
+    checkpoints = []
+    for log_path in audit_logs:
+        name = os.path.basename(log_path)
+        if any(c["path"] == name for c in checkpoints):
+            raise StateExportError(f"Two audit logs are named {name}")
+        with open(log_path, "rb") as f:
+            log_data = f.read()
+        root_file = None
+        if os.path.exists(log_path + MERKLE_SUFFIX):
+            with open(log_path + MERKLE_SUFFIX, "rb") as f:
+                root_file = f.read()
+        checkpoint = audit_checkpoint(log_data)
+        _check_root_file(name, checkpoint, root_file)
+        checkpoints.append(dict(checkpoint, path=name))
+        files.append(("audit", name, log_data))
+        if root_file is not None:
+            files.append(("audit", name + MERKLE_SUFFIX, root_file))
+
+    if policy_dir is not None:
+        files.extend(("policies", path, data) for path, data in _collect(policy_dir))
+
+    records = [_record(section, path, data) for section, path, data in files]
+    payload = b"".join(data for _, _, data in files)
+    envelope = reliquary_encryptor.encrypt_to_public_key(
+        *_with_params((payload, recovery_public_key), kyber_params))
+    manifest = {
+        "format": FORMAT,
+        "version": FORMAT_VERSION,
+        "created_at": int(time.time()),
+        "files": records,
+        "merkle_root": _merkle_root(records),
+        "checkpoints": checkpoints,
+        "payload_sha256": hashlib.sha256(envelope).hexdigest(),
+    }
+    manifest_bytes = json.dumps(manifest, sort_keys=True).encode()
+    signature = reliquary_encryptor.sign_falcon(
+        *_with_params((manifest_bytes, signing_key), falcon_params))
+    write_atomic(archive_path, json.dumps({
+        "manifest": manifest_bytes.decode(),
+        "signature": base64.b64encode(signature).decode(),
+        "payload": base64.b64encode(envelope).decode(),
+    }).encode())
+
+    _audit(audit_logger, "state_export", manifest, archive_path)
+    logger.info(f"Exported {len(records)} files to state archive {archive_path}")
+    return manifest
+
+
+def _open_archive(archive_path: str, recovery_secret_key: Any, signer_public_key: bytes,
+                  falcon_params: Any, policy_manager: Any
+                  ) -> Tuple[Dict[str, Any], List[Tuple[str, str, bytes]]]:
+    _require_encryptor()
+    try:
+        with open(archive_path, "rb") as f:
+            archive = json.loads(f.read())
+        manifest_bytes = archive["manifest"].encode()
+        signature = base64.b64decode(archive["signature"], validate=True)
+        envelope = base64.b64decode(archive["payload"], validate=True)
+    except (OSError, ValueError, KeyError, TypeError, AttributeError) as e:
+        raise StateExportError(f"Unreadable state archive: {e}")
+
+    try:
+        valid = reliquary_encryptor.verify_falcon(
+            *_with_params((manifest_bytes, signature, signer_public_key), falcon_params))
+    except ValueError as e:
+        raise StateExportError(f"State archive signature check failed: {e}")
+    if not valid:
+        raise StateExportError("State archive signature is invalid")
+
+    manifest = json.loads(manifest_bytes)
+    if manifest.get("format") != FORMAT or manifest.get("version") != FORMAT_VERSION:
+        raise StateExportError("Unsupported state archive format")
+    if hashlib.sha256(envelope).hexdigest() != manifest["payload_sha256"]:
+        raise StateExportError("State archive payload doesn't match its manifest")
+    try:
+        payload = reliquary_encryptor.decrypt_with_secret_key(envelope, recovery_secret_key)
+    except ValueError as e:
+        raise StateExportError(f"Could not decrypt state archive: {e}")
+
+    records = manifest["files"]
+    if _merkle_root(records) != manifest["merkle_root"]:
+        raise StateExportError("State archive file list doesn't match its Merkle root")
+    if sum(r["size"] for r in records) != len(payload):
+        raise StateExportError("State archive payload size doesn't match its manifest")
+    files, seen, offset = [], set(), 0
+    for record in records:
+        section, path = record["section"], record["path"]
+        if section not in SECTIONS or not _safe_path(path) or (section, path) in seen:
+            raise StateExportError(f"Invalid state archive entry {section}/{path}")
+        seen.add((section, path))
+        data = payload[offset:offset + record["size"]]
+        offset += record["size"]
+        if hashlib.sha256(data).hexdigest() != record["sha256"]:
+            raise StateExportError(f"Digest mismatch for {section}/{path}")
+        files.append((section, path, data))
+
+    _verify_checkpoints(manifest["checkpoints"], files)
+    if policy_manager is not None:
+        _verify_policies(policy_manager, files)
+    return manifest, files
+
+
+def _verify_checkpoints(checkpoints: List[Dict[str, Any]], files: List[Tuple[str, str, bytes]]):
+    audit = {path: data for section, path, data in files if section == "audit"}
+    logs = {path for path in audit if not path.endswith(MERKLE_SUFFIX)}
+    if logs != {c["path"] for c in checkpoints}:
+        raise StateExportError("Audit logs in the state archive don't match its checkpoints")
+    for checkpoint in checkpoints:
+        name = checkpoint["path"]
+        actual = audit_checkpoint(audit[name])
+        if (actual["entry_count"] != checkpoint["entry_count"]
+                or actual["merkle_root"] != checkpoint["merkle_root"]):
+            raise StateExportError(f"Audit log {name} doesn't match its checkpoint")
+        _check_root_file(name, actual, audit.get(name + MERKLE_SUFFIX))
+
+
+def _verify_policies(policy_manager: Any, files: List[Tuple[str, str, bytes]]):
+    policies = {path: data for section, path, data in files if section == "policies"}
+    for path, signature in policies.items():
+        if not path.endswith(SIGNATURE_SUFFIX):
+            continue
+        bundle_path = path[:-len(SIGNATURE_SUFFIX)]
+        if bundle_path not in policies:
+            raise StateExportError(f"Policy signature {path} has no bundle")
+        try:
+            policy_manager.compile(policies[bundle_path], signature)
+        except Exception as e:
+            raise StateExportError(f"Policy bundle {bundle_path} was rejected: {e}")
+
+
+def verify_state(archive_path: str, recovery_secret_key: Any, signer_public_key: bytes,
+                 falcon_params: Any = None, policy_manager: Any = None) -> Dict[str, Any]:
+    """
+    Run every import check on an archive without restoring it (e.g. for recovery drills).
+
+    Returns:
+        The verified manifest
+
+    Raises:
+        StateExportError: If any check fails
+    """
+    manifest, _ = _open_archive(archive_path, recovery_secret_key, signer_public_key,
+                                falcon_params, policy_manager)
+    return manifest
+
+
+def import_state(archive_path: str, recovery_secret_key: Any, signer_public_key: bytes,
+                 keystore_dir: Optional[str] = None, audit_dir: Optional[str] = None,
+                 policy_dir: Optional[str] = None, policy_manager: Any = None,
+                 falcon_params: Any = None, overwrite: bool = False,
+                 audit_logger: Any = None) -> Dict[str, Any]:
+    """
+    Verify a state archive and restore it.
+
+    Nothing is written unless every check passes. Sections without a target directory are
+    verified but not restored.
+
+    Args:
+        archive_path: Archive written by export_state
+        recovery_secret_key: Kyber secret key (or SecretKeyHandle) the archive was sealed to
+        signer_public_key: Falcon public key the manifest must be signed with
+        keystore_dir: Where to restore the keystore section
+        audit_dir: Where to restore the audit logs
+        policy_dir: Where to restore the policy bundles
+        policy_manager: PolicyManager whose key must have signed every restored bundle
+        falcon_params: FalconParams of the signer (Falcon-1024 by default)
+        overwrite: Replace existing files whose contents differ instead of refusing
+        audit_logger: Sink with add_entry(dict) (e.g. MerkleLogWriter) for the import event
+
+    Returns:
+        The verified manifest
+
+    Raises:
+        StateExportError: If a check fails or, without overwrite, a restore would change an
+            existing file
+    """
+    ensure_writable("Restoring a state archive")
+    manifest, files = _open_archive(archive_path, recovery_secret_key, signer_public_key,
+                                    falcon_params, policy_manager)
+    targets = {"keystore": keystore_dir, "audit": audit_dir, "policies": policy_dir}
+    plan = [(os.path.join(targets[section], *path.split("/")), data)
+            for section, path, data in files if targets[section] is not None]
+
+    if not overwrite:
+        conflicts = []
+        for target, data in plan:
+            if os.path.exists(target):
+                with open(target, "rb") as f:
+                    if f.read() != data:
+                        conflicts.append(target)
+        if conflicts:
+            raise StateExportError(
+                f"Restore would overwrite {len(conflicts)} existing file(s), e.g. {conflicts[0]}")
+
+    for target, data in plan:
+        os.makedirs(os.path.dirname(target), exist_ok=True)
+        write_atomic(target, data)
+
+    _audit(audit_logger, "state_import", manifest, archive_path)
+    logger.info(f"Restored {len(plan)} files from state archive {archive_path}")
+    return manifest
+
+
+def _audit(audit_logger: Any, event: str, manifest: Dict[str, Any], archive_path: str):
+    if audit_logger is None:
+        return
+    try:
+        audit_logger.add_entry({
+            "event": event,
+            "archive": archive_path,
+            "created_at": manifest["created_at"],
+            "merkle_root": manifest["merkle_root"],
+            "files": len(manifest["files"]),
+        })
+    except Exception as e:
+        logger.warning(f"Could not log {event}: {e}")
@@ -1,5 +1,6 @@
 # vaults/storage/local.py
 import os
+from core.read_only import ensure_directory, ensure_writable
 from vaults.storage.base import StorageInterface
 
 class LocalFileStorage(StorageInterface):
@@ -8,7 +9,7 @@ class LocalFileStorage(StorageInterface):
     """
     def __init__(self, base_path: str):
         self.base_path = base_path
-        os.makedirs(base_path, exist_ok=True)
+        ensure_directory(base_path)
 
     def _get_file_path(self, vault_id: str) -> str:
         """Constructs the full file path for a given vault ID."""
@@ -16,6 +17,7 @@ class LocalFileStorage(StorageInterface):
 
     def save_vault(self, vault_id: str, data: bytes):
         """Saves encrypted vault data to a local file."""
+        ensure_writable(f"Saving container {vault_id}")
         file_path = self._get_file_path(vault_id)
         with open(file_path, "wb") as f:
             f.write(data)
@@ -30,6 +32,7 @@ class LocalFileStorage(StorageInterface):
 
     def delete_vault(self, vault_id: str):
         """Deletes a local vault file."""
+        ensure_writable(f"Deleting container {vault_id}")
         file_path = self._get_file_path(vault_id)
         if os.path.exists(file_path):
             os.remove(file_path)
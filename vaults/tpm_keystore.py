@@ -0,0 +1,176 @@
+"""
+TPM 2.0 sealed storage for the vault master key.
+
+The master key is sealed to the TPM's storage hierarchy under a PCR policy, so the TPM only
+releases it when the selected PCRs hold the values they had at sealing time, i.e. the
+appliance booted the same firmware, bootloader and kernel (measured boot). Only the sealed
+blob (public and private parts, which are useless without this TPM) is kept on disk::
+
+    <state_dir>/master_key.pub
+    <state_dir>/master_key.priv
+    <state_dir>/master_key.json   {"pcr_bank": "sha256", "pcrs": [0, 2, 4, 7], "sealed_at": ...}
+
+The TPM is driven through the tpm2-tools command line (v5); `TPM2TOOLS_TCTI` selects the
+device, e.g. "device:/dev/tpmrm0" or "swtpm:port=2321" for a software TPM.
+"""
+
+import json
+import logging
+import os
+import secrets
+import subprocess
+import tempfile
+import time
+from typing import Callable, Dict, List, Optional, Sequence
+
+from core.read_only import ensure_writable
+
+DEFAULT_PCRS = (0, 2, 4, 7)  # Firmware, option ROMs, bootloader, Secure Boot state
+MASTER_KEY_BYTES = 32
+MAX_SEALED_BYTES = 128  # TPM2B_SENSITIVE_DATA limit
+
+# runner(args, stdin, env) -> stdout; raises TpmError on failure
+Runner = Callable[[List[str], Optional[bytes], Dict[str, str]], bytes]
+
+
+class TpmError(Exception):
+    """Raised when a TPM command fails"""
+
+
+class TpmUnsealError(TpmError):
+    """Raised when the TPM refuses to unseal, e.g. because PCR state changed"""
+
+
+def run_tpm2_tool(args: List[str], stdin: Optional[bytes], env: Dict[str, str]) -> bytes:
+    """Run a tpm2-tools command and return its stdout."""
+    try:
+        result = subprocess.run(args, input=stdin, capture_output=True, env=env, timeout=60)
+    except FileNotFoundError:
+        raise TpmError(f"{args[0]} not found; install tpm2-tools")
+    except subprocess.TimeoutExpired:
+        raise TpmError(f"{args[0]} timed out")
+    if result.returncode != 0:
+        raise TpmError(f"{args[0]} failed: {result.stderr.decode(errors='replace').strip()}")
+    return result.stdout
+
+
+class TpmKeyStore:
+    """
+    Keeps the vault master key sealed to TPM PCR state.
+
+    At provisioning the key is sealed with `seal_master_key` (or created with
+    `load_or_create_master_key`); at startup `unseal_master_key` asks the TPM for it, which
+    fails with TpmUnsealError if the boot chain measured into the PCRs has changed.
+    """
+
+    def __init__(self, state_dir: str, pcrs: Sequence[int] = DEFAULT_PCRS,
+                 pcr_bank: str = "sha256", tcti: Optional[str] = None,
+                 runner: Runner = run_tpm2_tool):
+        """
+        Initialize the keystore.
+
+        Args:
+            state_dir: Directory holding the sealed blob and its metadata
+            pcrs: PCR indices the key is bound to
+            pcr_bank: PCR hash bank, e.g. "sha256"
+            tcti: TPM transport (defaults to TPM2TOOLS_TCTI or the tools' default)
+            runner: Executes tpm2-tools commands (replaceable for testing)
+        """
+        if not pcrs or any(not 0 <= pcr <= 23 for pcr in pcrs):
+            raise ValueError("PCR indices must be between 0 and 23")
+        self.state_dir = state_dir
+        self.pcrs = sorted(set(pcrs))
+        self.pcr_bank = pcr_bank
+        self.tcti = tcti
+        self.runner = runner
+        self.logger = logging.getLogger(__name__)
+
+    @property
+    def pcr_selection(self) -> str:
+        return f"{self.pcr_bank}:{','.join(str(pcr) for pcr in self.pcrs)}"
+
+    def _path(self, suffix: str) -> str:
+        return os.path.join(self.state_dir, f"master_key.{suffix}")
+
+    def is_provisioned(self) -> bool:
+        """Whether a sealed master key exists in the state directory."""
+        return all(os.path.exists(self._path(s)) for s in ("pub", "priv", "json"))
+
+    def _run(self, *args: str, stdin: Optional[bytes] = None) -> bytes:
+        env = dict(os.environ)
+        if self.tcti:
+            env["TPM2TOOLS_TCTI"] = self.tcti
+        return self.runner(list(args), stdin, env)
+
+    def _create_primary(self, workdir: str) -> str:
+        # The owner-hierarchy primary is derived from the TPM's seed and the template, so it
+        # is recreated identically on every boot instead of being persisted
+        primary = os.path.join(workdir, "primary.ctx")
+        self._run("tpm2_createprimary", "-Q", "-C", "o", "-g", "sha256", "-G", "ecc",
+                  "-c", primary)
+        return primary
+
+    def seal_master_key(self, master_key: bytes):
+        """
+        Seal a master key to the current PCR state, replacing any previously sealed key.
+
+        Args:
+            master_key: Key bytes (at most 128)
+        """
+        if not 0 < len(master_key) <= MAX_SEALED_BYTES:
+            raise ValueError(f"Sealed data must be 1 to {MAX_SEALED_BYTES} bytes")
+        ensure_writable("Sealing the master key")
+        os.makedirs(self.state_dir, mode=0o700, exist_ok=True)
+        with tempfile.TemporaryDirectory() as workdir:
+            primary = self._create_primary(workdir)
+            policy = os.path.join(workdir, "pcr.policy")
+            self._run("tpm2_createpolicy", "-Q", "--policy-pcr", "-l", self.pcr_selection,
+                      "-L", policy)
+            # No userwithauth: only a session satisfying the PCR policy can unseal
+            self._run("tpm2_create", "-Q", "-C", primary, "-L", policy,
+                      "-a", "fixedtpm|fixedparent|noda", "-i", "-",
+                      "-u", self._path("pub.tmp"), "-r", self._path("priv.tmp"),
+                      stdin=bytes(master_key))
+        metadata = {"pcr_bank": self.pcr_bank, "pcrs": self.pcrs, "sealed_at": time.time()}
+        with open(self._path("json.tmp"), "w") as f:
+            json.dump(metadata, f)
+        for suffix in ("pub", "priv", "json"):
+            os.replace(self._path(f"{suffix}.tmp"), self._path(suffix))
+        self.logger.info(f"Master key sealed to PCRs {self.pcr_selection}")
+
+    def unseal_master_key(self) -> bytes:
+        """
+        Unseal the master key; the TPM checks the PCR policy.
+
+        Raises:
+            TpmError: If no key is provisioned or the TPM is unavailable
+            TpmUnsealError: If the PCR state no longer matches the sealing policy
+        """
+        if not self.is_provisioned():
+            raise TpmError(f"No sealed master key in {self.state_dir}")
+        with open(self._path("json")) as f:
+            metadata = json.load(f)
+        selection = f"{metadata['pcr_bank']}:{','.join(str(p) for p in metadata['pcrs'])}"
+        with tempfile.TemporaryDirectory() as workdir:
+            primary = self._create_primary(workdir)
+            sealed = os.path.join(workdir, "sealed.ctx")
+            self._run("tpm2_load", "-Q", "-C", primary, "-u", self._path("pub"),
+                      "-r", self._path("priv"), "-c", sealed)
+            try:
+                master_key = self._run("tpm2_unseal", "-c", sealed, "-p", f"pcr:{selection}")
+            except TpmError as e:
+                self.logger.error(f"TPM refused to unseal the master key: {e}")
+                raise TpmUnsealError(
+                    f"Unseal failed; PCR state ({selection}) may have changed: {e}")
+        return master_key
+
+    def load_or_create_master_key(self) -> bytes:
+        """
+        Startup helper: unseal the master key, or on first boot generate and seal a new one.
+        """
+        if self.is_provisioned():
+            return self.unseal_master_key()
+        master_key = secrets.token_bytes(MASTER_KEY_BYTES)
+        self.seal_master_key(master_key)
+        self.logger.info("Provisioned a new TPM-sealed master key")
+        return master_key
@@ -0,0 +1,339 @@
+"""
+Atomic seal + audit + keystore transactions.
+
+Sealing an item touches three stores: the container written to a storage backend, the audit
+log and the keystore's per-key counters. A crash between those writes used to leave them
+disagreeing. The coordinator makes them one unit with write-ahead intent records:
+
+1. Every write the transaction will make is recorded in an intent file, which is fsynced and
+   atomically renamed into place before anything else happens.
+2. The writes are applied. Each one is idempotent (containers and counters are set to
+   absolute values, audit entries carry the transaction id and are skipped if present).
+3. The intent file is removed.
+
+A crash after step 1 leaves the intent behind and `recover()` replays it at startup, so a
+transaction is either invisible (its intent never became durable) or fully applied. Intent
+files live in <state_dir>/intents, one JSON file per pending transaction.
+"""
+
+import base64
+import json
+import logging
+import os
+import threading
+import time
+import uuid
+from abc import ABC, abstractmethod
+from typing import Any, Dict, Iterable, List, Optional
+
+from core.read_only import ensure_directory, ensure_writable
+
+try:
+    import reliquary_encryptor
+except ImportError:
+    reliquary_encryptor = None
+
+
+class TransactionError(Exception):
+    """Raised when a transaction can't be committed or replayed"""
+
+
+def _fsync_dir(path: str):
+    if os.name != "posix":
+        return
+    fd = os.open(path, os.O_RDONLY)
+    try:
+        os.fsync(fd)
+    finally:
+        os.close(fd)
+
+
+def write_atomic(path: str, data: bytes):
+    """Replace `path` with `data` so that readers see either the old or the new contents."""
+    ensure_writable(f"Writing {path}")
+    directory = os.path.dirname(path) or "."
+    temp_path = f"{path}.{uuid.uuid4().hex}.tmp"
+    with open(temp_path, "wb") as f:
+        f.write(data)
+        f.flush()
+        os.fsync(f.fileno())
+    os.replace(temp_path, path)
+    _fsync_dir(directory)
+
+
+class Participant(ABC):
+    """A store that takes part in transactions. `apply` must be idempotent."""
+
+    name: str = ""
+
+    @abstractmethod
+    def apply(self, txid: str, op: Dict[str, Any]):
+        """Perform one recorded write."""
+
+
+class ContainerParticipant(Participant):
+    """Container writes to a storage backend (save_vault / delete_vault)"""
+
+    name = "containers"
+
+    def __init__(self, storage: Any):
+        self.storage = storage
+
+    def apply(self, txid: str, op: Dict[str, Any]):
+        if op["action"] == "put":
+            self.storage.save_vault(op["container_id"], base64.b64decode(op["data"]))
+        elif op["action"] == "delete":
+            self.storage.delete_vault(op["container_id"])
+        else:
+            raise TransactionError(f"Unknown container action {op['action']!r}")
+
+
+class AuditLogParticipant(Participant):
+    """
+    Audit entries appended to a MerkleLogWriter (or any sink with add_entry(dict)).
+
+    Each entry is tagged with the transaction id and a per-write `tx_op` id; on replay an entry
+    already in the log is not appended again when the sink supports find_entry().
+    """
+
+    name = "audit"
+
+    def __init__(self, audit_logger: Any):
+        self.audit_logger = audit_logger
+
+    def apply(self, txid: str, op: Dict[str, Any]):
+        tx_op = f"{txid}/{op['index']}"
+        find_entry = getattr(self.audit_logger, "find_entry", None)
+        if find_entry is not None and find_entry("tx_op", tx_op) is not None:
+            return
+        self.audit_logger.add_entry(dict(op["entry"], txid=txid, tx_op=tx_op))
+
+
+class KeyCounterStore(Participant):
+    """
+    Persistent per-key counters (e.g. how many items each key has sealed), kept in one JSON
+    file that is replaced atomically on every change.
+    """
+
+    name = "keystore"
+
+    def __init__(self, path: str):
+        self.path = path
+        self._lock = threading.Lock()
+        ensure_directory(os.path.dirname(path) or ".")
+        self._counters: Dict[str, int] = {}
+        if os.path.exists(path):
+            with open(path, "r") as f:
+                self._counters = {k: int(v) for k, v in json.load(f).items()}
+
+    def get(self, key_id: str) -> int:
+        with self._lock:
+            return self._counters.get(key_id, 0)
+
+    def counters(self) -> Dict[str, int]:
+        with self._lock:
+            return dict(self._counters)
+
+    def set(self, key_id: str, value: int):
+        with self._lock:
+            counters = dict(self._counters, **{key_id: value})
+            write_atomic(self.path, json.dumps(counters, sort_keys=True).encode())
+            self._counters = counters
+
+    def apply(self, txid: str, op: Dict[str, Any]):
+        self.set(op["key_id"], op["value"])
+
+
+class Transaction:
+    """
+    Writes staged for one atomic commit. Nothing touches a store until `commit()`.
+
+    Usable as a context manager: the transaction commits when the block exits normally and is
+    discarded if it raises.
+    """
+
+    def __init__(self, coordinator: "TransactionCoordinator"):
+        self.coordinator = coordinator
+        self.txid = uuid.uuid4().hex
+        self.ops: List[Dict[str, Any]] = []
+        self.counter_bumps: Dict[str, int] = {}
+        self.done = False
+
+    def _stage(self, participant: str, op: Dict[str, Any]):
+        if self.done:
+            raise TransactionError(f"Transaction {self.txid} is already finished")
+        self.coordinator._participant(participant)
+        self.ops.append({"participant": participant, "op": op})
+
+    def put_container(self, container_id: str, data: bytes):
+        self._stage(ContainerParticipant.name, {"action": "put", "container_id": container_id,
+                                                "data": base64.b64encode(data).decode("ascii")})
+
+    def delete_container(self, container_id: str):
+        self._stage(ContainerParticipant.name, {"action": "delete",
+                                                "container_id": container_id})
+
+    def append_audit(self, entry: Dict[str, Any]):
+        self._stage(AuditLogParticipant.name, {"entry": dict(entry)})
+
+    def bump_counter(self, key_id: str, by: int = 1):
+        """Add `by` to a key counter; the new value is fixed at commit time."""
+        if self.done:
+            raise TransactionError(f"Transaction {self.txid} is already finished")
+        self.coordinator._participant(KeyCounterStore.name)
+        self.counter_bumps[key_id] = self.counter_bumps.get(key_id, 0) + by
+
+    def commit(self) -> str:
+        """Apply every staged write atomically. Returns the transaction id."""
+        if self.done:
+            raise TransactionError(f"Transaction {self.txid} is already finished")
+        self.done = True
+        self.coordinator._commit(self)
+        return self.txid
+
+    def abort(self):
+        """Discard the staged writes."""
+        self.done = True
+
+    def __enter__(self) -> "Transaction":
+        return self
+
+    def __exit__(self, exc_type, exc, tb):
+        if exc_type is None and not self.done:
+            self.commit()
+        elif not self.done:
+            self.abort()
+
+
+class TransactionCoordinator:
+    """
+    Commits transactions across the container store, audit log and keystore counters.
+
+    Call `recover()` once at startup to replay any transaction that was interrupted by a crash;
+    commits are refused while one is pending. Commits are serialized.
+    """
+
+    def __init__(self, state_dir: str, participants: Iterable[Participant]):
+        """
+        Initialize the coordinator.
+
+        Args:
+            state_dir: Directory for intent records
+            participants: Stores taking part, at most one per name (e.g. ContainerParticipant,
+                AuditLogParticipant and KeyCounterStore)
+        """
+        self.intent_dir = os.path.join(state_dir, "intents")
+        ensure_directory(self.intent_dir)
+        self.participants: Dict[str, Participant] = {}
+        for participant in participants:
+            if participant.name in self.participants:
+                raise ValueError(f"Duplicate transaction participant {participant.name}")
+            self.participants[participant.name] = participant
+        self.logger = logging.getLogger(__name__)
+        self._lock = threading.Lock()
+
+    def _participant(self, name: str) -> Participant:
+        participant = self.participants.get(name)
+        if participant is None:
+            raise TransactionError(f"No transaction participant named {name!r}")
+        return participant
+
+    def begin(self) -> Transaction:
+        return Transaction(self)
+
+    def pending(self) -> List[str]:
+        """Ids of transactions with a durable intent that has not been fully applied."""
+        return [record["txid"] for _, record in self._intents()]
+
+    def _intents(self) -> List[tuple]:
+        intents = []
+        if not os.path.isdir(self.intent_dir):
+            return intents  # Read-only mode skipped creating it
+        for name in sorted(os.listdir(self.intent_dir)):
+            if not name.endswith(".json"):
+                continue  # Leftover temp file from a crash before the intent became durable
+            path = os.path.join(self.intent_dir, name)
+            with open(path, "r") as f:
+                intents.append((path, json.load(f)))
+        return intents
+
+    def _apply(self, path: str, record: Dict[str, Any]):
+        ensure_writable(f"Applying transaction {record['txid']}")
+        for index, item in enumerate(record["ops"]):
+            op = dict(item["op"], index=index)
+            self._participant(item["participant"]).apply(record["txid"], op)
+        os.remove(path)
+        _fsync_dir(self.intent_dir)
+
+    def _commit(self, transaction: Transaction):
+        ensure_writable(f"Committing transaction {transaction.txid}")
+        with self._lock:
+            # Counter values are computed from the store, so an unreplayed intent would later
+            # overwrite this transaction's values
+            if self._intents():
+                raise TransactionError("Interrupted transactions are pending; run recover() first")
+            ops = list(transaction.ops)
+            if transaction.counter_bumps:
+                counters = self._participant(KeyCounterStore.name)
+                for key_id, by in sorted(transaction.counter_bumps.items()):
+                    ops.append({"participant": KeyCounterStore.name,
+                                "op": {"key_id": key_id, "value": counters.get(key_id) + by}})
+            if not ops:
+                return
+            record = {"txid": transaction.txid, "created_at": time.time(), "ops": ops}
+            path = os.path.join(self.intent_dir,
+                                f"{time.time_ns():020d}-{transaction.txid}.json")
+            write_atomic(path, json.dumps(record).encode())
+            try:
+                self._apply(path, record)
+            except Exception as e:
+                self.logger.error(f"Transaction {transaction.txid} interrupted: {e}")
+                raise TransactionError(
+                    f"Transaction {transaction.txid} was interrupted after its intent was "
+                    f"recorded; recover() will complete it: {e}")
+
+    def recover(self) -> List[str]:
+        """
+        Replay every pending transaction in commit order.
+
+        Returns:
+            Ids of the transactions that were completed
+        """
+        completed = []
+        with self._lock:
+            intents = self._intents()
+            if intents:
+                ensure_writable("Replaying interrupted transactions")
+            for path, record in intents:
+                try:
+                    self._apply(path, record)
+                except Exception as e:
+                    raise TransactionError(f"Replaying transaction {record['txid']} failed: {e}")
+                self.logger.warning(f"Recovered interrupted transaction {record['txid']}")
+                completed.append(record["txid"])
+        return completed
+
+    def seal_item(self, container_id: str, data: bytes, public_key: bytes, key_id: str,
+                  audit_data: Optional[Dict[str, Any]] = None) -> bytes:
+        """
+        Seal `data` to a Kyber public key, store it, log it and count it, atomically.
+
+        Args:
+            container_id: Storage id of the container
+            data: Plaintext
+            public_key: Kyber-1024 public key to seal to
+            key_id: Keystore id of that key, whose counter is bumped
+            audit_data: Extra fields for the audit entry
+
+        Returns:
+            The sealed envelope
+        """
+        if reliquary_encryptor is None:
+            raise TransactionError("reliquary_encryptor is required to seal items")
+        envelope = reliquary_encryptor.encrypt_to_public_key(data, public_key)
+        with self.begin() as transaction:
+            transaction.put_container(container_id, envelope)
+            transaction.append_audit(dict(audit_data or {}, event="item_sealed",
+                                          container_id=container_id, key_id=key_id))
+            transaction.bump_counter(key_id)
+        return envelope
@@ -26,6 +26,20 @@ from .trust_engine import (
     TrustFactor
 )
 
+from .anomaly import (
+    AnomalyDetector,
+    AnomalyFlag,
+    AnomalyKind
+)
+
+from .velocity import (
+    VelocityChecker,
+    VelocityThresholds,
+    ContextHistoryStore,
+    ContextObservation,
+    IPReputation
+)
+
 # Import verifier components
 from .verifier.zk_runner import (
     ZKProofRunner,
@@ -57,6 +71,18 @@ __all__ = [
     "UserTrustProfile",
     "TrustFactor",
     
+    # Anomaly detection
+    "AnomalyDetector",
+    "AnomalyFlag",
+    "AnomalyKind",
+    
+    # Velocity checks
+    "VelocityChecker",
+    "VelocityThresholds",
+    "ContextHistoryStore",
+    "ContextObservation",
+    "IPReputation",
+    
     # ZK proof runner
     "ZKProofRunner",
     "ProofGenerationResult",
@@ -0,0 +1,226 @@
+# zk/anomaly.py
+
+import hashlib
+import logging
+import math
+import threading
+import time
+from collections import deque
+from dataclasses import dataclass, asdict
+from datetime import datetime, timezone
+from enum import Enum
+from typing import Any, Deque, Dict, List, Optional, Tuple
+
+class AnomalyKind(Enum):
+    """Anomaly signals raised on operation streams"""
+    FAILURE_SPIKE = "failure_spike"  # Failures in the current window far above the EWMA baseline
+    UNUSUAL_HOUR = "unusual_hour"    # Operation at an hour of day this subject rarely uses
+
+@dataclass
+class AnomalyFlag:
+    """One anomaly raised for a key or tenant"""
+    kind: AnomalyKind
+    subject_type: str  # "key" or "tenant"
+    subject: str
+    operation: str
+    observed: float
+    baseline: float
+    timestamp: float
+
+    def to_dict(self) -> Dict[str, Any]:
+        data = asdict(self)
+        data["kind"] = self.kind.value
+        return data
+
+class CountMinSketch:
+    """
+    Fixed-memory frequency estimates for an unbounded key space.
+
+    Estimates never undercount; they overcount by at most total/width with probability
+    1 - 2^-depth.
+    """
+
+    def __init__(self, width: int = 1024, depth: int = 4):
+        self.width = width
+        self.depth = depth
+        self.rows = [[0] * width for _ in range(depth)]
+
+    def _cells(self, key: str):
+        digest = hashlib.sha256(key.encode()).digest()
+        for row in range(self.depth):
+            yield row, int.from_bytes(digest[row * 4:row * 4 + 4], "big") % self.width
+
+    def add(self, key: str, count: int = 1):
+        for row, col in self._cells(key):
+            self.rows[row][col] += count
+
+    def estimate(self, key: str) -> int:
+        return min(self.rows[row][col] for row, col in self._cells(key))
+
+@dataclass
+class _WindowedRate:
+    """EWMA mean and variance of per-window event counts"""
+    window_start: float
+    current: int = 0
+    mean: float = 0.0
+    variance: float = 0.0
+    windows: int = 0
+    flagged: bool = False
+
+class AnomalyDetector:
+    """
+    Streaming anomaly detection over operation events, per key and per tenant.
+
+    Failures are counted in fixed windows; each closed window updates an exponentially
+    weighted mean and variance, and a window whose count exceeds the mean by `spike_sigma`
+    standard deviations raises a FAILURE_SPIKE (once per window). Usage by hour of day is kept
+    in a count-min sketch; once a subject has enough history, an operation in an hour holding
+    less than `rare_hour_ratio` of its usage raises UNUSUAL_HOUR. Memory is bounded by the
+    number of subjects, not the number of events.
+    """
+
+    # Empty windows replayed after a gap; beyond this the baseline has decayed anyway
+    MAX_IDLE_WINDOWS = 64
+
+    def __init__(self, window_seconds: float = 300.0, alpha: float = 0.3,
+                 spike_sigma: float = 3.0, min_spike_count: int = 5,
+                 min_baseline_windows: int = 5, rare_hour_ratio: float = 0.02,
+                 min_hour_observations: int = 50, flag_ttl: float = 3600.0,
+                 max_flags: int = 1000, audit_logger: Any = None):
+        """
+        Initialize the detector.
+
+        Args:
+            window_seconds: Length of a failure counting window
+            alpha: EWMA smoothing factor (weight of the newest window)
+            spike_sigma: Standard deviations above the baseline that count as a spike
+            min_spike_count: Failures needed in a window before it can be a spike
+            min_baseline_windows: Closed windows needed before spikes are flagged
+            rare_hour_ratio: Share of a subject's usage below which an hour is unusual
+            min_hour_observations: Events needed per subject before hours are judged
+            flag_ttl: Seconds a flag stays active for the trust engine
+            max_flags: Most recent flags kept in memory
+            audit_logger: Sink with add_entry(dict) (e.g. MerkleLogWriter) for raised flags
+        """
+        self.window_seconds = window_seconds
+        self.alpha = alpha
+        self.spike_sigma = spike_sigma
+        self.min_spike_count = min_spike_count
+        self.min_baseline_windows = min_baseline_windows
+        self.rare_hour_ratio = rare_hour_ratio
+        self.min_hour_observations = min_hour_observations
+        self.flag_ttl = flag_ttl
+        self.audit_logger = audit_logger
+        self.logger = logging.getLogger(__name__)
+
+        self._lock = threading.Lock()
+        self._failure_rates: Dict[Tuple[str, str, str], _WindowedRate] = {}
+        self._hours = CountMinSketch()
+        self._usage_totals: Dict[Tuple[str, str], int] = {}
+        self._flags: Deque[AnomalyFlag] = deque(maxlen=max_flags)
+
+    def record(self, operation: str, key_id: Optional[str] = None,
+               tenant: Optional[str] = None, success: bool = True,
+               timestamp: Optional[float] = None) -> List[AnomalyFlag]:
+        """
+        Feed one operation event (e.g. an unseal attempt) into the detector.
+
+        Args:
+            operation: Operation name, e.g. "unseal" or "sign"
+            key_id: Key the operation used, if any
+            tenant: Tenant the operation ran for, if any
+            success: Whether the operation succeeded
+            timestamp: Event time (defaults to now)
+
+        Returns:
+            Flags raised by this event
+        """
+        now = time.time() if timestamp is None else timestamp
+        subjects = [(kind, value) for kind, value in (("key", key_id), ("tenant", tenant))
+                    if value is not None]
+        raised = []
+        with self._lock:
+            for subject_type, subject in subjects:
+                if not success:
+                    flag = self._record_failure(subject_type, subject, operation, now)
+                    if flag:
+                        raised.append(flag)
+                flag = self._record_usage(subject_type, subject, operation, now)
+                if flag:
+                    raised.append(flag)
+            self._flags.extend(raised)
+
+        for flag in raised:
+            self._log_flag(flag)
+        return raised
+
+    def active_flags(self, key_id: Optional[str] = None, tenant: Optional[str] = None,
+                     now: Optional[float] = None) -> List[AnomalyFlag]:
+        """Flags raised within `flag_ttl` for the given key and/or tenant."""
+        now = time.time() if now is None else now
+        wanted = {("key", key_id), ("tenant", tenant)}
+        with self._lock:
+            return [flag for flag in self._flags
+                    if (flag.subject_type, flag.subject) in wanted
+                    and now - self.flag_ttl < flag.timestamp <= now]
+
+    def _record_failure(self, subject_type: str, subject: str, operation: str,
+                        now: float) -> Optional[AnomalyFlag]:
+        """Count a failure and check the current window against the baseline."""
+        rate = self._failure_rates.get((subject_type, subject, operation))
+        if rate is None:
+            rate = _WindowedRate(window_start=now)
+            self._failure_rates[(subject_type, subject, operation)] = rate
+        self._roll_windows(rate, now)
+        rate.current += 1
+
+        if (rate.flagged or rate.windows < self.min_baseline_windows
+                or rate.current < self.min_spike_count):
+            return None
+        limit = rate.mean + self.spike_sigma * math.sqrt(rate.variance)
+        if rate.current <= limit:
+            return None
+        rate.flagged = True
+        return AnomalyFlag(AnomalyKind.FAILURE_SPIKE, subject_type, subject, operation,
+                           observed=float(rate.current), baseline=round(rate.mean, 3),
+                           timestamp=now)
+
+    def _roll_windows(self, rate: _WindowedRate, now: float):
+        """Close every window that has ended, folding its count into the EWMA."""
+        elapsed = int((now - rate.window_start) // self.window_seconds)
+        if elapsed <= 0:
+            return
+        for count in [rate.current] + [0] * min(elapsed - 1, self.MAX_IDLE_WINDOWS):
+            delta = count - rate.mean
+            rate.mean += self.alpha * delta
+            rate.variance = (1 - self.alpha) * (rate.variance + self.alpha * delta * delta)
+            rate.windows += 1
+        rate.window_start += elapsed * self.window_seconds
+        rate.current = 0
+        rate.flagged = False
+
+    def _record_usage(self, subject_type: str, subject: str, operation: str,
+                      now: float) -> Optional[AnomalyFlag]:
+        """Judge the hour of day against the subject's history, then count it."""
+        hour = datetime.fromtimestamp(now, timezone.utc).hour
+        key = f"{subject_type}|{subject}|{hour}"
+        total = self._usage_totals.get((subject_type, subject), 0)
+        seen = self._hours.estimate(key)
+        self._hours.add(key)
+        self._usage_totals[(subject_type, subject)] = total + 1
+
+        if total < self.min_hour_observations or seen / total >= self.rare_hour_ratio:
+            return None
+        return AnomalyFlag(AnomalyKind.UNUSUAL_HOUR, subject_type, subject, operation,
+                           observed=float(hour), baseline=round(seen / total, 4),
+                           timestamp=now)
+
+    def _log_flag(self, flag: AnomalyFlag):
+        """Record a raised flag in the audit log."""
+        self.logger.warning(f"Anomaly {flag.kind.value} for {flag.subject_type} "
+                            f"{flag.subject} ({flag.operation})")
+        if self.audit_logger:
+            try:
+                self.audit_logger.add_entry({"event": "anomaly_detected", **flag.to_dict()})
+            except Exception as e:
+                self.logger.warning(f"Could not log anomaly: {e}")
@@ -11,6 +11,7 @@ from enum import Enum
 import logging
 from datetime import datetime, timedelta
 import hashlib
+import copy
 
 # Import our components
 import sys
@@ -18,6 +19,8 @@ import os
 sys.path.append(os.path.join(os.path.dirname(__file__), '../'))
 
 from core.merkle_logging.writer import MerkleLogWriter
+from zk.anomaly import AnomalyDetector
+from zk.velocity import ContextObservation, VelocityChecker
 
 class TrustFactor(Enum):
     """Individual trust factors for evaluation"""
@@ -38,6 +41,117 @@ class RiskLevel(Enum):
     HIGH = 4
     VERY_HIGH = 5
 
+class RedactionLevel(Enum):
+    """How much of a trust explanation may be exposed"""
+    FULL = "full"          # Operators: everything, including observed inputs
+    SUMMARY = "summary"    # Support staff: contributions, rules and thresholds, no raw inputs
+    END_USER = "end_user"  # End users: outcome and plain-language reasons only
+
+# Score bands for risk level determination, checked top-down
+RISK_BANDS = [
+    (90.0, RiskLevel.VERY_LOW),
+    (75.0, RiskLevel.LOW),
+    (60.0, RiskLevel.MEDIUM),
+    (40.0, RiskLevel.HIGH),
+]
+
+# Window after which risk events stop counting against the user
+RISK_EVENT_WINDOW = 86400 * 7
+
+# Bumped whenever scoring code changes in a way that can alter outcomes
+ENGINE_VERSION = "1.3.0"
+DECISION_RECORD_VERSION = 1
+
+def _canonical_json(obj: Any) -> str:
+    """Deterministic JSON used for digests and policy versions."""
+    return json.dumps(obj, sort_keys=True, separators=(",", ":"), default=str)
+
+def _inputs_digest(inputs: Dict[str, Any]) -> str:
+    return hashlib.sha256(_canonical_json(inputs).encode()).hexdigest()
+
+@dataclass
+class TrustPolicy:
+    """Scoring parameters that decide an evaluation, archived under a content-derived version"""
+    weights: Dict[str, float]
+    thresholds: Dict[str, float]
+    risk_bands: List[Tuple[float, str]]
+
+    @property
+    def version(self) -> str:
+        return hashlib.sha256(_canonical_json(asdict(self)).encode()).hexdigest()[:16]
+
+# Plain-language reasons for rules that lower trust, shown at END_USER redaction
+RULE_REASONS = {
+    "device_not_verified": "This device could not be verified",
+    "timestamp_not_verified": "The request time could not be verified",
+    "location_not_verified": "Your location could not be verified",
+    "pattern_not_verified": "Your usage pattern could not be verified",
+    "access_frequency_unusual": "Unusual number of recent access attempts",
+    "recent_risk_events": "Recent security events on this account",
+    "failed_verifications": "One or more context checks failed",
+    "rapid_access": "Repeated access attempts in a short time",
+    "compliance_violations": "Past policy violations on this account",
+    "outside_business_hours": "Access outside permitted hours",
+    "ip_inconsistent": "Network address differs from the usual one",
+    "anomaly_flags": "Unusual activity on the key or tenant in use",
+    "impossible_travel": "Access from a location too far from the previous one to travel in time",
+    "ip_velocity": "Access from many network addresses in a short time",
+    "country_velocity": "Access from several countries in a short time",
+    "ip_reputation": "Access from a network address with a poor reputation",
+}
+
+# Risk indicator penalty per distinct anomaly kind flagged for the request's key or tenant
+ANOMALY_PENALTY = 15.0
+
+@dataclass
+class TrustExplanation:
+    """Structured account of how a trust score was reached"""
+    signals: List[Dict[str, Any]]
+    rule_hits: List[Dict[str, Any]]
+    decay: Dict[str, Any]
+    thresholds: List[Dict[str, Any]]
+    adaptive_thresholds: Dict[str, float]
+    overall_trust_score: float
+    risk_level: RiskLevel
+
+    def to_dict(self, redaction: RedactionLevel = RedactionLevel.FULL) -> Dict[str, Any]:
+        """Render the explanation at the given redaction level."""
+        if redaction == RedactionLevel.END_USER:
+            # Reasons ordered by how many points their factor cost
+            lost = {signal["factor"]: signal["lost"] for signal in self.signals}
+            hits = sorted(
+                (hit for hit in self.rule_hits if hit["rule"] in RULE_REASONS),
+                key=lambda hit: lost.get(hit["factor"], 0.0),
+                reverse=True
+            )
+            reasons = []
+            for hit in hits:
+                if RULE_REASONS[hit["rule"]] not in reasons:
+                    reasons.append(RULE_REASONS[hit["rule"]])
+            return {
+                "redaction": redaction.value,
+                "risk_level": self.risk_level.name,
+                "reasons": reasons
+            }
+
+        rule_hits = self.rule_hits
+        if redaction == RedactionLevel.SUMMARY:
+            rule_hits = [{k: v for k, v in hit.items() if k != "detail"} for hit in rule_hits]
+        return {
+            "redaction": redaction.value,
+            "overall_trust_score": self.overall_trust_score,
+            "risk_level": self.risk_level.name,
+            "signals": self.signals,
+            "rule_hits": rule_hits,
+            "decay": self.decay,
+            "thresholds": self.thresholds,
+            "adaptive_thresholds": self.adaptive_thresholds
+        }
+
+    def to_json(self, redaction: RedactionLevel = RedactionLevel.FULL) -> str:
+        """Serialize the explanation as JSON at the given redaction level."""
+        return json.dumps(self.to_dict(redaction), sort_keys=True)
+
 @dataclass
 class TrustMetrics:
     """Individual trust metrics"""
@@ -62,6 +176,9 @@ class TrustEvaluation:
     recommendations: List[str]
     evaluation_timestamp: int
     session_id: Optional[str] = None
+    explanation: Optional[TrustExplanation] = None
+    explanation_json: Optional[str] = None
+    decision_record: Optional[Dict[str, Any]] = None
 
 @dataclass
 class UserTrustProfile:
@@ -88,12 +205,20 @@ class TrustScoringEngine:
     - Compliance monitoring
     """
     
-    def __init__(self, data_path: str = None):
+    def __init__(self, data_path: str = None,
+                 explanation_redaction: RedactionLevel = RedactionLevel.FULL,
+                 anomaly_detector: Optional[AnomalyDetector] = None,
+                 velocity_checker: Optional[VelocityChecker] = None,
+                 audit_log_path: str = "logs/trust_scoring.log"):
         """
         Initialize the trust scoring engine.
         
         Args:
             data_path: Path to store trust profiles and historical data
+            explanation_redaction: Redaction level for the serialized explanation
+            anomaly_detector: Source of active anomaly flags for the request's key and tenant
+            velocity_checker: Impossible-travel, IP velocity and IP reputation checks
+            audit_log_path: Merkle-logged audit trail of evaluations and decisions
         """
         if data_path is None:
             data_path = Path(__file__).parent / "trust_data"
@@ -106,7 +231,7 @@ class TrustScoringEngine:
         
         # Initialize audit logging
         try:
-            self.audit_logger = MerkleLogWriter("logs/trust_scoring.log")
+            self.audit_logger = MerkleLogWriter(str(audit_log_path))
         except Exception as e:
             self.logger.warning(f"Could not initialize audit logging: {e}")
             self.audit_logger = None
@@ -132,6 +257,10 @@ class TrustScoringEngine:
             "very_high_risk": 20.0
         }
         
+        self.explanation_redaction = explanation_redaction
+        self.anomaly_detector = anomaly_detector
+        self.velocity_checker = velocity_checker
+        
         # Load existing user profiles
         self.user_profiles: Dict[str, UserTrustProfile] = {}
         self._load_user_profiles()
@@ -150,29 +279,37 @@ class TrustScoringEngine:
             TrustEvaluation with complete trust assessment
         """
         start_time = time.time()
+        now = start_time
         
         try:
-            # Get or create user profile
+            # Get or create user profile, snapshotting it for the decision record
             user_profile = self._get_user_profile(user_id)
+            profile_snapshot = asdict(user_profile)
+            policy = self.current_policy()
             
-            # Calculate individual trust metrics
-            trust_metrics = self._calculate_trust_metrics(user_id, context_data, user_profile)
+            # Attach anomaly flags and velocity signals as inputs, so the decision record
+            # replays them
+            context_data = self._with_anomaly_flags(context_data, now)
+            context_data = self._with_velocity_signals(user_id, context_data, now)
             
-            # Calculate overall trust score
-            overall_score = self._calculate_overall_trust_score(trust_metrics)
-            
-            # Determine risk level
-            risk_level = self._determine_risk_level(overall_score, trust_metrics)
+            # Score against the current policy, recording which rules fired
+            trust_metrics, rule_hits, overall_score, risk_level = self._decide(
+                user_id, context_data, user_profile, now, policy)
             
             # Calculate confidence level
             confidence = self._calculate_confidence_level(user_profile, trust_metrics)
             
             # Get adaptive thresholds
-            adaptive_thresholds = self._get_adaptive_thresholds(user_profile)
+            adaptive_thresholds = self._get_adaptive_thresholds(user_profile, policy)
             
             # Generate recommendations
             recommendations = self._generate_recommendations(trust_metrics, risk_level, user_profile)
             
+            # Explain the outcome (before the profile absorbs this evaluation)
+            explanation = self._build_explanation(trust_metrics, rule_hits, overall_score,
+                                                  risk_level, adaptive_thresholds, user_profile,
+                                                  now, policy)
+            
             # Create evaluation result
             evaluation = TrustEvaluation(
                 user_id=user_id,
@@ -182,15 +319,20 @@ class TrustScoringEngine:
                 confidence_level=confidence,
                 adaptive_thresholds=adaptive_thresholds,
                 recommendations=recommendations,
-                evaluation_timestamp=int(time.time()),
-                session_id=session_id
+                evaluation_timestamp=int(now),
+                session_id=session_id,
+                explanation=explanation,
+                explanation_json=explanation.to_json(self.explanation_redaction)
             )
+            evaluation.decision_record = self._decision_record(
+                evaluation, context_data, profile_snapshot, now, policy)
             
             # Update user profile
             self._update_user_profile(user_profile, evaluation, context_data)
             
-            # Log the evaluation
+            # Log the evaluation and its replayable decision record
             self._log_trust_evaluation(evaluation, time.time() - start_time)
+            self._log_decision_record(evaluation.decision_record)
             
             return evaluation
             
@@ -209,15 +351,168 @@ class TrustScoringEngine:
                 session_id=session_id
             )
     
+    def current_policy(self) -> TrustPolicy:
+        """Current scoring policy; archived on first use so old decisions can be replayed."""
+        policy = TrustPolicy(
+            weights={factor.value: weight for factor, weight in self.trust_weights.items()},
+            thresholds=dict(self.default_thresholds),
+            risk_bands=[(threshold, level.name) for threshold, level in RISK_BANDS]
+        )
+        self._archive_policy(policy)
+        return policy
+    
+    def _archive_policy(self, policy: TrustPolicy):
+        """Store a policy under its version unless it is already archived."""
+        policy_dir = self.data_path / "policies"
+        policy_file = policy_dir / f"{policy.version}.json"
+        if policy_file.exists():
+            return
+        try:
+            policy_dir.mkdir(exist_ok=True)
+            with open(policy_file, 'w') as f:
+                json.dump(asdict(policy), f, indent=2)
+        except Exception as e:
+            self.logger.error(f"Failed to archive trust policy {policy.version}: {e}")
+    
+    def load_policy(self, version: str) -> TrustPolicy:
+        """
+        Load an archived policy version.
+        
+        Raises:
+            KeyError: If the version is not archived or its contents don't match the version
+        """
+        policy_file = self.data_path / "policies" / f"{version}.json"
+        if not policy_file.exists():
+            raise KeyError(f"Trust policy {version} is not archived")
+        with open(policy_file, 'r') as f:
+            data = json.load(f)
+        policy = TrustPolicy(
+            weights=data["weights"],
+            thresholds=data["thresholds"],
+            risk_bands=[(threshold, level) for threshold, level in data["risk_bands"]]
+        )
+        if policy.version != version:
+            raise KeyError(f"Archived trust policy {version} has been modified")
+        return policy
+    
+    def _with_anomaly_flags(self, context_data: Dict[str, Any], now: float) -> Dict[str, Any]:
+        """Copy of the context with active flags for its key_id / tenant_id, if any."""
+        if not self.anomaly_detector:
+            return context_data
+        flags = self.anomaly_detector.active_flags(key_id=context_data.get("key_id"),
+                                                   tenant=context_data.get("tenant_id"),
+                                                   now=now)
+        if not flags:
+            return context_data
+        return {**context_data, "anomaly_flags": [flag.to_dict() for flag in flags]}
+    
+    def _with_velocity_signals(self, user_id: str, context_data: Dict[str, Any],
+                               now: float) -> Dict[str, Any]:
+        """Copy of the context with velocity signals for its key_id (or the user), if any."""
+        if not self.velocity_checker:
+            return context_data
+        subject = context_data.get("key_id") or f"user:{user_id}"
+        signals = self.velocity_checker.assess(subject,
+                                               ContextObservation.from_context(context_data, now))
+        if not signals:
+            return context_data
+        return {**context_data, "velocity_signals": signals}
+    
+    def _decide(self, user_id: str, context_data: Dict[str, Any], user_profile: UserTrustProfile,
+                now: float, policy: TrustPolicy
+                ) -> Tuple[TrustMetrics, List[Dict[str, Any]], float, RiskLevel]:
+        """Deterministic core of an evaluation: metrics, rule hits, score and risk level."""
+        rule_hits: List[Dict[str, Any]] = []
+        trust_metrics = self._calculate_trust_metrics(user_id, context_data, user_profile,
+                                                      rule_hits, now)
+        overall_score = self._calculate_overall_trust_score(trust_metrics, policy)
+        risk_level = self._determine_risk_level(overall_score, trust_metrics, policy)
+        return trust_metrics, rule_hits, overall_score, risk_level
+    
+    def _decision_record(self, evaluation: TrustEvaluation, context_data: Dict[str, Any],
+                         profile_snapshot: Dict[str, Any], now: float,
+                         policy: TrustPolicy) -> Dict[str, Any]:
+        """Everything needed to re-execute this decision later."""
+        inputs = {
+            "user_id": evaluation.user_id,
+            "context": context_data,
+            "profile": profile_snapshot,
+            "now": now
+        }
+        return {
+            "record_version": DECISION_RECORD_VERSION,
+            "engine_version": ENGINE_VERSION,
+            "policy_version": policy.version,
+            "session_id": evaluation.session_id,
+            "inputs": json.loads(_canonical_json(inputs)),
+            "inputs_digest": _inputs_digest(inputs),
+            "output": {
+                "overall_trust_score": evaluation.overall_trust_score,
+                "risk_level": evaluation.risk_level.name
+            }
+        }
+    
+    def replay_decision(self, record: Dict[str, Any]) -> Dict[str, Any]:
+        """
+        Re-execute a logged decision against the policy version it was made under.
+        
+        The user's profile is restored from the record, so replay never reads or changes
+        live profiles.
+        
+        Args:
+            record: Decision record from an evaluation or the audit log
+            
+        Returns:
+            Replay report; "reproduced" is False if the inputs were altered, the policy is
+            missing, or the replayed output differs from the recorded one
+        """
+        inputs = record["inputs"]
+        report = {
+            "reproduced": False,
+            "divergences": [],
+            "policy_version": record["policy_version"],
+            "recorded_engine_version": record["engine_version"],
+            "engine_version": ENGINE_VERSION,
+            "recorded": record["output"],
+            "replayed": None
+        }
+        
+        if _inputs_digest(inputs) != record["inputs_digest"]:
+            report["divergences"].append("inputs_digest")
+        
+        try:
+            policy = self.load_policy(record["policy_version"])
+        except KeyError as e:
+            self.logger.warning(f"Cannot replay decision: {e}")
+            report["divergences"].append("policy_unavailable")
+            return report
+        
+        profile = UserTrustProfile(**copy.deepcopy(inputs["profile"]))
+        _, _, overall_score, risk_level = self._decide(
+            inputs["user_id"], copy.deepcopy(inputs["context"]), profile, inputs["now"], policy)
+        report["replayed"] = {
+            "overall_trust_score": overall_score,
+            "risk_level": risk_level.name
+        }
+        for key, value in report["replayed"].items():
+            if record["output"].get(key) != value:
+                report["divergences"].append(key)
+        
+        report["reproduced"] = not report["divergences"]
+        return report
+    
     def _calculate_trust_metrics(self, user_id: str, context_data: Dict[str, Any], 
-                                user_profile: UserTrustProfile) -> TrustMetrics:
+                                user_profile: UserTrustProfile,
+                                rule_hits: Optional[List[Dict[str, Any]]] = None,
+                                now: Optional[float] = None) -> TrustMetrics:
         """Calculate individual trust metrics."""
         
         # Device consistency score
         device_score = self._evaluate_device_consistency(
             context_data.get("device_verified", False),
             context_data.get("device_fingerprint", ""),
-            user_profile
+            user_profile,
+            rule_hits
         )
         
         # Temporal patterns score
@@ -225,7 +520,8 @@ class TrustScoringEngine:
             context_data.get("timestamp_verified", False),
             context_data.get("current_timestamp", 0),
             context_data.get("last_access_time", 0),
-            user_profile
+            user_profile,
+            rule_hits
         )
         
         # Geographic consistency score
@@ -233,7 +529,8 @@ class TrustScoringEngine:
             context_data.get("location_verified", False),
             context_data.get("latitude", 0),
             context_data.get("longitude", 0),
-            user_profile
+            user_profile,
+            rule_hits
         )
         
         # Behavioral patterns score
@@ -241,23 +538,25 @@ class TrustScoringEngine:
             context_data.get("pattern_verified", False),
             context_data.get("session_duration", 0),
             context_data.get("keystrokes_per_minute", 0),
-            user_profile
+            user_profile,
+            rule_hits
         )
         
         # Access frequency score
         frequency_score = self._evaluate_access_frequency(
             context_data.get("access_frequency", 0),
-            user_profile
+            user_profile,
+            rule_hits
         )
         
         # Risk indicators score
-        risk_score = self._evaluate_risk_indicators(context_data, user_profile)
+        risk_score = self._evaluate_risk_indicators(context_data, user_profile, rule_hits, now)
         
         # Compliance score
-        compliance_score = self._evaluate_compliance_score(context_data, user_profile)
+        compliance_score = self._evaluate_compliance_score(context_data, user_profile, rule_hits)
         
         # Historical reliability score
-        historical_score = self._evaluate_historical_reliability(user_profile)
+        historical_score = self._evaluate_historical_reliability(user_profile, rule_hits)
         
         return TrustMetrics(
             device_consistency=device_score,
@@ -270,10 +569,25 @@ class TrustScoringEngine:
             historical_reliability=historical_score
         )
     
+    def _record_rule(self, rule_hits: Optional[List[Dict[str, Any]]], factor: TrustFactor,
+                     rule: str, effect: float, **detail):
+        """Record a rule that shaped a factor score (effect is in factor points)."""
+        if rule_hits is not None:
+            rule_hits.append({
+                "factor": factor.value,
+                "rule": rule,
+                "effect": round(effect, 4),
+                "detail": detail
+            })
+    
     def _evaluate_device_consistency(self, device_verified: bool, device_fingerprint: str, 
-                                   user_profile: UserTrustProfile) -> float:
+                                   user_profile: UserTrustProfile,
+                                   rule_hits: Optional[List[Dict[str, Any]]] = None) -> float:
         """Evaluate device consistency and recognition."""
+        factor = TrustFactor.DEVICE_CONSISTENCY
         base_score = 80.0 if device_verified else 0.0
+        self._record_rule(rule_hits, factor,
+                          "device_verified" if device_verified else "device_not_verified", base_score)
         
         # Check if this is a known device
         if device_fingerprint:
@@ -282,15 +596,22 @@ class TrustScoringEngine:
             
             if device_hash in known_devices:
                 base_score += 20.0  # Bonus for known device
+                self._record_rule(rule_hits, factor, "known_device", 20.0)
             elif device_verified:
                 base_score += 10.0  # Smaller bonus for new but verified device
+                self._record_rule(rule_hits, factor, "new_verified_device", 10.0)
         
         return min(base_score, 100.0)
     
     def _evaluate_temporal_patterns(self, timestamp_verified: bool, current_timestamp: int,
-                                  last_access_time: int, user_profile: UserTrustProfile) -> float:
+                                  last_access_time: int, user_profile: UserTrustProfile,
+                                  rule_hits: Optional[List[Dict[str, Any]]] = None) -> float:
         """Evaluate temporal access patterns."""
+        factor = TrustFactor.TEMPORAL_PATTERNS
         base_score = 70.0 if timestamp_verified else 0.0
+        self._record_rule(rule_hits, factor,
+                          "timestamp_verified" if timestamp_verified else "timestamp_not_verified",
+                          base_score)
         
         if current_timestamp and last_access_time:
             time_diff = current_timestamp - last_access_time
@@ -305,13 +626,21 @@ class TrustScoringEngine:
                     deviation = abs(time_diff - avg_interval) / avg_interval
                     pattern_score = max(0, 30.0 * (1 - deviation))
                     base_score += pattern_score
+                    self._record_rule(rule_hits, factor, "access_interval_match", pattern_score,
+                                      interval=time_diff, typical_interval=avg_interval,
+                                      deviation=deviation)
         
         return min(base_score, 100.0)
     
     def _evaluate_geographic_consistency(self, location_verified: bool, latitude: float,
-                                       longitude: float, user_profile: UserTrustProfile) -> float:
+                                       longitude: float, user_profile: UserTrustProfile,
+                                       rule_hits: Optional[List[Dict[str, Any]]] = None) -> float:
         """Evaluate geographic access patterns."""
+        factor = TrustFactor.GEOGRAPHIC_CONSISTENCY
         base_score = 70.0 if location_verified else 0.0
+        self._record_rule(rule_hits, factor,
+                          "location_verified" if location_verified else "location_not_verified",
+                          base_score)
         
         if latitude and longitude:
             # Check against known locations
@@ -325,19 +654,28 @@ class TrustScoringEngine:
                     min_distance = min(min_distance, distance)
                 
                 # Score based on proximity to known locations
+                bonus = 0.0
                 if min_distance < 10:  # Within 10 km
-                    base_score += 30.0
+                    bonus = 30.0
                 elif min_distance < 50:  # Within 50 km
-                    base_score += 20.0
+                    bonus = 20.0
                 elif min_distance < 200:  # Within 200 km
-                    base_score += 10.0
+                    bonus = 10.0
+                base_score += bonus
+                self._record_rule(rule_hits, factor, "known_location_proximity", bonus,
+                                  distance_km=min_distance)
         
         return min(base_score, 100.0)
     
     def _evaluate_behavioral_patterns(self, pattern_verified: bool, session_duration: int,
-                                    keystrokes_per_minute: int, user_profile: UserTrustProfile) -> float:
+                                    keystrokes_per_minute: int, user_profile: UserTrustProfile,
+                                    rule_hits: Optional[List[Dict[str, Any]]] = None) -> float:
         """Evaluate behavioral consistency."""
+        factor = TrustFactor.BEHAVIORAL_PATTERNS
         base_score = 70.0 if pattern_verified else 0.0
+        self._record_rule(rule_hits, factor,
+                          "pattern_verified" if pattern_verified else "pattern_not_verified",
+                          base_score)
         
         # Evaluate session duration patterns
         if session_duration:
@@ -348,6 +686,10 @@ class TrustScoringEngine:
                     duration_deviation = abs(session_duration - avg_duration) / avg_duration
                     duration_score = max(0, 15.0 * (1 - duration_deviation))
                     base_score += duration_score
+                    self._record_rule(rule_hits, factor, "session_duration_match", duration_score,
+                                      session_duration=session_duration,
+                                      typical_duration=avg_duration,
+                                      deviation=duration_deviation)
         
         # Evaluate typing patterns
         if keystrokes_per_minute:
@@ -358,11 +700,17 @@ class TrustScoringEngine:
                     typing_deviation = abs(keystrokes_per_minute - avg_typing) / avg_typing
                     typing_score = max(0, 15.0 * (1 - typing_deviation))
                     base_score += typing_score
+                    self._record_rule(rule_hits, factor, "typing_speed_match", typing_score,
+                                      keystrokes_per_minute=keystrokes_per_minute,
+                                      typical_speed=avg_typing,
+                                      deviation=typing_deviation)
         
         return min(base_score, 100.0)
     
-    def _evaluate_access_frequency(self, access_frequency: int, user_profile: UserTrustProfile) -> float:
+    def _evaluate_access_frequency(self, access_frequency: int, user_profile: UserTrustProfile,
+                                 rule_hits: Optional[List[Dict[str, Any]]] = None) -> float:
         """Evaluate access frequency patterns."""
+        factor = TrustFactor.ACCESS_FREQUENCY
         # Get typical access frequency from user profile
         typical_frequency = user_profile.behavioral_baselines.get("typical_access_frequency", 5)
         
@@ -370,63 +718,103 @@ class TrustScoringEngine:
         if typical_frequency > 0:
             frequency_ratio = access_frequency / typical_frequency
             if 0.5 <= frequency_ratio <= 2.0:  # Within normal range
-                return 90.0
+                score, rule = 90.0, "access_frequency_normal"
             elif 0.2 <= frequency_ratio <= 3.0:  # Slightly unusual
-                return 70.0
+                score, rule = 70.0, "access_frequency_unusual"
             else:  # Very unusual
-                return 40.0
+                score, rule = 40.0, "access_frequency_unusual"
+            self._record_rule(rule_hits, factor, rule, score, access_frequency=access_frequency,
+                              typical_frequency=typical_frequency, ratio=frequency_ratio)
+            return score
         
+        self._record_rule(rule_hits, factor, "no_frequency_baseline", 60.0)
         return 60.0  # Default for new users
     
     def _evaluate_risk_indicators(self, context_data: Dict[str, Any], 
-                                user_profile: UserTrustProfile) -> float:
+                                user_profile: UserTrustProfile,
+                                rule_hits: Optional[List[Dict[str, Any]]] = None,
+                                now: Optional[float] = None) -> float:
         """Evaluate risk indicators and anomalies."""
+        factor = TrustFactor.RISK_INDICATORS
         risk_score = 100.0  # Start with perfect score, subtract for risks
+        now = time.time() if now is None else now
         
         # Check for recent compliance violations
         recent_violations = sum(1 for event in user_profile.risk_events 
-                              if event.get("timestamp", 0) > time.time() - 86400 * 7)  # Last 7 days
+                              if event.get("timestamp", 0) > now - RISK_EVENT_WINDOW)
         risk_score -= recent_violations * 10.0
+        if recent_violations:
+            self._record_rule(rule_hits, factor, "recent_risk_events", -recent_violations * 10.0,
+                              count=recent_violations)
         
         # Check for unusual verification patterns
-        verification_results = [
-            context_data.get("device_verified", False),
-            context_data.get("timestamp_verified", False),
-            context_data.get("location_verified", False),
-            context_data.get("pattern_verified", False)
-        ]
+        verification_results = {
+            "device": context_data.get("device_verified", False),
+            "timestamp": context_data.get("timestamp_verified", False),
+            "location": context_data.get("location_verified", False),
+            "pattern": context_data.get("pattern_verified", False)
+        }
         
-        failed_verifications = sum(1 for v in verification_results if not v)
-        risk_score -= failed_verifications * 15.0
+        failed = [name for name, verified in verification_results.items() if not verified]
+        risk_score -= len(failed) * 15.0
+        if failed:
+            self._record_rule(rule_hits, factor, "failed_verifications", -len(failed) * 15.0,
+                              failed=failed)
         
         # Check for rapid access attempts
         if context_data.get("last_access_time", 0):
             time_since_last = context_data.get("current_timestamp", 0) - context_data.get("last_access_time", 0)
             if time_since_last < 60:  # Less than 1 minute
                 risk_score -= 20.0
+                self._record_rule(rule_hits, factor, "rapid_access", -20.0,
+                                  seconds_since_last=time_since_last)
+        
+        # Anomalies flagged on the key or tenant in use
+        anomaly_kinds = sorted({flag["kind"] for flag in context_data.get("anomaly_flags", [])})
+        if anomaly_kinds:
+            risk_score -= len(anomaly_kinds) * ANOMALY_PENALTY
+            self._record_rule(rule_hits, factor, "anomaly_flags",
+                              -len(anomaly_kinds) * ANOMALY_PENALTY, kinds=anomaly_kinds)
+        
+        # Velocity and reputation signals carry their own configured penalty
+        for signal in context_data.get("velocity_signals", []):
+            risk_score -= signal["penalty"]
+            detail = {k: v for k, v in signal.items() if k not in ("signal", "penalty")}
+            self._record_rule(rule_hits, factor, signal["signal"], -signal["penalty"], **detail)
         
         return max(risk_score, 0.0)
     
     def _evaluate_compliance_score(self, context_data: Dict[str, Any], 
-                                 user_profile: UserTrustProfile) -> float:
+                                 user_profile: UserTrustProfile,
+                                 rule_hits: Optional[List[Dict[str, Any]]] = None) -> float:
         """Evaluate compliance with security policies."""
+        factor = TrustFactor.COMPLIANCE_SCORE
         compliance_score = 100.0
         
         # Subtract for each compliance violation
         compliance_score -= user_profile.compliance_violations * 5.0
+        if user_profile.compliance_violations:
+            self._record_rule(rule_hits, factor, "compliance_violations",
+                              -user_profile.compliance_violations * 5.0,
+                              count=user_profile.compliance_violations)
         
         # Check current session compliance
         if not context_data.get("business_hours_ok", True):
             compliance_score -= 10.0
+            self._record_rule(rule_hits, factor, "outside_business_hours", -10.0)
         
         if not context_data.get("ip_consistency_ok", True):
             compliance_score -= 15.0
+            self._record_rule(rule_hits, factor, "ip_inconsistent", -15.0)
         
         return max(compliance_score, 0.0)
     
-    def _evaluate_historical_reliability(self, user_profile: UserTrustProfile) -> float:
+    def _evaluate_historical_reliability(self, user_profile: UserTrustProfile,
+                                       rule_hits: Optional[List[Dict[str, Any]]] = None) -> float:
         """Evaluate historical reliability and consistency."""
+        factor = TrustFactor.HISTORICAL_RELIABILITY
         if not user_profile.trust_history:
+            self._record_rule(rule_hits, factor, "no_history", 50.0)
             return 50.0  # Default for new users
         
         # Calculate average historical trust score
@@ -440,30 +828,29 @@ class TrustScoringEngine:
             consistency_score = 70.0
         
         # Combine average performance and consistency
-        return (avg_historical_trust * 0.7 + consistency_score * 0.3)
+        reliability = avg_historical_trust * 0.7 + consistency_score * 0.3
+        self._record_rule(rule_hits, factor, "history_reliability", reliability,
+                          history_mean=avg_historical_trust, consistency=consistency_score)
+        return reliability
     
-    def _calculate_overall_trust_score(self, trust_metrics: TrustMetrics) -> float:
+    def _calculate_overall_trust_score(self, trust_metrics: TrustMetrics,
+                                       policy: TrustPolicy) -> float:
         """Calculate weighted overall trust score."""
         total_score = 0.0
         
-        for factor, weight in self.trust_weights.items():
-            metric_value = getattr(trust_metrics, factor.value, 0.0)
+        for factor, weight in policy.weights.items():
+            metric_value = getattr(trust_metrics, factor, 0.0)
             total_score += metric_value * weight
         
         return min(total_score, 100.0)
     
-    def _determine_risk_level(self, overall_score: float, trust_metrics: TrustMetrics) -> RiskLevel:
+    def _determine_risk_level(self, overall_score: float, trust_metrics: TrustMetrics,
+                              policy: TrustPolicy) -> RiskLevel:
         """Determine risk level based on trust score and metrics."""
-        if overall_score >= 90:
-            return RiskLevel.VERY_LOW
-        elif overall_score >= 75:
-            return RiskLevel.LOW
-        elif overall_score >= 60:
-            return RiskLevel.MEDIUM
-        elif overall_score >= 40:
-            return RiskLevel.HIGH
-        else:
-            return RiskLevel.VERY_HIGH
+        for threshold, level in policy.risk_bands:
+            if overall_score >= threshold:
+                return RiskLevel[level]
+        return RiskLevel.VERY_HIGH
     
     def _calculate_confidence_level(self, user_profile: UserTrustProfile, 
                                   trust_metrics: TrustMetrics) -> float:
@@ -479,22 +866,72 @@ class TrustScoringEngine:
         
         return min(base_confidence, 100.0)
     
-    def _get_adaptive_thresholds(self, user_profile: UserTrustProfile) -> Dict[str, float]:
+    def _get_adaptive_thresholds(self, user_profile: UserTrustProfile,
+                                 policy: TrustPolicy) -> Dict[str, float]:
         """Get adaptive thresholds based on user's historical behavior."""
         if not user_profile.trust_history:
-            return self.default_thresholds.copy()
-        
-        avg_trust = statistics.mean(user_profile.trust_history)
+            return dict(policy.thresholds)
         
         # Adjust thresholds based on user's typical trust level
+        adjustment = self._threshold_adjustment(user_profile)
         adaptive_thresholds = {}
-        for level, threshold in self.default_thresholds.items():
-            # Adjust threshold based on user's baseline
-            adjustment = (avg_trust - 75.0) * 0.1  # Adjust by 10% of difference from 75
+        for level, threshold in policy.thresholds.items():
             adaptive_thresholds[level] = max(0, threshold + adjustment)
         
         return adaptive_thresholds
     
+    def _threshold_adjustment(self, user_profile: UserTrustProfile) -> float:
+        """Threshold shift from the user's baseline: 10% of its difference from 75."""
+        if not user_profile.trust_history:
+            return 0.0
+        return (statistics.mean(user_profile.trust_history) - 75.0) * 0.1
+    
+    def _build_explanation(self, trust_metrics: TrustMetrics, rule_hits: List[Dict[str, Any]],
+                           overall_score: float, risk_level: RiskLevel,
+                           adaptive_thresholds: Dict[str, float],
+                           user_profile: UserTrustProfile, now: float,
+                           policy: TrustPolicy) -> TrustExplanation:
+        """Assemble the decision trace behind a trust score."""
+        signals = []
+        for factor, weight in policy.weights.items():
+            value = getattr(trust_metrics, factor, 0.0)
+            signals.append({
+                "factor": factor,
+                "value": round(value, 4),
+                "weight": weight,
+                "contribution": round(value * weight, 4),
+                "lost": round((100.0 - value) * weight, 4)
+            })
+        
+        # Bands are compared top-down until one is met
+        thresholds = []
+        for threshold, level in policy.risk_bands:
+            met = overall_score >= threshold
+            thresholds.append({"level": level, "threshold": threshold, "met": met})
+            if met:
+                break
+        
+        # Time-based decay: only recent risk events count, and history is a bounded window
+        recent_events = sum(1 for event in user_profile.risk_events
+                            if event.get("timestamp", 0) > now - RISK_EVENT_WINDOW)
+        decay = {
+            "risk_event_window_seconds": RISK_EVENT_WINDOW,
+            "risk_events_counted": recent_events,
+            "risk_events_expired": len(user_profile.risk_events) - recent_events,
+            "history_samples": len(user_profile.trust_history),
+            "threshold_adjustment": round(self._threshold_adjustment(user_profile), 4)
+        }
+        
+        return TrustExplanation(
+            signals=signals,
+            rule_hits=rule_hits,
+            decay=decay,
+            thresholds=thresholds,
+            adaptive_thresholds=adaptive_thresholds,
+            overall_trust_score=round(overall_score, 4),
+            risk_level=risk_level
+        )
+    
     def _generate_recommendations(self, trust_metrics: TrustMetrics, risk_level: RiskLevel,
                                 user_profile: UserTrustProfile) -> List[str]:
         """Generate actionable recommendations based on trust evaluation."""
@@ -668,6 +1105,14 @@ class TrustScoringEngine:
             except Exception as e:
                 self.logger.warning(f"Could not log trust evaluation: {e}")
 
+    def _log_decision_record(self, record: Optional[Dict[str, Any]]):
+        """Append a replayable decision record to the audit log."""
+        if self.audit_logger and record:
+            try:
+                self.audit_logger.add_entry({"event": "trust_decision", **record})
+            except Exception as e:
+                self.logger.warning(f"Could not log decision record: {e}")
+
 # Utility functions for integration
 def create_trust_engine() -> TrustScoringEngine:
     """Create a new trust scoring engine instance."""
@@ -0,0 +1,197 @@
+# zk/velocity.py
+
+import ipaddress
+import math
+import threading
+from collections import defaultdict, deque
+from dataclasses import dataclass
+from typing import Any, Deque, Dict, List, Optional, Tuple
+
+EARTH_RADIUS_KM = 6371.0
+
+def haversine_km(lat1: float, lon1: float, lat2: float, lon2: float) -> float:
+    """Great-circle distance between two points in kilometers."""
+    phi1, phi2 = math.radians(lat1), math.radians(lat2)
+    dphi = phi2 - phi1
+    dlambda = math.radians(lon2 - lon1)
+    a = math.sin(dphi / 2) ** 2 + math.cos(phi1) * math.cos(phi2) * math.sin(dlambda / 2) ** 2
+    return 2 * EARTH_RADIUS_KM * math.asin(math.sqrt(min(a, 1.0)))
+
+@dataclass
+class VelocityThresholds:
+    """Limits for velocity checks and the risk points each violation costs"""
+    max_speed_kmh: float = 900.0          # Faster than a commercial flight is impossible travel
+    min_travel_km: float = 100.0          # Ignore geolocation jitter below this distance
+    window_seconds: float = 3600.0        # Look-back for IP and country velocity
+    max_distinct_ips: int = 3             # More addresses than this in the window is suspicious
+    max_distinct_countries: int = 1       # More countries than this in the window is suspicious
+    bad_reputation_score: float = 0.5     # Reputation at or below this counts against the request
+    impossible_travel_penalty: float = 40.0
+    ip_velocity_penalty: float = 15.0
+    country_velocity_penalty: float = 20.0
+    ip_reputation_penalty: float = 30.0
+
+@dataclass
+class ContextObservation:
+    """Where and from which address a subject was seen"""
+    timestamp: float
+    ip_address: Optional[str] = None
+    latitude: Optional[float] = None
+    longitude: Optional[float] = None
+    country: Optional[str] = None
+
+    @classmethod
+    def from_context(cls, context_data: Dict[str, Any], now: float) -> "ContextObservation":
+        latitude = context_data.get("latitude")
+        longitude = context_data.get("longitude")
+        has_location = latitude is not None and longitude is not None
+        return cls(
+            timestamp=float(context_data.get("current_timestamp") or now),
+            ip_address=context_data.get("ip_address"),
+            latitude=float(latitude) if has_location else None,
+            longitude=float(longitude) if has_location else None,
+            country=context_data.get("country")
+        )
+
+class IPReputation:
+    """
+    Reputation scores for address ranges, from 0.0 (known bad) to 1.0 (known good).
+
+    The most specific matching network wins; unknown addresses have no score.
+    """
+
+    def __init__(self):
+        self._networks: List[Tuple[Any, float, str]] = []
+        self._lock = threading.Lock()
+
+    def set_network(self, cidr: str, score: float, reason: str = ""):
+        """Assign a score to an address range (e.g. a Tor exit list or an abuse feed)."""
+        if not 0.0 <= score <= 1.0:
+            raise ValueError("Reputation score must be between 0.0 and 1.0")
+        network = ipaddress.ip_network(cidr, strict=False)
+        with self._lock:
+            self._networks = [entry for entry in self._networks if entry[0] != network]
+            self._networks.append((network, score, reason))
+            self._networks.sort(key=lambda entry: entry[0].prefixlen, reverse=True)
+
+    def lookup(self, ip_address: str) -> Optional[Tuple[float, str]]:
+        """(score, reason) for an address, or None if no range matches or it doesn't parse."""
+        try:
+            address = ipaddress.ip_address(ip_address)
+        except ValueError:
+            return None
+        with self._lock:
+            for network, score, reason in self._networks:
+                if address.version == network.version and address in network:
+                    return score, reason
+        return None
+
+class ContextHistoryStore:
+    """Recent observations per subject (a key id or user id), bounded in age and count."""
+
+    def __init__(self, retention_seconds: float = 86400.0 * 7, max_per_subject: int = 500):
+        self.retention_seconds = retention_seconds
+        self.max_per_subject = max_per_subject
+        self._history: Dict[str, Deque[ContextObservation]] = defaultdict(
+            lambda: deque(maxlen=self.max_per_subject))
+        self._lock = threading.Lock()
+
+    def record(self, subject: str, observation: ContextObservation):
+        with self._lock:
+            history = self._history[subject]
+            history.append(observation)
+            while history and history[0].timestamp < observation.timestamp - self.retention_seconds:
+                history.popleft()
+
+    def recent(self, subject: str, since: float) -> List[ContextObservation]:
+        """Observations at or after `since`, oldest first."""
+        with self._lock:
+            return [obs for obs in self._history.get(subject, ()) if obs.timestamp >= since]
+
+    def last_located(self, subject: str, before: float) -> Optional[ContextObservation]:
+        """Most recent observation with coordinates strictly before `before`."""
+        with self._lock:
+            for obs in reversed(self._history.get(subject, ())):
+                if obs.timestamp < before and obs.latitude is not None:
+                    return obs
+        return None
+
+class VelocityChecker:
+    """
+    Impossible-travel, IP/country velocity and IP reputation checks against context history.
+
+    `assess` returns signals as plain dicts carrying the penalty they cost, so a trust
+    decision that consumed them can be replayed without the history store.
+    """
+
+    def __init__(self, thresholds: Optional[VelocityThresholds] = None,
+                 history: Optional[ContextHistoryStore] = None,
+                 reputation: Optional[IPReputation] = None):
+        self.thresholds = thresholds or VelocityThresholds()
+        self.history = history or ContextHistoryStore()
+        self.reputation = reputation or IPReputation()
+
+    def assess(self, subject: str, observation: ContextObservation,
+               record: bool = True) -> List[Dict[str, Any]]:
+        """
+        Check an observation against the subject's history, then (by default) record it.
+
+        Args:
+            subject: Key id or user id the observation belongs to
+            observation: Where and from which address the subject is now
+            record: Whether to add the observation to the history
+
+        Returns:
+            Velocity signals, each with "signal", "penalty" and details
+        """
+        t = self.thresholds
+        signals = []
+
+        previous = self.history.last_located(subject, observation.timestamp)
+        if previous is not None and observation.latitude is not None:
+            distance = haversine_km(previous.latitude, previous.longitude,
+                                    observation.latitude, observation.longitude)
+            hours = max(observation.timestamp - previous.timestamp, 1.0) / 3600.0
+            speed = distance / hours
+            if distance >= t.min_travel_km and speed > t.max_speed_kmh:
+                signals.append({
+                    "signal": "impossible_travel",
+                    "penalty": t.impossible_travel_penalty,
+                    "distance_km": round(distance, 1),
+                    "hours": round(hours, 3),
+                    "speed_kmh": round(speed, 1),
+                    "from_country": previous.country,
+                    "to_country": observation.country
+                })
+
+        window = self.history.recent(subject, observation.timestamp - t.window_seconds)
+        ips = {obs.ip_address for obs in window + [observation] if obs.ip_address}
+        if len(ips) > t.max_distinct_ips:
+            signals.append({
+                "signal": "ip_velocity",
+                "penalty": t.ip_velocity_penalty,
+                "distinct_ips": len(ips),
+                "window_seconds": t.window_seconds
+            })
+        countries = {obs.country for obs in window + [observation] if obs.country}
+        if len(countries) > t.max_distinct_countries:
+            signals.append({
+                "signal": "country_velocity",
+                "penalty": t.country_velocity_penalty,
+                "countries": sorted(countries),
+                "window_seconds": t.window_seconds
+            })
+
+        if observation.ip_address:
+            reputation = self.reputation.lookup(observation.ip_address)
+            if reputation is not None and reputation[0] <= t.bad_reputation_score:
+                signals.append({
+                    "signal": "ip_reputation",
+                    "penalty": t.ip_reputation_penalty,
+                    "score": reputation[0],
+                    "reason": reputation[1]
+                })
+
+        if record:
+            self.history.record(subject, observation)
+        return signals